@@ -0,0 +1,356 @@
+#!/usr/bin/env python3
+"""生成随应用打包的精简 General MIDI 音色库（src-tauri/resources/soundfont/MiniGM.sf2）
+
+音色全部由程序合成（单周期波形 + 包络，打击乐为合成的噪声/扫频），不含任何第三方采样，
+128 个 GM 音色按乐器族共用几种波形，另有一套标准鼓组（bank 128）。音质只求能听，
+需要更好的音色时用户可以在设置中选择其他 SoundFont。
+
+用法：python3 scripts/generate-soundfont.py [输出路径]
+"""
+
+import math
+import random
+import struct
+import sys
+from pathlib import Path
+
+# 单周期波形的采样率：256 点一个周期正好是 A3（220Hz）
+WAVE_RATE = 56320
+WAVE_ROOT_KEY = 57
+CYCLE = 256
+CYCLES = 8
+DRUM_RATE = 22050
+AMPLITUDE = 0.8 * 32767
+
+# 生成器编号（SoundFont 2.01 第 8.1.2 节）
+GEN_ATTACK_VOL_ENV = 34
+GEN_DECAY_VOL_ENV = 36
+GEN_SUSTAIN_VOL_ENV = 37
+GEN_RELEASE_VOL_ENV = 38
+GEN_INSTRUMENT = 41
+GEN_KEY_RANGE = 43
+GEN_INITIAL_ATTENUATION = 48
+GEN_SAMPLE_ID = 53
+GEN_SAMPLE_MODES = 54
+GEN_SCALE_TUNING = 56
+GEN_EXCLUSIVE_CLASS = 57
+GEN_OVERRIDING_ROOT_KEY = 58
+
+
+def timecents(seconds):
+    return max(-12000, round(1200 * math.log2(max(seconds, 0.001))))
+
+
+def additive(harmonics):
+    """按 (谐波次数, 幅度) 合成一个周期，归一化到满幅的 80%"""
+    cycle = [
+        sum(amp * math.sin(2 * math.pi * n * i / CYCLE) for n, amp in harmonics)
+        for i in range(CYCLE)
+    ]
+    peak = max(abs(v) for v in cycle) or 1.0
+    return [v / peak for v in cycle]
+
+
+def limited(harmonics, top):
+    return [(n, amp) for n, amp in harmonics if n <= top]
+
+
+def waveforms():
+    """每种波形给出低音区（谐波较多）和高音区（谐波较少，避免混叠）两个版本"""
+    saw = [(n, 1.0 / n) for n in range(1, 33)]
+    square = [(n, 1.0 / n) for n in range(1, 33, 2)]
+    triangle = [(n, (-1) ** ((n - 1) // 2) / (n * n)) for n in range(1, 33, 2)]
+    piano = [(n, 1.0 / (n ** 1.6)) for n in range(1, 25)]
+    organ = [(1, 1.0), (2, 0.8), (3, 0.6), (4, 0.5), (6, 0.35), (8, 0.3), (10, 0.15), (12, 0.1), (16, 0.08)]
+    bass = [(1, 1.0), (2, 0.45), (3, 0.2), (4, 0.08)]
+    bell = [(1, 1.0), (2, 0.3), (3, 0.45), (5, 0.25), (7, 0.12)]
+    waves = {
+        "piano": piano,
+        "saw": saw,
+        "square": square,
+        "triangle": triangle,
+        "organ": organ,
+        "bass": bass,
+        "bell": bell,
+        "sine": [(1, 1.0)],
+    }
+    return {name: (additive(h), additive(limited(h, 6))) for name, h in waves.items()}
+
+
+def noise(length, seed):
+    rng = random.Random(seed)
+    return [rng.uniform(-1.0, 1.0) for _ in range(length)]
+
+
+def drums():
+    """合成的打击乐采样（单次播放，不循环）"""
+    def decay(length, seconds):
+        return [math.exp(-i / (DRUM_RATE * seconds)) for i in range(length)]
+
+    def sweep(length, start_hz, end_hz, seconds):
+        phase = 0.0
+        out = []
+        for i, env in enumerate(decay(length, seconds)):
+            t = i / length
+            phase += 2 * math.pi * (end_hz + (start_hz - end_hz) * math.exp(-t * 12)) / DRUM_RATE
+            out.append(math.sin(phase) * env)
+        return out
+
+    def highpass(samples, amount):
+        out, prev_in, prev_out = [], 0.0, 0.0
+        for x in samples:
+            prev_out = amount * (prev_out + x - prev_in)
+            prev_in = x
+            out.append(prev_out)
+        return out
+
+    def mix(*parts):
+        return [sum(values) for values in zip(*parts)]
+
+    kick_len = int(DRUM_RATE * 0.5)
+    snare_len = int(DRUM_RATE * 0.35)
+    tom_len = int(DRUM_RATE * 0.5)
+    hat_len = int(DRUM_RATE * 0.12)
+    open_hat_len = int(DRUM_RATE * 0.6)
+    cymbal_len = int(DRUM_RATE * 1.5)
+    clap_len = int(DRUM_RATE * 0.3)
+
+    snare_noise = [n * e for n, e in zip(highpass(noise(snare_len, 1), 0.7), decay(snare_len, 0.08))]
+    snare_tone = [0.6 * v for v in sweep(snare_len, 330, 180, 0.05)]
+    clap_env = [
+        max(math.exp(-((i - offset) / (DRUM_RATE * 0.01))) if i >= offset else 0.0 for offset in (0, 220, 440))
+        * 0.7 + 0.3 * math.exp(-i / (DRUM_RATE * 0.08))
+        for i in range(clap_len)
+    ]
+
+    def metal(length, seconds, seed):
+        return [n * e for n, e in zip(highpass(noise(length, seed), 0.95), decay(length, seconds))]
+
+    return {
+        "kick": sweep(kick_len, 160, 45, 0.15),
+        "snare": mix(snare_noise, snare_tone),
+        "tom": sweep(tom_len, 220, 110, 0.18),
+        "hihat": metal(hat_len, 0.03, 2),
+        "openhat": metal(open_hat_len, 0.2, 3),
+        "cymbal": metal(cymbal_len, 0.5, 4),
+        "clap": [n * e for n, e in zip(highpass(noise(clap_len, 5), 0.8), clap_env)],
+        "click": [n * e for n, e in zip(highpass(noise(hat_len, 6), 0.5), decay(hat_len, 0.01))],
+    }
+
+
+def normalize(samples):
+    peak = max(abs(v) for v in samples) or 1.0
+    return [int(round(v / peak * AMPLITUDE)) for v in samples]
+
+
+# 乐器族，与 GM 的 16 个音色分组（每组 8 个音色）依次对应：(名称, 波形, 起音, 衰减, 持续电平衰减(cB), 释音, 衰减量(cB))
+FAMILIES = [
+    ("Piano", "piano", 0.002, 2.5, 960, 0.4, 0),
+    ("Chromatic", "bell", 0.002, 1.2, 960, 0.5, 20),
+    ("Organ", "organ", 0.01, 0.1, 0, 0.08, 40),
+    ("Guitar", "saw", 0.003, 1.5, 960, 0.3, 30),
+    ("Bass", "bass", 0.004, 1.2, 300, 0.12, 0),
+    ("Strings", "saw", 0.12, 0.3, 60, 0.5, 50),
+    ("Ensemble", "saw", 0.2, 0.3, 60, 0.8, 60),
+    ("Brass", "saw", 0.05, 0.4, 80, 0.2, 40),
+    ("Reed", "square", 0.04, 0.3, 60, 0.15, 60),
+    ("Pipe", "triangle", 0.05, 0.3, 40, 0.2, 30),
+    ("Synth Lead", "square", 0.005, 0.3, 40, 0.15, 60),
+    ("Synth Pad", "saw", 0.5, 0.5, 60, 1.2, 70),
+    ("Synth FX", "triangle", 0.3, 1.0, 120, 1.5, 40),
+    ("Ethnic", "piano", 0.002, 1.0, 960, 0.3, 20),
+    ("Percussive", "sine", 0.001, 0.4, 960, 0.2, 0),
+    ("Sound FX", "sine", 0.1, 1.0, 200, 0.8, 60),
+]
+
+GM_NAMES = [
+    "Acoustic Grand", "Bright Piano", "Electric Grand", "Honky-tonk", "E.Piano 1", "E.Piano 2", "Harpsichord", "Clavinet",
+    "Celesta", "Glockenspiel", "Music Box", "Vibraphone", "Marimba", "Xylophone", "Tubular Bells", "Dulcimer",
+    "Drawbar Organ", "Perc Organ", "Rock Organ", "Church Organ", "Reed Organ", "Accordion", "Harmonica", "Bandoneon",
+    "Nylon Guitar", "Steel Guitar", "Jazz Guitar", "Clean Guitar", "Muted Guitar", "Overdrive Gt", "Distortion Gt", "Gt Harmonics",
+    "Acoustic Bass", "Finger Bass", "Pick Bass", "Fretless Bass", "Slap Bass 1", "Slap Bass 2", "Synth Bass 1", "Synth Bass 2",
+    "Violin", "Viola", "Cello", "Contrabass", "Tremolo Str", "Pizzicato Str", "Harp", "Timpani",
+    "Strings", "Slow Strings", "Synth Str 1", "Synth Str 2", "Choir Aahs", "Voice Oohs", "Synth Voice", "Orchestra Hit",
+    "Trumpet", "Trombone", "Tuba", "Muted Trumpet", "French Horn", "Brass Section", "Synth Brass 1", "Synth Brass 2",
+    "Soprano Sax", "Alto Sax", "Tenor Sax", "Baritone Sax", "Oboe", "English Horn", "Bassoon", "Clarinet",
+    "Piccolo", "Flute", "Recorder", "Pan Flute", "Blown Bottle", "Shakuhachi", "Whistle", "Ocarina",
+    "Square Lead", "Saw Lead", "Calliope Lead", "Chiff Lead", "Charang Lead", "Voice Lead", "Fifths Lead", "Bass Lead",
+    "New Age Pad", "Warm Pad", "Polysynth Pad", "Choir Pad", "Bowed Pad", "Metallic Pad", "Halo Pad", "Sweep Pad",
+    "Rain", "Soundtrack", "Crystal", "Atmosphere", "Brightness", "Goblins", "Echoes", "Sci-fi",
+    "Sitar", "Banjo", "Shamisen", "Koto", "Kalimba", "Bagpipe", "Fiddle", "Shanai",
+    "Tinkle Bell", "Agogo", "Steel Drums", "Woodblock", "Taiko Drum", "Melodic Tom", "Synth Drum", "Reverse Cymbal",
+    "Gt Fret Noise", "Breath Noise", "Seashore", "Bird Tweet", "Telephone", "Helicopter", "Applause", "Gunshot",
+]
+
+# 鼓组：(键位区间, 采样, 是否随键位变调, 衰减(cB), 互斥组)
+DRUM_MAP = [
+    ((27, 34), "click", False, 60, 0),
+    ((35, 36), "kick", False, 0, 0),
+    ((37, 37), "click", False, 30, 0),
+    ((38, 38), "snare", False, 0, 0),
+    ((39, 39), "clap", False, 20, 0),
+    ((40, 40), "snare", False, 20, 0),
+    ((41, 41), "tom", True, 10, 0),
+    ((42, 42), "hihat", False, 40, 1),
+    ((43, 43), "tom", True, 10, 0),
+    ((44, 44), "hihat", False, 50, 1),
+    ((45, 45), "tom", True, 10, 0),
+    ((46, 46), "openhat", False, 40, 1),
+    ((47, 48), "tom", True, 10, 0),
+    ((49, 49), "cymbal", False, 40, 0),
+    ((50, 50), "tom", True, 10, 0),
+    ((51, 51), "openhat", False, 70, 0),
+    ((52, 52), "cymbal", False, 40, 0),
+    ((53, 53), "openhat", False, 60, 0),
+    ((54, 54), "click", False, 50, 0),
+    ((55, 55), "cymbal", False, 60, 0),
+    ((56, 56), "click", False, 40, 0),
+    ((57, 57), "cymbal", False, 40, 0),
+    ((58, 58), "click", False, 40, 0),
+    ((59, 59), "openhat", False, 70, 0),
+    ((60, 81), "click", True, 40, 0),
+]
+
+
+def chunk(tag, data):
+    pad = b"\0" if len(data) % 2 else b""
+    return tag + struct.pack("<I", len(data)) + data + pad
+
+
+def list_chunk(tag, chunks):
+    body = tag + b"".join(chunks)
+    return b"LIST" + struct.pack("<I", len(body)) + body
+
+
+def name20(name):
+    return name.encode("ascii")[:19].ljust(20, b"\0")
+
+
+def gen(oper, amount):
+    return struct.pack("<Hh", oper, amount)
+
+
+def gen_range(oper, lo, hi):
+    return struct.pack("<HBB", oper, lo, hi)
+
+
+def build():
+    sample_data = []  # 所有采样首尾相接，每个采样后补 46 个零
+    headers = []  # shdr 记录
+
+    def add_sample(name, points, rate, root_key, loop):
+        start = len(sample_data)
+        sample_data.extend(points)
+        end = len(sample_data)
+        sample_data.extend([0] * 46)
+        if loop:
+            loop_start, loop_end = start + 2 * CYCLE, start + 6 * CYCLE
+        else:
+            loop_start, loop_end = start + 8, end - 8
+        headers.append(struct.pack(
+            "<20sIIIIIBbHH", name20(name), start, end, loop_start, loop_end, rate, root_key, 0, 0, 1,
+        ))
+        return len(headers) - 1
+
+    wave_ids = {}
+    for name, (low, high) in waveforms().items():
+        wave_ids[name] = (
+            add_sample(name + " L", normalize(low * CYCLES), WAVE_RATE, WAVE_ROOT_KEY, True),
+            add_sample(name + " H", normalize(high * CYCLES), WAVE_RATE, WAVE_ROOT_KEY, True),
+        )
+    drum_ids = {name: add_sample(name, normalize(points), DRUM_RATE, 60, False) for name, points in drums().items()}
+    headers.append(struct.pack("<20sIIIIIBbHH", name20("EOS"), 0, 0, 0, 0, 0, 0, 0, 0, 0))
+
+    # 乐器：每个乐器族两个区域（低音区/高音区），鼓组每个键位区间一个区域
+    inst, ibag, igen = [], [], []
+
+    def add_instrument(name, zones):
+        inst.append(name20(name) + struct.pack("<H", len(ibag)))
+        for zone in zones:
+            ibag.append(struct.pack("<HH", len(igen), 0))
+            igen.extend(zone)
+
+    for name, wave, attack, decay, sustain, release, attenuation in FAMILIES:
+        low, high = wave_ids[wave]
+        envelope = [
+            gen(GEN_ATTACK_VOL_ENV, timecents(attack)),
+            gen(GEN_DECAY_VOL_ENV, timecents(decay)),
+            gen(GEN_SUSTAIN_VOL_ENV, sustain),
+            gen(GEN_RELEASE_VOL_ENV, timecents(release)),
+            gen(GEN_INITIAL_ATTENUATION, attenuation),
+            gen(GEN_SAMPLE_MODES, 1),
+        ]
+        add_instrument(name, [
+            [gen_range(GEN_KEY_RANGE, 0, 71)] + envelope + [gen(GEN_SAMPLE_ID, low)],
+            [gen_range(GEN_KEY_RANGE, 72, 127)] + envelope + [gen(GEN_SAMPLE_ID, high)],
+        ])
+
+    drum_zones = []
+    for (lo, hi), sample, pitched, attenuation, exclusive in DRUM_MAP:
+        zone = [
+            gen_range(GEN_KEY_RANGE, lo, hi),
+            gen(GEN_RELEASE_VOL_ENV, timecents(0.3)),
+            gen(GEN_INITIAL_ATTENUATION, attenuation),
+        ]
+        if not pitched:
+            zone += [gen(GEN_SCALE_TUNING, 0), gen(GEN_OVERRIDING_ROOT_KEY, 60)]
+        if exclusive:
+            zone.append(gen(GEN_EXCLUSIVE_CLASS, exclusive))
+        zone.append(gen(GEN_SAMPLE_ID, drum_ids[sample]))
+        drum_zones.append(zone)
+    add_instrument("Standard Kit", drum_zones)
+    drum_instrument = len(inst) - 1
+
+    inst.append(name20("EOI") + struct.pack("<H", len(ibag)))
+    ibag.append(struct.pack("<HH", len(igen), 0))
+    igen.append(gen(0, 0))
+
+    # 音色：128 个 GM 音色各指向所属乐器族，bank 128 为鼓组
+    phdr, pbag, pgen = [], [], []
+
+    def add_preset(name, program, bank, instrument):
+        phdr.append(name20(name) + struct.pack("<HHHIII", program, bank, len(pbag), 0, 0, 0))
+        pbag.append(struct.pack("<HH", len(pgen), 0))
+        pgen.append(gen(GEN_INSTRUMENT, instrument))
+
+    for program, name in enumerate(GM_NAMES):
+        add_preset(name, program, 0, program // 8)
+    add_preset("Standard Kit", 0, 128, drum_instrument)
+
+    phdr.append(name20("EOP") + struct.pack("<HHHIII", 0, 0, len(pbag), 0, 0, 0))
+    pbag.append(struct.pack("<HH", len(pgen), 0))
+    pgen.append(gen(0, 0))
+
+    terminal_mod = b"\0" * 10
+    info = list_chunk(b"INFO", [
+        chunk(b"ifil", struct.pack("<HH", 2, 1)),
+        chunk(b"isng", b"EMU8000\0"),
+        chunk(b"INAM", b"MiniGM\0\0"),
+        chunk(b"ICMT", b"Synthesized General MIDI set generated by scripts/generate-soundfont.py\0"),
+        chunk(b"ICOP", b"Public domain (CC0)\0"),
+    ])
+    sdta = list_chunk(b"sdta", [chunk(b"smpl", struct.pack("<%dh" % len(sample_data), *sample_data))])
+    pdta = list_chunk(b"pdta", [
+        chunk(b"phdr", b"".join(phdr)),
+        chunk(b"pbag", b"".join(pbag)),
+        chunk(b"pmod", terminal_mod),
+        chunk(b"pgen", b"".join(pgen)),
+        chunk(b"inst", b"".join(inst)),
+        chunk(b"ibag", b"".join(ibag)),
+        chunk(b"imod", terminal_mod),
+        chunk(b"igen", b"".join(igen)),
+        chunk(b"shdr", b"".join(headers)),
+    ])
+    body = b"sfbk" + info + sdta + pdta
+    return b"RIFF" + struct.pack("<I", len(body)) + body
+
+
+def main():
+    default = Path(__file__).resolve().parent.parent / "src-tauri" / "resources" / "soundfont" / "MiniGM.sf2"
+    output = Path(sys.argv[1]) if len(sys.argv) > 1 else default
+    output.write_bytes(build())
+    print("已生成 %s（%d 字节）" % (output, output.stat().st_size))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,161 @@
+package com.tauri_app.app
+
+import android.app.Activity
+import android.graphics.Bitmap
+import android.graphics.BitmapFactory
+import android.media.MediaDescription
+import android.media.MediaMetadata
+import android.media.session.MediaSession
+import android.media.session.PlaybackState
+import android.os.SystemClock
+import android.util.Base64
+import app.tauri.annotation.Command
+import app.tauri.annotation.InvokeArg
+import app.tauri.annotation.TauriPlugin
+import app.tauri.plugin.Channel
+import app.tauri.plugin.Invoke
+import app.tauri.plugin.JSObject
+import app.tauri.plugin.Plugin
+
+// 与 src/media_session.rs 中的 MediaSessionState 对应
+
+@InvokeArg
+class SessionMetadataArgs {
+    var title: String = ""
+    var artist: String? = null
+    var album: String? = null
+    var durationMs: Long? = null
+    var artwork: String? = null // data URL
+    var trackNumber: Long? = null
+}
+
+@InvokeArg
+class QueueItemArgs {
+    var id: Long = 0
+    var title: String = ""
+    var subtitle: String? = null
+}
+
+@InvokeArg
+class SessionStateArgs {
+    var metadata: SessionMetadataArgs? = null
+    var state: String = "Stopped"
+    var positionMs: Long = 0
+    var updatedAt: Long = 0 // Unix 毫秒
+    var queue: Array<QueueItemArgs> = arrayOf()
+    var activeQueueItem: Long? = null
+    var actions: Array<String> = arrayOf()
+}
+
+@InvokeArg
+class ActionHandlerArgs {
+    lateinit var handler: Channel
+}
+
+/**
+ * 系统媒体会话：通知栏、锁屏、耳机按钮、蓝牙和车机界面显示当前曲目并控制播放
+ * 状态由 Rust 一侧的 update 命令推送，系统发来的操作经 setActionHandler 注册的通道交回 Rust（MediaSessionAction）
+ */
+@TauriPlugin
+class MediaSessionPlugin(private val activity: Activity) : Plugin(activity) {
+    private val session = MediaSession(activity, "MusicPlayer")
+    private var handler: Channel? = null
+    // 上次解码的封面，曲目不变时不重复解码
+    private var artworkUrl: String? = null
+    private var artwork: Bitmap? = null
+
+    init {
+        session.setCallback(object : MediaSession.Callback() {
+            override fun onPlay() = send("play")
+            override fun onPause() = send("pause")
+            override fun onStop() = send("stop")
+            override fun onSkipToNext() = send("skipToNext")
+            override fun onSkipToPrevious() = send("skipToPrevious")
+            override fun onFastForward() = send("fastForward")
+            override fun onRewind() = send("rewind")
+            override fun onSeekTo(pos: Long) = send("seekTo") { put("positionMs", pos) }
+            override fun onSkipToQueueItem(id: Long) = send("skipToQueueItem") { put("id", id) }
+        })
+    }
+
+    private fun send(action: String, fill: JSObject.() -> Unit = {}) {
+        val payload = JSObject()
+        payload.put("action", action)
+        payload.fill()
+        handler?.send(payload)
+    }
+
+    @Command
+    fun setActionHandler(invoke: Invoke) {
+        handler = invoke.parseArgs(ActionHandlerArgs::class.java).handler
+        invoke.resolve()
+    }
+
+    @Command
+    fun update(invoke: Invoke) {
+        val args = invoke.parseArgs(SessionStateArgs::class.java)
+
+        args.metadata?.let { metadata ->
+            val builder = MediaMetadata.Builder()
+                .putString(MediaMetadata.METADATA_KEY_TITLE, metadata.title)
+                .putString(MediaMetadata.METADATA_KEY_ARTIST, metadata.artist)
+                .putString(MediaMetadata.METADATA_KEY_ALBUM, metadata.album)
+            metadata.durationMs?.let { builder.putLong(MediaMetadata.METADATA_KEY_DURATION, it) }
+            metadata.trackNumber?.let { builder.putLong(MediaMetadata.METADATA_KEY_TRACK_NUMBER, it) }
+            decodeArtwork(metadata.artwork)?.let { builder.putBitmap(MediaMetadata.METADATA_KEY_ALBUM_ART, it) }
+            session.setMetadata(builder.build())
+        }
+
+        session.setQueue(args.queue.map { item ->
+            val description = MediaDescription.Builder()
+                .setMediaId(item.id.toString())
+                .setTitle(item.title)
+                .setSubtitle(item.subtitle)
+                .build()
+            MediaSession.QueueItem(description, item.id)
+        })
+
+        val state = when (args.state) {
+            "Playing" -> PlaybackState.STATE_PLAYING
+            "Paused" -> PlaybackState.STATE_PAUSED
+            else -> PlaybackState.STATE_STOPPED
+        }
+        // 系统按 elapsedRealtime 外推播放位置
+        val updateTime = SystemClock.elapsedRealtime() - (System.currentTimeMillis() - args.updatedAt).coerceAtLeast(0)
+        val playback = PlaybackState.Builder()
+            .setActions(actionMask(args.actions))
+            .setState(state, args.positionMs, if (state == PlaybackState.STATE_PLAYING) 1f else 0f, updateTime)
+        args.activeQueueItem?.let { playback.setActiveQueueItemId(it) }
+        session.setPlaybackState(playback.build())
+
+        session.isActive = args.metadata != null
+        invoke.resolve()
+    }
+
+    private fun decodeArtwork(url: String?): Bitmap? {
+        if (url != artworkUrl) {
+            artworkUrl = url
+            artwork = url?.substringAfter("base64,", "")?.takeIf { it.isNotEmpty() }?.let {
+                val bytes = Base64.decode(it, Base64.DEFAULT)
+                BitmapFactory.decodeByteArray(bytes, 0, bytes.size)
+            }
+        }
+        return artwork
+    }
+
+    private fun actionMask(actions: Array<String>): Long = actions.fold(0L) { mask, action ->
+        mask or when (action) {
+            "play" -> PlaybackState.ACTION_PLAY
+            "pause" -> PlaybackState.ACTION_PAUSE
+            "playPause" -> PlaybackState.ACTION_PLAY_PAUSE
+            "stop" -> PlaybackState.ACTION_STOP
+            "seekTo" -> PlaybackState.ACTION_SEEK_TO
+            "fastForward" -> PlaybackState.ACTION_FAST_FORWARD
+            "rewind" -> PlaybackState.ACTION_REWIND
+            "skipToNext" -> PlaybackState.ACTION_SKIP_TO_NEXT
+            "skipToPrevious" -> PlaybackState.ACTION_SKIP_TO_PREVIOUS
+            "skipToQueueItem" -> PlaybackState.ACTION_SKIP_TO_QUEUE_ITEM
+            else -> 0L
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use rodio::Source;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// 分析用的能量包络帧率（每秒帧数）
+const ENVELOPE_RATE: u32 = 200;
+
+/// 最多分析的音频时长（秒），跳过开头的前奏部分
+const ANALYZE_SECS: u32 = 60;
+const SKIP_SECS: u64 = 15;
+
+/// 检测范围，超出范围的结果按倍速/半速折算
+const MIN_BPM: f32 = 60.0;
+const MAX_BPM: f32 = 180.0;
+
+/// 获取曲目BPM：优先读取标签（ID3 TBPM），没有时分析音频
+pub fn detect(path: &Path) -> Option<f32> {
+    read_tag_bpm(path).or_else(|| analyze(path))
+}
+
+fn read_tag_bpm(path: &Path) -> Option<f32> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    if ext != "mp3" {
+        return None;
+    }
+    let tag = id3::Tag::read_from_path(path).ok()?;
+    let bpm: f32 = tag.get("TBPM")?.content().text()?.trim().parse().ok()?;
+    (bpm > 0.0).then_some(bpm)
+}
+
+/// 基于能量起伏的自相关估算BPM
+fn analyze(path: &Path) -> Option<f32> {
+    let file = File::open(path).ok()?;
+    let decoder = rodio::Decoder::new(BufReader::new(file)).ok()?;
+    let channels = decoder.channels().max(1) as usize;
+    let sample_rate = decoder.sample_rate().max(1);
+    let hop = (sample_rate / ENVELOPE_RATE).max(1) as usize;
+
+    // 混为单声道并按帧计算能量
+    let mut energies = Vec::with_capacity((ANALYZE_SECS * ENVELOPE_RATE) as usize);
+    let mut frame_energy = 0f64;
+    let mut frame_len = 0usize;
+    let samples = decoder
+        .skip_duration(std::time::Duration::from_secs(SKIP_SECS))
+        .take_duration(std::time::Duration::from_secs(ANALYZE_SECS as u64));
+    let mut mono = 0f64;
+    for (idx, sample) in samples.enumerate() {
+        mono += sample as f64 / i16::MAX as f64;
+        if (idx + 1) % channels != 0 {
+            continue;
+        }
+        let value = mono / channels as f64;
+        mono = 0.0;
+        frame_energy += value * value;
+        frame_len += 1;
+        if frame_len == hop {
+            energies.push(frame_energy.sqrt());
+            frame_energy = 0.0;
+            frame_len = 0;
+        }
+    }
+    if energies.len() < (ENVELOPE_RATE * 10) as usize {
+        return None; // 有效音频不足10秒
+    }
+
+    // 起音包络：能量上升的部分
+    let onsets: Vec<f64> = energies.windows(2).map(|w| (w[1] - w[0]).max(0.0)).collect();
+    let mean = onsets.iter().sum::<f64>() / onsets.len() as f64;
+    let onsets: Vec<f64> = onsets.iter().map(|v| v - mean).collect();
+
+    let rate = ENVELOPE_RATE as f32;
+    let min_lag = (60.0 * rate / MAX_BPM) as usize;
+    let max_lag = (60.0 * rate / MIN_BPM) as usize;
+    let correlation = |lag: usize| -> f64 { onsets.iter().zip(&onsets[lag..]).map(|(a, b)| a * b).sum() };
+    let scores: Vec<f64> = (min_lag - 1..=max_lag + 1).map(correlation).collect();
+
+    let (best, best_score) = scores[1..scores.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (i + 1, *s))
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+    if best_score <= 0.0 {
+        return None;
+    }
+
+    // 抛物线插值细化峰值位置
+    let (prev, next) = (scores[best - 1], scores[best + 1]);
+    let denom = prev - 2.0 * best_score + next;
+    let offset = if denom.abs() > f64::EPSILON { 0.5 * (prev - next) / denom } else { 0.0 };
+    let lag = (min_lag - 1 + best) as f64 + offset;
+
+    let bpm = (60.0 * rate as f64 / lag) as f32;
+    Some((bpm * 10.0).round() / 10.0)
+}
+
+/// 把BPM按倍速/半速折算到最接近目标的值（如 170 对应 85）
+pub fn nearest_to(bpm: f32, target: f32) -> f32 {
+    [bpm / 2.0, bpm, bpm * 2.0]
+        .into_iter()
+        .min_by(|a, b| (a - target).abs().total_cmp(&(b - target).abs()))
+        .unwrap_or(bpm)
+}
@@ -0,0 +1,249 @@
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::fs::FileTimes;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// 默认缓存总大小上限
+const DEFAULT_BUDGET_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// 缓存写入后磁盘至少保留的剩余空间
+const MIN_FREE_BYTES: u64 = 512 * 1024 * 1024;
+
+/// 缓存类型，每种类型一个子目录
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheKind {
+    Cover,    // 在线获取的封面
+    Artwork,  // 从音频文件中提取并缩放的封面
+    Lyrics,   // 在线获取的歌词
+    Stream,   // 已完整下载的网络音源
+}
+
+impl CacheKind {
+    pub const ALL: [CacheKind; 4] = [CacheKind::Cover, CacheKind::Artwork, CacheKind::Lyrics, CacheKind::Stream];
+
+    fn dir_name(&self) -> &'static str {
+        match self {
+            CacheKind::Cover => "covers",
+            CacheKind::Artwork => "artwork",
+            CacheKind::Lyrics => "lyrics",
+            CacheKind::Stream => "streams",
+        }
+    }
+}
+
+/// 缓存设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheSettings {
+    #[serde(rename = "budgetBytes", default = "default_budget")]
+    pub budget_bytes: u64, // 所有缓存合计的大小上限
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        Self {
+            budget_bytes: default_budget(),
+        }
+    }
+}
+
+fn default_budget() -> u64 {
+    DEFAULT_BUDGET_BYTES
+}
+
+/// 单类缓存的占用
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheKindUsage {
+    pub kind: CacheKind,
+    pub bytes: u64,
+    pub files: usize,
+}
+
+/// 缓存占用情况
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheUsage {
+    pub kinds: Vec<CacheKindUsage>,
+    #[serde(rename = "totalBytes")]
+    pub total_bytes: u64,
+    #[serde(rename = "budgetBytes")]
+    pub budget_bytes: u64,
+    #[serde(rename = "availableBytes")]
+    pub available_bytes: Option<u64>, // 缓存所在磁盘的剩余空间
+}
+
+/// 缓存根目录
+fn cache_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(storage::data_dir)
+        .join("music-player")
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("cache.json")
+}
+
+/// 串行化写入和淘汰，避免并发写入时重复删除或超出上限
+fn write_lock() -> &'static Mutex<()> {
+    static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+    LOCK.get_or_init(|| Mutex::new(()))
+}
+
+/// 读取缓存设置
+pub fn load_settings() -> CacheSettings {
+    storage::load_json(&settings_path())
+        .unwrap_or_else(|e| {
+            eprintln!("读取缓存设置失败: {}", e);
+            None
+        })
+        .unwrap_or_default()
+}
+
+/// 保存缓存设置，立即按新上限淘汰
+pub fn save_settings(settings: &CacheSettings) -> anyhow::Result<()> {
+    storage::save_json(&settings_path(), settings)?;
+    let _guard = write_lock().lock().map_err(|_| anyhow::anyhow!("无法锁定缓存"))?;
+    evict(0)
+}
+
+/// 缓存文件路径（键名取哈希，避免URL等特殊字符）
+fn entry_path(kind: CacheKind, key: &str) -> PathBuf {
+    cache_dir().join(kind.dir_name()).join(blake3::hash(key.as_bytes()).to_hex().as_str())
+}
+
+/// 更新文件修改时间作为最近使用时间
+fn touch(path: &Path) {
+    let result = std::fs::File::options()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_times(FileTimes::new().set_modified(SystemTime::now())));
+    if let Err(e) = result {
+        eprintln!("更新缓存访问时间失败 {}: {}", path.display(), e);
+    }
+}
+
+/// 查找缓存文件，命中时更新最近使用时间
+pub fn lookup(kind: CacheKind, key: &str) -> Option<PathBuf> {
+    let path = entry_path(kind, key);
+    if !path.is_file() {
+        return None;
+    }
+    touch(&path);
+    Some(path)
+}
+
+/// 写入缓存，超出总大小上限或磁盘空间不足时按最近最少使用淘汰旧文件
+pub fn put(kind: CacheKind, key: &str, data: &[u8]) -> anyhow::Result<PathBuf> {
+    let _guard = write_lock().lock().map_err(|_| anyhow::anyhow!("无法锁定缓存"))?;
+    evict(data.len() as u64)?;
+    let path = entry_path(kind, key);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, data)?;
+    std::fs::rename(&tmp_path, &path)?;
+    Ok(path)
+}
+
+/// 所有缓存文件：(路径, 类型, 大小, 最近使用时间)
+fn entries() -> Vec<(PathBuf, CacheKind, u64, SystemTime)> {
+    let mut result = Vec::new();
+    for kind in CacheKind::ALL {
+        let dir = cache_dir().join(kind.dir_name());
+        let read_dir = match std::fs::read_dir(&dir) {
+            Ok(read_dir) => read_dir,
+            Err(_) => continue,
+        };
+        for entry in read_dir.flatten() {
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            if metadata.is_file() {
+                let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                result.push((entry.path(), kind, metadata.len(), modified));
+            }
+        }
+    }
+    result
+}
+
+/// 缓存所在磁盘的剩余空间
+fn available_space() -> Option<u64> {
+    let dir = cache_dir();
+    let probe = if dir.exists() { dir } else { storage::data_dir() };
+    fs2::available_space(probe).ok()
+}
+
+/// 淘汰最久未使用的缓存，直到能再放下 incoming 字节：
+/// 总大小不超过上限，且写入后磁盘仍保留 MIN_FREE_BYTES 剩余空间
+fn evict(incoming: u64) -> anyhow::Result<()> {
+    let mut entries = entries();
+    let mut total: u64 = entries.iter().map(|(_, _, size, _)| size).sum();
+    let mut limit = load_settings().budget_bytes;
+    if let Some(available) = available_space() {
+        // 已占用的缓存可以腾出来，所以把它加回可用空间
+        limit = limit.min((total + available).saturating_sub(MIN_FREE_BYTES));
+    }
+    if incoming > limit {
+        return Err(anyhow::anyhow!("缓存空间不足，无法写入 {} 字节", incoming));
+    }
+
+    entries.sort_by_key(|(_, _, _, modified)| *modified);
+    for (path, _, size, _) in entries {
+        if total + incoming <= limit {
+            break;
+        }
+        match std::fs::remove_file(&path) {
+            Ok(()) => total -= size,
+            Err(e) => eprintln!("删除缓存文件失败 {}: {}", path.display(), e),
+        }
+    }
+    Ok(())
+}
+
+/// 淘汰超出上限的最久未使用缓存，返回释放的字节数
+pub fn trim() -> anyhow::Result<u64> {
+    let _guard = write_lock().lock().map_err(|_| anyhow::anyhow!("无法锁定缓存"))?;
+    let before: u64 = entries().iter().map(|(_, _, size, _)| size).sum();
+    evict(0)?;
+    let after: u64 = entries().iter().map(|(_, _, size, _)| size).sum();
+    Ok(before.saturating_sub(after))
+}
+
+/// 统计各类缓存占用
+pub fn usage() -> CacheUsage {
+    let entries = entries();
+    let kinds: Vec<CacheKindUsage> = CacheKind::ALL
+        .iter()
+        .map(|kind| {
+            let files: Vec<u64> = entries.iter().filter(|(_, k, _, _)| k == kind).map(|(_, _, size, _)| *size).collect();
+            CacheKindUsage {
+                kind: *kind,
+                bytes: files.iter().sum(),
+                files: files.len(),
+            }
+        })
+        .collect();
+    CacheUsage {
+        total_bytes: kinds.iter().map(|k| k.bytes).sum(),
+        kinds,
+        budget_bytes: load_settings().budget_bytes,
+        available_bytes: available_space(),
+    }
+}
+
+/// 清空指定类型的缓存，kind 为空时清空全部；返回释放的字节数
+pub fn clear(kind: Option<CacheKind>) -> anyhow::Result<u64> {
+    let _guard = write_lock().lock().map_err(|_| anyhow::anyhow!("无法锁定缓存"))?;
+    let mut freed = 0;
+    for (path, entry_kind, size, _) in entries() {
+        if kind.map(|k| k == entry_kind).unwrap_or(true) {
+            std::fs::remove_file(&path)?;
+            freed += size;
+        }
+    }
+    Ok(freed)
+}
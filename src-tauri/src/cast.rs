@@ -0,0 +1,589 @@
+use crate::global_player::GlobalPlayer;
+use crate::http_server;
+use crate::player_fixed::{PlayerCommand, PlayerEvent, SongInfo};
+use crate::player_safe::SafePlayerManager;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// SSDP 组播地址
+const SSDP_ADDR: &str = "239.255.255.250:1900";
+
+/// 搜索渲染器时等待应答的时间
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 投屏时查询渲染器播放状态的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 连续这么多次查询失败（设备离线）后结束投屏
+const MAX_POLL_FAILURES: u32 = 5;
+
+const AV_TRANSPORT: &str = "urn:schemas-upnp-org:service:AVTransport:1";
+const RENDERING_CONTROL: &str = "urn:schemas-upnp-org:service:RenderingControl:1";
+
+/// 局域网中的 DLNA 渲染器（电视、音箱等）
+#[derive(Debug, Clone, Serialize)]
+pub struct CastTarget {
+    pub id: String, // 设备 UDN
+    pub name: String,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    #[serde(skip)]
+    av_transport_url: String,
+    #[serde(skip)]
+    rendering_control_url: Option<String>,
+}
+
+/// 投屏状态，投屏期间每秒通过 cast-status 事件发送给前端
+#[derive(Debug, Clone, Serialize)]
+pub struct CastStatus {
+    #[serde(rename = "targetId")]
+    pub target_id: String,
+    #[serde(rename = "targetName")]
+    pub target_name: String,
+    pub index: Option<usize>, // 正在投屏的播放列表索引
+    pub state: String,        // 渲染器传输状态：PLAYING、PAUSED_PLAYBACK、STOPPED、TRANSITIONING、NO_MEDIA_PRESENT
+    pub position: u64,        // 秒
+    pub duration: u64,
+}
+
+/// 渲染器的传输控制
+enum Transport {
+    Play,
+    Pause,
+    Stop,
+    Seek(u64),
+}
+
+struct Session {
+    target: CastTarget,
+    status: CastStatus,
+    generation: u64,
+    muted_before: bool,   // 投屏前本机是否静音，结束时恢复
+    loading: bool,        // 正在让渲染器载入新歌曲，期间的停止状态不算播完
+    stop_requested: bool, // 用户主动停止，渲染器停止后不切到下一首
+}
+
+fn targets() -> &'static Mutex<Vec<CastTarget>> {
+    static TARGETS: OnceLock<Mutex<Vec<CastTarget>>> = OnceLock::new();
+    TARGETS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+fn session() -> &'static Mutex<Option<Session>> {
+    static SESSION: OnceLock<Mutex<Option<Session>>> = OnceLock::new();
+    SESSION.get_or_init(|| Mutex::new(None))
+}
+
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// 访问局域网设备用的客户端，不走代理设置
+fn lan_client() -> reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT
+        .get_or_init(|| {
+            reqwest::Client::builder()
+                .no_proxy()
+                .timeout(Duration::from_secs(5))
+                .build()
+                .unwrap_or_default()
+        })
+        .clone()
+}
+
+/// 当前投屏状态（未投屏时为 None）
+pub fn status() -> Option<CastStatus> {
+    session().lock().ok()?.as_ref().map(|s| s.status.clone())
+}
+
+/// 通过 SSDP 搜索局域网中的渲染器
+pub async fn discover() -> anyhow::Result<Vec<CastTarget>> {
+    let socket = tokio::net::UdpSocket::bind(("0.0.0.0", 0)).await?;
+    let request = format!(
+        "M-SEARCH * HTTP/1.1\r\nHOST: {}\r\nMAN: \"ssdp:discover\"\r\nMX: 2\r\nST: urn:schemas-upnp-org:device:MediaRenderer:1\r\n\r\n",
+        SSDP_ADDR
+    );
+    // UDP 可能丢包，发两次
+    for _ in 0..2 {
+        socket.send_to(request.as_bytes(), SSDP_ADDR).await?;
+    }
+
+    let mut locations: Vec<String> = Vec::new();
+    let mut buf = [0u8; 2048];
+    let deadline = tokio::time::Instant::now() + DISCOVERY_TIMEOUT;
+    while let Ok(Ok((len, _))) = tokio::time::timeout_at(deadline, socket.recv_from(&mut buf)).await {
+        let response = String::from_utf8_lossy(&buf[..len]);
+        let location = response.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim().eq_ignore_ascii_case("location").then(|| value.trim().to_string())
+        });
+        if let Some(location) = location {
+            if !locations.contains(&location) {
+                locations.push(location);
+            }
+        }
+    }
+
+    let mut found: Vec<CastTarget> = Vec::new();
+    for location in locations {
+        match describe(&location).await {
+            Ok(Some(target)) if !found.iter().any(|t| t.id == target.id) => found.push(target),
+            Ok(_) => {}
+            Err(e) => eprintln!("读取投屏设备描述失败 {}: {}", location, e),
+        }
+    }
+    found.sort_by(|a, b| a.name.cmp(&b.name));
+    println!("📺 发现 {} 个投屏设备", found.len());
+    if let Ok(mut targets) = targets().lock() {
+        *targets = found.clone();
+    }
+    Ok(found)
+}
+
+/// 依次回调 XML 中每个文本节点和它所在的元素路径（只用本地名，忽略命名空间前缀）
+fn walk_xml(xml: &str, mut f: impl FnMut(&[String], String)) {
+    let mut reader = Reader::from_str(xml);
+    let mut path: Vec<String> = Vec::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => path.push(String::from_utf8_lossy(e.local_name().as_ref()).into_owned()),
+            Ok(Event::End(_)) => {
+                path.pop();
+            }
+            Ok(Event::Text(t)) => {
+                let text = t.unescape().map(|t| t.trim().to_string()).unwrap_or_default();
+                if !text.is_empty() {
+                    f(&path, text);
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                eprintln!("解析XML失败: {}", e);
+                break;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 读取设备描述，只返回带 AVTransport 服务的设备
+async fn describe(location: &str) -> anyhow::Result<Option<CastTarget>> {
+    let xml = lan_client().get(location).send().await?.error_for_status()?.text().await?;
+
+    let mut fields: HashMap<String, String> = HashMap::new();
+    let mut services: Vec<(String, String)> = Vec::new(); // (serviceType, controlURL)
+    let mut service_type = String::new();
+    walk_xml(&xml, |path, text| {
+        let Some(name) = path.last() else { return };
+        match name.as_str() {
+            // serviceType 在同一 service 的 controlURL 之前
+            "serviceType" => service_type = text,
+            "controlURL" => services.push((service_type.clone(), text)),
+            "friendlyName" | "UDN" | "manufacturer" | "modelName" | "URLBase" => {
+                // 嵌套设备也有这些字段，取根设备的
+                fields.entry(name.clone()).or_insert(text);
+            }
+            _ => {}
+        }
+    });
+
+    let base = reqwest::Url::parse(fields.get("URLBase").map(String::as_str).unwrap_or(location))?;
+    let control_url = |service: &str| {
+        services
+            .iter()
+            .find(|(kind, _)| kind.starts_with(service.trim_end_matches(":1")))
+            .and_then(|(_, url)| base.join(url).ok())
+            .map(|url| url.to_string())
+    };
+    let Some(av_transport_url) = control_url(AV_TRANSPORT) else {
+        return Ok(None);
+    };
+    Ok(Some(CastTarget {
+        id: fields.get("UDN").cloned().unwrap_or_else(|| location.to_string()),
+        name: fields.get("friendlyName").cloned().unwrap_or_else(|| "DLNA 设备".to_string()),
+        manufacturer: fields.get("manufacturer").cloned(),
+        model: fields.get("modelName").cloned(),
+        rendering_control_url: control_url(RENDERING_CONTROL),
+        av_transport_url,
+    }))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// 调用 SOAP 操作，返回响应中各元素的文本
+async fn soap(url: &str, service: &str, action: &str, args: &[(&str, String)]) -> anyhow::Result<HashMap<String, String>> {
+    let args: String = args
+        .iter()
+        .map(|(name, value)| format!("<{}>{}</{}>", name, escape(value), name))
+        .collect();
+    let body = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\
+         <s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\">\
+         <s:Body><u:{} xmlns:u=\"{}\">{}</u:{}></s:Body></s:Envelope>",
+        action, service, args, action
+    );
+    let response = lan_client()
+        .post(url)
+        .header("Content-Type", "text/xml; charset=\"utf-8\"")
+        .header("SOAPAction", format!("\"{}#{}\"", service, action))
+        .body(body)
+        .send()
+        .await?;
+    let status = response.status();
+    let xml = response.text().await?;
+    let mut values = HashMap::new();
+    walk_xml(&xml, |path, text| {
+        if let Some(name) = path.last() {
+            values.insert(name.clone(), text);
+        }
+    });
+    if !status.is_success() {
+        let detail = values
+            .get("errorDescription")
+            .or(values.get("faultstring"))
+            .cloned()
+            .unwrap_or_else(|| status.to_string());
+        return Err(anyhow::anyhow!("{} 失败: {}", action, detail));
+    }
+    Ok(values)
+}
+
+/// 秒 -> H:MM:SS
+fn format_time(secs: u64) -> String {
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// H:MM:SS[.fff] -> 秒，NOT_IMPLEMENTED 等无效值为 None
+fn parse_time(text: &str) -> Option<u64> {
+    let parts: Vec<&str> = text.trim().split(':').collect();
+    let [h, m, s] = parts.as_slice() else { return None };
+    let s: f64 = s.parse().ok()?;
+    Some(h.parse::<u64>().ok()? * 3600 + m.parse::<u64>().ok()? * 60 + s as u64)
+}
+
+async fn control(target: &CastTarget, action: Transport) -> anyhow::Result<()> {
+    let instance = ("InstanceID", "0".to_string());
+    let url = &target.av_transport_url;
+    match action {
+        Transport::Play => soap(url, AV_TRANSPORT, "Play", &[instance, ("Speed", "1".to_string())]).await?,
+        Transport::Pause => soap(url, AV_TRANSPORT, "Pause", &[instance]).await?,
+        Transport::Stop => soap(url, AV_TRANSPORT, "Stop", &[instance]).await?,
+        Transport::Seek(secs) => {
+            let args = [instance, ("Unit", "REL_TIME".to_string()), ("Target", format_time(secs))];
+            soap(url, AV_TRANSPORT, "Seek", &args).await?
+        }
+    };
+    Ok(())
+}
+
+async fn set_volume(target: &CastTarget, volume: f32) -> anyhow::Result<()> {
+    let Some(url) = &target.rendering_control_url else { return Ok(()) };
+    let args = [
+        ("InstanceID", "0".to_string()),
+        ("Channel", "Master".to_string()),
+        ("DesiredVolume", ((volume.clamp(0.0, 1.0) * 100.0).round() as u32).to_string()),
+    ];
+    soap(url, RENDERING_CONTROL, "SetVolume", &args).await?;
+    Ok(())
+}
+
+/// 渲染器访问本机时使用的地址：连到渲染器所用网卡的 IP
+fn local_ip_for(target: &CastTarget) -> anyhow::Result<std::net::IpAddr> {
+    let url = reqwest::Url::parse(&target.av_transport_url)?;
+    let host = url.host_str().ok_or_else(|| anyhow::anyhow!("无效的设备地址"))?;
+    let socket = std::net::UdpSocket::bind(("0.0.0.0", 0))?;
+    socket.connect((host, url.port_or_known_default().unwrap_or(80)))?;
+    Ok(socket.local_addr()?.ip())
+}
+
+/// 让渲染器播放歌曲：本地文件通过内嵌HTTP服务共享，网络串流直接交给渲染器
+/// 分段曲目（CUE、多音轨/多章节容器中的一段）只是文件的一部分，渲染器只能播放整个文件，不支持投屏
+async fn load(target: &CastTarget, song: &SongInfo, position: u64) -> anyhow::Result<()> {
+    if song.segment.is_some() {
+        anyhow::bail!("分段曲目（CUE、多音轨容器）不支持投屏");
+    }
+    let mime = http_server::mime_for_path(&song.path);
+    let url = if song.path.starts_with("http://") || song.path.starts_with("https://") {
+        song.path.clone()
+    } else {
+        let port = http_server::ensure_lan(http_server::DEFAULT_PORT).await.map_err(|e| anyhow::anyhow!(e))?;
+        let token = http_server::share_for_cast(&song.path);
+        let ext = Path::new(&song.path).extension().and_then(|e| e.to_str()).unwrap_or("bin");
+        format!("http://{}:{}/cast/{}.{}", local_ip_for(target)?, port, token, ext)
+    };
+    let title = song.title.clone().unwrap_or_else(|| {
+        Path::new(&song.path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    });
+    let class = if mime.starts_with("video/") { "object.item.videoItem" } else { "object.item.audioItem.musicTrack" };
+    let metadata = format!(
+        "<DIDL-Lite xmlns=\"urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\" \
+         xmlns:upnp=\"urn:schemas-upnp-org:metadata-1-0/upnp/\"><item id=\"0\" parentID=\"-1\" restricted=\"1\">\
+         <dc:title>{}</dc:title><upnp:artist>{}</upnp:artist><upnp:album>{}</upnp:album><upnp:class>{}</upnp:class>\
+         <res protocolInfo=\"http-get:*:{}:*\">{}</res></item></DIDL-Lite>",
+        escape(&title),
+        escape(song.artist.as_deref().unwrap_or("")),
+        escape(song.album.as_deref().unwrap_or("")),
+        class,
+        mime,
+        escape(&url)
+    );
+
+    let instance = ("InstanceID", "0".to_string());
+    let args = [instance.clone(), ("CurrentURI", url), ("CurrentURIMetaData", metadata)];
+    soap(&target.av_transport_url, AV_TRANSPORT, "SetAVTransportURI", &args).await?;
+    control(target, Transport::Play).await?;
+
+    // 多数渲染器要进入播放状态后才接受跳转
+    if position > 0 {
+        for _ in 0..10 {
+            let info = soap(&target.av_transport_url, AV_TRANSPORT, "GetTransportInfo", &[instance.clone()]).await?;
+            if info.get("CurrentTransportState").map(String::as_str) == Some("PLAYING") {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        control(target, Transport::Seek(position)).await?;
+    }
+    println!("📺 投屏到 {}: {}", target.name, title);
+    Ok(())
+}
+
+async fn local_player() -> anyhow::Result<Arc<SafePlayerManager>> {
+    let instance = GlobalPlayer::instance()
+        .lock()
+        .map_err(|_| anyhow::anyhow!("无法锁定 GlobalPlayer"))?
+        .get_player()
+        .ok_or_else(|| anyhow::anyhow!("播放器未初始化"))?;
+    let player = instance.lock().await.player.clone();
+    Ok(player)
+}
+
+/// 结束投屏：停止渲染器，restore_local 时本机回到渲染器的播放位置并恢复静音状态（保持暂停）
+async fn end_session(restore_local: bool) -> Option<Session> {
+    let session = session().lock().ok()?.take()?;
+    if let Err(e) = control(&session.target, Transport::Stop).await {
+        eprintln!("停止投屏设备失败: {}", e);
+    }
+    http_server::clear_cast_files();
+    if restore_local {
+        if let Ok(player) = local_player().await {
+            let _ = player.send_local_command(PlayerCommand::SeekTo(session.status.position)).await;
+            let _ = player.send_local_command(PlayerCommand::SetMuted(session.muted_before)).await;
+        }
+        println!("📺 已结束投屏");
+    }
+    Some(session)
+}
+
+/// 投屏当前歌曲到指定渲染器（target_id 为 None 时结束投屏），本机暂停并静音，之后的播放控制转给渲染器
+pub async fn cast_to<R: Runtime>(app: AppHandle<R>, target_id: Option<String>) -> anyhow::Result<Option<CastStatus>> {
+    let Some(target_id) = target_id else {
+        end_session(true).await;
+        let _ = app.emit("cast-status", None::<CastStatus>);
+        return Ok(None);
+    };
+
+    let cached = targets().lock().ok().and_then(|t| t.iter().find(|t| t.id == target_id).cloned());
+    let target = match cached {
+        Some(target) => target,
+        None => discover()
+            .await?
+            .into_iter()
+            .find(|t| t.id == target_id)
+            .ok_or_else(|| anyhow::anyhow!("找不到投屏设备: {}", target_id))?,
+    };
+
+    let player = local_player().await?;
+    let playlist = player.get_playlist();
+    let (index, song) = player
+        .get_current_index()
+        .and_then(|index| playlist.get(index).map(|song| (index, song.clone())))
+        .ok_or_else(|| anyhow::anyhow!("没有正在播放的歌曲"))?;
+
+    // 换到另一台设备时沿用原来的播放位置和静音状态
+    let (position, muted_before) = match end_session(false).await {
+        Some(previous) => (previous.status.position, previous.muted_before),
+        None => (player.get_position(), player.is_muted()),
+    };
+    player.send_local_command(PlayerCommand::Pause).await?;
+    player.send_local_command(PlayerCommand::SetMuted(true)).await?;
+
+    if let Err(e) = load(&target, &song, position).await {
+        let _ = player.send_local_command(PlayerCommand::SetMuted(muted_before)).await;
+        http_server::clear_cast_files();
+        return Err(e);
+    }
+
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let status = CastStatus {
+        target_id: target.id.clone(),
+        target_name: target.name.clone(),
+        index: Some(index),
+        state: "TRANSITIONING".to_string(),
+        position,
+        duration: song.duration.unwrap_or(0),
+    };
+    *session().lock().map_err(|_| anyhow::anyhow!("无法锁定投屏状态"))? = Some(Session {
+        target,
+        status: status.clone(),
+        generation,
+        muted_before,
+        loading: false,
+        stop_requested: false,
+    });
+    tauri::async_runtime::spawn(poll(app, generation));
+    Ok(Some(status))
+}
+
+/// 投屏期间定时查询渲染器状态并发送给前端；渲染器播完一首后让本机切到下一首
+async fn poll<R: Runtime>(app: AppHandle<R>, generation: u64) {
+    let mut failures = 0;
+    let mut last_state = String::new();
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let target = match session().lock().ok().as_ref().and_then(|s| s.as_ref()) {
+            Some(s) if s.generation == generation => s.target.clone(),
+            _ => break,
+        };
+        let instance = [("InstanceID", "0".to_string())];
+        let result = async {
+            let info = soap(&target.av_transport_url, AV_TRANSPORT, "GetTransportInfo", &instance).await?;
+            let position = soap(&target.av_transport_url, AV_TRANSPORT, "GetPositionInfo", &instance).await?;
+            anyhow::Ok((info, position))
+        }
+        .await;
+
+        let (info, position) = match result {
+            Ok(result) => {
+                failures = 0;
+                result
+            }
+            Err(e) => {
+                failures += 1;
+                if failures >= MAX_POLL_FAILURES {
+                    eprintln!("投屏设备无响应，结束投屏: {}", e);
+                    end_session(true).await;
+                    let _ = app.emit("cast-status", None::<CastStatus>);
+                    break;
+                }
+                continue;
+            }
+        };
+
+        let mut finished = false;
+        let status = {
+            let Ok(mut guard) = session().lock() else { break };
+            let Some(session) = guard.as_mut().filter(|s| s.generation == generation) else { break };
+            let state = info.get("CurrentTransportState").cloned().unwrap_or_default();
+            if session.loading {
+                last_state.clear();
+            } else {
+                finished = last_state == "PLAYING" && state == "STOPPED" && !session.stop_requested;
+                last_state = state.clone();
+            }
+            if finished {
+                session.loading = true; // 等 SongChanged 载入下一首
+            }
+            session.status.state = state;
+            if let Some(secs) = position.get("RelTime").and_then(|t| parse_time(t)) {
+                session.status.position = secs;
+            }
+            if let Some(secs) = position.get("TrackDuration").and_then(|t| parse_time(t)).filter(|d| *d > 0) {
+                session.status.duration = secs;
+            }
+            session.status.clone()
+        };
+        if let Err(e) = app.emit("cast-status", Some(status)) {
+            eprintln!("发送投屏状态到前端失败: {:?}", e);
+        }
+        if finished {
+            if let Ok(player) = local_player().await {
+                let _ = player.send_local_command(PlayerCommand::AutoAdvance).await;
+            }
+        }
+    }
+}
+
+/// 投屏时把播放控制转给渲染器，返回 None 表示命令已被接管；音量同时调整本机和渲染器
+pub fn intercept(command: PlayerCommand) -> Option<PlayerCommand> {
+    let (target, position) = {
+        let Ok(mut guard) = session().lock() else { return Some(command) };
+        let Some(session) = guard.as_mut() else { return Some(command) };
+        match command {
+            PlayerCommand::Stop => session.stop_requested = true,
+            PlayerCommand::Play | PlayerCommand::ResumeWithFadeIn(_) => session.stop_requested = false,
+            _ => {}
+        }
+        (session.target.clone(), session.status.position)
+    };
+    let action = match &command {
+        PlayerCommand::Play | PlayerCommand::ResumeWithFadeIn(_) => Transport::Play,
+        PlayerCommand::Pause => Transport::Pause,
+        PlayerCommand::Stop => Transport::Stop,
+        PlayerCommand::SeekTo(secs) => Transport::Seek(*secs),
+        PlayerCommand::SkipBy(delta) => Transport::Seek(position.saturating_add_signed(*delta)),
+        PlayerCommand::SetVolume(volume) => {
+            let volume = *volume;
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = set_volume(&target, volume).await {
+                    eprintln!("设置投屏设备音量失败: {}", e);
+                }
+            });
+            return Some(command);
+        }
+        _ => return Some(command),
+    };
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = control(&target, action).await {
+            eprintln!("投屏控制失败: {}", e);
+        }
+    });
+    None
+}
+
+/// 投屏时本机切歌（上一首/下一首/点选/播完自动切歌）后，让渲染器播放新歌曲，本机保持暂停
+pub fn on_player_event(event: &PlayerEvent) {
+    let PlayerEvent::SongChanged(index, song) = event else { return };
+    let target = {
+        let Ok(mut guard) = session().lock() else { return };
+        let Some(session) = guard.as_mut() else { return };
+        if session.status.index == Some(*index) && !session.loading {
+            return;
+        }
+        session.loading = true;
+        session.stop_requested = false;
+        session.status.index = Some(*index);
+        session.status.position = 0;
+        session.status.duration = song.duration.unwrap_or(0);
+        session.target.clone()
+    };
+    let song = song.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Ok(player) = local_player().await {
+            let _ = player.send_local_command(PlayerCommand::Pause).await;
+        }
+        let result = load(&target, &song, 0).await;
+        if let Ok(mut guard) = session().lock() {
+            if let Some(session) = guard.as_mut() {
+                session.loading = false;
+            }
+        }
+        if let Err(e) = result {
+            eprintln!("投屏切歌失败: {}", e);
+        }
+    });
+}
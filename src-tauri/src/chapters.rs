@@ -0,0 +1,211 @@
+use crate::matroska::{self, Chapter};
+use crate::mp4::{be_u32, be_u64, boxes, child, read_moov};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// 章节标题样本的最大读取长度
+const MAX_TITLE_SIZE: u32 = 1024;
+
+/// 读取文件中的章节（有声书、播客、DJ混音），按起始时间排序，缺少结束时间的用下一章的起始时间补全
+/// 支持 MP3 的 ID3v2 章节（CHAP，取自已读入的标签）、MP4/M4A/M4B 的 Nero 章节（chpl）和 QuickTime 章节轨，以及 Matroska 章节；没有章节或读取失败时返回空列表
+pub fn read(path: &Path, id3: Option<&id3::Tag>) -> Vec<Chapter> {
+    let ext = path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).unwrap_or_default();
+    let result = match ext.as_str() {
+        "mp3" => Ok(id3.map(id3_chapters).unwrap_or_default()),
+        "m4a" | "m4b" | "mp4" | "m4v" | "mov" => read_mp4(path),
+        "mka" | "mkv" | "webm" => matroska::probe(path).map(|info| info.chapters),
+        _ => return Vec::new(),
+    };
+    let mut chapters = match result {
+        Ok(chapters) => chapters,
+        Err(e) => {
+            eprintln!("读取章节失败 {}: {}", path.display(), e);
+            return Vec::new();
+        }
+    };
+    chapters.sort_by_key(|c| c.start_ms);
+    chapters.dedup_by_key(|c| c.start_ms);
+    let next_starts: Vec<u64> = chapters.iter().skip(1).map(|c| c.start_ms).collect();
+    for (chapter, next_start) in chapters.iter_mut().zip(next_starts) {
+        chapter.end_ms = chapter.end_ms.or(Some(next_start));
+    }
+    // 只有一章时没有导航的意义
+    if chapters.len() < 2 {
+        chapters.clear();
+    }
+    chapters
+}
+
+/// ID3v2 章节：每个 CHAP 帧是一章，标题在其内嵌的 TIT2 帧中，时间单位为毫秒
+fn id3_chapters(tag: &id3::Tag) -> Vec<Chapter> {
+    tag.chapters()
+        .map(|chapter| Chapter {
+            title: chapter
+                .frames
+                .iter()
+                .find(|frame| frame.id() == "TIT2")
+                .and_then(|frame| frame.content().text())
+                .map(|title| title.trim().to_string())
+                .filter(|title| !title.is_empty()),
+            start_ms: chapter.start_time as u64,
+            end_ms: Some(chapter.end_time as u64).filter(|&end| end > chapter.start_time as u64),
+        })
+        .collect()
+}
+
+/// Nero 章节：moov/udta/chpl，时间单位为 100 纳秒
+fn nero_chapters(moov: &[u8]) -> Option<Vec<Chapter>> {
+    let chpl = child(child(moov, b"udta")?, b"chpl")?;
+    let version = *chpl.first()?;
+    let mut pos = if version > 0 { 8 } else { 4 };
+    let count = *chpl.get(pos)? as usize;
+    pos += 1;
+    let mut chapters = Vec::with_capacity(count);
+    for _ in 0..count {
+        let start = be_u64(chpl, pos)?;
+        let len = *chpl.get(pos + 8)? as usize;
+        let title = chpl.get(pos + 9..pos + 9 + len)?;
+        pos += 9 + len;
+        chapters.push(Chapter {
+            title: Some(String::from_utf8_lossy(title).trim().to_string()).filter(|t| !t.is_empty()),
+            start_ms: start / 10_000,
+            end_ms: None,
+        });
+    }
+    Some(chapters)
+}
+
+/// 解析 trak 得到的信息（只保留章节轨需要的部分）
+#[derive(Default)]
+struct Track {
+    id: u32,
+    chapter_refs: Vec<u32>, // tref/chap 引用的章节轨
+    handler: [u8; 4],
+    timescale: u32,
+    durations: Vec<(u32, u32)>,   // stts：(样本数, 每个样本的时长)
+    sizes: Vec<u32>,              // stsz：每个样本的大小
+    chunk_runs: Vec<(u32, u32)>,  // stsc：(起始块号, 每块样本数)
+    chunk_offsets: Vec<u64>,      // stco/co64
+}
+
+fn parse_track(trak: &[u8]) -> Option<Track> {
+    let mut track = Track::default();
+    let tkhd = child(trak, b"tkhd")?;
+    track.id = be_u32(tkhd, if tkhd.first() == Some(&1) { 20 } else { 12 })?;
+    if let Some(chap) = child(trak, b"tref").and_then(|tref| child(tref, b"chap")) {
+        track.chapter_refs = chap.chunks_exact(4).map(|b| u32::from_be_bytes(b.try_into().unwrap())).collect();
+    }
+    let mdia = child(trak, b"mdia")?;
+    let mdhd = child(mdia, b"mdhd")?;
+    track.timescale = be_u32(mdhd, if mdhd.first() == Some(&1) { 20 } else { 12 })?;
+    track.handler = child(mdia, b"hdlr")?.get(8..12)?.try_into().ok()?;
+    // 章节轨都是文本轨，音视频轨的样本表可能很大，不解析
+    if &track.handler != b"text" {
+        return Some(track);
+    }
+    let stbl = child(child(mdia, b"minf")?, b"stbl")?;
+
+    let table = |kind: &[u8; 4], entry: usize| -> Vec<&[u8]> {
+        child(stbl, kind)
+            .and_then(|data| {
+                let count = be_u32(data, 4)? as usize;
+                Some(data.get(8..)?.chunks_exact(entry).take(count).collect())
+            })
+            .unwrap_or_default()
+    };
+    track.durations = table(b"stts", 8).into_iter().map(|e| (be_u32(e, 0).unwrap(), be_u32(e, 4).unwrap())).collect();
+    track.chunk_runs = table(b"stsc", 12).into_iter().map(|e| (be_u32(e, 0).unwrap(), be_u32(e, 4).unwrap())).collect();
+    track.chunk_offsets = match child(stbl, b"co64") {
+        Some(_) => table(b"co64", 8).into_iter().map(|e| be_u64(e, 0).unwrap()).collect(),
+        None => table(b"stco", 4).into_iter().map(|e| be_u32(e, 0).unwrap() as u64).collect(),
+    };
+    if let Some(stsz) = child(stbl, b"stsz") {
+        let uniform = be_u32(stsz, 4)?;
+        let count = be_u32(stsz, 8)? as usize;
+        track.sizes = if uniform != 0 {
+            vec![uniform; count]
+        } else {
+            stsz.get(12..)?.chunks_exact(4).take(count).map(|b| u32::from_be_bytes(b.try_into().unwrap())).collect()
+        };
+    }
+    Some(track)
+}
+
+/// 每个样本在文件中的偏移
+fn sample_offsets(track: &Track) -> Vec<u64> {
+    let mut offsets = Vec::with_capacity(track.sizes.len());
+    let mut sample = 0usize;
+    for (chunk_idx, &chunk_offset) in track.chunk_offsets.iter().enumerate() {
+        let chunk = chunk_idx as u32 + 1;
+        let per_chunk = track
+            .chunk_runs
+            .iter()
+            .rev()
+            .find(|(first, _)| *first <= chunk)
+            .map(|(_, n)| *n)
+            .unwrap_or(0);
+        let mut offset = chunk_offset;
+        for _ in 0..per_chunk {
+            let Some(&size) = track.sizes.get(sample) else { return offsets };
+            offsets.push(offset);
+            offset += size as u64;
+            sample += 1;
+        }
+    }
+    offsets
+}
+
+/// 读取文本样本：2 字节长度 + UTF-8（或带 BOM 的 UTF-16）
+fn read_title(file: &mut File, offset: u64, size: u32) -> Option<String> {
+    let mut data = vec![0u8; size.min(MAX_TITLE_SIZE) as usize];
+    file.seek(SeekFrom::Start(offset)).ok()?;
+    file.read_exact(&mut data).ok()?;
+    let len = u16::from_be_bytes(data.get(0..2)?.try_into().ok()?) as usize;
+    let text = data.get(2..2 + len.min(data.len() - 2))?;
+    let title = if text.starts_with(&[0xFE, 0xFF]) {
+        let units: Vec<u16> = text[2..].chunks_exact(2).map(|b| u16::from_be_bytes([b[0], b[1]])).collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        String::from_utf8_lossy(text).into_owned()
+    };
+    Some(title.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+/// QuickTime 章节轨：音轨通过 tref/chap 引用的文本轨，每个样本是一章的标题
+/// 没有被引用的文本轨可能是字幕或歌词，不当作章节
+fn quicktime_chapters(file: &mut File, moov: &[u8]) -> Option<Vec<Chapter>> {
+    let tracks: Vec<Track> = boxes(moov).into_iter().filter(|(k, _)| k == b"trak").filter_map(|(_, trak)| parse_track(trak)).collect();
+    let referenced: Vec<u32> = tracks.iter().flat_map(|t| t.chapter_refs.iter().copied()).collect();
+    let track = tracks.iter().find(|t| referenced.contains(&t.id) && &t.handler == b"text")?;
+    if track.timescale == 0 {
+        return None;
+    }
+    let offsets = sample_offsets(track);
+    let mut chapters = Vec::with_capacity(offsets.len());
+    let mut time = 0u64;
+    let mut sample = 0usize;
+    for &(count, delta) in &track.durations {
+        for _ in 0..count {
+            let (Some(&offset), Some(&size)) = (offsets.get(sample), track.sizes.get(sample)) else { return Some(chapters) };
+            chapters.push(Chapter {
+                title: read_title(file, offset, size),
+                start_ms: time * 1000 / track.timescale as u64,
+                end_ms: None,
+            });
+            time += delta as u64;
+            sample += 1;
+        }
+    }
+    Some(chapters)
+}
+
+/// MP4 章节：优先使用 Nero 章节，没有时读取 QuickTime 章节轨
+fn read_mp4(path: &Path) -> anyhow::Result<Vec<Chapter>> {
+    let mut file = File::open(path)?;
+    let moov = read_moov(&mut file)?;
+    if let Some(chapters) = nero_chapters(&moov).filter(|c| !c.is_empty()) {
+        return Ok(chapters);
+    }
+    Ok(quicktime_chapters(&mut file, &moov).unwrap_or_default())
+}
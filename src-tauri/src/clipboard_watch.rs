@@ -0,0 +1,174 @@
+use crate::player_fixed::{PlayerEvent, SongInfo};
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+/// 剪贴板检查间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 一次最多识别的行数，避免复制大段文本时逐行检查文件
+const MAX_LINES: usize = 200;
+
+/// 可识别为串流的URL扩展名
+const STREAM_EXTENSIONS: &[&str] = &["mp3", "aac", "m4a", "ogg", "opus", "flac", "wav", "m3u", "m3u8", "pls"];
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// 剪贴板监听设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClipboardWatchOptions {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// 剪贴板中识别到的媒体
+#[derive(Debug, Clone, Serialize)]
+pub enum ClipboardMedia {
+    File(String), // 本地音频文件路径
+    Url(String),  // 串流URL
+}
+
+fn options_path() -> PathBuf {
+    storage::data_dir().join("clipboard_watch.json")
+}
+
+fn load_options() -> ClipboardWatchOptions {
+    storage::load_json(&options_path())
+        .unwrap_or_else(|e| {
+            eprintln!("读取剪贴板监听设置失败: {}", e);
+            None
+        })
+        .unwrap_or_default()
+}
+
+/// 是否正在监听剪贴板
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// 开启/关闭剪贴板监听并保存设置
+pub fn set_enabled(enabled: bool) -> anyhow::Result<()> {
+    ENABLED.store(enabled, Ordering::SeqCst);
+    storage::save_json(&options_path(), &ClipboardWatchOptions { enabled })
+}
+
+/// 启动剪贴板监听任务，未开启监听时只做空转
+/// 开启监听时剪贴板里已有的内容不会触发提示，只识别之后新复制的内容
+pub fn start<R: Runtime>(app_handle: AppHandle<R>) {
+    ENABLED.store(load_options().enabled, Ordering::SeqCst);
+    tauri::async_runtime::spawn(async move {
+        let mut last_text: Option<String> = None;
+        let mut was_enabled = false;
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            if !is_enabled() {
+                was_enabled = false;
+                continue;
+            }
+            let text = match app_handle.clipboard().read_text() {
+                Ok(text) => text,
+                Err(_) => continue, // 剪贴板为空或不是文本
+            };
+            if !was_enabled {
+                // 刚开启监听：记录当前内容作为基准
+                was_enabled = true;
+                last_text = Some(text);
+                continue;
+            }
+            if last_text.as_deref() == Some(text.as_str()) {
+                continue;
+            }
+            let media = detect(&text);
+            last_text = Some(text);
+            if !media.is_empty() {
+                if let Err(e) = app_handle.emit("player-event", PlayerEvent::ClipboardMediaDetected(media)) {
+                    eprintln!("发送剪贴板媒体事件失败: {:?}", e);
+                }
+            }
+        }
+    });
+}
+
+/// 读取当前剪贴板内容中的媒体
+pub fn read_current<R: Runtime>(app_handle: &AppHandle<R>) -> Vec<ClipboardMedia> {
+    app_handle
+        .clipboard()
+        .read_text()
+        .map(|text| detect(&text))
+        .unwrap_or_default()
+}
+
+/// 从文本中识别音频文件路径和串流URL（每行一个）
+pub fn detect(text: &str) -> Vec<ClipboardMedia> {
+    let mut result = Vec::new();
+    for line in text.lines().take(MAX_LINES) {
+        let item = line.trim().trim_matches(|c| c == '"' || c == '\'');
+        if item.is_empty() {
+            continue;
+        }
+        let lower = item.to_lowercase();
+        if lower.starts_with("http://") || lower.starts_with("https://") {
+            if !item.contains(char::is_whitespace) && looks_like_stream(&lower) {
+                result.push(ClipboardMedia::Url(item.to_string()));
+            }
+            continue;
+        }
+        let path = match item.strip_prefix("file://") {
+            Some(rest) => file_url_path(rest),
+            None => item.to_string(),
+        };
+        let ext = Path::new(&path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+        if SongInfo::is_audio_format(&ext) && Path::new(&path).is_file() {
+            result.push(ClipboardMedia::File(path));
+        }
+    }
+    result
+}
+
+/// 按扩展名或常见串流路径判断URL是否为音频串流
+fn looks_like_stream(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let ext = path.rsplit('/').next().and_then(|name| name.rsplit_once('.')).map(|(_, ext)| ext);
+    if ext.map(|e| STREAM_EXTENSIONS.contains(&e)).unwrap_or(false) {
+        return true;
+    }
+    ["/stream", "/listen", "/live", ";stream"].iter().any(|p| path.contains(p))
+}
+
+/// 解码 URL 中的百分号编码
+pub fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// 解码 file:// URL 中的路径
+fn file_url_path(input: &str) -> String {
+    let decoded = percent_decode(input);
+    // Windows 下 file:///C:/... 解码后去掉开头的斜杠
+    match decoded.strip_prefix('/') {
+        Some(rest) if rest.chars().nth(1) == Some(':') => rest.to_string(),
+        _ => decoded,
+    }
+}
@@ -0,0 +1,187 @@
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+/// GB2312 一级汉字按拼音排列，各声母第一个汉字的编码（A B C D E F G H J K L M N O P Q R S T W X Y Z）
+const PINYIN_BOUNDARIES: [(u16, char); 23] = [
+    (0xB0A1, 'a'),
+    (0xB0C5, 'b'),
+    (0xB2C1, 'c'),
+    (0xB4EE, 'd'),
+    (0xB6EA, 'e'),
+    (0xB7A2, 'f'),
+    (0xB8C1, 'g'),
+    (0xB9FE, 'h'),
+    (0xBBF7, 'j'),
+    (0xBFA6, 'k'),
+    (0xC0AC, 'l'),
+    (0xC2E8, 'm'),
+    (0xC4C3, 'n'),
+    (0xC5B6, 'o'),
+    (0xC5BE, 'p'),
+    (0xC6DA, 'q'),
+    (0xC8BB, 'r'),
+    (0xC8F6, 's'),
+    (0xCBFA, 't'),
+    (0xCDDA, 'w'),
+    (0xCEF4, 'x'),
+    (0xD1B9, 'y'),
+    (0xD4D1, 'z'),
+];
+
+/// GB2312 一级汉字的最后一个编码，二级汉字按部首排列，无法取得拼音
+const PINYIN_END: u16 = 0xD7F9;
+
+/// 数字串补齐到的位数，使 "Track 2" 排在 "Track 10" 之前
+const NATURAL_DIGITS: usize = 10;
+
+/// 音乐库排序规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollationSettings {
+    /// 汉字按拼音首字母排序，与同首字母的拉丁字母排在一起（如"周杰伦"排在 Z 中）
+    #[serde(default = "default_true")]
+    pub pinyin: bool,
+    /// 忽略开头的冠词（"The Beatles" 按 "Beatles" 排序）
+    #[serde(rename = "ignoreArticles", default = "default_true")]
+    pub ignore_articles: bool,
+    /// 要忽略的冠词，不区分大小写
+    #[serde(default = "default_articles")]
+    pub articles: Vec<String>,
+    /// 自然排序：连续数字按数值比较
+    #[serde(default = "default_true")]
+    pub natural: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_articles() -> Vec<String> {
+    ["the", "a", "an"].iter().map(|s| s.to_string()).collect()
+}
+
+impl Default for CollationSettings {
+    fn default() -> Self {
+        Self {
+            pinyin: true,
+            ignore_articles: true,
+            articles: default_articles(),
+            natural: true,
+        }
+    }
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("collation.json")
+}
+
+fn settings_lock() -> &'static RwLock<CollationSettings> {
+    static SETTINGS: OnceLock<RwLock<CollationSettings>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let loaded = storage::load_json(&settings_path()).unwrap_or_else(|e| {
+            eprintln!("读取排序规则设置失败: {}", e);
+            None
+        });
+        RwLock::new(loaded.unwrap_or_default())
+    })
+}
+
+/// 获取排序规则设置
+pub fn settings() -> CollationSettings {
+    settings_lock().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// 保存排序规则设置（调用方需要重建音乐库的排序键）
+pub fn set_settings(settings: CollationSettings) -> anyhow::Result<()> {
+    storage::save_json(&settings_path(), &settings)?;
+    *settings_lock().write().map_err(|_| anyhow::anyhow!("无法锁定排序规则设置"))? = settings;
+    Ok(())
+}
+
+/// 汉字的拼音首字母（仅 GB2312 一级常用字）
+fn pinyin_initial(c: char) -> Option<char> {
+    let mut buf = [0u8; 4];
+    let (encoded, _, unmappable) = encoding_rs::GBK.encode(c.encode_utf8(&mut buf));
+    if unmappable || encoded.len() != 2 {
+        return None;
+    }
+    let code = u16::from_be_bytes([encoded[0], encoded[1]]);
+    if !(PINYIN_BOUNDARIES[0].0..=PINYIN_END).contains(&code) {
+        return None;
+    }
+    PINYIN_BOUNDARIES.iter().rev().find(|(start, _)| code >= *start).map(|(_, initial)| *initial)
+}
+
+/// 去掉常见拉丁字母的重音符号（é → e），使其与不带重音的字母排在一起
+fn fold_accent(c: char) -> char {
+    match c {
+        'à'..='å' | 'ā' => 'a',
+        'ç' | 'č' => 'c',
+        'è'..='ë' | 'ē' | 'ě' => 'e',
+        'ì'..='ï' | 'ī' => 'i',
+        'ñ' | 'ń' => 'n',
+        'ò'..='ö' | 'ø' | 'ō' => 'o',
+        'š' | 'ś' => 's',
+        'ù'..='ü' | 'ū' => 'u',
+        'ý' | 'ÿ' => 'y',
+        'ž' | 'ź' | 'ż' => 'z',
+        c => c,
+    }
+}
+
+/// 规范化文本用于比较：小写并去掉拉丁字母的重音（搜索时不区分大小写和重音）
+pub fn normalize(text: &str) -> String {
+    text.to_lowercase().chars().map(fold_accent).collect()
+}
+
+/// 按当前排序规则生成排序键，数据库中按排序键的二进制顺序排序即可得到期望的顺序
+pub fn sort_key(text: &str) -> String {
+    sort_key_with(text, &settings())
+}
+
+/// 按给定排序规则生成排序键
+pub fn sort_key_with(text: &str, settings: &CollationSettings) -> String {
+    let mut text = text.trim().to_lowercase();
+    if settings.ignore_articles {
+        for article in &settings.articles {
+            let article = article.to_lowercase();
+            let stripped = text
+                .strip_prefix(&article)
+                .filter(|rest| rest.starts_with(' '))
+                .map(|rest| rest.trim_start().to_string());
+            if let Some(rest) = stripped.filter(|rest| !rest.is_empty()) {
+                text = rest;
+                break;
+            }
+        }
+    }
+
+    let mut key = String::with_capacity(text.len() * 2);
+    let mut digits = String::new();
+    let flush_digits = |digits: &mut String, key: &mut String| {
+        if !digits.is_empty() {
+            let trimmed = digits.trim_start_matches('0');
+            key.push_str(&"0".repeat(NATURAL_DIGITS.saturating_sub(trimmed.len())));
+            key.push_str(trimmed);
+            digits.clear();
+        }
+    };
+    for c in text.chars() {
+        if settings.natural && c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        flush_digits(&mut digits, &mut key);
+        match pinyin_initial(c).filter(|_| settings.pinyin) {
+            // 首字母在前，同首字母的汉字之间仍按原字排序
+            Some(initial) => {
+                key.push(initial);
+                key.push(c);
+            }
+            None => key.push(fold_accent(c)),
+        }
+    }
+    flush_digits(&mut digits, &mut key);
+    key
+}
@@ -0,0 +1,39 @@
+use crate::cache::{self, CacheKind};
+use base64::Engine;
+use image::ImageFormat;
+use std::io::Cursor;
+
+/// 缩放后封面的最大边长
+const COVER_SIZE: u32 = 300;
+
+/// 把内嵌封面图片缩放并转为 JPEG，结果按图片内容的哈希缓存在磁盘上：
+/// 同一文件再次添加、同一专辑的曲目共用同一张封面时都不必重新解码和缩放
+pub fn resized_jpeg(image_data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let key = blake3::hash(image_data).to_hex();
+    if let Some(path) = cache::lookup(CacheKind::Artwork, key.as_str()) {
+        match std::fs::read(&path) {
+            Ok(bytes) => return Ok(bytes),
+            Err(e) => eprintln!("读取封面缓存失败 {}: {}", path.display(), e),
+        }
+    }
+
+    let img = image::load_from_memory(image_data)?;
+    let resized = img.resize(COVER_SIZE, COVER_SIZE, image::imageops::FilterType::Lanczos3);
+    let mut jpeg = Vec::new();
+    resized.write_to(&mut Cursor::new(&mut jpeg), ImageFormat::Jpeg)?;
+    if let Err(e) = cache::put(CacheKind::Artwork, key.as_str(), &jpeg) {
+        eprintln!("写入封面缓存失败: {}", e);
+    }
+    Ok(jpeg)
+}
+
+/// 缩放后的封面编码为前端可直接显示的 data URL
+pub fn data_url(image_data: &[u8]) -> anyhow::Result<String> {
+    let jpeg = resized_jpeg(image_data)?;
+    Ok(format!("data:image/jpeg;base64,{}", base64::engine::general_purpose::STANDARD.encode(jpeg)))
+}
+
+/// 清空封面缓存，返回释放的字节数
+pub fn clear() -> anyhow::Result<u64> {
+    cache::clear(Some(CacheKind::Artwork))
+}
@@ -0,0 +1,335 @@
+use crate::cache::{self, CacheKind};
+use crate::net;
+use crate::player_fixed::SongInfo;
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::Duration;
+
+/// 单次请求的总超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// MusicBrainz 要求每秒不超过一次请求
+const MUSICBRAINZ_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 下载的封面图片最大大小
+const MAX_IMAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// 批量查找任务是否正在运行
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 在线封面来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoverProvider {
+    CoverArtArchive, // 通过 MusicBrainz 查到专辑后从 Cover Art Archive 下载
+    Itunes,          // iTunes Search API
+}
+
+/// 在线封面获取设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverFetchSettings {
+    /// 是否允许联网获取封面，默认关闭
+    #[serde(default)]
+    pub enabled: bool,
+    /// 按顺序尝试的来源
+    #[serde(default = "default_providers")]
+    pub providers: Vec<CoverProvider>,
+}
+
+fn default_providers() -> Vec<CoverProvider> {
+    vec![CoverProvider::CoverArtArchive, CoverProvider::Itunes]
+}
+
+impl Default for CoverFetchSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            providers: default_providers(),
+        }
+    }
+}
+
+/// 批量查找缺失封面的结果汇总
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CoverFetchSummary {
+    pub albums: usize,  // 查找的专辑数
+    pub found: usize,   // 找到封面的专辑数
+    pub missing: usize, // 各来源都没有封面
+    pub failed: usize,  // 请求失败
+    pub files: usize,   // 更新了封面的文件数
+}
+
+/// 批量查找过程中发送给前端的事件
+#[derive(Debug, Clone, Serialize)]
+pub enum CoverFetchEvent {
+    Progress {
+        done: usize,
+        total: usize,
+        artist: String,
+        album: String,
+        found: bool,
+    },
+    Finished(CoverFetchSummary),
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("cover_fetch.json")
+}
+
+fn settings_lock() -> &'static RwLock<CoverFetchSettings> {
+    static SETTINGS: OnceLock<RwLock<CoverFetchSettings>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let loaded = storage::load_json(&settings_path()).unwrap_or_else(|e| {
+            eprintln!("读取在线封面设置失败: {}", e);
+            None
+        });
+        RwLock::new(loaded.unwrap_or_default())
+    })
+}
+
+/// 获取在线封面设置
+pub fn settings() -> CoverFetchSettings {
+    settings_lock().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// 保存在线封面设置
+pub fn set_settings(settings: CoverFetchSettings) -> anyhow::Result<()> {
+    storage::save_json(&settings_path(), &settings)?;
+    *settings_lock().write().map_err(|_| anyhow::anyhow!("无法锁定在线封面设置"))? = settings;
+    Ok(())
+}
+
+/// 本次运行中各来源都没有封面的专辑，避免重复查询
+fn misses() -> &'static Mutex<HashSet<String>> {
+    static MISSES: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+    MISSES.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// 比较专辑名/艺术家时忽略大小写、空白和标点
+fn normalize(text: &str) -> String {
+    text.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+fn cache_key(artist: &str, album: &str) -> String {
+    blake3::hash(format!("{}\n{}", normalize(artist), normalize(album)).as_bytes()).to_hex().to_string()
+}
+
+fn user_agent() -> String {
+    format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+}
+
+/// 按艺术家和专辑获取封面图片：先查缓存，再按设置的来源顺序联网查找，找到后写入缓存
+/// 各来源都没有时返回 None；未开启在线获取时返回错误
+pub async fn fetch(artist: &str, album: &str) -> anyhow::Result<Option<Vec<u8>>> {
+    let key = cache_key(artist, album);
+    if let Some(path) = cache::lookup(CacheKind::Cover, &key) {
+        match std::fs::read(&path) {
+            Ok(bytes) => return Ok(Some(bytes)),
+            Err(e) => eprintln!("读取在线封面缓存失败 {}: {}", path.display(), e),
+        }
+    }
+    let settings = settings();
+    if !settings.enabled {
+        return Err(anyhow::anyhow!("在线获取封面未开启"));
+    }
+    if misses().lock().map(|m| m.contains(&key)).unwrap_or(false) {
+        return Ok(None);
+    }
+
+    let mut last_error = None;
+    for provider in &settings.providers {
+        let result = match provider {
+            CoverProvider::CoverArtArchive => cover_art_archive(artist, album).await,
+            CoverProvider::Itunes => itunes(artist, album).await,
+        };
+        match result {
+            Ok(Some(bytes)) => {
+                if let Err(e) = cache::put(CacheKind::Cover, &key, &bytes) {
+                    eprintln!("写入在线封面缓存失败: {}", e);
+                }
+                println!("🖼️ 已在线获取封面: {} - {} ({:?})", artist, album, provider);
+                return Ok(Some(bytes));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("从 {:?} 获取封面失败 {} - {}: {}", provider, artist, album, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    match last_error {
+        Some(e) => Err(e),
+        None => {
+            if let Ok(mut misses) = misses().lock() {
+                misses.insert(key);
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// 下载图片，404 视为没有封面
+async fn download_image(url: &str) -> anyhow::Result<Option<Vec<u8>>> {
+    let response = match net::send(net::RetryPolicy::default(), |client| {
+        client.get(url).header(reqwest::header::USER_AGENT, user_agent()).timeout(REQUEST_TIMEOUT)
+    })
+    .await
+    {
+        Ok((response, _)) => response,
+        Err(e) if e.status == Some(404) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let bytes = response.bytes().await?;
+    if bytes.len() > MAX_IMAGE_SIZE {
+        return Err(anyhow::anyhow!("封面图片过大: {} 字节", bytes.len()));
+    }
+    image::guess_format(&bytes).map_err(|_| anyhow::anyhow!("下载的内容不是图片: {}", url))?;
+    Ok(Some(bytes.to_vec()))
+}
+
+async fn get_json<T: serde::de::DeserializeOwned>(url: &str, query: &[(&str, &str)]) -> anyhow::Result<T> {
+    let (response, _) = net::send(net::RetryPolicy::default(), |client| {
+        client
+            .get(url)
+            .query(query)
+            .header(reqwest::header::USER_AGENT, user_agent())
+            .timeout(REQUEST_TIMEOUT)
+    })
+    .await?;
+    Ok(serde_json::from_slice(&response.bytes().await?)?)
+}
+
+#[derive(Deserialize)]
+struct ReleaseGroupSearch {
+    #[serde(rename = "release-groups", default)]
+    release_groups: Vec<ReleaseGroup>,
+}
+
+#[derive(Deserialize)]
+struct ReleaseGroup {
+    id: String,
+    #[serde(default)]
+    score: u32,
+}
+
+/// 在 MusicBrainz 查找专辑（release group），再从 Cover Art Archive 下载正面封面
+async fn cover_art_archive(artist: &str, album: &str) -> anyhow::Result<Option<Vec<u8>>> {
+    net::set_min_interval("musicbrainz.org", MUSICBRAINZ_INTERVAL);
+    let quote = |text: &str| text.replace(['"', '\\'], " ");
+    let query = format!("releasegroup:\"{}\" AND artist:\"{}\"", quote(album), quote(artist));
+    let search: ReleaseGroupSearch = get_json(
+        "https://musicbrainz.org/ws/2/release-group/",
+        &[("query", query.as_str()), ("fmt", "json"), ("limit", "3")],
+    )
+    .await?;
+    // 相关度过低的结果多半是别的专辑
+    for group in search.release_groups.iter().filter(|g| g.score >= 90) {
+        let url = format!("https://coverartarchive.org/release-group/{}/front-500", group.id);
+        if let Some(bytes) = download_image(&url).await? {
+            return Ok(Some(bytes));
+        }
+    }
+    Ok(None)
+}
+
+#[derive(Deserialize)]
+struct ItunesSearch {
+    #[serde(default)]
+    results: Vec<ItunesAlbum>,
+}
+
+#[derive(Deserialize)]
+struct ItunesAlbum {
+    #[serde(rename = "artistName", default)]
+    artist_name: String,
+    #[serde(rename = "collectionName", default)]
+    collection_name: String,
+    #[serde(rename = "artworkUrl100")]
+    artwork_url: Option<String>,
+}
+
+/// 用 iTunes Search API 按“艺术家 专辑”搜索，只接受专辑名和艺术家都对得上的结果
+async fn itunes(artist: &str, album: &str) -> anyhow::Result<Option<Vec<u8>>> {
+    let term = format!("{} {}", artist, album);
+    let search: ItunesSearch = get_json(
+        "https://itunes.apple.com/search",
+        &[("term", term.as_str()), ("entity", "album"), ("limit", "10")],
+    )
+    .await?;
+    let (artist, album) = (normalize(artist), normalize(album));
+    let matched = search.results.iter().find(|r| {
+        let name = normalize(&r.collection_name);
+        (name == album || name.starts_with(&album)) && normalize(&r.artist_name).contains(&artist)
+    });
+    let Some(url) = matched.and_then(|r| r.artwork_url.as_deref()) else {
+        return Ok(None);
+    };
+    // 缩略图地址换成大图
+    download_image(&url.replace("100x100bb", "600x600bb")).await
+}
+
+/// 查找播放列表中没有封面的曲目的在线封面，同一专辑只查一次；每找到一张调用 apply(路径列表, 图片)
+pub async fn find_missing(
+    playlist: &[SongInfo],
+    mut apply: impl FnMut(Vec<String>, Vec<u8>),
+    mut emit: impl FnMut(CoverFetchEvent),
+) -> anyhow::Result<CoverFetchSummary> {
+    if !settings().enabled {
+        return Err(anyhow::anyhow!("在线获取封面未开启"));
+    }
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(anyhow::anyhow!("正在查找缺失的封面"));
+    }
+
+    let mut albums: Vec<(String, String)> = Vec::new();
+    let mut paths: HashMap<(String, String), Vec<String>> = HashMap::new();
+    for song in playlist.iter().filter(|s| !s.has_embedded_cover() && !s.is_placeholder()) {
+        let (Some(artist), Some(album)) = (song.artist.clone(), song.album.clone()) else { continue };
+        let entry = paths.entry((artist.clone(), album.clone())).or_insert_with(|| {
+            albums.push((artist, album));
+            Vec::new()
+        });
+        if !entry.contains(&song.path) {
+            entry.push(song.path.clone());
+        }
+    }
+
+    let mut summary = CoverFetchSummary {
+        albums: albums.len(),
+        ..Default::default()
+    };
+    let total = albums.len();
+    for (done, (artist, album)) in albums.into_iter().enumerate() {
+        let found = match fetch(&artist, &album).await {
+            Ok(Some(image)) => {
+                let album_paths = paths.remove(&(artist.clone(), album.clone())).unwrap_or_default();
+                summary.found += 1;
+                summary.files += album_paths.len();
+                apply(album_paths, image);
+                true
+            }
+            Ok(None) => {
+                summary.missing += 1;
+                false
+            }
+            Err(_) => {
+                summary.failed += 1;
+                false
+            }
+        };
+        emit(CoverFetchEvent::Progress {
+            done: done + 1,
+            total,
+            artist,
+            album,
+            found,
+        });
+    }
+    RUNNING.store(false, Ordering::SeqCst);
+    emit(CoverFetchEvent::Finished(summary.clone()));
+    Ok(summary)
+}
@@ -0,0 +1,206 @@
+use crate::matroska::MediaSegment;
+use crate::player_fixed::{FileTags, SongInfo};
+use lofty::{ItemKey, TaggedFileExt};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// CUE 中的一个曲目
+#[derive(Debug, Clone, Default)]
+struct CueTrack {
+    file: String,
+    number: u32,
+    title: Option<String>,
+    performer: Option<String>,
+    start_ms: u64,
+}
+
+/// 解析后的 CUE 表
+#[derive(Debug, Clone, Default)]
+struct CueSheet {
+    title: Option<String>,
+    performer: Option<String>,
+    tracks: Vec<CueTrack>,
+}
+
+/// 是否为 CUE 文件
+pub fn is_cue_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("cue"))
+        .unwrap_or(false)
+}
+
+/// 取出命令参数，去掉两侧引号
+fn argument(rest: &str) -> String {
+    let rest = rest.trim();
+    match rest.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next().unwrap_or("").to_string(),
+        None => rest.split_whitespace().next().unwrap_or("").to_string(),
+    }
+}
+
+/// CUE 时间格式为 分:秒:帧（每秒75帧）
+fn parse_time(value: &str) -> Option<u64> {
+    let mut parts = value.trim().split(':').map(|p| p.parse::<u64>().ok());
+    let (minutes, seconds, frames) = (parts.next()??, parts.next()??, parts.next()??);
+    Some((minutes * 60 + seconds) * 1000 + frames * 1000 / 75)
+}
+
+fn parse(text: &str) -> CueSheet {
+    let mut sheet = CueSheet::default();
+    let mut file = String::new();
+    let mut track: Option<CueTrack> = None;
+    for line in text.lines() {
+        let line = line.trim().trim_start_matches('\u{feff}');
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        match command.to_ascii_uppercase().as_str() {
+            "FILE" => {
+                // FILE 后跟文件类型（WAVE/MP3...），只取文件名
+                file = argument(rest);
+            }
+            "TRACK" => {
+                sheet.tracks.extend(track.take().filter(|_| !file.is_empty()));
+                track = Some(CueTrack {
+                    file: file.clone(),
+                    number: argument(rest).parse().unwrap_or(sheet.tracks.len() as u32 + 1),
+                    start_ms: u64::MAX,
+                    ..Default::default()
+                });
+            }
+            "TITLE" => match track.as_mut() {
+                Some(t) => t.title = Some(argument(rest)),
+                None => sheet.title = Some(argument(rest)),
+            },
+            "PERFORMER" => match track.as_mut() {
+                Some(t) => t.performer = Some(argument(rest)),
+                None => sheet.performer = Some(argument(rest)),
+            },
+            "INDEX" => {
+                // INDEX 01 为曲目起点；INDEX 00（前置间隙）只在没有 01 时使用
+                let mut parts = rest.split_whitespace();
+                let (index, time) = (parts.next().unwrap_or(""), parts.next().and_then(parse_time));
+                if let (Some(t), Some(time)) = (track.as_mut(), time) {
+                    if index == "01" || (index == "00" && t.start_ms == u64::MAX) {
+                        t.start_ms = time;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    sheet.tracks.extend(track.filter(|_| !file.is_empty()));
+    sheet.tracks.retain(|t| t.start_ms != u64::MAX);
+    sheet
+}
+
+/// 把 CUE 表展开为虚拟曲目：每个曲目指向所在的音频文件并带有起止位置
+/// 同一文件中下一首的起点即本曲终点，文件中的最后一首播放到文件结束
+fn to_songs(sheet: &CueSheet, base_dir: &Path, mut base_song: impl FnMut(&Path) -> Option<SongInfo>) -> Vec<SongInfo> {
+    let mut bases: HashMap<String, Option<SongInfo>> = HashMap::new();
+    let mut songs = Vec::new();
+    for (idx, track) in sheet.tracks.iter().enumerate() {
+        let file: PathBuf = base_dir.join(&track.file);
+        let base = bases
+            .entry(track.file.clone())
+            .or_insert_with(|| base_song(&file))
+            .clone();
+        let base = match base {
+            Some(base) => base,
+            None => continue,
+        };
+        let end_ms = sheet.tracks.get(idx + 1).filter(|next| next.file == track.file).map(|next| next.start_ms);
+        let duration = match end_ms {
+            Some(end) => Some(end.saturating_sub(track.start_ms) / 1000),
+            None => base.duration.map(|d| d.saturating_sub(track.start_ms / 1000)),
+        };
+        songs.push(SongInfo {
+            title: track.title.clone().or_else(|| Some(format!("曲目{}", track.number))),
+            artist: track.performer.clone().or_else(|| sheet.performer.clone()).or_else(|| base.artist.clone()),
+            album: sheet.title.clone().or_else(|| base.album.clone()),
+            duration,
+            track_number: Some(track.number),
+            gapless: None,
+            chapters: Vec::new(),
+            video_info: None,
+            trim: None,
+            segment: Some(MediaSegment {
+                track_id: None,
+                start_ms: track.start_ms,
+                end_ms,
+            }),
+            ..base
+        });
+    }
+    songs
+}
+
+/// 读取 CUE 文件，展开为其中的虚拟曲目（非 UTF-8 编码的文件按有损方式读取）
+pub fn expand(path: &Path) -> anyhow::Result<Vec<SongInfo>> {
+    let bytes = std::fs::read(path)?;
+    let sheet = parse(&String::from_utf8_lossy(&bytes));
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    let songs = to_songs(&sheet, base_dir, |file| {
+        SongInfo::from_path(file)
+            .map_err(|e| eprintln!("CUE 引用的文件无法读取 {}: {}", file.display(), e))
+            .ok()
+    });
+    if songs.is_empty() {
+        return Err(anyhow::anyhow!("CUE 中没有可播放的曲目: {}", path.display()));
+    }
+    Ok(songs)
+}
+
+/// 音频文件内嵌的 CUE 表（FLAC/APE 等的 CUESHEET 标签，取自创建 song 时读入的标签），展开为虚拟曲目；没有或只有一首时返回 None
+pub fn expand_embedded(song: &SongInfo, tags: &FileTags) -> Option<Vec<SongInfo>> {
+    let path = Path::new(&song.path);
+    let text = tags
+        .tagged_file
+        .as_ref()?
+        .tags()
+        .iter()
+        .find_map(|tag| tag.get_string(&ItemKey::Unknown("CUESHEET".to_string())).map(str::to_string))?;
+    let mut sheet = parse(&text);
+    // 内嵌 CUE 的 FILE 指向的就是文件本身，文件名可能已被改过
+    for track in &mut sheet.tracks {
+        track.file = String::new();
+    }
+    let songs = to_songs(&sheet, path, |_| Some(song.clone()));
+    (songs.len() > 1).then_some(songs)
+}
+
+/// 整轨文件旁的 CUE 表：优先找同名的 .cue（album.cue 或 album.flac.cue），
+/// 否则找同目录下 FILE 指向这个文件的 CUE；展开为虚拟曲目，没有或只有一首时返回 None
+pub fn expand_sidecar(song: &SongInfo) -> Option<Vec<SongInfo>> {
+    let path = Path::new(&song.path);
+    let dir = path.parent()?;
+    let file_name = path.file_name()?.to_string_lossy().into_owned();
+    let references = |track: &CueTrack| {
+        Path::new(&track.file)
+            .file_name()
+            .map(|name| name.to_string_lossy().eq_ignore_ascii_case(&file_name))
+            .unwrap_or(false)
+    };
+    let read_sheet = |cue_path: &Path| std::fs::read(cue_path).ok().map(|bytes| parse(&String::from_utf8_lossy(&bytes)));
+
+    let named = [path.with_extension("cue"), dir.join(format!("{}.cue", file_name))];
+    let mut sheet = named.iter().filter(|p| p.is_file()).find_map(|p| read_sheet(p)).or_else(|| {
+        std::fs::read_dir(dir)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|p| is_cue_path(p))
+            .filter_map(|p| read_sheet(&p))
+            .find(|sheet| sheet.tracks.iter().any(references))
+    })?;
+    // 只引用一个文件的 CUE 整个使用（转换格式后扩展名可能已经变了，如 WAV 压成 FLAC），
+    // 引用多个文件时只保留这个文件中的曲目
+    let single_file = sheet.tracks.windows(2).all(|w| w[0].file == w[1].file);
+    if !single_file {
+        sheet.tracks.retain(|t| references(t));
+    }
+    for track in &mut sheet.tracks {
+        track.file = String::new();
+    }
+    let songs = to_songs(&sheet, path, |_| Some(song.clone()));
+    (songs.len() > 1).then_some(songs)
+}
@@ -0,0 +1,154 @@
+use crate::clipboard_watch::percent_decode;
+use crate::hotkeys::HotkeyAction;
+use crate::player_fixed::{PlayerCommand, SongInfo};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime, Url};
+
+/// 注册的链接协议，如 musicplayer://play?path=...、musicplayer://playlist/<名称>
+pub const SCHEME: &str = "musicplayer";
+
+const MAIN_WINDOW: &str = "main";
+
+/// 链接对应的操作
+#[derive(Debug)]
+pub enum DeepLinkAction {
+    /// musicplayer:// 或 musicplayer://open：显示主窗口
+    Show,
+    /// musicplayer://play?path=...：插入到当前歌曲之后立即播放（本地文件或 http(s) 串流）
+    PlayNow(String),
+    /// musicplayer://enqueue?path=...：按添加策略加入播放列表
+    Enqueue(String),
+    /// musicplayer://playlist/<名称>：载入已保存的播放列表
+    Playlist(String),
+    /// musicplayer://play、pause、stop、next、previous
+    Command(PlayerCommand),
+    /// musicplayer://toggle 等与快捷键相同的操作
+    Hotkey(HotkeyAction),
+}
+
+/// 播放器就绪前收到的链接（冷启动时由链接打开应用）
+struct Pending {
+    ready: bool,
+    urls: Vec<Url>,
+}
+
+static PENDING: Mutex<Pending> = Mutex::new(Pending {
+    ready: false,
+    urls: Vec::new(),
+});
+
+/// 播放器就绪前先把链接排队；已就绪时原样返回，由调用方立即执行
+pub fn queue_until_ready(url: Url) -> Option<Url> {
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    if pending.ready {
+        return Some(url);
+    }
+    // 启动链接可能既由 get_current 取到，又通过打开链接事件送达
+    if !pending.urls.contains(&url) {
+        pending.urls.push(url);
+    }
+    None
+}
+
+/// 播放器已就绪，取出排队的链接
+pub fn mark_ready() -> Vec<Url> {
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    pending.ready = true;
+    std::mem::take(&mut pending.urls)
+}
+
+/// 命令行参数是否为本应用的链接（第二个实例启动时转交给正在运行的实例）
+pub fn is_link(arg: &str) -> bool {
+    arg.get(..SCHEME.len() + 1)
+        .map(|prefix| prefix.eq_ignore_ascii_case(&format!("{}:", SCHEME)))
+        .unwrap_or(false)
+}
+
+/// 解析链接；musicplayer://play?path=... 的动作在主机名位置，musicplayer:play?path=... 则在路径中
+pub fn parse(url: &Url) -> anyhow::Result<DeepLinkAction> {
+    if !url.scheme().eq_ignore_ascii_case(SCHEME) {
+        return Err(anyhow::anyhow!("不支持的链接协议: {}", url.scheme()));
+    }
+    let mut segments: Vec<String> = url
+        .host_str()
+        .into_iter()
+        .map(percent_decode)
+        .chain(url.path_segments().into_iter().flatten().map(percent_decode))
+        .filter(|s| !s.is_empty())
+        .collect();
+    let action = if segments.is_empty() {
+        String::new()
+    } else {
+        segments.remove(0).to_lowercase()
+    };
+    let query = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    let action = match action.as_str() {
+        "" | "open" | "show" => DeepLinkAction::Show,
+        "play" => match query("path") {
+            Some(path) => DeepLinkAction::PlayNow(media_target(path)?),
+            None => DeepLinkAction::Command(PlayerCommand::Play),
+        },
+        "enqueue" | "add" => {
+            let path = query("path").ok_or_else(|| anyhow::anyhow!("链接缺少 path 参数"))?;
+            DeepLinkAction::Enqueue(media_target(path)?)
+        }
+        "playlist" => {
+            // 名称中可以含有 /，其余路径段都属于名称
+            let name = if segments.is_empty() { query("name") } else { Some(segments.join("/")) };
+            DeepLinkAction::Playlist(name.ok_or_else(|| anyhow::anyhow!("链接缺少播放列表名称"))?)
+        }
+        "pause" => DeepLinkAction::Command(PlayerCommand::Pause),
+        "stop" => DeepLinkAction::Command(PlayerCommand::Stop),
+        "next" => DeepLinkAction::Command(PlayerCommand::Next),
+        "previous" | "prev" => DeepLinkAction::Command(PlayerCommand::Previous),
+        "toggle" | "playpause" => DeepLinkAction::Hotkey(HotkeyAction::PlayPause),
+        "mute" => DeepLinkAction::Hotkey(HotkeyAction::ToggleMute),
+        other => return Err(anyhow::anyhow!("未知的链接操作: {}", other)),
+    };
+    Ok(action)
+}
+
+/// 链接中的播放目标只接受 http(s) 串流和本机上已存在的音频/视频文件，
+/// 不接受网络共享路径（\\host\share、//host/share），打开时会向外发起连接
+fn media_target(path: String) -> anyhow::Result<String> {
+    let lower = path.to_lowercase();
+    if lower.starts_with("http://") || lower.starts_with("https://") {
+        return Ok(path);
+    }
+    if lower.contains("://") {
+        return Err(anyhow::anyhow!("不支持的播放地址: {}", path));
+    }
+    if path.starts_with("\\\\") || path.starts_with("//") || path.starts_with("\\/") || path.starts_with("/\\") {
+        return Err(anyhow::anyhow!("不支持网络共享路径: {}", path));
+    }
+    let target = Path::new(&path);
+    let is_media = target
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .is_some_and(|e| SongInfo::is_audio_format(&e) || SongInfo::is_video_format(&e));
+    if !is_media {
+        return Err(anyhow::anyhow!("不支持的文件类型: {}", path));
+    }
+    // 符号链接按其指向判断，必须是普通文件（不能是目录、设备等）
+    match std::fs::metadata(target) {
+        Ok(metadata) if metadata.is_file() => Ok(path),
+        Ok(_) => Err(anyhow::anyhow!("不是文件: {}", path)),
+        Err(e) => Err(anyhow::anyhow!("无法打开文件 {}: {}", path, e)),
+    }
+}
+
+/// 显示并聚焦主窗口
+pub fn show_main_window<R: Runtime>(app: &AppHandle<R>) {
+    let Some(window) = app.get_webview_window(MAIN_WINDOW) else { return };
+    if let Err(e) = window.unminimize().and_then(|_| window.show()).and_then(|_| window.set_focus()) {
+        eprintln!("显示主窗口失败: {}", e);
+    }
+}
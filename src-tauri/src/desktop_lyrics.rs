@@ -0,0 +1,240 @@
+use crate::player_fixed::{LyricLine, LyricWord, PlayerEvent, PlayerState};
+use serde::Serialize;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindowBuilder};
+
+/// 桌面歌词窗口的标签
+const WINDOW_LABEL: &str = "desktop-lyrics";
+
+/// 窗口大小（逻辑像素）和距屏幕底部的距离
+const WINDOW_WIDTH: f64 = 900.0;
+const WINDOW_HEIGHT: f64 = 130.0;
+const BOTTOM_MARGIN: f64 = 80.0;
+
+/// 页面按本地时钟推算的位置与播放器上报的位置相差超过这么多毫秒（同一行内跳转）时重新同步
+/// 每秒一次的进度只精确到秒，所以要大于一秒
+const RESYNC_TOLERANCE_MS: u64 = 1500;
+
+/// 推送给桌面歌词窗口的当前行
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DesktopLyricsLine {
+    pub text: String,
+    pub next: Option<String>, // 下一行，显示在第二行
+    pub time: u64,            // 本行开始时间（毫秒）
+    pub words: Option<Vec<LyricWord>>,
+    #[serde(rename = "positionMs")]
+    pub position_ms: u64,
+    pub playing: bool, // 暂停时页面停止推进逐字高亮
+}
+
+/// 同一行内的位置同步：跳转、暂停、继续播放时发送
+#[derive(Debug, Clone, Serialize)]
+struct DesktopLyricsSync {
+    #[serde(rename = "positionMs")]
+    position_ms: u64,
+    playing: bool,
+}
+
+#[derive(Default)]
+struct Overlay {
+    visible: bool,
+    current_index: Option<usize>,
+    lyrics: Vec<LyricLine>,
+    shown: Option<Option<usize>>, // 已推送的行（Some(None) 为第一行之前），None 为还没有推送
+    position_ms: u64,
+    playing: bool,
+    synced: Option<(Instant, u64)>, // 最近一次推送/同步给页面的时刻和位置，页面从这里按本地时钟推算
+}
+
+impl Overlay {
+    /// 页面当前推算出的位置
+    fn page_position_ms(&self) -> Option<u64> {
+        let (at, position_ms) = self.synced?;
+        Some(match self.playing {
+            true => position_ms + at.elapsed().as_millis() as u64,
+            false => position_ms,
+        })
+    }
+}
+
+fn overlay() -> &'static Mutex<Overlay> {
+    static OVERLAY: OnceLock<Mutex<Overlay>> = OnceLock::new();
+    OVERLAY.get_or_init(|| Mutex::new(Overlay::default()))
+}
+
+/// 显示桌面歌词：置顶、无边框、透明、鼠标穿透的窗口，第一次显示时创建并放在主屏幕下方居中
+pub fn show<R: Runtime>(app: &AppHandle<R>) -> anyhow::Result<()> {
+    let window = match app.get_webview_window(WINDOW_LABEL) {
+        Some(window) => window,
+        None => {
+            let builder = WebviewWindowBuilder::new(app, WINDOW_LABEL, WebviewUrl::App("desktop-lyrics.html".into()));
+            // macOS 上透明窗口需要 macos-private-api，未开启时保留页面的半透明背景
+            #[cfg(not(target_os = "macos"))]
+            let builder = builder.transparent(true);
+            let window = builder
+                .title("桌面歌词")
+                .inner_size(WINDOW_WIDTH, WINDOW_HEIGHT)
+                .decorations(false)
+                .shadow(false)
+                .always_on_top(true)
+                .skip_taskbar(true)
+                .resizable(false)
+                .focused(false)
+                .visible(false)
+                .build()?;
+            if let Some(monitor) = window.primary_monitor()? {
+                let scale = monitor.scale_factor();
+                let size = monitor.size().to_logical::<f64>(scale);
+                let origin = monitor.position().to_logical::<f64>(scale);
+                window.set_position(tauri::LogicalPosition::new(
+                    origin.x + (size.width - WINDOW_WIDTH) / 2.0,
+                    origin.y + size.height - WINDOW_HEIGHT - BOTTOM_MARGIN,
+                ))?;
+            }
+            window
+        }
+    };
+    window.set_ignore_cursor_events(true)?;
+    window.show()?;
+    if let Ok(mut overlay) = overlay().lock() {
+        overlay.visible = true;
+        // 重新推送当前行
+        overlay.shown = None;
+        let position_ms = overlay.position_ms;
+        push(app, &mut overlay, position_ms);
+    }
+    Ok(())
+}
+
+/// 隐藏桌面歌词窗口
+pub fn hide<R: Runtime>(app: &AppHandle<R>) -> anyhow::Result<()> {
+    if let Ok(mut overlay) = overlay().lock() {
+        overlay.visible = false;
+    }
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        window.hide()?;
+    }
+    Ok(())
+}
+
+/// 跟随播放器事件更新歌词，当前行变化时推送到桌面歌词窗口
+pub fn on_player_event<R: Runtime>(app: &AppHandle<R>, event: &PlayerEvent) {
+    let Ok(mut overlay) = overlay().lock() else { return };
+    let position_ms = match event {
+        PlayerEvent::SongChanged(index, song) => {
+            overlay.current_index = Some(*index);
+            overlay.lyrics = song.lyrics.clone().unwrap_or_default();
+            overlay.shown = None;
+            0
+        }
+        // 在线获取歌词、调整偏移后更新
+        PlayerEvent::SongMetadataUpdated(index, song) if overlay.current_index == Some(*index) => {
+            overlay.lyrics = song.lyrics.clone().unwrap_or_default();
+            overlay.shown = None;
+            overlay.position_ms
+        }
+        PlayerEvent::PositionUpdate { position_ms, .. } => *position_ms,
+        PlayerEvent::ProgressUpdate { position, .. } => {
+            // 每秒一次的进度只在它跨过了当前行时才有意义，毫秒级进度开启时以后者为准
+            let position_ms = position * 1000;
+            if position_ms / 1000 == overlay.position_ms / 1000 {
+                return;
+            }
+            position_ms
+        }
+        PlayerEvent::StateChanged(PlayerState::Stopped) => {
+            overlay.shown = None;
+            overlay.synced = None;
+            overlay.position_ms = 0;
+            overlay.playing = false;
+            if overlay.visible {
+                send(app, &DesktopLyricsLine::default());
+            }
+            return;
+        }
+        // 暂停时停在页面推算到的位置（比最近一次上报的更准），继续播放时从这个位置接着推算
+        PlayerEvent::StateChanged(state) => {
+            let playing = *state == PlayerState::Playing;
+            if playing == overlay.playing {
+                return;
+            }
+            let position_ms = overlay.page_position_ms().unwrap_or(overlay.position_ms);
+            overlay.playing = playing;
+            if overlay.visible && overlay.shown.is_some() {
+                sync(app, &mut overlay, position_ms);
+            }
+            return;
+        }
+        _ => return,
+    };
+    overlay.position_ms = position_ms;
+    if overlay.visible {
+        push(app, &mut overlay, position_ms);
+    }
+}
+
+/// 找到位置所在的行，与已推送的行不同时推送；仍是同一行但页面推算的位置偏差较大（行内跳转）时重新同步
+fn push<R: Runtime>(app: &AppHandle<R>, overlay: &mut Overlay, position_ms: u64) {
+    let line = overlay.lyrics.partition_point(|l| l.time <= position_ms).checked_sub(1);
+    if overlay.shown == Some(line) {
+        let drifted = overlay
+            .page_position_ms()
+            .map(|page| page.abs_diff(position_ms) > RESYNC_TOLERANCE_MS)
+            .unwrap_or(false);
+        if drifted {
+            sync(app, overlay, position_ms);
+        }
+        return;
+    }
+    overlay.shown = Some(line);
+    overlay.synced = Some((Instant::now(), position_ms));
+    let playing = overlay.playing;
+    let payload = match line {
+        Some(i) => {
+            let current = &overlay.lyrics[i];
+            DesktopLyricsLine {
+                text: current.text.clone(),
+                next: overlay.lyrics.get(i + 1).map(|l| l.text.clone()),
+                time: current.time,
+                words: current.words.clone(),
+                position_ms,
+                playing,
+            }
+        }
+        // 第一行之前显示第一行作为预告
+        None => DesktopLyricsLine {
+            next: overlay.lyrics.first().map(|l| l.text.clone()),
+            position_ms,
+            playing,
+            ..Default::default()
+        },
+    };
+    send(app, &payload);
+}
+
+/// 不换行，只同步页面的位置和播放状态
+fn sync<R: Runtime>(app: &AppHandle<R>, overlay: &mut Overlay, position_ms: u64) {
+    overlay.synced = Some((Instant::now(), position_ms));
+    let payload = DesktopLyricsSync {
+        position_ms,
+        playing: overlay.playing,
+    };
+    eval(app, "syncLyric", &payload);
+}
+
+fn send<R: Runtime>(app: &AppHandle<R>, payload: &DesktopLyricsLine) {
+    eval(app, "showLyric", payload);
+}
+
+/// 窗口页面提供 window.showLyric / window.syncLyric，直接调用，不需要页面订阅事件
+fn eval<R: Runtime>(app: &AppHandle<R>, function: &str, payload: &impl Serialize) {
+    let Some(window) = app.get_webview_window(WINDOW_LABEL) else { return };
+    let script = match serde_json::to_string(payload) {
+        Ok(json) => format!("window.{0} && window.{0}({1})", function, json),
+        Err(_) => return,
+    };
+    if let Err(e) = window.eval(&script) {
+        eprintln!("更新桌面歌词失败: {}", e);
+    }
+}
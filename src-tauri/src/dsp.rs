@@ -0,0 +1,405 @@
+use crate::radio_host;
+use crate::storage;
+use rodio::Source;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// 限幅器释放时间（秒）
+const LIMITER_RELEASE_SECS: f32 = 0.1;
+
+/// 交叉馈送低通截止频率（Hz）
+const CROSSFEED_CUTOFF_HZ: f32 = 700.0;
+
+/// 均衡器频段类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EqBandKind {
+    Peaking,
+    LowShelf,
+    HighShelf,
+}
+
+/// 均衡器频段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EqBand {
+    pub kind: EqBandKind,
+    pub frequency: f32, // Hz
+    #[serde(rename = "gainDb")]
+    pub gain_db: f32,
+    #[serde(default = "default_q")]
+    pub q: f32,
+}
+
+fn default_q() -> f32 {
+    0.707
+}
+
+/// DSP 处理链配置：前级增益 → 均衡器 → 交叉馈送 → 限幅器
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DspConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(rename = "preampDb", default)]
+    pub preamp_db: f32,
+    #[serde(default)]
+    pub eq: Vec<EqBand>,
+    #[serde(default)]
+    pub crossfeed: f32, // 0-1，耳机交叉馈送强度，0为关闭
+    #[serde(default)]
+    pub limiter: bool,
+    #[serde(rename = "limiterThresholdDb", default)]
+    pub limiter_threshold_db: f32, // 限幅阈值（dBFS，≤0）
+}
+
+/// 当前生效的DSP配置，播放中的音源通过版本号感知变化
+pub struct SharedDsp {
+    config: Mutex<DspConfig>,
+    version: AtomicU64,
+}
+
+impl SharedDsp {
+    fn set(&self, config: DspConfig) {
+        if let Ok(mut guard) = self.config.lock() {
+            *guard = config;
+        }
+        self.version.fetch_add(1, Ordering::Release);
+    }
+
+    fn get(&self) -> DspConfig {
+        self.config.lock().map(|c| c.clone()).unwrap_or_default()
+    }
+}
+
+/// 全局DSP状态
+pub fn shared() -> &'static Arc<SharedDsp> {
+    static SHARED: OnceLock<Arc<SharedDsp>> = OnceLock::new();
+    SHARED.get_or_init(|| {
+        Arc::new(SharedDsp {
+            config: Mutex::new(DspConfig::default()),
+            version: AtomicU64::new(0),
+        })
+    })
+}
+
+fn profiles_path() -> PathBuf {
+    storage::data_dir().join("dsp_profiles.json")
+}
+
+/// 各输出设备的DSP配置，键为输出设备标识
+fn profiles() -> &'static Mutex<HashMap<String, DspConfig>> {
+    static PROFILES: OnceLock<Mutex<HashMap<String, DspConfig>>> = OnceLock::new();
+    PROFILES.get_or_init(|| {
+        let loaded = storage::load_json(&profiles_path()).unwrap_or_else(|e| {
+            eprintln!("读取DSP配置失败: {}", e);
+            None
+        });
+        Mutex::new(loaded.unwrap_or_default())
+    })
+}
+
+/// 当前输出设备标识
+fn active_device() -> &'static Mutex<Option<String>> {
+    static DEVICE: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+    DEVICE.get_or_init(|| Mutex::new(None))
+}
+
+/// 输出设备变化时切换到该设备的DSP配置
+pub fn activate_device(device_key: &str) {
+    if let Ok(mut active) = active_device().lock() {
+        if active.as_deref() == Some(device_key) {
+            return;
+        }
+        *active = Some(device_key.to_string());
+    }
+    let config = profiles()
+        .lock()
+        .ok()
+        .and_then(|p| p.get(device_key).cloned())
+        .unwrap_or_default();
+    println!("🎛️ 切换到输出设备 {} 的DSP配置 (启用: {})", device_key, config.enabled);
+    shared().set(config);
+}
+
+/// 当前输出设备标识及其DSP配置
+pub fn current() -> (Option<String>, DspConfig) {
+    let device = active_device().lock().ok().and_then(|d| d.clone());
+    (device, shared().get())
+}
+
+/// 所有已保存的设备DSP配置
+pub fn list_profiles() -> HashMap<String, DspConfig> {
+    profiles().lock().map(|p| p.clone()).unwrap_or_default()
+}
+
+/// 保存设备的DSP配置（为空时使用当前输出设备），是当前设备时立即生效
+pub fn set_config(device_key: Option<String>, config: DspConfig) -> anyhow::Result<()> {
+    let active = active_device().lock().ok().and_then(|d| d.clone());
+    let key = device_key
+        .or_else(|| active.clone())
+        .ok_or_else(|| anyhow::anyhow!("尚未打开输出设备，请指定设备"))?;
+
+    let mut config = config;
+    config.crossfeed = config.crossfeed.max(0.0).min(1.0);
+    config.limiter_threshold_db = config.limiter_threshold_db.min(0.0);
+
+    let snapshot = {
+        let mut guard = profiles().lock().map_err(|_| anyhow::anyhow!("无法锁定DSP配置"))?;
+        guard.insert(key.clone(), config.clone());
+        guard.clone()
+    };
+    storage::save_json(&profiles_path(), &snapshot)?;
+
+    if active.as_deref() == Some(key.as_str()) {
+        shared().set(config);
+    }
+    Ok(())
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// 二阶IIR滤波器（RBJ Audio EQ Cookbook）
+#[derive(Clone, Copy)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    z1: f32,
+    z2: f32,
+}
+
+impl Biquad {
+    fn new(band: &EqBand, sample_rate: f32) -> Self {
+        let a = 10f32.powf(band.gain_db / 40.0);
+        let w0 = 2.0 * std::f32::consts::PI * band.frequency.max(10.0).min(sample_rate * 0.49) / sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * band.q.max(0.05));
+        let sqrt_a2 = 2.0 * a.sqrt() * alpha;
+
+        let (b0, b1, b2, a0, a1, a2) = match band.kind {
+            EqBandKind::Peaking => (
+                1.0 + alpha * a,
+                -2.0 * cos,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos,
+                1.0 - alpha / a,
+            ),
+            EqBandKind::LowShelf => (
+                a * ((a + 1.0) - (a - 1.0) * cos + sqrt_a2),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                a * ((a + 1.0) - (a - 1.0) * cos - sqrt_a2),
+                (a + 1.0) + (a - 1.0) * cos + sqrt_a2,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                (a + 1.0) + (a - 1.0) * cos - sqrt_a2,
+            ),
+            EqBandKind::HighShelf => (
+                a * ((a + 1.0) + (a - 1.0) * cos + sqrt_a2),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                a * ((a + 1.0) + (a - 1.0) * cos - sqrt_a2),
+                (a + 1.0) - (a - 1.0) * cos + sqrt_a2,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                (a + 1.0) - (a - 1.0) * cos - sqrt_a2,
+            ),
+        };
+
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y + self.z2;
+        self.z2 = self.b2 * x - self.a2 * y;
+        y
+    }
+}
+
+/// 根据配置生成的处理状态
+struct DspRuntime {
+    config: DspConfig,
+    preamp: f32,
+    filters: Vec<Vec<Biquad>>, // 每声道一组
+    crossfeed_coeff: f32,
+    crossfeed_lp: Vec<f32>, // 每声道低通后的值，供另一声道混入
+    limiter_threshold: f32,
+    limiter_release: f32,
+    limiter_gain: f32,
+}
+
+impl DspRuntime {
+    fn new(config: DspConfig, channels: usize, sample_rate: u32) -> Self {
+        let rate = sample_rate.max(1) as f32;
+        let bands: Vec<Biquad> = config.eq.iter().map(|band| Biquad::new(band, rate)).collect();
+        Self {
+            preamp: db_to_gain(config.preamp_db),
+            filters: vec![bands; channels],
+            crossfeed_coeff: 1.0 - (-2.0 * std::f32::consts::PI * CROSSFEED_CUTOFF_HZ / rate).exp(),
+            crossfeed_lp: vec![0.0; channels],
+            limiter_threshold: db_to_gain(config.limiter_threshold_db),
+            limiter_release: 1.0 - (-1.0 / (LIMITER_RELEASE_SECS * rate)).exp(),
+            limiter_gain: 1.0,
+            config,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, channel: usize, sample: f32) -> f32 {
+        let mut x = sample * self.preamp;
+        if let Some(filters) = self.filters.get_mut(channel) {
+            for filter in filters.iter_mut() {
+                x = filter.process(x);
+            }
+        }
+
+        // 交叉馈送：混入另一声道（上一帧）的低频部分，只用于立体声
+        if self.config.crossfeed > 0.0 && self.crossfeed_lp.len() == 2 {
+            let other = self.crossfeed_lp[1 - channel];
+            let lp = &mut self.crossfeed_lp[channel];
+            *lp += self.crossfeed_coeff * (x - *lp);
+            x = (x + self.config.crossfeed * other) / (1.0 + self.config.crossfeed);
+        }
+
+        if self.config.limiter {
+            let peak = x.abs() * self.limiter_gain;
+            if peak > self.limiter_threshold {
+                self.limiter_gain = self.limiter_threshold / x.abs();
+            } else {
+                self.limiter_gain += (1.0 - self.limiter_gain) * self.limiter_release;
+            }
+            x *= self.limiter_gain;
+        }
+        x
+    }
+}
+
+/// 应用DSP处理链的音源包装器，配置变化时在下一帧开始处生效
+pub struct DspChain<S> {
+    inner: S,
+    shared: Arc<SharedDsp>,
+    version: u64,
+    runtime: Option<DspRuntime>,
+    channel: usize,
+    duck: f32, // 电台播报期间压低的音乐音量比例
+}
+
+impl<S> DspChain<S>
+where
+    S: Source<Item = i16>,
+{
+    pub fn new(inner: S, shared: Arc<SharedDsp>) -> Self {
+        let mut chain = Self {
+            inner,
+            shared,
+            version: u64::MAX,
+            runtime: None,
+            channel: 0,
+            duck: 1.0,
+        };
+        chain.refresh();
+        chain
+    }
+
+    fn refresh(&mut self) {
+        self.version = self.shared.version.load(Ordering::Acquire);
+        let config = self.shared.get();
+        self.runtime = if config.enabled {
+            Some(DspRuntime::new(
+                config,
+                self.inner.channels().max(1) as usize,
+                self.inner.sample_rate(),
+            ))
+        } else {
+            None
+        };
+    }
+}
+
+impl<S> Iterator for DspChain<S>
+where
+    S: Source<Item = i16>,
+{
+    type Item = i16;
+
+    #[inline]
+    fn next(&mut self) -> Option<i16> {
+        let channels = self.inner.channels().max(1) as usize;
+        if self.channel == 0 {
+            let version_changed = self.shared.version.load(Ordering::Relaxed) != self.version;
+            let layout_changed = self.runtime.as_ref().map(|r| r.filters.len() != channels).unwrap_or(false);
+            if version_changed || layout_changed {
+                self.refresh();
+            }
+            self.duck = radio_host::duck_step(self.duck);
+        }
+
+        let sample = self.inner.next()?;
+        let channel = self.channel;
+        self.channel = (self.channel + 1) % channels;
+
+        let sample = match &mut self.runtime {
+            Some(runtime) => {
+                let x = runtime.process(channel, sample as f32 / 32768.0);
+                (x * 32768.0).round().max(i16::MIN as f32).min(i16::MAX as f32) as i16
+            }
+            None => sample,
+        };
+        if self.duck < 1.0 {
+            Some((sample as f32 * self.duck) as i16)
+        } else {
+            Some(sample)
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<S> Source for DspChain<S>
+where
+    S: Source<Item = i16>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    /// 定位后清空滤波器状态，避免跳转处的爆音
+    fn try_seek(&mut self, pos: Duration) -> Result<(), rodio::source::SeekError> {
+        self.inner.try_seek(pos)?;
+        self.channel = 0;
+        self.refresh();
+        Ok(())
+    }
+}
@@ -0,0 +1,371 @@
+use crate::matroska;
+use crate::media_source::{self, MediaReader};
+use crate::player_fixed::{MediaType, SongInfo};
+use crate::replaygain;
+use rodio::Source;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 导出任务是否正在运行
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// ReplayGain 2.0 的参考响度（LUFS）
+pub const REPLAYGAIN_REFERENCE_LUFS: f64 = -18.0;
+
+/// 响度测量的门限（BS.1770）
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+const RELATIVE_GATE_LU: f64 = -10.0;
+
+/// 音量标准化方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NormalizeMode {
+    #[default]
+    Off,
+    ReplayGain, // 使用标签中的 ReplayGain 值，没有标签时测量响度
+    Lufs,       // 总是测量响度
+}
+
+/// 导出选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportOptions {
+    #[serde(default)]
+    pub normalize: NormalizeMode,
+    #[serde(rename = "targetLufs", default = "default_target_lufs")]
+    pub target_lufs: f64, // 标准化的目标响度
+    #[serde(rename = "sampleRate", default)]
+    pub sample_rate: Option<u32>, // 重采样目标，为空时保持原采样率
+    #[serde(rename = "peakLimitDb", default = "default_peak_limit")]
+    pub peak_limit_db: f64, // 增益后峰值不超过该值（dBFS），避免削波
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            normalize: NormalizeMode::Off,
+            target_lufs: default_target_lufs(),
+            sample_rate: None,
+            peak_limit_db: default_peak_limit(),
+        }
+    }
+}
+
+fn default_target_lufs() -> f64 {
+    -16.0
+}
+
+fn default_peak_limit() -> f64 {
+    -1.0
+}
+
+/// 单个文件的导出结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedTrack {
+    pub source: String,
+    pub output: Option<String>,
+    #[serde(rename = "gainDb")]
+    pub gain_db: Option<f64>, // 实际应用的增益
+    #[serde(rename = "measuredLufs")]
+    pub measured_lufs: Option<f64>, // 测量到的响度（使用 ReplayGain 标签时为空）
+    pub error: Option<String>,
+}
+
+/// 导出过程中发送给前端的事件
+#[derive(Debug, Clone, Serialize)]
+pub enum ExportEvent {
+    Progress { done: usize, total: usize },
+    Track(ExportedTrack),
+    Finished(Vec<ExportedTrack>),
+}
+
+/// 把曲目转换为 16 位 PCM WAV 导出到目录，按选项标准化音量和重采样
+/// 输出文件名带序号，保持播放列表顺序；网络音源和视频会被跳过
+pub fn export(
+    songs: Vec<SongInfo>,
+    dest_dir: &Path,
+    options: &ExportOptions,
+    mut emit: impl FnMut(ExportEvent),
+) -> anyhow::Result<Vec<ExportedTrack>> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(anyhow::anyhow!("导出正在进行中"));
+    }
+    let result = run(&songs, dest_dir, options, &mut emit);
+    RUNNING.store(false, Ordering::SeqCst);
+    let results = result?;
+    emit(ExportEvent::Finished(results.clone()));
+    Ok(results)
+}
+
+fn run(
+    songs: &[SongInfo],
+    dest_dir: &Path,
+    options: &ExportOptions,
+    emit: &mut impl FnMut(ExportEvent),
+) -> anyhow::Result<Vec<ExportedTrack>> {
+    std::fs::create_dir_all(dest_dir)?;
+    let total = songs.len();
+    let mut results = Vec::with_capacity(total);
+    for (idx, song) in songs.iter().enumerate() {
+        let output = dest_dir.join(output_name(idx + 1, song));
+        let result = match export_track(song, &output, options) {
+            Ok((gain_db, measured_lufs)) => ExportedTrack {
+                source: song.path.clone(),
+                output: Some(output.to_string_lossy().into_owned()),
+                gain_db,
+                measured_lufs,
+                error: None,
+            },
+            Err(e) => {
+                eprintln!("导出失败 {}: {}", song.path, e);
+                ExportedTrack {
+                    source: song.path.clone(),
+                    output: None,
+                    gain_db: None,
+                    measured_lufs: None,
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+        emit(ExportEvent::Track(result.clone()));
+        emit(ExportEvent::Progress { done: idx + 1, total });
+        results.push(result);
+    }
+    Ok(results)
+}
+
+/// 输出文件名：序号 + 标题（没有标题时用原文件名），去掉文件系统不允许的字符
+fn output_name(number: usize, song: &SongInfo) -> String {
+    let stem = Path::new(&song.path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match (&song.segment, &song.title) {
+        (Some(_), Some(title)) => title.clone(),
+        _ => stem,
+    };
+    let name: String = name
+        .chars()
+        .map(|c| if "\\/:*?\"<>|".contains(c) || c.is_control() { '_' } else { c })
+        .collect();
+    format!("{:03} {}.wav", number, name.trim())
+}
+
+/// 导出单个曲目，返回 (应用的增益, 测量到的响度)
+fn export_track(song: &SongInfo, output: &Path, options: &ExportOptions) -> anyhow::Result<(Option<f64>, Option<f64>)> {
+    if media_source::is_url(&song.path) {
+        return Err(anyhow::anyhow!("不支持导出网络音源"));
+    }
+    if song.media_type == Some(MediaType::Video) {
+        return Err(anyhow::anyhow!("不支持导出视频"));
+    }
+
+    let file = File::open(&song.path)?;
+    let source = matroska::decode(MediaReader::File(file), song)?;
+    let channels = source.channels().max(1);
+    let sample_rate = source.sample_rate().max(1);
+    let samples: Vec<i16> = source.collect();
+    if samples.is_empty() {
+        return Err(anyhow::anyhow!("没有解码出音频数据"));
+    }
+
+    let (gain_db, measured_lufs) = match options.normalize {
+        NormalizeMode::Off => (None, None),
+        NormalizeMode::ReplayGain => match read_replaygain(Path::new(&song.path)) {
+            Some(track_gain) if song.segment.is_none() => {
+                (Some(track_gain + options.target_lufs - REPLAYGAIN_REFERENCE_LUFS), None)
+            }
+            _ => measure_gain(&samples, channels, sample_rate, options.target_lufs),
+        },
+        NormalizeMode::Lufs => measure_gain(&samples, channels, sample_rate, options.target_lufs),
+    };
+    let gain_db = gain_db.map(|gain| limit_gain(gain, &samples, options.peak_limit_db));
+
+    let scaled: Vec<i16> = match gain_db {
+        Some(gain) => {
+            let factor = 10f64.powf(gain / 20.0);
+            samples
+                .iter()
+                .map(|s| (*s as f64 * factor).round().clamp(i16::MIN as f64, i16::MAX as f64) as i16)
+                .collect()
+        }
+        None => samples,
+    };
+
+    let target_rate = options.sample_rate.filter(|rate| *rate > 0).unwrap_or(sample_rate);
+    let buffer = rodio::buffer::SamplesBuffer::new(channels, sample_rate, scaled);
+    let resampled = rodio::source::UniformSourceIterator::<_, i16>::new(buffer, channels, target_rate);
+    write_wav(output, channels, target_rate, resampled)?;
+    Ok((gain_db, measured_lufs))
+}
+
+/// 一个文件的响度测量结果
+#[derive(Debug, Clone, Copy)]
+pub struct LoudnessMeasurement {
+    pub lufs: Option<f64>, // 积分响度，音频过静无法测量时为空
+    pub peak: f32,         // 采样峰值（线性幅度，1.0 为满幅）
+}
+
+/// 测量本地文件的积分响度（LUFS）和采样峰值
+pub fn measure_file(path: &Path) -> anyhow::Result<LoudnessMeasurement> {
+    let file = File::open(path)?;
+    let source = rodio::Decoder::new(std::io::BufReader::new(file))?;
+    let channels = source.channels().max(1);
+    let sample_rate = source.sample_rate().max(1);
+    // 边解码边测量，不把整个文件解码到内存中（很长的现场录音、播客也只占用很少内存）
+    let mut peak = 0u16;
+    let lufs = integrated_loudness(source.inspect(|s| peak = peak.max(s.unsigned_abs())), channels, sample_rate);
+    Ok(LoudnessMeasurement {
+        lufs,
+        peak: peak as f32 / i16::MAX as f32,
+    })
+}
+
+/// 读取标签中的曲目 ReplayGain 增益（dB）
+fn read_replaygain(path: &Path) -> Option<f64> {
+    replaygain::read_tags(&lofty::read_from_path(path).ok()?)?.track_gain.map(f64::from)
+}
+
+/// 测量响度，返回 (达到目标响度所需的增益, 测量到的响度)；音频过静无法测量时不调整
+fn measure_gain(samples: &[i16], channels: u16, sample_rate: u32, target_lufs: f64) -> (Option<f64>, Option<f64>) {
+    match integrated_loudness(samples.iter().copied(), channels, sample_rate) {
+        Some(lufs) => (Some(target_lufs - lufs), Some(lufs)),
+        None => (None, None),
+    }
+}
+
+/// 增益后的峰值不超过 peak_limit_db
+fn limit_gain(gain_db: f64, samples: &[i16], peak_limit_db: f64) -> f64 {
+    let peak = samples.iter().map(|s| (*s as i32).unsigned_abs()).max().unwrap_or(0);
+    if peak == 0 {
+        return gain_db;
+    }
+    let peak_db = 20.0 * (peak as f64 / i16::MAX as f64).log10();
+    gain_db.min(peak_limit_db - peak_db)
+}
+
+/// 二阶 IIR 滤波器
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 3],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[1] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[2] * y;
+        y
+    }
+}
+
+/// BS.1770 的 K 计权滤波器（高架 + 高通），系数按采样率计算
+fn k_weighting(sample_rate: u32) -> (Biquad, Biquad) {
+    let rate = sample_rate as f64;
+
+    let f0 = 1681.974450955533;
+    let g = 3.999843853973347;
+    let q = 0.7071752369554196;
+    let k = (std::f64::consts::PI * f0 / rate).tan();
+    let vh = 10f64.powf(g / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+        a: [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    let f0 = 38.13547087602444;
+    let q = 0.5003270373238773;
+    let k = (std::f64::consts::PI * f0 / rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let highpass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+    (shelf, highpass)
+}
+
+/// 按 BS.1770 测量积分响度（LUFS）：400ms 块、75% 重叠，绝对门限 -70 LUFS，相对门限 -10 LU
+/// 各声道权重按 1 计算
+fn integrated_loudness(samples: impl IntoIterator<Item = i16>, channels: u16, sample_rate: u32) -> Option<f64> {
+    let channels = channels as usize;
+    let mut filters: Vec<(Biquad, Biquad)> = (0..channels).map(|_| k_weighting(sample_rate)).collect();
+
+    // 每 100ms 一段的均方和（各声道相加）
+    let segment_len = (sample_rate as usize / 10).max(1);
+    let mut segments = Vec::new();
+    let mut sum = 0f64;
+    let mut frames = 0usize;
+    let mut channel = 0usize;
+    for sample in samples {
+        let (shelf, highpass) = &mut filters[channel];
+        let y = highpass.process(shelf.process(sample as f64 / i16::MAX as f64));
+        sum += y * y;
+        channel += 1;
+        if channel < channels {
+            continue;
+        }
+        channel = 0;
+        frames += 1;
+        if frames == segment_len {
+            segments.push(sum / segment_len as f64);
+            sum = 0.0;
+            frames = 0;
+        }
+    }
+
+    let loudness = |power: f64| -0.691 + 10.0 * power.log10();
+    let blocks: Vec<f64> = segments
+        .windows(4)
+        .map(|w| w.iter().sum::<f64>() / 4.0)
+        .filter(|power| *power > 0.0 && loudness(*power) > ABSOLUTE_GATE_LUFS)
+        .collect();
+    if blocks.is_empty() {
+        return None;
+    }
+    let relative_gate = loudness(blocks.iter().sum::<f64>() / blocks.len() as f64) + RELATIVE_GATE_LU;
+    let gated: Vec<f64> = blocks.into_iter().filter(|power| loudness(*power) > relative_gate).collect();
+    if gated.is_empty() {
+        return None;
+    }
+    Some(loudness(gated.iter().sum::<f64>() / gated.len() as f64))
+}
+
+/// 写入 16 位 PCM WAV，先写临时文件再改名，避免留下不完整的文件
+fn write_wav(path: &Path, channels: u16, sample_rate: u32, samples: impl Iterator<Item = i16>) -> anyhow::Result<()> {
+    let samples: Vec<i16> = samples.collect();
+    let data_len = samples.len() as u64 * 2;
+    if data_len > u32::MAX as u64 - 36 {
+        return Err(anyhow::anyhow!("音频过长，超出 WAV 文件大小限制"));
+    }
+    let data_len = data_len as u32;
+    let block_align = channels * 2;
+
+    let tmp_path: PathBuf = path.with_extension("wav.tmp");
+    let mut writer = BufWriter::new(File::create(&tmp_path)?);
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(36 + data_len).to_le_bytes())?;
+    writer.write_all(b"WAVEfmt ")?;
+    writer.write_all(&16u32.to_le_bytes())?;
+    writer.write_all(&1u16.to_le_bytes())?; // PCM
+    writer.write_all(&channels.to_le_bytes())?;
+    writer.write_all(&sample_rate.to_le_bytes())?;
+    writer.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+    writer.write_all(&block_align.to_le_bytes())?;
+    writer.write_all(&16u16.to_le_bytes())?;
+    writer.write_all(b"data")?;
+    writer.write_all(&data_len.to_le_bytes())?;
+    for sample in samples {
+        writer.write_all(&sample.to_le_bytes())?;
+    }
+    writer.flush()?;
+    drop(writer);
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
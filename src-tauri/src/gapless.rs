@@ -0,0 +1,160 @@
+use crate::mp4;
+use rodio::Source;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+
+/// MP3 解码器固有延迟（采样数），LAME 头中的编码延迟不包含这一部分
+const MP3_DECODER_DELAY: u32 = 529;
+
+/// 编码器延迟/填充信息（单位：每声道采样数）
+/// 播放时跳过开头的 delay 个采样，只播放 total_samples 个采样，去掉编码器引入的首尾静音
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GaplessInfo {
+    pub delay: u32,
+    pub padding: u32,
+    #[serde(rename = "totalSamples")]
+    pub total_samples: Option<u64>,
+}
+
+/// 分析文件的编码器延迟/填充，只支持 MP3（LAME/iTunSMPB）和 AAC/M4A（iTunSMPB）；id3 为已读入的 ID3 标签
+pub fn analyze(path: &Path, id3: Option<&id3::Tag>) -> Option<GaplessInfo> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match ext.as_str() {
+        "mp3" => read_lame_header(path).or_else(|| id3.and_then(read_id3_itunsmpb)),
+        "m4a" | "aac" | "mp4" => read_mp4_itunsmpb(path),
+        _ => None,
+    }
+}
+
+/// 播放时需要自行裁剪的编码器延迟/填充：symphonia 解码 MP3 时已按 LAME 头去掉首尾静音，
+/// 再裁剪会多切掉一段，只对 AAC/M4A（iTunSMPB）裁剪
+pub fn playback_info(path: &str, info: Option<GaplessInfo>) -> Option<GaplessInfo> {
+    let is_mp3 = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("mp3"))
+        .unwrap_or(false);
+    info.filter(|_| !is_mp3)
+}
+
+/// 按编码器延迟/填充裁剪音源
+pub fn trim<S>(source: S, info: Option<GaplessInfo>) -> Box<dyn Source<Item = i16> + Send>
+where
+    S: Source<Item = i16> + Send + 'static,
+{
+    let info = match info {
+        Some(info) if info.delay > 0 || info.total_samples.is_some() => info,
+        _ => return Box::new(source),
+    };
+    let sample_rate = source.sample_rate().max(1) as u64;
+    let skip = Duration::from_micros(info.delay as u64 * 1_000_000 / sample_rate);
+    let skipped = source.skip_duration(skip);
+    match info.total_samples {
+        Some(total) => Box::new(skipped.take_duration(Duration::from_micros(total * 1_000_000 / sample_rate))),
+        None => Box::new(skipped),
+    }
+}
+
+/// 读取 MP3 第一帧中的 Xing/Info + LAME 扩展头
+fn read_lame_header(path: &Path) -> Option<GaplessInfo> {
+    let mut file = File::open(path).ok()?;
+
+    // 跳过 ID3v2 标签
+    let mut header = [0u8; 10];
+    file.read_exact(&mut header).ok()?;
+    let mut offset = 0u64;
+    if &header[0..3] == b"ID3" {
+        let size = ((header[6] as u64 & 0x7f) << 21)
+            | ((header[7] as u64 & 0x7f) << 14)
+            | ((header[8] as u64 & 0x7f) << 7)
+            | (header[9] as u64 & 0x7f);
+        let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+        offset = 10 + size + footer;
+    }
+
+    let mut buf = vec![0u8; 4096];
+    file.seek(SeekFrom::Start(offset)).ok()?;
+    let len = file.read(&mut buf).ok()?;
+    buf.truncate(len);
+
+    // 找到第一个帧同步字
+    let frame = (0..buf.len().saturating_sub(4)).find(|&i| buf[i] == 0xff && buf[i + 1] & 0xe0 == 0xe0)?;
+    let version_bits = (buf[frame + 1] >> 3) & 0x03; // 3 = MPEG1
+    let channel_mode = (buf[frame + 3] >> 6) & 0x03; // 3 = 单声道
+    let side_info = match (version_bits == 3, channel_mode == 3) {
+        (true, false) => 32,
+        (true, true) => 17,
+        (false, false) => 17,
+        (false, true) => 9,
+    };
+
+    let xing = frame + 4 + side_info;
+    let tag = buf.get(xing..xing + 4)?;
+    if tag != b"Xing" && tag != b"Info" {
+        return None;
+    }
+
+    // 总帧数（可选字段），每帧 1152（MPEG1）或 576 个采样
+    let flags = u32::from_be_bytes(buf.get(xing + 4..xing + 8)?.try_into().ok()?);
+    let frames = if flags & 0x1 != 0 {
+        Some(u32::from_be_bytes(buf.get(xing + 8..xing + 12)?.try_into().ok()?) as u64)
+    } else {
+        None
+    };
+
+    // LAME 扩展头位于 Xing 头之后第120字节，编码延迟/填充在其第21-23字节（各12位）
+    let lame = xing + 120;
+    if buf.get(lame..lame + 4)? != b"LAME" {
+        return None;
+    }
+    let bytes = buf.get(lame + 21..lame + 24)?;
+    let enc_delay = ((bytes[0] as u32) << 4) | ((bytes[1] as u32) >> 4);
+    let enc_padding = (((bytes[1] as u32) & 0x0f) << 8) | bytes[2] as u32;
+
+    let samples_per_frame = if version_bits == 3 { 1152 } else { 576 };
+    let delay = enc_delay + MP3_DECODER_DELAY;
+    let padding = enc_padding.saturating_sub(MP3_DECODER_DELAY);
+    let total_samples = frames.map(|f| (f * samples_per_frame).saturating_sub(delay as u64 + padding as u64));
+
+    Some(GaplessInfo {
+        delay,
+        padding,
+        total_samples,
+    })
+}
+
+/// 解析 iTunSMPB 字符串：" 00000000 00000840 000001CA 00000000003F31F6 ..."
+fn parse_itunsmpb(text: &str) -> Option<GaplessInfo> {
+    let fields: Vec<&str> = text.split_whitespace().collect();
+    if fields.len() < 4 {
+        return None;
+    }
+    let delay = u32::from_str_radix(fields[1], 16).ok()?;
+    let padding = u32::from_str_radix(fields[2], 16).ok()?;
+    let total = u64::from_str_radix(fields[3], 16).ok()?;
+    Some(GaplessInfo {
+        delay,
+        padding,
+        total_samples: if total > 0 { Some(total) } else { None },
+    })
+}
+
+/// iTunes 编码的 MP3 把 iTunSMPB 存在 ID3 COMM 帧中
+fn read_id3_itunsmpb(tag: &id3::Tag) -> Option<GaplessInfo> {
+    let comment = tag.comments().find(|c| c.description == "iTunSMPB")?;
+    parse_itunsmpb(&comment.text)
+}
+
+/// MP4 元数据（moov/udta/meta/ilst）中的 "----:com.apple.iTunes:iTunSMPB" 自由格式条目
+fn read_mp4_itunsmpb(path: &Path) -> Option<GaplessInfo> {
+    let moov = mp4::read_moov(&mut File::open(path).ok()?).ok()?;
+    let value = mp4::freeform_value(mp4::ilst(&moov)?, "iTunSMPB")?;
+    parse_itunsmpb(&String::from_utf8_lossy(value))
+}
@@ -0,0 +1,205 @@
+use crate::media_session;
+use crate::player_fixed::{PlayerCommand, PlayerState};
+use crate::seek_step;
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+/// 音量快捷键每次调整的幅度
+const VOLUME_STEP: f32 = 0.05;
+
+/// 可以绑定全局快捷键的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HotkeyAction {
+    PlayPause,
+    Stop,
+    Next,
+    Previous,
+    VolumeUp,
+    VolumeDown,
+    ToggleMute,
+    SeekForward,  // 按快进步长前进
+    SeekBackward, // 按快退步长后退
+}
+
+impl HotkeyAction {
+    /// 转换为播放器命令，音量类操作需要当前音量和静音状态
+    pub fn to_command(&self, volume: f32, muted: bool) -> PlayerCommand {
+        match self {
+            HotkeyAction::PlayPause if media_session::state().state == PlayerState::Playing => PlayerCommand::Pause,
+            HotkeyAction::PlayPause => PlayerCommand::Play,
+            HotkeyAction::Stop => PlayerCommand::Stop,
+            HotkeyAction::Next => PlayerCommand::Next,
+            HotkeyAction::Previous => PlayerCommand::Previous,
+            HotkeyAction::VolumeUp => PlayerCommand::SetVolume((volume + VOLUME_STEP).min(1.0)),
+            HotkeyAction::VolumeDown => PlayerCommand::SetVolume((volume - VOLUME_STEP).max(0.0)),
+            HotkeyAction::ToggleMute => PlayerCommand::SetMuted(!muted),
+            HotkeyAction::SeekForward => PlayerCommand::SkipBy(seek_step::settings().forward_secs as i64),
+            HotkeyAction::SeekBackward => PlayerCommand::SkipBy(-(seek_step::settings().backward_secs as i64)),
+        }
+    }
+}
+
+/// 全局快捷键设置：操作 -> 组合键（如 "CommandOrControl+Alt+Right"、"MediaPlayPause"），为空表示不绑定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeySettings {
+    #[serde(default = "default_bindings")]
+    pub bindings: BTreeMap<HotkeyAction, Option<String>>,
+}
+
+fn default_bindings() -> BTreeMap<HotkeyAction, Option<String>> {
+    [
+        (HotkeyAction::PlayPause, "MediaPlayPause"),
+        (HotkeyAction::Stop, "MediaStop"),
+        (HotkeyAction::Next, "MediaTrackNext"),
+        (HotkeyAction::Previous, "MediaTrackPrevious"),
+        (HotkeyAction::VolumeUp, "CommandOrControl+Alt+Up"),
+        (HotkeyAction::VolumeDown, "CommandOrControl+Alt+Down"),
+        (HotkeyAction::SeekForward, "CommandOrControl+Alt+Right"),
+        (HotkeyAction::SeekBackward, "CommandOrControl+Alt+Left"),
+    ]
+    .into_iter()
+    .map(|(action, accelerator)| (action, Some(accelerator.to_string())))
+    .chain([(HotkeyAction::ToggleMute, None)])
+    .collect()
+}
+
+impl Default for HotkeySettings {
+    fn default() -> Self {
+        Self {
+            bindings: default_bindings(),
+        }
+    }
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("hotkeys.json")
+}
+
+fn settings_lock() -> &'static RwLock<HotkeySettings> {
+    static SETTINGS: OnceLock<RwLock<HotkeySettings>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let loaded: Option<HotkeySettings> = storage::load_json(&settings_path()).unwrap_or_else(|e| {
+            eprintln!("读取快捷键设置失败: {}", e);
+            None
+        });
+        // 新版本增加的操作使用默认绑定
+        let settings = loaded.map(|mut s| {
+            for (action, accelerator) in default_bindings() {
+                s.bindings.entry(action).or_insert(accelerator);
+            }
+            s
+        });
+        RwLock::new(settings.unwrap_or_default())
+    })
+}
+
+/// 获取快捷键设置
+pub fn settings() -> HotkeySettings {
+    settings_lock().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// 已注册的快捷键 ID -> 操作
+#[cfg(desktop)]
+fn registered() -> &'static std::sync::Mutex<std::collections::HashMap<u32, HotkeyAction>> {
+    static REGISTERED: OnceLock<std::sync::Mutex<std::collections::HashMap<u32, HotkeyAction>>> = OnceLock::new();
+    REGISTERED.get_or_init(Default::default)
+}
+
+/// 按下的快捷键对应的操作
+#[cfg(desktop)]
+pub fn action_for(shortcut: &tauri_plugin_global_shortcut::Shortcut) -> Option<HotkeyAction> {
+    registered().lock().ok()?.get(&shortcut.id()).copied()
+}
+
+/// 按设置注册全部全局快捷键（先注销之前注册的），被其他程序占用的组合键跳过
+#[cfg(desktop)]
+pub fn register<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+    let shortcuts = app.global_shortcut();
+    if let Err(e) = shortcuts.unregister_all() {
+        eprintln!("注销全局快捷键失败: {}", e);
+    }
+    let Ok(mut registered) = registered().lock() else { return };
+    registered.clear();
+    for (action, accelerator) in settings().bindings {
+        let Some(accelerator) = accelerator else { continue };
+        let shortcut: Shortcut = match accelerator.parse() {
+            Ok(shortcut) => shortcut,
+            Err(e) => {
+                eprintln!("无效的快捷键 {:?} = {}: {}", action, accelerator, e);
+                continue;
+            }
+        };
+        match shortcuts.register(shortcut) {
+            Ok(()) => {
+                registered.insert(shortcut.id(), action);
+            }
+            Err(e) => eprintln!("注册快捷键 {} 失败（可能已被其他程序占用）: {}", accelerator, e),
+        }
+    }
+    println!("⌨️ 已注册 {} 个全局快捷键", registered.len());
+}
+
+/// 修改一个操作的快捷键（None 为取消绑定），保存后重新注册
+#[cfg(desktop)]
+pub fn set_hotkey<R: tauri::Runtime>(app: &tauri::AppHandle<R>, action: HotkeyAction, accelerator: Option<String>) -> anyhow::Result<()> {
+    use tauri_plugin_global_shortcut::Shortcut;
+
+    let accelerator = accelerator.map(|a| a.trim().to_string()).filter(|a| !a.is_empty());
+    let mut settings = settings();
+    if let Some(accelerator) = &accelerator {
+        let shortcut: Shortcut = accelerator.parse().map_err(|e| anyhow::anyhow!("无效的快捷键 {}: {}", accelerator, e))?;
+        let conflict = settings.bindings.iter().find(|(other, bound)| {
+            **other != action && bound.as_ref().and_then(|b| b.parse::<Shortcut>().ok()).map(|b| b.id()) == Some(shortcut.id())
+        });
+        if let Some((other, _)) = conflict {
+            return Err(anyhow::anyhow!("快捷键 {} 已用于 {:?}", accelerator, other));
+        }
+    }
+    settings.bindings.insert(action, accelerator);
+    storage::save_json(&settings_path(), &settings)?;
+    *settings_lock().write().map_err(|_| anyhow::anyhow!("无法锁定快捷键设置"))? = settings;
+    register(app);
+    Ok(())
+}
+
+/// 替换全部快捷键绑定（未列出的操作保持原样），保存后重新注册
+#[cfg(desktop)]
+pub fn set_bindings<R: tauri::Runtime>(app: &tauri::AppHandle<R>, bindings: BTreeMap<HotkeyAction, Option<String>>) -> anyhow::Result<()> {
+    use tauri_plugin_global_shortcut::Shortcut;
+
+    let mut settings = settings();
+    for (action, accelerator) in bindings {
+        let accelerator = accelerator.map(|a| a.trim().to_string()).filter(|a| !a.is_empty());
+        settings.bindings.insert(action, accelerator);
+    }
+    let mut seen = std::collections::HashMap::new();
+    for (action, accelerator) in &settings.bindings {
+        let Some(accelerator) = accelerator else { continue };
+        let shortcut: Shortcut = accelerator.parse().map_err(|e| anyhow::anyhow!("无效的快捷键 {}: {}", accelerator, e))?;
+        if let Some(other) = seen.insert(shortcut.id(), *action) {
+            return Err(anyhow::anyhow!("快捷键 {} 同时用于 {:?} 和 {:?}", accelerator, other, action));
+        }
+    }
+    storage::save_json(&settings_path(), &settings)?;
+    *settings_lock().write().map_err(|_| anyhow::anyhow!("无法锁定快捷键设置"))? = settings;
+    register(app);
+    Ok(())
+}
+
+/// 移动端没有全局快捷键
+#[cfg(not(desktop))]
+pub fn set_bindings<R: tauri::Runtime>(_app: &tauri::AppHandle<R>, _bindings: BTreeMap<HotkeyAction, Option<String>>) -> anyhow::Result<()> {
+    Err(anyhow::anyhow!("当前平台不支持全局快捷键"))
+}
+
+/// 移动端没有全局快捷键
+#[cfg(not(desktop))]
+pub fn set_hotkey<R: tauri::Runtime>(_app: &tauri::AppHandle<R>, _action: HotkeyAction, _accelerator: Option<String>) -> anyhow::Result<()> {
+    Err(anyhow::anyhow!("当前平台不支持全局快捷键"))
+}
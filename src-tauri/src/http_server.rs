@@ -0,0 +1,309 @@
+use crate::m3u;
+use crate::now_playing;
+use crate::player_fixed::{PlayerEvent, SongInfo};
+use crate::remote_api;
+use crate::video_stream;
+use axum::body::Body;
+use axum::extract::{ConnectInfo, Path as UrlPath, Query, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::oneshot;
+
+/// 内嵌HTTP服务共享状态
+#[derive(Default)]
+pub struct ServerState {
+    playlist: RwLock<Vec<SongInfo>>, // 播放列表快照，收到 PlaylistUpdated 时刷新
+    cast_files: RwLock<HashMap<String, String>>, // 投屏令牌 -> 文件路径，局域网设备只能访问这些文件
+}
+
+/// 默认端口
+pub const DEFAULT_PORT: u16 = 17890;
+
+/// 停止服务时等待进行中的请求结束的时间，投屏等长连接超时后直接断开
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+
+/// 正在运行的服务
+struct RunningServer {
+    port: u16,
+    lan: bool, // 是否监听局域网（投屏时需要）
+    shutdown: oneshot::Sender<()>,
+    task: tokio::task::JoinHandle<()>, // 服务任务，结束后监听端口才释放
+}
+
+fn shared_state() -> &'static Arc<ServerState> {
+    static STATE: OnceLock<Arc<ServerState>> = OnceLock::new();
+    STATE.get_or_init(|| Arc::new(ServerState::default()))
+}
+
+fn running_server() -> &'static Mutex<Option<RunningServer>> {
+    static SERVER: OnceLock<Mutex<Option<RunningServer>>> = OnceLock::new();
+    SERVER.get_or_init(|| Mutex::new(None))
+}
+
+/// 根据播放器事件刷新服务使用的数据
+pub fn on_player_event(event: &PlayerEvent) {
+    if let PlayerEvent::PlaylistUpdated(playlist) = event {
+        update_playlist(playlist);
+    }
+}
+
+/// 更新播放列表快照
+pub fn update_playlist(playlist: &[SongInfo]) {
+    if let Ok(mut guard) = shared_state().playlist.write() {
+        *guard = playlist.to_vec();
+    }
+}
+
+/// 当前服务端口（未运行时为 None）
+pub fn running_port() -> Option<u16> {
+    running_server().lock().ok()?.as_ref().map(|s| s.port)
+}
+
+/// 在局域网上可访问时的服务端口
+pub fn lan_port() -> Option<u16> {
+    running_server().lock().ok()?.as_ref().filter(|s| s.lan).map(|s| s.port)
+}
+
+/// 启动HTTP服务（已在运行时先停止），仅监听本机；已在局域网上提供（投屏、远程控制）时保持监听局域网
+pub async fn start(port: u16) -> Result<u16, String> {
+    let lan = lan_port().is_some() || remote_api::settings().enabled;
+    start_on(port, lan).await
+}
+
+/// 确保服务在局域网上可访问（投屏、远程控制用），只监听本机时在同一端口重新启动，未运行时在 default_port 启动，返回端口
+pub async fn ensure_lan(default_port: u16) -> Result<u16, String> {
+    let running = running_server().lock().ok().and_then(|s| s.as_ref().map(|s| (s.port, s.lan)));
+    match running {
+        Some((port, true)) => Ok(port),
+        Some((port, false)) => start_on(port, true).await,
+        None => start_on(default_port, true).await,
+    }
+}
+
+async fn start_on(port: u16, lan: bool) -> Result<u16, String> {
+    // 等旧服务释放端口后再监听，否则在同一端口重启会失败
+    stop().await;
+
+    let host = if lan { "0.0.0.0" } else { "127.0.0.1" };
+    let listener = tokio::net::TcpListener::bind((host, port))
+        .await
+        .map_err(|e| format!("无法监听端口 {}: {}", port, e))?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+
+    let app = Router::new()
+        .route("/playlist.m3u8", get(playlist_m3u))
+        .route("/stream/:index", get(stream_song))
+        .route("/overlay", get(overlay))
+        .route("/now-playing.json", get(now_playing_json))
+        .route("/now-playing/cover", get(now_playing_cover))
+        .route("/cast/:file", get(cast_file))
+        .with_state(shared_state().clone())
+        .merge(remote_api::router())
+        .layer(middleware::from_fn(lan_guard));
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let task = tokio::spawn(async move {
+        let result = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+            .with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+            })
+            .await;
+        if let Err(e) = result {
+            eprintln!("HTTP服务异常退出: {}", e);
+        }
+    });
+
+    *running_server().lock().map_err(|_| "无法锁定HTTP服务状态".to_string())? = Some(RunningServer {
+        port,
+        lan,
+        shutdown: shutdown_tx,
+        task,
+    });
+    println!("🌐 HTTP服务已启动: http://{}:{}", host, port);
+    Ok(port)
+}
+
+/// 停止HTTP服务，服务任务结束（监听端口已释放）后返回
+pub async fn stop() {
+    let server = running_server().lock().ok().and_then(|mut guard| guard.take());
+    let Some(server) = server else { return };
+    let _ = server.shutdown.send(());
+    let mut task = server.task;
+    if tokio::time::timeout(SHUTDOWN_GRACE, &mut task).await.is_err() {
+        task.abort();
+        let _ = task.await;
+    }
+    println!("🌐 HTTP服务已停止");
+}
+
+/// 共享一个文件给投屏设备，返回地址中使用的令牌（不含扩展名）
+/// 渲染器同时只播放一个文件，之前共享的文件随之失效
+pub fn share_for_cast(path: &str) -> String {
+    use rand::distributions::{Alphanumeric, DistString};
+    let token = Alphanumeric.sample_string(&mut rand::thread_rng(), 16);
+    if let Ok(mut files) = shared_state().cast_files.write() {
+        files.clear();
+        files.insert(token.clone(), path.to_string());
+    }
+    token
+}
+
+/// 取消全部投屏共享
+pub fn clear_cast_files() {
+    if let Ok(mut files) = shared_state().cast_files.write() {
+        files.clear();
+    }
+}
+
+/// 监听局域网时，其他设备只能访问 /cast/ 下的投屏文件和启用时的远程控制接口（由令牌保护）
+async fn lan_guard(ConnectInfo(addr): ConnectInfo<SocketAddr>, request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    let remote = remote_api::settings().enabled && remote_api::serves(path);
+    if addr.ip().is_loopback() || path.starts_with("/cast/") || remote {
+        next.run(request).await
+    } else {
+        (StatusCode::FORBIDDEN, "只允许本机访问").into_response()
+    }
+}
+
+#[derive(Deserialize)]
+struct PlaylistQuery {
+    #[serde(default)]
+    mode: Option<String>, // "stream" 时输出串流URL，否则输出绝对路径
+}
+
+/// GET /playlist.m3u8 — 当前播放列表
+/// 串流地址固定指向本机（/stream/ 只允许本机访问），不使用请求中的 Host 头
+async fn playlist_m3u(State(state): State<Arc<ServerState>>, Query(query): Query<PlaylistQuery>) -> Response {
+    let Some(port) = running_port() else {
+        return (StatusCode::SERVICE_UNAVAILABLE, "HTTP服务未运行").into_response();
+    };
+    let stream_urls = query.mode.as_deref() == Some("stream");
+
+    let playlist = state.playlist.read().map(|p| p.clone()).unwrap_or_default();
+    let body = m3u::to_extm3u(&playlist, |idx, song| {
+        if stream_urls {
+            format!("http://127.0.0.1:{}/stream/{}", port, idx)
+        } else {
+            song.path.clone()
+        }
+    });
+
+    (
+        [(header::CONTENT_TYPE, "audio/x-mpegurl; charset=utf-8")],
+        body,
+    )
+        .into_response()
+}
+
+/// GET /stream/:index — 播放列表中指定歌曲的文件内容，支持 Range
+async fn stream_song(State(state): State<Arc<ServerState>>, UrlPath(index): UrlPath<usize>, headers: HeaderMap) -> Response {
+    let path = match state.playlist.read().ok().and_then(|p| p.get(index).map(|s| s.path.clone())) {
+        Some(path) => path,
+        None => return (StatusCode::NOT_FOUND, "歌曲不存在").into_response(),
+    };
+    serve_file(&path, &headers).await
+}
+
+/// GET /cast/:token.ext — 投屏文件，支持 Range，渲染器可以边下边播并跳转
+async fn cast_file(State(state): State<Arc<ServerState>>, UrlPath(file): UrlPath<String>, headers: HeaderMap) -> Response {
+    let token = file.split('.').next().unwrap_or_default();
+    let Some(path) = state.cast_files.read().ok().and_then(|f| f.get(token).cloned()) else {
+        return (StatusCode::NOT_FOUND, "文件不存在").into_response();
+    };
+    serve_file(&path, &headers).await
+}
+
+/// 以流的方式返回文件内容（不整个读入内存），支持 Range 请求
+async fn serve_file(path: &str, headers: &HeaderMap) -> Response {
+    let mut file = match tokio::fs::File::open(path).await {
+        Ok(file) => file,
+        Err(e) => return (StatusCode::NOT_FOUND, format!("无法读取文件: {}", e)).into_response(),
+    };
+    let len = match file.metadata().await {
+        Ok(metadata) => metadata.len(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let range = headers.get(header::RANGE).and_then(|v| v.to_str().ok());
+    let (status, start, end) = match range {
+        Some(range) => match video_stream::parse_range(range, len) {
+            Some((start, end)) => (StatusCode::PARTIAL_CONTENT, start, end),
+            None => {
+                return (StatusCode::RANGE_NOT_SATISFIABLE, [(header::CONTENT_RANGE, format!("bytes */{}", len))]).into_response();
+            }
+        },
+        None => (StatusCode::OK, 0, len.saturating_sub(1)),
+    };
+    if let Err(e) = file.seek(std::io::SeekFrom::Start(start)).await {
+        return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+    let length = if len == 0 { 0 } else { end - start + 1 };
+    let body = Body::from_stream(tokio_util::io::ReaderStream::new(file.take(length)));
+    let mut response = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, mime_for_path(path))
+        .header(header::CONTENT_LENGTH, length)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header("transferMode.dlna.org", "Streaming")
+        .header("contentFeatures.dlna.org", "DLNA.ORG_OP=01;DLNA.ORG_FLAGS=01700000000000000000000000000000");
+    if status == StatusCode::PARTIAL_CONTENT {
+        response = response.header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, len));
+    }
+    response.body(body).unwrap_or_default()
+}
+
+/// GET /overlay — 直播用的"正在播放"叠加层页面（OBS 浏览器源）
+async fn overlay() -> Response {
+    (
+        [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+        now_playing::OVERLAY_HTML,
+    )
+        .into_response()
+}
+
+/// GET /now-playing.json — 当前曲目信息
+async fn now_playing_json() -> Response {
+    match now_playing::info() {
+        Some(info) => axum::Json(info).into_response(),
+        None => (StatusCode::NOT_FOUND, "尚未开始播放").into_response(),
+    }
+}
+
+/// GET /now-playing/cover — 当前曲目封面
+async fn now_playing_cover() -> Response {
+    match now_playing::cover() {
+        Some((mime, data)) => ([(header::CONTENT_TYPE, mime)], Body::from(data)).into_response(),
+        None => (StatusCode::NOT_FOUND, "没有封面").into_response(),
+    }
+}
+
+/// 根据扩展名推断MIME类型
+pub fn mime_for_path(path: &str) -> &'static str {
+    let ext = std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match ext.as_str() {
+        "mp3" => "audio/mpeg",
+        "flac" => "audio/flac",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "m4a" | "aac" => "audio/mp4",
+        "mp4" | "m4v" => "video/mp4",
+        "mka" => "audio/x-matroska",
+        "mid" | "midi" | "kar" => "audio/midi",
+        "mkv" => "video/x-matroska",
+        "webm" => "video/webm",
+        _ => "application/octet-stream",
+    }
+}
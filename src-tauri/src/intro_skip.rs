@@ -0,0 +1,68 @@
+use crate::library;
+use serde::Serialize;
+
+/// 只统计在曲目开头这段时间内发生的跳转
+const MAX_SEEK_FROM_SECS: u64 = 10;
+
+/// 跳转目标需要落在这个范围内才视为跳过前奏
+const MIN_SKIP_SECS: u64 = 5;
+const MAX_SKIP_SECS: u64 = 180;
+
+/// 最近几次跳过中至少有这么多次落在同一位置附近才提示
+const MIN_OBSERVATIONS: usize = 3;
+const RECENT_WINDOW: usize = 5;
+const CLUSTER_SPREAD_MS: u64 = 10_000;
+
+/// 建议为曲目设置的自动起始裁剪点
+#[derive(Debug, Clone, Serialize)]
+pub struct IntroSkipSuggestion {
+    pub path: String,
+    #[serde(rename = "startMs")]
+    pub start_ms: u64,
+    pub occurrences: usize,
+}
+
+/// 记录一次跳转，用户多次跳过同一段前奏时返回建议
+/// from_secs / to_secs 为跳转前后的播放位置，duration 为曲目时长（秒）
+pub fn observe_seek(path: &str, from_secs: u64, to_secs: u64, duration: u64) -> Option<IntroSkipSuggestion> {
+    let max_target = MAX_SKIP_SECS.min(duration / 2);
+    if from_secs > MAX_SEEK_FROM_SECS || to_secs <= from_secs || to_secs < MIN_SKIP_SECS || to_secs > max_target {
+        return None;
+    }
+
+    let result = library::with_library(|lib| {
+        lib.record_intro_skip(path, to_secs * 1000)?;
+        if lib.trim_points(path)?.is_some() || lib.intro_skip_dismissed(path)? {
+            return Ok(None);
+        }
+        Ok(suggest(path, &lib.recent_intro_skips(path, RECENT_WINDOW)?))
+    });
+    match result {
+        Ok(suggestion) => suggestion,
+        Err(e) => {
+            eprintln!("记录跳过前奏失败: {}", e);
+            None
+        }
+    }
+}
+
+/// 在最近的跳转目标中找出落在同一位置附近的一组，取其中最早的位置作为起始点，避免切掉正文
+fn suggest(path: &str, targets: &[u64]) -> Option<IntroSkipSuggestion> {
+    targets
+        .iter()
+        .map(|&anchor| {
+            let cluster: Vec<u64> = targets
+                .iter()
+                .copied()
+                .filter(|t| *t >= anchor && *t - anchor <= CLUSTER_SPREAD_MS)
+                .collect();
+            (anchor, cluster.len())
+        })
+        .filter(|(_, count)| *count >= MIN_OBSERVATIONS)
+        .max_by_key(|(anchor, count)| (*count, std::cmp::Reverse(*anchor)))
+        .map(|(start_ms, occurrences)| IntroSkipSuggestion {
+            path: path.to_string(),
+            start_ms,
+            occurrences,
+        })
+}
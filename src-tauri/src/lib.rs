@@ -1,15 +1,78 @@
+mod bpm;
+mod cache;
+mod cast;
+mod chapters;
+mod clipboard_watch;
+mod collation;
+mod cover_cache;
+mod cover_fetch;
+mod cue;
+mod deep_link;
+mod desktop_lyrics;
+mod dsp;
+mod export;
+mod gapless;
 mod global_player;
+mod hotkeys;
+mod http_server;
+mod intro_skip;
+mod library;
+mod library_scan;
+mod library_verify;
+mod library_watch;
+mod listening_stats;
+mod loudness;
+mod lyrics_provider;
+mod low_memory;
+mod m3u;
+mod maintenance;
+mod matroska;
+mod media_session;
+mod media_source;
+mod metadata_priority;
+mod midi;
+mod mp4;
+mod net;
+mod now_playing;
+mod output_device;
+mod party;
+mod playback_monitor;
 mod player_fixed;
 mod player_safe;
+mod playlist_store;
+mod playlist_tools;
+mod podcast;
+mod progress;
+mod radio_host;
+mod remote_api;
+mod replaygain;
+mod search;
+mod seek_step;
+mod session;
+mod settings;
+mod setlist;
+mod shuffle;
+mod skip_filter;
+mod storage;
+mod subtitles;
+mod tag_write;
+mod video_probe;
+mod video_stream;
+mod voice;
+mod webhooks;
 
 use crate::global_player::{GlobalPlayer, PlayerWrapper};
-use crate::player_fixed::{PlayMode, PlayerCommand, PlayerEvent, PlayerState, SongInfo};
+use crate::player_fixed::{AbCompare, EnqueuePolicy, FileTags, PlayMode, PlayerCommand, PlayerEvent, PlayerState, SongInfo, SortDirection, SortField};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tauri_plugin_deep_link::DeepLinkExt;
 use tauri_plugin_dialog::DialogExt;
 use tokio::sync::Mutex as AsyncMutex;
 
+/// 从文件对话框导入时最多同时读取元数据的文件数（不超过CPU核数）
+const IMPORT_MAX_WORKERS: usize = 8;
+
 /// Tauri 应用状态
 #[derive(Default, Clone)]
 struct AppState {
@@ -33,6 +96,7 @@ struct InitialPlayerState {
     current_song_index: Option<usize>,
     is_playing: bool,
     volume: f32,
+    muted: bool,
     play_mode: PlayMode,
 }
 
@@ -54,7 +118,7 @@ async fn init_player<R: Runtime>(
     }
 
     // 初始化全局播放器
-    let (_player_state_arc, mut event_rx) = match GlobalPlayer::instance().lock() {
+    let (player_arc, mut event_rx) = match GlobalPlayer::instance().lock() {
         Ok(mut global_player) => global_player.initialize(),
         Err(_) => return Err("无法获取全局播放器锁进行初始化".to_string()),
     };
@@ -62,22 +126,393 @@ async fn init_player<R: Runtime>(
     // 启动事件监听器
     let app_handle_clone = app_handle.clone();
     tokio::spawn(async move {
+        let player = player_arc.lock().await.player.clone();
         while let Some(event) = event_rx.recv().await {
             // 记录错误事件
             if let PlayerEvent::Error(err) = &event {
                 eprintln!("播放器错误: {}", err);
             }
 
-            // 发送事件到前端
+            // 低内存模式下播放列表条目是精简过的，当前歌曲补全封面、歌词等再发给前端
+            let event = match event {
+                PlayerEvent::SongChanged(index, song) if low_memory::settings().enabled => {
+                    let slim = song.clone();
+                    let song = tokio::task::spawn_blocking(move || low_memory::hydrate(&song))
+                        .await
+                        .unwrap_or_else(|e| {
+                            eprintln!("补全歌曲信息失败: {}", e);
+                            slim
+                        });
+                    PlayerEvent::SongChanged(index, song)
+                }
+                event => event,
+            };
+
+            // 音量也可能由快捷键、语音、远程控制修改，统一在这里保存
+            if let PlayerEvent::VolumeChanged { volume, muted } = &event {
+                if let Err(e) = settings::remember_volume(&app_handle_clone, *volume, *muted) {
+                    eprintln!("保存设置失败: {}", e);
+                }
+            }
+
+            // 记录会话，下次启动时恢复
+            if matches!(
+                event,
+                PlayerEvent::PlaylistUpdated(_)
+                    | PlayerEvent::SongChanged(..)
+                    | PlayerEvent::StateChanged(_)
+                    | PlayerEvent::VolumeChanged { .. }
+            ) {
+                session::record(&player);
+            } else if let PlayerEvent::ProgressUpdate { position, .. } = &event {
+                session::record_position(*position);
+            }
+
+            // 同步内嵌HTTP服务使用的数据
+            http_server::on_player_event(&event);
+            // 触发已配置的webhook
+            webhooks::on_player_event(&event);
+            // 记录曲目列表（播放历史）
+            setlist::on_player_event(&event);
+            // 统计播放次数和收听时长
+            listening_stats::on_player_event(&event);
+            // 写出直播叠加层使用的正在播放信息
+            now_playing::on_player_event(&event);
+            // 派对模式：切歌时清空跳过票
+            party::on_player_event(&event);
+            // 投屏时让渲染器跟随切歌
+            cast::on_player_event(&event);
+            // 更新桌面歌词
+            desktop_lyrics::on_player_event(&app_handle_clone, &event);
+            // 同步平台媒体会话（通知栏、蓝牙/车机界面）
+            if media_session::on_player_event(&event) {
+                media_session::sync_native(&app_handle_clone);
+                if let Err(e) = app_handle_clone.emit("media-session", media_session::state()) {
+                    eprintln!("发送媒体会话到前端失败: {:?}", e);
+                }
+            }
+
+            // 发送事件到前端，播放列表和条目更新不携带封面（当前歌曲的封面随 SongChanged 发送，其余按需用 get_album_cover 获取）
+            let event = match event {
+                PlayerEvent::PlaylistUpdated(songs) => {
+                    PlayerEvent::PlaylistUpdated(songs.into_iter().map(SongInfo::without_cover).collect())
+                }
+                PlayerEvent::SongMetadataUpdated(index, song) => PlayerEvent::SongMetadataUpdated(index, song.without_cover()),
+                event => event,
+            };
             if let Err(e) = app_handle_clone.emit("player-event", event.clone()) {
                 eprintln!("发送事件到前端失败: {:?}", e);
             }
         }
     });
 
+    spawn_maintenance_scheduler(app_handle.clone());
+    apply_preferences().await?;
+    restore_last_session().await?;
+
+    // 由链接冷启动时，链接在播放器就绪、恢复上次会话之后再执行
+    for url in deep_link::mark_ready() {
+        open_deep_link(&app_handle, url);
+    }
+    Ok(())
+}
+
+/// 把保存的音量、播放模式、输出设备等偏好应用到播放器
+async fn apply_preferences() -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    for command in settings::startup_commands() {
+        player_state_guard
+            .player
+            .send_command(command)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 每分钟检查是否进入维护时段，播放器空闲时自动运行音乐库维护；维护期间开始播放则取消
+fn spawn_maintenance_scheduler<R: Runtime>(app_handle: AppHandle<R>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        let mut idle_since: Option<std::time::Instant> = None;
+        let mut started_here = false;
+        loop {
+            interval.tick().await;
+            let playing = match get_player_instance().await {
+                Ok(player_instance) => player_instance.lock().await.player.get_state() == PlayerState::Playing,
+                Err(_) => false,
+            };
+            if playing {
+                idle_since = None;
+                if started_here && maintenance::cancel() {
+                    println!("🛠️ 开始播放，取消正在进行的维护任务");
+                }
+                continue;
+            }
+            let idle_secs = idle_since.get_or_insert_with(std::time::Instant::now).elapsed().as_secs();
+            started_here = started_here && maintenance::is_running();
+            if !maintenance::due(Some(idle_secs)) {
+                continue;
+            }
+            started_here = true;
+            let app_handle = app_handle.clone();
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = maintenance::run(|event| {
+                    if let Err(e) = app_handle.emit("maintenance", event) {
+                        eprintln!("发送维护事件失败: {:?}", e);
+                    }
+                }) {
+                    eprintln!("音乐库维护失败: {}", e);
+                }
+            });
+        }
+    });
+}
+
+/// 恢复上次退出时的播放会话；按启动设置自动继续播放时音量渐入，避免登录时突然出声
+async fn restore_last_session() -> Result<(), String> {
+    let last = match session::load_last() {
+        Ok(Some(last)) if !last.songs.is_empty() => last,
+        Ok(_) => return Ok(()),
+        Err(e) => {
+            eprintln!("读取上次播放会话失败: {}", e);
+            return Ok(());
+        }
+    };
+    let options = session::load_options();
+
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    let player = &player_state_guard.player;
+    player
+        .send_command(PlayerCommand::LoadPlaylist {
+            name: None,
+            songs: last.songs,
+            index: last.current_index,
+            position: last.position,
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+    if options.auto_resume && last.was_playing {
+        player
+            .send_command(PlayerCommand::ResumeWithFadeIn(options.volume_ramp_secs))
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 将当前播放队列和播放位置保存为命名会话
+#[tauri::command]
+async fn save_session(name: String, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    let saved_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let named = session::NamedSession {
+        name,
+        saved_at,
+        songs: player_state_guard.player.get_playlist(),
+        current_index: player_state_guard.player.get_current_index(),
+        position: player_state_guard.player.get_position(),
+    };
+    session::save_named(&named).map_err(|e| format!("保存会话失败: {}", e))
+}
+
+/// 恢复命名会话：替换当前播放队列，停在保存时的歌曲和位置
+#[tauri::command]
+async fn load_session(name: String, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let named = session::load_named(&name).map_err(|e| e.to_string())?;
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+
+    // 离开已保存的播放列表前记住它的位置
+    if let Some(active) = player_state_guard.player.get_active_playlist() {
+        if let Err(e) = playlist_store::update_position(
+            &active,
+            player_state_guard.player.get_current_index(),
+            player_state_guard.player.get_position(),
+        ) {
+            eprintln!("保存播放列表位置失败 {}: {}", active, e);
+        }
+    }
+
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::LoadPlaylist {
+            name: None,
+            songs: named.songs,
+            index: named.current_index,
+            position: named.position,
+        })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 列出所有命名会话
+#[tauri::command]
+async fn list_sessions() -> Result<Vec<session::NamedSessionSummary>, String> {
+    session::list_named().map_err(|e| e.to_string())
+}
+
+/// 删除命名会话
+#[tauri::command]
+async fn delete_session(name: String) -> Result<(), String> {
+    session::delete_named(&name).map_err(|e| e.to_string())
+}
+
+/// 正在播放信息（now_playing.txt / now_playing.json / 封面）的输出目录，供 OBS 文本源读取
+#[tauri::command]
+async fn get_now_playing_dir() -> Result<String, String> {
+    Ok(now_playing::output_dir().to_string_lossy().into_owned())
+}
+
+/// 获取平台媒体会话状态（曲目信息、封面、队列、可用操作），之后监听 "media-session" 事件
+/// Android 上由 media_session 插件直接同步到系统 MediaSession
+#[tauri::command]
+async fn get_media_session() -> Result<media_session::MediaSessionState, String> {
+    Ok(media_session::state())
+}
+
+/// 处理系统媒体会话发来的操作（通知栏、耳机按钮、Android Auto / CarPlay 界面）
+#[tauri::command]
+async fn media_session_action(action: media_session::MediaSessionAction) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(action.to_command())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 执行全局快捷键对应的播放器操作
+#[cfg(desktop)]
+fn dispatch_hotkey(action: hotkeys::HotkeyAction) {
+    tauri::async_runtime::spawn(async move {
+        let result = async {
+            let player_instance = get_player_instance().await?;
+            let player_state_guard = player_instance.lock().await;
+            let player = &player_state_guard.player;
+            let command = action.to_command(player.get_volume(), player.is_muted());
+            player.send_command(command).await.map_err(|e| e.to_string())
+        }
+        .await;
+        if let Err(e) = result {
+            eprintln!("执行快捷键 {:?} 失败: {}", action, e);
+        }
+    });
+}
+
+/// 执行 musicplayer:// 链接（浏览器书签、其他应用触发播放），播放器未就绪时先排队
+fn open_deep_link<R: Runtime>(app_handle: &AppHandle<R>, url: tauri::Url) {
+    let Some(url) = deep_link::queue_until_ready(url) else { return };
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = run_deep_link(&app_handle, &url).await {
+            eprintln!("执行链接失败 {}: {}", url, e);
+        }
+    });
+}
+
+async fn run_deep_link<R: Runtime>(app_handle: &AppHandle<R>, url: &tauri::Url) -> Result<(), String> {
+    let action = deep_link::parse(url).map_err(|e| e.to_string())?;
+    println!("🔗 打开链接: {}", url);
+    let (target, play_now) = match action {
+        deep_link::DeepLinkAction::Show => {
+            deep_link::show_main_window(app_handle);
+            return Ok(());
+        }
+        deep_link::DeepLinkAction::Playlist(name) => return load_saved_playlist(name).await,
+        deep_link::DeepLinkAction::Hotkey(action) => {
+            dispatch_hotkey(action);
+            return Ok(());
+        }
+        deep_link::DeepLinkAction::Command(command) => {
+            let player_instance = get_player_instance().await?;
+            let player_state_guard = player_instance.lock().await;
+            return player_state_guard.player.send_command(command).await.map_err(|e| e.to_string());
+        }
+        deep_link::DeepLinkAction::PlayNow(target) => (target, true),
+        deep_link::DeepLinkAction::Enqueue(target) => (target, false),
+    };
+
+    let songs = tokio::task::spawn_blocking(move || {
+        if target.contains("://") {
+            Ok(vec![SongInfo::from_url(&target)])
+        } else {
+            songs_from_path(&PathBuf::from(&target)).map_err(|e| format!("无法从路径创建歌曲信息: {}", e))
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    let player = &player_state_guard.player;
+    if !play_now {
+        return player.send_command(PlayerCommand::Enqueue(songs)).await.map_err(|e| e.to_string());
+    }
+    let index = player.get_current_index().map(|i| i + 1).unwrap_or(player.get_playlist().len());
+    player
+        .send_command(PlayerCommand::InsertSongs { index, songs })
+        .await
+        .map_err(|e| e.to_string())?;
+    player.send_command(PlayerCommand::SetSong(index)).await.map_err(|e| e.to_string())
+}
+
+/// 获取全局快捷键设置
+#[tauri::command]
+async fn get_hotkeys() -> Result<hotkeys::HotkeySettings, String> {
+    Ok(hotkeys::settings())
+}
+
+/// 设置某个操作的全局快捷键（如 "CommandOrControl+Shift+P"、"MediaPlayPause"），accelerator 为空时取消绑定
+#[tauri::command]
+async fn set_hotkey<R: Runtime>(
+    app_handle: AppHandle<R>,
+    action: hotkeys::HotkeyAction,
+    accelerator: Option<String>,
+) -> Result<(), String> {
+    hotkeys::set_hotkey(&app_handle, action, accelerator).map_err(|e| e.to_string())?;
+    settings::notify(&app_handle, "hotkeys");
+    Ok(())
+}
+
+/// 获取本次运行播放过的曲目列表（含开始/结束时间）
+#[tauri::command]
+async fn get_setlist() -> Result<Vec<setlist::SetlistEntry>, String> {
+    Ok(setlist::entries())
+}
+
+/// 清空曲目列表
+#[tauri::command]
+async fn clear_setlist() -> Result<(), String> {
+    setlist::clear();
     Ok(())
 }
 
+/// 导出曲目列表为 txt/csv/cue，返回导出的条目数
+#[tauri::command]
+async fn export_setlist(path: String, format: setlist::SetlistFormat) -> Result<usize, String> {
+    setlist::export(std::path::Path::new(&path), format).map_err(|e| format!("导出曲目列表失败: {}", e))
+}
+
+/// 获取启动设置
+#[tauri::command]
+async fn get_startup_options() -> Result<session::StartupOptions, String> {
+    Ok(session::load_options())
+}
+
+/// 保存启动设置（是否自动继续播放、音量渐入时长）
+#[tauri::command]
+async fn set_startup_options(options: session::StartupOptions) -> Result<(), String> {
+    session::save_options(&options).map_err(|e| e.to_string())
+}
+
 /// 获取播放器状态
 #[tauri::command]
 async fn get_player_state(_state: tauri::State<'_, AppState>) -> Result<PlayerState, String> {
@@ -91,7 +526,71 @@ async fn get_player_state(_state: tauri::State<'_, AppState>) -> Result<PlayerSt
 async fn get_playlist(_state: tauri::State<'_, AppState>) -> Result<Vec<SongInfo>, String> {
     let player_instance = get_player_instance().await?;
     let player_state_guard = player_instance.lock().await;
-    Ok(player_state_guard.player.get_playlist())
+    Ok(player_state_guard.player.get_playlist().into_iter().map(SongInfo::without_cover).collect())
+}
+
+/// 在播放列表和音乐库中搜索（标题、艺术家、专辑、文件名，不区分大小写和重音，支持拼音和拼写容错），按相关度返回
+#[tauri::command]
+async fn search_songs(query: String, limit: Option<usize>) -> Result<Vec<search::SearchResult>, String> {
+    let player_instance = get_player_instance().await?;
+    let player = player_instance.lock().await.player.clone();
+    let playlist = player.get_playlist();
+    tokio::task::spawn_blocking(move || search::search(&query, &playlist, limit.unwrap_or(100)))
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// 获取播放列表条目的封面（data URL），song_id 为播放列表索引
+/// 播放列表和 PlaylistUpdated 事件不携带封面，前端显示时再按需获取
+#[tauri::command]
+async fn get_album_cover(song_id: usize) -> Result<Option<String>, String> {
+    let player_instance = get_player_instance().await?;
+    let player = player_instance.lock().await.player.clone();
+    let song = player.get_song(song_id).ok_or_else(|| "无效的歌曲索引".to_string())?;
+    if let Some(cover) = song.album_cover.clone().or_else(|| song.video_thumbnail.clone()) {
+        return Ok(Some(cover));
+    }
+    // 低内存模式下条目不保留封面，重新读取
+    tokio::task::spawn_blocking(move || {
+        let song = low_memory::hydrate(&song);
+        song.album_cover.or(song.video_thumbnail)
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// 获取低内存模式设置
+#[tauri::command]
+async fn get_low_memory_settings() -> Result<low_memory::LowMemorySettings, String> {
+    Ok(low_memory::settings())
+}
+
+/// 设置低内存模式；开启时立即精简当前播放列表（关闭后已精简的条目仍需按需补全）
+#[tauri::command]
+async fn set_low_memory_settings(settings: low_memory::LowMemorySettings) -> Result<(), String> {
+    let enabled = settings.enabled;
+    low_memory::set_settings(settings).map_err(|e| e.to_string())?;
+    if enabled {
+        let player_instance = get_player_instance().await?;
+        let player_state_guard = player_instance.lock().await;
+        player_state_guard
+            .player
+            .send_command(PlayerCommand::CompactPlaylist)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 获取播放列表中指定条目的完整信息（低内存模式下按需补全封面和歌词）
+#[tauri::command]
+async fn get_song_details(indices: Vec<usize>) -> Result<Vec<SongInfo>, String> {
+    let player_instance = get_player_instance().await?;
+    let playlist = player_instance.lock().await.player.get_playlist();
+    let songs: Vec<SongInfo> = indices.iter().filter_map(|&idx| playlist.get(idx).cloned()).collect();
+    tokio::task::spawn_blocking(move || songs.iter().map(low_memory::hydrate).collect())
+        .await
+        .map_err(|e| e.to_string())
 }
 
 /// 获取当前播放索引
@@ -102,138 +601,2521 @@ async fn get_current_index(_state: tauri::State<'_, AppState>) -> Result<Option<
     Ok(player_state_guard.player.get_current_index())
 }
 
-/// 获取播放模式
+/// 接下来要播放的曲目（最多 limit 首，默认 50）：随机播放时为本轮打乱后的剩余顺序，
+/// 其他模式为列表中的后续曲目，均不包括被时长过滤的曲目
+#[tauri::command]
+async fn get_up_next(limit: Option<usize>, _state: tauri::State<'_, AppState>) -> Result<Vec<player_safe::UpNextEntry>, String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    Ok(player_state_guard.player.get_up_next(limit.unwrap_or(50)))
+}
+
+/// 获取播放模式
+#[tauri::command]
+async fn get_play_mode(_state: tauri::State<'_, AppState>) -> Result<PlayMode, String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    Ok(player_state_guard.player.get_play_mode())
+}
+
+/// 播放
+#[tauri::command]
+async fn play(_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::Play)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 暂停
+#[tauri::command]
+async fn pause(_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::Pause)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 下一曲
+#[tauri::command]
+async fn next(_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::Next)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 上一曲
+#[tauri::command]
+async fn previous(_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::Previous)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 设置当前歌曲
+#[tauri::command]
+async fn set_song(_state: State<'_, AppState>, index: usize) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::SetSong(index))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 将新添加的歌曲写入音乐库索引（失败只记录日志，不影响添加）
+fn index_in_library(songs: &[SongInfo]) {
+    // 容器中的音轨/章节是同一文件的虚拟曲目，串流URL不是本地文件，音乐库只记录本地文件本身
+    let songs: Vec<SongInfo> = songs
+        .iter()
+        .filter(|s| s.segment.is_none() && !s.path.contains("://"))
+        .cloned()
+        .collect();
+    if songs.is_empty() {
+        return;
+    }
+    if let Err(e) = library::with_library(|lib| lib.upsert_tracks(&songs)) {
+        eprintln!("写入音乐库失败: {}", e);
+    }
+}
+
+/// 从文件创建播放列表条目，多音轨/多章节的 MKA 文件、CUE 文件和内嵌 CUE 的音频文件展开为多个条目
+fn songs_from_path(path: &std::path::Path) -> anyhow::Result<Vec<SongInfo>> {
+    if cue::is_cue_path(path) {
+        return cue::expand(path);
+    }
+    if matroska::is_matroska_audio(path) {
+        match matroska::expand(path) {
+            Ok(Some(songs)) => return Ok(songs),
+            Ok(None) => {}
+            Err(e) => eprintln!("读取MKA音轨/章节失败，按单个文件处理 {}: {}", path.display(), e),
+        }
+    }
+    let tags = FileTags::read(path);
+    let song = SongInfo::from_tags(path, &tags)?;
+    Ok(cue::expand_embedded(&song, &tags).or_else(|| cue::expand_sidecar(&song)).unwrap_or_else(|| vec![song]))
+}
+
+/// 添加歌曲
+#[tauri::command]
+async fn add_song(path: String, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    // 创建SongInfo对象代替直接使用PathBuf
+    match songs_from_path(&PathBuf::from(&path)) {
+        Ok(songs) => {
+            index_in_library(&songs);
+            player_state_guard
+                .player
+                .send_command(PlayerCommand::Enqueue(songs))
+                .await
+                .map_err(|e| e.to_string())
+        }
+        Err(e) => Err(format!("无法从路径创建歌曲信息: {}", e)),
+    }
+}
+
+/// 是否监听剪贴板中的音频文件路径/串流URL
+#[tauri::command]
+async fn get_clipboard_watch() -> Result<bool, String> {
+    Ok(clipboard_watch::is_enabled())
+}
+
+/// 开启/关闭剪贴板监听，识别到媒体时发送 ClipboardMediaDetected 事件
+#[tauri::command]
+async fn set_clipboard_watch(enabled: bool) -> Result<(), String> {
+    clipboard_watch::set_enabled(enabled).map_err(|e| format!("保存剪贴板监听设置失败: {}", e))
+}
+
+/// 把剪贴板中的音频文件和串流URL加入播放列表（按添加策略），返回加入的条目数
+#[tauri::command]
+async fn add_from_clipboard<R: Runtime>(
+    app_handle: AppHandle<R>,
+    _state: tauri::State<'_, AppState>,
+) -> Result<usize, String> {
+    let media = clipboard_watch::read_current(&app_handle);
+    if media.is_empty() {
+        return Err("剪贴板中没有可识别的音频文件或串流URL".to_string());
+    }
+    let songs = tokio::task::spawn_blocking(move || {
+        let mut songs = Vec::new();
+        for item in media {
+            match item {
+                clipboard_watch::ClipboardMedia::File(path) => match songs_from_path(std::path::Path::new(&path)) {
+                    Ok(entries) => songs.extend(entries),
+                    Err(e) => eprintln!("处理剪贴板中的文件失败 {}: {}", path, e),
+                },
+                clipboard_watch::ClipboardMedia::Url(url) => songs.push(SongInfo::from_url(&url)),
+            }
+        }
+        songs
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let count = songs.len();
+    if count > 0 {
+        index_in_library(&songs);
+        let player_instance = get_player_instance().await?;
+        let player_state_guard = player_instance.lock().await;
+        player_state_guard
+            .player
+            .send_command(PlayerCommand::Enqueue(songs))
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(count)
+}
+
+/// 获取 MIDI/KAR 播放设置
+#[tauri::command]
+async fn get_midi_options() -> Result<midi::MidiOptions, String> {
+    Ok(midi::load_options())
+}
+
+/// 设置 MIDI/KAR 使用的 SoundFont，为空时使用随应用打包的音色库
+#[tauri::command]
+async fn set_midi_options(options: midi::MidiOptions) -> Result<(), String> {
+    midi::save_options(&options).map_err(|e| format!("保存MIDI设置失败: {}", e))
+}
+
+/// 检查 MIDI/KAR 播放所需的 SoundFont，返回将要使用的音色库路径，没有可用的音色库时返回提示用户选择的错误
+#[tauri::command]
+async fn check_midi_sound_font() -> Result<String, String> {
+    midi::check_sound_font()
+        .map(|path| path.to_string_lossy().into_owned())
+        .map_err(|e| e.to_string())
+}
+
+/// 获取 MKA 文件中的音轨和章节
+#[tauri::command]
+async fn get_container_info(path: String) -> Result<matroska::MatroskaInfo, String> {
+    tokio::task::spawn_blocking(move || matroska::probe(std::path::Path::new(&path)))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("读取容器信息失败: {}", e))
+}
+
+/// 把 MKA 文件展开为可单独选择的播放列表条目（每个音轨/章节一条），前端选择后通过 add_container_entries 加入
+#[tauri::command]
+async fn list_container_entries(path: String) -> Result<Vec<SongInfo>, String> {
+    tokio::task::spawn_blocking(move || songs_from_path(std::path::Path::new(&path)))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("读取容器信息失败: {}", e))
+}
+
+/// 加入选中的容器条目（来自 list_container_entries）
+#[tauri::command]
+async fn add_container_entries(entries: Vec<SongInfo>, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::Enqueue(entries))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 设置添加歌曲时的处理策略（追加 / 立即播放 / 替换播放列表），对所有添加入口生效
+#[tauri::command]
+async fn set_enqueue_policy(policy: EnqueuePolicy, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::SetEnqueuePolicy(policy))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取添加歌曲时的处理策略
+#[tauri::command]
+async fn get_enqueue_policy(_state: tauri::State<'_, AppState>) -> Result<EnqueuePolicy, String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    Ok(player_state_guard.player.get_enqueue_policy())
+}
+
+/// 继续播放整张专辑：把音乐库中当前歌曲所在专辑的后续曲目按音轨顺序排到当前歌曲之后
+#[tauri::command]
+async fn play_album_of_current(_state: tauri::State<'_, AppState>) -> Result<usize, String> {
+    let player_instance = get_player_instance().await?;
+    let player = player_instance.lock().await.player.clone();
+    let playlist = player.get_playlist();
+    let current_index = player
+        .get_current_index()
+        .ok_or_else(|| "当前没有播放的歌曲".to_string())?;
+    let current = playlist
+        .get(current_index)
+        .cloned()
+        .ok_or_else(|| "当前没有播放的歌曲".to_string())?;
+    let album = current
+        .album
+        .clone()
+        .filter(|a| !a.trim().is_empty())
+        .ok_or_else(|| "当前歌曲没有专辑信息".to_string())?;
+
+    // 查询音乐库和读取每首曲目的标签在后台线程中进行，不占用播放器锁
+    let songs = tokio::task::spawn_blocking(move || -> Result<Vec<SongInfo>, String> {
+        let tracks = library::with_library(|lib| lib.album_tracks(&album, current.artist.as_deref()))?;
+
+        // 只取当前歌曲之后的曲目；当前歌曲不在库中时按音轨号比较
+        let rest: Vec<_> = match tracks.iter().position(|t| t.path == current.path) {
+            Some(pos) => tracks[pos + 1..].to_vec(),
+            None => {
+                let current_key = (current.disc_number.unwrap_or(1), current.track_number.unwrap_or(0));
+                tracks
+                    .into_iter()
+                    .filter(|t| (t.disc_number.unwrap_or(1), t.track_number.unwrap_or(0)) > current_key)
+                    .collect()
+            }
+        };
+
+        let mut songs = Vec::new();
+        for track in rest {
+            match SongInfo::from_path(&PathBuf::from(&track.path)) {
+                Ok(song) => songs.push(song),
+                Err(e) => eprintln!("跳过无法读取的专辑曲目 {}: {}", track.path, e),
+            }
+        }
+        Ok(songs)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let count = songs.len();
+    if count > 0 {
+        player_instance
+            .lock()
+            .await
+            .player
+            .send_command(PlayerCommand::InsertSongs {
+                index: current_index + 1,
+                songs,
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(count)
+}
+
+/// 语音指令的处理结果
+#[derive(serde::Serialize)]
+struct VoiceCommandResult {
+    intent: voice::VoiceIntent,
+    message: String,
+}
+
+/// 解析简单的自然语言指令（"play something by Queen"、"volume up"、"下一首"）并执行
+/// 点播时先在播放列表中查找，找不到再从音乐库查找并插入到当前歌曲之后播放
+#[tauri::command]
+async fn handle_voice_command(text: String, _state: tauri::State<'_, AppState>) -> Result<VoiceCommandResult, String> {
+    use rand::seq::SliceRandom;
+    use voice::VoiceIntent;
+
+    let intent = voice::parse(&text).ok_or_else(|| format!("无法识别的指令: {}", text))?;
+    let player_instance = get_player_instance().await?;
+    let player = player_instance.lock().await.player.clone();
+    let snapshot = player.get_state_nonblocking();
+
+    let (commands, message) = match &intent {
+        VoiceIntent::Play => (vec![PlayerCommand::Play], "继续播放".to_string()),
+        VoiceIntent::Pause => (vec![PlayerCommand::Pause], "已暂停".to_string()),
+        VoiceIntent::Stop => (vec![PlayerCommand::Stop], "已停止".to_string()),
+        VoiceIntent::Next => (vec![PlayerCommand::Next], "下一首".to_string()),
+        VoiceIntent::Previous => (vec![PlayerCommand::Previous], "上一首".to_string()),
+        // 静音保留原音量，取消静音后恢复
+        VoiceIntent::Mute => (vec![PlayerCommand::SetMuted(true)], "已静音".to_string()),
+        VoiceIntent::VolumeUp | VoiceIntent::VolumeDown | VoiceIntent::SetVolume { .. } => {
+            // 与 set_volume 相同，范围 0-2（超过 1 为增益）
+            let volume = match &intent {
+                VoiceIntent::VolumeUp => snapshot.volume + voice::VOLUME_STEP,
+                VoiceIntent::VolumeDown => snapshot.volume - voice::VOLUME_STEP,
+                VoiceIntent::SetVolume { volume } => *volume,
+                _ => snapshot.volume,
+            }
+            .clamp(0.0, 2.0);
+            (vec![PlayerCommand::SetVolume(volume)], format!("音量 {}%", (volume * 100.0).round()))
+        }
+        VoiceIntent::SetPlayMode { mode } => (vec![PlayerCommand::SetPlayMode(*mode)], format!("播放模式: {:?}", mode)),
+        VoiceIntent::PlayQuery { query, random } => {
+            let mut matches: Vec<usize> = snapshot
+                .playlist
+                .iter()
+                .enumerate()
+                .filter(|(_, song)| query.matches(song))
+                .map(|(idx, _)| idx)
+                .collect();
+            if !matches.is_empty() {
+                if *random {
+                    // 随机挑选时优先选择未被时长过滤排除的曲目
+                    let filter = skip_filter::settings();
+                    matches.shuffle(&mut rand::thread_rng());
+                    matches.sort_by_key(|&idx| !filter.allows_song(&snapshot.playlist[idx]));
+                }
+                let index = matches[0];
+                let title = snapshot.playlist[index].title.clone().unwrap_or_default();
+                (vec![PlayerCommand::SetSong(index)], format!("播放: {}", title))
+            } else {
+                let tracks = library::with_library(|lib| {
+                    lib.find_tracks(
+                        query.title.as_deref(),
+                        query.artist.as_deref(),
+                        query.album.as_deref(),
+                        query.any.as_deref(),
+                        50,
+                    )
+                })?;
+                let random = *random;
+                let mut songs = tokio::task::spawn_blocking(move || {
+                    tracks
+                        .iter()
+                        .filter_map(|track| SongInfo::from_path(std::path::Path::new(&track.path)).ok())
+                        .collect::<Vec<_>>()
+                })
+                .await
+                .map_err(|e| e.to_string())?;
+                if songs.is_empty() {
+                    return Err(format!("没有找到匹配的曲目: {}", text));
+                }
+                if random {
+                    let filter = skip_filter::settings();
+                    songs.retain(|song| filter.allows_song(song));
+                    if songs.is_empty() {
+                        return Err(format!("匹配的曲目都被时长过滤排除: {}", text));
+                    }
+                    songs.shuffle(&mut rand::thread_rng());
+                }
+                let index = snapshot.current_index.map(|idx| idx + 1).unwrap_or(snapshot.playlist.len());
+                let message = format!("从音乐库找到{}首，开始播放: {}", songs.len(), songs[0].title.clone().unwrap_or_default());
+                (vec![PlayerCommand::InsertSongs { index, songs }, PlayerCommand::SetSong(index)], message)
+            }
+        }
+    };
+
+    for command in commands {
+        player.send_command(command).await.map_err(|e| e.to_string())?;
+    }
+    println!("🎙️ 语音指令 \"{}\": {}", text, message);
+    Ok(VoiceCommandResult { intent, message })
+}
+
+/// 分析音乐库中尚未分析BPM的曲目，返回本次分析的曲目数
+#[tauri::command]
+async fn analyze_library_bpm(limit: Option<usize>) -> Result<usize, String> {
+    let paths = library::with_library(|lib| lib.tracks_missing_bpm(limit.unwrap_or(200)))?;
+    let count = paths.len();
+    tokio::task::spawn_blocking(move || {
+        for path in paths {
+            let bpm = bpm::detect(std::path::Path::new(&path)).unwrap_or(0.0);
+            if let Err(e) = library::with_library(|lib| lib.set_bpm(&path, bpm)) {
+                eprintln!("保存BPM失败 {}: {}", path, e);
+            }
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(count)
+}
+
+/// 从音乐库中挑选BPM在 target_bpm ± tolerance 范围内的曲目（倍速/半速也算匹配），
+/// 按节拍速度递增排列后加入播放队列，返回加入的曲目数
+#[tauri::command]
+async fn build_tempo_queue(
+    target_bpm: f32,
+    tolerance: f32,
+    _state: tauri::State<'_, AppState>,
+) -> Result<usize, String> {
+    if target_bpm <= 0.0 {
+        return Err("目标BPM必须大于0".to_string());
+    }
+    let tolerance = tolerance.abs();
+
+    // 查询音乐库和读取匹配曲目的标签在后台线程中进行
+    let songs = tokio::task::spawn_blocking(move || -> Result<Vec<SongInfo>, String> {
+        let tracks = library::with_library(|lib| lib.tracks_with_bpm())?;
+
+        let mut matched: Vec<(f32, String)> = tracks
+            .into_iter()
+            .filter_map(|t| {
+                let bpm = bpm::nearest_to(t.bpm?, target_bpm);
+                ((bpm - target_bpm).abs() <= tolerance).then_some((bpm, t.path))
+            })
+            .collect();
+        // 按折算后的BPM排序，相邻曲目速度变化最小
+        matched.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut songs = Vec::new();
+        for (_, path) in matched {
+            match SongInfo::from_path(&PathBuf::from(&path)) {
+                Ok(song) => songs.push(song),
+                Err(e) => eprintln!("跳过无法读取的曲目 {}: {}", path, e),
+            }
+        }
+        Ok(songs)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let count = songs.len();
+    if count > 0 {
+        let player_instance = get_player_instance().await?;
+        let player_state_guard = player_instance.lock().await;
+        player_state_guard
+            .player
+            .send_command(PlayerCommand::Enqueue(songs))
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(count)
+}
+
+/// 扫描文件夹并建立/更新音乐库索引（未修改的文件跳过），进度通过 library-scan 事件发送
+/// folders 为空时重新扫描已添加的文件夹
+#[tauri::command]
+async fn library_scan<R: Runtime>(
+    app_handle: AppHandle<R>,
+    folders: Option<Vec<String>>,
+) -> Result<library_scan::ScanSummary, String> {
+    tokio::task::spawn_blocking(move || {
+        let summary = library_scan::scan(folders.unwrap_or_default(), |event| {
+            if let Err(e) = app_handle.emit("library-scan", event) {
+                eprintln!("发送扫描事件失败: {:?}", e);
+            }
+        })
+        .map_err(|e| e.to_string())?;
+        // 扫描可能添加了新文件夹
+        library_watch::refresh();
+        Ok(summary)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 按 EBU R128 分析文件响度并写入音乐库（可选同时写入 ReplayGain 标签），进度通过 loudness-analysis 事件发送
+/// paths 为空时分析音乐库中所有尚未测量的曲目
+#[tauri::command]
+async fn analyze_loudness<R: Runtime>(
+    app_handle: AppHandle<R>,
+    paths: Option<Vec<String>>,
+    write_tags: Option<bool>,
+) -> Result<loudness::LoudnessSummary, String> {
+    tokio::task::spawn_blocking(move || {
+        loudness::analyze(paths.unwrap_or_default(), write_tags.unwrap_or(false), |event| {
+            if let Err(e) = app_handle.emit("loudness-analysis", event) {
+                eprintln!("发送响度分析事件失败: {:?}", e);
+            }
+        })
+        .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 开启/关闭音乐库文件夹监听（文件变化时自动更新音乐库）
+#[tauri::command]
+async fn set_library_watch(enabled: bool) -> Result<(), String> {
+    let mut folders = library_scan::load_folders();
+    folders.watch = enabled;
+    library_scan::save_folders(&folders).map_err(|e| e.to_string())?;
+    library_watch::refresh();
+    Ok(())
+}
+
+/// 按条件查询音乐库，不读取文件
+#[tauri::command]
+async fn library_query(query: library::TrackQuery) -> Result<Vec<library::LibraryTrack>, String> {
+    library::with_library(|lib| lib.query_tracks(&query))
+}
+
+/// 音乐库统计（曲目、艺术家、专辑数和总时长）
+#[tauri::command]
+async fn library_stats() -> Result<library::LibraryStats, String> {
+    library::with_library(|lib| lib.stats())
+}
+
+/// 获取已添加的音乐库文件夹及上次扫描时间
+#[tauri::command]
+async fn get_library_folders() -> Result<library_scan::ScanFolders, String> {
+    Ok(library_scan::load_folders())
+}
+
+/// 把评分/收藏同步到播放列表中的对应条目
+async fn apply_rating(path: String, rating: Option<u8>, favorite: bool) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::ApplyRating { path, rating, favorite })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 播放列表中指定条目的文件路径（song_id 为播放列表索引）
+async fn playlist_song_path(song_id: usize) -> Result<String, String> {
+    let player_instance = get_player_instance().await?;
+    let player = player_instance.lock().await.player.clone();
+    player
+        .get_song(song_id)
+        .map(|song| song.path)
+        .ok_or_else(|| "无效的歌曲索引".to_string())
+}
+
+/// 设置播放列表中指定条目的评分（0-5星，0 为取消评分）
+#[tauri::command]
+async fn set_rating(song_id: usize, rating: u8) -> Result<(), String> {
+    if rating > 5 {
+        return Err("评分必须在 0 到 5 之间".to_string());
+    }
+    let path = playlist_song_path(song_id).await?;
+    let rating = (rating > 0).then_some(rating);
+    let (rating, favorite) = library::with_library(|lib| lib.set_rating(&path, rating))?;
+    apply_rating(path, rating, favorite).await
+}
+
+/// 切换播放列表中指定条目的收藏状态，返回切换后是否已收藏
+#[tauri::command]
+async fn toggle_favorite(song_id: usize) -> Result<bool, String> {
+    let path = playlist_song_path(song_id).await?;
+    let (rating, favorite) = library::with_library(|lib| lib.toggle_favorite(&path))?;
+    apply_rating(path, rating, favorite).await?;
+    Ok(favorite)
+}
+
+/// 获取收藏的曲目
+#[tauri::command]
+async fn get_favorites() -> Result<Vec<library::RatedTrack>, String> {
+    library::with_library(|lib| lib.favorites())
+}
+
+/// 播放统计（播放次数、收听时长），按播放次数排序；给出路径时只返回该曲目
+#[tauri::command]
+async fn get_track_stats(path: Option<String>, limit: Option<usize>) -> Result<Vec<library::TrackStats>, String> {
+    library::with_library(|lib| lib.track_stats(path.as_deref(), limit.unwrap_or(100)))
+}
+
+/// 播放次数最多的艺术家
+#[tauri::command]
+async fn get_top_artists(limit: Option<usize>) -> Result<Vec<library::ArtistStats>, String> {
+    library::with_library(|lib| lib.top_artists(limit.unwrap_or(20)))
+}
+
+/// 累计收听时长（秒）
+#[tauri::command]
+async fn get_total_listening_time() -> Result<u64, String> {
+    library::with_library(|lib| lib.total_listen_secs())
+}
+
+/// 校验音乐库中所有曲目的文件内容，进度和发现的问题通过 library-verify 事件逐条发送
+#[tauri::command]
+async fn verify_library<R: Runtime>(app_handle: AppHandle<R>) -> Result<library_verify::VerifySummary, String> {
+    tokio::task::spawn_blocking(move || {
+        library_verify::verify(|event| {
+            if let Err(e) = app_handle.emit("library-verify", event) {
+                eprintln!("发送校验事件失败: {:?}", e);
+            }
+        })
+        .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 获取音乐库维护计划设置
+#[tauri::command]
+async fn get_maintenance_settings() -> Result<maintenance::MaintenanceSettings, String> {
+    Ok(maintenance::load_settings())
+}
+
+/// 设置音乐库维护计划（时段、要运行的任务）
+#[tauri::command]
+async fn set_maintenance_settings(settings: maintenance::MaintenanceSettings) -> Result<(), String> {
+    maintenance::save_settings(&settings).map_err(|e| e.to_string())
+}
+
+/// 获取维护任务状态及最近一次运行的结果
+#[tauri::command]
+async fn get_maintenance_status() -> Result<maintenance::MaintenanceStatus, String> {
+    Ok(maintenance::status())
+}
+
+/// 立即运行音乐库维护，进度通过 maintenance 事件发送
+#[tauri::command]
+async fn run_maintenance_now<R: Runtime>(app_handle: AppHandle<R>) -> Result<maintenance::MaintenanceStatus, String> {
+    tokio::task::spawn_blocking(move || {
+        maintenance::run(|event| {
+            if let Err(e) = app_handle.emit("maintenance", event) {
+                eprintln!("发送维护事件失败: {:?}", e);
+            }
+        })
+        .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 取消正在运行的维护任务，没有任务在运行时返回 false
+#[tauri::command]
+async fn cancel_maintenance() -> Result<bool, String> {
+    Ok(maintenance::cancel())
+}
+
+/// 清除曲目的校验值（不指定路径时清除全部），用于确认文件修改是预期的，下次校验时重新记录
+#[tauri::command]
+async fn reset_track_checksum(path: Option<String>) -> Result<(), String> {
+    library::with_library(|lib| lib.clear_checksum(path.as_deref()))
+}
+
+/// 列出文件在各元数据来源（文件中的各个标签和文件名）中的取值及冲突字段，供"修正元数据"界面选择
+#[tauri::command]
+async fn get_metadata_candidates(path: String) -> Result<player_fixed::MetadataCandidates, String> {
+    tokio::task::spawn_blocking(move || SongInfo::metadata_candidates(std::path::Path::new(&path)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取标签写入保护设置（只读音乐库）
+#[tauri::command]
+async fn get_tag_write_settings() -> Result<tag_write::TagWriteSettings, String> {
+    Ok(tag_write::settings())
+}
+
+/// 设置标签写入保护（只读音乐库）
+#[tauri::command]
+async fn set_tag_write_settings(settings: tag_write::TagWriteSettings) -> Result<(), String> {
+    tag_write::set_settings(settings).map_err(|e| e.to_string())
+}
+
+/// 获取按时长自动跳过曲目的设置
+#[tauri::command]
+async fn get_skip_filter_settings() -> Result<skip_filter::SkipFilterSettings, String> {
+    Ok(skip_filter::settings())
+}
+
+/// 设置按时长自动跳过曲目（随机播放、随机挑选时跳过过短/过长的文件）
+#[tauri::command]
+async fn set_skip_filter_settings(settings: skip_filter::SkipFilterSettings) -> Result<(), String> {
+    skip_filter::set_settings(settings).map_err(|e| e.to_string())
+}
+
+/// 获取电台主持人模式设置
+#[tauri::command]
+async fn get_radio_host_settings() -> Result<radio_host::RadioHostSettings, String> {
+    Ok(radio_host::settings())
+}
+
+/// 设置电台主持人模式（自然切歌时用系统语音合成播报上一首/下一首，播报期间压低音乐音量）
+#[tauri::command]
+async fn set_radio_host_settings(settings: radio_host::RadioHostSettings) -> Result<(), String> {
+    radio_host::set_settings(settings).map_err(|e| e.to_string())
+}
+
+/// 获取快进/快退默认步长
+#[tauri::command]
+async fn get_seek_step_settings() -> Result<seek_step::SeekStepSettings, String> {
+    Ok(seek_step::settings())
+}
+
+/// 设置快进/快退默认步长（秒）
+#[tauri::command]
+async fn set_seek_step_settings(settings: seek_step::SeekStepSettings) -> Result<(), String> {
+    seek_step::set_settings(settings).map_err(|e| e.to_string())
+}
+
+/// 获取毫秒级进度事件设置
+#[tauri::command]
+async fn get_progress_settings() -> Result<progress::ProgressSettings, String> {
+    Ok(progress::settings())
+}
+
+/// 设置毫秒级进度事件（PositionUpdate）的发送间隔，0 表示关闭；下一秒内生效
+#[tauri::command]
+async fn set_progress_settings(settings: progress::ProgressSettings) -> Result<(), String> {
+    progress::set_settings(settings).map_err(|e| e.to_string())
+}
+
+/// 获取 ReplayGain 音量标准化设置
+#[tauri::command]
+async fn get_replaygain_settings() -> Result<replaygain::ReplayGainSettings, String> {
+    Ok(replaygain::settings())
+}
+
+/// 设置 ReplayGain 音量标准化（模式、前级增益、防削波），从下一首或下一次跳转开始生效
+#[tauri::command]
+async fn set_replaygain_settings(settings: replaygain::ReplayGainSettings) -> Result<(), String> {
+    replaygain::set_settings(settings).map_err(|e| e.to_string())
+}
+
+/// 切换 ReplayGain 模式（off/track/album），其余设置不变
+#[tauri::command]
+async fn set_replaygain_mode(mode: replaygain::ReplayGainMode) -> Result<(), String> {
+    let settings = replaygain::ReplayGainSettings {
+        mode,
+        ..replaygain::settings()
+    };
+    replaygain::set_settings(settings).map_err(|e| e.to_string())
+}
+
+/// 获取音乐库排序规则
+#[tauri::command]
+async fn get_collation_settings() -> Result<collation::CollationSettings, String> {
+    Ok(collation::settings())
+}
+
+/// 设置音乐库排序规则（拼音首字母、忽略冠词、自然排序），并重建排序键，返回更新的曲目数
+#[tauri::command]
+async fn set_collation_settings(settings: collation::CollationSettings) -> Result<usize, String> {
+    collation::set_settings(settings).map_err(|e| e.to_string())?;
+    tokio::task::spawn_blocking(|| library::with_library(|lib| lib.rebuild_sort_keys()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 按排序规则分页列出音乐库曲目
+#[tauri::command]
+async fn list_library_tracks(
+    order: Option<library::TrackOrder>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<Vec<library::LibraryTrack>, String> {
+    library::with_library(|lib| lib.tracks_sorted(order.unwrap_or_default(), offset.unwrap_or(0), limit.unwrap_or(500)))
+}
+
+/// 批量写入文件标签；dry_run 为 true 时只返回每个文件会变化的字段，不修改文件
+/// 写入成功后更新播放列表中的对应条目
+#[tauri::command]
+async fn batch_edit_tags(
+    edits: Vec<tag_write::PathTagEdit>,
+    dry_run: bool,
+    _state: tauri::State<'_, AppState>,
+) -> Result<tag_write::BatchResult, String> {
+    let result = {
+        let edits = edits.clone();
+        tokio::task::spawn_blocking(move || tag_write::batch(&edits, dry_run))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())?
+    };
+    if result.dry_run {
+        return Ok(result);
+    }
+
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    for (item, file) in edits.iter().zip(&result.files) {
+        if file.written {
+            player_state_guard
+                .player
+                .send_command(PlayerCommand::ApplyMetadata {
+                    path: item.path.clone(),
+                    metadata: item.edit.as_metadata(),
+                })
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(result)
+}
+
+/// 编辑播放列表中指定条目的标签并写回文件，重新读取后更新播放列表和音乐库
+/// （播放器发送 SongMetadataUpdated 事件）
+#[tauri::command]
+async fn update_tags(
+    song_id: usize,
+    edit: tag_write::TagEdit,
+    _state: tauri::State<'_, AppState>,
+) -> Result<Vec<tag_write::FieldChange>, String> {
+    let player_instance = get_player_instance().await?;
+    let path = {
+        let player_state_guard = player_instance.lock().await;
+        let playlist = player_state_guard.player.get_playlist();
+        let song = playlist.get(song_id).ok_or_else(|| "无效的歌曲索引".to_string())?;
+        if song.path.contains("://") || song.media_type != Some(crate::player_fixed::MediaType::Audio) {
+            return Err("只能编辑本地音频文件的标签".to_string());
+        }
+        song.path.clone()
+    };
+
+    let (changes, song) = tokio::task::spawn_blocking(move || -> anyhow::Result<_> {
+        let path = PathBuf::from(&path);
+        let changes = tag_write::write(&path, &edit)?;
+        Ok((changes, SongInfo::from_path(&path)?))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+    if changes.is_empty() {
+        return Ok(changes);
+    }
+
+    index_in_library(std::slice::from_ref(&song));
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::RefreshSong(song))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(changes)
+}
+
+/// 把图片（文件路径或图片数据）作为封面嵌入播放列表中指定条目的文件，
+/// 更新封面缓存和播放列表中该文件的所有条目（播放器发送 SongMetadataUpdated 事件）
+#[tauri::command]
+async fn set_album_cover(
+    song_id: usize,
+    image: tag_write::CoverImage,
+    _state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let path = {
+        let player_state_guard = player_instance.lock().await;
+        let playlist = player_state_guard.player.get_playlist();
+        let song = playlist.get(song_id).ok_or_else(|| "无效的歌曲索引".to_string())?;
+        if song.path.contains("://") || song.media_type != Some(crate::player_fixed::MediaType::Audio) {
+            return Err("只能为本地音频文件嵌入封面".to_string());
+        }
+        song.path.clone()
+    };
+
+    let cover = {
+        let path = path.clone();
+        tokio::task::spawn_blocking(move || -> anyhow::Result<String> {
+            let data = image.load()?;
+            tag_write::write_cover(&PathBuf::from(&path), &data)?;
+            cover_cache::data_url(&data)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?
+    };
+
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::ApplyCover { path, cover: Some(cover) })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取在线封面设置
+#[tauri::command]
+async fn get_cover_fetch_settings() -> Result<cover_fetch::CoverFetchSettings, String> {
+    Ok(cover_fetch::settings())
+}
+
+/// 设置在线封面获取（是否开启、来源顺序）
+#[tauri::command]
+async fn set_cover_fetch_settings(settings: cover_fetch::CoverFetchSettings) -> Result<(), String> {
+    cover_fetch::set_settings(settings).map_err(|e| e.to_string())
+}
+
+/// 按艺术家和专辑在线查找播放列表中指定条目的封面，找到时更新该文件的所有条目并返回封面
+/// （播放器发送 SongMetadataUpdated 事件），不修改文件
+#[tauri::command]
+async fn fetch_cover(song_id: usize, _state: tauri::State<'_, AppState>) -> Result<Option<String>, String> {
+    let player_instance = get_player_instance().await?;
+    let song = {
+        let player_state_guard = player_instance.lock().await;
+        player_state_guard
+            .player
+            .get_playlist()
+            .get(song_id)
+            .cloned()
+            .ok_or_else(|| "无效的歌曲索引".to_string())?
+    };
+    let (Some(artist), Some(album)) = (song.artist.as_deref(), song.album.as_deref()) else {
+        return Err("缺少艺术家或专辑信息，无法在线查找封面".to_string());
+    };
+    let Some(image) = cover_fetch::fetch(artist, album).await.map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+    let cover = tokio::task::spawn_blocking(move || cover_cache::data_url(&image))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::ApplyCover {
+            path: song.path,
+            cover: Some(cover.clone()),
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(Some(cover))
+}
+
+/// 为播放列表中没有封面的曲目批量在线查找封面（同一专辑只查一次），进度通过 cover-fetch 事件发送
+#[tauri::command]
+async fn find_missing_covers<R: Runtime>(app_handle: AppHandle<R>) -> Result<cover_fetch::CoverFetchSummary, String> {
+    let player_instance = get_player_instance().await?;
+    let playlist = player_instance.lock().await.player.get_playlist();
+
+    // 找到的封面交给单独的任务缩放并更新播放列表，查找不必等待
+    let (cover_tx, mut cover_rx) = tokio::sync::mpsc::unbounded_channel::<(Vec<String>, Vec<u8>)>();
+    let applier = tokio::spawn(async move {
+        while let Some((paths, image)) = cover_rx.recv().await {
+            let cover = match tokio::task::spawn_blocking(move || cover_cache::data_url(&image)).await {
+                Ok(Ok(cover)) => cover,
+                Ok(Err(e)) => {
+                    eprintln!("处理在线封面失败: {}", e);
+                    continue;
+                }
+                Err(_) => continue,
+            };
+            let player_state_guard = player_instance.lock().await;
+            for path in paths {
+                let command = PlayerCommand::ApplyCover {
+                    path,
+                    cover: Some(cover.clone()),
+                };
+                if let Err(e) = player_state_guard.player.send_command(command).await {
+                    eprintln!("更新封面失败: {}", e);
+                }
+            }
+        }
+    });
+
+    let summary = cover_fetch::find_missing(
+        &playlist,
+        |paths, image| {
+            let _ = cover_tx.send((paths, image));
+        },
+        |event| {
+            if let Err(e) = app_handle.emit("cover-fetch", event) {
+                eprintln!("发送封面查找事件失败: {:?}", e);
+            }
+        },
+    )
+    .await;
+    drop(cover_tx);
+    let _ = applier.await;
+    summary.map_err(|e| e.to_string())
+}
+
+/// 获取在线歌词设置
+#[tauri::command]
+async fn get_lyrics_fetch_settings() -> Result<lyrics_provider::LyricsFetchSettings, String> {
+    Ok(lyrics_provider::settings())
+}
+
+/// 设置在线歌词的保存位置（同名歌词文件或文件标签）
+#[tauri::command]
+async fn set_lyrics_fetch_settings(settings: lyrics_provider::LyricsFetchSettings) -> Result<(), String> {
+    lyrics_provider::set_settings(settings).map_err(|e| e.to_string())
+}
+
+/// 按标题、艺术家和时长在线查找播放列表中指定条目的歌词，按设置保存后重新读取并更新播放列表
+/// （播放器发送 SongMetadataUpdated 事件），没有找到时返回 None
+#[tauri::command]
+async fn fetch_lyrics(
+    song_id: usize,
+    _state: tauri::State<'_, AppState>,
+) -> Result<Option<Vec<crate::player_fixed::LyricLine>>, String> {
+    let player_instance = get_player_instance().await?;
+    let song = {
+        let player_state_guard = player_instance.lock().await;
+        player_state_guard
+            .player
+            .get_playlist()
+            .get(song_id)
+            .cloned()
+            .ok_or_else(|| "无效的歌曲索引".to_string())?
+    };
+    if song.path.contains("://") || song.media_type != Some(crate::player_fixed::MediaType::Audio) {
+        return Err("只能为本地音频文件获取歌词".to_string());
+    }
+    let (Some(title), Some(artist)) = (song.title.clone(), song.artist.clone()) else {
+        return Err("缺少标题或艺术家信息，无法在线查找歌词".to_string());
+    };
+    let query = lyrics_provider::LyricsQuery {
+        title,
+        artist,
+        album: song.album.clone(),
+        duration: song.duration,
+    };
+    let Some((lyrics, provider)) = lyrics_provider::search(&query).await.map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+    println!("从 {} 找到歌词: {}", provider, song.path);
+
+    let refreshed = tokio::task::spawn_blocking(move || -> anyhow::Result<SongInfo> {
+        let path = PathBuf::from(&song.path);
+        lyrics_provider::save(&path, &lyrics, lyrics_provider::settings().save_to)?;
+        SongInfo::from_path(&path)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+    let lyrics = refreshed.lyrics.clone();
+
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::RefreshSong(refreshed))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(lyrics)
+}
+
+/// 设置播放列表中指定条目的歌词时间偏移（毫秒，正数表示歌词提前，0 为清除），按曲目保存，
+/// 不修改歌词文件；重新读取后更新播放列表（播放器发送 SongMetadataUpdated 事件），返回调整后的歌词
+#[tauri::command]
+async fn set_lyrics_offset(
+    song_id: usize,
+    offset_ms: i64,
+    _state: tauri::State<'_, AppState>,
+) -> Result<Option<Vec<crate::player_fixed::LyricLine>>, String> {
+    let player_instance = get_player_instance().await?;
+    let path = {
+        let player_state_guard = player_instance.lock().await;
+        let playlist = player_state_guard.player.get_playlist();
+        let song = playlist.get(song_id).ok_or_else(|| "无效的歌曲索引".to_string())?;
+        if song.path.contains("://") {
+            return Err("串流不支持歌词偏移".to_string());
+        }
+        song.path.clone()
+    };
+    library::with_library(|lib| lib.set_lyrics_offset(&path, offset_ms))?;
+
+    let refreshed = tokio::task::spawn_blocking(move || SongInfo::from_path(&PathBuf::from(&path)))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    let lyrics = refreshed.lyrics.clone();
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::RefreshSong(refreshed))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(lyrics)
+}
+
+/// 保存歌词编辑器的结果：写成同名 .lrc 文件（覆盖原有文件），embed 为 true 时同时写入文件的歌词标签
+/// 保存的时间已包含调整，曲目的歌词偏移清零；重新读取后更新播放列表（播放器发送 SongMetadataUpdated 事件）
+#[tauri::command]
+async fn save_lyrics(
+    song_id: usize,
+    lines: Vec<crate::player_fixed::LyricLine>,
+    embed: Option<bool>,
+    _state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let song = {
+        let player_state_guard = player_instance.lock().await;
+        player_state_guard
+            .player
+            .get_playlist()
+            .get(song_id)
+            .cloned()
+            .ok_or_else(|| "无效的歌曲索引".to_string())?
+    };
+    if song.path.contains("://") || song.media_type != Some(crate::player_fixed::MediaType::Audio) {
+        return Err("只能为本地音频文件保存歌词".to_string());
+    }
+    let embed = embed.unwrap_or(false);
+    if embed {
+        tag_write::ensure_writable().map_err(|e| e.to_string())?;
+    }
+
+    let refreshed = tokio::task::spawn_blocking(move || -> anyhow::Result<SongInfo> {
+        let path = PathBuf::from(&song.path);
+        let text = song.format_lrc(&lines);
+        let lrc_path = path.with_extension("lrc");
+        std::fs::write(&lrc_path, &text)?;
+        println!("📝 已保存歌词: {}", lrc_path.display());
+        if embed {
+            tag_write::write_lyrics(&path, &text)?;
+        }
+        library::with_library(|lib| lib.set_lyrics_offset(&song.path, 0)).map_err(anyhow::Error::msg)?;
+        SongInfo::from_path(&path)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::RefreshSong(refreshed))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 显示桌面歌词（置顶、鼠标穿透的独立窗口，跟随播放进度显示当前歌词行）
+#[tauri::command]
+async fn show_desktop_lyrics<R: Runtime>(app_handle: AppHandle<R>) -> Result<(), String> {
+    desktop_lyrics::show(&app_handle).map_err(|e| e.to_string())
+}
+
+/// 隐藏桌面歌词
+#[tauri::command]
+async fn hide_desktop_lyrics<R: Runtime>(app_handle: AppHandle<R>) -> Result<(), String> {
+    desktop_lyrics::hide(&app_handle).map_err(|e| e.to_string())
+}
+
+/// 播放列表条目对应的视频文件（视频本身，或歌曲的MV）
+async fn video_path_of(song_id: usize) -> Result<PathBuf, String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    let playlist = player_state_guard.player.get_playlist();
+    let song = playlist.get(song_id).ok_or_else(|| "无效的歌曲索引".to_string())?;
+    match &song.mv_path {
+        Some(mv_path) if !mv_path.contains("://") => Ok(PathBuf::from(mv_path)),
+        _ => Err("该曲目没有本地视频".to_string()),
+    }
+}
+
+/// 获取视频的外挂字幕（与视频同名的 .srt/.vtt/.ass 文件）和当前选中字幕的内容
+#[tauri::command]
+async fn get_subtitles(song_id: usize, _state: tauri::State<'_, AppState>) -> Result<subtitles::Subtitles, String> {
+    let path = video_path_of(song_id).await?;
+    tokio::task::spawn_blocking(move || subtitles::subtitles(&path))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 选择视频的字幕轨，track_id 为空时关闭字幕；返回选择后的字幕
+#[tauri::command]
+async fn set_subtitle_track(
+    song_id: usize,
+    track_id: Option<usize>,
+    _state: tauri::State<'_, AppState>,
+) -> Result<subtitles::Subtitles, String> {
+    let path = video_path_of(song_id).await?;
+    tokio::task::spawn_blocking(move || subtitles::select(&path, track_id))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// 获取元数据提取顺序设置
+#[tauri::command]
+async fn get_metadata_priority() -> Result<metadata_priority::MetadataPriority, String> {
+    Ok(metadata_priority::get())
+}
+
+/// 设置元数据提取顺序（默认顺序及按格式覆盖），之后读取的文件按新顺序提取
+#[tauri::command]
+async fn set_metadata_priority(priority: metadata_priority::MetadataPriority) -> Result<(), String> {
+    metadata_priority::set(priority).map_err(|e| e.to_string())
+}
+
+/// 试运行所有元数据提取方式，列出各自的结果和实际会采用的方式，用于排查标签读取错误
+#[tauri::command]
+async fn debug_extract(path: String) -> Result<player_fixed::ExtractionReport, String> {
+    tokio::task::spawn_blocking(move || SongInfo::debug_extract(std::path::Path::new(&path)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 保存用户选定的元数据，之后读取该文件时优先使用，并更新播放列表中的对应条目
+#[tauri::command]
+async fn set_metadata_choice(
+    path: String,
+    metadata: library::MetadataOverride,
+    _state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    library::with_library(|lib| lib.set_metadata_override(&path, &metadata))?;
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::ApplyMetadata { path, metadata })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 清除选定的元数据，恢复按标签读取
+#[tauri::command]
+async fn clear_metadata_choice(path: String, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    library::with_library(|lib| lib.clear_metadata_override(&path))?;
+    let song = SongInfo::from_path(std::path::Path::new(&path)).map_err(|e| e.to_string())?;
+    index_in_library(std::slice::from_ref(&song));
+    let metadata = library::MetadataOverride {
+        title: song.title,
+        artist: song.artist,
+        album: song.album,
+        track_number: song.track_number,
+        disc_number: song.disc_number,
+    };
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::ApplyMetadata { path, metadata })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取曲目的播放起止点
+#[tauri::command]
+async fn get_trim_points(path: String) -> Result<Option<library::TrimPoints>, String> {
+    library::with_library(|lib| lib.trim_points(&path))
+}
+
+/// 把播放起止点同步到播放列表中的对应条目
+async fn apply_trim_points(path: String, trim: Option<library::TrimPoints>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::ApplyTrimPoints { path, trim })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 设置曲目的播放起止点（毫秒），下次播放该曲目时生效；接受跳过前奏的建议时也使用此命令
+#[tauri::command]
+async fn set_trim_points(path: String, start_ms: u64, end_ms: Option<u64>) -> Result<(), String> {
+    let trim = library::TrimPoints { start_ms, end_ms };
+    library::with_library(|lib| lib.set_trim_points(&path, trim))?;
+    apply_trim_points(path, Some(trim)).await
+}
+
+/// 清除曲目的播放起止点
+#[tauri::command]
+async fn clear_trim_points(path: String) -> Result<(), String> {
+    library::with_library(|lib| lib.clear_trim_points(&path))?;
+    apply_trim_points(path, None).await
+}
+
+/// 开启/关闭曲目的续播：开启后切到该曲目时从上次播放到的位置继续，关闭时清除保存的位置
+/// 未设置过的曲目按时长自动决定（20分钟以上的播客、有声书、DJ混音默认开启）
+#[tauri::command]
+async fn set_resume_enabled(path: String, enabled: bool) -> Result<(), String> {
+    library::with_library(|lib| lib.set_resume_enabled(&path, enabled))?;
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::ApplyResumeEnabled { path, enabled })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 拒绝跳过前奏的建议，之后不再为该曲目提示
+#[tauri::command]
+async fn dismiss_intro_skip(path: String) -> Result<(), String> {
+    library::with_library(|lib| lib.dismiss_intro_skip(&path))
+}
+
+/// 获取曲目的编码器延迟/填充信息（来自音乐库）
+#[tauri::command]
+async fn get_gapless_info(path: String) -> Result<Option<gapless::GaplessInfo>, String> {
+    library::with_library(|lib| lib.gapless_info(&path))
+}
+
+/// 移除歌曲
+#[tauri::command]
+async fn remove_song(index: usize, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::RemoveSong(index))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 调整播放列表顺序：把 from 处的歌曲移动到 to 处
+#[tauri::command]
+async fn move_song(from: usize, to: usize, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::MoveSong { from, to })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 按元数据排序播放列表（field: Title/Artist/Album/Duration/DateAdded/Path，direction: Ascending/Descending）
+#[tauri::command]
+async fn sort_playlist(field: SortField, direction: SortDirection, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::SortPlaylist { field, direction })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 设置音量（0-2，1为原始音量），同时取消静音
+#[tauri::command]
+async fn set_volume(volume: f32, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    if !volume.is_finite() {
+        return Err("无效的音量".to_string());
+    }
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::SetVolume(volume))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取当前音量（静音时返回静音前的音量）
+#[tauri::command]
+async fn get_volume(_state: tauri::State<'_, AppState>) -> Result<f32, String> {
+    let player_instance = get_player_instance().await?;
+    let volume = player_instance.lock().await.player.get_volume();
+    Ok(volume)
+}
+
+/// 切换静音，返回切换后是否静音
+#[tauri::command]
+async fn toggle_mute(_state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    let muted = !player_state_guard.player.is_muted();
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::SetMuted(muted))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(muted)
+}
+
+/// 清空播放列表
+#[tauri::command]
+async fn clear_playlist(_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::ClearPlaylist)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 一次性清理播放列表：移除重复、失效路径和空文件，返回清理结果
+#[tauri::command]
+async fn cleanup_playlist(
+    options: Option<playlist_tools::CleanupOptions>,
+    _state: tauri::State<'_, AppState>,
+) -> Result<playlist_tools::CleanupSummary, String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::CleanupPlaylist {
+            options: options.unwrap_or_default(),
+            reply: reply_tx,
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+    // 等待回复期间不占用播放器，其他命令和状态读取不被阻塞
+    drop(player_state_guard);
+    reply_rx.await.map_err(|_| "播放器未返回清理结果".to_string())
+}
+
+/// 设置播放列表条目的颜色/分组（传空值清除），随播放列表一起保存和导出
+#[tauri::command]
+async fn set_playlist_entry_style(
+    indices: Vec<usize>,
+    color: Option<String>,
+    group: Option<String>,
+    _state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let color = match color.filter(|c| !c.trim().is_empty()) {
+        Some(c) => Some(playlist_tools::normalize_color(&c).ok_or_else(|| format!("无效的颜色: {}", c))?),
+        None => None,
+    };
+    let group = group.map(|g| g.trim().to_string()).filter(|g| !g.is_empty());
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::SetEntryStyle { indices, color, group })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 把播放列表中的曲目导出为 WAV（不指定路径时导出整个播放列表），可选音量标准化和重采样，进度通过 export-progress 事件上报
+#[tauri::command]
+async fn export_tracks<R: Runtime>(
+    app_handle: AppHandle<R>,
+    dest_dir: String,
+    paths: Option<Vec<String>>,
+    options: Option<export::ExportOptions>,
+) -> Result<Vec<export::ExportedTrack>, String> {
+    let songs: Vec<SongInfo> = {
+        let player_instance = get_player_instance().await?;
+        let player_state_guard = player_instance.lock().await;
+        player_state_guard
+            .player
+            .get_playlist()
+            .into_iter()
+            .filter(|song| paths.as_ref().map(|p| p.contains(&song.path)).unwrap_or(true))
+            .collect()
+    };
+    if songs.is_empty() {
+        return Err("没有可导出的曲目".to_string());
+    }
+    let options = options.unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        export::export(songs, std::path::Path::new(&dest_dir), &options, |event| {
+            if let Err(e) = app_handle.emit("export-progress", event) {
+                eprintln!("发送导出事件失败: {:?}", e);
+            }
+        })
+        .map_err(|e| format!("导出失败: {}", e))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 获取当前播放列表的分组及各组包含的条目索引
+#[tauri::command]
+async fn get_playlist_groups(
+    _state: tauri::State<'_, AppState>,
+) -> Result<Vec<playlist_tools::PlaylistGroup>, String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    Ok(playlist_tools::groups(&player_state_guard.player.get_playlist()))
+}
+
+/// 将当前播放列表保存为命名播放列表，并记录当前播放位置
+#[tauri::command]
+async fn save_playlist(name: String, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    let playlist = playlist_store::SavedPlaylist {
+        name: name.clone(),
+        songs: player_state_guard.player.get_playlist(),
+        last_index: player_state_guard.player.get_current_index(),
+        last_position: player_state_guard.player.get_position(),
+    };
+    playlist_store::save(&playlist).map_err(|e| format!("保存播放列表失败: {}", e))?;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::SetActivePlaylist(Some(name)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 切换到已保存的播放列表，恢复到该列表上次播放的歌曲和位置
+#[tauri::command]
+async fn load_playlist(name: String, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    load_saved_playlist(name).await
+}
+
+async fn load_saved_playlist(name: String) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+
+    // 先记住当前播放列表的位置，切回来时从这里继续
+    if let Some(active) = player_state_guard.player.get_active_playlist() {
+        if let Err(e) = playlist_store::update_position(
+            &active,
+            player_state_guard.player.get_current_index(),
+            player_state_guard.player.get_position(),
+        ) {
+            eprintln!("保存播放列表位置失败 {}: {}", active, e);
+        }
+    }
+
+    let playlist = playlist_store::load(&name).map_err(|e| e.to_string())?;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::LoadPlaylist {
+            name: Some(playlist.name),
+            songs: playlist.songs,
+            index: playlist.last_index,
+            position: playlist.last_position,
+        })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 列出已保存的播放列表
+#[tauri::command]
+async fn list_saved_playlists() -> Result<Vec<playlist_store::SavedPlaylistSummary>, String> {
+    playlist_store::list().map_err(|e| format!("读取播放列表失败: {}", e))
+}
+
+/// 删除已保存的播放列表
+#[tauri::command]
+async fn delete_saved_playlist(name: String) -> Result<(), String> {
+    playlist_store::delete(&name).map_err(|e| format!("删除播放列表失败: {}", e))
+}
+
+/// 读取用于比较/合并的播放列表
+async fn load_playlist_source(source: playlist_tools::PlaylistSource) -> Result<Vec<SongInfo>, String> {
+    match source {
+        playlist_tools::PlaylistSource::Current => {
+            let player_instance = get_player_instance().await?;
+            let player_state_guard = player_instance.lock().await;
+            Ok(player_state_guard.player.get_playlist())
+        }
+        playlist_tools::PlaylistSource::Saved { name } => {
+            playlist_store::load(&name).map(|p| p.songs).map_err(|e| e.to_string())
+        }
+        playlist_tools::PlaylistSource::M3u { path } => songs_from_m3u(path).await,
+    }
+}
+
+/// 读取 M3U/M3U8 文件并为每个条目创建歌曲信息；串流URL和缺少时长的条目使用 #EXTINF 中的标题/时长
+/// 无法读取的文件被跳过
+async fn songs_from_m3u(path: String) -> Result<Vec<SongInfo>, String> {
+    let bytes = std::fs::read(&path).map_err(|e| format!("读取M3U文件失败: {}", e))?;
+    let base_dir = std::path::Path::new(&path).parent().map(|p| p.to_path_buf()).unwrap_or_default();
+    let entries = m3u::parse(&String::from_utf8_lossy(&bytes), &base_dir);
+    tokio::task::spawn_blocking(move || {
+        entries
+            .iter()
+            .flat_map(|entry| {
+                if media_source::is_url(&entry.location) {
+                    let mut song = SongInfo::from_url(&entry.location);
+                    song.title = entry.title.clone().or(song.title);
+                    song.duration = entry.duration;
+                    return vec![song];
+                }
+                match songs_from_path(std::path::Path::new(&entry.location)) {
+                    Ok(mut songs) => {
+                        if let [song] = songs.as_mut_slice() {
+                            song.duration = song.duration.or(entry.duration);
+                        }
+                        songs
+                    }
+                    Err(e) => {
+                        eprintln!("跳过无法读取的M3U条目 {}: {}", entry.location, e);
+                        Vec::new()
+                    }
+                }
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// 导入 M3U/M3U8 播放列表，条目追加到当前播放列表末尾，返回导入的歌曲数
+#[tauri::command]
+async fn import_playlist(path: String, _state: tauri::State<'_, AppState>) -> Result<usize, String> {
+    let songs = songs_from_m3u(path).await?;
+    if songs.is_empty() {
+        return Ok(0);
+    }
+    let count = songs.len();
+    index_in_library(&songs);
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::AddSongs(songs))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(count)
+}
+
+/// 导出当前播放队列为扩展 M3U 或 JSON；未给出路径时弹出保存对话框
+/// 返回导出的条目数，用户取消对话框时返回 None
+#[tauri::command]
+async fn export_playlist<R: Runtime>(
+    app_handle: AppHandle<R>,
+    path: Option<String>,
+    format: playlist_tools::PlaylistFormat,
+) -> Result<Option<usize>, String> {
+    let songs = {
+        let player_instance = get_player_instance().await?;
+        let player_state_guard = player_instance.lock().await;
+        player_state_guard.player.get_playlist()
+    };
+    if songs.is_empty() {
+        return Err("播放列表为空".to_string());
+    }
+    let path = match path {
+        Some(path) => path,
+        None => {
+            let extension = format.extension();
+            let picked = tokio::task::spawn_blocking(move || {
+                app_handle
+                    .dialog()
+                    .file()
+                    .add_filter("播放列表", &[extension])
+                    .set_file_name(format!("playlist.{}", extension))
+                    .set_title("导出播放列表")
+                    .blocking_save_file()
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+            match picked {
+                Some(file_path) => file_path.to_string(),
+                None => return Ok(None),
+            }
+        }
+    };
+    playlist_tools::export(&songs, std::path::Path::new(&path), format)
+        .map(Some)
+        .map_err(|e| format!("导出播放列表失败: {}", e))
+}
+
+/// 比较两个播放列表（以 a 为基准列出新增/移除的条目）
+#[tauri::command]
+async fn diff_playlists(
+    a: playlist_tools::PlaylistSource,
+    b: playlist_tools::PlaylistSource,
+) -> Result<playlist_tools::PlaylistDiff, String> {
+    let a = load_playlist_source(a).await?;
+    let b = load_playlist_source(b).await?;
+    Ok(playlist_tools::diff(&a, &b))
+}
+
+/// 合并两个播放列表（不含重复条目）；指定 save_as 时保存为命名播放列表
+#[tauri::command]
+async fn merge_playlists(
+    a: playlist_tools::PlaylistSource,
+    b: playlist_tools::PlaylistSource,
+    strategy: Option<playlist_tools::MergeStrategy>,
+    save_as: Option<String>,
+) -> Result<Vec<SongInfo>, String> {
+    let a = load_playlist_source(a).await?;
+    let b = load_playlist_source(b).await?;
+    let merged = playlist_tools::merge(&a, &b, strategy.unwrap_or_default());
+    if let Some(name) = save_as {
+        let playlist = playlist_store::SavedPlaylist {
+            name,
+            songs: merged.clone(),
+            last_index: None,
+            last_position: 0,
+        };
+        playlist_store::save(&playlist).map_err(|e| format!("保存播放列表失败: {}", e))?;
+    }
+    Ok(merged)
+}
+
+/// 设置播放模式
+#[tauri::command]
+async fn set_play_mode<R: Runtime>(
+    app_handle: AppHandle<R>,
+    mode: PlayMode,
+    _state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::SetPlayMode(mode))
+        .await
+        .map_err(|e| e.to_string())?;
+    settings::remember(&app_handle, |preferences| preferences.play_mode = mode).map_err(|e| e.to_string())
+}
+
+/// 设置曲间静音间隔（0-5秒），仅在自动切歌时生效
+#[tauri::command]
+async fn set_silence_gap<R: Runtime>(
+    app_handle: AppHandle<R>,
+    seconds: f32,
+    _state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::SetSilenceGap(seconds))
+        .await
+        .map_err(|e| e.to_string())?;
+    settings::remember(&app_handle, |preferences| preferences.silence_gap = seconds.clamp(0.0, 5.0)).map_err(|e| e.to_string())
+}
+
+/// 开启/关闭现场专辑连续播放：顺序播放时同一专辑的相邻曲目连续解码，
+/// 跨曲目的掌声、观众声不会因为切歌而中断（不插入曲间静音、不做跳过片头）
+#[tauri::command]
+async fn set_live_album_mode(enabled: bool, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::SetLiveAlbumMode(enabled))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 是否开启现场专辑连续播放
+#[tauri::command]
+async fn get_live_album_mode(_state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    Ok(player_state_guard.player.get_live_album_mode())
+}
+
+/// 开启/关闭"播完列表后停止"：开启即切换到不循环模式（播完最后一首后停止并发送 PlaylistEnded），
+/// 关闭时回到列表循环；单曲循环、随机模式下不改变播放模式
+#[tauri::command]
+async fn set_stop_at_playlist_end<R: Runtime>(
+    app_handle: AppHandle<R>,
+    enabled: bool,
+    _state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::SetStopAtPlaylistEnd(enabled))
+        .await
+        .map_err(|e| e.to_string())?;
+    settings::remember(&app_handle, |preferences| {
+        preferences.play_mode = match (enabled, preferences.play_mode) {
+            (true, PlayMode::RepeatAll) => PlayMode::NoRepeat,
+            (false, PlayMode::NoRepeat) => PlayMode::RepeatAll,
+            (_, mode) => mode,
+        };
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// 是否开启"播完列表后停止"（当前为不循环模式）
+#[tauri::command]
+async fn get_stop_at_playlist_end(_state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    Ok(player_state_guard.player.get_stop_at_playlist_end())
+}
+
+/// 设置播放器空闲（暂停/停止）多久后释放音频输出设备，再次播放时自动重新获取
+#[tauri::command]
+async fn set_idle_release_timeout(seconds: u64, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::SetIdleReleaseTimeout(seconds))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 列出可用的音频输出设备（启用 asio 特性编译时包含 ASIO 设备）
+#[tauri::command]
+async fn list_output_devices() -> Result<Vec<output_device::OutputDeviceInfo>, String> {
+    tokio::task::spawn_blocking(output_device::list_devices)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 选择音频输出设备、缓冲区大小及是否匹配音源采样率，正在播放时在新设备上从当前位置继续
+#[tauri::command]
+async fn set_output_device<R: Runtime>(
+    app_handle: AppHandle<R>,
+    selection: output_device::OutputDeviceSelection,
+    _state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::SetOutputDevice(selection.clone()))
+        .await
+        .map_err(|e| e.to_string())?;
+    settings::remember(&app_handle, |preferences| preferences.output_device = selection).map_err(|e| e.to_string())
+}
+
+/// 获取当前音频输出设备选择
+#[tauri::command]
+async fn get_output_device(
+    _state: tauri::State<'_, AppState>,
+) -> Result<output_device::OutputDeviceSelection, String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    Ok(player_state_guard.player.get_output_device())
+}
+
+/// 当前输出设备的延迟补偿
+#[derive(serde::Serialize)]
+struct DeviceLatency {
+    device: Option<String>,
+    #[serde(rename = "latencyMs")]
+    latency_ms: u64,
+}
+
+/// 获取当前输出设备的延迟补偿（毫秒）
+#[tauri::command]
+async fn get_device_latency() -> Result<DeviceLatency, String> {
+    Ok(DeviceLatency {
+        device: output_device::active_device(),
+        latency_ms: output_device::latency_ms(),
+    })
+}
+
+/// 列出所有设备的延迟补偿
+#[tauri::command]
+async fn list_device_latency() -> Result<std::collections::HashMap<String, u64>, String> {
+    Ok(output_device::list_latency())
+}
+
+/// 设置输出设备的延迟补偿（不指定设备时为当前设备，0为清除），上报的播放位置会扣除该延迟
+#[tauri::command]
+async fn set_device_latency(device: Option<String>, ms: u64) -> Result<(), String> {
+    output_device::set_latency_ms(device, ms).map_err(|e| format!("保存设备延迟失败: {}", e))
+}
+
+/// 获取扣除设备延迟后的播放位置（毫秒），用于歌词/MV同步
+#[tauri::command]
+async fn get_position_ms(_state: tauri::State<'_, AppState>) -> Result<u64, String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::QueryPositionMs { reply: reply_tx })
+        .await
+        .map_err(|e| e.to_string())?;
+    drop(player_state_guard);
+    reply_rx.await.map_err(|_| "播放器未返回播放位置".to_string())
+}
+
+/// 开始延迟校准：在当前输出设备上播放等间隔的提示音，返回第一声的播放时间（Unix毫秒）
+/// 前端在用户每次听到提示音时记录点击时间，结束后调用 finish_latency_calibration
+#[tauri::command]
+async fn start_latency_calibration(
+    count: Option<u32>,
+    interval_ms: Option<u64>,
+    _state: tauri::State<'_, AppState>,
+) -> Result<u64, String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::PlayCalibrationClicks {
+            count: count.unwrap_or(8).min(32),
+            interval_ms: interval_ms.unwrap_or(1000).clamp(200, 3000),
+            reply: reply_tx,
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+    drop(player_state_guard);
+    reply_rx
+        .await
+        .map_err(|_| "播放器未返回校准结果".to_string())?
+        .map_err(|e| format!("播放校准提示音失败: {}", e))
+}
+
+/// 完成延迟校准：根据点击时间估算当前设备的延迟并保存，返回估算结果（毫秒）
+#[tauri::command]
+async fn finish_latency_calibration(start_ms: u64, interval_ms: Option<u64>, taps: Vec<u64>) -> Result<u64, String> {
+    let interval_ms = interval_ms.unwrap_or(1000).clamp(200, 3000);
+    let latency = output_device::estimate_latency(start_ms, interval_ms, &taps)
+        .ok_or_else(|| "有效点击不足3次，请重新校准".to_string())?;
+    output_device::set_latency_ms(None, latency).map_err(|e| format!("保存设备延迟失败: {}", e))?;
+    Ok(latency)
+}
+
+/// 当前输出设备的DSP配置
+#[derive(serde::Serialize)]
+struct ActiveDspConfig {
+    device: Option<String>,
+    config: dsp::DspConfig,
+}
+
+/// 获取当前输出设备及其EQ/DSP配置
+#[tauri::command]
+async fn get_dsp_config() -> Result<ActiveDspConfig, String> {
+    let (device, config) = dsp::current();
+    Ok(ActiveDspConfig { device, config })
+}
+
+/// 保存输出设备的EQ/DSP配置（不指定设备时为当前设备），切换到该设备时自动生效
+#[tauri::command]
+async fn set_dsp_config(device: Option<String>, config: dsp::DspConfig) -> Result<(), String> {
+    dsp::set_config(device, config).map_err(|e| e.to_string())
+}
+
+/// 列出所有设备的EQ/DSP配置
+#[tauri::command]
+async fn list_dsp_profiles() -> Result<std::collections::HashMap<String, dsp::DspConfig>, String> {
+    Ok(dsp::list_profiles())
+}
+
+/// 自动播完N首后停止播放（当前歌曲算第一首），0为取消
+#[tauri::command]
+async fn stop_after_tracks(n: u32, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::StopAfterTracks(n))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取剩余的停止前曲目数
+#[tauri::command]
+async fn get_stop_after_tracks(_state: tauri::State<'_, AppState>) -> Result<Option<u32>, String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    Ok(player_state_guard.player.get_stop_after_tracks())
+}
+
+/// 回放最近几秒（默认10秒）并继续播放，当前曲目不足时跳到上一首的结尾部分
+#[tauri::command]
+async fn replay_last(seconds: Option<u64>, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::ReplayLast(seconds.unwrap_or(10).max(1)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 快进指定秒数（未指定时使用设置的默认步长），快进越过结尾时切到下一首
+#[tauri::command]
+async fn skip_forward(secs: Option<u64>, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let secs = secs.unwrap_or_else(|| seek_step::settings().forward_secs).max(1);
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::SkipBy(secs.min(i64::MAX as u64) as i64))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 快退指定秒数（未指定时使用设置的默认步长），最多退到当前曲目开头
+#[tauri::command]
+async fn skip_backward(secs: Option<u64>, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let secs = secs.unwrap_or_else(|| seek_step::settings().backward_secs).max(1);
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::SkipBy(-(secs.min(i64::MAX as u64) as i64)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 跳到当前曲目的下一章（有声书、播客），已是最后一章时切到下一首
+#[tauri::command]
+async fn next_chapter(_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::NextChapter)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 回到本章开头；在本章开头3秒内时跳到上一章
+#[tauri::command]
+async fn previous_chapter(_state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::PreviousChapter)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 跳到当前曲目的指定章节（索引对应 SongInfo.chapters）
 #[tauri::command]
-async fn get_play_mode(_state: tauri::State<'_, AppState>) -> Result<PlayMode, String> {
+async fn seek_to_chapter(index: usize, _state: tauri::State<'_, AppState>) -> Result<(), String> {
     let player_instance = get_player_instance().await?;
     let player_state_guard = player_instance.lock().await;
-    Ok(player_state_guard.player.get_play_mode())
+    player_state_guard
+        .player
+        .send_command(PlayerCommand::SeekToChapter(index))
+        .await
+        .map_err(|e| e.to_string())
 }
 
-/// 播放
+/// 设置当前曲目的 A–B 循环（秒），播放越过终点时跳回起点
 #[tauri::command]
-async fn play(_state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn set_ab_loop(start_secs: u64, end_secs: u64, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    if start_secs >= end_secs {
+        return Err("循环终点必须在起点之后".to_string());
+    }
     let player_instance = get_player_instance().await?;
     let player_state_guard = player_instance.lock().await;
     player_state_guard
         .player
-        .send_command(PlayerCommand::Play)
+        .send_command(PlayerCommand::SetAbLoop { start: start_secs, end: end_secs })
         .await
         .map_err(|e| e.to_string())
 }
 
-/// 暂停
+/// 清除 A–B 循环
 #[tauri::command]
-async fn pause(_state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn clear_ab_loop(_state: tauri::State<'_, AppState>) -> Result<(), String> {
     let player_instance = get_player_instance().await?;
     let player_state_guard = player_instance.lock().await;
     player_state_guard
         .player
-        .send_command(PlayerCommand::Pause)
+        .send_command(PlayerCommand::ClearAbLoop)
         .await
         .map_err(|e| e.to_string())
 }
 
-/// 下一曲
+/// 开始 A/B 对比：当前歌曲（A）与指定文件（B）从当前位置同步播放，默认听到 A
 #[tauri::command]
-async fn next(_state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn start_ab_compare(path: String, _state: tauri::State<'_, AppState>) -> Result<AbCompare, String> {
     let player_instance = get_player_instance().await?;
     let player_state_guard = player_instance.lock().await;
+    let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
     player_state_guard
         .player
-        .send_command(PlayerCommand::Next)
+        .send_command(PlayerCommand::StartAbCompare { path, reply: reply_tx })
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    drop(player_state_guard);
+    reply_rx.await.map_err(|_| "播放器未返回A/B对比结果".to_string())?
 }
 
-/// 上一曲
+/// 在 A/B 两路之间切换发声的一路，播放位置不变
 #[tauri::command]
-async fn previous(_state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn switch_ab(_state: tauri::State<'_, AppState>) -> Result<AbCompare, String> {
     let player_instance = get_player_instance().await?;
     let player_state_guard = player_instance.lock().await;
+    let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
     player_state_guard
         .player
-        .send_command(PlayerCommand::Previous)
+        .send_command(PlayerCommand::SwitchAb { reply: reply_tx })
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    drop(player_state_guard);
+    reply_rx.await.map_err(|_| "播放器未返回A/B对比结果".to_string())?
 }
 
-/// 设置当前歌曲
+/// 结束 A/B 对比，继续播放 A
 #[tauri::command]
-async fn set_song(_state: State<'_, AppState>, index: usize) -> Result<(), String> {
+async fn stop_ab_compare(_state: tauri::State<'_, AppState>) -> Result<(), String> {
     let player_instance = get_player_instance().await?;
     let player_state_guard = player_instance.lock().await;
     player_state_guard
         .player
-        .send_command(PlayerCommand::SetSong(index))
+        .send_command(PlayerCommand::StopAbCompare)
         .await
         .map_err(|e| e.to_string())
 }
 
-/// 添加歌曲
+/// 获取曲间静音间隔
 #[tauri::command]
-async fn add_song(path: String, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn get_silence_gap(_state: tauri::State<'_, AppState>) -> Result<f32, String> {
     let player_instance = get_player_instance().await?;
     let player_state_guard = player_instance.lock().await;
-    // 创建SongInfo对象代替直接使用PathBuf
-    match SongInfo::from_path(&PathBuf::from(&path)) {
-        Ok(song_info) => player_state_guard
-            .player
-            .send_command(PlayerCommand::AddSong(song_info))
-            .await
-            .map_err(|e| e.to_string()),
-        Err(e) => Err(format!("无法从路径创建歌曲信息: {}", e)),
-    }
+    Ok(player_state_guard.player.get_silence_gap())
 }
 
-/// 移除歌曲
+/// 跳转到指定位置
 #[tauri::command]
-async fn remove_song(index: usize, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn seek_to(position: u64, _state: tauri::State<'_, AppState>) -> Result<(), String> {
     let player_instance = get_player_instance().await?;
     let player_state_guard = player_instance.lock().await;
     player_state_guard
         .player
-        .send_command(PlayerCommand::RemoveSong(index))
+        .send_command(PlayerCommand::SeekTo(position))
         .await
         .map_err(|e| e.to_string())
 }
 
-/// 清空播放列表
+/// 获取播放卡顿诊断汇总
 #[tauri::command]
-async fn clear_playlist(_state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn get_playback_diagnostics(
+    _state: tauri::State<'_, AppState>,
+) -> Result<playback_monitor::PlaybackDiagnostics, String> {
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    Ok(player_state_guard.player.get_playback_diagnostics())
+}
+
+/// 清空播放卡顿诊断
+#[tauri::command]
+async fn clear_playback_diagnostics(_state: tauri::State<'_, AppState>) -> Result<(), String> {
     let player_instance = get_player_instance().await?;
     let player_state_guard = player_instance.lock().await;
     player_state_guard
         .player
-        .send_command(PlayerCommand::ClearPlaylist)
+        .send_command(PlayerCommand::ClearPlaybackDiagnostics)
         .await
         .map_err(|e| e.to_string())
 }
 
-/// 设置播放模式
+/// 获取播放中输出静音检测设置
+#[tauri::command]
+async fn get_watchdog_settings() -> Result<playback_monitor::WatchdogSettings, String> {
+    Ok(playback_monitor::watchdog_settings())
+}
+
+/// 设置播放中输出静音检测（检测时长、是否自动重新打开输出设备）
+#[tauri::command]
+async fn set_watchdog_settings(settings: playback_monitor::WatchdogSettings) -> Result<(), String> {
+    playback_monitor::set_watchdog_settings(settings).map_err(|e| e.to_string())
+}
+
+/// 通知前端某首歌曲的标记已变化
+fn emit_markers_updated<R: Runtime>(app_handle: &AppHandle<R>, path: &str) {
+    match library::with_library(|lib| lib.list_markers(path)) {
+        Ok(markers) => {
+            let _ = app_handle.emit(
+                "player-event",
+                PlayerEvent::MarkersUpdated {
+                    path: path.to_string(),
+                    markers,
+                },
+            );
+        }
+        Err(e) => eprintln!("读取曲目标记失败: {}", e),
+    }
+}
+
+/// 在曲目中添加命名标记，position 单位为毫秒
+#[tauri::command]
+async fn add_marker<R: Runtime>(
+    app_handle: AppHandle<R>,
+    path: String,
+    position: u64,
+    label: String,
+) -> Result<library::Marker, String> {
+    let label = label.trim().to_string();
+    if label.is_empty() {
+        return Err("标记名称不能为空".to_string());
+    }
+    let marker = library::with_library(|lib| lib.add_marker(&path, position, &label))?;
+    emit_markers_updated(&app_handle, &path);
+    Ok(marker)
+}
+
+/// 列出曲目的所有标记
+#[tauri::command]
+async fn list_markers(path: String) -> Result<Vec<library::Marker>, String> {
+    library::with_library(|lib| lib.list_markers(&path))
+}
+
+/// 删除标记
+#[tauri::command]
+async fn remove_marker<R: Runtime>(app_handle: AppHandle<R>, id: i64) -> Result<(), String> {
+    if let Some(path) = library::with_library(|lib| lib.remove_marker(id))? {
+        emit_markers_updated(&app_handle, &path);
+    }
+    Ok(())
+}
+
+/// 跳转到标记位置，标记不属于当前歌曲时先切换到该歌曲
 #[tauri::command]
-async fn set_play_mode(mode: PlayMode, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn jump_to_marker(id: i64, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let marker = library::with_library(|lib| lib.get_marker(id))?
+        .ok_or_else(|| "标记不存在".to_string())?;
+
     let player_instance = get_player_instance().await?;
     let player_state_guard = player_instance.lock().await;
+    let playlist = player_state_guard.player.get_playlist();
+    let current_index = player_state_guard.player.get_current_index();
+
+    let is_current = current_index
+        .and_then(|idx| playlist.get(idx))
+        .map_or(false, |song| song.path == marker.path);
+    if !is_current {
+        let index = playlist
+            .iter()
+            .position(|song| song.path == marker.path)
+            .ok_or_else(|| "标记所属的歌曲不在播放列表中".to_string())?;
+        player_state_guard
+            .player
+            .send_command(PlayerCommand::SetSong(index))
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
     player_state_guard
         .player
-        .send_command(PlayerCommand::SetPlayMode(mode))
+        .send_command(PlayerCommand::SeekTo(marker.position / 1000))
         .await
         .map_err(|e| e.to_string())
 }
 
-/// 跳转到指定位置
+/// 启用/停用内嵌HTTP服务（提供 /playlist.m3u8 等接口），返回实际监听端口
 #[tauri::command]
-async fn seek_to(position: u64, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn set_http_server_enabled(enabled: bool, port: Option<u16>) -> Result<Option<u16>, String> {
+    if !enabled {
+        http_server::stop().await;
+        // 远程控制接口挂在同一个服务上，仍启用时继续在局域网上提供
+        remote_api::start_saved().await;
+        return Ok(None);
+    }
+
+    // 用当前播放列表初始化快照，之后由 PlaylistUpdated 事件刷新
+    if let Ok(player_instance) = get_player_instance().await {
+        let player_state_guard = player_instance.lock().await;
+        http_server::update_playlist(&player_state_guard.player.get_playlist());
+    }
+    http_server::start(port.unwrap_or(http_server::DEFAULT_PORT)).await.map(Some)
+}
+
+/// 获取内嵌HTTP服务端口（未运行时为 None）
+#[tauri::command]
+async fn get_http_server_port() -> Result<Option<u16>, String> {
+    Ok(http_server::running_port())
+}
+
+/// 启用/停用局域网远程控制接口（/api/state、/api/next 等，需带访问令牌），返回实际监听端口
+#[tauri::command]
+async fn set_remote_api_enabled(enabled: bool, port: Option<u16>) -> Result<Option<u16>, String> {
+    remote_api::set_enabled(enabled, port).await.map_err(|e| e.to_string())
+}
+
+/// 获取远程控制接口设置（含访问令牌）和运行中的端口
+#[tauri::command]
+async fn get_remote_api_settings() -> Result<remote_api::RemoteApiStatus, String> {
+    Ok(remote_api::status())
+}
+
+/// 获取手机遥控页面的局域网地址和二维码（SVG），需先启用远程控制接口
+#[tauri::command]
+async fn get_remote_qr() -> Result<remote_api::RemoteQr, String> {
+    remote_api::qr().map_err(|e| e.to_string())
+}
+
+/// 重新生成远程控制接口的访问令牌，返回新令牌
+#[tauri::command]
+async fn reset_remote_api_token() -> Result<String, String> {
+    remote_api::reset_token().map_err(|e| e.to_string())
+}
+
+/// 重新生成遥控二维码中的手机令牌，已扫码的手机需要重新扫描
+#[tauri::command]
+async fn reset_remote_guest_token() -> Result<(), String> {
+    remote_api::reset_guest_token().map_err(|e| e.to_string())
+}
+
+/// 搜索局域网中的 DLNA 渲染器（约 3 秒）
+#[tauri::command]
+async fn list_cast_targets() -> Result<Vec<cast::CastTarget>, String> {
+    cast::discover().await.map_err(|e| e.to_string())
+}
+
+/// 把当前歌曲投屏到渲染器（target_id 为空时结束投屏，本机回到渲染器的播放位置），之后的播放控制转给渲染器
+#[tauri::command]
+async fn cast_to<R: Runtime>(app_handle: AppHandle<R>, target_id: Option<String>) -> Result<Option<cast::CastStatus>, String> {
+    cast::cast_to(app_handle, target_id).await.map_err(|e| e.to_string())
+}
+
+/// 获取投屏状态（未投屏时为 null），投屏期间也会通过 cast-status 事件推送
+#[tauri::command]
+async fn get_cast_status() -> Result<Option<cast::CastStatus>, String> {
+    Ok(cast::status())
+}
+
+/// 订阅播客（RSS 地址），已订阅时刷新节目
+#[tauri::command]
+async fn add_podcast(url: String) -> Result<podcast::PodcastSummary, String> {
+    podcast::add(&url).await.map_err(|e| e.to_string())
+}
+
+/// 取消订阅播客，同时删除已下载的节目
+#[tauri::command]
+async fn remove_podcast(podcast_id: String) -> Result<(), String> {
+    podcast::remove(&podcast_id).map_err(|e| e.to_string())
+}
+
+/// 获取订阅的播客列表
+#[tauri::command]
+async fn get_podcasts() -> Result<Vec<podcast::PodcastSummary>, String> {
+    Ok(podcast::list())
+}
+
+/// 刷新所有播客订阅，返回更新后的列表
+#[tauri::command]
+async fn refresh_podcasts() -> Result<Vec<podcast::PodcastSummary>, String> {
+    Ok(podcast::refresh_all().await)
+}
+
+/// 获取播客的节目列表（含简介、时长和上次收听到的位置）
+#[tauri::command]
+async fn get_episodes(podcast_id: String) -> Result<Vec<podcast::EpisodeEntry>, String> {
+    tokio::task::spawn_blocking(move || podcast::episodes(&podcast_id))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// 下载一期播客节目，返回本地文件路径
+#[tauri::command]
+async fn download_episode(podcast_id: String, episode_id: String) -> Result<String, String> {
+    podcast::download(&podcast_id, &episode_id).await.map_err(|e| e.to_string())
+}
+
+/// 播放一期播客节目（已下载时播放本地文件，否则在线播放），插入到当前歌曲之后，从上次收听的位置继续
+#[tauri::command]
+async fn play_episode(podcast_id: String, episode_id: String) -> Result<(), String> {
+    let (podcast, episode) = podcast::episode(&podcast_id, &episode_id).ok_or_else(|| format!("找不到这期节目: {}", episode_id))?;
+    let song = tokio::task::spawn_blocking(move || podcast::song_for(&podcast, &episode))
+        .await
+        .map_err(|e| e.to_string())?;
+
     let player_instance = get_player_instance().await?;
     let player_state_guard = player_instance.lock().await;
-    player_state_guard
-        .player
-        .send_command(PlayerCommand::SeekTo(position))
+    let player = &player_state_guard.player;
+    let index = match player.get_playlist().iter().position(|s| s.path == song.path) {
+        Some(index) => index,
+        None => {
+            let index = player.get_current_index().map(|i| i + 1).unwrap_or(player.get_playlist().len());
+            player
+                .send_command(PlayerCommand::InsertSongs { index, songs: vec![song] })
+                .await
+                .map_err(|e| e.to_string())?;
+            index
+        }
+    };
+    player.send_command(PlayerCommand::SetSong(index)).await.map_err(|e| e.to_string())
+}
+
+/// 获取全部设置（volume、muted、playMode、playbackMode、silenceGap、outputDevice、libraryFolders、hotkeys）
+#[tauri::command]
+async fn get_all_settings() -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    tokio::task::spawn_blocking(settings::all).await.map_err(|e| e.to_string())
+}
+
+/// 读取一项设置
+#[tauri::command]
+async fn get_setting(key: String) -> Result<serde_json::Value, String> {
+    tokio::task::spawn_blocking(move || settings::get(&key))
         .await
+        .map_err(|e| e.to_string())?
         .map_err(|e| e.to_string())
 }
 
+/// 修改一项设置：保存后立即应用到播放器，并发送 SettingsChanged 事件
+#[tauri::command]
+async fn set_setting<R: Runtime>(app_handle: AppHandle<R>, key: String, value: serde_json::Value) -> Result<(), String> {
+    let commands = tokio::task::spawn_blocking(move || settings::set(&app_handle, &key, value))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    if commands.is_empty() {
+        return Ok(());
+    }
+    let player_instance = get_player_instance().await?;
+    let player_state_guard = player_instance.lock().await;
+    for command in commands {
+        player_state_guard
+            .player
+            .send_command(command)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 获取派对模式设置（跳过票数、点歌限流）
+#[tauri::command]
+async fn get_party_settings() -> Result<party::PartySettings, String> {
+    Ok(party::settings())
+}
+
+/// 保存派对模式设置；来宾页面为远程控制接口的 /party，需先启用远程控制接口
+#[tauri::command]
+async fn set_party_settings(settings: party::PartySettings) -> Result<(), String> {
+    party::set_settings(settings).map_err(|e| e.to_string())
+}
+
+/// 获取派对状态：在线来宾、待播点歌和当前跳过票数
+#[tauri::command]
+async fn get_party_state() -> Result<party::PartyState, String> {
+    Ok(party::state())
+}
+
+/// 获取网络代理设置
+#[tauri::command]
+async fn get_proxy_settings() -> Result<net::ProxySettings, String> {
+    Ok(net::proxy_settings())
+}
+
+/// 设置网络代理（直连/系统代理/手动HTTP或SOCKS5代理），对之后的所有联网请求生效
+#[tauri::command]
+async fn set_proxy_settings(settings: net::ProxySettings) -> Result<(), String> {
+    net::set_proxy_settings(settings).map_err(|e| format!("保存代理设置失败: {}", e))
+}
+
+/// 获取各类缓存（封面、歌词、波形、网络音源）的占用情况
+#[tauri::command]
+async fn get_cache_usage() -> Result<cache::CacheUsage, String> {
+    tokio::task::spawn_blocking(cache::usage).await.map_err(|e| e.to_string())
+}
+
+/// 清空从音频文件提取的封面缓存，返回释放的字节数
+#[tauri::command]
+async fn clear_cover_cache() -> Result<u64, String> {
+    tokio::task::spawn_blocking(cover_cache::clear)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("清理封面缓存失败: {}", e))
+}
+
+/// 清空指定类型的缓存，不指定时清空全部，返回释放的字节数
+#[tauri::command]
+async fn clear_cache(kind: Option<cache::CacheKind>) -> Result<u64, String> {
+    tokio::task::spawn_blocking(move || cache::clear(kind))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("清理缓存失败: {}", e))
+}
+
+/// 获取缓存设置
+#[tauri::command]
+async fn get_cache_settings() -> Result<cache::CacheSettings, String> {
+    Ok(cache::load_settings())
+}
+
+/// 设置缓存总大小上限，超出部分立即按最近最少使用淘汰
+#[tauri::command]
+async fn set_cache_settings(settings: cache::CacheSettings) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || cache::save_settings(&settings))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("保存缓存设置失败: {}", e))
+}
+
+/// 网络是否可用（根据最近一次联网请求能否建立连接判断）
+#[tauri::command]
+async fn get_network_status() -> Result<bool, String> {
+    Ok(net::is_online())
+}
+
+/// 获取webhook配置
+#[tauri::command]
+async fn get_webhooks() -> Result<Vec<webhooks::Webhook>, String> {
+    Ok(webhooks::list())
+}
+
+/// 保存webhook配置（整体替换）
+#[tauri::command]
+async fn set_webhooks(hooks: Vec<webhooks::Webhook>) -> Result<(), String> {
+    webhooks::set(hooks).map_err(|e| format!("保存webhook配置失败: {}", e))
+}
+
+/// 立即触发一次webhook，用于测试配置是否正确
+#[tauri::command]
+async fn test_webhook(hook: webhooks::Webhook) -> Result<webhooks::WebhookTestResult, String> {
+    Ok(webhooks::test_fire(&hook).await)
+}
+
 /// 打开文件对话框添加歌曲，支持音频和视频文件
 #[tauri::command]
 async fn open_audio_files<R: Runtime>(
@@ -271,9 +3153,9 @@ async fn open_audio_files<R: Runtime>(
         app_handle_clone
             .dialog()
             .file()
-            .add_filter("音频文件", &["mp3", "wav", "ogg", "flac", "m4a", "aac"])
+            .add_filter("音频文件", &["mp3", "wav", "ogg", "flac", "m4a", "aac", "mka", "mid", "midi", "kar"])
             .add_filter("视频文件", &["mp4", "mkv", "avi", "mov", "wmv", "flv", "webm", "m4v"])
-            .add_filter("所有媒体文件", &["mp3", "wav", "ogg", "flac", "m4a", "aac", "mp4", "mkv", "avi", "mov", "wmv", "flv", "webm", "m4v"])
+            .add_filter("所有媒体文件", &["mp3", "wav", "ogg", "flac", "m4a", "aac", "mka", "mid", "midi", "kar", "mp4", "mkv", "avi", "mov", "wmv", "flv", "webm", "m4v"])
             .set_title("选择音频或视频文件")
             .pick_files(move |file_paths| {
                 if let Some(paths) = file_paths {
@@ -281,78 +3163,98 @@ async fn open_audio_files<R: Runtime>(
                         return;
                     }
 
-                    let mut songs_to_add = Vec::new(); // 处理每个选中的文件
-                    for path in paths {
-                        let path_str = path.to_string();
-
-                        match SongInfo::from_path(&PathBuf::from(&path_str)) {
-                            Ok(song_info) => {
-                                songs_to_add.push(song_info);
-                            }
-                            Err(e) => {
-                                eprintln!("处理媒体文件失败 {}: {}", path_str, e);
-                            }
-                        }
-                    } // 如果有有效的媒体文件，添加到播放器
-                    if !songs_to_add.is_empty() {
-                        tauri::async_runtime::block_on(async {
-                            let player_guard = player_clone.lock().await;
-                            match player_guard
-                                .player
-                                .send_command(PlayerCommand::AddSongs(songs_to_add))
-                                .await
-                            {
-                                Ok(_) => {
-                                    // 发送songs_added事件
-                                    let _ = app_handle_clone.emit("songs_added", ());
-
-                                    // 同时手动触发播放列表更新，确保前端能收到
-                                    // 获取最新的播放列表
-                                    let updated_playlist = player_guard.player.get_playlist();
-                                    let _ = app_handle_clone.emit(
-                                        "player-event",
-                                        crate::player_fixed::PlayerEvent::PlaylistUpdated(
-                                            updated_playlist,
-                                        ),
-                                    );
-                                }
-                                Err(e) => {
-                                    eprintln!("添加媒体文件失败: {}", e);
-                                    let _ = app_handle_clone
-                                        .emit("player_error", format!("添加媒体文件失败: {}", e));
-                                }
-                            }
-                        });
+                    // 先按文件名加入占位条目（按添加策略处理），元数据在后台读取后逐个替换，
+                    // 选中大量文件时界面不会长时间无响应
+                    let paths: Vec<PathBuf> = paths.iter().map(|path| PathBuf::from(path.to_string())).collect();
+                    let placeholders: Vec<SongInfo> = paths.iter().map(|path| SongInfo::placeholder(path)).collect();
+                    let result = tauri::async_runtime::block_on(async {
+                        let player_guard = player_clone.lock().await;
+                        player_guard.player.send_command(PlayerCommand::Enqueue(placeholders)).await
+                    });
+                    if let Err(e) = result {
+                        eprintln!("添加媒体文件失败: {}", e);
+                        let _ = app_handle_clone.emit("player_error", format!("添加媒体文件失败: {}", e));
+                        return;
                     }
+                    let _ = app_handle_clone.emit("songs_added", ());
+                    tauri::async_runtime::spawn(resolve_placeholders(app_handle_clone, player_clone, paths));
                 }
             });
     });
     Ok(())
 }
 
-/// 获取视频流数据，用于前端播放视频
-#[tauri::command]
-async fn get_video_stream(file_path: String) -> Result<Vec<u8>, String> {
-    println!("开始读取视频文件: {}", file_path);
-    
-    // 检查文件是否存在
-    if !std::path::Path::new(&file_path).exists() {
-        return Err(format!("视频文件不存在: {}", file_path));
+/// 在后台线程中并行读取占位条目的元数据，每读完一个文件就替换播放列表中的占位条目
+/// （播放器发送 SongMetadataUpdated 事件），读取失败的文件保留占位条目
+async fn resolve_placeholders<R: Runtime>(
+    app_handle: AppHandle<R>,
+    player: Arc<AsyncMutex<PlayerWrapper>>,
+    paths: Vec<PathBuf>,
+) {
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(2)
+        .min(IMPORT_MAX_WORKERS);
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(workers));
+    let total = paths.len();
+    let mut tasks = tokio::task::JoinSet::new();
+    for path in paths {
+        let semaphore = semaphore.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let result = tokio::task::spawn_blocking({
+                let path = path.clone();
+                move || songs_from_path(&path)
+            })
+            .await;
+            (path, result)
+        });
     }
-    
-    // 读取视频文件
-    match std::fs::read(&file_path) {
-        Ok(data) => {
-            println!("成功读取视频文件，大小: {} 字节", data.len());
-            Ok(data)
+
+    let (mut processed, mut resolved) = (0, 0);
+    while let Some(joined) = tasks.join_next().await {
+        processed += 1;
+        let (path, result) = match joined {
+            Ok(done) => done,
+            Err(e) => {
+                eprintln!("读取元数据的任务异常结束: {}", e);
+                continue;
+            }
+        };
+        match result.map_err(anyhow::Error::from).and_then(|r| r) {
+            Ok(songs) if !songs.is_empty() => {
+                index_in_library(&songs);
+                let command = PlayerCommand::ResolvePlaceholder {
+                    path: path.to_string_lossy().into_owned(),
+                    songs,
+                };
+                let player_guard = player.lock().await;
+                match player_guard.player.send_command(command).await {
+                    Ok(()) => resolved += 1,
+                    Err(e) => eprintln!("更新播放列表条目失败 {}: {}", path.display(), e),
+                }
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("处理媒体文件失败 {}: {}", path.display(), e),
         }
-        Err(e) => {
-            eprintln!("读取视频文件失败: {}", e);
-            Err(format!("读取视频文件失败: {}", e))
+        if processed % 10 == 0 || processed == total {
+            let _ = app_handle.emit(
+                "songs_import_progress",
+                serde_json::json!({ "processed": processed, "total": total, "added": resolved }),
+            );
         }
     }
 }
 
+/// 获取视频的播放地址：通过 stream:// 协议按范围读取，前端 <video> 可以边播边读并直接跳转
+#[tauri::command]
+async fn get_video_stream(file_path: String) -> Result<String, String> {
+    if !std::path::Path::new(&file_path).exists() {
+        return Err(format!("视频文件不存在: {}", file_path));
+    }
+    Ok(video_stream::url_for(&file_path))
+}
+
 #[tauri::command]
 async fn get_initial_player_state(
     _state: State<'_, AppState>,
@@ -360,12 +3262,12 @@ async fn get_initial_player_state(
     let player_instance = get_player_instance().await?;
     let player_state_guard = player_instance.lock().await;
 
-    // 使用默认音量1.0
     Ok(InitialPlayerState {
         songs: player_state_guard.player.get_playlist(),
         current_song_index: player_state_guard.player.get_current_index(),
         is_playing: player_state_guard.player.get_state() == PlayerState::Playing,
-        volume: 1.0, // 使用默认音量值
+        volume: player_state_guard.player.get_volume(),
+        muted: player_state_guard.player.is_muted(),
         play_mode: player_state_guard.player.get_play_mode(),
     })
 }
@@ -376,37 +3278,304 @@ fn setup_app<R: Runtime>(app: &mut tauri::App<R>) -> Result<(), Box<dyn std::err
     let app_state = AppState {};
     app.manage(app_state);
 
+    // 剪贴板监听（按设置开启）
+    clipboard_watch::start(app.handle().clone());
+
+    // 监听音乐库文件夹，文件变化时自动更新音乐库
+    library_watch::start(app.handle().clone());
+
+    // 按保存的设置启动远程控制接口
+    tauri::async_runtime::spawn(remote_api::start_saved());
+
+    // 注册全局快捷键
+    #[cfg(desktop)]
+    hotkeys::register(app.handle());
+
+    // 打开 musicplayer:// 链接；Windows/Linux 下开发和免安装运行时需要在运行时注册协议
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        eprintln!("注册链接协议失败: {}", e);
+    }
+    let handle = app.handle().clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            open_deep_link(&handle, url);
+        }
+    });
+    match app.deep_link().get_current() {
+        Ok(urls) => {
+            for url in urls.unwrap_or_default() {
+                open_deep_link(app.handle(), url);
+            }
+        }
+        Err(e) => eprintln!("读取启动链接失败: {}", e),
+    }
+
+    // MIDI 播放使用随应用打包的 SoundFont
+    match app.path().resource_dir() {
+        Ok(dir) => midi::set_resource_dir(dir),
+        Err(e) => eprintln!("获取资源目录失败: {}", e),
+    }
+    if let Err(e) = midi::check_sound_font() {
+        eprintln!("{}", e);
+    }
+
     Ok(())
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    let builder = tauri::Builder::default();
+    // 单实例：再次启动（包括点击链接）时交给正在运行的实例，链接由 deep-link 插件转发
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+        if !argv.iter().any(|arg| deep_link::is_link(arg)) {
+            deep_link::show_main_window(app);
+        }
+    }));
+    // 全局快捷键在窗口不在前台时也能控制播放
+    #[cfg(desktop)]
+    let builder = builder.plugin(
+        tauri_plugin_global_shortcut::Builder::new()
+            .with_handler(|_app, shortcut, event| {
+                if event.state() == tauri_plugin_global_shortcut::ShortcutState::Pressed {
+                    if let Some(action) = hotkeys::action_for(shortcut) {
+                        dispatch_hotkey(action);
+                    }
+                }
+            })
+            .build(),
+    );
+    builder
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        // Android 系统媒体会话（通知栏、锁屏、蓝牙/车机界面）
+        .plugin(media_session::init(|action| {
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = media_session_action(action).await {
+                    eprintln!("执行媒体会话操作失败: {}", e);
+                }
+            });
+        }))
+        // 视频按 Range 分段读取，不必整个读进内存
+        .register_asynchronous_uri_scheme_protocol(video_stream::SCHEME, |_ctx, request, responder| {
+            tauri::async_runtime::spawn_blocking(move || responder.respond(video_stream::handle(&request)));
+        })
         .setup(setup_app)
         .invoke_handler(tauri::generate_handler![
             init_player,
+            get_startup_options,
+            set_startup_options,
+            save_session,
+            load_session,
+            list_sessions,
+            delete_session,
+            get_setlist,
+            get_now_playing_dir,
+            get_media_session,
+            media_session_action,
+            get_hotkeys,
+            set_hotkey,
+            clear_setlist,
+            export_setlist,
             get_player_state,
             get_playlist,
+            get_album_cover,
+            search_songs,
+            get_low_memory_settings,
+            set_low_memory_settings,
+            get_song_details,
             get_current_index,
             get_play_mode,
+            get_up_next,
             play,
             pause,
             next,
             previous,
             set_song,
             add_song,
+            get_container_info,
+            list_container_entries,
+            add_container_entries,
+            get_clipboard_watch,
+            set_clipboard_watch,
+            add_from_clipboard,
+            get_midi_options,
+            set_midi_options,
+            check_midi_sound_font,
+            play_album_of_current,
+            set_enqueue_policy,
+            get_enqueue_policy,
+            get_gapless_info,
+            library_scan,
+            analyze_loudness,
+            library_query,
+            library_stats,
+            get_library_folders,
+            set_library_watch,
+            set_rating,
+            toggle_favorite,
+            get_favorites,
+            get_track_stats,
+            get_top_artists,
+            get_total_listening_time,
+            verify_library,
+            reset_track_checksum,
+            get_maintenance_settings,
+            set_maintenance_settings,
+            get_maintenance_status,
+            run_maintenance_now,
+            cancel_maintenance,
+            get_metadata_candidates,
+            get_tag_write_settings,
+            get_skip_filter_settings,
+            set_skip_filter_settings,
+            get_radio_host_settings,
+            set_radio_host_settings,
+            get_replaygain_settings,
+            set_replaygain_settings,
+            set_replaygain_mode,
+            get_seek_step_settings,
+            set_seek_step_settings,
+            get_progress_settings,
+            set_progress_settings,
+            get_collation_settings,
+            set_collation_settings,
+            list_library_tracks,
+            set_tag_write_settings,
+            batch_edit_tags,
+            update_tags,
+            set_album_cover,
+            get_cover_fetch_settings,
+            set_cover_fetch_settings,
+            fetch_cover,
+            find_missing_covers,
+            get_lyrics_fetch_settings,
+            set_lyrics_fetch_settings,
+            fetch_lyrics,
+            set_lyrics_offset,
+            save_lyrics,
+            show_desktop_lyrics,
+            hide_desktop_lyrics,
+            get_subtitles,
+            set_subtitle_track,
+            get_metadata_priority,
+            set_metadata_priority,
+            debug_extract,
+            set_metadata_choice,
+            clear_metadata_choice,
+            get_trim_points,
+            set_trim_points,
+            clear_trim_points,
+            set_resume_enabled,
+            dismiss_intro_skip,
+            analyze_library_bpm,
+            build_tempo_queue,
             remove_song,
+            move_song,
+            sort_playlist,
+            set_volume,
+            get_volume,
+            toggle_mute,
             clear_playlist,
+            cleanup_playlist,
+            set_playlist_entry_style,
+            get_playlist_groups,
+            export_tracks,
+            save_playlist,
+            load_playlist,
+            list_saved_playlists,
+            delete_saved_playlist,
+            import_playlist,
+            export_playlist,
+            diff_playlists,
+            merge_playlists,
             set_play_mode,
+            set_silence_gap,
+            stop_after_tracks,
+            get_stop_after_tracks,
+            replay_last,
+            skip_forward,
+            skip_backward,
+            next_chapter,
+            previous_chapter,
+            seek_to_chapter,
+            set_ab_loop,
+            clear_ab_loop,
+            start_ab_compare,
+            switch_ab,
+            stop_ab_compare,
+            get_device_latency,
+            list_device_latency,
+            set_device_latency,
+            get_position_ms,
+            start_latency_calibration,
+            finish_latency_calibration,
+            get_silence_gap,
+            set_live_album_mode,
+            get_live_album_mode,
+            set_stop_at_playlist_end,
+            get_stop_at_playlist_end,
+            set_idle_release_timeout,
+            list_output_devices,
+            set_output_device,
+            get_output_device,
+            get_dsp_config,
+            set_dsp_config,
+            list_dsp_profiles,
+            get_playback_diagnostics,
+            get_watchdog_settings,
+            set_watchdog_settings,
+            add_marker,
+            list_markers,
+            remove_marker,
+            jump_to_marker,
+            clear_playback_diagnostics,
             seek_to,
             open_audio_files,
+            set_http_server_enabled,
+            get_http_server_port,
+            set_remote_api_enabled,
+            get_remote_api_settings,
+            reset_remote_api_token,
+            reset_remote_guest_token,
+            get_remote_qr,
+            get_party_settings,
+            set_party_settings,
+            get_party_state,
+            get_all_settings,
+            get_setting,
+            set_setting,
+            add_podcast,
+            remove_podcast,
+            get_podcasts,
+            refresh_podcasts,
+            get_episodes,
+            download_episode,
+            play_episode,
+            list_cast_targets,
+            cast_to,
+            get_cast_status,
+            get_proxy_settings,
+            set_proxy_settings,
+            get_network_status,
+            get_cache_usage,
+            clear_cache,
+            clear_cover_cache,
+            get_cache_settings,
+            set_cache_settings,
+            get_webhooks,
+            set_webhooks,
+            test_webhook,
             get_initial_player_state,
             get_video_stream,
             update_video_progress,
             toggle_playback_mode,
             set_playback_mode,
             get_current_playback_mode,
+            get_state_nonblocking,
+            handle_voice_command,
             check_song_mode_support,
             // 新增：音视频互斥控制命令
             force_stop_audio,
@@ -415,8 +3584,14 @@ pub fn run() {
             activate_audio_player,
             activate_video_player,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                settings::flush();
+                session::flush();
+            }
+        });
 }
 
 /// 更新视频播放进度，专门用于视频文件的进度同步
@@ -429,8 +3604,8 @@ async fn update_video_progress(position: u64, duration: u64, _state: tauri::Stat
     if let Some(current_idx) = player_state_guard.player.get_current_index() {
         let playlist = player_state_guard.player.get_playlist();
         if let Some(song) = playlist.get(current_idx) {
-            // 只有当前播放的是视频文件时才处理
-            if song.media_type == Some(crate::player_fixed::MediaType::Video) {
+            // 只有当前播放的是视频文件或带MV的歌曲时才处理（播放器线程再按播放模式判断）
+            if song.media_type == Some(crate::player_fixed::MediaType::Video) || song.mv_path.is_some() {
                 //直接访问事件发送器来发送进度更新
                 player_state_guard
                     .player
@@ -504,28 +3679,46 @@ async fn activate_video_player(_state: tauri::State<'_, AppState>) -> Result<(),
         .map_err(|e| e.to_string())
 }
 
-/// 切换播放模式（音频/视频）
+/// 切换播放模式（音频/视频），从原来的位置继续；position 为前端视频当前的位置（秒），从MV切回音频时使用
 #[tauri::command]
-async fn toggle_playback_mode(_state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn toggle_playback_mode(position: Option<u64>, _state: tauri::State<'_, AppState>) -> Result<(), String> {
     let player_instance = get_player_instance().await?;
     let player_state_guard = player_instance.lock().await;
     player_state_guard
         .player
-        .send_command(PlayerCommand::TogglePlaybackMode)
+        .send_command(PlayerCommand::TogglePlaybackMode { position })
         .await
         .map_err(|e| e.to_string())
 }
 
-/// 设置播放模式
+/// 设置播放模式，从原来的位置继续；position 同 toggle_playback_mode
 #[tauri::command]
-async fn set_playback_mode(mode: crate::player_fixed::MediaType, _state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn set_playback_mode<R: Runtime>(
+    app_handle: AppHandle<R>,
+    mode: crate::player_fixed::MediaType,
+    position: Option<u64>,
+    _state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
     let player_instance = get_player_instance().await?;
     let player_state_guard = player_instance.lock().await;
     player_state_guard
         .player
-        .send_command(PlayerCommand::SetPlaybackMode(mode))
+        .send_command(PlayerCommand::SetPlaybackMode { mode, position })
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    settings::remember(&app_handle, |preferences| preferences.playback_mode = mode).map_err(|e| e.to_string())
+}
+
+/// 获取播放器状态快照，不等待正在进行的切歌/跳转等文件IO，适合界面轮询
+#[tauri::command]
+async fn get_state_nonblocking(
+    _state: tauri::State<'_, AppState>,
+) -> Result<player_safe::SafePlayerStateSnapshot, String> {
+    let player_instance = get_player_instance().await?;
+    let player = player_instance.lock().await.player.clone();
+    let mut snapshot = player.get_state_nonblocking();
+    snapshot.playlist = std::mem::take(&mut snapshot.playlist).into_iter().map(SongInfo::without_cover).collect();
+    Ok(snapshot)
 }
 
 /// 获取当前播放模式
@@ -0,0 +1,1222 @@
+use crate::collation;
+use crate::gapless::GaplessInfo;
+use crate::player_fixed::{self, SongInfo};
+use crate::storage;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+/// 数据库结构迁移，按顺序执行，已执行的版本记录在 PRAGMA user_version 中
+const MIGRATIONS: &[&str] = &[
+    // 1: 曲目内命名标记
+    "CREATE TABLE markers (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        path TEXT NOT NULL,
+        position_ms INTEGER NOT NULL,
+        label TEXT NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX idx_markers_path ON markers(path);",
+    // 2: 曲目索引
+    "CREATE TABLE tracks (
+        path TEXT PRIMARY KEY,
+        title TEXT,
+        artist TEXT,
+        album TEXT,
+        track_number INTEGER,
+        disc_number INTEGER,
+        duration INTEGER,
+        added_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+    CREATE INDEX idx_tracks_album ON tracks(album);",
+    // 3: 编码器延迟/填充（无缝播放）
+    "ALTER TABLE tracks ADD COLUMN encoder_delay INTEGER;
+    ALTER TABLE tracks ADD COLUMN encoder_padding INTEGER;
+    ALTER TABLE tracks ADD COLUMN total_samples INTEGER;",
+    // 4: 节拍速度
+    "ALTER TABLE tracks ADD COLUMN bpm REAL;
+    CREATE INDEX idx_tracks_bpm ON tracks(bpm);",
+    // 5: 曲目起止裁剪点
+    "CREATE TABLE trim_points (
+        path TEXT PRIMARY KEY,
+        start_ms INTEGER NOT NULL DEFAULT 0,
+        end_ms INTEGER,
+        updated_at INTEGER NOT NULL
+    );",
+    // 6: 跳过前奏的行为记录
+    "CREATE TABLE intro_skips (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        path TEXT NOT NULL,
+        target_ms INTEGER NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX idx_intro_skips_path ON intro_skips(path);
+    CREATE TABLE intro_skip_dismissed (
+        path TEXT PRIMARY KEY,
+        dismissed_at INTEGER NOT NULL
+    );",
+    // 7: 文件内容校验
+    "ALTER TABLE tracks ADD COLUMN content_hash TEXT;
+    ALTER TABLE tracks ADD COLUMN file_size INTEGER;
+    ALTER TABLE tracks ADD COLUMN file_mtime INTEGER;
+    ALTER TABLE tracks ADD COLUMN verified_at INTEGER;",
+    // 8: 用户选定的元数据（多个标签库结果冲突时）
+    "CREATE TABLE metadata_overrides (
+        path TEXT PRIMARY KEY,
+        title TEXT,
+        artist TEXT,
+        album TEXT,
+        track_number INTEGER,
+        disc_number INTEGER,
+        updated_at INTEGER NOT NULL
+    );",
+    // 9: 响度分析（维护任务），loudness_at 非空表示已测量（过静无法测量时 loudness_lufs 为空）
+    "ALTER TABLE tracks ADD COLUMN loudness_lufs REAL;
+    ALTER TABLE tracks ADD COLUMN loudness_at INTEGER;",
+    // 10: 排序键（按排序规则设置生成，规则变化时重建）
+    "ALTER TABLE tracks ADD COLUMN title_sort TEXT;
+    ALTER TABLE tracks ADD COLUMN artist_sort TEXT;
+    ALTER TABLE tracks ADD COLUMN album_sort TEXT;
+    CREATE INDEX idx_tracks_title_sort ON tracks(title_sort);
+    CREATE INDEX idx_tracks_artist_sort ON tracks(artist_sort, album_sort);",
+    // 11: 扫描记录的文件修改时间（未变化的文件重新扫描时跳过）和封面来源
+    "ALTER TABLE tracks ADD COLUMN mtime INTEGER;
+    ALTER TABLE tracks ADD COLUMN cover_ref TEXT;",
+    // 12: 播放统计（串流URL等不在曲目索引中的音源也记录，所以单独建表）
+    "CREATE TABLE play_stats (
+        path TEXT PRIMARY KEY,
+        title TEXT,
+        artist TEXT,
+        play_count INTEGER NOT NULL DEFAULT 0,
+        listen_secs INTEGER NOT NULL DEFAULT 0,
+        last_played INTEGER
+    );",
+    // 13: 评分和收藏
+    "CREATE TABLE ratings (
+        path TEXT PRIMARY KEY,
+        rating INTEGER,
+        favorite INTEGER NOT NULL DEFAULT 0,
+        updated_at INTEGER NOT NULL
+    );
+    CREATE INDEX idx_ratings_favorite ON ratings(favorite);",
+    // 14: 响度分析时测得的采样峰值（用于防削波）
+    "ALTER TABLE tracks ADD COLUMN loudness_peak REAL;",
+    // 15: 续播位置（enabled 为空时按曲目时长自动决定）
+    "CREATE TABLE resume_positions (
+        path TEXT PRIMARY KEY,
+        enabled INTEGER,
+        position_ms INTEGER,
+        updated_at INTEGER NOT NULL
+    );",
+    // 16: 每首歌的歌词时间偏移（毫秒，正数表示歌词提前）
+    "CREATE TABLE lyrics_offsets (
+        path TEXT PRIMARY KEY,
+        offset_ms INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );",
+];
+
+/// 音乐库中的曲目记录
+#[derive(Debug, Clone, Serialize)]
+pub struct LibraryTrack {
+    pub path: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    #[serde(rename = "trackNumber")]
+    pub track_number: Option<u32>,
+    #[serde(rename = "discNumber")]
+    pub disc_number: Option<u32>,
+    pub duration: Option<u64>,
+    pub bpm: Option<f32>,
+    pub mtime: Option<i64>, // 索引时的文件修改时间（Unix秒）
+    #[serde(rename = "coverRef")]
+    pub cover_ref: Option<String>, // "embedded" 表示内嵌封面，否则为同目录封面图片的路径
+}
+
+const TRACK_COLUMNS: &str = "path, title, artist, album, track_number, disc_number, duration, bpm, mtime, cover_ref";
+
+fn track_from_row(row: &rusqlite::Row) -> rusqlite::Result<LibraryTrack> {
+    Ok(LibraryTrack {
+        path: row.get(0)?,
+        title: row.get(1)?,
+        artist: row.get(2)?,
+        album: row.get(3)?,
+        track_number: row.get::<_, Option<i64>>(4)?.map(|n| n as u32),
+        disc_number: row.get::<_, Option<i64>>(5)?.map(|n| n as u32),
+        duration: row.get::<_, Option<i64>>(6)?.map(|n| n as u64),
+        bpm: row.get::<_, Option<f64>>(7)?.map(|n| n as f32),
+        mtime: row.get(8)?,
+        cover_ref: row.get(9)?,
+    })
+}
+
+/// 文件修改时间（Unix秒）
+pub fn file_mtime(path: &std::path::Path) -> Option<i64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    modified
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs() as i64)
+}
+
+/// 曲目封面的来源：内嵌封面，或同目录下的封面图片（载入时记录在 SongInfo 中；视频缩略图等没有记录的封面视为内嵌）
+fn cover_reference(song: &SongInfo) -> Option<String> {
+    song.cover_source
+        .clone()
+        .or_else(|| song.has_embedded_cover().then(|| player_fixed::EMBEDDED_COVER.to_string()))
+}
+
+/// 音乐库查询条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TrackQuery {
+    #[serde(default)]
+    pub text: Option<String>, // 匹配标题、艺术家或专辑
+    #[serde(default)]
+    pub artist: Option<String>,
+    #[serde(default)]
+    pub album: Option<String>,
+    #[serde(default)]
+    pub order: TrackOrder,
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// 音乐库统计
+#[derive(Debug, Clone, Serialize)]
+pub struct LibraryStats {
+    pub tracks: usize,
+    pub artists: usize,
+    pub albums: usize,
+    #[serde(rename = "totalDuration")]
+    pub total_duration: u64, // 单位：秒
+    #[serde(rename = "withCover")]
+    pub with_cover: usize,
+    #[serde(rename = "lastUpdated")]
+    pub last_updated: Option<i64>, // 最近一次写入曲目的时间（Unix秒）
+}
+
+/// 曲目列表的排序字段
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrackOrder {
+    Title,
+    #[default]
+    Artist,
+    Album,
+}
+
+impl TrackOrder {
+    /// 对应的 ORDER BY 子句，无标题/艺术家/专辑的曲目排在最后
+    fn order_by(self) -> &'static str {
+        match self {
+            TrackOrder::Title => "title_sort IS NULL, title_sort, path",
+            TrackOrder::Artist => {
+                "artist_sort IS NULL, artist_sort, album_sort, COALESCE(disc_number, 1), COALESCE(track_number, 0), path"
+            }
+            TrackOrder::Album => "album_sort IS NULL, album_sort, COALESCE(disc_number, 1), COALESCE(track_number, 0), path",
+        }
+    }
+}
+
+/// 曲目内的命名标记（如"solo at 2:31"、"chapter 3"）
+#[derive(Debug, Clone, Serialize)]
+pub struct Marker {
+    pub id: i64,
+    pub path: String,
+    pub position: u64, // 单位：毫秒
+    pub label: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: u64, // Unix秒
+}
+
+/// 曲目的播放起止点，播放时跳过 start 之前和 end 之后的部分
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrimPoints {
+    #[serde(rename = "startMs")]
+    pub start_ms: u64,
+    #[serde(rename = "endMs")]
+    pub end_ms: Option<u64>,
+}
+
+/// 曲目的续播设置和上次播放到的位置
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ResumePoint {
+    pub enabled: Option<bool>, // 用户设置的开关，为空时按曲目时长自动决定
+    #[serde(rename = "positionMs")]
+    pub position_ms: Option<u64>,
+}
+
+/// 从文件载入曲目时需要的音乐库数据（用户选定的元数据、评分、起止点、续播、响度、歌词偏移），一次取出
+#[derive(Debug, Clone, Default)]
+pub struct TrackExtras {
+    pub metadata: Option<MetadataOverride>,
+    pub rating: Option<(Option<u8>, bool)>,
+    pub trim: Option<TrimPoints>,
+    pub resume: Option<ResumePoint>,
+    pub loudness: Option<(f64, Option<f32>)>,
+    pub lyrics_offset_ms: i64,
+}
+
+/// 用户为曲目选定的元数据，为空的字段沿用标签中读取的值
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetadataOverride {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub artist: Option<String>,
+    #[serde(default)]
+    pub album: Option<String>,
+    #[serde(rename = "trackNumber", default)]
+    pub track_number: Option<u32>,
+    #[serde(rename = "discNumber", default)]
+    pub disc_number: Option<u32>,
+}
+
+/// 单曲播放统计
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackStats {
+    pub path: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    #[serde(rename = "playCount")]
+    pub play_count: u64,
+    #[serde(rename = "listenSecs")]
+    pub listen_secs: u64,
+    #[serde(rename = "lastPlayed")]
+    pub last_played: Option<u64>, // Unix秒
+}
+
+/// 艺术家播放统计
+#[derive(Debug, Clone, Serialize)]
+pub struct ArtistStats {
+    pub artist: String,
+    #[serde(rename = "playCount")]
+    pub play_count: u64,
+    #[serde(rename = "listenSecs")]
+    pub listen_secs: u64,
+    pub tracks: usize,
+}
+
+/// 评分或收藏过的曲目
+#[derive(Debug, Clone, Serialize)]
+pub struct RatedTrack {
+    pub path: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub rating: Option<u8>,
+    pub favorite: bool,
+}
+
+/// 曲目文件的校验记录
+#[derive(Debug, Clone)]
+pub struct ChecksumRecord {
+    pub path: String,
+    pub hash: Option<String>,
+    pub size: Option<u64>,
+    pub mtime: Option<i64>,
+}
+
+/// 音乐库（SQLite 持久化）
+pub struct Library {
+    conn: Connection,
+}
+
+static LIBRARY: OnceLock<Result<Mutex<Library>, String>> = OnceLock::new();
+
+/// 曲目索引的修订号，每次添加、更新或移除曲目后递增，用于判断内存中的缓存（如搜索索引）是否过期
+static REVISION: AtomicU64 = AtomicU64::new(0);
+
+/// 当前曲目索引的修订号
+pub fn revision() -> u64 {
+    REVISION.load(Ordering::Acquire)
+}
+
+/// 在全局音乐库上执行操作
+pub fn with_library<T>(f: impl FnOnce(&mut Library) -> anyhow::Result<T>) -> Result<T, String> {
+    let library = LIBRARY
+        .get_or_init(|| {
+            let path = storage::data_dir().join("library.db");
+            Library::open(&path)
+                .map(Mutex::new)
+                .map_err(|e| format!("无法打开音乐库 {}: {}", path.display(), e))
+        })
+        .as_ref()
+        .map_err(|e| e.clone())?;
+    let mut guard = library.lock().map_err(|_| "无法锁定音乐库".to_string())?;
+    f(&mut guard).map_err(|e| e.to_string())
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl Library {
+    /// 打开（或创建）音乐库数据库并执行迁移
+    pub fn open(path: &std::path::Path) -> anyhow::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        let mut library = Library { conn };
+        library.migrate()?;
+        // 升级后旧曲目还没有排序键
+        let missing: i64 = library
+            .conn
+            .query_row("SELECT COUNT(*) FROM tracks WHERE title_sort IS NULL", [], |row| row.get(0))?;
+        if missing > 0 {
+            library.rebuild_sort_keys()?;
+        }
+        Ok(library)
+    }
+
+    fn migrate(&mut self) -> anyhow::Result<()> {
+        let version: usize = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get::<_, i64>(0))? as usize;
+        for (idx, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = self.conn.transaction()?;
+            tx.execute_batch(sql)?;
+            tx.pragma_update(None, "user_version", (idx + 1) as i64)?;
+            tx.commit()?;
+            println!("📚 音乐库已迁移到版本 {}", idx + 1);
+        }
+        Ok(())
+    }
+
+    /// 添加曲目标记
+    pub fn add_marker(&mut self, path: &str, position_ms: u64, label: &str) -> anyhow::Result<Marker> {
+        let created_at = now_secs();
+        self.conn.execute(
+            "INSERT INTO markers (path, position_ms, label, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![path, position_ms as i64, label, created_at as i64],
+        )?;
+        Ok(Marker {
+            id: self.conn.last_insert_rowid(),
+            path: path.to_string(),
+            position: position_ms,
+            label: label.to_string(),
+            created_at,
+        })
+    }
+
+    /// 列出曲目的所有标记（按位置排序）
+    pub fn list_markers(&self, path: &str) -> anyhow::Result<Vec<Marker>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, path, position_ms, label, created_at FROM markers WHERE path = ?1 ORDER BY position_ms",
+        )?;
+        let markers = stmt
+            .query_map(params![path], |row| {
+                Ok(Marker {
+                    id: row.get(0)?,
+                    path: row.get(1)?,
+                    position: row.get::<_, i64>(2)? as u64,
+                    label: row.get(3)?,
+                    created_at: row.get::<_, i64>(4)? as u64,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(markers)
+    }
+
+    /// 获取单个标记
+    pub fn get_marker(&self, id: i64) -> anyhow::Result<Option<Marker>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT id, path, position_ms, label, created_at FROM markers WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok(Marker {
+                        id: row.get(0)?,
+                        path: row.get(1)?,
+                        position: row.get::<_, i64>(2)? as u64,
+                        label: row.get(3)?,
+                        created_at: row.get::<_, i64>(4)? as u64,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// 删除标记，返回被删除标记所属的曲目路径
+    pub fn remove_marker(&mut self, id: i64) -> anyhow::Result<Option<String>> {
+        let path = self.get_marker(id)?.map(|m| m.path);
+        self.conn.execute("DELETE FROM markers WHERE id = ?1", params![id])?;
+        Ok(path)
+    }
+
+    /// 将歌曲写入曲目索引（已存在则更新元数据）
+    pub fn upsert_tracks(&mut self, songs: &[SongInfo]) -> anyhow::Result<()> {
+        let now = now_secs() as i64;
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO tracks (path, title, artist, album, track_number, disc_number, duration, added_at, updated_at,
+                                     encoder_delay, encoder_padding, total_samples, title_sort, artist_sort, album_sort,
+                                     mtime, cover_ref)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
+                 ON CONFLICT(path) DO UPDATE SET
+                    title = excluded.title,
+                    artist = excluded.artist,
+                    album = excluded.album,
+                    track_number = excluded.track_number,
+                    disc_number = excluded.disc_number,
+                    duration = excluded.duration,
+                    updated_at = excluded.updated_at,
+                    encoder_delay = excluded.encoder_delay,
+                    encoder_padding = excluded.encoder_padding,
+                    total_samples = excluded.total_samples,
+                    title_sort = excluded.title_sort,
+                    artist_sort = excluded.artist_sort,
+                    album_sort = excluded.album_sort,
+                    mtime = excluded.mtime,
+                    cover_ref = excluded.cover_ref",
+            )?;
+            let collation = collation::settings();
+            let key = |value: &Option<String>| value.as_deref().map(|v| collation::sort_key_with(v, &collation));
+            for song in songs {
+                stmt.execute(params![
+                    song.path,
+                    song.title,
+                    song.artist,
+                    song.album,
+                    song.track_number.map(|n| n as i64),
+                    song.disc_number.map(|n| n as i64),
+                    song.duration.map(|d| d as i64),
+                    now,
+                    song.gapless.map(|g| g.delay as i64),
+                    song.gapless.map(|g| g.padding as i64),
+                    song.gapless.and_then(|g| g.total_samples).map(|n| n as i64),
+                    key(&song.title).unwrap_or_else(|| collation::sort_key_with(&song.path, &collation)),
+                    key(&song.artist),
+                    key(&song.album),
+                    file_mtime(std::path::Path::new(&song.path)),
+                    cover_reference(song),
+                ])?;
+            }
+        }
+        tx.commit()?;
+        REVISION.fetch_add(1, Ordering::AcqRel);
+        Ok(())
+    }
+
+    /// 按路径查询单个曲目
+    pub fn track(&self, path: &str) -> anyhow::Result<Option<LibraryTrack>> {
+        let track = self
+            .conn
+            .query_row(
+                &format!("SELECT {} FROM tracks WHERE path = ?1", TRACK_COLUMNS),
+                params![path],
+                track_from_row,
+            )
+            .optional()?;
+        Ok(track)
+    }
+
+    /// 曲目在音乐库中的 ID（rowid，曲目更新时保持不变），不在音乐库中的路径没有对应项
+    pub fn track_ids(&self, paths: &[String]) -> anyhow::Result<std::collections::HashMap<String, i64>> {
+        let mut stmt = self.conn.prepare("SELECT rowid FROM tracks WHERE path = ?1")?;
+        let mut ids = std::collections::HashMap::new();
+        for path in paths {
+            if let Some(id) = stmt.query_row(params![path], |row| row.get(0)).optional()? {
+                ids.insert(path.clone(), id);
+            }
+        }
+        Ok(ids)
+    }
+
+    /// 按 ID 查询曲目路径
+    pub fn track_path(&self, id: i64) -> anyhow::Result<Option<String>> {
+        let path = self
+            .conn
+            .query_row("SELECT path FROM tracks WHERE rowid = ?1", params![id], |row| row.get(0))
+            .optional()?;
+        Ok(path)
+    }
+
+    /// 查询专辑的全部曲目，按碟号、音轨号排序
+    /// 给出艺术家时只返回该艺术家的曲目，避免同名专辑混在一起
+    pub fn album_tracks(&self, album: &str, artist: Option<&str>) -> anyhow::Result<Vec<LibraryTrack>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM tracks
+             WHERE album = ?1 AND (?2 IS NULL OR artist = ?2)
+             ORDER BY COALESCE(disc_number, 1), COALESCE(track_number, 0), path",
+            TRACK_COLUMNS
+        ))?;
+        let tracks = stmt
+            .query_map(params![album, artist], track_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(tracks)
+    }
+
+    /// 获取曲目的编码器延迟/填充信息
+    pub fn gapless_info(&self, path: &str) -> anyhow::Result<Option<GaplessInfo>> {
+        let info = self
+            .conn
+            .query_row(
+                "SELECT encoder_delay, encoder_padding, total_samples FROM tracks WHERE path = ?1",
+                params![path],
+                |row| {
+                    let delay: Option<i64> = row.get(0)?;
+                    let padding: Option<i64> = row.get(1)?;
+                    let total: Option<i64> = row.get(2)?;
+                    Ok(delay.map(|delay| GaplessInfo {
+                        delay: delay as u32,
+                        padding: padding.unwrap_or(0) as u32,
+                        total_samples: total.map(|n| n as u64),
+                    }))
+                },
+            )
+            .optional()?;
+        Ok(info.flatten())
+    }
+
+    /// 列出尚未分析BPM的曲目路径
+    pub fn tracks_missing_bpm(&self, limit: usize) -> anyhow::Result<Vec<String>> {
+        let mut stmt = self.conn.prepare("SELECT path FROM tracks WHERE bpm IS NULL ORDER BY added_at LIMIT ?1")?;
+        let paths = stmt
+            .query_map(params![limit as i64], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(paths)
+    }
+
+    /// 记录曲目BPM，无法分析的曲目记为0，避免重复分析
+    pub fn set_bpm(&mut self, path: &str, bpm: f32) -> anyhow::Result<()> {
+        self.conn.execute(
+            "UPDATE tracks SET bpm = ?2 WHERE path = ?1",
+            params![path, bpm as f64],
+        )?;
+        Ok(())
+    }
+
+    /// 列出所有已知BPM的曲目
+    pub fn tracks_with_bpm(&self) -> anyhow::Result<Vec<LibraryTrack>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT {} FROM tracks WHERE bpm > 0 ORDER BY bpm", TRACK_COLUMNS))?;
+        let tracks = stmt.query_map([], track_from_row)?.collect::<Result<Vec<_>, _>>()?;
+        Ok(tracks)
+    }
+
+    /// 按标题/艺术家/专辑模糊查找曲目（包含匹配，不区分大小写），any 匹配任一字段
+    pub fn find_tracks(
+        &self,
+        title: Option<&str>,
+        artist: Option<&str>,
+        album: Option<&str>,
+        any: Option<&str>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<LibraryTrack>> {
+        let pattern = |value: Option<&str>| value.map(|v| format!("%{}%", v.replace(['%', '_'], "")));
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM tracks
+             WHERE (?1 IS NULL OR title LIKE ?1)
+               AND (?2 IS NULL OR artist LIKE ?2)
+               AND (?3 IS NULL OR album LIKE ?3)
+               AND (?4 IS NULL OR title LIKE ?4 OR artist LIKE ?4 OR album LIKE ?4)
+             ORDER BY artist_sort, album_sort, COALESCE(disc_number, 1), COALESCE(track_number, 0), path
+             LIMIT ?5",
+            TRACK_COLUMNS
+        ))?;
+        let tracks = stmt
+            .query_map(
+                params![pattern(title), pattern(artist), pattern(album), pattern(any), limit as i64],
+                track_from_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(tracks)
+    }
+
+    /// 按排序规则分页列出曲目
+    pub fn tracks_sorted(&self, order: TrackOrder, offset: usize, limit: usize) -> anyhow::Result<Vec<LibraryTrack>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM tracks ORDER BY {} LIMIT ?1 OFFSET ?2",
+            TRACK_COLUMNS,
+            order.order_by()
+        ))?;
+        let tracks = stmt
+            .query_map(params![limit as i64, offset as i64], track_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(tracks)
+    }
+
+    /// 按条件查询曲目（包含匹配，不区分大小写），按排序规则排序并分页
+    pub fn query_tracks(&self, query: &TrackQuery) -> anyhow::Result<Vec<LibraryTrack>> {
+        let pattern = |value: &Option<String>| value.as_ref().map(|v| format!("%{}%", v.replace(['%', '_'], "")));
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM tracks
+             WHERE (?1 IS NULL OR title LIKE ?1 OR artist LIKE ?1 OR album LIKE ?1)
+               AND (?2 IS NULL OR artist LIKE ?2)
+               AND (?3 IS NULL OR album LIKE ?3)
+             ORDER BY {}
+             LIMIT ?4 OFFSET ?5",
+            TRACK_COLUMNS,
+            query.order.order_by()
+        ))?;
+        let limit = query.limit.map(|n| n as i64).unwrap_or(-1);
+        let tracks = stmt
+            .query_map(
+                params![
+                    pattern(&query.text),
+                    pattern(&query.artist),
+                    pattern(&query.album),
+                    limit,
+                    query.offset as i64
+                ],
+                track_from_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(tracks)
+    }
+
+    /// 统计曲目数、艺术家数、专辑数和总时长
+    pub fn stats(&self) -> anyhow::Result<LibraryStats> {
+        let stats = self.conn.query_row(
+            "SELECT COUNT(*), COUNT(DISTINCT artist), COUNT(DISTINCT album), COALESCE(SUM(duration), 0),
+                    COUNT(cover_ref), MAX(updated_at)
+             FROM tracks",
+            [],
+            |row| {
+                Ok(LibraryStats {
+                    tracks: row.get::<_, i64>(0)? as usize,
+                    artists: row.get::<_, i64>(1)? as usize,
+                    albums: row.get::<_, i64>(2)? as usize,
+                    total_duration: row.get::<_, i64>(3)? as u64,
+                    with_cover: row.get::<_, i64>(4)? as usize,
+                    last_updated: row.get(5)?,
+                })
+            },
+        )?;
+        Ok(stats)
+    }
+
+    /// 所有曲目索引时记录的文件修改时间
+    pub fn track_mtimes(&self) -> anyhow::Result<std::collections::HashMap<String, Option<i64>>> {
+        let mut stmt = self.conn.prepare("SELECT path, mtime FROM tracks")?;
+        let mtimes = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        Ok(mtimes)
+    }
+
+    /// 所有曲目加入音乐库的时间（Unix秒）
+    pub fn added_times(&self) -> anyhow::Result<std::collections::HashMap<String, i64>> {
+        let mut stmt = self.conn.prepare("SELECT path, added_at FROM tracks")?;
+        let times = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        Ok(times)
+    }
+
+    /// 从曲目索引中移除（标记、裁剪点等与路径关联的数据保留，文件恢复后仍可使用）
+    pub fn remove_tracks(&mut self, paths: &[String]) -> anyhow::Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut removed = 0;
+        {
+            let mut stmt = tx.prepare("DELETE FROM tracks WHERE path = ?1")?;
+            for path in paths {
+                removed += stmt.execute(params![path])?;
+            }
+        }
+        tx.commit()?;
+        REVISION.fetch_add(1, Ordering::AcqRel);
+        Ok(removed)
+    }
+
+    /// 按当前排序规则重新生成全部曲目的排序键，返回更新的曲目数
+    pub fn rebuild_sort_keys(&mut self) -> anyhow::Result<usize> {
+        let collation = collation::settings();
+        let rows = {
+            let mut stmt = self.conn.prepare("SELECT path, title, artist, album FROM tracks")?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, Option<String>>(3)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows
+        };
+        let key = |value: &Option<String>| value.as_deref().map(|v| collation::sort_key_with(v, &collation));
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "UPDATE tracks SET title_sort = ?2, artist_sort = ?3, album_sort = ?4 WHERE path = ?1",
+            )?;
+            for (path, title, artist, album) in &rows {
+                let title_sort = key(title).unwrap_or_else(|| collation::sort_key_with(path, &collation));
+                stmt.execute(params![path, title_sort, key(artist), key(album)])?;
+            }
+        }
+        tx.commit()?;
+        Ok(rows.len())
+    }
+
+    /// 获取曲目的起止裁剪点
+    pub fn trim_points(&self, path: &str) -> anyhow::Result<Option<TrimPoints>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT start_ms, end_ms FROM trim_points WHERE path = ?1",
+                params![path],
+                |row| {
+                    Ok(TrimPoints {
+                        start_ms: row.get::<_, i64>(0)? as u64,
+                        end_ms: row.get::<_, Option<i64>>(1)?.map(|n| n as u64),
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// 设置曲目的起止裁剪点
+    pub fn set_trim_points(&mut self, path: &str, trim: TrimPoints) -> anyhow::Result<()> {
+        if let Some(end_ms) = trim.end_ms {
+            if end_ms <= trim.start_ms {
+                return Err(anyhow::anyhow!("结束点必须晚于起始点"));
+            }
+        }
+        self.conn.execute(
+            "INSERT INTO trim_points (path, start_ms, end_ms, updated_at) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(path) DO UPDATE SET start_ms = excluded.start_ms, end_ms = excluded.end_ms, updated_at = excluded.updated_at",
+            params![path, trim.start_ms as i64, trim.end_ms.map(|n| n as i64), now_secs() as i64],
+        )?;
+        Ok(())
+    }
+
+    /// 清除曲目的起止裁剪点
+    pub fn clear_trim_points(&mut self, path: &str) -> anyhow::Result<()> {
+        self.conn.execute("DELETE FROM trim_points WHERE path = ?1", params![path])?;
+        Ok(())
+    }
+
+    /// 获取曲目的续播设置和保存的位置
+    pub fn resume_point(&self, path: &str) -> anyhow::Result<Option<ResumePoint>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT enabled, position_ms FROM resume_positions WHERE path = ?1",
+                params![path],
+                |row| {
+                    Ok(ResumePoint {
+                        enabled: row.get::<_, Option<i64>>(0)?.map(|n| n != 0),
+                        position_ms: row.get::<_, Option<i64>>(1)?.map(|n| n as u64),
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// 开启/关闭曲目的续播，关闭时清除保存的位置
+    pub fn set_resume_enabled(&mut self, path: &str, enabled: bool) -> anyhow::Result<()> {
+        self.conn.execute(
+            "INSERT INTO resume_positions (path, enabled, position_ms, updated_at) VALUES (?1, ?2, NULL, ?3)
+             ON CONFLICT(path) DO UPDATE SET enabled = excluded.enabled,
+                 position_ms = CASE WHEN excluded.enabled THEN position_ms ELSE NULL END,
+                 updated_at = excluded.updated_at",
+            params![path, enabled, now_secs() as i64],
+        )?;
+        Ok(())
+    }
+
+    /// 保存曲目的续播位置，None 表示已听完（下次从头播放）
+    pub fn set_resume_position(&mut self, path: &str, position_ms: Option<u64>) -> anyhow::Result<()> {
+        self.conn.execute(
+            "INSERT INTO resume_positions (path, enabled, position_ms, updated_at) VALUES (?1, NULL, ?2, ?3)
+             ON CONFLICT(path) DO UPDATE SET position_ms = excluded.position_ms, updated_at = excluded.updated_at",
+            params![path, position_ms.map(|n| n as i64), now_secs() as i64],
+        )?;
+        Ok(())
+    }
+
+    /// 曲目的歌词时间偏移（毫秒），没有设置时为 0
+    pub fn lyrics_offset(&self, path: &str) -> anyhow::Result<i64> {
+        Ok(self
+            .conn
+            .query_row("SELECT offset_ms FROM lyrics_offsets WHERE path = ?1", params![path], |row| row.get(0))
+            .optional()?
+            .unwrap_or(0))
+    }
+
+    /// 设置曲目的歌词时间偏移，0 表示清除
+    pub fn set_lyrics_offset(&mut self, path: &str, offset_ms: i64) -> anyhow::Result<()> {
+        if offset_ms == 0 {
+            self.conn.execute("DELETE FROM lyrics_offsets WHERE path = ?1", params![path])?;
+        } else {
+            self.conn.execute(
+                "INSERT INTO lyrics_offsets (path, offset_ms, updated_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(path) DO UPDATE SET offset_ms = excluded.offset_ms, updated_at = excluded.updated_at",
+                params![path, offset_ms, now_secs() as i64],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// 用户为曲目选定的元数据
+    pub fn metadata_override(&self, path: &str) -> anyhow::Result<Option<MetadataOverride>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT title, artist, album, track_number, disc_number FROM metadata_overrides WHERE path = ?1",
+                params![path],
+                |row| {
+                    Ok(MetadataOverride {
+                        title: row.get(0)?,
+                        artist: row.get(1)?,
+                        album: row.get(2)?,
+                        track_number: row.get::<_, Option<i64>>(3)?.map(|n| n as u32),
+                        disc_number: row.get::<_, Option<i64>>(4)?.map(|n| n as u32),
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// 保存用户选定的元数据，并同步到曲目索引
+    pub fn set_metadata_override(&mut self, path: &str, metadata: &MetadataOverride) -> anyhow::Result<()> {
+        let track_number = metadata.track_number.map(|n| n as i64);
+        let disc_number = metadata.disc_number.map(|n| n as i64);
+        let now = now_secs() as i64;
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO metadata_overrides (path, title, artist, album, track_number, disc_number, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(path) DO UPDATE SET title = excluded.title, artist = excluded.artist, album = excluded.album,
+                track_number = excluded.track_number, disc_number = excluded.disc_number, updated_at = excluded.updated_at",
+            params![path, metadata.title, metadata.artist, metadata.album, track_number, disc_number, now],
+        )?;
+        tx.execute(
+            "UPDATE tracks SET
+                title = COALESCE(?2, title),
+                artist = COALESCE(?3, artist),
+                album = COALESCE(?4, album),
+                track_number = COALESCE(?5, track_number),
+                disc_number = COALESCE(?6, disc_number),
+                updated_at = ?7
+             WHERE path = ?1",
+            params![path, metadata.title, metadata.artist, metadata.album, track_number, disc_number, now],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 清除用户选定的元数据，之后重新按标签读取
+    pub fn clear_metadata_override(&mut self, path: &str) -> anyhow::Result<()> {
+        self.conn.execute("DELETE FROM metadata_overrides WHERE path = ?1", params![path])?;
+        Ok(())
+    }
+
+    /// 记录一次在曲目开头跳过前奏的操作
+    pub fn record_intro_skip(&mut self, path: &str, target_ms: u64) -> anyhow::Result<()> {
+        self.conn.execute(
+            "INSERT INTO intro_skips (path, target_ms, created_at) VALUES (?1, ?2, ?3)",
+            params![path, target_ms as i64, now_secs() as i64],
+        )?;
+        Ok(())
+    }
+
+    /// 最近几次跳过前奏的目标位置（毫秒，最新的在前）
+    pub fn recent_intro_skips(&self, path: &str, limit: usize) -> anyhow::Result<Vec<u64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT target_ms FROM intro_skips WHERE path = ?1 ORDER BY id DESC LIMIT ?2")?;
+        let targets = stmt
+            .query_map(params![path, limit as i64], |row| row.get::<_, i64>(0).map(|n| n as u64))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(targets)
+    }
+
+    /// 用户拒绝为曲目设置自动跳过前奏，之后不再提示
+    pub fn dismiss_intro_skip(&mut self, path: &str) -> anyhow::Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO intro_skip_dismissed (path, dismissed_at) VALUES (?1, ?2)",
+            params![path, now_secs() as i64],
+        )?;
+        Ok(())
+    }
+
+    /// 用户是否已拒绝该曲目的跳过前奏提示
+    pub fn intro_skip_dismissed(&self, path: &str) -> anyhow::Result<bool> {
+        Ok(self
+            .conn
+            .query_row("SELECT 1 FROM intro_skip_dismissed WHERE path = ?1", params![path], |_| Ok(()))
+            .optional()?
+            .is_some())
+    }
+
+    /// 列出所有曲目的校验记录
+    pub fn checksum_records(&self) -> anyhow::Result<Vec<ChecksumRecord>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, content_hash, file_size, file_mtime FROM tracks ORDER BY path")?;
+        let records = stmt
+            .query_map([], |row| {
+                Ok(ChecksumRecord {
+                    path: row.get(0)?,
+                    hash: row.get(1)?,
+                    size: row.get::<_, Option<i64>>(2)?.map(|n| n as u64),
+                    mtime: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(records)
+    }
+
+    /// 保存曲目文件的校验值
+    pub fn set_checksum(&mut self, path: &str, hash: &str, size: u64, mtime: i64) -> anyhow::Result<()> {
+        self.conn.execute(
+            "UPDATE tracks SET content_hash = ?2, file_size = ?3, file_mtime = ?4, verified_at = ?5 WHERE path = ?1",
+            params![path, hash, size as i64, mtime, now_secs() as i64],
+        )?;
+        Ok(())
+    }
+
+    /// 记录曲目校验通过的时间
+    pub fn mark_verified(&mut self, path: &str) -> anyhow::Result<()> {
+        self.conn.execute(
+            "UPDATE tracks SET verified_at = ?2 WHERE path = ?1",
+            params![path, now_secs() as i64],
+        )?;
+        Ok(())
+    }
+
+    /// 列出所有曲目及其在音乐库中最后更新的时间（Unix秒），用于找出之后被修改过的文件
+    pub fn track_update_times(&self) -> anyhow::Result<Vec<(String, i64)>> {
+        let mut stmt = self.conn.prepare("SELECT path, updated_at FROM tracks ORDER BY path")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// 列出尚未测量响度的曲目
+    pub fn tracks_missing_loudness(&self, limit: usize) -> anyhow::Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path FROM tracks WHERE loudness_at IS NULL ORDER BY added_at LIMIT ?1")?;
+        let paths = stmt
+            .query_map(params![limit as i64], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(paths)
+    }
+
+    /// 保存曲目的积分响度（LUFS）和采样峰值，无法测量时记为空
+    pub fn set_loudness(&mut self, path: &str, lufs: Option<f64>, peak: Option<f32>) -> anyhow::Result<()> {
+        self.conn.execute(
+            "UPDATE tracks SET loudness_lufs = ?2, loudness_peak = ?3, loudness_at = ?4 WHERE path = ?1",
+            params![path, lufs, peak, now_secs() as i64],
+        )?;
+        Ok(())
+    }
+
+    /// 从文件载入曲目时需要的音乐库数据
+    pub fn track_extras(&self, path: &str) -> anyhow::Result<TrackExtras> {
+        Ok(TrackExtras {
+            metadata: self.metadata_override(path)?,
+            rating: self.rating(path)?,
+            trim: self.trim_points(path)?,
+            resume: self.resume_point(path)?,
+            loudness: self.loudness(path)?,
+            lyrics_offset_ms: self.lyrics_offset(path)?,
+        })
+    }
+
+    /// 曲目已测量的积分响度（LUFS）和采样峰值，未测量或无法测量时返回 None
+    pub fn loudness(&self, path: &str) -> anyhow::Result<Option<(f64, Option<f32>)>> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT loudness_lufs, loudness_peak FROM tracks WHERE path = ?1 AND loudness_lufs IS NOT NULL",
+                params![path],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        Ok(row)
+    }
+
+    /// 按文件校验值找出内容完全相同的曲目，每组至少两个路径
+    pub fn duplicate_groups(&self) -> anyhow::Result<Vec<Vec<String>>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT content_hash, path FROM tracks
+             WHERE content_hash IN (SELECT content_hash FROM tracks WHERE content_hash IS NOT NULL
+                                    GROUP BY content_hash HAVING COUNT(*) > 1)
+             ORDER BY content_hash, path",
+        )?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+        for (hash, path) in rows {
+            match groups.last_mut() {
+                Some((last, paths)) if *last == hash => paths.push(path),
+                _ => groups.push((hash, vec![path])),
+            }
+        }
+        Ok(groups.into_iter().map(|(_, paths)| paths).collect())
+    }
+
+    /// 累加曲目的收听时长，counted 为 true 时播放次数加一
+    pub fn record_listen(
+        &mut self,
+        path: &str,
+        title: Option<&str>,
+        artist: Option<&str>,
+        listen_secs: u64,
+        counted: bool,
+    ) -> anyhow::Result<()> {
+        let now = now_secs() as i64;
+        self.conn.execute(
+            "INSERT INTO play_stats (path, title, artist, play_count, listen_secs, last_played)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(path) DO UPDATE SET
+                title = COALESCE(excluded.title, play_stats.title),
+                artist = COALESCE(excluded.artist, play_stats.artist),
+                play_count = play_stats.play_count + excluded.play_count,
+                listen_secs = play_stats.listen_secs + excluded.listen_secs,
+                last_played = COALESCE(excluded.last_played, play_stats.last_played)",
+            params![
+                path,
+                title,
+                artist,
+                counted as i64,
+                listen_secs as i64,
+                counted.then_some(now)
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 播放统计，按播放次数从多到少；给出路径时只返回该曲目
+    pub fn track_stats(&self, path: Option<&str>, limit: usize) -> anyhow::Result<Vec<TrackStats>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.path, COALESCE(t.title, s.title), COALESCE(t.artist, s.artist), s.play_count, s.listen_secs, s.last_played
+             FROM play_stats s LEFT JOIN tracks t ON t.path = s.path
+             WHERE ?1 IS NULL OR s.path = ?1
+             ORDER BY s.play_count DESC, s.listen_secs DESC
+             LIMIT ?2",
+        )?;
+        let stats = stmt
+            .query_map(params![path, limit as i64], |row| {
+                Ok(TrackStats {
+                    path: row.get(0)?,
+                    title: row.get(1)?,
+                    artist: row.get(2)?,
+                    play_count: row.get::<_, i64>(3)? as u64,
+                    listen_secs: row.get::<_, i64>(4)? as u64,
+                    last_played: row.get::<_, Option<i64>>(5)?.map(|t| t as u64),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(stats)
+    }
+
+    /// 播放次数最多的艺术家（优先使用音乐库中的艺术家名）
+    pub fn top_artists(&self, limit: usize) -> anyhow::Result<Vec<ArtistStats>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT COALESCE(t.artist, s.artist) AS name, SUM(s.play_count), SUM(s.listen_secs), COUNT(*)
+             FROM play_stats s LEFT JOIN tracks t ON t.path = s.path
+             WHERE name IS NOT NULL AND name != ''
+             GROUP BY name
+             ORDER BY SUM(s.play_count) DESC, SUM(s.listen_secs) DESC
+             LIMIT ?1",
+        )?;
+        let artists = stmt
+            .query_map(params![limit as i64], |row| {
+                Ok(ArtistStats {
+                    artist: row.get(0)?,
+                    play_count: row.get::<_, i64>(1)? as u64,
+                    listen_secs: row.get::<_, i64>(2)? as u64,
+                    tracks: row.get::<_, i64>(3)? as usize,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(artists)
+    }
+
+    /// 累计收听时长（秒）
+    pub fn total_listen_secs(&self) -> anyhow::Result<u64> {
+        let total: i64 = self
+            .conn
+            .query_row("SELECT COALESCE(SUM(listen_secs), 0) FROM play_stats", [], |row| row.get(0))?;
+        Ok(total as u64)
+    }
+
+    /// 曲目的评分和收藏状态，没有记录时返回 None
+    pub fn rating(&self, path: &str) -> anyhow::Result<Option<(Option<u8>, bool)>> {
+        let rating = self
+            .conn
+            .query_row(
+                "SELECT rating, favorite FROM ratings WHERE path = ?1",
+                params![path],
+                |row| Ok((row.get::<_, Option<i64>>(0)?.map(|r| r as u8), row.get::<_, i64>(1)? != 0)),
+            )
+            .optional()?;
+        Ok(rating)
+    }
+
+    /// 设置评分（None 为取消评分），返回更新后的 (评分, 收藏)
+    pub fn set_rating(&mut self, path: &str, rating: Option<u8>) -> anyhow::Result<(Option<u8>, bool)> {
+        self.conn.execute(
+            "INSERT INTO ratings (path, rating, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(path) DO UPDATE SET rating = excluded.rating, updated_at = excluded.updated_at",
+            params![path, rating.map(|r| r as i64), now_secs() as i64],
+        )?;
+        Ok(self.rating(path)?.unwrap_or((rating, false)))
+    }
+
+    /// 切换收藏状态，返回更新后的 (评分, 收藏)
+    pub fn toggle_favorite(&mut self, path: &str) -> anyhow::Result<(Option<u8>, bool)> {
+        self.conn.execute(
+            "INSERT INTO ratings (path, favorite, updated_at) VALUES (?1, 1, ?2)
+             ON CONFLICT(path) DO UPDATE SET favorite = 1 - ratings.favorite, updated_at = excluded.updated_at",
+            params![path, now_secs() as i64],
+        )?;
+        Ok(self.rating(path)?.unwrap_or((None, true)))
+    }
+
+    /// 收藏的曲目，按评分从高到低、收藏时间从新到旧
+    pub fn favorites(&self) -> anyhow::Result<Vec<RatedTrack>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT r.path, t.title, t.artist, t.album, r.rating, r.favorite
+             FROM ratings r LEFT JOIN tracks t ON t.path = r.path
+             WHERE r.favorite = 1
+             ORDER BY r.rating IS NULL, r.rating DESC, r.updated_at DESC",
+        )?;
+        let tracks = stmt
+            .query_map([], |row| {
+                Ok(RatedTrack {
+                    path: row.get(0)?,
+                    title: row.get(1)?,
+                    artist: row.get(2)?,
+                    album: row.get(3)?,
+                    rating: row.get::<_, Option<i64>>(4)?.map(|r| r as u8),
+                    favorite: row.get::<_, i64>(5)? != 0,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(tracks)
+    }
+
+    /// 清除校验值（不指定路径时清除全部），下次校验时重新记录
+    pub fn clear_checksum(&mut self, path: Option<&str>) -> anyhow::Result<()> {
+        self.conn.execute(
+            "UPDATE tracks SET content_hash = NULL, file_size = NULL, file_mtime = NULL, verified_at = NULL
+             WHERE ?1 IS NULL OR path = ?1",
+            params![path],
+        )?;
+        Ok(())
+    }
+}
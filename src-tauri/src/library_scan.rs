@@ -0,0 +1,202 @@
+use crate::library;
+use crate::player_fixed::SongInfo;
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 每批写入音乐库的曲目数
+const UPSERT_BATCH: usize = 100;
+
+/// 扫描任务是否正在运行
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 已添加的音乐库文件夹
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanFolders {
+    #[serde(default)]
+    pub folders: Vec<String>,
+    #[serde(rename = "lastScanAt", default)]
+    pub last_scan_at: Option<u64>, // Unix秒
+    #[serde(default = "default_watch")]
+    pub watch: bool, // 监听文件夹变化，自动更新音乐库
+}
+
+fn default_watch() -> bool {
+    true
+}
+
+impl Default for ScanFolders {
+    fn default() -> Self {
+        Self {
+            folders: Vec::new(),
+            last_scan_at: None,
+            watch: default_watch(),
+        }
+    }
+}
+
+/// 扫描结果汇总
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScanSummary {
+    pub found: usize,     // 找到的音频文件数
+    pub added: usize,     // 新加入音乐库
+    pub updated: usize,   // 文件修改过，重新读取了标签
+    pub unchanged: usize, // 修改时间未变，跳过
+    pub removed: usize,   // 文件已不存在，从音乐库移除
+    pub failed: usize,    // 无法读取标签
+}
+
+/// 扫描过程中发送给前端的事件
+#[derive(Debug, Clone, Serialize)]
+pub enum ScanEvent {
+    Progress { scanned: usize, total: usize },
+    Finished(ScanSummary),
+}
+
+fn folders_path() -> PathBuf {
+    storage::data_dir().join("library_folders.json")
+}
+
+/// 读取音乐库文件夹
+pub fn load_folders() -> ScanFolders {
+    storage::load_json(&folders_path())
+        .unwrap_or_else(|e| {
+            eprintln!("读取音乐库文件夹失败: {}", e);
+            None
+        })
+        .unwrap_or_default()
+}
+
+/// 保存音乐库文件夹
+pub fn save_folders(folders: &ScanFolders) -> anyhow::Result<()> {
+    storage::save_json(&folders_path(), folders)
+}
+
+/// 按扩展名判断是否为音乐库收录的音频文件
+pub fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| SongInfo::is_audio_format(&e.to_lowercase()))
+        .unwrap_or(false)
+}
+
+/// 递归收集目录下的音频文件（跳过隐藏目录）
+pub fn collect_audio_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let read_dir = match std::fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) => {
+            eprintln!("无法读取目录 {}: {}", dir.display(), e);
+            return;
+        }
+    };
+    for entry in read_dir.flatten() {
+        let path = entry.path();
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        match entry.file_type() {
+            Ok(t) if t.is_dir() && !hidden => collect_audio_files(&path, files),
+            Ok(t) if t.is_file() && is_audio_file(&path) => files.push(path),
+            _ => {}
+        }
+    }
+}
+
+/// 扫描文件夹并更新音乐库索引：修改时间未变化的文件不再读取标签
+/// folders 为空时扫描上次保存的文件夹，否则扫描给定文件夹并记住它们；
+/// 扫描过的文件夹中已不存在的文件会从音乐库移除
+pub fn scan(folders: Vec<String>, mut emit: impl FnMut(ScanEvent)) -> anyhow::Result<ScanSummary> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(anyhow::anyhow!("音乐库扫描正在进行中"));
+    }
+    let result = run(folders, &mut emit);
+    RUNNING.store(false, Ordering::SeqCst);
+    let summary = result?;
+    emit(ScanEvent::Finished(summary.clone()));
+    Ok(summary)
+}
+
+fn run(folders: Vec<String>, emit: &mut impl FnMut(ScanEvent)) -> anyhow::Result<ScanSummary> {
+    let mut saved = load_folders();
+    let folders = if folders.is_empty() { saved.folders.clone() } else { folders };
+    if folders.is_empty() {
+        return Err(anyhow::anyhow!("没有要扫描的文件夹"));
+    }
+
+    let mut files = Vec::new();
+    for folder in &folders {
+        collect_audio_files(Path::new(folder), &mut files);
+    }
+    let known = library::with_library(|lib| lib.track_mtimes()).map_err(anyhow::Error::msg)?;
+    let total = files.len();
+    let mut summary = ScanSummary {
+        found: total,
+        ..Default::default()
+    };
+
+    let mut batch: Vec<SongInfo> = Vec::new();
+    let flush = |batch: &mut Vec<SongInfo>| -> anyhow::Result<()> {
+        if !batch.is_empty() {
+            library::with_library(|lib| lib.upsert_tracks(batch)).map_err(anyhow::Error::msg)?;
+            batch.clear();
+        }
+        Ok(())
+    };
+    for (scanned, file) in files.iter().enumerate() {
+        let key = file.to_string_lossy().into_owned();
+        let stored = known.get(&key);
+        let mtime = library::file_mtime(file);
+        if stored.is_some_and(|m| m.is_some() && *m == mtime) {
+            summary.unchanged += 1;
+        } else {
+            match SongInfo::from_path(file) {
+                Ok(song) => {
+                    if stored.is_some() {
+                        summary.updated += 1;
+                    } else {
+                        summary.added += 1;
+                    }
+                    batch.push(song);
+                    if batch.len() >= UPSERT_BATCH {
+                        flush(&mut batch)?;
+                    }
+                }
+                Err(e) => {
+                    eprintln!("读取标签失败 {}: {}", file.display(), e);
+                    summary.failed += 1;
+                }
+            }
+        }
+        if (scanned + 1) % 50 == 0 || scanned + 1 == total {
+            emit(ScanEvent::Progress {
+                scanned: scanned + 1,
+                total,
+            });
+        }
+    }
+    flush(&mut batch)?;
+
+    // 只清理扫描过的文件夹中的曲目，其他来源（单独添加的文件）不受影响
+    let found: HashSet<String> = files.iter().map(|f| f.to_string_lossy().into_owned()).collect();
+    let missing: Vec<String> = known
+        .into_keys()
+        .filter(|path| !found.contains(path))
+        .filter(|path| folders.iter().any(|folder| Path::new(path).starts_with(folder)))
+        .filter(|path| !Path::new(path).exists())
+        .collect();
+    summary.removed = library::with_library(|lib| lib.remove_tracks(&missing)).map_err(anyhow::Error::msg)?;
+
+    for folder in folders {
+        if !saved.folders.contains(&folder) {
+            saved.folders.push(folder);
+        }
+    }
+    saved.last_scan_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs());
+    if let Err(e) = save_folders(&saved) {
+        eprintln!("保存音乐库文件夹失败: {}", e);
+    }
+    Ok(summary)
+}
@@ -0,0 +1,132 @@
+use crate::library;
+use serde::Serialize;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 校验任务是否正在运行
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 校验发现的问题类型
+#[derive(Debug, Clone, Copy, Serialize)]
+pub enum IssueKind {
+    Missing,    // 文件不存在
+    Unreadable, // 无法读取
+    Modified,   // 内容变化且修改时间/大小也变化（可能是编辑了标签）
+    Corrupted,  // 内容变化但修改时间和大小都未变（疑似静默损坏）
+}
+
+/// 校验发现的问题
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyIssue {
+    pub path: String,
+    pub kind: IssueKind,
+    pub detail: Option<String>,
+}
+
+/// 校验结果汇总
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerifySummary {
+    pub checked: usize,
+    pub baselined: usize, // 首次记录校验值的曲目数
+    pub ok: usize,
+    pub issues: Vec<VerifyIssue>,
+}
+
+/// 校验过程中发送给前端的事件
+#[derive(Debug, Clone, Serialize)]
+pub enum VerifyEvent {
+    Progress { checked: usize, total: usize },
+    Issue(VerifyIssue),
+    Finished(VerifySummary),
+}
+
+/// 计算文件内容的 BLAKE3 校验值，同时返回文件大小和修改时间（Unix秒）
+pub fn hash_file(path: &Path) -> std::io::Result<(String, u64, i64)> {
+    let metadata = std::fs::metadata(path)?;
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    let mut file = File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = vec![0u8; 256 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok((hasher.finalize().to_hex().to_string(), metadata.len(), mtime))
+}
+
+/// 校验音乐库中所有曲目的文件内容
+/// 没有校验值的曲目记录当前值作为基准；已有校验值的曲目比较是否变化，问题通过 emit 逐条上报
+pub fn verify(mut emit: impl FnMut(VerifyEvent)) -> anyhow::Result<VerifySummary> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(anyhow::anyhow!("音乐库校验正在进行中"));
+    }
+    let result = run(&mut emit);
+    RUNNING.store(false, Ordering::SeqCst);
+    let summary = result?;
+    emit(VerifyEvent::Finished(summary.clone()));
+    Ok(summary)
+}
+
+fn run(emit: &mut impl FnMut(VerifyEvent)) -> anyhow::Result<VerifySummary> {
+    let records = library::with_library(|lib| lib.checksum_records()).map_err(anyhow::Error::msg)?;
+    let total = records.len();
+    let mut summary = VerifySummary::default();
+
+    for record in records {
+        summary.checked += 1;
+        let path = Path::new(&record.path);
+        let issue = if !path.exists() {
+            Some((IssueKind::Missing, None))
+        } else {
+            match hash_file(path) {
+                Err(e) => Some((IssueKind::Unreadable, Some(e.to_string()))),
+                Ok((hash, size, mtime)) => match &record.hash {
+                    None => {
+                        library::with_library(|lib| lib.set_checksum(&record.path, &hash, size, mtime))
+                            .map_err(anyhow::Error::msg)?;
+                        summary.baselined += 1;
+                        None
+                    }
+                    Some(expected) if *expected == hash => {
+                        library::with_library(|lib| lib.mark_verified(&record.path)).map_err(anyhow::Error::msg)?;
+                        summary.ok += 1;
+                        None
+                    }
+                    Some(_) => {
+                        let unchanged = record.size == Some(size) && record.mtime == Some(mtime);
+                        let kind = if unchanged { IssueKind::Corrupted } else { IssueKind::Modified };
+                        Some((kind, Some(format!("当前校验值 {}", hash))))
+                    }
+                },
+            }
+        };
+
+        if let Some((kind, detail)) = issue {
+            let issue = VerifyIssue {
+                path: record.path.clone(),
+                kind,
+                detail,
+            };
+            emit(VerifyEvent::Issue(issue.clone()));
+            summary.issues.push(issue);
+        }
+        if summary.checked % 20 == 0 || summary.checked == total {
+            emit(VerifyEvent::Progress {
+                checked: summary.checked,
+                total,
+            });
+        }
+    }
+    Ok(summary)
+}
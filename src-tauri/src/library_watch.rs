@@ -0,0 +1,146 @@
+use crate::library;
+use crate::library_scan;
+use crate::player_fixed::{PlayerEvent, SongInfo};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// 文件系统事件平静多久后再更新音乐库（复制大量文件时合并为一次更新）
+const DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// 当前的文件夹监听器，文件夹或开关变化时重新创建
+fn watcher() -> &'static Mutex<Option<RecommendedWatcher>> {
+    static WATCHER: OnceLock<Mutex<Option<RecommendedWatcher>>> = OnceLock::new();
+    WATCHER.get_or_init(|| Mutex::new(None))
+}
+
+/// 监听器事件发往的处理线程
+static EVENTS: OnceLock<mpsc::Sender<notify::Result<notify::Event>>> = OnceLock::new();
+
+/// 启动音乐库文件夹监听：新增、删除、重命名的文件增量写入音乐库，并发送 LibraryChanged 事件
+pub fn start<R: Runtime>(app_handle: AppHandle<R>) {
+    let (tx, rx) = mpsc::channel();
+    if EVENTS.set(tx).is_err() {
+        return;
+    }
+    std::thread::spawn(move || {
+        while let Ok(first) = rx.recv() {
+            let mut paths = HashSet::new();
+            collect_paths(first, &mut paths);
+            loop {
+                match rx.recv_timeout(DEBOUNCE) {
+                    Ok(event) => collect_paths(event, &mut paths),
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+            match apply(paths) {
+                Ok(Some(event)) => {
+                    if let Err(e) = app_handle.emit("player-event", event) {
+                        eprintln!("发送音乐库变化事件失败: {:?}", e);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("更新音乐库失败: {}", e),
+            }
+        }
+    });
+    refresh();
+}
+
+/// 按当前设置重新监听音乐库文件夹（添加文件夹或切换开关后调用）
+pub fn refresh() {
+    let Some(tx) = EVENTS.get() else {
+        return;
+    };
+    let settings = library_scan::load_folders();
+    let mut guard = match watcher().lock() {
+        Ok(guard) => guard,
+        Err(_) => return,
+    };
+    // 先停止旧的监听器
+    *guard = None;
+    if !settings.watch || settings.folders.is_empty() {
+        return;
+    }
+    let mut new_watcher = match notify::recommended_watcher(tx.clone()) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            eprintln!("无法创建文件夹监听: {}", e);
+            return;
+        }
+    };
+    for folder in &settings.folders {
+        match new_watcher.watch(Path::new(folder), RecursiveMode::Recursive) {
+            Ok(()) => println!("👀 监听音乐库文件夹: {}", folder),
+            Err(e) => eprintln!("无法监听文件夹 {}: {}", folder, e),
+        }
+    }
+    *guard = Some(new_watcher);
+}
+
+fn collect_paths(event: notify::Result<notify::Event>, paths: &mut HashSet<PathBuf>) {
+    match event {
+        // 只读访问不改变文件
+        Ok(event) if !matches!(event.kind, notify::EventKind::Access(_)) => paths.extend(event.paths),
+        Ok(_) => {}
+        Err(e) => eprintln!("文件夹监听出错: {}", e),
+    }
+}
+
+/// 按变化路径的当前状态更新音乐库：存在的音频文件（或新出现的目录中的音频文件）写入，
+/// 已不存在的路径（包括整个目录）移除；重命名表现为旧路径移除、新路径写入
+fn apply(paths: HashSet<PathBuf>) -> anyhow::Result<Option<PlayerEvent>> {
+    let known = library::with_library(|lib| lib.track_mtimes()).map_err(anyhow::Error::msg)?;
+    let mut files = Vec::new();
+    let mut removed = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            library_scan::collect_audio_files(&path, &mut files);
+        } else if path.is_file() {
+            if library_scan::is_audio_file(&path) {
+                files.push(path);
+            }
+        } else {
+            removed.extend(known.keys().filter(|k| Path::new(k).starts_with(&path)).cloned());
+        }
+    }
+
+    let (mut added, mut updated, mut songs) = (Vec::new(), Vec::new(), Vec::new());
+    for file in files {
+        let key = file.to_string_lossy().into_owned();
+        let stored = known.get(&key);
+        if stored.is_some_and(|m| m.is_some() && *m == library::file_mtime(&file)) {
+            continue;
+        }
+        match SongInfo::from_path(&file) {
+            Ok(song) => {
+                songs.push(song);
+                if stored.is_some() {
+                    updated.push(key);
+                } else {
+                    added.push(key);
+                }
+            }
+            // 文件可能还在复制中，下一次写入事件时再读取
+            Err(e) => eprintln!("读取标签失败 {}: {}", file.display(), e),
+        }
+    }
+    removed.sort();
+    removed.dedup();
+
+    if songs.is_empty() && removed.is_empty() {
+        return Ok(None);
+    }
+    library::with_library(|lib| {
+        lib.upsert_tracks(&songs)?;
+        lib.remove_tracks(&removed)
+    })
+    .map_err(anyhow::Error::msg)?;
+    println!("📚 音乐库自动更新: 新增{}，更新{}，移除{}", added.len(), updated.len(), removed.len());
+    Ok(Some(PlayerEvent::LibraryChanged { added, updated, removed }))
+}
@@ -0,0 +1,101 @@
+use crate::library;
+use crate::player_fixed::{PlayerEvent, PlayerState, SongInfo};
+use std::sync::{Mutex, OnceLock};
+
+/// 两次进度更新之间超过这个秒数视为跳转，不计入收听时长
+const MAX_PROGRESS_STEP: u64 = 2;
+
+/// 正在收听的曲目
+struct Listening {
+    path: String,
+    title: Option<String>,
+    artist: Option<String>,
+    duration: u64,
+    last_position: Option<u64>,
+    heard: u64,       // 尚未写入的收听秒数
+    total_heard: u64, // 本次播放累计收听秒数
+    counted: bool,    // 本次播放是否已计入播放次数
+}
+
+impl Listening {
+    fn new(song: &SongInfo) -> Self {
+        Self {
+            path: song.path.clone(),
+            title: song.title.clone(),
+            artist: song.artist.clone(),
+            duration: song.duration.unwrap_or(0),
+            last_position: None,
+            heard: 0,
+            total_heard: 0,
+            counted: false,
+        }
+    }
+
+    /// 写入未保存的收听时长，count 为 true 时同时计入一次播放
+    fn flush(&mut self, count: bool) {
+        if self.heard == 0 && !count {
+            return;
+        }
+        let result = library::with_library(|lib| {
+            lib.record_listen(&self.path, self.title.as_deref(), self.artist.as_deref(), self.heard, count)
+        });
+        match result {
+            Ok(()) => self.heard = 0,
+            Err(e) => eprintln!("保存播放统计失败 {}: {}", self.path, e),
+        }
+    }
+}
+
+fn current() -> &'static Mutex<Option<Listening>> {
+    static CURRENT: OnceLock<Mutex<Option<Listening>>> = OnceLock::new();
+    CURRENT.get_or_init(|| Mutex::new(None))
+}
+
+/// 根据播放器事件统计收听时长和播放次数：实际听到的时长（跳过的部分不算）超过一半时计一次播放
+pub fn on_player_event(event: &PlayerEvent) {
+    let mut current = match current().lock() {
+        Ok(current) => current,
+        Err(_) => return,
+    };
+    match event {
+        PlayerEvent::SongChanged(_, song) => {
+            if let Some(mut listening) = current.take() {
+                listening.flush(false);
+            }
+            *current = Some(Listening::new(song));
+        }
+        PlayerEvent::ProgressUpdate { position, duration, .. } => {
+            let Some(listening) = current.as_mut() else {
+                return;
+            };
+            if listening.duration == 0 {
+                listening.duration = *duration;
+            }
+            if let Some(last) = listening.last_position {
+                let step = position.saturating_sub(last);
+                if *position > last && step <= MAX_PROGRESS_STEP {
+                    listening.heard += step;
+                    listening.total_heard += step;
+                }
+            }
+            listening.last_position = Some(*position);
+            if !listening.counted && listening.duration > 0 && listening.total_heard * 2 > listening.duration {
+                listening.counted = true;
+                listening.flush(true);
+            }
+        }
+        PlayerEvent::StateChanged(state @ (PlayerState::Paused | PlayerState::Stopped)) => {
+            if let Some(listening) = current.as_mut() {
+                listening.flush(false);
+                // 恢复播放后的第一次进度更新只作为基准
+                listening.last_position = None;
+                // 停止后再播放是从头重新收听
+                if *state == PlayerState::Stopped {
+                    listening.total_heard = 0;
+                    listening.counted = false;
+                }
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,113 @@
+use crate::export::{self, REPLAYGAIN_REFERENCE_LUFS};
+use crate::library;
+use crate::media_source;
+use crate::player_fixed::SongInfo;
+use crate::tag_write;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 未指定文件时一次最多分析的曲目数
+const MAX_PENDING: usize = 100_000;
+
+/// 分析任务是否正在运行
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 分析结束时（包括中途 panic）清除运行标记，否则之后再也无法开始分析
+struct RunningGuard;
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// 响度分析结果汇总
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LoudnessSummary {
+    pub analyzed: usize, // 测得响度的文件数
+    pub silent: usize,   // 过静无法测量
+    pub failed: usize,   // 无法解码
+    pub tagged: usize,   // 写入了 ReplayGain 标签
+}
+
+/// 分析过程中发送给前端的事件
+#[derive(Debug, Clone, Serialize)]
+pub enum LoudnessEvent {
+    Progress {
+        analyzed: usize,
+        total: usize,
+        path: String,
+        lufs: Option<f64>,
+    },
+    Finished(LoudnessSummary),
+}
+
+/// 按 EBU R128 测量文件的积分响度并写入音乐库（不在音乐库中的文件先加入），
+/// 播放时没有 ReplayGain 标签的文件按测量结果标准化音量
+/// paths 为空时分析音乐库中所有尚未测量的曲目；write_tags 为 true 时同时把曲目增益和峰值写入文件标签
+pub fn analyze(paths: Vec<String>, write_tags: bool, mut emit: impl FnMut(LoudnessEvent)) -> anyhow::Result<LoudnessSummary> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(anyhow::anyhow!("响度分析正在进行中"));
+    }
+    let guard = RunningGuard;
+    let result = run(paths, write_tags, &mut emit);
+    drop(guard);
+    let summary = result?;
+    emit(LoudnessEvent::Finished(summary.clone()));
+    Ok(summary)
+}
+
+fn run(paths: Vec<String>, write_tags: bool, emit: &mut impl FnMut(LoudnessEvent)) -> anyhow::Result<LoudnessSummary> {
+    let paths = if paths.is_empty() {
+        library::with_library(|lib| lib.tracks_missing_loudness(MAX_PENDING)).map_err(anyhow::Error::msg)?
+    } else {
+        paths.into_iter().filter(|path| !media_source::is_url(path)).collect()
+    };
+    // 只读音乐库时仍然测量，只是不写标签
+    let write_tags = write_tags && tag_write::ensure_writable().is_ok();
+    let total = paths.len();
+    let mut summary = LoudnessSummary::default();
+
+    for (index, path) in paths.into_iter().enumerate() {
+        let file = Path::new(&path);
+        let known = library::with_library(|lib| lib.track(&path)).map_err(anyhow::Error::msg)?.is_some();
+        if !known {
+            match SongInfo::from_path(file) {
+                Ok(song) => library::with_library(|lib| lib.upsert_tracks(&[song])).map_err(anyhow::Error::msg)?,
+                Err(e) => eprintln!("读取标签失败 {}: {}", path, e),
+            }
+        }
+
+        // 无法解码的文件也记为已测量，避免每次都重试
+        let (lufs, peak) = match export::measure_file(file) {
+            Ok(measurement) => (measurement.lufs, Some(measurement.peak)),
+            Err(e) => {
+                eprintln!("测量响度失败 {}: {}", path, e);
+                summary.failed += 1;
+                (None, None)
+            }
+        };
+        library::with_library(|lib| lib.set_loudness(&path, lufs, peak)).map_err(anyhow::Error::msg)?;
+        match lufs {
+            Some(lufs) => {
+                summary.analyzed += 1;
+                if write_tags {
+                    match tag_write::write_replaygain(file, REPLAYGAIN_REFERENCE_LUFS - lufs, peak.unwrap_or(1.0)) {
+                        Ok(()) => summary.tagged += 1,
+                        Err(e) => eprintln!("写入ReplayGain标签失败 {}: {}", path, e),
+                    }
+                }
+            }
+            None if peak.is_some() => summary.silent += 1,
+            None => {}
+        }
+        emit(LoudnessEvent::Progress {
+            analyzed: index + 1,
+            total,
+            path,
+            lufs,
+        });
+    }
+    Ok(summary)
+}
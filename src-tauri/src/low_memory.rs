@@ -0,0 +1,92 @@
+use crate::media_source;
+use crate::player_fixed::{PlayerCommand, SongInfo};
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+/// 低内存模式设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LowMemorySettings {
+    /// 开启后播放列表条目不保留封面、视频缩略图和歌词（占用内存的大部分），需要时从文件中读取；
+    /// 艺术家/专辑等元数据照常保留，列表显示、搜索和排序不受影响
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("low_memory.json")
+}
+
+fn settings_lock() -> &'static RwLock<LowMemorySettings> {
+    static SETTINGS: OnceLock<RwLock<LowMemorySettings>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let loaded = storage::load_json(&settings_path()).unwrap_or_else(|e| {
+            eprintln!("读取低内存模式设置失败: {}", e);
+            None
+        });
+        RwLock::new(loaded.unwrap_or_default())
+    })
+}
+
+/// 获取低内存模式设置
+pub fn settings() -> LowMemorySettings {
+    settings_lock().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// 保存低内存模式设置
+pub fn set_settings(settings: LowMemorySettings) -> anyhow::Result<()> {
+    storage::save_json(&settings_path(), &settings)?;
+    *settings_lock().write().map_err(|_| anyhow::anyhow!("无法锁定低内存模式设置"))? = settings;
+    Ok(())
+}
+
+/// 精简条目，只去掉可以按需从文件重新读取的封面和歌词
+pub fn slim(song: SongInfo) -> SongInfo {
+    SongInfo {
+        album_cover: None,
+        video_thumbnail: None,
+        lyrics: None,
+        ..song
+    }
+}
+
+/// 低内存模式下精简加入播放列表的歌曲（在发送给播放器线程之前调用）
+pub fn compact_command(cmd: PlayerCommand) -> PlayerCommand {
+    if !settings().enabled {
+        return cmd;
+    }
+    let compact = |songs: Vec<SongInfo>| songs.into_iter().map(slim).collect::<Vec<_>>();
+    match cmd {
+        PlayerCommand::AddSongs(songs) => PlayerCommand::AddSongs(compact(songs)),
+        PlayerCommand::Enqueue(songs) => PlayerCommand::Enqueue(compact(songs)),
+        PlayerCommand::InsertSongs { index, songs } => PlayerCommand::InsertSongs { index, songs: compact(songs) },
+        PlayerCommand::ResolvePlaceholder { path, songs } => PlayerCommand::ResolvePlaceholder { path, songs: compact(songs) },
+        PlayerCommand::ApplyCover { path, .. } => PlayerCommand::ApplyCover { path, cover: None },
+        PlayerCommand::RefreshSong(song) => PlayerCommand::RefreshSong(slim(song)),
+        PlayerCommand::LoadPlaylist { name, songs, index, position } => PlayerCommand::LoadPlaylist {
+            name,
+            songs: compact(songs),
+            index,
+            position,
+        },
+        cmd => cmd,
+    }
+}
+
+/// 补全精简过的条目：封面、视频缩略图和歌词从文件读取，其余字段保持条目中的值；条目未被精简时原样返回
+pub fn hydrate(song: &SongInfo) -> SongInfo {
+    if song.album_cover.is_some() || song.lyrics.is_some() || media_source::is_url(&song.path) {
+        return song.clone();
+    }
+    let mut full = song.clone();
+    match SongInfo::from_path(Path::new(&song.path)) {
+        Ok(file) => {
+            full.album_cover = file.album_cover;
+            full.video_thumbnail = file.video_thumbnail;
+            full.lyrics = file.lyrics;
+        }
+        Err(e) => eprintln!("读取曲目详情失败 {}: {}", song.path, e),
+    }
+    full
+}
@@ -0,0 +1,244 @@
+use crate::cache::{self, CacheKind};
+use crate::net;
+use crate::storage;
+use crate::tag_write;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+/// 单次请求的总超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// 搜索结果与本地文件时长相差超过这么多秒时不采用
+const MAX_DURATION_DIFF_SECS: f64 = 3.0;
+
+/// 在线获取的歌词保存到哪里
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LyricsTarget {
+    #[default]
+    Sidecar, // 音频文件旁的同名 .lrc（只有非同步歌词时为 .txt）
+    Tag,     // 写入文件的歌词标签（USLT / LYRICS / ©lyr）
+}
+
+/// 在线歌词设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LyricsFetchSettings {
+    #[serde(rename = "saveTo", default)]
+    pub save_to: LyricsTarget,
+}
+
+/// 搜索条件
+#[derive(Debug, Clone)]
+pub struct LyricsQuery {
+    pub title: String,
+    pub artist: String,
+    pub album: Option<String>,
+    pub duration: Option<u64>, // 秒
+}
+
+/// 在线找到的歌词，同步歌词为 LRC 文本
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FetchedLyrics {
+    pub synced: Option<String>,
+    pub plain: Option<String>,
+}
+
+impl FetchedLyrics {
+    fn is_empty(&self) -> bool {
+        self.synced.is_none() && self.plain.is_none()
+    }
+}
+
+/// 在线歌词来源
+pub trait LyricsProvider {
+    fn name(&self) -> &'static str;
+
+    /// 按标题、艺术家和时长搜索，没有找到时返回 None
+    async fn search(&self, query: &LyricsQuery) -> anyhow::Result<Option<FetchedLyrics>>;
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("lyrics_fetch.json")
+}
+
+fn settings_lock() -> &'static RwLock<LyricsFetchSettings> {
+    static SETTINGS: OnceLock<RwLock<LyricsFetchSettings>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let loaded = storage::load_json(&settings_path()).unwrap_or_else(|e| {
+            eprintln!("读取在线歌词设置失败: {}", e);
+            None
+        });
+        RwLock::new(loaded.unwrap_or_default())
+    })
+}
+
+/// 获取在线歌词设置
+pub fn settings() -> LyricsFetchSettings {
+    settings_lock().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// 保存在线歌词设置
+pub fn set_settings(settings: LyricsFetchSettings) -> anyhow::Result<()> {
+    storage::save_json(&settings_path(), &settings)?;
+    *settings_lock().write().map_err(|_| anyhow::anyhow!("无法锁定在线歌词设置"))? = settings;
+    Ok(())
+}
+
+fn user_agent() -> String {
+    format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+}
+
+/// LRCLIB（lrclib.net）：公开的 LRC 歌词库，无需密钥
+pub struct Lrclib;
+
+#[derive(Deserialize)]
+struct LrclibRecord {
+    #[serde(default)]
+    duration: Option<f64>,
+    #[serde(default)]
+    instrumental: bool,
+    #[serde(rename = "plainLyrics", default)]
+    plain_lyrics: Option<String>,
+    #[serde(rename = "syncedLyrics", default)]
+    synced_lyrics: Option<String>,
+}
+
+impl LrclibRecord {
+    fn into_lyrics(self) -> Option<FetchedLyrics> {
+        let text = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+        let lyrics = FetchedLyrics {
+            synced: text(self.synced_lyrics),
+            plain: text(self.plain_lyrics),
+        };
+        (!self.instrumental && !lyrics.is_empty()).then_some(lyrics)
+    }
+}
+
+impl Lrclib {
+    const BASE_URL: &'static str = "https://lrclib.net/api";
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, endpoint: &str, query: &[(&str, String)]) -> anyhow::Result<Option<T>> {
+        let url = format!("{}/{}", Self::BASE_URL, endpoint);
+        let response = match net::send(net::RetryPolicy::default(), |client| {
+            client
+                .get(&url)
+                .query(query)
+                .header(reqwest::header::USER_AGENT, user_agent())
+                .timeout(REQUEST_TIMEOUT)
+        })
+        .await
+        {
+            Ok((response, _)) => response,
+            Err(e) if e.status == Some(404) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Some(serde_json::from_slice(&response.bytes().await?)?))
+    }
+}
+
+impl LyricsProvider for Lrclib {
+    fn name(&self) -> &'static str {
+        "LRCLIB"
+    }
+
+    async fn search(&self, query: &LyricsQuery) -> anyhow::Result<Option<FetchedLyrics>> {
+        // 有时长时先精确匹配，找不到再模糊搜索
+        if let Some(duration) = query.duration {
+            let mut params = vec![
+                ("track_name", query.title.clone()),
+                ("artist_name", query.artist.clone()),
+                ("duration", duration.to_string()),
+            ];
+            if let Some(album) = &query.album {
+                params.push(("album_name", album.clone()));
+            }
+            if let Some(lyrics) = self.get::<LrclibRecord>("get", &params).await?.and_then(LrclibRecord::into_lyrics) {
+                return Ok(Some(lyrics));
+            }
+        }
+
+        let params = [("track_name", query.title.clone()), ("artist_name", query.artist.clone())];
+        let records: Vec<LrclibRecord> = self.get("search", &params).await?.unwrap_or_default();
+        let close_enough = |record: &LrclibRecord| match (query.duration, record.duration) {
+            (Some(expected), Some(actual)) => (expected as f64 - actual).abs() <= MAX_DURATION_DIFF_SECS,
+            _ => true,
+        };
+        // 优先有同步歌词的结果
+        let mut candidates: Vec<LrclibRecord> = records.into_iter().filter(close_enough).collect();
+        candidates.sort_by_key(|record| record.synced_lyrics.is_none());
+        Ok(candidates.into_iter().find_map(LrclibRecord::into_lyrics))
+    }
+}
+
+/// 歌词缓存的键：标题、艺术家和时长（时长不同的版本歌词时间轴不同）
+fn cache_key(query: &LyricsQuery) -> String {
+    format!(
+        "{}\n{}\n{}",
+        query.title.to_lowercase(),
+        query.artist.to_lowercase(),
+        query.duration.map(|d| d.to_string()).unwrap_or_default()
+    )
+}
+
+/// 读取缓存的歌词
+fn cached(key: &str) -> Option<FetchedLyrics> {
+    let path = cache::lookup(CacheKind::Lyrics, key)?;
+    let parsed = std::fs::read(&path)
+        .map_err(anyhow::Error::from)
+        .and_then(|bytes| serde_json::from_slice(&bytes).map_err(anyhow::Error::from));
+    match parsed {
+        Ok(lyrics) => Some(lyrics),
+        Err(e) => {
+            eprintln!("读取歌词缓存失败 {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// 依次向各来源搜索歌词，返回第一个找到的结果和来源名称；先查缓存，找到后写入缓存
+pub async fn search(query: &LyricsQuery) -> anyhow::Result<Option<(FetchedLyrics, &'static str)>> {
+    let key = cache_key(query);
+    if let Some(lyrics) = cached(&key) {
+        return Ok(Some((lyrics, "缓存")));
+    }
+    let provider = Lrclib;
+    match provider.search(query).await {
+        Ok(Some(lyrics)) => {
+            let result = serde_json::to_vec(&lyrics)
+                .map_err(anyhow::Error::from)
+                .and_then(|bytes| cache::put(CacheKind::Lyrics, &key, &bytes));
+            if let Err(e) = result {
+                eprintln!("写入歌词缓存失败: {}", e);
+            }
+            Ok(Some((lyrics, provider.name())))
+        }
+        Ok(None) => Ok(None),
+        Err(e) => {
+            eprintln!("从 {} 获取歌词失败: {}", provider.name(), e);
+            Err(e)
+        }
+    }
+}
+
+/// 按设置保存歌词：同名 .lrc/.txt 文件（已存在时不覆盖），或写入文件的歌词标签
+pub fn save(audio_path: &Path, lyrics: &FetchedLyrics, target: LyricsTarget) -> anyhow::Result<()> {
+    let (text, ext) = match (&lyrics.synced, &lyrics.plain) {
+        (Some(synced), _) => (synced, "lrc"),
+        (None, Some(plain)) => (plain, "txt"),
+        (None, None) => return Err(anyhow::anyhow!("歌词为空")),
+    };
+    match target {
+        LyricsTarget::Sidecar => {
+            let lyric_path = audio_path.with_extension(ext);
+            if lyric_path.exists() {
+                return Err(anyhow::anyhow!("歌词文件已存在: {}", lyric_path.display()));
+            }
+            std::fs::write(&lyric_path, text)?;
+            println!("📝 已保存歌词: {}", lyric_path.display());
+        }
+        LyricsTarget::Tag => tag_write::write_lyrics(audio_path, text)?,
+    }
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use crate::player_fixed::SongInfo;
+
+/// 生成扩展 M3U 文本
+/// location 用于决定每个条目写入的位置（绝对路径或串流URL）
+/// 条目的分组写为 #EXTGRP，颜色写为播放器自定义的 #EXTCOLOR，其他播放器会忽略后者
+/// M3U 无法表示起止位置，CUE/多章节容器中同一文件（同一音轨）的连续虚拟曲目合并为一个条目，
+/// 标题用专辑名，时长为各段之和，否则其他播放器会把整个文件重复播放多遍
+pub fn to_extm3u<F>(songs: &[SongInfo], mut location: F) -> String
+where
+    F: FnMut(usize, &SongInfo) -> String,
+{
+    let mut out = String::from("#EXTM3U\n");
+    let mut idx = 0;
+    while idx < songs.len() {
+        let song = &songs[idx];
+        let same_file = |other: &SongInfo| {
+            other.path == song.path
+                && other.segment.is_some()
+                && other.segment.as_ref().and_then(|s| s.track_id) == song.segment.as_ref().and_then(|s| s.track_id)
+        };
+        let count = match song.segment {
+            Some(_) => songs[idx..].iter().take_while(|other| same_file(other)).count(),
+            None => 1,
+        };
+        let merged = &songs[idx..idx + count];
+
+        let duration = merged
+            .iter()
+            .map(|s| s.duration)
+            .sum::<Option<u64>>()
+            .map(|d| d as i64)
+            .unwrap_or(-1);
+        let file_stem = || {
+            std::path::Path::new(&song.path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        };
+        let title = if count > 1 {
+            song.album.clone().filter(|a| !a.is_empty()).unwrap_or_else(file_stem)
+        } else {
+            song.title.clone().unwrap_or_else(file_stem)
+        };
+        let display = match &song.artist {
+            Some(artist) if !artist.is_empty() => format!("{} - {}", artist, title),
+            _ => title,
+        };
+        out.push_str(&format!("#EXTINF:{},{}\n", duration, display.replace('\n', " ")));
+        if let Some(group) = song.group.as_deref().filter(|g| !g.is_empty()) {
+            out.push_str(&format!("#EXTGRP:{}\n", group.replace('\n', " ")));
+        }
+        if let Some(color) = &song.color {
+            out.push_str(&format!("#EXTCOLOR:{}\n", color));
+        }
+        out.push_str(&location(idx, song));
+        out.push('\n');
+        idx += count;
+    }
+    out
+}
+
+/// M3U 中的一个条目
+#[derive(Debug, Clone)]
+pub struct M3uEntry {
+    pub location: String,      // 绝对路径或串流URL
+    pub duration: Option<u64>, // #EXTINF 中的时长（秒），写为 -1 时为 None
+    pub title: Option<String>, // #EXTINF 中逗号后的显示名称
+}
+
+/// 解析 M3U/M3U8 文本，相对路径按 base_dir 解析，串流URL原样保留
+pub fn parse(text: &str, base_dir: &std::path::Path) -> Vec<M3uEntry> {
+    let mut entries = Vec::new();
+    let mut pending: (Option<u64>, Option<String>) = (None, None);
+    for line in text.lines().map(|line| line.trim().trim_start_matches('\u{feff}')) {
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            let (duration, title) = info.split_once(',').unwrap_or((info, ""));
+            // 时长后可能跟属性（tvg-id="..." 等），只取第一个数字
+            let duration = duration
+                .split_whitespace()
+                .next()
+                .and_then(|d| d.parse::<f64>().ok())
+                .filter(|d| *d >= 0.0)
+                .map(|d| d.round() as u64);
+            pending = (duration, Some(title.trim().to_string()).filter(|t| !t.is_empty()));
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let location = if line.contains("://") || std::path::Path::new(line).is_absolute() {
+            line.to_string()
+        } else {
+            base_dir.join(line.replace('\\', "/")).to_string_lossy().into_owned()
+        };
+        let (duration, title) = std::mem::take(&mut pending);
+        entries.push(M3uEntry { location, duration, title });
+    }
+    entries
+}
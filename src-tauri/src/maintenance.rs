@@ -0,0 +1,419 @@
+use crate::bpm;
+use crate::cache;
+use crate::export;
+use crate::library;
+use crate::library_verify;
+use crate::player_fixed::SongInfo;
+use crate::storage;
+use chrono::Timelike;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+/// 维护任务是否正在运行
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 请求取消正在运行的维护任务
+static CANCEL: AtomicBool = AtomicBool::new(false);
+
+/// 维护任务结束时（包括任务中途 panic）清除运行标记，否则之后再也无法开始维护
+struct RunningGuard;
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        update_status(|status| {
+            status.running = false;
+            status.current_job = None;
+        });
+        RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// 两次自动维护之间至少间隔的时间（秒），避免空闲窗口内反复执行
+const MIN_INTERVAL_SECS: u64 = 20 * 3600;
+
+/// 重新扫描时每批写入音乐库的曲目数
+const UPSERT_BATCH: usize = 100;
+
+/// 维护任务
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MaintenanceJob {
+    Rescan,       // 重新读取修改过的文件的标签，统计已不存在的文件
+    Loudness,     // 测量尚未分析的曲目的积分响度
+    Bpm,          // 分析尚未分析的曲目的BPM
+    Duplicates,   // 补全文件校验值并找出内容相同的曲目
+    CacheCleanup, // 淘汰超出上限的缓存
+}
+
+impl MaintenanceJob {
+    const ALL: [MaintenanceJob; 5] = [
+        MaintenanceJob::Rescan,
+        MaintenanceJob::Loudness,
+        MaintenanceJob::Bpm,
+        MaintenanceJob::Duplicates,
+        MaintenanceJob::CacheCleanup,
+    ];
+}
+
+/// 允许自动运行维护任务的时段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MaintenanceWindow {
+    /// 每天的固定时段（本地时间，一天中的分钟数），结束早于开始时表示跨越午夜
+    Daily {
+        #[serde(rename = "startMinute")]
+        start_minute: u32,
+        #[serde(rename = "endMinute")]
+        end_minute: u32,
+    },
+    /// 播放器持续空闲（未在播放）超过指定分钟数
+    Idle { minutes: u64 },
+}
+
+impl Default for MaintenanceWindow {
+    fn default() -> Self {
+        MaintenanceWindow::Daily {
+            start_minute: 3 * 60,
+            end_minute: 5 * 60,
+        }
+    }
+}
+
+/// 维护计划设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub window: MaintenanceWindow,
+    #[serde(default = "default_jobs")]
+    pub jobs: Vec<MaintenanceJob>,
+    /// 分析类任务（响度、BPM、校验值）每次最多处理的曲目数，剩下的留到下次
+    #[serde(rename = "batchLimit", default = "default_batch_limit")]
+    pub batch_limit: usize,
+}
+
+fn default_jobs() -> Vec<MaintenanceJob> {
+    MaintenanceJob::ALL.to_vec()
+}
+
+fn default_batch_limit() -> usize {
+    500
+}
+
+impl Default for MaintenanceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: MaintenanceWindow::default(),
+            jobs: default_jobs(),
+            batch_limit: default_batch_limit(),
+        }
+    }
+}
+
+/// 单个任务的执行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobReport {
+    pub job: MaintenanceJob,
+    #[serde(rename = "startedAt")]
+    pub started_at: u64,
+    #[serde(rename = "finishedAt")]
+    pub finished_at: u64,
+    pub processed: usize,
+    pub detail: Option<String>,
+    pub error: Option<String>,
+    pub cancelled: bool,
+    /// 内容相同的曲目（仅查重任务）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub duplicates: Vec<Vec<String>>,
+}
+
+/// 维护状态，最近一次运行的结果会持久化
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MaintenanceStatus {
+    #[serde(default)]
+    pub running: bool,
+    #[serde(rename = "currentJob", default)]
+    pub current_job: Option<MaintenanceJob>,
+    #[serde(rename = "lastRun", default)]
+    pub last_run: Option<u64>, // 最近一次开始运行的时间（Unix秒）
+    #[serde(default)]
+    pub reports: Vec<JobReport>,
+}
+
+/// 维护过程中发送给前端的事件
+#[derive(Debug, Clone, Serialize)]
+pub enum MaintenanceEvent {
+    JobStarted(MaintenanceJob),
+    JobFinished(JobReport),
+    Finished(MaintenanceStatus),
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("maintenance.json")
+}
+
+fn status_path() -> PathBuf {
+    storage::data_dir().join("maintenance_status.json")
+}
+
+/// 读取维护计划设置
+pub fn load_settings() -> MaintenanceSettings {
+    storage::load_json(&settings_path())
+        .unwrap_or_else(|e| {
+            eprintln!("读取维护计划设置失败: {}", e);
+            None
+        })
+        .unwrap_or_default()
+}
+
+/// 保存维护计划设置
+pub fn save_settings(settings: &MaintenanceSettings) -> anyhow::Result<()> {
+    storage::save_json(&settings_path(), settings)
+}
+
+fn status_lock() -> &'static Mutex<MaintenanceStatus> {
+    static STATUS: OnceLock<Mutex<MaintenanceStatus>> = OnceLock::new();
+    STATUS.get_or_init(|| {
+        let loaded: Option<MaintenanceStatus> = storage::load_json(&status_path()).unwrap_or_else(|e| {
+            eprintln!("读取维护状态失败: {}", e);
+            None
+        });
+        Mutex::new(MaintenanceStatus {
+            running: false,
+            current_job: None,
+            ..loaded.unwrap_or_default()
+        })
+    })
+}
+
+fn update_status(f: impl FnOnce(&mut MaintenanceStatus)) -> MaintenanceStatus {
+    match status_lock().lock() {
+        Ok(mut status) => {
+            f(&mut status);
+            status.clone()
+        }
+        Err(_) => MaintenanceStatus::default(),
+    }
+}
+
+/// 获取维护状态
+pub fn status() -> MaintenanceStatus {
+    update_status(|_| {})
+}
+
+/// 请求取消正在运行的维护任务，当前处理的曲目完成后停止；没有任务在运行时返回 false
+pub fn cancel() -> bool {
+    if !RUNNING.load(Ordering::SeqCst) {
+        return false;
+    }
+    CANCEL.store(true, Ordering::SeqCst);
+    true
+}
+
+/// 维护任务是否正在运行
+pub fn is_running() -> bool {
+    RUNNING.load(Ordering::SeqCst)
+}
+
+fn cancelled() -> bool {
+    CANCEL.load(Ordering::SeqCst)
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 调度器每分钟调用：现在是否应当自动开始维护
+/// idle_secs 为播放器已持续空闲的秒数（正在播放时为 None），正在播放时从不自动开始
+pub fn due(idle_secs: Option<u64>) -> bool {
+    let settings = load_settings();
+    let idle_secs = match idle_secs {
+        Some(secs) if settings.enabled && !is_running() => secs,
+        _ => return false,
+    };
+    if status().last_run.map(|last| now_secs().saturating_sub(last) < MIN_INTERVAL_SECS).unwrap_or(false) {
+        return false;
+    }
+    match settings.window {
+        MaintenanceWindow::Idle { minutes } => idle_secs >= minutes * 60,
+        MaintenanceWindow::Daily { start_minute, end_minute } => {
+            let local = chrono::Local::now();
+            let now = local.hour() * 60 + local.minute();
+            if start_minute <= end_minute {
+                (start_minute..end_minute).contains(&now)
+            } else {
+                now >= start_minute || now < end_minute
+            }
+        }
+    }
+}
+
+/// 按设置依次运行维护任务，进度通过 emit 上报
+pub fn run(mut emit: impl FnMut(MaintenanceEvent)) -> anyhow::Result<MaintenanceStatus> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(anyhow::anyhow!("维护任务正在进行中"));
+    }
+    let guard = RunningGuard;
+    CANCEL.store(false, Ordering::SeqCst);
+    let settings = load_settings();
+    update_status(|status| {
+        status.running = true;
+        status.last_run = Some(now_secs());
+        status.reports.clear();
+    });
+    println!("🛠️ 开始音乐库维护: {:?}", settings.jobs);
+
+    for job in MaintenanceJob::ALL.iter().filter(|job| settings.jobs.contains(job)) {
+        if cancelled() {
+            break;
+        }
+        update_status(|status| status.current_job = Some(*job));
+        emit(MaintenanceEvent::JobStarted(*job));
+        let report = run_job(*job, settings.batch_limit);
+        update_status(|status| status.reports.push(report.clone()));
+        emit(MaintenanceEvent::JobFinished(report));
+    }
+
+    let status = update_status(|status| {
+        status.running = false;
+        status.current_job = None;
+    });
+    if let Err(e) = storage::save_json(&status_path(), &status) {
+        eprintln!("保存维护状态失败: {}", e);
+    }
+    drop(guard);
+    println!("🛠️ 音乐库维护结束{}", if cancelled() { "（已取消）" } else { "" });
+    emit(MaintenanceEvent::Finished(status.clone()));
+    Ok(status)
+}
+
+/// 运行单个任务，错误记录在结果中，不中断后续任务
+fn run_job(job: MaintenanceJob, batch_limit: usize) -> JobReport {
+    let started_at = now_secs();
+    let mut duplicates = Vec::new();
+    let result = match job {
+        MaintenanceJob::Rescan => rescan(),
+        MaintenanceJob::Loudness => analyze_loudness(batch_limit),
+        MaintenanceJob::Bpm => analyze_bpm(batch_limit),
+        MaintenanceJob::Duplicates => find_duplicates(batch_limit).map(|(processed, groups)| {
+            let detail = format!("发现{}组内容相同的曲目", groups.len());
+            duplicates = groups;
+            (processed, detail)
+        }),
+        MaintenanceJob::CacheCleanup => {
+            cache::trim().map(|freed| (0, format!("释放 {} 字节", freed)))
+        }
+    };
+    let (processed, detail, error) = match result {
+        Ok((processed, detail)) => (processed, Some(detail), None),
+        Err(e) => (0, None, Some(e.to_string())),
+    };
+    JobReport {
+        job,
+        started_at,
+        finished_at: now_secs(),
+        processed,
+        detail,
+        error,
+        cancelled: cancelled(),
+        duplicates,
+    }
+}
+
+fn rescan() -> anyhow::Result<(usize, String)> {
+    let tracks = library::with_library(|lib| lib.track_update_times()).map_err(anyhow::Error::msg)?;
+    let (mut updated, mut missing) = (0, 0);
+    let mut batch: Vec<SongInfo> = Vec::new();
+    let flush = |batch: &mut Vec<SongInfo>| -> anyhow::Result<()> {
+        if !batch.is_empty() {
+            library::with_library(|lib| lib.upsert_tracks(batch)).map_err(anyhow::Error::msg)?;
+            batch.clear();
+        }
+        Ok(())
+    };
+    for (path, updated_at) in tracks {
+        if cancelled() {
+            break;
+        }
+        let file = Path::new(&path);
+        match library::file_mtime(file) {
+            None => missing += 1,
+            Some(mtime) if mtime > updated_at => match SongInfo::from_path(file) {
+                Ok(song) => {
+                    batch.push(song);
+                    updated += 1;
+                    if batch.len() >= UPSERT_BATCH {
+                        flush(&mut batch)?;
+                    }
+                }
+                Err(e) => eprintln!("重新读取标签失败 {}: {}", path, e),
+            },
+            Some(_) => {}
+        }
+    }
+    flush(&mut batch)?;
+    Ok((updated, format!("更新{}首，{}首文件已不存在", updated, missing)))
+}
+
+fn analyze_loudness(limit: usize) -> anyhow::Result<(usize, String)> {
+    let paths = library::with_library(|lib| lib.tracks_missing_loudness(limit)).map_err(anyhow::Error::msg)?;
+    let mut processed = 0;
+    for path in paths {
+        if cancelled() {
+            break;
+        }
+        // 无法解码的文件也记为已测量，避免每次维护都重试
+        let (lufs, peak) = match export::measure_file(Path::new(&path)) {
+            Ok(measurement) => (measurement.lufs, Some(measurement.peak)),
+            Err(e) => {
+                eprintln!("测量响度失败 {}: {}", path, e);
+                (None, None)
+            }
+        };
+        library::with_library(|lib| lib.set_loudness(&path, lufs, peak)).map_err(anyhow::Error::msg)?;
+        processed += 1;
+    }
+    Ok((processed, format!("测量了{}首", processed)))
+}
+
+fn analyze_bpm(limit: usize) -> anyhow::Result<(usize, String)> {
+    let paths = library::with_library(|lib| lib.tracks_missing_bpm(limit)).map_err(anyhow::Error::msg)?;
+    let mut processed = 0;
+    for path in paths {
+        if cancelled() {
+            break;
+        }
+        let bpm = bpm::detect(Path::new(&path)).unwrap_or(0.0);
+        library::with_library(|lib| lib.set_bpm(&path, bpm)).map_err(anyhow::Error::msg)?;
+        processed += 1;
+    }
+    Ok((processed, format!("分析了{}首", processed)))
+}
+
+/// 补全缺少的校验值后按校验值分组，返回 (本次计算校验值的曲目数, 重复分组)
+fn find_duplicates(limit: usize) -> anyhow::Result<(usize, Vec<Vec<String>>)> {
+    let records = library::with_library(|lib| lib.checksum_records()).map_err(anyhow::Error::msg)?;
+    let mut processed = 0;
+    for record in records.iter().filter(|r| r.hash.is_none()).take(limit) {
+        if cancelled() {
+            break;
+        }
+        match library_verify::hash_file(Path::new(&record.path)) {
+            Ok((hash, size, mtime)) => {
+                library::with_library(|lib| lib.set_checksum(&record.path, &hash, size, mtime))
+                    .map_err(anyhow::Error::msg)?;
+                processed += 1;
+            }
+            Err(e) => eprintln!("计算校验值失败 {}: {}", record.path, e),
+        }
+    }
+    let groups = library::with_library(|lib| lib.duplicate_groups()).map_err(anyhow::Error::msg)?;
+    Ok((processed, groups))
+}
@@ -0,0 +1,552 @@
+use crate::media_source::MediaReader;
+use crate::midi;
+use crate::player_fixed::SongInfo;
+use rodio::Source;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{Decoder, DecoderOptions};
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+// EBML/Matroska 元素ID
+const EBML_HEADER: u32 = 0x1A45DFA3;
+const SEGMENT: u32 = 0x18538067;
+const INFO: u32 = 0x1549A966;
+const TIMESTAMP_SCALE: u32 = 0x2AD7B1;
+const DURATION: u32 = 0x4489;
+const TRACKS: u32 = 0x1654AE6B;
+const TRACK_ENTRY: u32 = 0xAE;
+const TRACK_NUMBER: u32 = 0xD7;
+const TRACK_TYPE: u32 = 0x83;
+const TRACK_NAME: u32 = 0x536E;
+const TRACK_LANGUAGE: u32 = 0x22B59C;
+const CODEC_ID: u32 = 0x86;
+const VIDEO: u32 = 0xE0;
+const PIXEL_WIDTH: u32 = 0xB0;
+const PIXEL_HEIGHT: u32 = 0xBA;
+const CHAPTERS: u32 = 0x1043A770;
+const EDITION_ENTRY: u32 = 0x45B9;
+const CHAPTER_ATOM: u32 = 0xB6;
+const CHAPTER_TIME_START: u32 = 0x91;
+const CHAPTER_TIME_END: u32 = 0x92;
+const CHAPTER_FLAG_HIDDEN: u32 = 0x98;
+const CHAPTER_DISPLAY: u32 = 0x80;
+const CHAP_STRING: u32 = 0x85;
+const CLUSTER: u32 = 0x1F43B675;
+
+const TRACK_TYPE_VIDEO: u64 = 1;
+const TRACK_TYPE_AUDIO: u64 = 2;
+
+/// 头部元素（Info/Tracks/Chapters）的最大读取大小，避免异常文件占用过多内存
+const MAX_MASTER_SIZE: u64 = 16 * 1024 * 1024;
+
+/// 多音轨/多章节容器中的一段，作为独立的播放列表条目（虚拟曲目）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaSegment {
+    #[serde(rename = "trackId", default)]
+    pub track_id: Option<u32>, // 容器内的音轨号，为空时使用默认音轨
+    #[serde(rename = "startMs", default)]
+    pub start_ms: u64,
+    #[serde(rename = "endMs", default)]
+    pub end_ms: Option<u64>, // 为空时播放到音轨结束
+}
+
+/// 容器中的音频轨
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioTrack {
+    pub id: u32,
+    pub name: Option<String>,
+    pub language: Option<String>,
+    pub codec: String,
+}
+
+/// 容器中的第一个视频轨
+#[derive(Debug, Clone, Serialize)]
+pub struct VideoTrack {
+    pub codec: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// 容器中的章节
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chapter {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(rename = "startMs")]
+    pub start_ms: u64,
+    #[serde(rename = "endMs", default)]
+    pub end_ms: Option<u64>,
+}
+
+/// Matroska 文件的音轨和章节信息
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MatroskaInfo {
+    #[serde(rename = "durationMs")]
+    pub duration_ms: Option<u64>,
+    pub tracks: Vec<AudioTrack>,
+    pub chapters: Vec<Chapter>,
+    pub video: Option<VideoTrack>,
+}
+
+/// 是否为 Matroska 音频文件（.mka）
+pub fn is_matroska_audio(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("mka"))
+        .unwrap_or(false)
+}
+
+/// 读取一个 EBML 变长整数，keep_marker 为 true 时保留长度标记位（元素ID）
+fn read_vint(reader: &mut impl Read, keep_marker: bool) -> std::io::Result<(u64, usize)> {
+    let mut first = [0u8; 1];
+    reader.read_exact(&mut first)?;
+    let len = first[0].leading_zeros() as usize + 1;
+    if len > 8 {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "无效的EBML变长整数"));
+    }
+    let mut value = if keep_marker {
+        first[0] as u64
+    } else {
+        first[0] as u64 & ((1u64 << (8 - len)) - 1)
+    };
+    for _ in 1..len {
+        let mut byte = [0u8; 1];
+        reader.read_exact(&mut byte)?;
+        value = (value << 8) | byte[0] as u64;
+    }
+    Ok((value, len))
+}
+
+/// 读取元素头，返回元素ID和数据大小（未知大小时为 None）
+fn read_header(reader: &mut impl Read) -> std::io::Result<(u32, Option<u64>)> {
+    let (id, _) = read_vint(reader, true)?;
+    let (size, len) = read_vint(reader, false)?;
+    let unknown = size == (1u64 << (7 * len)) - 1;
+    Ok((id as u32, if unknown { None } else { Some(size) }))
+}
+
+/// 解析主元素数据中的子元素
+fn children(data: &[u8]) -> Vec<(u32, &[u8])> {
+    let mut result = Vec::new();
+    let mut cursor = std::io::Cursor::new(data);
+    while (cursor.position() as usize) < data.len() {
+        let (id, size) = match read_header(&mut cursor) {
+            Ok((id, Some(size))) => (id, size as usize),
+            _ => break,
+        };
+        let start = cursor.position() as usize;
+        let end = match start.checked_add(size) {
+            Some(end) if end <= data.len() => end,
+            _ => break,
+        };
+        result.push((id, &data[start..end]));
+        cursor.set_position(end as u64);
+    }
+    result
+}
+
+fn read_uint(data: &[u8]) -> u64 {
+    data.iter().take(8).fold(0u64, |acc, b| (acc << 8) | *b as u64)
+}
+
+fn read_float(data: &[u8]) -> Option<f64> {
+    match data.len() {
+        4 => Some(f32::from_be_bytes(data.try_into().ok()?) as f64),
+        8 => Some(f64::from_be_bytes(data.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+fn read_string(data: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(data).trim_end_matches('\0').trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// 读取 Matroska 文件的时长、音频轨和章节（只读取头部元素，跳过音频数据）
+pub fn probe(path: &Path) -> anyhow::Result<MatroskaInfo> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let file_len = reader.get_ref().metadata()?.len();
+
+    match read_header(&mut reader)? {
+        (EBML_HEADER, Some(size)) => {
+            reader.seek(SeekFrom::Current(size as i64))?;
+        }
+        _ => return Err(anyhow::anyhow!("不是有效的Matroska文件")),
+    }
+    let segment_end = match read_header(&mut reader)? {
+        (SEGMENT, Some(size)) => (reader.stream_position()? + size).min(file_len),
+        (SEGMENT, None) => file_len,
+        _ => return Err(anyhow::anyhow!("找不到Matroska Segment")),
+    };
+
+    let mut info = MatroskaInfo::default();
+    let mut timestamp_scale = 1_000_000u64;
+    let mut raw_duration = None;
+    while reader.stream_position()? < segment_end {
+        let (id, size) = match read_header(&mut reader) {
+            Ok(header) => header,
+            Err(_) => break,
+        };
+        let size = match size {
+            Some(size) => size,
+            None => break, // 未知大小的元素（通常是直播流的Cluster），无法继续跳过
+        };
+        match id {
+            INFO | TRACKS | CHAPTERS if size <= MAX_MASTER_SIZE => {
+                let mut data = vec![0u8; size as usize];
+                reader.read_exact(&mut data)?;
+                match id {
+                    INFO => {
+                        for (child, value) in children(&data) {
+                            match child {
+                                TIMESTAMP_SCALE => timestamp_scale = read_uint(value).max(1),
+                                DURATION => raw_duration = read_float(value),
+                                _ => {}
+                            }
+                        }
+                    }
+                    TRACKS => {
+                        info.tracks = parse_tracks(&data);
+                        info.video = parse_video_track(&data);
+                    }
+                    _ => info.chapters = parse_chapters(&data),
+                }
+            }
+            CLUSTER if !info.tracks.is_empty() && !info.chapters.is_empty() => break,
+            _ => {
+                reader.seek(SeekFrom::Current(size as i64))?;
+            }
+        }
+    }
+
+    info.duration_ms = raw_duration.map(|d| (d * timestamp_scale as f64 / 1_000_000.0) as u64);
+    Ok(info)
+}
+
+fn parse_tracks(data: &[u8]) -> Vec<AudioTrack> {
+    children(data)
+        .into_iter()
+        .filter(|(id, _)| *id == TRACK_ENTRY)
+        .filter_map(|(_, entry)| {
+            let mut number = None;
+            let mut track_type = None;
+            let mut track = AudioTrack {
+                id: 0,
+                name: None,
+                language: None,
+                codec: String::new(),
+            };
+            for (id, value) in children(entry) {
+                match id {
+                    TRACK_NUMBER => number = Some(read_uint(value) as u32),
+                    TRACK_TYPE => track_type = Some(read_uint(value)),
+                    TRACK_NAME => track.name = read_string(value),
+                    TRACK_LANGUAGE => track.language = read_string(value),
+                    CODEC_ID => track.codec = read_string(value).unwrap_or_default(),
+                    _ => {}
+                }
+            }
+            track.id = number?;
+            (track_type == Some(TRACK_TYPE_AUDIO)).then_some(track)
+        })
+        .collect()
+}
+
+fn parse_video_track(data: &[u8]) -> Option<VideoTrack> {
+    children(data).into_iter().filter(|(id, _)| *id == TRACK_ENTRY).find_map(|(_, entry)| {
+        let fields = children(entry);
+        let is_video = fields.iter().any(|(id, value)| *id == TRACK_TYPE && read_uint(value) == TRACK_TYPE_VIDEO);
+        if !is_video {
+            return None;
+        }
+        let mut track = VideoTrack {
+            codec: String::new(),
+            width: None,
+            height: None,
+        };
+        for (id, value) in fields {
+            match id {
+                CODEC_ID => track.codec = read_string(value).unwrap_or_default(),
+                VIDEO => {
+                    for (child, value) in children(value) {
+                        match child {
+                            PIXEL_WIDTH => track.width = Some(read_uint(value) as u32),
+                            PIXEL_HEIGHT => track.height = Some(read_uint(value) as u32),
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Some(track)
+    })
+}
+
+/// 只读取第一个版本（Edition）中的顶层可见章节，时间单位为纳秒
+fn parse_chapters(data: &[u8]) -> Vec<Chapter> {
+    let edition = match children(data).into_iter().find(|(id, _)| *id == EDITION_ENTRY) {
+        Some((_, edition)) => edition,
+        None => return Vec::new(),
+    };
+    let mut chapters: Vec<Chapter> = children(edition)
+        .into_iter()
+        .filter(|(id, _)| *id == CHAPTER_ATOM)
+        .filter_map(|(_, atom)| {
+            let mut chapter = Chapter {
+                title: None,
+                start_ms: 0,
+                end_ms: None,
+            };
+            for (id, value) in children(atom) {
+                match id {
+                    CHAPTER_TIME_START => chapter.start_ms = read_uint(value) / 1_000_000,
+                    CHAPTER_TIME_END => chapter.end_ms = Some(read_uint(value) / 1_000_000),
+                    CHAPTER_FLAG_HIDDEN if read_uint(value) != 0 => return None,
+                    CHAPTER_DISPLAY if chapter.title.is_none() => {
+                        chapter.title = children(value)
+                            .into_iter()
+                            .find(|(id, _)| *id == CHAP_STRING)
+                            .and_then(|(_, s)| read_string(s));
+                    }
+                    _ => {}
+                }
+            }
+            Some(chapter)
+        })
+        .collect();
+    chapters.sort_by_key(|c| c.start_ms);
+    chapters
+}
+
+/// 把多音轨/多章节的 Matroska 文件展开为多个播放列表条目
+/// 只有一个音轨且没有章节时返回 None，按普通文件处理
+pub fn expand(path: &Path) -> anyhow::Result<Option<Vec<SongInfo>>> {
+    let info = probe(path)?;
+    if info.tracks.len() <= 1 && info.chapters.len() <= 1 {
+        return Ok(None);
+    }
+    let base = SongInfo::from_path(path)?;
+    let base_title = base.title.clone().unwrap_or_else(|| {
+        path.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    });
+    let total_ms = info.duration_ms.or(base.duration.map(|d| d * 1000));
+
+    // 没有章节时整条音轨作为一段
+    let chapters: Vec<(Option<String>, u64, Option<u64>)> = if info.chapters.len() > 1 {
+        info.chapters
+            .iter()
+            .enumerate()
+            .map(|(idx, chapter)| {
+                let next_start = info.chapters.get(idx + 1).map(|c| c.start_ms);
+                let end_ms = chapter.end_ms.or(next_start).or(total_ms);
+                let title = chapter.title.clone().unwrap_or_else(|| format!("{} - 第{}章", base_title, idx + 1));
+                (Some(title), chapter.start_ms, end_ms)
+            })
+            .collect()
+    } else {
+        vec![(None, 0, None)]
+    };
+    let tracks: Vec<Option<&AudioTrack>> = if info.tracks.len() > 1 {
+        info.tracks.iter().map(Some).collect()
+    } else {
+        vec![None]
+    };
+
+    let mut songs = Vec::new();
+    for track in &tracks {
+        for (idx, (chapter_title, start_ms, end_ms)) in chapters.iter().enumerate() {
+            let mut title = chapter_title.clone().unwrap_or_else(|| base_title.clone());
+            if let Some(track) = track {
+                let label = track
+                    .name
+                    .clone()
+                    .or_else(|| track.language.clone())
+                    .unwrap_or_else(|| format!("音轨{}", track.id));
+                title = format!("{} [{}]", title, label);
+            }
+            let duration = match end_ms.or(total_ms) {
+                Some(end) => Some(end.saturating_sub(*start_ms) / 1000),
+                None => base.duration,
+            };
+            songs.push(SongInfo {
+                title: Some(title),
+                duration,
+                track_number: if chapters.len() > 1 { Some(idx as u32 + 1) } else { base.track_number },
+                gapless: None,
+                chapters: Vec::new(),
+                video_info: None,
+                trim: None,
+                segment: Some(MediaSegment {
+                    track_id: track.map(|t| t.id),
+                    start_ms: *start_ms,
+                    end_ms: *end_ms,
+                }),
+                ..base.clone()
+            });
+        }
+    }
+    Ok(Some(songs))
+}
+
+/// 解码歌曲对应的音源：普通文件使用 rodio 解码器，MIDI/KAR 使用软件合成器，容器中的音轨/章节使用 symphonia 按音轨解码并定位到起始位置
+pub fn decode(file: MediaReader, song: &SongInfo) -> anyhow::Result<Box<dyn Source<Item = i16> + Send>> {
+    let segment = match &song.segment {
+        Some(segment) => segment,
+        None if midi::is_midi_path(&song.path) => return midi::decode(file),
+        None => return Ok(Box::new(rodio::Decoder::new(BufReader::new(file))?)),
+    };
+    let source = TrackSource::open(file, segment.track_id, segment.start_ms)?;
+    Ok(match segment.end_ms {
+        Some(end_ms) => Box::new(source.take_duration(Duration::from_millis(end_ms.saturating_sub(segment.start_ms)))),
+        None => Box::new(source),
+    })
+}
+
+/// 解码容器中指定音轨的音源
+struct TrackSource {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    channels: u16,
+    sample_rate: u32,
+    buffer: Option<SampleBuffer<i16>>,
+    pos: usize,
+    skip_samples: usize, // 定位后需要丢弃的采样数（定位落在目标位置之前的包内）
+}
+
+impl TrackSource {
+    fn open(file: MediaReader, track_id: Option<u32>, start_ms: u64) -> anyhow::Result<Self> {
+        let stream = MediaSourceStream::new(Box::new(file), Default::default());
+        let mut hint = Hint::new();
+        hint.with_extension("mka");
+        let probed = symphonia::default::get_probe().format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )?;
+        let mut format = probed.format;
+        let track = match track_id {
+            Some(id) => format.tracks().iter().find(|t| t.id == id),
+            None => format.default_track(),
+        }
+        .ok_or_else(|| anyhow::anyhow!("找不到音轨 {:?}", track_id))?;
+        let track_id = track.id;
+        let params = track.codec_params.clone();
+        let sample_rate = params.sample_rate.ok_or_else(|| anyhow::anyhow!("音轨采样率未知"))?;
+        let channels = params.channels.map(|c| c.count() as u16).unwrap_or(2);
+        let decoder = symphonia::default::get_codecs().make(&params, &DecoderOptions::default())?;
+
+        let mut skip_samples = 0;
+        if start_ms > 0 {
+            let seeked = format.seek(
+                SeekMode::Accurate,
+                SeekTo::Time {
+                    time: (start_ms as f64 / 1000.0).into(),
+                    track_id: Some(track_id),
+                },
+            )?;
+            if let Some(time_base) = params.time_base {
+                let early = time_base.calc_time(seeked.required_ts.saturating_sub(seeked.actual_ts));
+                let frames = (early.seconds as f64 + early.frac) * sample_rate as f64;
+                skip_samples = frames as usize * channels as usize;
+            }
+        }
+
+        Ok(Self {
+            format,
+            decoder,
+            track_id,
+            channels,
+            sample_rate,
+            buffer: None,
+            pos: 0,
+            skip_samples,
+        })
+    }
+
+    /// 解码下一个属于该音轨的包，文件结束或出现不可恢复的错误时返回 false
+    fn decode_next(&mut self) -> bool {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(_) => return false,
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                Err(symphonia::core::errors::Error::DecodeError(e)) => {
+                    eprintln!("音轨解码错误，跳过该包: {}", e);
+                    continue;
+                }
+                Err(_) => return false,
+            };
+            let frames = decoded.capacity() as u64;
+            if self.buffer.as_ref().map_or(true, |b| (b.capacity() as u64) < frames) {
+                self.buffer = Some(SampleBuffer::new(frames, *decoded.spec()));
+            }
+            let buffer = match self.buffer.as_mut() {
+                Some(buffer) => buffer,
+                None => return false,
+            };
+            buffer.copy_interleaved_ref(decoded);
+            self.pos = 0;
+            if !buffer.samples().is_empty() {
+                return true;
+            }
+        }
+    }
+}
+
+impl Iterator for TrackSource {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        loop {
+            let len = self.buffer.as_ref().map(|b| b.samples().len()).unwrap_or(0);
+            if self.pos >= len {
+                if !self.decode_next() {
+                    return None;
+                }
+                continue;
+            }
+            let sample = self.buffer.as_ref()?.samples()[self.pos];
+            self.pos += 1;
+            if self.skip_samples > 0 {
+                self.skip_samples -= 1;
+                continue;
+            }
+            return Some(sample);
+        }
+    }
+}
+
+impl Source for TrackSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
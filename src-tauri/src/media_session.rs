@@ -0,0 +1,246 @@
+use crate::player_fixed::{PlayerCommand, PlayerEvent, PlayerState, SongInfo};
+use crate::seek_step;
+use serde::{Deserialize, Serialize};
+use std::sync::{OnceLock, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Runtime};
+
+/// Android 原生插件（gen/android/app/src/main/java/com/tauri_app/app/MediaSessionPlugin.kt）
+#[cfg(target_os = "android")]
+const ANDROID_PACKAGE: &str = "com.tauri_app.app";
+
+/// 平台媒体会话（Android MediaSession、车机界面）需要的当前曲目信息
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionMetadata {
+    pub title: String,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: Option<u64>,
+    pub artwork: Option<String>, // 封面（与 SongInfo.albumCover 相同的 data URL）
+    #[serde(rename = "trackNumber")]
+    pub track_number: Option<u32>,
+}
+
+impl SessionMetadata {
+    fn from_song(song: &SongInfo) -> Self {
+        let title = song.title.clone().unwrap_or_else(|| {
+            std::path::Path::new(&song.path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+        Self {
+            title,
+            artist: song.artist.clone(),
+            album: song.album.clone(),
+            duration_ms: song.duration.map(|d| d * 1000),
+            artwork: song.album_cover.clone(),
+            track_number: song.track_number,
+        }
+    }
+}
+
+/// 队列中的一个条目，id 即播放列表中的索引
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueItem {
+    pub id: usize,
+    pub title: String,
+    pub subtitle: Option<String>, // 艺术家
+}
+
+/// 媒体会话状态，原生一侧据此更新系统会话；位置按 updatedAt 和播放状态外推
+/// Android 上由 init 注册的插件推送给系统 MediaSession（通知栏、锁屏、蓝牙/车机），
+/// 其他平台的原生层用 get_media_session 命令和 "media-session" 事件读取
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaSessionState {
+    pub metadata: Option<SessionMetadata>,
+    pub state: PlayerState,
+    #[serde(rename = "positionMs")]
+    pub position_ms: u64,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: u64, // positionMs 对应的时刻（Unix毫秒）
+    pub queue: Vec<QueueItem>,
+    #[serde(rename = "activeQueueItem")]
+    pub active_queue_item: Option<usize>,
+    pub actions: Vec<&'static str>, // 当前可用的操作，对应 MediaSessionAction
+}
+
+impl Default for MediaSessionState {
+    fn default() -> Self {
+        Self {
+            metadata: None,
+            state: PlayerState::Stopped,
+            position_ms: 0,
+            updated_at: 0,
+            queue: Vec::new(),
+            active_queue_item: None,
+            actions: Vec::new(),
+        }
+    }
+}
+
+/// 系统媒体按钮、通知栏、车机界面发来的操作
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub enum MediaSessionAction {
+    Play,
+    Pause,
+    PlayPause,
+    Stop,
+    SkipToNext,
+    SkipToPrevious,
+    SeekTo {
+        #[serde(rename = "positionMs")]
+        position_ms: u64,
+    },
+    SkipToQueueItem { id: usize },
+    FastForward, // 耳机/车机的快进键，按设置的默认步长
+    Rewind,      // 快退键
+}
+
+impl MediaSessionAction {
+    /// 转换为播放器命令
+    pub fn to_command(&self) -> PlayerCommand {
+        match self {
+            MediaSessionAction::Play => PlayerCommand::Play,
+            MediaSessionAction::Pause => PlayerCommand::Pause,
+            MediaSessionAction::PlayPause if state().state == PlayerState::Playing => PlayerCommand::Pause,
+            MediaSessionAction::PlayPause => PlayerCommand::Play,
+            MediaSessionAction::Stop => PlayerCommand::Stop,
+            MediaSessionAction::SkipToNext => PlayerCommand::Next,
+            MediaSessionAction::SkipToPrevious => PlayerCommand::Previous,
+            MediaSessionAction::SeekTo { position_ms } => PlayerCommand::SeekTo(position_ms / 1000),
+            MediaSessionAction::SkipToQueueItem { id } => PlayerCommand::SetSong(*id),
+            MediaSessionAction::FastForward => PlayerCommand::SkipBy(seek_step::settings().forward_secs as i64),
+            MediaSessionAction::Rewind => PlayerCommand::SkipBy(-(seek_step::settings().backward_secs as i64)),
+        }
+    }
+}
+
+fn session() -> &'static RwLock<MediaSessionState> {
+    static SESSION: OnceLock<RwLock<MediaSessionState>> = OnceLock::new();
+    SESSION.get_or_init(|| RwLock::new(MediaSessionState::default()))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 根据当前状态列出可用的操作
+fn available_actions(state: &MediaSessionState) -> Vec<&'static str> {
+    let mut actions = Vec::new();
+    if state.metadata.is_some() {
+        actions.extend(["playPause", "stop"]);
+        actions.push(if state.state == PlayerState::Playing { "pause" } else { "play" });
+        if state.metadata.as_ref().and_then(|m| m.duration_ms).is_some() {
+            actions.extend(["seekTo", "fastForward", "rewind"]);
+        }
+    }
+    if !state.queue.is_empty() {
+        actions.extend(["skipToNext", "skipToPrevious", "skipToQueueItem"]);
+    }
+    actions
+}
+
+/// 获取当前媒体会话状态
+pub fn state() -> MediaSessionState {
+    session().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// 根据播放器事件更新会话，返回是否需要通知原生一侧
+/// 进度事件只记录位置，不触发通知（系统会话按播放状态自行外推位置）
+pub fn on_player_event(event: &PlayerEvent) -> bool {
+    let mut state = match session().write() {
+        Ok(state) => state,
+        Err(_) => return false,
+    };
+    match event {
+        PlayerEvent::StateChanged(player_state) => {
+            state.state = *player_state;
+        }
+        PlayerEvent::SongChanged(index, song) => {
+            state.metadata = Some(SessionMetadata::from_song(song));
+            state.active_queue_item = Some(*index);
+            state.position_ms = 0;
+        }
+        PlayerEvent::PlaylistUpdated(playlist) => {
+            state.queue = playlist
+                .iter()
+                .enumerate()
+                .map(|(id, song)| {
+                    let metadata = SessionMetadata::from_song(song);
+                    QueueItem { id, title: metadata.title, subtitle: metadata.artist }
+                })
+                .collect();
+        }
+        PlayerEvent::ProgressUpdate { position, .. } => {
+            // 与外推位置相差较大（跳转）时才通知
+            let expected = match state.state {
+                PlayerState::Playing => state.position_ms + now_ms().saturating_sub(state.updated_at),
+                _ => state.position_ms,
+            };
+            let position_ms = position * 1000;
+            state.position_ms = position_ms;
+            state.updated_at = now_ms();
+            return expected.abs_diff(position_ms) > 2000;
+        }
+        _ => return false,
+    }
+    state.updated_at = now_ms();
+    state.actions = available_actions(&state);
+    true
+}
+
+/// Android 原生 MediaSession 插件的句柄
+#[cfg(target_os = "android")]
+struct NativeSession<R: Runtime>(tauri::plugin::PluginHandle<R>);
+
+/// 注册媒体会话插件：Android 上创建系统 MediaSession，系统发来的操作交给 on_action；其他平台不做任何事
+pub fn init<R: Runtime>(on_action: fn(MediaSessionAction)) -> TauriPlugin<R> {
+    tauri::plugin::Builder::new("media-session")
+        .setup(move |app, api| {
+            #[cfg(target_os = "android")]
+            {
+                use tauri::Manager;
+
+                let handle = api.register_android_plugin(ANDROID_PACKAGE, "MediaSessionPlugin")?;
+                let handler = tauri::ipc::Channel::new(move |body| {
+                    match body.deserialize::<MediaSessionAction>() {
+                        Ok(action) => on_action(action),
+                        Err(e) => eprintln!("无法解析媒体会话操作: {}", e),
+                    }
+                    Ok(())
+                });
+                handle.run_mobile_plugin::<()>("setActionHandler", serde_json::json!({ "handler": handler }))?;
+                app.manage(NativeSession(handle));
+            }
+            #[cfg(not(target_os = "android"))]
+            let _ = (app, api, on_action);
+            Ok(())
+        })
+        .build()
+}
+
+/// 把当前会话推送给原生 MediaSession（只在 Android 上有效），在后台线程中调用原生插件
+pub fn sync_native<R: Runtime>(app: &AppHandle<R>) {
+    #[cfg(target_os = "android")]
+    {
+        use tauri::Manager;
+
+        let app = app.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            if let Some(native) = app.try_state::<NativeSession<R>>() {
+                if let Err(e) = native.0.run_mobile_plugin::<()>("update", state()) {
+                    eprintln!("更新系统媒体会话失败: {}", e);
+                }
+            }
+        });
+    }
+    #[cfg(not(target_os = "android"))]
+    let _ = app;
+}
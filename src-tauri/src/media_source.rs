@@ -0,0 +1,462 @@
+use crate::cache::{self, CacheKind};
+use crate::net;
+use crate::player_fixed::{MediaType, PlayerEvent, SongInfo};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// 距离曲目结尾多少秒时开始预取下一首
+pub const PREFETCH_AHEAD_SECS: u64 = 15;
+
+/// 网络音源开始播放前需要缓冲的数据量
+const INITIAL_BUFFER_BYTES: usize = 256 * 1024;
+
+/// 缓冲不足暂停后，至少再缓冲这么多数据才继续读取
+const RESUME_BUFFER_BYTES: usize = 128 * 1024;
+
+/// 等待网络数据的超时时间
+const STALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 有总长度的网络音源最多缓存的数据量，更大的文件不播放
+const MAX_STREAM_BYTES: u64 = 256 * 1024 * 1024;
+
+/// 直播流（没有总长度，不能定位）只在内存中保留最近的这么多数据
+const LIVE_WINDOW_BYTES: usize = 16 * 1024 * 1024;
+
+/// 本地文件超过这个大小时不预读到内存
+const MAX_PREFETCH_FILE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// 同时保留的预取/网络缓冲数（当前曲目和下一首）
+const CACHE_SLOTS: usize = 2;
+
+/// 是否为网络音源
+pub fn is_url(location: &str) -> bool {
+    let lower = location.to_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+#[derive(Default)]
+struct BufferState {
+    data: Vec<u8>,
+    offset: u64,        // data 第一个字节在音源中的位置，直播流丢弃旧数据后大于 0
+    total: Option<u64>, // 服务器返回的总长度，直播流为空
+    finished: bool,
+    cancelled: bool, // 切歌后下载被中止
+    error: Option<String>,
+}
+
+impl BufferState {
+    /// 已下载到的位置
+    fn end(&self) -> u64 {
+        self.offset + self.data.len() as u64
+    }
+
+    /// 是否已缓冲到可以开始播放：None 表示还在缓冲，Some(Err) 表示下载失败或被中止
+    fn initial(&self) -> Option<Result<(), String>> {
+        if self.end() >= INITIAL_BUFFER_BYTES as u64 || (self.finished && self.error.is_none()) {
+            Some(Ok(()))
+        } else if self.finished {
+            Some(Err(self.error.clone().unwrap_or_default()))
+        } else {
+            None
+        }
+    }
+}
+
+/// 下载中（或已预读）的音源数据，多个读取器共享
+struct StreamBuffer {
+    state: Mutex<BufferState>,
+    ready: Condvar,
+    task: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>, // 下载任务，中止时取消
+}
+
+impl StreamBuffer {
+    fn new() -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(BufferState::default()),
+            ready: Condvar::new(),
+            task: Mutex::new(None),
+        })
+    }
+
+    fn finished_with(data: Vec<u8>) -> Arc<Self> {
+        let total = Some(data.len() as u64);
+        Arc::new(Self {
+            state: Mutex::new(BufferState {
+                data,
+                total,
+                finished: true,
+                ..Default::default()
+            }),
+            ready: Condvar::new(),
+            task: Mutex::new(None),
+        })
+    }
+
+    fn update(&self, f: impl FnOnce(&mut BufferState)) {
+        if let Ok(mut state) = self.state.lock() {
+            f(&mut state);
+        }
+        self.ready.notify_all();
+    }
+
+    /// 中止下载，唤醒所有等待数据的读取器
+    fn cancel(&self) {
+        if let Some(task) = self.task.lock().ok().and_then(|mut task| task.take()) {
+            task.abort();
+        }
+        self.update(|s| {
+            if !s.finished {
+                s.finished = true;
+                s.cancelled = true;
+                s.error = Some("下载已中止".to_string());
+            }
+        });
+    }
+
+    /// 等待缓冲到 target 位置（或下载结束），超时返回错误，下载被中止时返回 Interrupted
+    fn wait_for(&self, target: u64) -> std::io::Result<()> {
+        let state = self.state.lock().map_err(|_| std::io::Error::other("缓冲区锁失效"))?;
+        let (state, timeout) = self
+            .ready
+            .wait_timeout_while(state, STALL_TIMEOUT, |s| s.end() < target && !s.finished)
+            .map_err(|_| std::io::Error::other("缓冲区锁失效"))?;
+        if let Some(error) = &state.error {
+            if state.end() < target {
+                let kind = if state.cancelled { std::io::ErrorKind::Interrupted } else { std::io::ErrorKind::Other };
+                return Err(std::io::Error::new(kind, error.clone()));
+            }
+        }
+        if timeout.timed_out() {
+            return Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "网络音源缓冲超时"));
+        }
+        Ok(())
+    }
+}
+
+/// 在后台下载网络音源，边下载边写入缓冲区
+fn start_download(url: &str) -> Arc<StreamBuffer> {
+    let buffer = StreamBuffer::new();
+    let target = buffer.clone();
+    let url = url.to_string();
+    let task = tauri::async_runtime::spawn(async move {
+        let result: anyhow::Result<()> = async {
+            let (mut response, _) = net::send(net::RetryPolicy::default(), |client| client.get(&url)).await?;
+            let total = response.content_length();
+            if total.is_some_and(|total| total > MAX_STREAM_BYTES) {
+                anyhow::bail!("网络音源超过缓存上限（{}MB）", MAX_STREAM_BYTES / 1024 / 1024);
+            }
+            target.update(|s| s.total = total);
+            while let Some(chunk) = response.chunk().await? {
+                target.update(|s| {
+                    s.data.extend_from_slice(&chunk);
+                    // 直播流不能定位，丢弃已经很久以前的数据
+                    if s.total.is_none() && s.data.len() > LIVE_WINDOW_BYTES {
+                        let excess = s.data.len() - LIVE_WINDOW_BYTES;
+                        s.data.drain(..excess);
+                        s.offset += excess as u64;
+                    }
+                });
+            }
+            if total.is_none() {
+                return Ok(());
+            }
+            // 完整下载的音源写入磁盘缓存，下次播放不再下载
+            let data = target.state.lock().map(|s| s.data.clone()).unwrap_or_default();
+            let key = url.clone();
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = cache::put(CacheKind::Stream, &key, &data) {
+                    eprintln!("写入网络音源缓存失败 {}: {}", key, e);
+                }
+            });
+            Ok(())
+        }
+        .await;
+        if let Err(e) = &result {
+            eprintln!("下载网络音源失败 {}: {}", url, e);
+        }
+        target.update(|s| {
+            s.finished = true;
+            s.error = result.err().map(|e| e.to_string());
+        });
+    });
+    if let Ok(mut slot) = buffer.task.lock() {
+        *slot = Some(task);
+    }
+    buffer
+}
+
+/// 预取/网络缓冲缓存，最近使用的在后
+fn buffers() -> &'static Mutex<Vec<(String, Arc<StreamBuffer>)>> {
+    static BUFFERS: OnceLock<Mutex<Vec<(String, Arc<StreamBuffer>)>>> = OnceLock::new();
+    BUFFERS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+fn cached(location: &str) -> Option<Arc<StreamBuffer>> {
+    let mut cache = buffers().lock().ok()?;
+    let idx = cache.iter().position(|(path, _)| path == location)?;
+    let entry = cache.remove(idx);
+    let buffer = entry.1.clone();
+    cache.push(entry);
+    Some(buffer)
+}
+
+fn insert_cache(location: &str, buffer: Arc<StreamBuffer>) {
+    if let Ok(mut cache) = buffers().lock() {
+        cache.retain(|(path, _)| path != location);
+        cache.push((location.to_string(), buffer));
+        while cache.len() > CACHE_SLOTS {
+            cache.remove(0).1.cancel();
+        }
+    }
+}
+
+fn remove_cache(location: &str) {
+    if let Ok(mut cache) = buffers().lock() {
+        cache.retain(|(path, _)| path != location);
+    }
+}
+
+/// 取得网络音源的缓冲，还没有时开始下载
+fn stream_buffer(location: &str) -> Arc<StreamBuffer> {
+    cached(location).unwrap_or_else(|| {
+        let buffer = start_download(location);
+        insert_cache(location, buffer.clone());
+        buffer
+    })
+}
+
+/// 打开歌曲的数据源：优先使用预取的数据，网络音源边下载边播放，其余直接打开本地文件
+/// 网络音源还没有缓冲到可以开始播放时不等待，返回 WouldBlock（先用 buffer_then 在后台缓冲）
+pub fn open(song: &SongInfo, event_tx: &mpsc::Sender<PlayerEvent>) -> std::io::Result<MediaReader> {
+    let buffer = match cached(&song.path) {
+        Some(buffer) => buffer,
+        None if !is_url(&song.path) => return File::open(&song.path).map(MediaReader::File),
+        // 已完整下载过的网络音源直接读磁盘缓存
+        None => match cache::lookup(CacheKind::Stream, &song.path) {
+            Some(path) => return File::open(path).map(MediaReader::File),
+            None => stream_buffer(&song.path),
+        },
+    };
+
+    let initial = buffer.state.lock().ok().and_then(|s| s.initial());
+    match initial {
+        Some(Ok(())) => {}
+        Some(Err(e)) => {
+            // 下载失败的缓冲不保留，下次播放时重新请求
+            remove_cache(&song.path);
+            return Err(std::io::Error::other(e));
+        }
+        None => return Err(std::io::Error::new(std::io::ErrorKind::WouldBlock, "网络音源正在缓冲")),
+    }
+    Ok(MediaReader::Stream(StreamReader {
+        buffer,
+        pos: 0,
+        path: song.path.clone(),
+        event_tx: event_tx.clone(),
+        buffering: false,
+    }))
+}
+
+/// 是否可以直接打开：本地文件、已完整下载过或已缓冲到可以开始播放的网络音源
+pub fn is_ready(location: &str) -> bool {
+    if !is_url(location) || cache::lookup(CacheKind::Stream, location).is_some() {
+        return true;
+    }
+    cached(location)
+        .and_then(|buffer| buffer.state.lock().ok().and_then(|s| s.initial()))
+        .is_some_and(|initial| initial.is_ok())
+}
+
+/// 在后台缓冲网络音源，缓冲到可以开始播放、失败或被中止（Interrupted）时在另一个线程调用 on_ready，调用方不等待
+pub fn buffer_then(
+    song: &SongInfo,
+    event_tx: &mpsc::Sender<PlayerEvent>,
+    on_ready: impl FnOnce(std::io::Result<()>) + Send + 'static,
+) {
+    let path = song.path.clone();
+    let buffer = stream_buffer(&path);
+    let event_tx = event_tx.clone();
+    let _ = event_tx.try_send(PlayerEvent::BufferingStarted { path: path.clone() });
+    std::thread::spawn(move || {
+        let result = buffer.wait_for(INITIAL_BUFFER_BYTES as u64);
+        let _ = event_tx.try_send(PlayerEvent::BufferingFinished { path: path.clone() });
+        if result.is_err() {
+            remove_cache(&path);
+        }
+        on_ready(result);
+    });
+}
+
+/// 中止除 keep 以外所有还在进行的网络音源下载（切歌、停止时调用），已完成的预取保留
+pub fn cancel_downloads(keep: Option<&str>) {
+    let Ok(mut cache) = buffers().lock() else {
+        return;
+    };
+    cache.retain(|(path, buffer)| {
+        let downloading = buffer.state.lock().map(|s| !s.finished).unwrap_or(false);
+        if Some(path.as_str()) == keep || !downloading {
+            return true;
+        }
+        buffer.cancel();
+        false
+    });
+}
+
+/// 预取下一首：本地文件预读到内存，网络音源提前开始缓冲，完成后发送 NextTrackPrefetched 事件
+pub fn prefetch(index: usize, song: &SongInfo, event_tx: mpsc::Sender<PlayerEvent>) {
+    if song.media_type == Some(MediaType::Video) || cached(&song.path).is_some() {
+        return;
+    }
+    let path = song.path.clone();
+    if is_url(&path) {
+        if cache::lookup(CacheKind::Stream, &path).is_some() {
+            let _ = event_tx.try_send(PlayerEvent::NextTrackPrefetched { index, path });
+            return;
+        }
+        let buffer = stream_buffer(&path);
+        std::thread::spawn(move || match buffer.wait_for(INITIAL_BUFFER_BYTES as u64) {
+            Ok(()) => {
+                let _ = event_tx.try_send(PlayerEvent::NextTrackPrefetched { index, path });
+            }
+            Err(e) => eprintln!("预缓冲网络音源失败 {}: {}", path, e),
+        });
+        return;
+    }
+    std::thread::spawn(move || {
+        let too_large = std::fs::metadata(&path).map(|m| m.len() > MAX_PREFETCH_FILE_BYTES).unwrap_or(true);
+        if too_large {
+            return;
+        }
+        match std::fs::read(&path) {
+            Ok(data) => {
+                insert_cache(&path, StreamBuffer::finished_with(data));
+                let _ = event_tx.try_send(PlayerEvent::NextTrackPrefetched { index, path });
+            }
+            Err(e) => eprintln!("预读下一首失败 {}: {}", path, e),
+        }
+    });
+}
+
+/// 可供 rodio / symphonia 解码的数据源
+pub enum MediaReader {
+    File(File),
+    Stream(StreamReader),
+}
+
+impl Read for MediaReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            MediaReader::File(file) => file.read(buf),
+            MediaReader::Stream(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Seek for MediaReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            MediaReader::File(file) => file.seek(pos),
+            MediaReader::Stream(stream) => stream.seek(pos),
+        }
+    }
+}
+
+impl symphonia::core::io::MediaSource for MediaReader {
+    fn is_seekable(&self) -> bool {
+        match self {
+            MediaReader::File(_) => true,
+            MediaReader::Stream(stream) => stream.total().is_some(),
+        }
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        match self {
+            MediaReader::File(file) => file.metadata().ok().map(|m| m.len()),
+            MediaReader::Stream(stream) => stream.total(),
+        }
+    }
+}
+
+/// 从缓冲区读取数据，数据不足时等待下载并发送缓冲事件
+pub struct StreamReader {
+    buffer: Arc<StreamBuffer>,
+    pos: u64,
+    path: String,
+    event_tx: mpsc::Sender<PlayerEvent>,
+    buffering: bool,
+}
+
+impl StreamReader {
+    fn total(&self) -> Option<u64> {
+        self.buffer.state.lock().ok().and_then(|s| s.total)
+    }
+
+    fn set_buffering(&mut self, buffering: bool) {
+        if self.buffering == buffering {
+            return;
+        }
+        self.buffering = buffering;
+        let path = self.path.clone();
+        let event = if buffering {
+            PlayerEvent::BufferingStarted { path }
+        } else {
+            PlayerEvent::BufferingFinished { path }
+        };
+        let _ = self.event_tx.try_send(event);
+    }
+}
+
+impl Read for StreamReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = {
+            let state = self.buffer.state.lock().map_err(|_| std::io::Error::other("缓冲区锁失效"))?;
+            state.end() > self.pos || state.finished
+        };
+        if !available {
+            // 缓冲不足：等待更多数据
+            self.set_buffering(true);
+            let result = self.buffer.wait_for(self.pos + RESUME_BUFFER_BYTES as u64);
+            self.set_buffering(false);
+            result?;
+        }
+        let state = self.buffer.state.lock().map_err(|_| std::io::Error::other("缓冲区锁失效"))?;
+        // 直播流暂停太久，需要的数据已被丢弃：从保留的最早位置继续
+        self.pos = self.pos.max(state.offset);
+        let start = (self.pos - state.offset) as usize;
+        if start >= state.data.len() {
+            return Ok(0);
+        }
+        let n = buf.len().min(state.data.len() - start);
+        buf[..n].copy_from_slice(&state.data[start..start + n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for StreamReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+            SeekFrom::End(delta) => {
+                let state = self.buffer.state.lock().map_err(|_| std::io::Error::other("缓冲区锁失效"))?;
+                let end = match (state.total, state.finished) {
+                    (_, true) => Some(state.end()),
+                    (Some(total), false) => Some(total),
+                    (None, false) => None,
+                };
+                match end {
+                    Some(end) => end.checked_add_signed(delta),
+                    None => {
+                        return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "直播流不支持从结尾定位"));
+                    }
+                }
+            }
+        };
+        self.pos = target.ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "无效的定位位置"))?;
+        Ok(self.pos)
+    }
+}
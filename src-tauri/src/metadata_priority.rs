@@ -0,0 +1,100 @@
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+/// 元数据提取方式，均失败时使用文件名兜底
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MetadataStrategy {
+    Lofty,
+    Audiotags,
+    FormatSpecific, // ID3 / FLAC / OGG 专用解析
+}
+
+impl MetadataStrategy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MetadataStrategy::Lofty => "lofty",
+            MetadataStrategy::Audiotags => "audiotags",
+            MetadataStrategy::FormatSpecific => "formatSpecific",
+        }
+    }
+}
+
+/// 元数据提取顺序设置：formats 按扩展名（小写、不含点）覆盖默认顺序
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataPriority {
+    #[serde(default = "default_order")]
+    pub default: Vec<MetadataStrategy>,
+    #[serde(default)]
+    pub formats: HashMap<String, Vec<MetadataStrategy>>,
+}
+
+impl Default for MetadataPriority {
+    fn default() -> Self {
+        Self {
+            default: default_order(),
+            formats: HashMap::new(),
+        }
+    }
+}
+
+fn default_order() -> Vec<MetadataStrategy> {
+    vec![MetadataStrategy::Lofty, MetadataStrategy::Audiotags, MetadataStrategy::FormatSpecific]
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("metadata_priority.json")
+}
+
+fn settings() -> &'static RwLock<MetadataPriority> {
+    static SETTINGS: OnceLock<RwLock<MetadataPriority>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let loaded = storage::load_json(&settings_path()).unwrap_or_else(|e| {
+            eprintln!("读取元数据提取顺序设置失败: {}", e);
+            None
+        });
+        RwLock::new(loaded.unwrap_or_default())
+    })
+}
+
+/// 获取元数据提取顺序设置
+pub fn get() -> MetadataPriority {
+    settings().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// 校验并保存元数据提取顺序设置
+pub fn set(mut priority: MetadataPriority) -> anyhow::Result<()> {
+    let check = |name: &str, order: &[MetadataStrategy]| -> anyhow::Result<()> {
+        if order.is_empty() {
+            return Err(anyhow::anyhow!("{} 的提取顺序不能为空", name));
+        }
+        for (i, strategy) in order.iter().enumerate() {
+            if order[..i].contains(strategy) {
+                return Err(anyhow::anyhow!("{} 的提取顺序中 {} 重复", name, strategy.label()));
+            }
+        }
+        Ok(())
+    };
+    check("默认", &priority.default)?;
+    priority.formats = priority
+        .formats
+        .into_iter()
+        .map(|(ext, order)| (ext.trim().trim_start_matches('.').to_lowercase(), order))
+        .collect();
+    for (ext, order) in &priority.formats {
+        check(ext, order)?;
+    }
+
+    storage::save_json(&settings_path(), &priority)?;
+    *settings().write().map_err(|_| anyhow::anyhow!("无法锁定元数据提取顺序设置"))? = priority;
+    Ok(())
+}
+
+/// 某个扩展名使用的提取顺序
+pub fn order_for(ext: &str) -> Vec<MetadataStrategy> {
+    let priority = get();
+    priority.formats.get(ext).cloned().unwrap_or(priority.default)
+}
@@ -0,0 +1,305 @@
+use crate::media_source::MediaReader;
+use crate::player_fixed::LyricLine;
+use crate::storage;
+use midly::{MetaMessage, Smf, Timing, TrackEventKind};
+use rodio::Source;
+use rustysynth::{MidiFile, MidiFileSequencer, SoundFont, Synthesizer, SynthesizerSettings};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// 合成输出采样率
+const SAMPLE_RATE: u32 = 44100;
+
+/// 每次合成的帧数
+const BLOCK_FRAMES: usize = 1024;
+
+/// 最后一个音符结束后继续合成的时长，保留释音和混响尾音
+const TAIL_SECS: f64 = 2.0;
+
+/// 随应用打包的 SoundFont 所在目录（相对资源目录）
+const BUNDLED_SOUND_FONT_DIR: &str = "resources/soundfont";
+
+/// 没有可用音色库时的提示（打包的 MiniGM.sf2 被删除、又没有设置自定义音色库时）
+const MISSING_SOUND_FONT: &str = "播放 MIDI/KAR 需要 SoundFont 音色库（.sf2），当前没有可用的音色库，请在设置中选择一个 SoundFont 文件";
+
+static RESOURCE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// MIDI 播放设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MidiOptions {
+    #[serde(rename = "soundFontPath", default)]
+    pub sound_font_path: Option<String>, // 自定义 SoundFont，为空时使用随应用打包的音色库
+}
+
+fn options_path() -> PathBuf {
+    storage::data_dir().join("midi.json")
+}
+
+/// 读取 MIDI 播放设置
+pub fn load_options() -> MidiOptions {
+    storage::load_json(&options_path())
+        .unwrap_or_else(|e| {
+            eprintln!("读取MIDI设置失败: {}", e);
+            None
+        })
+        .unwrap_or_default()
+}
+
+/// 保存 MIDI 播放设置，下次播放时按新设置加载音色库
+pub fn save_options(options: &MidiOptions) -> anyhow::Result<()> {
+    if let Some(path) = &options.sound_font_path {
+        if !Path::new(path).is_file() {
+            return Err(anyhow::anyhow!("SoundFont 文件不存在: {}", path));
+        }
+    }
+    storage::save_json(&options_path(), options)
+}
+
+/// 记录应用资源目录，用于查找打包的 SoundFont
+pub fn set_resource_dir(dir: PathBuf) {
+    let _ = RESOURCE_DIR.set(dir);
+}
+
+/// 是否为 MIDI/KAR 文件
+pub fn is_midi(ext: &str) -> bool {
+    matches!(ext, "mid" | "midi" | "kar")
+}
+
+/// 路径是否为 MIDI/KAR 文件
+pub fn is_midi_path(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| is_midi(&e.to_lowercase()))
+        .unwrap_or(false)
+}
+
+/// 当前使用的 SoundFont 路径：自定义设置优先，其次为打包目录中的第一个 .sf2
+fn sound_font_path() -> Option<PathBuf> {
+    if let Some(path) = load_options().sound_font_path {
+        return Some(PathBuf::from(path));
+    }
+    let dir = RESOURCE_DIR.get()?.join(BUNDLED_SOUND_FONT_DIR);
+    let mut fonts: Vec<PathBuf> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.extension().and_then(|e| e.to_str()).map(|e| e.eq_ignore_ascii_case("sf2")).unwrap_or(false))
+        .collect();
+    fonts.sort();
+    fonts.into_iter().next()
+}
+
+/// 检查 MIDI 播放所需的 SoundFont，返回将要使用的路径；没有可用的音色库时返回说明如何设置的错误
+pub fn check_sound_font() -> anyhow::Result<PathBuf> {
+    let path = sound_font_path().ok_or_else(|| anyhow::anyhow!(MISSING_SOUND_FONT))?;
+    if !path.is_file() {
+        return Err(anyhow::anyhow!("设置的 SoundFont 文件不存在: {}，请在设置中重新选择", path.display()));
+    }
+    Ok(path)
+}
+
+/// 加载 SoundFont，同一路径只加载一次
+fn sound_font() -> anyhow::Result<Arc<SoundFont>> {
+    static LOADED: OnceLock<Mutex<Option<(PathBuf, Arc<SoundFont>)>>> = OnceLock::new();
+    let path = check_sound_font()?;
+    let mut loaded = LOADED
+        .get_or_init(|| Mutex::new(None))
+        .lock()
+        .map_err(|_| anyhow::anyhow!("SoundFont 缓存锁失效"))?;
+    if let Some((loaded_path, font)) = loaded.as_ref() {
+        if *loaded_path == path {
+            return Ok(font.clone());
+        }
+    }
+    println!("加载 SoundFont: {}", path.display());
+    let mut file = std::fs::File::open(&path)?;
+    let font = Arc::new(SoundFont::new(&mut file).map_err(|e| anyhow::anyhow!("解析 SoundFont 失败: {:?}", e))?);
+    *loaded = Some((path, font.clone()));
+    Ok(font)
+}
+
+/// 读取 MIDI 文件时长（秒）
+pub fn duration(path: &Path) -> Option<u64> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let midi = MidiFile::new(&mut file).ok()?;
+    Some(midi.get_length().ceil() as u64)
+}
+
+/// 用软件合成器把 MIDI/KAR 渲染为音源
+pub fn decode(mut reader: MediaReader) -> anyhow::Result<Box<dyn Source<Item = i16> + Send>> {
+    let font = sound_font()?;
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    let midi = Arc::new(MidiFile::new(&mut bytes.as_slice()).map_err(|e| anyhow::anyhow!("解析MIDI文件失败: {:?}", e))?);
+    let settings = SynthesizerSettings::new(SAMPLE_RATE as i32);
+    let synthesizer =
+        Synthesizer::new(&font, &settings).map_err(|e| anyhow::anyhow!("创建合成器失败: {:?}", e))?;
+    let mut sequencer = MidiFileSequencer::new(synthesizer);
+    sequencer.play(&midi, false);
+
+    let length = midi.get_length() + TAIL_SECS;
+    Ok(Box::new(MidiSource {
+        sequencer,
+        left: Vec::new(),
+        right: Vec::new(),
+        pos: 0,
+        remaining_frames: (length * SAMPLE_RATE as f64) as usize,
+        total: Duration::from_secs_f64(length),
+    }))
+}
+
+/// 逐块合成 MIDI 的立体声音源
+struct MidiSource {
+    sequencer: MidiFileSequencer,
+    left: Vec<f32>,
+    right: Vec<f32>,
+    pos: usize, // 当前块内的交错采样位置
+    remaining_frames: usize,
+    total: Duration,
+}
+
+impl Iterator for MidiSource {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        if self.pos >= self.left.len() * 2 {
+            if self.remaining_frames == 0 {
+                return None;
+            }
+            let frames = BLOCK_FRAMES.min(self.remaining_frames);
+            self.left.resize(frames, 0.0);
+            self.right.resize(frames, 0.0);
+            self.sequencer.render(&mut self.left, &mut self.right);
+            self.remaining_frames -= frames;
+            self.pos = 0;
+        }
+        let frame = self.pos / 2;
+        let sample = if self.pos % 2 == 0 { self.left[frame] } else { self.right[frame] };
+        self.pos += 1;
+        Some((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+    }
+}
+
+impl Source for MidiSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(self.total)
+    }
+}
+
+/// 解码 MIDI 中的文本，先按 UTF-8，失败时按 GBK，再退回 Windows-1252
+fn decode_text(bytes: &[u8]) -> String {
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return text.to_string();
+    }
+    let (decoded, _, had_errors) = encoding_rs::GBK.decode(bytes);
+    if !had_errors {
+        return decoded.into_owned();
+    }
+    encoding_rs::WINDOWS_1252.decode(bytes).0.into_owned()
+}
+
+/// 提取 MIDI/KAR 中嵌入的卡拉OK歌词
+/// 优先使用 Lyric 事件；KAR 文件的歌词放在 Text 事件中，以 "/" 换行、"\" 换段，"@" 开头的是文件信息
+pub fn karaoke_lyrics(path: &Path) -> Option<Vec<LyricLine>> {
+    let bytes = std::fs::read(path).ok()?;
+    let smf = Smf::parse(&bytes).ok()?;
+
+    // 收集所有音轨的速度变化和歌词事件（绝对tick）
+    let mut tempos: Vec<(u64, u32)> = Vec::new();
+    let mut lyric_events: Vec<(u64, String)> = Vec::new();
+    let mut text_events: Vec<(u64, String)> = Vec::new();
+    for track in &smf.tracks {
+        let mut tick = 0u64;
+        for event in track {
+            tick += event.delta.as_int() as u64;
+            match event.kind {
+                TrackEventKind::Meta(MetaMessage::Tempo(tempo)) => tempos.push((tick, tempo.as_int())),
+                TrackEventKind::Meta(MetaMessage::Lyric(text)) => lyric_events.push((tick, decode_text(text))),
+                TrackEventKind::Meta(MetaMessage::Text(text)) => text_events.push((tick, decode_text(text))),
+                _ => {}
+            }
+        }
+    }
+
+    let is_kar = path.extension().and_then(|e| e.to_str()).map(|e| e.eq_ignore_ascii_case("kar")).unwrap_or(false)
+        || text_events.iter().any(|(_, text)| text.starts_with("@K"));
+    let mut syllables = if !lyric_events.is_empty() {
+        lyric_events
+    } else if is_kar {
+        text_events.into_iter().filter(|(_, text)| !text.starts_with('@')).collect()
+    } else {
+        return None;
+    };
+    syllables.sort_by_key(|(tick, _)| *tick);
+    tempos.sort_by_key(|(tick, _)| *tick);
+
+    let to_ms = |tick: u64| -> u64 {
+        match smf.header.timing {
+            Timing::Metrical(ticks_per_beat) => {
+                let ticks_per_beat = ticks_per_beat.as_int().max(1) as f64;
+                let mut ms = 0.0;
+                let mut last_tick = 0u64;
+                let mut tempo = 500_000u32; // 默认 120 BPM
+                for &(change_tick, new_tempo) in tempos.iter().take_while(|(t, _)| *t < tick) {
+                    ms += (change_tick - last_tick) as f64 * tempo as f64 / ticks_per_beat / 1000.0;
+                    last_tick = change_tick;
+                    tempo = new_tempo;
+                }
+                ms += (tick - last_tick) as f64 * tempo as f64 / ticks_per_beat / 1000.0;
+                ms as u64
+            }
+            Timing::Timecode(fps, subframes) => {
+                (tick as f64 * 1000.0 / (fps.as_f32() as f64 * subframes.max(1) as f64)) as u64
+            }
+        }
+    };
+
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut line_time = 0u64;
+    let flush = |current: &mut String, line_time: u64, lines: &mut Vec<LyricLine>| {
+        let text = current.trim().to_string();
+        if !text.is_empty() {
+            lines.push(LyricLine { time: line_time, text, words: None });
+        }
+        current.clear();
+    };
+    for (tick, raw) in &syllables {
+        let mut text = raw.as_str();
+        if let Some(rest) = text.strip_prefix('/').or_else(|| text.strip_prefix('\\')) {
+            flush(&mut current, line_time, &mut lines);
+            text = rest;
+        }
+        let syllable = text.trim_end_matches(['\r', '\n']);
+        if current.is_empty() {
+            line_time = to_ms(*tick);
+        }
+        current.push_str(syllable);
+        if syllable.len() != text.len() {
+            flush(&mut current, line_time, &mut lines);
+        }
+    }
+    flush(&mut current, line_time, &mut lines);
+
+    if lines.is_empty() {
+        None
+    } else {
+        println!("从MIDI文件中提取到 {} 行卡拉OK歌词", lines.len());
+        Some(lines)
+    }
+}
@@ -0,0 +1,92 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+/// moov 盒子的最大读取大小，避免异常文件占用过多内存
+const MAX_MOOV_SIZE: u64 = 64 * 1024 * 1024;
+
+/// 解析 MP4 盒子数据中的子盒子
+pub fn boxes(data: &[u8]) -> Vec<([u8; 4], &[u8])> {
+    let mut result = Vec::new();
+    let mut pos = 0usize;
+    while pos + 8 <= data.len() {
+        let size = u32::from_be_bytes(data[pos..pos + 4].try_into().unwrap()) as u64;
+        let kind: [u8; 4] = data[pos + 4..pos + 8].try_into().unwrap();
+        let (header, size) = match size {
+            0 => (8, (data.len() - pos) as u64),
+            1 if pos + 16 <= data.len() => (16, u64::from_be_bytes(data[pos + 8..pos + 16].try_into().unwrap())),
+            _ => (8, size),
+        };
+        let end = pos as u64 + size;
+        if size < header as u64 || end > data.len() as u64 {
+            break;
+        }
+        result.push((kind, &data[pos + header..end as usize]));
+        pos = end as usize;
+    }
+    result
+}
+
+pub fn child<'a>(data: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    boxes(data).into_iter().find(|(k, _)| k == kind).map(|(_, d)| d)
+}
+
+pub fn be_u32(data: &[u8], at: usize) -> Option<u32> {
+    data.get(at..at + 4).map(|b| u32::from_be_bytes(b.try_into().unwrap()))
+}
+
+pub fn be_u64(data: &[u8], at: usize) -> Option<u64> {
+    data.get(at..at + 8).map(|b| u64::from_be_bytes(b.try_into().unwrap()))
+}
+
+/// 在文件顶层找到 moov 盒子并整个读入（moov 可能在 mdat 之后，逐个盒子跳过，不读取媒体数据）
+pub fn read_moov(file: &mut File) -> anyhow::Result<Vec<u8>> {
+    let file_len = file.metadata()?.len();
+    let mut pos = 0u64;
+    while pos + 8 <= file_len {
+        file.seek(SeekFrom::Start(pos))?;
+        let mut header = [0u8; 16];
+        file.read_exact(&mut header[..8])?;
+        let size = u32::from_be_bytes(header[..4].try_into().unwrap()) as u64;
+        let (header_len, size) = match size {
+            0 => (8, file_len - pos),
+            1 => {
+                file.read_exact(&mut header[8..])?;
+                (16, u64::from_be_bytes(header[8..].try_into().unwrap()))
+            }
+            _ => (8, size),
+        };
+        if size < header_len {
+            break;
+        }
+        if &header[4..8] == b"moov" {
+            let body = size - header_len;
+            if body > MAX_MOOV_SIZE {
+                return Err(anyhow::anyhow!("moov 过大: {} 字节", body));
+            }
+            let mut data = vec![0u8; body as usize];
+            file.read_exact(&mut data)?;
+            return Ok(data);
+        }
+        pos += size;
+    }
+    Err(anyhow::anyhow!("没有找到 moov"))
+}
+
+/// moov/udta/meta/ilst 元数据列表；meta 在 MP4 中带 4 字节版本/标志，QuickTime 文件中没有
+pub fn ilst(moov: &[u8]) -> Option<&[u8]> {
+    let meta = child(child(moov, b"udta")?, b"meta")?;
+    let children = if meta.get(4..8) == Some(b"hdlr") { meta } else { meta.get(4..)? };
+    child(children, b"ilst")
+}
+
+/// ilst 中 "----" 自由格式条目（如 com.apple.iTunes:iTunSMPB）的值
+pub fn freeform_value<'a>(ilst: &'a [u8], name: &str) -> Option<&'a [u8]> {
+    boxes(ilst).into_iter().filter(|(k, _)| k == b"----").find_map(|(_, entry)| {
+        // name 盒子：4 字节版本/标志 + 名称；data 盒子：4 字节类型 + 4 字节 locale + 值
+        let entry_name = child(entry, b"name")?.get(4..)?;
+        if !entry_name.eq_ignore_ascii_case(name.as_bytes()) {
+            return None;
+        }
+        child(entry, b"data")?.get(8..)
+    })
+}
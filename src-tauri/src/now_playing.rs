@@ -0,0 +1,169 @@
+use crate::player_fixed::{PlayerEvent, SongInfo};
+use crate::storage;
+use base64::Engine;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 直播叠加层（OBS 文本源/浏览器源）使用的当前曲目信息
+#[derive(Debug, Clone, Serialize)]
+pub struct NowPlaying {
+    pub title: String,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration: Option<u64>, // 单位：秒
+    #[serde(rename = "coverPath")]
+    pub cover_path: Option<String>, // 写出的封面图片文件
+    #[serde(rename = "startedAt")]
+    pub started_at: u64, // 开始播放的时间（Unix毫秒）
+}
+
+/// 封面图片（MIME类型和内容）
+struct Cover {
+    mime: String,
+    data: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Current {
+    info: Option<NowPlaying>,
+    cover: Option<Cover>,
+}
+
+fn current() -> &'static RwLock<Current> {
+    static CURRENT: OnceLock<RwLock<Current>> = OnceLock::new();
+    CURRENT.get_or_init(|| RwLock::new(Current::default()))
+}
+
+/// 输出文件所在目录：now_playing.txt（"艺术家 - 标题"）、now_playing.json、cover.*
+pub fn output_dir() -> PathBuf {
+    storage::data_dir().join("now_playing")
+}
+
+/// 解析 data URL 形式的封面
+fn decode_cover(data_url: &str) -> Option<Cover> {
+    let (header, payload) = data_url.strip_prefix("data:")?.split_once(',')?;
+    let mime = header.strip_suffix(";base64")?;
+    let data = base64::engine::general_purpose::STANDARD.decode(payload).ok()?;
+    Some(Cover { mime: mime.to_string(), data })
+}
+
+/// 先写临时文件再重命名，避免 OBS 读到写了一半的文件
+fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, contents)?;
+    std::fs::rename(&tmp_path, path)
+}
+
+/// 写出文本、JSON 和封面文件
+fn write_files(info: &mut NowPlaying, cover: Option<&Cover>) -> anyhow::Result<()> {
+    let dir = output_dir();
+    std::fs::create_dir_all(&dir)?;
+    if let Some(cover) = cover {
+        let ext = match cover.mime.as_str() {
+            "image/png" => "png",
+            "image/gif" => "gif",
+            "image/webp" => "webp",
+            _ => "jpg",
+        };
+        // 扩展名变化时删掉旧封面，目录中只保留一个 cover.*
+        for old in ["png", "gif", "webp", "jpg"].iter().filter(|e| **e != ext) {
+            let _ = std::fs::remove_file(dir.join(format!("cover.{}", old)));
+        }
+        let cover_path = dir.join(format!("cover.{}", ext));
+        write_atomic(&cover_path, &cover.data)?;
+        info.cover_path = Some(cover_path.to_string_lossy().into_owned());
+    }
+    let text = match &info.artist {
+        Some(artist) if !artist.is_empty() => format!("{} - {}", artist, info.title),
+        _ => info.title.clone(),
+    };
+    write_atomic(&dir.join("now_playing.txt"), text.as_bytes())?;
+    write_atomic(&dir.join("now_playing.json"), &serde_json::to_vec_pretty(info)?)?;
+    Ok(())
+}
+
+/// 切歌时更新当前曲目信息并写出文件
+pub fn on_player_event(event: &PlayerEvent) {
+    if let PlayerEvent::SongChanged(_, song) = event {
+        update(song);
+    }
+}
+
+fn update(song: &SongInfo) {
+    let title = song.title.clone().unwrap_or_else(|| {
+        Path::new(&song.path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    });
+    let mut info = NowPlaying {
+        title,
+        artist: song.artist.clone(),
+        album: song.album.clone(),
+        duration: song.duration,
+        cover_path: None,
+        started_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+    };
+    let cover = song.album_cover.as_deref().and_then(decode_cover);
+    if let Err(e) = write_files(&mut info, cover.as_ref()) {
+        eprintln!("写出正在播放信息失败: {}", e);
+    }
+    if let Ok(mut current) = current().write() {
+        *current = Current { info: Some(info), cover };
+    }
+}
+
+/// 当前曲目信息（尚未播放时为 None）
+pub fn info() -> Option<NowPlaying> {
+    current().read().ok()?.info.clone()
+}
+
+/// 当前曲目的封面（MIME类型和内容）
+pub fn cover() -> Option<(String, Vec<u8>)> {
+    let current = current().read().ok()?;
+    current.cover.as_ref().map(|c| (c.mime.clone(), c.data.clone()))
+}
+
+/// 浏览器源使用的叠加层页面，每两秒轮询 /now-playing.json
+pub const OVERLAY_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<style>
+  body { margin: 0; background: transparent; font-family: sans-serif; color: #fff; }
+  #np { display: none; align-items: center; gap: 12px; padding: 10px 14px; background: rgba(0, 0, 0, 0.55); border-radius: 8px; width: max-content; }
+  #cover { width: 64px; height: 64px; object-fit: cover; border-radius: 4px; }
+  #title { font-size: 20px; font-weight: bold; }
+  #artist { font-size: 15px; opacity: 0.85; }
+</style>
+</head>
+<body>
+<div id="np"><img id="cover" alt=""><div><div id="title"></div><div id="artist"></div></div></div>
+<script>
+let last = null;
+async function refresh() {
+  try {
+    const res = await fetch("/now-playing.json", { cache: "no-store" });
+    if (!res.ok) return;
+    const np = await res.json();
+    if (np.startedAt === last) return;
+    last = np.startedAt;
+    document.getElementById("title").textContent = np.title;
+    document.getElementById("artist").textContent = np.artist || "";
+    const cover = document.getElementById("cover");
+    cover.style.display = np.coverPath ? "" : "none";
+    cover.src = "/now-playing/cover?t=" + np.startedAt;
+    document.getElementById("np").style.display = "flex";
+  } catch (e) {}
+}
+refresh();
+setInterval(refresh, 2000);
+</script>
+</body>
+</html>
+"#;
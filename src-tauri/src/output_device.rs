@@ -0,0 +1,383 @@
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{BufferSize, Sample, SampleFormat, SupportedBufferSize};
+use rodio::dynamic_mixer::{self, DynamicMixerController};
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// 输出流报告设备已不可用（拔出耳机、断开USB/蓝牙设备）
+static DEVICE_LOST: AtomicBool = AtomicBool::new(false);
+
+/// 输出设备选择，全部为空时使用系统默认设备
+/// host 为音频后端名称（如 "WASAPI"、"ASIO"、"CoreAudio"、"ALSA"），ASIO 需要启用 asio 特性编译
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputDeviceSelection {
+    #[serde(default)]
+    pub host: Option<String>,
+    #[serde(default)]
+    pub device: Option<String>,
+    #[serde(default, rename = "bufferSize")]
+    pub buffer_size: Option<u32>, // 每次回调的帧数，为空时由驱动决定
+    #[serde(default, rename = "matchSampleRate")]
+    pub match_sample_rate: bool, // 设备支持时按文件原始采样率重新打开输出，避免重采样
+}
+
+impl OutputDeviceSelection {
+    /// 是否使用 rodio 默认输出（不指定后端/设备/缓冲区）
+    pub fn is_default(&self) -> bool {
+        self.host.is_none() && self.device.is_none() && self.buffer_size.is_none()
+    }
+}
+
+/// 可用输出设备
+#[derive(Debug, Clone, Serialize)]
+pub struct OutputDeviceInfo {
+    pub host: String,
+    pub name: String,
+    #[serde(rename = "isDefault")]
+    pub is_default: bool,
+    #[serde(rename = "sampleRate")]
+    pub sample_rate: Option<u32>,
+    #[serde(rename = "minBufferSize")]
+    pub min_buffer_size: Option<u32>,
+    #[serde(rename = "maxBufferSize")]
+    pub max_buffer_size: Option<u32>,
+}
+
+/// 已打开的音频输出
+pub enum AudioOutput {
+    /// rodio 默认输出流及其采样率
+    Default(rodio::OutputStream, rodio::OutputStreamHandle, u32),
+    /// 指定设备/缓冲区大小的输出流，rodio 0.18 打开输出流时只能使用设备的缓冲区范围，无法指定固定大小，因此自行构建 cpal 流
+    Device {
+        _stream: cpal::Stream,
+        mixer: Arc<DynamicMixerController<f32>>,
+        key: String,
+        sample_rate: u32,
+    },
+}
+
+impl AudioOutput {
+    /// 在该输出上创建sink
+    pub fn new_sink(&self) -> anyhow::Result<rodio::Sink> {
+        match self {
+            AudioOutput::Default(_, handle, _) => Ok(rodio::Sink::try_new(handle)?),
+            AudioOutput::Device { mixer, .. } => {
+                let (sink, queue) = rodio::Sink::new_idle();
+                mixer.add(queue);
+                Ok(sink)
+            }
+        }
+    }
+
+    /// 输出设备标识（"后端/设备名"），用于按设备保存配置
+    pub fn device_key(&self) -> String {
+        match self {
+            AudioOutput::Default(..) => default_device_key(),
+            AudioOutput::Device { key, .. } => key.clone(),
+        }
+    }
+
+    /// 输出流的采样率
+    pub fn sample_rate(&self) -> u32 {
+        match self {
+            AudioOutput::Default(_, _, rate) => *rate,
+            AudioOutput::Device { sample_rate, .. } => *sample_rate,
+        }
+    }
+}
+
+fn device_key(host: cpal::HostId, device: &cpal::Device) -> String {
+    format!("{}/{}", host.name(), device.name().unwrap_or_default())
+}
+
+/// 系统默认输出设备的默认采样率（rodio 默认输出使用该配置）
+pub fn default_sample_rate() -> Option<u32> {
+    let config = cpal::default_host().default_output_device()?.default_output_config().ok()?;
+    Some(config.sample_rate().0)
+}
+
+/// 系统默认输出设备的标识
+pub fn default_device_key() -> String {
+    let host = cpal::default_host();
+    match host.default_output_device() {
+        Some(device) => device_key(host.id(), &device),
+        None => format!("{}/default", host.id().name()),
+    }
+}
+
+/// 列出所有后端下的输出设备
+pub fn list_devices() -> Vec<OutputDeviceInfo> {
+    let mut result = Vec::new();
+    for host_id in cpal::available_hosts() {
+        let host = match cpal::host_from_id(host_id) {
+            Ok(host) => host,
+            Err(e) => {
+                eprintln!("音频后端 {} 不可用: {}", host_id.name(), e);
+                continue;
+            }
+        };
+        let default_name = host.default_output_device().and_then(|d| d.name().ok());
+        let devices = match host.output_devices() {
+            Ok(devices) => devices,
+            Err(e) => {
+                eprintln!("无法枚举 {} 输出设备: {}", host_id.name(), e);
+                continue;
+            }
+        };
+        for device in devices {
+            let name = match device.name() {
+                Ok(name) => name,
+                Err(_) => continue,
+            };
+            let config = device.default_output_config().ok();
+            let (min_buffer_size, max_buffer_size) = match config.as_ref().map(|c| c.buffer_size()) {
+                Some(SupportedBufferSize::Range { min, max }) => (Some(*min), Some(*max)),
+                _ => (None, None),
+            };
+            result.push(OutputDeviceInfo {
+                host: host_id.name().to_string(),
+                is_default: default_name.as_deref() == Some(name.as_str()),
+                name,
+                sample_rate: config.as_ref().map(|c| c.sample_rate().0),
+                min_buffer_size,
+                max_buffer_size,
+            });
+        }
+    }
+    result
+}
+
+fn find_device(selection: &OutputDeviceSelection) -> anyhow::Result<(cpal::Host, cpal::Device)> {
+    let host = match &selection.host {
+        Some(name) => {
+            let id = cpal::available_hosts()
+                .into_iter()
+                .find(|id| id.name().eq_ignore_ascii_case(name))
+                .ok_or_else(|| anyhow::anyhow!("音频后端不可用: {}", name))?;
+            cpal::host_from_id(id)?
+        }
+        None => cpal::default_host(),
+    };
+    let device = match &selection.device {
+        Some(name) => host
+            .output_devices()?
+            .find(|d| d.name().map(|n| &n == name).unwrap_or(false))
+            .ok_or_else(|| anyhow::anyhow!("找不到输出设备: {}", name))?,
+        None => host
+            .default_output_device()
+            .ok_or_else(|| anyhow::anyhow!("没有默认输出设备"))?,
+    };
+    Ok((host, device))
+}
+
+/// 所选设备是否支持以指定采样率输出（声道数与默认配置相同）
+pub fn supports_sample_rate(selection: &OutputDeviceSelection, rate: u32) -> bool {
+    let check = || -> anyhow::Result<bool> {
+        let (_, device) = find_device(selection)?;
+        let channels = device.default_output_config()?.channels();
+        let supported = device.supported_output_configs()?.any(|range| {
+            range.channels() == channels && range.min_sample_rate().0 <= rate && rate <= range.max_sample_rate().0
+        });
+        Ok(supported)
+    };
+    check().unwrap_or(false)
+}
+
+/// 按选择打开输出设备；指定 sample_rate 时以该采样率打开（需设备支持）
+pub fn open(selection: &OutputDeviceSelection, sample_rate: Option<u32>) -> anyhow::Result<AudioOutput> {
+    let (host, device) = find_device(selection)?;
+
+    let mut supported = device.default_output_config()?;
+    if let Some(rate) = sample_rate {
+        let channels = supported.channels();
+        supported = device
+            .supported_output_configs()?
+            .filter(|range| range.channels() == channels)
+            .filter(|range| range.min_sample_rate().0 <= rate && rate <= range.max_sample_rate().0)
+            .max_by_key(|range| range.sample_format() == supported.sample_format())
+            .map(|range| range.with_sample_rate(cpal::SampleRate(rate)))
+            .ok_or_else(|| anyhow::anyhow!("设备不支持 {}Hz 输出", rate))?;
+    }
+    let mut config = supported.config();
+    if let Some(frames) = selection.buffer_size {
+        if let SupportedBufferSize::Range { min, max } = supported.buffer_size() {
+            if frames < *min || frames > *max {
+                return Err(anyhow::anyhow!("缓冲区大小 {} 超出设备支持范围 {}-{}", frames, min, max));
+            }
+        }
+        config.buffer_size = BufferSize::Fixed(frames);
+    }
+
+    // 以设备原生格式输出，不做额外重采样
+    let (mixer, mut mixer_rx) = dynamic_mixer::mixer::<f32>(config.channels, config.sample_rate.0);
+    DEVICE_LOST.store(false, Ordering::SeqCst);
+    let error_callback = |e: cpal::StreamError| {
+        eprintln!("音频输出流错误: {}", e);
+        if matches!(e, cpal::StreamError::DeviceNotAvailable) {
+            DEVICE_LOST.store(true, Ordering::SeqCst);
+        }
+    };
+    let stream = match supported.sample_format() {
+        SampleFormat::F32 => device.build_output_stream::<f32, _, _>(
+            &config,
+            move |data, _| data.iter_mut().for_each(|d| *d = mixer_rx.next().unwrap_or(0.0)),
+            error_callback,
+            None,
+        )?,
+        SampleFormat::I32 => device.build_output_stream::<i32, _, _>(
+            &config,
+            move |data, _| data.iter_mut().for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0)),
+            error_callback,
+            None,
+        )?,
+        SampleFormat::I16 => device.build_output_stream::<i16, _, _>(
+            &config,
+            move |data, _| data.iter_mut().for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(0)),
+            error_callback,
+            None,
+        )?,
+        SampleFormat::U16 => device.build_output_stream::<u16, _, _>(
+            &config,
+            move |data, _| {
+                data.iter_mut()
+                    .for_each(|d| *d = mixer_rx.next().map(Sample::from_sample).unwrap_or(u16::MAX / 2))
+            },
+            error_callback,
+            None,
+        )?,
+        format => return Err(anyhow::anyhow!("不支持的设备采样格式: {:?}", format)),
+    };
+    stream.play()?;
+
+    println!(
+        "✅ 已打开输出设备: {} / {} ({}Hz, 缓冲区: {})",
+        host.id().name(),
+        device.name().unwrap_or_default(),
+        config.sample_rate.0,
+        selection.buffer_size.map(|f| f.to_string()).unwrap_or_else(|| "默认".to_string())
+    );
+    Ok(AudioOutput::Device {
+        _stream: stream,
+        mixer,
+        key: device_key(host.id(), &device),
+        sample_rate: config.sample_rate.0,
+    })
+}
+
+/// 当前输出设备标识
+fn active_key() -> &'static Mutex<Option<String>> {
+    static ACTIVE: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+    ACTIVE.get_or_init(|| Mutex::new(None))
+}
+
+/// 记录当前使用的输出设备
+pub fn set_active_device(key: &str) {
+    if let Ok(mut active) = active_key().lock() {
+        *active = Some(key.to_string());
+    }
+}
+
+/// 当前使用的输出设备标识（尚未打开设备时为 None）
+pub fn active_device() -> Option<String> {
+    active_key().lock().ok().and_then(|k| k.clone())
+}
+
+/// 所选设备的标识（设备不可用时为空）
+pub fn selection_key(selection: &OutputDeviceSelection) -> Option<String> {
+    find_device(selection).ok().map(|(host, device)| device_key(host.id(), &device))
+}
+
+/// 输出流是否报告过设备不可用（读取后清除）
+pub fn take_device_lost() -> bool {
+    DEVICE_LOST.swap(false, Ordering::SeqCst)
+}
+
+/// 所选设备当前是否存在
+pub fn is_available(selection: &OutputDeviceSelection) -> bool {
+    find_device(selection).is_ok()
+}
+
+/// 使用系统默认设备时，默认设备是否已变为其他设备（如插拔耳机后系统切换了默认输出）
+pub fn default_device_changed() -> bool {
+    match active_device() {
+        Some(active) => active != default_device_key(),
+        None => false,
+    }
+}
+
+fn latency_path() -> PathBuf {
+    storage::data_dir().join("device_latency.json")
+}
+
+/// 各输出设备的延迟补偿（毫秒），键为输出设备标识
+fn latency_offsets() -> &'static Mutex<HashMap<String, u64>> {
+    static OFFSETS: OnceLock<Mutex<HashMap<String, u64>>> = OnceLock::new();
+    OFFSETS.get_or_init(|| {
+        let loaded = storage::load_json(&latency_path()).unwrap_or_else(|e| {
+            eprintln!("读取设备延迟配置失败: {}", e);
+            None
+        });
+        Mutex::new(loaded.unwrap_or_default())
+    })
+}
+
+/// 当前输出设备的延迟补偿（毫秒）
+pub fn latency_ms() -> u64 {
+    let key = match active_device() {
+        Some(key) => key,
+        None => return 0,
+    };
+    latency_offsets()
+        .lock()
+        .ok()
+        .and_then(|o| o.get(&key).copied())
+        .unwrap_or(0)
+}
+
+/// 所有设备的延迟补偿
+pub fn list_latency() -> HashMap<String, u64> {
+    latency_offsets().lock().map(|o| o.clone()).unwrap_or_default()
+}
+
+/// 设置设备的延迟补偿（不指定设备时为当前设备），0为清除
+pub fn set_latency_ms(device_key: Option<String>, ms: u64) -> anyhow::Result<()> {
+    let key = device_key
+        .or_else(active_device)
+        .ok_or_else(|| anyhow::anyhow!("尚未打开输出设备，请指定设备"))?;
+    let snapshot = {
+        let mut offsets = latency_offsets()
+            .lock()
+            .map_err(|_| anyhow::anyhow!("无法锁定设备延迟配置"))?;
+        if ms == 0 {
+            offsets.remove(&key);
+        } else {
+            offsets.insert(key, ms.min(2000));
+        }
+        offsets.clone()
+    };
+    storage::save_json(&latency_path(), &snapshot)
+}
+
+/// 根据校准结果估算设备延迟
+/// 从 start_ms 起每隔 interval_ms 播放一次提示音，taps 为用户听到提示音时点击的时间（均为Unix毫秒）
+/// 每次点击对应最近一次已播放的提示音，取偏差中位数，排除反应明显过慢的点击
+pub fn estimate_latency(start_ms: u64, interval_ms: u64, taps: &[u64]) -> Option<u64> {
+    if interval_ms == 0 {
+        return None;
+    }
+    let mut offsets: Vec<u64> = taps
+        .iter()
+        .filter(|&&tap| tap >= start_ms)
+        .map(|&tap| (tap - start_ms) % interval_ms)
+        .filter(|offset| *offset < interval_ms / 2)
+        .collect();
+    if offsets.len() < 3 {
+        return None;
+    }
+    offsets.sort_unstable();
+    Some(offsets[offsets.len() / 2])
+}
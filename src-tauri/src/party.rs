@@ -0,0 +1,636 @@
+use crate::library;
+use crate::player_fixed::{PlayerCommand, PlayerEvent, SongInfo};
+use crate::remote_api::{self, ApiResult, RemoteSong};
+use crate::search;
+use crate::storage;
+use axum::extract::{ConnectInfo, Query};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use rand::distributions::{Alphanumeric, DistString};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+/// 这么久没有请求的来宾不再计入在线人数
+const GUEST_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// 来宾搜索最多返回的结果数
+const SEARCH_LIMIT: usize = 30;
+
+/// 同一个 IP 同时最多有几位来宾
+const MAX_GUESTS_PER_IP: usize = 3;
+
+/// 同时最多有几位来宾
+const MAX_GUESTS: usize = 200;
+
+/// 派对模式设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartySettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 跳过当前歌曲需要的票数
+    #[serde(rename = "skipVotes", default = "default_skip_votes")]
+    pub skip_votes: usize,
+    /// 每位来宾在 requestWindowSecs 内最多点歌数
+    #[serde(rename = "maxRequests", default = "default_max_requests")]
+    pub max_requests: usize,
+    #[serde(rename = "requestWindowSecs", default = "default_request_window")]
+    pub request_window_secs: u64,
+}
+
+fn default_skip_votes() -> usize {
+    3
+}
+
+fn default_max_requests() -> usize {
+    3
+}
+
+fn default_request_window() -> u64 {
+    15 * 60
+}
+
+impl Default for PartySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            skip_votes: default_skip_votes(),
+            max_requests: default_max_requests(),
+            request_window_secs: default_request_window(),
+        }
+    }
+}
+
+/// 一首点播的歌
+#[derive(Debug, Clone, Serialize)]
+pub struct SongRequest {
+    #[serde(skip)]
+    pub path: String, // 不发给来宾页面
+    pub title: String,
+    pub artist: Option<String>,
+    #[serde(rename = "requestedBy")]
+    pub requested_by: String, // 来宾昵称
+    #[serde(skip)]
+    queued: bool, // 已在播放列表更新中看到这首歌（插入命令处理之前不因旧的播放列表被移出）
+}
+
+/// 派对状态（主界面和来宾页面显示）
+#[derive(Debug, Clone, Serialize)]
+pub struct PartyState {
+    pub enabled: bool,
+    pub guests: usize,              // 在线来宾数
+    pub requests: Vec<SongRequest>, // 还没播放的点歌，按播放顺序
+    #[serde(rename = "skipVotes")]
+    pub skip_votes: usize, // 当前歌曲已有的跳过票数
+    #[serde(rename = "skipThreshold")]
+    pub skip_threshold: usize,
+}
+
+struct Guest {
+    name: String,
+    ip: IpAddr, // 加入时的地址，来宾 ID 只能从这个地址使用
+    last_seen: Instant,
+    requests: VecDeque<Instant>, // 限流窗口内的点歌时间
+}
+
+#[derive(Default)]
+struct Party {
+    guests: HashMap<String, Guest>,
+    requests: Vec<SongRequest>,
+    votes: HashSet<IpAddr>, // 对当前歌曲投了跳过票的设备（按 IP，同一设备加入多次也只算一票）
+    playlist: Vec<String>,  // 播放列表各条目的路径（开启派对模式时记录）
+    current: Option<usize>, // 当前播放的曲目索引
+}
+
+impl Party {
+    /// 只保留排在当前曲目之后、还在等待播放的点歌：开始播放、被跳过或移出播放列表（包括清空）的都移出
+    fn drop_stale_requests(&mut self) {
+        let upcoming = &self.playlist[self.current.map_or(0, |i| i + 1).min(self.playlist.len())..];
+        for request in &mut self.requests {
+            request.queued |= upcoming.contains(&request.path);
+        }
+        self.requests.retain(|r| !r.queued || upcoming.contains(&r.path));
+    }
+
+    /// 移除长时间没有请求的来宾
+    fn prune_guests(&mut self) {
+        self.guests.retain(|_, guest| guest.last_seen.elapsed() < GUEST_IDLE_TIMEOUT);
+    }
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("party.json")
+}
+
+fn settings_lock() -> &'static RwLock<PartySettings> {
+    static SETTINGS: OnceLock<RwLock<PartySettings>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let loaded = storage::load_json(&settings_path()).unwrap_or_else(|e| {
+            eprintln!("读取派对模式设置失败: {}", e);
+            None
+        });
+        RwLock::new(loaded.unwrap_or_default())
+    })
+}
+
+/// 获取派对模式设置
+pub fn settings() -> PartySettings {
+    settings_lock().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// 保存派对模式设置，关闭时清空来宾、点歌和投票
+pub fn set_settings(settings: PartySettings) -> anyhow::Result<()> {
+    let enabled = settings.enabled;
+    storage::save_json(&settings_path(), &settings)?;
+    *settings_lock().write().map_err(|_| anyhow::anyhow!("无法锁定派对模式设置"))? = settings;
+    if !enabled {
+        if let Ok(mut party) = party().lock() {
+            *party = Party {
+                current: party.current,
+                ..Party::default()
+            };
+        }
+    }
+    Ok(())
+}
+
+fn party() -> &'static Mutex<Party> {
+    static PARTY: OnceLock<Mutex<Party>> = OnceLock::new();
+    PARTY.get_or_init(|| Mutex::new(Party::default()))
+}
+
+/// 当前派对状态
+pub fn state() -> PartyState {
+    let settings = settings();
+    let Ok(mut party) = party().lock() else {
+        return PartyState {
+            enabled: settings.enabled,
+            guests: 0,
+            requests: Vec::new(),
+            skip_votes: 0,
+            skip_threshold: settings.skip_votes,
+        };
+    };
+    party.prune_guests();
+    PartyState {
+        enabled: settings.enabled,
+        guests: party.guests.len(),
+        requests: party.requests.clone(),
+        skip_votes: party.votes.len(),
+        skip_threshold: settings.skip_votes,
+    }
+}
+
+/// 切歌时清空跳过票；点播的歌开始播放、被跳过或移出播放列表后移出点歌列表
+pub fn on_player_event(event: &PlayerEvent) {
+    match event {
+        PlayerEvent::SongChanged(index, _) => {
+            let Ok(mut party) = party().lock() else { return };
+            party.votes.clear();
+            party.current = Some(*index);
+            party.drop_stale_requests();
+        }
+        PlayerEvent::PlaylistUpdated(songs) => {
+            let Ok(mut party) = party().lock() else { return };
+            if !settings().enabled && party.requests.is_empty() {
+                party.playlist.clear();
+                return;
+            }
+            party.playlist = songs.iter().map(|song| song.path.clone()).collect();
+            party.drop_stale_requests();
+        }
+        _ => {}
+    }
+}
+
+/// 来宾接口，挂在远程控制服务下，不需要主人的访问令牌；未开启派对模式时返回 404
+pub fn router() -> Router {
+    Router::new()
+        .route("/party", get(party_page))
+        .route("/party/api/join", post(join))
+        .route("/party/api/state", get(get_state))
+        .route("/party/api/search", get(search_songs))
+        .route("/party/api/request", post(request_song))
+        .route("/party/api/vote-skip", post(vote_skip))
+}
+
+fn ensure_enabled() -> ApiResult<PartySettings> {
+    let settings = settings();
+    if !settings.enabled {
+        return Err((StatusCode::NOT_FOUND, "派对模式未开启".to_string()));
+    }
+    Ok(settings)
+}
+
+/// 从 X-Guest-Id 头识别来宾并更新最后活动时间，返回来宾 ID 和昵称；来宾 ID 只能从加入时的地址使用
+fn guest(headers: &HeaderMap, ip: IpAddr) -> ApiResult<(String, String)> {
+    let id = headers
+        .get("x-guest-id")
+        .and_then(|h| h.to_str().ok())
+        .ok_or_else(|| (StatusCode::UNAUTHORIZED, "请先加入派对".to_string()))?;
+    let mut party = party().lock().map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "无法锁定派对状态".to_string()))?;
+    let guest = party
+        .guests
+        .get_mut(id)
+        .filter(|guest| guest.ip == ip)
+        .ok_or_else(|| (StatusCode::UNAUTHORIZED, "请先加入派对".to_string()))?;
+    guest.last_seen = Instant::now();
+    Ok((id.to_string(), guest.name.clone()))
+}
+
+/// GET /party — 来宾页面
+async fn party_page() -> Response {
+    if let Err(e) = ensure_enabled() {
+        return e.into_response();
+    }
+    ([(header::CONTENT_TYPE, "text/html; charset=utf-8")], PARTY_HTML).into_response()
+}
+
+#[derive(Deserialize)]
+struct JoinBody {
+    name: String,
+}
+
+#[derive(Serialize)]
+struct JoinResponse {
+    #[serde(rename = "guestId")]
+    guest_id: String,
+}
+
+/// POST /party/api/join {"name": 昵称} — 加入派对，返回来宾 ID；每个 IP 和总来宾数都有上限
+async fn join(ConnectInfo(addr): ConnectInfo<SocketAddr>, Json(body): Json<JoinBody>) -> ApiResult<Json<JoinResponse>> {
+    ensure_enabled()?;
+    let name: String = body.name.trim().chars().take(24).collect();
+    if name.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "请输入昵称".to_string()));
+    }
+    let guest_id = Alphanumeric.sample_string(&mut rand::thread_rng(), 24);
+    let mut party = party().lock().map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "无法锁定派对状态".to_string()))?;
+    party.prune_guests();
+    if party.guests.values().filter(|guest| guest.ip == addr.ip()).count() >= MAX_GUESTS_PER_IP {
+        return Err((StatusCode::TOO_MANY_REQUESTS, "这台设备加入的次数太多了，请稍后再试".to_string()));
+    }
+    if party.guests.len() >= MAX_GUESTS {
+        return Err((StatusCode::SERVICE_UNAVAILABLE, "派对人数已满".to_string()));
+    }
+    party.guests.insert(
+        guest_id.clone(),
+        Guest {
+            name,
+            ip: addr.ip(),
+            last_seen: Instant::now(),
+            requests: VecDeque::new(),
+        },
+    );
+    Ok(Json(JoinResponse { guest_id }))
+}
+
+#[derive(Serialize)]
+struct GuestState {
+    party: PartyState,
+    song: Option<RemoteSong>,
+    voted: bool, // 该来宾是否已对当前歌曲投票
+}
+
+/// GET /party/api/state — 当前歌曲、点歌列表和投票情况
+async fn get_state(ConnectInfo(addr): ConnectInfo<SocketAddr>, headers: HeaderMap) -> ApiResult<Json<GuestState>> {
+    ensure_enabled()?;
+    guest(&headers, addr.ip())?;
+    let player = remote_api::player().await?;
+    let playlist = player.get_playlist();
+    let song = player
+        .get_current_index()
+        .and_then(|index| playlist.get(index).map(|song| RemoteSong::new(index, song)));
+    let voted = party().lock().map(|p| p.votes.contains(&addr.ip())).unwrap_or(false);
+    Ok(Json(GuestState { party: state(), song, voted }))
+}
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    q: String,
+}
+
+#[derive(Serialize)]
+struct GuestSearchResult {
+    id: i64, // 音乐库中的曲目 ID，点歌时使用（不向来宾暴露文件路径）
+    title: String,
+    artist: Option<String>,
+    album: Option<String>,
+}
+
+/// GET /party/api/search?q= — 在音乐库和播放列表中搜索，只返回音乐库中的曲目
+async fn search_songs(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Query(query): Query<SearchQuery>,
+) -> ApiResult<Json<Vec<GuestSearchResult>>> {
+    ensure_enabled()?;
+    guest(&headers, addr.ip())?;
+    let playlist = remote_api::player().await?.get_playlist();
+    let results = tokio::task::spawn_blocking(move || {
+        let results = search::search(&query.q, &playlist, SEARCH_LIMIT);
+        let paths: Vec<String> = results.iter().map(|r| r.path.clone()).collect();
+        library::with_library(|lib| lib.track_ids(&paths)).map(|ids| (results, ids))
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    let (results, ids) = results;
+    // 播放列表和音乐库中的同一首歌只保留一个
+    let mut seen = HashSet::new();
+    Ok(Json(
+        results
+            .into_iter()
+            .filter_map(|r| {
+                let id = *ids.get(&r.path)?;
+                seen.insert(id).then(|| GuestSearchResult {
+                    id,
+                    title: r.title.unwrap_or_else(|| file_title(&r.path)),
+                    artist: r.artist,
+                    album: r.album,
+                })
+            })
+            .collect(),
+    ))
+}
+
+fn file_title(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[derive(Deserialize)]
+struct RequestBody {
+    id: i64, // 搜索结果中的曲目 ID
+}
+
+/// POST /party/api/request {"id"} — 点歌：排在当前歌曲和之前的点歌之后，每位来宾按设置限流
+async fn request_song(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(body): Json<RequestBody>,
+) -> ApiResult<StatusCode> {
+    let settings = ensure_enabled()?;
+    let (id, name) = guest(&headers, addr.ip())?;
+    let player = remote_api::player().await?;
+    let playlist = player.get_playlist();
+
+    // 只能点音乐库中的歌，来宾不能让播放器打开任意路径
+    let track_id = body.id;
+    let path = tokio::task::spawn_blocking(move || library::with_library(|lib| lib.track_path(track_id)))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "歌曲不存在".to_string()))?;
+
+    let pending = {
+        let mut party = party().lock().map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "无法锁定派对状态".to_string()))?;
+        if party.requests.iter().any(|r| r.path == path) {
+            return Err((StatusCode::CONFLICT, "这首歌已经有人点了".to_string()));
+        }
+        let window = Duration::from_secs(settings.request_window_secs);
+        let guest = party
+            .guests
+            .get_mut(&id)
+            .ok_or_else(|| (StatusCode::UNAUTHORIZED, "请先加入派对".to_string()))?;
+        while guest.requests.front().is_some_and(|t| t.elapsed() >= window) {
+            guest.requests.pop_front();
+        }
+        if guest.requests.len() >= settings.max_requests {
+            let wait = guest.requests.front().map(|t| window.saturating_sub(t.elapsed()).as_secs() / 60 + 1).unwrap_or(1);
+            return Err((StatusCode::TOO_MANY_REQUESTS, format!("点歌太频繁了，请 {} 分钟后再试", wait)));
+        }
+        party.requests.len()
+    };
+
+    let song = {
+        let path = PathBuf::from(&path);
+        tokio::task::spawn_blocking(move || SongInfo::from_path(&path))
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, format!("无法读取歌曲: {}", e)))?
+    };
+    let request = SongRequest {
+        path: song.path.clone(),
+        title: song.title.clone().unwrap_or_else(|| file_title(&song.path)),
+        artist: song.artist.clone(),
+        requested_by: name,
+        queued: false,
+    };
+    let index = (player.get_current_index().map(|i| i + 1).unwrap_or(0) + pending).min(playlist.len());
+    player
+        .send_command(PlayerCommand::InsertSongs { index, songs: vec![song] })
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    // 插入成功后才占用来宾的点歌次数
+    if let Ok(mut party) = party().lock() {
+        println!("🎉 {} 点歌: {}", request.requested_by, request.title);
+        if let Some(guest) = party.guests.get_mut(&id) {
+            guest.requests.push_back(Instant::now());
+        }
+        party.requests.push(request);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /party/api/vote-skip — 投票跳过当前歌曲，达到票数后切到下一首
+async fn vote_skip(ConnectInfo(addr): ConnectInfo<SocketAddr>, headers: HeaderMap) -> ApiResult<Json<PartyState>> {
+    let settings = ensure_enabled()?;
+    guest(&headers, addr.ip())?;
+    let reached = {
+        let mut party = party().lock().map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "无法锁定派对状态".to_string()))?;
+        party.votes.insert(addr.ip());
+        party.votes.len() >= settings.skip_votes.max(1)
+    };
+    if reached {
+        println!("🎉 投票跳过当前歌曲");
+        if let Ok(mut party) = party().lock() {
+            party.votes.clear();
+        }
+        remote_api::send(PlayerCommand::Next).await?;
+    }
+    Ok(Json(state()))
+}
+
+/// 来宾页面：加入、搜索点歌、投票跳过
+const PARTY_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1, maximum-scale=1">
+<title>派对点歌</title>
+<style>
+  * { box-sizing: border-box; }
+  body { margin: 0; font-family: -apple-system, "PingFang SC", "Microsoft YaHei", sans-serif;
+         background: #14101c; color: #eee; -webkit-tap-highlight-color: transparent; }
+  section { padding: 16px 20px; }
+  h2 { font-size: 15px; color: #a78bfa; margin: 0 0 10px; }
+  input { width: 100%; padding: 12px; border: 0; border-radius: 8px; font-size: 16px; background: #251d33; color: #fff; }
+  button { padding: 12px 18px; border: 0; border-radius: 8px; font-size: 16px; background: #a78bfa; color: #14101c; }
+  button:disabled { opacity: 0.5; }
+  #now { font-size: 20px; font-weight: bold; }
+  #nowArtist { color: #999; margin-top: 4px; }
+  #skip { margin-top: 12px; width: 100%; }
+  ul { list-style: none; margin: 0; padding: 0; }
+  li { padding: 12px 0; border-bottom: 1px solid #251d33; display: flex; align-items: center; gap: 12px; }
+  li div { flex: 1; min-width: 0; }
+  li small { display: block; color: #888; margin-top: 2px; }
+  li button { padding: 8px 12px; font-size: 14px; }
+  #message { display: none; padding: 12px 20px; background: #3b2a55; text-align: center; }
+  #joinForm { display: flex; gap: 10px; }
+</style>
+</head>
+<body>
+<div id="message"></div>
+<section id="join">
+  <h2>加入派对</h2>
+  <form id="joinForm"><input id="name" placeholder="你的昵称" maxlength="24"><button>加入</button></form>
+</section>
+<div id="main" style="display:none">
+  <section>
+    <h2>正在播放</h2>
+    <div id="now">-</div>
+    <div id="nowArtist"></div>
+    <button id="skip">投票跳过</button>
+  </section>
+  <section>
+    <h2>点歌</h2>
+    <input id="query" placeholder="搜索歌名、歌手、专辑" type="search">
+    <ul id="results"></ul>
+  </section>
+  <section>
+    <h2>待播点歌</h2>
+    <ul id="requests"></ul>
+  </section>
+</div>
+<script>
+let guestId = localStorage.getItem("partyGuestId");
+
+function showMessage(text) {
+  const el = document.getElementById("message");
+  el.textContent = text || "";
+  el.style.display = text ? "block" : "none";
+  if (text) setTimeout(() => { if (el.textContent === text) showMessage(""); }, 4000);
+}
+
+async function api(path, body) {
+  const options = { method: body === undefined ? "GET" : "POST", headers: { "X-Guest-Id": guestId || "" } };
+  if (body !== undefined) {
+    options.headers["Content-Type"] = "application/json";
+    options.body = JSON.stringify(body);
+  }
+  const response = await fetch("/party/api/" + path, options);
+  if (response.status === 401) {
+    localStorage.removeItem("partyGuestId");
+    guestId = null;
+    showJoined(false);
+  }
+  if (!response.ok) throw new Error(await response.text());
+  return response.status === 204 ? null : response.json();
+}
+
+function showJoined(joined) {
+  document.getElementById("join").style.display = joined ? "none" : "block";
+  document.getElementById("main").style.display = joined ? "block" : "none";
+}
+
+function item(title, subtitle, button) {
+  const li = document.createElement("li");
+  const text = document.createElement("div");
+  text.textContent = title;
+  if (subtitle) {
+    const small = document.createElement("small");
+    small.textContent = subtitle;
+    text.appendChild(small);
+  }
+  li.appendChild(text);
+  if (button) li.appendChild(button);
+  return li;
+}
+
+async function refresh() {
+  if (!guestId) return;
+  try {
+    const state = await api("state");
+    document.getElementById("now").textContent = state.song ? state.song.title : "-";
+    document.getElementById("nowArtist").textContent = state.song && state.song.artist ? state.song.artist : "";
+    const skip = document.getElementById("skip");
+    skip.textContent = "投票跳过 (" + state.party.skipVotes + "/" + state.party.skipThreshold + ")";
+    skip.disabled = state.voted;
+    const list = document.getElementById("requests");
+    list.textContent = "";
+    state.party.requests.forEach((r) => {
+      list.appendChild(item(r.title, (r.artist ? r.artist + " · " : "") + r.requestedBy + " 点的"));
+    });
+  } catch (e) {
+    showMessage(e.message);
+  }
+}
+
+document.getElementById("joinForm").onsubmit = async (e) => {
+  e.preventDefault();
+  try {
+    const result = await api("join", { name: document.getElementById("name").value });
+    guestId = result.guestId;
+    localStorage.setItem("partyGuestId", guestId);
+    showJoined(true);
+    refresh();
+  } catch (e) {
+    showMessage(e.message);
+  }
+};
+
+document.getElementById("skip").onclick = async () => {
+  try {
+    await api("vote-skip", {});
+    refresh();
+  } catch (e) {
+    showMessage(e.message);
+  }
+};
+
+let searchTimer = 0;
+document.getElementById("query").oninput = (e) => {
+  clearTimeout(searchTimer);
+  const q = e.target.value.trim();
+  searchTimer = setTimeout(async () => {
+    const list = document.getElementById("results");
+    list.textContent = "";
+    if (!q) return;
+    try {
+      const results = await api("search?q=" + encodeURIComponent(q));
+      results.forEach((song) => {
+        const button = document.createElement("button");
+        button.textContent = "点歌";
+        button.onclick = async () => {
+          try {
+            await api("request", { id: song.id });
+            button.disabled = true;
+            showMessage("已点歌：" + song.title);
+            refresh();
+          } catch (e) {
+            showMessage(e.message);
+          }
+        };
+        list.appendChild(item(song.title, [song.artist, song.album].filter(Boolean).join(" · "), button));
+      });
+    } catch (e) {
+      showMessage(e.message);
+    }
+  }, 300);
+};
+
+showJoined(!!guestId);
+refresh();
+setInterval(refresh, 3000);
+</script>
+</body>
+</html>
+"#;
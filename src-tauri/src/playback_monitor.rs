@@ -0,0 +1,334 @@
+use crate::storage;
+use cpal::Sample as _;
+use rodio::{Sample, Source};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 单次取样超过该时长即视为解码卡顿（音频回调很可能因此欠载）
+const STALL_THRESHOLD: Duration = Duration::from_millis(50);
+
+/// 诊断信息中保留的最近卡顿记录条数
+const MAX_RECENT_GLITCHES: usize = 100;
+
+/// 低于该幅度（满幅的比例，约 -80 dBFS）的采样视为静音
+const SILENCE_LEVEL: f32 = 1e-4;
+
+/// 每输出这么长的音频更新一次当前播放位置（毫秒）
+const POSITION_UPDATE_MS: u64 = 50;
+
+/// 送往输出设备的采样计数，音频回调线程写入、播放器线程每秒读取
+static SAMPLES_PLAYED: AtomicU64 = AtomicU64::new(0);
+static SAMPLES_AUDIBLE: AtomicU64 = AtomicU64::new(0);
+
+/// 一次播放卡顿记录
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaybackGlitch {
+    pub timestamp: u64, // 发生时间（Unix毫秒）
+    pub position: u64,  // 发生时的播放位置（毫秒）
+    #[serde(rename = "stallMs")]
+    pub stall_ms: u64, // 卡顿时长（毫秒）
+    pub path: String,
+}
+
+/// 播放卡顿诊断汇总
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PlaybackDiagnostics {
+    #[serde(rename = "totalGlitches")]
+    pub total_glitches: u64,
+    #[serde(rename = "totalStallMs")]
+    pub total_stall_ms: u64,
+    #[serde(rename = "maxStallMs")]
+    pub max_stall_ms: u64,
+    #[serde(rename = "recentGlitches")]
+    pub recent_glitches: Vec<PlaybackGlitch>,
+}
+
+impl PlaybackDiagnostics {
+    /// 记录一次卡顿
+    pub fn record(&mut self, glitch: PlaybackGlitch) {
+        self.total_glitches += 1;
+        self.total_stall_ms += glitch.stall_ms;
+        self.max_stall_ms = self.max_stall_ms.max(glitch.stall_ms);
+        if self.recent_glitches.len() >= MAX_RECENT_GLITCHES {
+            self.recent_glitches.remove(0);
+        }
+        self.recent_glitches.push(glitch);
+    }
+}
+
+/// 监测解码卡顿的音源包装器
+/// 在音频回调线程中计时每次取样，超过阈值时通过通道上报给播放器线程
+pub struct GlitchMonitor<S> {
+    inner: S,
+    path: String,
+    reporter: Sender<PlaybackGlitch>,
+    samples_read: u64,
+    start_offset_ms: u64,
+    position_every: u64, // 每读取多少个采样上报一次位置
+}
+
+/// 正在输出的音源（路径, 在曲目中的位置毫秒），音频回调线程写入、播放器线程读取
+fn playing() -> &'static Mutex<Option<(String, u64)>> {
+    static PLAYING: OnceLock<Mutex<Option<(String, u64)>>> = OnceLock::new();
+    PLAYING.get_or_init(|| Mutex::new(None))
+}
+
+/// 该曲目已输出到的位置（毫秒），按音源实际送往输出设备的采样数计算；
+/// 正在输出的不是该曲目（或尚未开始输出）时返回 None
+pub fn playing_position_ms(path: &str) -> Option<u64> {
+    let playing = playing().lock().ok()?;
+    playing.as_ref().filter(|(playing_path, _)| playing_path == path).map(|(_, ms)| *ms)
+}
+
+impl<S> GlitchMonitor<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    /// 包装音源，start_offset_ms 为音源在文件中的起始位置（跳转后播放时使用）
+    pub fn new(inner: S, path: &str, start_offset_ms: u64, reporter: Sender<PlaybackGlitch>) -> Self {
+        let samples_per_sec = inner.sample_rate() as u64 * inner.channels().max(1) as u64;
+        // 清除上一个音源的位置，避免重播同一首时在新音源开始输出前读到旧位置
+        if let Ok(mut playing) = playing().lock() {
+            *playing = None;
+        }
+        Self {
+            inner,
+            path: path.to_string(),
+            reporter,
+            samples_read: 0,
+            start_offset_ms,
+            position_every: (samples_per_sec * POSITION_UPDATE_MS / 1000).max(1),
+        }
+    }
+
+    /// 上报当前位置；音频回调线程中不等待锁，拿不到时留到下一次
+    fn report_position(&self) {
+        if let Ok(mut playing) = playing().try_lock() {
+            let position = self.position_ms();
+            match playing.as_mut() {
+                Some((path, ms)) if *path == self.path => *ms = position,
+                _ => *playing = Some((self.path.clone(), position)),
+            }
+        }
+    }
+
+    /// 当前已播放到的位置（毫秒）
+    fn position_ms(&self) -> u64 {
+        let samples_per_sec = self.inner.sample_rate() as u64 * self.inner.channels().max(1) as u64;
+        if samples_per_sec == 0 {
+            return self.start_offset_ms;
+        }
+        self.start_offset_ms + self.samples_read * 1000 / samples_per_sec
+    }
+}
+
+impl<S> Iterator for GlitchMonitor<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    type Item = S::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<S::Item> {
+        let started = Instant::now();
+        let sample = self.inner.next();
+        let elapsed = started.elapsed();
+
+        // 第一次取样包含解码器初始化，不计入卡顿
+        if elapsed >= STALL_THRESHOLD && self.samples_read > 0 {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0);
+            let _ = self.reporter.send(PlaybackGlitch {
+                timestamp,
+                position: self.position_ms(),
+                stall_ms: elapsed.as_millis() as u64,
+                path: self.path.clone(),
+            });
+        }
+
+        if let Some(value) = sample {
+            self.samples_read += 1;
+            if self.samples_read % self.position_every == 1 || self.position_every == 1 {
+                self.report_position();
+            }
+            SAMPLES_PLAYED.fetch_add(1, Ordering::Relaxed);
+            if value.to_float_sample().to_sample::<f32>().abs() > SILENCE_LEVEL {
+                SAMPLES_AUDIBLE.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        sample
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<S> Source for GlitchMonitor<S>
+where
+    S: Source,
+    S::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), rodio::source::SeekError> {
+        self.inner.try_seek(pos)?;
+        self.start_offset_ms = pos.as_millis() as u64;
+        self.samples_read = 0;
+        self.report_position();
+        Ok(())
+    }
+}
+
+/// 静音检测设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchdogSettings {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 持续多少秒没有输出或输出全为静音才上报
+    #[serde(rename = "silenceSecs", default = "default_silence_secs")]
+    pub silence_secs: u64,
+    /// 输出设备不再取样时自动重新打开输出流并从当前位置继续
+    #[serde(rename = "autoReinit", default)]
+    pub auto_reinit: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_silence_secs() -> u64 {
+    5
+}
+
+impl Default for WatchdogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            silence_secs: default_silence_secs(),
+            auto_reinit: false,
+        }
+    }
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("watchdog.json")
+}
+
+fn settings_lock() -> &'static RwLock<WatchdogSettings> {
+    static SETTINGS: OnceLock<RwLock<WatchdogSettings>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let loaded = storage::load_json(&settings_path()).unwrap_or_else(|e| {
+            eprintln!("读取静音检测设置失败: {}", e);
+            None
+        });
+        RwLock::new(loaded.unwrap_or_default())
+    })
+}
+
+/// 获取静音检测设置
+pub fn watchdog_settings() -> WatchdogSettings {
+    settings_lock().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// 保存静音检测设置
+pub fn set_watchdog_settings(settings: WatchdogSettings) -> anyhow::Result<()> {
+    let settings = WatchdogSettings {
+        silence_secs: settings.silence_secs.max(1),
+        ..settings
+    };
+    storage::save_json(&settings_path(), &settings)?;
+    *settings_lock().write().map_err(|_| anyhow::anyhow!("无法锁定静音检测设置"))? = settings;
+    Ok(())
+}
+
+/// 播放中检测到的静音类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SilenceKind {
+    Stalled, // 输出设备不再取样（蓝牙连接断开、sink 卡住）
+    Silent,  // 仍在取样但信号持续静音
+}
+
+/// 播放状态下的输出静音检测，由播放器线程每秒调用一次
+#[derive(Debug, Default)]
+pub struct SilenceWatchdog {
+    session: Option<Instant>,
+    last_played: u64,
+    last_audible: u64,
+    stalled_secs: u64,
+    silent_secs: u64,
+    reported: bool,
+}
+
+impl SilenceWatchdog {
+    /// 停止计时（未在播放时调用）
+    pub fn reset(&mut self) {
+        self.session = None;
+    }
+
+    /// session 为本次开始出声的时刻（切歌、跳转、恢复播放后会变化，计时随之重新开始）
+    /// 持续 threshold_secs 秒取不到采样或采样全为静音时返回一次检测结果，同一 session 只上报一次
+    pub fn check(&mut self, session: Instant, threshold_secs: u64) -> Option<(SilenceKind, u64)> {
+        let played = SAMPLES_PLAYED.load(Ordering::Relaxed);
+        let audible = SAMPLES_AUDIBLE.load(Ordering::Relaxed);
+        if self.session != Some(session) {
+            *self = Self {
+                session: Some(session),
+                last_played: played,
+                last_audible: audible,
+                ..Self::default()
+            };
+            return None;
+        }
+
+        self.stalled_secs = if played == self.last_played { self.stalled_secs + 1 } else { 0 };
+        self.silent_secs = if played != self.last_played && audible == self.last_audible {
+            self.silent_secs + 1
+        } else {
+            0
+        };
+        self.last_played = played;
+        self.last_audible = audible;
+
+        if self.reported {
+            return None;
+        }
+        let detected = if self.stalled_secs >= threshold_secs {
+            Some((SilenceKind::Stalled, self.stalled_secs))
+        } else if self.silent_secs >= threshold_secs {
+            Some((SilenceKind::Silent, self.silent_secs))
+        } else {
+            None
+        };
+        self.reported = detected.is_some();
+        detected
+    }
+}
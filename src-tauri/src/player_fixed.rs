@@ -9,8 +9,20 @@ use image::{ImageFormat, Rgb, RgbImage};
 use serde::{Deserialize, Serialize};
 use std::io::Cursor;
 use thiserror::Error;
-use lofty::{AudioFile, Probe, TaggedFileExt, Accessor};
+use lofty::{AudioFile, ItemKey, Probe, TaggedFileExt, Accessor};
 use audiotags::Tag as AudioTag;
+use crate::playback_monitor::{PlaybackGlitch, SilenceKind};
+use crate::playlist_tools::{CleanupOptions, CleanupSummary};
+use crate::intro_skip::IntroSkipSuggestion;
+use crate::library::{self, Marker, MetadataOverride, ResumePoint, TrackExtras, TrimPoints};
+use crate::gapless::{self, GaplessInfo};
+use crate::output_device::OutputDeviceSelection;
+use crate::matroska::{Chapter, MediaSegment};
+use crate::chapters;
+use crate::clipboard_watch::ClipboardMedia;
+use crate::metadata_priority::{self, MetadataStrategy};
+use crate::replaygain::{self, ReplayGainTags};
+use crate::video_probe::{self, VideoInfo};
 
 /// 音乐播放器错误类型
 #[derive(Debug, Error)]
@@ -41,9 +53,38 @@ pub enum PlayerError {
 /// 播放模式
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PlayMode {
-    Sequential, // 顺序播放
-    Repeat,     // 单曲循环
-    Shuffle,    // 随机播放
+    #[serde(alias = "Sequential")]
+    RepeatAll, // 列表循环：顺序播放，播完最后一首回到第一首
+    #[serde(alias = "Repeat")]
+    RepeatOne, // 单曲循环：自然播完后重播当前曲目，手动切歌仍切到上一首/下一首
+    NoRepeat,  // 不循环：顺序播放，播完最后一首后停止
+    Shuffle,   // 随机播放
+}
+
+/// 播放列表排序字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortField {
+    Title,     // 标题（无标题时用文件名）
+    Artist,    // 艺术家，同一艺术家按专辑、碟号、音轨号
+    Album,     // 专辑，同一专辑按碟号、音轨号
+    Duration,  // 时长
+    DateAdded, // 加入音乐库的时间（不在音乐库中的按文件修改时间）
+    Path,      // 文件路径
+}
+
+/// 排序方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+/// 添加歌曲时的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EnqueuePolicy {
+    Append,  // 追加到播放列表末尾
+    PlayNow, // 追加并立即播放第一首新歌曲
+    Replace, // 替换整个播放列表并从第一首开始播放
 }
 
 /// 播放器状态
@@ -59,6 +100,16 @@ pub enum PlayerState {
 pub struct LyricLine {
     pub time: u64,      // 时间戳（毫秒）
     pub text: String,   // 歌词文本
+    #[serde(default)]
+    pub words: Option<Vec<LyricWord>>, // 增强型LRC的逐字时间，用于卡拉OK式逐字高亮
+}
+
+/// 歌词中一个字（词）的时间
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LyricWord {
+    pub time: u64,        // 开始时间（毫秒）
+    pub text: String,
+    pub end: Option<u64>, // 结束时间（毫秒），即下一个时间标签
 }
 
 /// 媒体类型枚举
@@ -88,12 +139,348 @@ pub struct SongInfo {
     pub video_thumbnail: Option<String>, // 视频缩略图
     #[serde(rename = "hasLyrics")]
     pub has_lyrics: Option<bool>,       // 是否有歌词
+    #[serde(rename = "trackNumber", default)]
+    pub track_number: Option<u32>,      // 音轨号
+    #[serde(rename = "discNumber", default)]
+    pub disc_number: Option<u32>,       // 碟号
+    #[serde(default)]
+    pub gapless: Option<GaplessInfo>,   // 编码器延迟/填充（MP3/AAC）
+    #[serde(default)]
+    pub color: Option<String>,          // 用户为播放列表条目指定的颜色（#RRGGBB）
+    #[serde(default)]
+    pub group: Option<String>,          // 用户为播放列表条目指定的分组
+    #[serde(default)]
+    pub segment: Option<MediaSegment>,  // 多音轨/多章节容器中的一段（虚拟曲目）
+    #[serde(default)]
+    pub rating: Option<u8>,             // 用户评分（1-5星），未评分为空
+    #[serde(default)]
+    pub favorite: bool,                 // 是否收藏
+    #[serde(rename = "replayGain", default)]
+    pub replay_gain: Option<ReplayGainTags>, // 标签中的 ReplayGain 增益/峰值，没有标签时为音乐库中响度分析换算的增益
+    #[serde(rename = "resumePosition", default)]
+    pub resume_position: Option<u64>,   // 上次播放到的位置（毫秒），开启续播的曲目切到时从这里继续
+    #[serde(rename = "resumeEnabled", default)]
+    pub resume_enabled: bool,           // 是否记住播放位置，载入时按用户设置和曲目时长决定
+    #[serde(default)]
+    pub chapters: Vec<Chapter>,         // 文件内的章节（有声书、播客），按起始时间排序
+    #[serde(rename = "videoInfo", default)]
+    pub video_info: Option<VideoInfo>,  // 视频的分辨率、编码和码率
+    #[serde(default)]
+    pub trim: Option<TrimPoints>,       // 用户设置的播放起止点，载入时从音乐库读取，播放时不再查询
+    #[serde(rename = "coverSource", default)]
+    pub cover_source: Option<String>,   // 封面来源：内嵌封面为 embedded，文件夹封面为图片路径，没有封面时为空
+}
+
+/// 某个元数据来源提取到的值
+#[derive(Debug, Clone, Serialize)]
+pub struct MetadataCandidate {
+    pub source: String, // 标签类型（Id3v2 / Id3v1 / Ape / VorbisComments / Mp4Ilst 等）或 filename
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    #[serde(rename = "trackNumber")]
+    pub track_number: Option<u32>,
+    #[serde(rename = "discNumber")]
+    pub disc_number: Option<u32>,
+}
+
+impl MetadataCandidate {
+    fn from_song(source: &str, song: &SongInfo) -> Self {
+        Self {
+            source: source.to_string(),
+            title: song.title.clone(),
+            artist: song.artist.clone(),
+            album: song.album.clone(),
+            track_number: song.track_number,
+            disc_number: song.disc_number,
+        }
+    }
+}
+
+/// 单个提取方式的试运行结果
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyResult {
+    pub strategy: String,
+    #[serde(rename = "inOrder")]
+    pub in_order: bool, // 是否在该格式的提取顺序中（不在顺序中的方式也会试运行，便于对比）
+    pub succeeded: bool,
+    #[serde(rename = "elapsedMs")]
+    pub elapsed_ms: u64,
+    pub duration: Option<u64>,
+    #[serde(rename = "hasCover")]
+    pub has_cover: bool, // 是否读到了内嵌封面
+    pub metadata: Option<MetadataCandidate>,
+}
+
+/// 元数据提取试运行报告
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtractionReport {
+    pub path: String,
+    pub format: String,
+    pub order: Vec<String>,  // 该格式使用的提取顺序
+    pub selected: String,    // 实际会采用的方式，均失败时为 fallback
+    pub results: Vec<StrategyResult>,
+    pub fallback: MetadataCandidate, // 文件名兜底的结果
+}
+
+/// A/B 对比中当前发声的一路
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AbSide {
+    A,
+    B,
+}
+
+/// A/B 对比状态：A 为当前歌曲，B 为用于对比的另一个文件（如同一首歌的 MP3 与 FLAC）
+#[derive(Debug, Clone, Serialize)]
+pub struct AbCompare {
+    #[serde(rename = "pathA")]
+    pub path_a: String,
+    #[serde(rename = "pathB")]
+    pub path_b: String,
+    pub active: AbSide,
+}
+
+/// A–B 循环区间（秒，相对当前曲目）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct AbLoop {
+    pub start: u64,
+    pub end: u64,
+}
+
+/// 文件的所有元数据候选值
+#[derive(Debug, Clone, Serialize)]
+pub struct MetadataCandidates {
+    pub path: String,
+    pub candidates: Vec<MetadataCandidate>,
+    pub conflicts: Vec<String>, // 各来源取值不一致的字段
+    pub chosen: Option<MetadataOverride>, // 用户已选定的值
+}
+
+/// 封面来源为文件内嵌封面
+pub const EMBEDDED_COVER: &str = "embedded";
+
+/// 至少这么长的曲目（播客、有声书、DJ混音）默认记住播放位置
+const RESUME_MIN_DURATION_SECS: u64 = 20 * 60;
+
+/// 一次读入的文件标签，提取元数据、内嵌歌词、ReplayGain、章节和内嵌 CUE 时共用，不重复读取文件
+pub struct FileTags {
+    pub tagged_file: Option<lofty::TaggedFile>,
+    pub id3: Option<Tag>, // ID3v2 标签（SYLT 同步歌词、CHAP 章节、iTunSMPB），lofty 不提供这些帧
+}
+
+impl FileTags {
+    pub fn read(path: &Path) -> Self {
+        let tagged_file = match Probe::open(path).and_then(|probe| probe.read()) {
+            Ok(tagged_file) => Some(tagged_file),
+            Err(e) => {
+                println!("lofty 读取标签失败: {}", e);
+                None
+            }
+        };
+        // 没有 ID3 标签的文件只读取文件头即返回
+        let id3 = Tag::read_from_path(path).ok();
+        Self { tagged_file, id3 }
+    }
 }
 
 impl SongInfo {
-    /// 从文件路径创建歌曲信息
+    /// 从文件路径创建歌曲信息，用户选定过元数据时使用选定的值
     pub fn from_path(path: &Path) -> Result<Self> {
-        let _path_str = path.to_string_lossy().into_owned();
+        Self::from_tags(path, &FileTags::read(path))
+    }
+
+    /// 用已读入的标签创建歌曲信息（调用方还需要标签中的其他内容时使用，如内嵌 CUE）
+    pub fn from_tags(path: &Path, tags: &FileTags) -> Result<Self> {
+        let mut song_info = Self::extract_from_path(path, tags)?;
+        song_info.apply_folder_cover();
+        let extras = library::with_library(|lib| lib.track_extras(&song_info.path)).unwrap_or_else(|e| {
+            eprintln!("读取音乐库中的曲目数据失败: {}", e);
+            TrackExtras::default()
+        });
+        if song_info.media_type == Some(MediaType::Audio) {
+            if let Some(metadata) = &extras.metadata {
+                song_info.apply_metadata(metadata);
+            }
+        }
+        if let Some((rating, favorite)) = extras.rating {
+            song_info.rating = rating;
+            song_info.favorite = favorite;
+        }
+        song_info.trim = extras.trim;
+        if let Some(lyrics) = song_info.lyrics.as_mut() {
+            Self::shift_lyrics(lyrics, extras.lyrics_offset_ms);
+        }
+        song_info.replay_gain = tags
+            .tagged_file
+            .as_ref()
+            .and_then(replaygain::read_tags)
+            .or_else(|| extras.loudness.map(replaygain::analyzed_tags));
+        song_info.chapters = chapters::read(path, tags.id3.as_ref());
+        song_info.apply_resume(extras.resume);
+        Ok(song_info)
+    }
+
+    /// 为串流URL创建歌曲信息，标题取URL最后一段
+    pub fn from_url(url: &str) -> Self {
+        let name = url
+            .split(['?', '#'])
+            .next()
+            .and_then(|u| u.trim_end_matches('/').rsplit('/').next())
+            .filter(|n| !n.is_empty() && !n.contains(':'))
+            .unwrap_or(url);
+        SongInfo {
+            path: url.to_string(),
+            title: Some(name.to_string()),
+            artist: None,
+            album: None,
+            album_cover: Self::get_default_album_cover(),
+            duration: None,
+            lyrics: None,
+            media_type: Some(MediaType::Audio),
+            mv_path: None,
+            video_thumbnail: None,
+            has_lyrics: Some(false),
+            track_number: None,
+            disc_number: None,
+            gapless: None,
+            color: None,
+            group: None,
+            segment: None,
+            rating: None,
+            favorite: false,
+            replay_gain: None,
+            resume_position: None,
+            resume_enabled: false,
+            chapters: Vec::new(),
+            video_info: None,
+            trim: None,
+            cover_source: None,
+        }
+    }
+
+    /// 读取音乐库中的续播设置和上次播放到的位置；串流URL中只有播客节目记住位置
+    /// 用户设置优先，没有设置时长曲目和播客节目默认开启
+    pub fn apply_resume_point(&mut self) {
+        if !self.resume_supported() {
+            return;
+        }
+        let point = match library::with_library(|lib| lib.resume_point(&self.path)) {
+            Ok(point) => point,
+            Err(e) => {
+                eprintln!("读取续播位置失败: {}", e);
+                None
+            }
+        };
+        self.apply_resume(point);
+    }
+
+    /// 本地文件和播客节目可以续播，其他串流URL不记住位置
+    fn resume_supported(&self) -> bool {
+        !crate::media_source::is_url(&self.path) || crate::podcast::is_episode(&self.path)
+    }
+
+    /// 按音乐库中的续播设置决定是否续播及起始位置
+    fn apply_resume(&mut self, point: Option<ResumePoint>) {
+        if !self.resume_supported() {
+            return;
+        }
+        let is_episode = crate::podcast::is_episode(&self.path);
+        self.resume_enabled = point.and_then(|point| point.enabled).unwrap_or_else(|| {
+            is_episode || self.duration.map_or(false, |duration| duration >= RESUME_MIN_DURATION_SECS)
+        });
+        self.resume_position = point.and_then(|point| point.position_ms).filter(|_| self.resume_enabled);
+    }
+
+    /// 用选定的元数据覆盖对应字段
+    pub fn apply_metadata(&mut self, metadata: &MetadataOverride) {
+        if metadata.title.is_some() {
+            self.title = metadata.title.clone();
+        }
+        if metadata.artist.is_some() {
+            self.artist = metadata.artist.clone();
+        }
+        if metadata.album.is_some() {
+            self.album = metadata.album.clone();
+        }
+        if metadata.track_number.is_some() {
+            self.track_number = metadata.track_number;
+        }
+        if metadata.disc_number.is_some() {
+            self.disc_number = metadata.disc_number;
+        }
+    }
+
+    /// 文件中的每个标签（如 MP3 同时带有的 ID3v2、ID3v1、APE 标签）和文件名分别作为一个来源，列出各来源的结果及冲突字段
+    pub fn metadata_candidates(path: &Path) -> MetadataCandidates {
+        let tags = FileTags::read(path);
+        let mut candidates: Vec<MetadataCandidate> = tags
+            .tagged_file
+            .iter()
+            .flat_map(|tagged_file| tagged_file.tags())
+            .map(|tag| MetadataCandidate {
+                source: format!("{:?}", tag.tag_type()),
+                title: tag.title().map(|s| s.to_string()),
+                artist: tag.artist().map(|s| s.to_string()),
+                album: tag.album().map(|s| s.to_string()),
+                track_number: tag.track(),
+                disc_number: tag.disk(),
+            })
+            .collect();
+        // 文件名形如 "艺术家 - 标题" 时拆分
+        let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let (artist, title) = match stem.split_once(" - ") {
+            Some((artist, title)) => (Some(artist.trim().to_string()), title.trim().to_string()),
+            None => (None, stem.clone()),
+        };
+        candidates.push(MetadataCandidate {
+            source: "filename".to_string(),
+            title: Some(title),
+            artist,
+            album: None,
+            track_number: None,
+            disc_number: None,
+        });
+
+        let tagged = &candidates[..candidates.len() - 1];
+        let conflicting = |values: Vec<String>| {
+            let mut distinct: Vec<String> = values.into_iter().filter(|v| !v.trim().is_empty()).collect();
+            distinct.sort();
+            distinct.dedup();
+            distinct.len() > 1
+        };
+        let mut conflicts = Vec::new();
+        if conflicting(tagged.iter().filter_map(|c| c.title.clone()).collect()) {
+            conflicts.push("title".to_string());
+        }
+        if conflicting(tagged.iter().filter_map(|c| c.artist.clone()).collect()) {
+            conflicts.push("artist".to_string());
+        }
+        if conflicting(tagged.iter().filter_map(|c| c.album.clone()).collect()) {
+            conflicts.push("album".to_string());
+        }
+        if conflicting(tagged.iter().filter_map(|c| c.track_number.map(|n| n.to_string())).collect()) {
+            conflicts.push("trackNumber".to_string());
+        }
+        if conflicting(tagged.iter().filter_map(|c| c.disc_number.map(|n| n.to_string())).collect()) {
+            conflicts.push("discNumber".to_string());
+        }
+
+        let path_str = path.to_string_lossy().into_owned();
+        let chosen = library::with_library(|lib| lib.metadata_override(&path_str)).unwrap_or_else(|e| {
+            eprintln!("读取选定的元数据失败: {}", e);
+            None
+        });
+        MetadataCandidates {
+            path: path_str,
+            candidates,
+            conflicts,
+            chosen,
+        }
+    }
+
+    /// 从文件读取歌曲信息（按设置的提取顺序依次尝试，使用第一个成功的结果，均失败时使用文件名）
+    fn extract_from_path(path: &Path, tags: &FileTags) -> Result<Self> {
         println!("正在解析媒体文件: {}", path.display());
         
         // 检查文件扩展名确定媒体类型
@@ -102,65 +489,92 @@ impl SongInfo {
             .unwrap_or("")
             .to_lowercase();
         
-        let media_type = if Self::is_video_format(&ext) {
-            Some(MediaType::Video)
-        } else if Self::is_audio_format(&ext) {
-            Some(MediaType::Audio)
-        } else {
-            None
-        };
+        let media_type = Self::media_type_for(&ext);
         
         // 对于视频文件，使用特殊处理
         if media_type == Some(MediaType::Video) {
             return Self::create_video_song_info(path);
         }
         
-        // 使用lofty库
-        if let Some(mut song_info) = Self::try_lofty_extraction(path) {
-            println!("✅ 使用 lofty 库成功提取元数据");
-            song_info.media_type = media_type;
-            song_info.has_lyrics = Some(song_info.lyrics.is_some());
-            // 尝试加载歌词
-            song_info.lyrics = Self::load_lyrics(path);
-            // 查找对应的MV文件
-            song_info.find_associated_mv();
-            return Ok(song_info);
-        }
-        
-        // 使用audiotags库
-        if let Some(mut song_info) = Self::try_audiotags_extraction(path) {
-            println!("✅ 使用 audiotags 库成功提取元数据");
-            song_info.media_type = media_type;
-            song_info.has_lyrics = Some(song_info.lyrics.is_some());
-            // 尝试加载歌词
-            song_info.lyrics = Self::load_lyrics(path);
-            // 查找对应的MV文件
-            song_info.find_associated_mv();
-            return Ok(song_info);
-        }
-        
-        // 使用格式特定的方法（原有的 ID3/FLAC/OGG 方法）
-        if let Some(mut song_info) = Self::try_format_specific_extraction(path) {
-            println!("✅ 使用格式特定方法成功提取元数据");
-            song_info.media_type = media_type;
-            song_info.has_lyrics = Some(song_info.lyrics.is_some());
-            // 尝试加载歌词
-            song_info.lyrics = Self::load_lyrics(path);
-            // 查找对应的MV文件
-            song_info.find_associated_mv();
-            return Ok(song_info);
+        for strategy in metadata_priority::order_for(&ext) {
+            if let Some(song_info) = Self::run_strategy(strategy, path, tags) {
+                println!("✅ 使用 {} 成功提取元数据", strategy.label());
+                return Ok(song_info.finish_extraction(path, media_type, tags));
+            }
         }
         
         // 使用文件名作为标题
         println!("⚠️  所有元数据提取方法都失败，使用兜底方案");
-        let mut song_info = Self::create_fallback_song_info(path);
-        song_info.media_type = media_type;
-        song_info.has_lyrics = Some(song_info.lyrics.is_some());
+        Ok(Self::create_fallback_song_info(path).finish_extraction(path, media_type, tags))
+    }
+
+    fn media_type_for(ext: &str) -> Option<MediaType> {
+        if Self::is_video_format(ext) {
+            Some(MediaType::Video)
+        } else if Self::is_audio_format(ext) {
+            Some(MediaType::Audio)
+        } else {
+            None
+        }
+    }
+
+    fn run_strategy(strategy: MetadataStrategy, path: &Path, tags: &FileTags) -> Option<SongInfo> {
+        match strategy {
+            MetadataStrategy::Lofty => Self::try_lofty_extraction(path, tags),
+            MetadataStrategy::Audiotags => Self::try_audiotags_extraction(path),
+            MetadataStrategy::FormatSpecific => Self::try_format_specific_extraction(path, tags),
+        }
+    }
+
+    /// 补全元数据以外的信息：媒体类型、歌词、无缝播放信息和对应的MV
+    fn finish_extraction(mut self, path: &Path, media_type: Option<MediaType>, tags: &FileTags) -> Self {
+        self.media_type = media_type;
+        self.has_lyrics = Some(self.lyrics.is_some());
         // 尝试加载歌词
-        song_info.lyrics = Self::load_lyrics(path);
+        self.lyrics = Self::load_lyrics(path, Some(tags));
+        self.gapless = gapless::analyze(path, tags.id3.as_ref());
         // 查找对应的MV文件
-        song_info.find_associated_mv();
-        Ok(song_info)
+        self.find_associated_mv();
+        self
+    }
+
+    /// 试运行所有提取方式（不加载歌词、不应用用户选定的元数据），列出各方式的结果和实际会采用的方式
+    pub fn debug_extract(path: &Path) -> ExtractionReport {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+        let order = metadata_priority::order_for(&ext);
+        let mut results = Vec::new();
+        let mut selected = None;
+        let all = [MetadataStrategy::Lofty, MetadataStrategy::Audiotags, MetadataStrategy::FormatSpecific];
+        // 各方式取不到封面时都会填入默认封面，需排除
+        let default_cover = Self::get_default_album_cover();
+        let tags = FileTags::read(path);
+        for strategy in order.iter().chain(all.iter().filter(|s| !order.contains(s))) {
+            let started = std::time::Instant::now();
+            let song = Self::run_strategy(*strategy, path, &tags);
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+            let in_order = order.contains(strategy);
+            if selected.is_none() && in_order && song.is_some() {
+                selected = Some(strategy.label().to_string());
+            }
+            results.push(StrategyResult {
+                strategy: strategy.label().to_string(),
+                in_order,
+                succeeded: song.is_some(),
+                elapsed_ms,
+                duration: song.as_ref().and_then(|s| s.duration),
+                has_cover: song.as_ref().map(|s| s.album_cover.is_some() && s.album_cover != default_cover).unwrap_or(false),
+                metadata: song.as_ref().map(|s| MetadataCandidate::from_song(strategy.label(), s)),
+            });
+        }
+        let fallback = Self::create_fallback_song_info(path);
+        ExtractionReport {
+            path: path.to_string_lossy().into_owned(),
+            format: ext,
+            order: order.iter().map(|s| s.label().to_string()).collect(),
+            selected: selected.unwrap_or_else(|| "fallback".to_string()),
+            results,
+            fallback: MetadataCandidate::from_song("fallback", &fallback),
+        }
     }
 
     /// 查找对应的MV文件
@@ -202,6 +616,53 @@ impl SongInfo {
         }
     }
 
+    /// 没有内嵌封面时使用同目录下的封面图片（cover.jpg、folder.jpg、front.png 等），并记录封面来源
+    fn apply_folder_cover(&mut self) {
+        if self.media_type != Some(MediaType::Audio) {
+            return;
+        }
+        if self.has_embedded_cover() {
+            self.cover_source = Some(EMBEDDED_COVER.to_string());
+            return;
+        }
+        let Some(dir) = Path::new(&self.path).parent() else { return };
+        let Some(image_path) = Self::find_folder_cover(dir) else { return };
+        match Self::folder_cover_data_url(&image_path) {
+            Ok(data_url) => {
+                println!("使用文件夹封面: {}", image_path.display());
+                self.album_cover = Some(data_url);
+                self.cover_source = Some(image_path.to_string_lossy().into_owned());
+            }
+            Err(e) => eprintln!("读取文件夹封面失败 {}: {}", image_path.display(), e),
+        }
+    }
+
+    /// 文件夹封面图片缩放后的 data URL（与内嵌封面同样经过封面缓存）
+    fn folder_cover_data_url(image_path: &Path) -> Result<String> {
+        let base64_string = Self::convert_image_to_base64(&std::fs::read(image_path)?)?;
+        Ok(format!("data:image/jpeg;base64,{}", base64_string))
+    }
+
+    /// 按常见命名的优先顺序查找文件夹封面，文件名不区分大小写
+    pub fn find_folder_cover(dir: &Path) -> Option<std::path::PathBuf> {
+        const NAMES: [&str; 6] = ["cover", "folder", "front", "album", "albumart", "albumartlarge"];
+        const EXTENSIONS: [&str; 3] = ["jpg", "jpeg", "png"];
+        let images: Vec<(String, std::path::PathBuf)> = std::fs::read_dir(dir)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|p| p.is_file())
+            .filter_map(|p| {
+                let stem = p.file_stem()?.to_str()?.to_lowercase();
+                let ext = p.extension()?.to_str()?.to_lowercase();
+                EXTENSIONS.contains(&ext.as_str()).then_some((stem, p))
+            })
+            .collect();
+        NAMES
+            .iter()
+            .find_map(|name| images.iter().find(|(stem, _)| stem == name).map(|(_, p)| p.clone()))
+    }
+
     /// 检查是否有关联的MV
     pub fn has_mv(&self) -> bool {
         self.mv_path.is_some()
@@ -230,13 +691,13 @@ impl SongInfo {
     }
 
     /// 检查是否为视频格式
-    fn is_video_format(ext: &str) -> bool {
+    pub fn is_video_format(ext: &str) -> bool {
         matches!(ext, "mp4" | "mkv" | "avi" | "mov" | "wmv" | "flv" | "webm" | "m4v")
     }
 
     /// 检查是否为音频格式
-    fn is_audio_format(ext: &str) -> bool {
-        matches!(ext, "mp3" | "flac" | "wav" | "ogg" | "m4a" | "aac" | "wma")
+    pub fn is_audio_format(ext: &str) -> bool {
+        matches!(ext, "mp3" | "flac" | "wav" | "ogg" | "m4a" | "aac" | "wma" | "mka" | "mid" | "midi" | "kar")
     }
 
     /// 创建视频文件信息
@@ -249,14 +710,15 @@ impl SongInfo {
             .and_then(|s| s.to_str())
             .map(|s| s.to_string());
         
-        // 对于视频文件，不估算时长，让前端VideoPlayer来提供真实时长
-        let duration = None;
+        // 导入时读取时长、分辨率和编码，读取失败时由前端VideoPlayer提供真实时长
+        let video_info = video_probe::probe(path);
+        let duration = video_info.as_ref().and_then(|info| info.duration_ms).map(|ms| ms / 1000);
         
         // 尝试生成视频缩略图
         let video_thumbnail = Self::generate_video_thumbnail(path);
         
         // 检查是否有对应的歌词文件
-        let lyrics = Self::load_lyrics(path);
+        let lyrics = Self::load_lyrics(path, None);
         
         Ok(SongInfo {
             path: path_str.clone(),
@@ -264,12 +726,27 @@ impl SongInfo {
             artist: None, // 视频文件通常没有艺术家信息
             album: None,  // 视频文件通常没有专辑信息
             album_cover: video_thumbnail.clone(), // 使用视频缩略图作为封面
-            duration, // 设置为None，由前端提供真实时长
+            duration,
             lyrics: lyrics.clone(),
             media_type: Some(MediaType::Video),
             mv_path: Some(path_str), // MV路径就是文件本身的路径
             video_thumbnail,
             has_lyrics: Some(lyrics.is_some()),
+            track_number: None,
+            disc_number: None,
+            gapless: None,
+            color: None,
+            group: None,
+            segment: None,
+            rating: None,
+            favorite: false,
+            replay_gain: None,
+            resume_position: None,
+            resume_enabled: false,
+            chapters: Vec::new(),
+            video_info,
+            trim: None,
+            cover_source: None,
         })
     }
 
@@ -326,67 +803,127 @@ impl SongInfo {
     }
 
     /// 加载歌词文件
-    fn load_lyrics(audio_path: &Path) -> Option<Vec<LyricLine>> {
+    /// 查找顺序：同名 .lrc 文件、标签内嵌的同步歌词、同名 .txt 文件、标签内嵌的非同步歌词、MIDI/KAR 卡拉OK歌词
+    fn load_lyrics(audio_path: &Path, tags: Option<&FileTags>) -> Option<Vec<LyricLine>> {
         let audio_dir = audio_path.parent()?;
         let audio_stem = audio_path.file_stem()?.to_str()?;
-        
-        // 可能的歌词文件扩展名
-        let lyric_extensions = ["lrc", "txt"];
-        
-        for ext in &lyric_extensions {
-            let lyric_path = audio_dir.join(format!("{}.{}", audio_stem, ext));
-            
-            if lyric_path.exists() {
-                println!("找到歌词文件: {}", lyric_path.display());
-                
-                match ext {
-                    &"lrc" => {
-                        if let Some(lyrics) = Self::parse_lrc_file(&lyric_path) {
-                            return Some(lyrics);
-                        }
-                    }
-                    &"txt" => {
-                        if let Some(lyrics) = Self::parse_txt_file(&lyric_path) {
-                            return Some(lyrics);
-                        }
-                    }
-                    _ => {}
-                }
+
+        let lrc_path = audio_dir.join(format!("{}.lrc", audio_stem));
+        if lrc_path.exists() {
+            println!("找到歌词文件: {}", lrc_path.display());
+            if let Some(lyrics) = Self::parse_lrc_file(&lrc_path) {
+                return Some(lyrics);
             }
         }
-        
+
+        let (synced, unsynced) = tags.map(Self::load_embedded_lyrics).unwrap_or_default();
+        if synced.is_some() {
+            println!("使用内嵌的同步歌词: {}", audio_stem);
+            return synced;
+        }
+
+        let txt_path = audio_dir.join(format!("{}.txt", audio_stem));
+        if txt_path.exists() {
+            println!("找到歌词文件: {}", txt_path.display());
+            if let Some(lyrics) = Self::parse_txt_file(&txt_path) {
+                return Some(lyrics);
+            }
+        }
+
+        if unsynced.is_some() {
+            println!("使用内嵌的歌词: {}", audio_stem);
+            return unsynced;
+        }
+
+        // MIDI/KAR 文件内嵌的卡拉OK歌词
+        let ext = audio_path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+        if crate::midi::is_midi(&ext) {
+            return crate::midi::karaoke_lyrics(audio_path);
+        }
+
         println!("未找到歌词文件: {}", audio_stem);
         None
     }
 
+    /// 读取标签内嵌的歌词，返回 (同步歌词, 非同步歌词)
+    /// 同步歌词来自 ID3 的 SYLT 帧，或内容为 LRC 格式的歌词标签（USLT / Vorbis LYRICS / MP4 ©lyr）
+    fn load_embedded_lyrics(tags: &FileTags) -> (Option<Vec<LyricLine>>, Option<Vec<LyricLine>>) {
+        let mut synced = tags.id3.as_ref().and_then(Self::read_sylt);
+        let mut unsynced = None;
+        let text = tags.tagged_file.as_ref().and_then(|tagged_file| {
+            tagged_file
+                .tags()
+                .iter()
+                .find_map(|tag| tag.get_string(&ItemKey::Lyrics).map(str::to_string))
+        });
+        if let Some(text) = text.filter(|t| !t.trim().is_empty()) {
+            match Self::parse_lrc_text(&text) {
+                Some(lyrics) => synced = synced.or(Some(lyrics)),
+                None => unsynced = Self::parse_txt_text(&text),
+            }
+        }
+        (synced, unsynced)
+    }
+
+    /// ID3 SYLT 同步歌词帧（只支持以毫秒为时间单位的帧），多个帧时取内容最多的
+    fn read_sylt(tag: &Tag) -> Option<Vec<LyricLine>> {
+        let frame = tag
+            .synchronised_lyrics()
+            .filter(|sylt| sylt.timestamp_format == id3::frame::TimestampFormat::Ms)
+            .max_by_key(|sylt| sylt.content.len())?;
+        let mut lyrics: Vec<LyricLine> = frame
+            .content
+            .iter()
+            .map(|(time, text)| LyricLine {
+                time: *time as u64,
+                // SYLT 的每一项可能以换行开头表示新的一行
+                text: text.trim().to_string(),
+                words: None,
+            })
+            .filter(|line| !line.text.is_empty())
+            .collect();
+        lyrics.sort_by_key(|line| line.time);
+        (!lyrics.is_empty()).then_some(lyrics)
+    }
+
     /// 解析LRC格式歌词文件
     fn parse_lrc_file(lrc_path: &Path) -> Option<Vec<LyricLine>> {
         // 尝试多种编码方式读取文件
         let content = Self::read_file_with_encoding(lrc_path)?;
-        
+        Self::parse_lrc_text(&content)
+    }
+
+    /// 解析LRC格式歌词文本，支持一行多个时间标签、[offset:] 偏移，
+    /// 以及增强型LRC（A2扩展）的逐字时间标签 <mm:ss.xx>
+    fn parse_lrc_text(content: &str) -> Option<Vec<LyricLine>> {
         let mut lyrics = Vec::new();
-        
+        let mut offset_ms = 0i64;
+
         for line_content in content.lines() {
-            let line_content = line_content.trim();
-            
-            // 跳过空行和标签行（如[ar:], [ti:], [al:]等）
-            if line_content.is_empty() || 
-               (line_content.starts_with('[') && 
-                (line_content.contains("ar:") || line_content.contains("ti:") || 
-                 line_content.contains("al:") || line_content.contains("by:") ||
-                 line_content.contains("offset:"))) {
+            let line_content = line_content.trim().trim_start_matches('\u{feff}');
+            if line_content.is_empty() {
                 continue;
             }
-            
-            // 解析时间标签格式：[mm:ss.xx]歌词内容
-            if let Some(lyric_line) = Self::parse_lrc_line(line_content) {
-                lyrics.push(lyric_line);
+            let (times, body) = Self::split_lrc_tags(line_content, &mut offset_ms);
+            if times.is_empty() {
+                // 标签行（如[ar:], [ti:], [al:], [by:], [offset:], [ve:]等）或非LRC内容
+                continue;
+            }
+            let (text, words) = Self::parse_lrc_words(body);
+            for time in times {
+                lyrics.push(LyricLine {
+                    time,
+                    text: text.clone(),
+                    words: words.clone(),
+                });
             }
         }
-        
+
+        Self::shift_lyrics(&mut lyrics, offset_ms);
+
         // 按时间排序
         lyrics.sort_by_key(|line| line.time);
-        
+
         if lyrics.is_empty() {
             None
         } else {
@@ -395,54 +932,150 @@ impl SongInfo {
         }
     }
 
-    /// 解析单行LRC歌词
-    fn parse_lrc_line(line: &str) -> Option<LyricLine> {
-        // 正则表达式匹配 [mm:ss.xx] 格式
-        if !line.starts_with('[') {
-            return None;
+    /// 把歌词整理为标准LRC文本：带标题/艺术家/专辑标签，按时间排序，有逐字时间的行写成增强型LRC
+    pub fn format_lrc(&self, lines: &[LyricLine]) -> String {
+        let time = |ms: u64| format!("{:02}:{:02}.{:02}", ms / 60_000, ms / 1000 % 60, ms % 1000 / 10);
+        let mut out = String::new();
+        for (key, value) in [("ti", &self.title), ("ar", &self.artist), ("al", &self.album)] {
+            if let Some(value) = value.as_deref().filter(|v| !v.is_empty()) {
+                out.push_str(&format!("[{}:{}]\n", key, value));
+            }
         }
-        
-        let end_bracket = line.find(']')?;
-        let time_str = &line[1..end_bracket];
-        let text = line[end_bracket + 1..].trim().to_string();
-        
-        // 解析时间 mm:ss.xx
-        let parts: Vec<&str> = time_str.split(':').collect();
-        if parts.len() != 2 {
-            return None;
+        if let Some(duration) = self.duration {
+            out.push_str(&format!("[length:{:02}:{:02}]\n", duration / 60, duration % 60));
         }
-        
-        let minutes: u64 = parts[0].parse().ok()?;
-        let seconds_parts: Vec<&str> = parts[1].split('.').collect();
-        
-        let seconds: u64 = seconds_parts[0].parse().ok()?;
-        let milliseconds: u64 = if seconds_parts.len() > 1 {
-            // 处理毫秒部分，确保是两位数
-            let ms_str = seconds_parts[1];
-            let ms_str = if ms_str.len() == 1 {
-                format!("{}0", ms_str)
-            } else if ms_str.len() > 2 {
-                ms_str[..2].to_string()
-            } else {
-                ms_str.to_string()
+        let mut sorted: Vec<&LyricLine> = lines.iter().collect();
+        sorted.sort_by_key(|line| line.time);
+        for line in sorted {
+            out.push_str(&format!("[{}]", time(line.time)));
+            match line.words.as_deref().filter(|w| !w.is_empty()) {
+                Some(words) => {
+                    for word in words {
+                        out.push_str(&format!("<{}>{}", time(word.time), word.text));
+                    }
+                    if let Some(end) = words.last().and_then(|w| w.end) {
+                        out.push_str(&format!("<{}>", time(end)));
+                    }
+                }
+                None => out.push_str(&line.text),
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// 按偏移调整歌词时间，offset 为正表示歌词提前显示（与LRC的 [offset:] 含义相同）
+    fn shift_lyrics(lyrics: &mut [LyricLine], offset_ms: i64) {
+        if offset_ms == 0 {
+            return;
+        }
+        let shift = |time: u64| (time as i64 - offset_ms).max(0) as u64;
+        for line in lyrics {
+            line.time = shift(line.time);
+            for word in line.words.iter_mut().flatten() {
+                word.time = shift(word.time);
+                word.end = word.end.map(shift);
+            }
+        }
+    }
+
+    /// 拆出行首的方括号标签：返回行时间标签（毫秒）和其余文本；遇到 [offset:] 时更新偏移
+    fn split_lrc_tags<'a>(line: &'a str, offset_ms: &mut i64) -> (Vec<u64>, &'a str) {
+        let mut times = Vec::new();
+        let mut rest = line;
+        while let Some(tag_end) = rest.strip_prefix('[').and_then(|r| r.find(']')) {
+            let tag = &rest[1..tag_end + 1];
+            match Self::parse_lrc_time(tag) {
+                Some(time) => times.push(time),
+                None => {
+                    if let Some((key, value)) = tag.split_once(':') {
+                        if key.trim().eq_ignore_ascii_case("offset") {
+                            *offset_ms = value.trim().parse().unwrap_or(0);
+                        }
+                    }
+                }
+            }
+            rest = &rest[tag_end + 2..];
+        }
+        (times, rest)
+    }
+
+    /// 解析增强型LRC的逐字时间：<mm:ss.xx>字<mm:ss.xx>字<mm:ss.xx>
+    /// 每个字到下一个时间标签为止，最后一个标签只表示结束时间；没有逐字标签时返回 None
+    fn parse_lrc_words(body: &str) -> (String, Option<Vec<LyricWord>>) {
+        let mut words: Vec<LyricWord> = Vec::new();
+        let mut leading = String::new();
+        let mut rest = body;
+        while let Some(start) = rest.find('<') {
+            let time = rest[start + 1..]
+                .find('>')
+                .and_then(|end| Self::parse_lrc_time(&rest[start + 1..start + 1 + end]).map(|t| (t, start + end + 2)));
+            let Some((time, next)) = time else {
+                // 不是时间标签的 '<' 按普通文本处理
+                let text = &rest[..start + 1];
+                match words.last_mut() {
+                    Some(word) => word.text.push_str(text),
+                    None => leading.push_str(text),
+                }
+                rest = &rest[start + 1..];
+                continue;
             };
-            ms_str.parse().unwrap_or(0) * 10 // 转换为毫秒
-        } else {
-            0
+            let text = &rest[..start];
+            match words.last_mut() {
+                Some(word) => word.text.push_str(text),
+                None => leading.push_str(text),
+            }
+            if let Some(word) = words.last_mut() {
+                word.end = Some(time);
+            }
+            words.push(LyricWord {
+                time,
+                text: String::new(),
+                end: None,
+            });
+            rest = &rest[next..];
+        }
+        match words.last_mut() {
+            Some(word) => word.text.push_str(rest),
+            None => return (body.trim().to_string(), None),
+        }
+        // 末尾只有结束时间、没有文字的标签不算一个字
+        if words.last().is_some_and(|w| w.text.is_empty()) {
+            words.pop();
+        }
+        let text = leading + &words.iter().map(|w| w.text.as_str()).collect::<String>();
+        (text.trim().to_string(), (!words.is_empty()).then_some(words))
+    }
+
+    /// 解析LRC时间 mm:ss、mm:ss.xx 或 mm:ss.xxx（也接受 mm:ss:xx），返回毫秒
+    fn parse_lrc_time(value: &str) -> Option<u64> {
+        let (minutes, rest) = value.trim().split_once(':')?;
+        let minutes: u64 = minutes.parse().ok()?;
+        let (seconds, fraction) = match rest.split_once(['.', ':']) {
+            Some((seconds, fraction)) => (seconds, fraction),
+            None => (rest, ""),
         };
-        
-        let total_milliseconds = minutes * 60 * 1000 + seconds * 1000 + milliseconds;
-        
-        Some(LyricLine {
-            time: total_milliseconds,
-            text,
-        })
+        let seconds: u64 = seconds.parse().ok()?;
+        if seconds >= 60 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        // 小数部分按位数换算：.5 为500毫秒，.25 为250毫秒，.125 为125毫秒
+        let fraction: String = fraction.chars().take(3).collect();
+        let milliseconds = match fraction.len() {
+            0 => 0,
+            len => fraction.parse::<u64>().ok()? * 10u64.pow(3 - len as u32),
+        };
+        Some(minutes * 60 * 1000 + seconds * 1000 + milliseconds)
     }
 
     /// 解析普通文本格式歌词文件
     fn parse_txt_file(txt_path: &Path) -> Option<Vec<LyricLine>> {
         let content = Self::read_file_with_encoding(txt_path)?;
-        
+        Self::parse_txt_text(&content)
+    }
+
+    /// 解析普通文本歌词，按每行3秒估算时间
+    fn parse_txt_text(content: &str) -> Option<Vec<LyricLine>> {
         let mut lyrics = Vec::new();
         let mut time_offset = 0u64;
         
@@ -453,6 +1086,7 @@ impl SongInfo {
                 lyrics.push(LyricLine {
                     time: time_offset,
                     text: line_content.to_string(),
+                    words: None,
                 });
                 
                 // 每行间隔3秒（估算）
@@ -468,7 +1102,7 @@ impl SongInfo {
     }
 
     /// 使用多种编码方式读取文件内容
-    fn read_file_with_encoding(file_path: &Path) -> Option<String> {
+    pub fn read_file_with_encoding(file_path: &Path) -> Option<String> {
         // 首先尝试UTF-8编码
         if let Ok(content) = std::fs::read_to_string(file_path) {
             // 检查是否包含无效字符（乱码的迹象）
@@ -505,9 +1139,9 @@ impl SongInfo {
     }
 
     //使用lofty库提取元数据和封面
-    fn try_lofty_extraction(path: &Path) -> Option<SongInfo> {
-        match Probe::open(path).and_then(|probe| probe.read()) {
-            Ok(tagged_file) => {
+    fn try_lofty_extraction(path: &Path, tags: &FileTags) -> Option<SongInfo> {
+        match &tags.tagged_file {
+            Some(tagged_file) => {
                 let path_str = path.to_string_lossy().into_owned();
                 let tag = tagged_file.primary_tag()?;
                 
@@ -515,9 +1149,11 @@ impl SongInfo {
                 let title = tag.title().map(|s| s.to_string());
                 let artist = tag.artist().map(|s| s.to_string());
                 let album = tag.album().map(|s| s.to_string());
+                let track_number = tag.track();
+                let disc_number = tag.disk();
                 
                 // 提取封面
-                let album_cover = Self::extract_cover_from_lofty(&tagged_file)
+                let album_cover = Self::extract_cover_from_lofty(tagged_file)
                     .or_else(|| Self::get_default_album_cover());
                 
                 // 提取时长
@@ -539,10 +1175,25 @@ impl SongInfo {
                     mv_path: None,
                     video_thumbnail: None,
                     has_lyrics: None,
+                    track_number,
+                    disc_number,
+                    gapless: None,
+                    color: None,
+                    group: None,
+                    segment: None,
+                    rating: None,
+                    favorite: false,
+                    replay_gain: None,
+                    resume_position: None,
+                    resume_enabled: false,
+                    chapters: Vec::new(),
+                    video_info: None,
+                    trim: None,
+                    cover_source: None,
                 })
             }
-            Err(e) => {
-                println!("lofty 提取失败: {}", e);
+            None => {
+                println!("lofty 提取失败: 无法读取标签");
                 None
             }
         }
@@ -558,6 +1209,8 @@ impl SongInfo {
                 let title = tag.title().map(|s| s.to_string());
                 let artist = tag.artist().map(|s| s.to_string());
                 let album = tag.album_title().map(|s| s.to_string());
+                let track_number = tag.track_number().map(|n| n as u32);
+                let disc_number = tag.disc_number().map(|n| n as u32);
                 
                 // 提取封面
                 let album_cover = if let Some(artwork) = tag.album_cover() {
@@ -600,6 +1253,21 @@ impl SongInfo {
                     mv_path: None,
                     video_thumbnail: None,
                     has_lyrics: None,
+                    track_number,
+                    disc_number,
+                    gapless: None,
+                    color: None,
+                    group: None,
+                    segment: None,
+                    rating: None,
+                    favorite: false,
+                    replay_gain: None,
+                    resume_position: None,
+                    resume_enabled: false,
+                    chapters: Vec::new(),
+                    video_info: None,
+                    trim: None,
+                    cover_source: None,
                 })
             }
             Err(e) => {
@@ -610,11 +1278,11 @@ impl SongInfo {
     }
 
     //使用格式特定的方法
-    fn try_format_specific_extraction(path: &Path) -> Option<SongInfo> {
-        match Tag::read_from_path(path) {
-            Ok(tag) => {
+    fn try_format_specific_extraction(path: &Path, tags: &FileTags) -> Option<SongInfo> {
+        match &tags.id3 {
+            Some(tag) => {
                 // 提取专辑封面
-                let album_cover = Self::extract_album_cover(&tag);
+                let album_cover = Self::extract_album_cover(tag);
                 
                 // 尝试从ID3标签获取时长
                 let duration = tag.duration().map(|d| d as u64);
@@ -634,10 +1302,25 @@ impl SongInfo {
                     mv_path: None,
                     video_thumbnail: None,
                     has_lyrics: None,
+                    track_number: tag.track(),
+                    disc_number: tag.disc(),
+                    gapless: None,
+                    color: None,
+                    group: None,
+                    segment: None,
+                    rating: None,
+                    favorite: false,
+                    replay_gain: None,
+                    resume_position: None,
+                    resume_enabled: false,
+                    chapters: Vec::new(),
+                    video_info: None,
+                    trim: None,
+                    cover_source: None,
                 })
             }
-            Err(e) => {
-                println!("格式特定方法提取失败: {}", e);
+            None => {
+                println!("格式特定方法提取失败: 没有 ID3 标签");
                 None
             }
         }
@@ -668,6 +1351,21 @@ impl SongInfo {
             mv_path: None,
             video_thumbnail: None,
             has_lyrics: None,
+            track_number: None,
+            disc_number: None,
+            gapless: None,
+            color: None,
+            group: None,
+            segment: None,
+            rating: None,
+            favorite: false,
+            replay_gain: None,
+            resume_position: None,
+            resume_enabled: false,
+            chapters: Vec::new(),
+            video_info: None,
+            trim: None,
+            cover_source: None,
         }
     }
 
@@ -717,6 +1415,72 @@ impl SongInfo {
         }
     }
 
+    /// 是否有文件自身或文件夹中的封面（不是读取失败时填入的默认封面）
+    pub fn has_embedded_cover(&self) -> bool {
+        self.album_cover.is_some() && self.album_cover != *Self::cached_default_cover()
+    }
+
+    /// 默认封面只读取一次
+    fn cached_default_cover() -> &'static Option<String> {
+        static DEFAULT_COVER: std::sync::OnceLock<Option<String>> = std::sync::OnceLock::new();
+        DEFAULT_COVER.get_or_init(Self::get_default_album_cover)
+    }
+
+    /// 尚未读取元数据的占位条目（标题取文件名），先加入播放列表，元数据读取完成后替换
+    pub fn placeholder(path: &Path) -> Self {
+        SongInfo {
+            path: path.to_string_lossy().into_owned(),
+            title: path.file_stem().map(|s| s.to_string_lossy().into_owned()),
+            artist: None,
+            album: None,
+            album_cover: Self::cached_default_cover().clone(),
+            duration: None,
+            lyrics: None,
+            media_type: Some(if Self::is_video_path(path) { MediaType::Video } else { MediaType::Audio }),
+            mv_path: None,
+            video_thumbnail: None,
+            has_lyrics: None,
+            track_number: None,
+            disc_number: None,
+            gapless: None,
+            color: None,
+            group: None,
+            segment: None,
+            rating: None,
+            favorite: false,
+            replay_gain: None,
+            resume_position: None,
+            resume_enabled: false,
+            chapters: Vec::new(),
+            video_info: None,
+            trim: None,
+            cover_source: None,
+        }
+    }
+
+    /// 去掉封面（base64）的副本，发给前端的播放列表不携带封面，由前端按需获取
+    pub fn without_cover(mut self) -> Self {
+        self.album_cover = None;
+        self.video_thumbnail = None;
+        self
+    }
+
+    /// 是否为尚未读取元数据的占位条目
+    pub fn is_placeholder(&self) -> bool {
+        self.duration.is_none()
+            && self.artist.is_none()
+            && self.album.is_none()
+            && self.segment.is_none()
+            && !self.path.contains("://")
+    }
+
+    fn is_video_path(path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| Self::is_video_format(&e.to_lowercase()))
+            .unwrap_or(false)
+    }
+
     /// 获取默认专辑封面
     fn get_default_album_cover() -> Option<String> {
         let possible_paths = [
@@ -772,13 +1536,7 @@ impl SongInfo {
 
     /// 将图片数据转换为Base64字符串
     fn convert_image_to_base64(image_data: &[u8]) -> Result<String> {
-        let img = image::load_from_memory(image_data)?;
-        let resized_img = img.resize(300, 300, image::imageops::FilterType::Lanczos3);
-
-        let mut jpeg_bytes = Vec::new();
-        let mut cursor = Cursor::new(&mut jpeg_bytes);
-        resized_img.write_to(&mut cursor, ImageFormat::Jpeg)?;
-
+        let jpeg_bytes = crate::cover_cache::resized_jpeg(image_data)?;
         let base64_string = base64::engine::general_purpose::STANDARD.encode(&jpeg_bytes);
         Ok(base64_string)
     }
@@ -798,6 +1556,8 @@ impl SongInfo {
             "flac" => Self::get_flac_duration(path),
             "wav" => Self::get_wav_duration(path),
             "m4a" | "aac" => Self::get_aac_duration(path),
+            "mka" => crate::matroska::probe(path).ok().and_then(|info| info.duration_ms).map(|ms| ms / 1000),
+            "mid" | "midi" | "kar" => crate::midi::duration(path),
             _ => None,
         };
         
@@ -896,7 +1656,75 @@ pub enum PlayerEvent {
     StateChanged(PlayerState),
     SongChanged(usize, SongInfo),
     PlaylistUpdated(Vec<SongInfo>),
-    ProgressUpdate { position: u64, duration: u64 },
+    ProgressUpdate {
+        position: u64,
+        duration: u64,
+        #[serde(rename = "abLoop")]
+        ab_loop: Option<AbLoop>, // 当前曲目设置的 A–B 循环区间
+    },
+    // 播放中按设置的间隔（默认 250 毫秒）发送的毫秒级进度，位置不变时不重复发送
+    PositionUpdate {
+        #[serde(rename = "positionMs")]
+        position_ms: u64,
+        #[serde(rename = "durationMs")]
+        duration_ms: u64,
+    },
+    // 播放 CUE 虚拟曲目或章节时，除相对本曲的进度外附带在整个文件中的位置（秒）
+    SegmentProgress {
+        position: u64,
+        duration: u64,
+        #[serde(rename = "filePosition")]
+        file_position: u64,
+        #[serde(rename = "segmentStart")]
+        segment_start: u64,
+    },
+    PlaybackGlitch(PlaybackGlitch), // 解码卡顿/欠载
+    MarkersUpdated { path: String, markers: Vec<Marker> }, // 曲目标记变化
+    IntroSkipSuggested(IntroSkipSuggestion), // 用户多次跳过同一段前奏，建议设置自动起始点
+    StopAfterTriggered, // "播完N首后停止"计数归零，已停止播放
+    SongFinished(usize), // 该索引的歌曲自然播放完毕（用户切歌、停止不算）
+    PlaylistEnded, // 不循环模式下播完（或切过）列表最后一首，已停止播放
+    ClipboardMediaDetected(Vec<ClipboardMedia>), // 剪贴板中复制了音频文件路径或串流URL
+    // 音源采样率与输出设备不一致（reconfigured 表示已按音源采样率重新打开输出）
+    SampleRateMismatch {
+        #[serde(rename = "fileRate")]
+        file_rate: u32,
+        #[serde(rename = "deviceRate")]
+        device_rate: u32,
+        reconfigured: bool,
+    },
+    // 下一首已预读到内存（网络音源为已缓冲到可开始播放），可以无缝切换
+    NextTrackPrefetched {
+        index: usize,
+        path: String,
+    },
+    BufferingStarted { path: String },  // 网络音源缓冲不足，开始等待数据
+    BufferingFinished { path: String }, // 网络音源缓冲完成，继续播放
+    AbCompareChanged(Option<AbCompare>), // A/B 对比开始、切换或结束
+    AbLoopChanged(Option<AbLoop>), // A–B 循环设置、清除，或切到其他曲目后自动取消
+    // 播放状态下输出设备持续无输出或全为静音（reinitialized 表示已自动重新打开输出设备）
+    SilentPlaybackDetected {
+        kind: SilenceKind,
+        seconds: u64,
+        path: String,
+        reinitialized: bool,
+    },
+    AnnouncementPlaying { text: String }, // 电台主持人模式开始播报
+    VolumeChanged { volume: f32, muted: bool }, // 音量或静音状态变化
+    // 输出设备断开或系统默认设备变化，已重新打开输出并从原位置继续（fallback 表示所选设备已不存在，改用系统默认设备）
+    DeviceChanged {
+        previous: Option<String>,
+        device: String,
+        fallback: bool,
+    },
+    SongMetadataUpdated(usize, SongInfo), // 占位条目的元数据已读取完成
+    // 监听到音乐库文件夹变化，已增量更新音乐库（路径列表）
+    LibraryChanged {
+        added: Vec<String>,
+        updated: Vec<String>,
+        removed: Vec<String>,
+    },
+    SettingsChanged { key: String, value: serde_json::Value }, // 设置项已修改并保存（键名同 get_setting）
     Error(String),
 }
 
@@ -909,16 +1737,62 @@ pub enum PlayerCommand {
     Next,
     Previous,
     SetSong(usize),
+    SetSongAt { index: usize, position_ms: u64 }, // 切换到指定歌曲并从指定位置开始播放（不使用续播位置）
+    // 网络音源已在后台缓冲到可以开始播放（内部使用）：仍停在这首歌、还没有开始播放时从 position_ms（为空时按续播位置）开始
+    StreamBuffered { index: usize, path: String, position_ms: Option<u64> },
     AddSong(SongInfo),
     AddSongs(Vec<SongInfo>),
+    InsertSongs { index: usize, songs: Vec<SongInfo> }, // 在指定位置插入歌曲
+    CompactPlaylist, // 低内存模式：精简当前播放列表中的所有条目
+    Enqueue(Vec<SongInfo>), // 按添加策略加入歌曲（所有添加入口统一使用）
+    SetEnqueuePolicy(EnqueuePolicy),
     RemoveSong(usize),
+    MoveSong { from: usize, to: usize }, // 把 from 处的歌曲移动到 to 处（移动后的索引）
+    SortPlaylist { field: SortField, direction: SortDirection }, // 按元数据排序，当前歌曲跟随到新位置
+    // 按加入时间排序（内部使用）：加入时间在后台线程读取，added 与 generation 版本的播放列表逐项对应
+    SortPlaylistByDateAdded { generation: u64, direction: SortDirection, added: Vec<Option<i64>> },
     ClearPlaylist,
+    CleanupPlaylist { options: CleanupOptions, reply: tokio::sync::oneshot::Sender<CleanupSummary> }, // 去重并移除失效/空条目
+    LoadPlaylist { name: Option<String>, songs: Vec<SongInfo>, index: Option<usize>, position: u64 }, // 载入播放列表并恢复位置（name 为空表示恢复上次会话）
+    ResumeWithFadeIn(f32), // 从暂停位置继续播放，音量在指定秒数内从0渐升
+    SetActivePlaylist(Option<String>), // 标记当前列表对应的已保存播放列表
     SetPlayMode(PlayMode),
     SetVolume(f32),
+    SetMuted(bool), // 静音/取消静音，音量值保持不变
+    SetSilenceGap(f32), // 设置自动切歌时的曲间静音间隔（秒）
+    SetLiveAlbumMode(bool), // 同一专辑相邻曲目连续播放，不插入静音、不做淡入
+    SetIdleReleaseTimeout(u64), // 设置空闲多久后释放音频设备（秒）
+    ClearPlaybackDiagnostics, // 清空播放卡顿统计
+    StopAfterTracks(u32), // 再自动播完N首后停止，0为取消
+    SetStopAtPlaylistEnd(bool), // 开启时切换到不循环模式（播完列表后停止），关闭时切换到列表循环
+    AutoAdvance, // 歌曲自然播完后切歌（内部使用），单曲循环时重播当前曲目
+    ReplayLast(u64), // 回退指定秒数并继续播放，可跨到上一首的结尾
+    SkipBy(i64), // 相对当前位置快进（正数）或快退（负数）指定秒数，只在当前曲目内
+    NextChapter, // 跳到当前曲目的下一章，已是最后一章时切到下一首
+    PreviousChapter, // 本章开头几秒内跳到上一章，否则回到本章开头
+    SeekToChapter(usize), // 跳到当前曲目的指定章节
+    ApplyMetadata { path: String, metadata: MetadataOverride }, // 更新播放列表中该文件条目的元数据
+    ApplyTrimPoints { path: String, trim: Option<TrimPoints> }, // 更新播放列表中该文件条目的播放起止点
+    ApplyCover { path: String, cover: Option<String> }, // 更新播放列表中该文件所有条目（包括虚拟曲目）的封面
+    RefreshSong(SongInfo), // 标签写回文件后，用重新读取的信息替换播放列表中该文件的条目
+    ResolvePlaceholder { path: String, songs: Vec<SongInfo> }, // 用读取完成的元数据替换该文件的占位条目（可展开为多个条目）
+    ApplyRating { path: String, rating: Option<u8>, favorite: bool }, // 更新播放列表中该文件条目的评分和收藏
+    ApplyResumeEnabled { path: String, enabled: bool }, // 更新播放列表中该文件条目的续播开关
+    SetAbLoop { start: u64, end: u64 }, // 循环播放当前曲目的一段：播放越过 end 时跳回 start
+    ClearAbLoop,
+    StartAbCompare { path: String, reply: tokio::sync::oneshot::Sender<Result<AbCompare, String>> }, // 当前歌曲与另一个文件同步播放，用于 A/B 对比
+    SwitchAb { reply: tokio::sync::oneshot::Sender<Result<AbCompare, String>> }, // 切换 A/B 对比中发声的一路
+    StopAbCompare, // 结束 A/B 对比
+    QueryPositionMs { reply: tokio::sync::oneshot::Sender<u64> }, // 查询扣除设备延迟后的播放位置（毫秒）
+    PlayCalibrationClicks { count: u32, interval_ms: u64, reply: tokio::sync::oneshot::Sender<Result<u64, String>> }, // 播放延迟校准提示音
+    SetOutputDevice(OutputDeviceSelection), // 切换音频输出设备/后端/缓冲区大小
+    ReopenOutput(OutputDeviceSelection), // 重新打开输出设备（内部使用）：设备断开时临时改用默认设备、恢复后切回，不改变用户的选择
+    SetEntryStyle { indices: Vec<usize>, color: Option<String>, group: Option<String> }, // 设置播放列表条目的颜色/分组
     SeekTo(u64),
+    SeekToMs(u64), // 跳转到毫秒位置（章节起点等需要精确定位的跳转）
     UpdateVideoProgress { position: u64, duration: u64 },
-    TogglePlaybackMode, // 在音频模式和MV模式之间切换
-    SetPlaybackMode(MediaType), // 直接设置播放模式（音频或视频）
+    TogglePlaybackMode { position: Option<u64> }, // 在音频模式和MV模式之间切换，从原来的位置继续（position 为前端视频的位置，秒）
+    SetPlaybackMode { mode: MediaType, position: Option<u64> }, // 直接设置播放模式（音频或视频）
     // 新增：音视频互斥控制命令
     ForceStopAudio,     // 强制停止音频播放
     ForceStopVideo,     // 强制停止视频播放
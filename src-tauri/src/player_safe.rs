@@ -1,14 +1,32 @@
-use crate::player_fixed::{PlayMode, PlayerCommand, PlayerEvent, PlayerState, SongInfo, MediaType};
-use rand::Rng;
-use std::sync::{Arc, Mutex};
+use crate::cast;
+use crate::dsp::{self, DspChain};
+use crate::gapless;
+use crate::intro_skip;
+use crate::library;
+use crate::low_memory;
+use crate::matroska;
+use crate::media_source;
+use crate::progress;
+use crate::output_device::{self, AudioOutput, OutputDeviceSelection};
+use crate::radio_host;
+use crate::replaygain;
+use crate::playback_monitor::{self, GlitchMonitor, PlaybackDiagnostics, PlaybackGlitch, SilenceKind, SilenceWatchdog};
+use crate::collation;
+use crate::player_fixed::{AbCompare, AbLoop, AbSide, EnqueuePolicy, PlayMode, PlayerCommand, PlayerEvent, PlayerState, SongInfo, MediaType, SortDirection, SortField};
+use crate::shuffle::ShuffleQueue;
+use crate::skip_filter;
+use serde::Serialize;
+use std::sync::{Arc, Mutex, RwLock};
 use tokio::sync::mpsc;
 use rodio::Source;
 
 /// 线程安全的播放器适配器
 /// 将处理分为两部分：前端可以访问的线程安全状态和后台播放器线程
+/// 播放列表用 Arc 共享，发布状态副本时不复制列表，修改时写时复制
+#[derive(Clone)]
 pub struct SafePlayerState {
     state: PlayerState,
-    playlist: Vec<SongInfo>,
+    playlist: Arc<Vec<SongInfo>>,
     current_index: Option<usize>,
     play_mode: PlayMode,
     volume: f32, // Added volume field
@@ -16,19 +34,76 @@ pub struct SafePlayerState {
     // 新增：音视频互斥控制
     is_audio_active: bool, // 音频播放器是否激活
     is_video_active: bool, // 视频播放器是否激活
+    silence_gap_secs: f32, // 自动切歌时插入的静音间隔（秒，0-5）
+    idle_release_secs: u64, // 非播放状态持续多久后释放音频设备（秒）
+    position: u64, // 当前播放位置（秒），由播放器线程更新
+    active_playlist: Option<String>, // 当前载入的已保存播放列表名称
+    diagnostics: PlaybackDiagnostics, // 播放卡顿统计
+    enqueue_policy: EnqueuePolicy, // 添加歌曲时的处理策略
+    output_device: OutputDeviceSelection, // 音频输出设备选择
+    stop_after_tracks: Option<u32>, // 再自动播完几首后停止，None 为不限制
+    live_album_mode: bool, // 同一专辑的相邻曲目连续解码播放（现场专辑的掌声跨曲目不中断）
+    muted: bool, // 静音（保留音量值，取消静音后恢复）
+    shuffle: ShuffleQueue, // 随机播放时打乱后的播放顺序
+    playlist_generation: u64, // 播放列表增删、替换或重排条目时递增，随机播放顺序据此判断是否过期
+}
+
+impl SafePlayerState {
+    /// 实际输出的音量：静音时为0
+    fn output_volume(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.volume
+        }
+    }
+
+    /// 增删或移动条目时使用，递增播放列表版本；只修改条目内容时直接用 Arc::make_mut
+    fn playlist_mut(&mut self) -> &mut Vec<SongInfo> {
+        self.playlist_generation += 1;
+        Arc::make_mut(&mut self.playlist)
+    }
+
+    /// 整个替换播放列表
+    fn replace_playlist(&mut self, songs: Vec<SongInfo>) {
+        self.playlist_generation += 1;
+        self.playlist = Arc::new(songs);
+    }
+
+    /// 按排序后的原索引顺序重排播放列表，当前歌曲和随机播放顺序跟随到新位置
+    fn reorder_playlist(&mut self, order: &[usize]) {
+        let sorted: Vec<SongInfo> = order.iter().map(|&i| self.playlist[i].clone()).collect();
+        self.replace_playlist(sorted);
+        self.shuffle.remap(order, self.playlist_generation);
+        if let Some(current_idx) = self.current_index {
+            self.current_index = order.iter().position(|&i| i == current_idx);
+        }
+    }
 }
 
 impl Default for SafePlayerState {
     fn default() -> Self {
         Self {
             state: PlayerState::Stopped,
-            playlist: Vec::new(),
+            playlist: Arc::new(Vec::new()),
             current_index: None,
-            play_mode: PlayMode::Sequential,
+            play_mode: PlayMode::RepeatAll,
             volume: 1.0, // Default volume
             current_playback_mode: MediaType::Audio, // 默认音频模式
             is_audio_active: false,
             is_video_active: false,
+            silence_gap_secs: 0.0,
+            idle_release_secs: 300,
+            position: 0,
+            active_playlist: None,
+            diagnostics: PlaybackDiagnostics::default(),
+            enqueue_policy: EnqueuePolicy::Append,
+            output_device: OutputDeviceSelection::default(),
+            stop_after_tracks: None,
+            live_album_mode: false,
+            muted: false,
+            shuffle: ShuffleQueue::default(),
+            playlist_generation: 0,
         }
     }
 }
@@ -37,6 +112,8 @@ impl Default for SafePlayerState {
 /// 处理与前端的交互，维护线程安全的状态
 pub struct SafePlayerManager {
     state: Arc<Mutex<SafePlayerState>>,
+    // 播放器线程每处理完一条命令或一次进度更新后发布的状态副本
+    published: Arc<RwLock<SafePlayerState>>,
     command_sender: mpsc::Sender<PlayerCommand>,
 }
 
@@ -48,14 +125,16 @@ impl SafePlayerManager {
 
         // 创建线程安全状态
         let state = Arc::new(Mutex::new(SafePlayerState::default()));
+        let published = Arc::new(RwLock::new(SafePlayerState::default()));
 
         // 启动处理播放器命令的线程
         let state_clone = state.clone();
+        let published_clone = published.clone();
         let event_tx_clone = event_tx.clone();
         let cmd_tx_clone_for_thread = cmd_tx.clone(); // Clone sender for the thread
 
         std::thread::spawn(move || {
-            if let Err(e) = run_player_thread(cmd_rx, event_tx_clone, state_clone, cmd_tx_clone_for_thread) {
+            if let Err(e) = run_player_thread(cmd_rx, event_tx_clone, state_clone, published_clone, cmd_tx_clone_for_thread) {
                 eprintln!("播放器线程错误: {}", e);
             }
         });
@@ -63,75 +142,204 @@ impl SafePlayerManager {
         (
             SafePlayerManager {
                 state,
+                published,
                 command_sender: cmd_tx,
             },
             event_rx,
         )
     }
 
+    /// 读取状态：播放器线程正持有锁（处理命令中，可能在做文件IO或解码）时不等待，
+    /// 改为读取最近一次发布的副本，即该命令执行前的状态
+    fn read<T>(&self, f: impl FnOnce(&SafePlayerState) -> T) -> T {
+        match self.state.try_lock() {
+            Ok(guard) => f(&guard),
+            Err(_) => f(&self.published.read().unwrap_or_else(|e| e.into_inner())),
+        }
+    }
+
     /// 获取播放器状态
     pub fn get_state(&self) -> PlayerState {
-        self.state.lock().unwrap().state
+        self.read(|s| s.state)
     }
 
     /// 获取当前播放列表
     pub fn get_playlist(&self) -> Vec<SongInfo> {
-        self.state.lock().unwrap().playlist.clone()
+        self.read(|s| s.playlist.to_vec())
+    }
+
+    /// 播放列表版本号，增删、替换或重排条目后变化
+    pub fn get_playlist_generation(&self) -> u64 {
+        self.read(|s| s.playlist_generation)
+    }
+
+    /// 获取播放列表中指定索引的条目
+    pub fn get_song(&self, index: usize) -> Option<SongInfo> {
+        self.read(|s| s.playlist.get(index).cloned())
     }
 
     /// 获取当前播放的歌曲索引
     pub fn get_current_index(&self) -> Option<usize> {
-        self.state.lock().unwrap().current_index
+        self.read(|s| s.current_index)
     }
 
     /// 获取当前播放模式
     pub fn get_play_mode(&self) -> PlayMode {
-        self.state.lock().unwrap().play_mode
+        self.read(|s| s.play_mode)
+    }
+
+    /// 获取当前播放位置（秒）
+    pub fn get_position(&self) -> u64 {
+        self.read(|s| s.position)
+    }
+
+    /// 获取当前载入的已保存播放列表名称
+    pub fn get_active_playlist(&self) -> Option<String> {
+        self.read(|s| s.active_playlist.clone())
+    }
+
+    /// 获取添加歌曲时的处理策略
+    pub fn get_enqueue_policy(&self) -> EnqueuePolicy {
+        self.read(|s| s.enqueue_policy)
+    }
+
+    /// 获取当前音频输出设备选择
+    pub fn get_output_device(&self) -> OutputDeviceSelection {
+        self.read(|s| s.output_device.clone())
+    }
+
+    /// 获取剩余的"播完N首后停止"计数
+    pub fn get_stop_after_tracks(&self) -> Option<u32> {
+        self.read(|s| s.stop_after_tracks)
+    }
+
+    /// 获取播放卡顿诊断汇总
+    pub fn get_playback_diagnostics(&self) -> PlaybackDiagnostics {
+        self.read(|s| s.diagnostics.clone())
+    }
+
+    /// 获取曲间静音间隔（秒）
+    pub fn get_silence_gap(&self) -> f32 {
+        self.read(|s| s.silence_gap_secs)
+    }
+
+    /// 获取音量（0-2，静音时仍为静音前的音量）
+    pub fn get_volume(&self) -> f32 {
+        self.read(|s| s.volume)
+    }
+
+    /// 是否静音
+    pub fn is_muted(&self) -> bool {
+        self.read(|s| s.muted)
+    }
+
+    /// 接下来要播放的曲目（最多 limit 首）
+    pub fn get_up_next(&self, limit: usize) -> Vec<UpNextEntry> {
+        self.read(|s| {
+            up_next(s, limit)
+                .into_iter()
+                .map(|index| UpNextEntry {
+                    index,
+                    song: s.playlist[index].clone().without_cover(),
+                })
+                .collect()
+        })
+    }
+
+    /// 是否开启现场专辑连续播放
+    pub fn get_live_album_mode(&self) -> bool {
+        self.read(|s| s.live_album_mode)
+    }
+
+    /// 是否开启了"播完列表后停止"（即不循环模式）
+    pub fn get_stop_at_playlist_end(&self) -> bool {
+        self.read(|s| s.play_mode == PlayMode::NoRepeat)
     }
 
     // 获取播放器状态快照，用于初始化前端状态
+    // 会等待播放器线程处理完当前命令，保证与刚发出的事件一致
     pub async fn get_player_state_snapshot(&self) -> SafePlayerStateSnapshot {
         let guard = self.state.lock().unwrap();
-        SafePlayerStateSnapshot {
-            state: guard.state,
-            playlist: guard.playlist.clone(),
-            current_index: guard.current_index,
-            play_mode: guard.play_mode,
-            volume: guard.volume, // Include volume
-            current_playback_mode: guard.current_playback_mode, // 添加播放模式字段
-        }
+        SafePlayerStateSnapshot::from_state(&guard)
+    }
+
+    /// 获取播放器状态快照，从不等待播放器线程（切歌、跳转时的文件IO和解码不会卡住界面）
+    pub fn get_state_nonblocking(&self) -> SafePlayerStateSnapshot {
+        self.read(SafePlayerStateSnapshot::from_state)
     }
 
     /// 发送命令到播放器
     pub async fn send_command(&self, cmd: PlayerCommand) -> Result<(), anyhow::Error> {
-        self.command_sender.send(cmd).await?;
+        // 投屏时播放、暂停、跳转等由渲染器处理
+        let Some(cmd) = cast::intercept(cmd) else { return Ok(()) };
+        self.send_local_command(cmd).await
+    }
+
+    /// 直接发给本机播放器，不经过投屏转发（投屏模块控制本机播放器时使用）
+    pub async fn send_local_command(&self, cmd: PlayerCommand) -> Result<(), anyhow::Error> {
+        self.command_sender.send(low_memory::compact_command(cmd)).await?;
         Ok(())
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize)]
 pub struct SafePlayerStateSnapshot {
     pub state: PlayerState,
     pub playlist: Vec<SongInfo>,
+    #[serde(rename = "currentIndex")]
     pub current_index: Option<usize>,
+    #[serde(rename = "playMode")]
     pub play_mode: PlayMode,
     pub volume: f32, // Added volume
+    pub muted: bool,
+    #[serde(rename = "currentPlaybackMode")]
     pub current_playback_mode: MediaType, // 添加播放模式字段
+    pub position: u64, // 播放位置（秒）
 }
 
-/// 在独立线程中运行播放器
-/// 此函数处理所有与rodio相关的操作，确保线程安全
-fn run_player_thread(
-    mut cmd_rx: mpsc::Receiver<PlayerCommand>,
-    event_tx: mpsc::Sender<PlayerEvent>,
-    state: Arc<Mutex<SafePlayerState>>,
-    command_sender_for_internal_use: mpsc::Sender<PlayerCommand>, // For sending commands like auto-next
-) -> anyhow::Result<()> {
+impl SafePlayerStateSnapshot {
+    fn from_state(state: &SafePlayerState) -> Self {
+        Self {
+            state: state.state,
+            playlist: state.playlist.to_vec(),
+            current_index: state.current_index,
+            play_mode: state.play_mode,
+            volume: state.volume,
+            muted: state.muted,
+            current_playback_mode: state.current_playback_mode,
+            position: state.position,
+        }
+    }
+}
+
+/// 接下来要播放的一首曲目及其在播放列表中的索引
+#[derive(Clone, Serialize)]
+pub struct UpNextEntry {
+    pub index: usize,
+    pub song: SongInfo,
+}
+
+/// 打开音频输出设备，指定设备打开失败时回退到默认设备
+fn open_output_stream(
+    selection: &OutputDeviceSelection,
+    sample_rate: Option<u32>,
+    event_tx: &mpsc::Sender<PlayerEvent>,
+) -> anyhow::Result<AudioOutput> {
     // 修复：增加音频输出设备初始化的详细日志和错误处理
     println!("🔊 正在初始化音频输出设备...");
+
+    if !selection.is_default() || sample_rate.is_some() {
+        match output_device::open(selection, sample_rate) {
+            Ok(output) => return Ok(output),
+            Err(e) => {
+                eprintln!("❌ 指定的音频输出设备打开失败，回退到默认设备: {}", e);
+                let _ = event_tx.try_send(PlayerEvent::Error(format!("无法打开所选输出设备，已使用默认设备: {}", e)));
+            }
+        }
+    }
     
     // 尝试多种音频输出方式
-    let (_stream, stream_handle) = match rodio::OutputStream::try_default() {
+    let output = match rodio::OutputStream::try_default() {
         Ok(output) => {
             println!("✅ 默认音频输出设备初始化成功");
             output
@@ -157,14 +365,565 @@ fn run_player_thread(
         }
     };
     
+    let sample_rate = output_device::default_sample_rate().unwrap_or(44100);
+    Ok(AudioOutput::Default(output.0, output.1, sample_rate))
+}
+
+/// 打开音频输出设备并切换到该设备的配置
+fn open_output(
+    output_stream: &mut Option<AudioOutput>,
+    selection: &OutputDeviceSelection,
+    sample_rate: Option<u32>,
+    event_tx: &mpsc::Sender<PlayerEvent>,
+) -> anyhow::Result<()> {
+    let output = open_output_stream(selection, sample_rate, event_tx)?;
+    // 不同设备使用各自的EQ/DSP配置和延迟补偿
+    let device_key = output.device_key();
+    output_device::set_active_device(&device_key);
+    dsp::activate_device(&device_key);
+    *output_stream = Some(output);
+    Ok(())
+}
+
+/// 在音频输出设备上创建sink，设备尚未占用（或已因空闲释放）时重新获取
+/// source_rate 为即将播放的音源采样率：高于设备采样率时发送提示事件，
+/// 开启"匹配采样率"且设备支持时以音源采样率重新打开输出
+fn create_sink(
+    output_stream: &mut Option<AudioOutput>,
+    selection: &OutputDeviceSelection,
+    source_rate: Option<u32>,
+    event_tx: &mpsc::Sender<PlayerEvent>,
+) -> anyhow::Result<rodio::Sink> {
+    if output_stream.is_none() {
+        open_output(output_stream, selection, None, event_tx)?;
+    }
+    let device_rate = output_stream.as_ref().unwrap().sample_rate();
+    if let Some(file_rate) = source_rate.filter(|rate| *rate != device_rate) {
+        let reconfigure = selection.match_sample_rate && output_device::supports_sample_rate(selection, file_rate);
+        if reconfigure {
+            // 先释放当前设备，部分驱动不允许同一设备同时打开两个流
+            *output_stream = None;
+            if let Err(e) = open_output(output_stream, selection, Some(file_rate), event_tx) {
+                eprintln!("❌ 以 {}Hz 重新打开输出设备失败: {}", file_rate, e);
+                open_output(output_stream, selection, None, event_tx)?;
+            }
+        }
+        let output_rate = output_stream.as_ref().unwrap().sample_rate();
+        if file_rate > device_rate || output_rate != device_rate {
+            let _ = event_tx.try_send(PlayerEvent::SampleRateMismatch {
+                file_rate,
+                device_rate,
+                reconfigured: output_rate == file_rate,
+            });
+        }
+    }
+    output_stream.as_ref().unwrap().new_sink()
+}
+
+/// 检查输出设备是否断开/默认设备是否变化的间隔
+const DEVICE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// 当前歌曲自然播完后将要播放的曲目，用于提前预取；随机模式及即将停止时无法预知，返回 None
+fn upcoming_index(state: &SafePlayerState) -> Option<usize> {
+    let idx = state.current_index?;
+    if state.stop_after_tracks == Some(1) {
+        return None;
+    }
+    match state.play_mode {
+        PlayMode::RepeatAll | PlayMode::NoRepeat => step_index(state, true, true).filter(|next| *next != idx),
+        PlayMode::Shuffle => step_index(state, true, true),
+        PlayMode::RepeatOne => None,
+    }
+}
+
+/// 打开 A/B 对比中的一路音源，从 position 处开始，经过与正常播放相同的 DSP 处理
+fn open_compare_source(
+    song: &SongInfo,
+    position: std::time::Duration,
+    shared_dsp: &Arc<dsp::SharedDsp>,
+    event_tx: &mpsc::Sender<PlayerEvent>,
+) -> anyhow::Result<Box<dyn Source<Item = i16> + Send>> {
+    let source = matroska::decode(media_source::open(song, event_tx)?, song)?;
+    Ok(Box::new(DspChain::new(replaygain::apply(source.skip_duration(position), song, false), shared_dsp.clone())))
+}
+
+/// 自然切歌时播放电台主持人播报（已为刚播完的曲目合成好时），与下一首混音，播报期间压低音乐音量
+fn start_announcement(
+    output_stream: &Option<AudioOutput>,
+    finished_path: &str,
+    volume: f32,
+    event_tx: &mpsc::Sender<PlayerEvent>,
+) -> Option<rodio::Sink> {
+    let (text, clip) = radio_host::take_clip(finished_path)?;
+    let sink = match output_stream.as_ref().map(|output| output.new_sink()) {
+        Some(Ok(sink)) => sink,
+        Some(Err(e)) => {
+            eprintln!("🎙️ 无法创建播报sink: {}", e);
+            return None;
+        }
+        None => return None,
+    };
+    sink.set_volume(volume);
+    sink.append(clip);
+    println!("🎙️ 电台播报: {}", text);
+    let _ = event_tx.try_send(PlayerEvent::AnnouncementPlaying { text });
+    Some(sink)
+}
+
+/// 现场专辑模式下提前排入 sink 的时间（距离当前曲目结尾的秒数）
+const CHAIN_AHEAD_SECS: u64 = 5;
+
+/// 现场专辑模式下可以与当前曲目连续播放的下一首：顺序模式、同一目录下的同一专辑
+fn live_album_next(state: &SafePlayerState) -> Option<usize> {
+    if !state.live_album_mode || !matches!(state.play_mode, PlayMode::RepeatAll | PlayMode::NoRepeat) || state.stop_after_tracks == Some(1) || at_playlist_end(state) {
+        return None;
+    }
+    let idx = state.current_index?;
+    let current = state.playlist.get(idx)?;
+    let next = state.playlist.get(idx + 1)?;
+    let same_album = match (&current.album, &next.album) {
+        (Some(a), Some(b)) => !a.trim().is_empty() && a.trim().eq_ignore_ascii_case(b.trim()),
+        _ => false,
+    };
+    let same_dir = std::path::Path::new(&current.path).parent() == std::path::Path::new(&next.path).parent();
+    let audio = next.media_type != Some(MediaType::Video);
+    (same_album && same_dir && audio && current.segment.is_none() && next.segment.is_none()).then_some(idx + 1)
+}
+
+/// 会替换或停止当前 sink 的命令：已排入 sink 的下一首、A/B 对比的 B 路都随之失效
+fn replaces_sink(cmd: &PlayerCommand) -> bool {
+    matches!(
+        cmd,
+        PlayerCommand::Stop
+            | PlayerCommand::Next
+            | PlayerCommand::AutoAdvance
+            | PlayerCommand::Previous
+            | PlayerCommand::SetSong(_)
+            | PlayerCommand::SeekTo(_)
+            | PlayerCommand::SeekToMs(_)
+            | PlayerCommand::ReplayLast(_)
+            | PlayerCommand::SetSongAt { .. }
+            | PlayerCommand::ClearPlaylist
+            | PlayerCommand::LoadPlaylist { .. }
+            | PlayerCommand::SetOutputDevice(_)
+            | PlayerCommand::ReopenOutput(_)
+            | PlayerCommand::TogglePlaybackMode { .. }
+            | PlayerCommand::SetPlaybackMode { .. }
+            | PlayerCommand::ForceStopAudio
+            | PlayerCommand::ForceStopAll
+            | PlayerCommand::StartAbCompare { .. }
+    )
+}
+
+/// A/B 对比时只让选中的一路发声，另一路静音但继续同步播放
+fn apply_ab_volume(current_sink: &Option<rodio::Sink>, ab_compare: &Option<(rodio::Sink, AbCompare)>, volume: f32) {
+    if let (Some(sink_a), Some((sink_b, compare))) = (current_sink, ab_compare) {
+        let (volume_a, volume_b) = match compare.active {
+            AbSide::A => (volume, 0.0),
+            AbSide::B => (0.0, volume),
+        };
+        sink_a.set_volume(volume_a);
+        sink_b.set_volume(volume_b);
+    }
+}
+
+/// 结束 A/B 对比：停止 B 路，A 路恢复当前音量
+fn end_ab_compare(
+    ab_compare: &mut Option<(rodio::Sink, AbCompare)>,
+    current_sink: &Option<rodio::Sink>,
+    volume: f32,
+    event_tx: &mpsc::Sender<PlayerEvent>,
+) {
+    if let Some((sink_b, _)) = ab_compare.take() {
+        sink_b.stop();
+        if let Some(sink_a) = current_sink {
+            sink_a.set_volume(volume);
+        }
+        let _ = event_tx.try_send(PlayerEvent::AbCompareChanged(None));
+        println!("🅰️🅱️ A/B 对比已结束");
+    }
+}
+
+/// 进度事件中附带的 A–B 循环区间
+fn loop_bounds(ab_loop: &Option<(String, AbLoop)>) -> Option<AbLoop> {
+    ab_loop.as_ref().map(|(_, bounds)| *bounds)
+}
+
+/// 把当前状态复制到发布副本（播放列表共享，不复制）
+fn publish_state(state: &Mutex<SafePlayerState>, published: &RwLock<SafePlayerState>) {
+    let mut guard = state.lock().unwrap();
+    // 播放列表或当前曲目变化后同步随机播放顺序，读取副本时接下来的曲目总是有效的
+    if guard.play_mode == PlayMode::Shuffle {
+        let state = &mut *guard;
+        state.shuffle.sync(state.playlist.len(), state.playlist_generation, state.current_index);
+    }
+    let copy = guard.clone();
+    drop(guard);
+    *published.write().unwrap_or_else(|e| e.into_inner()) = copy;
+}
+
+/// 按播放模式计算切歌目标；auto 为 true 表示歌曲自然播完（单曲循环时重播当前曲目）
+/// 不循环模式下已经是最后一首时下一首返回 None，第一首的上一首为重新播放当前曲目
+/// 随机模式下只预览随机顺序中的下一首（本轮已播完时为 None），实际切歌用 shuffle_step 推进顺序
+fn step_index(state: &SafePlayerState, forward: bool, auto: bool) -> Option<usize> {
+    let len = state.playlist.len();
+    if len == 0 {
+        return None;
+    }
+    let Some(idx) = state.current_index else {
+        return Some(if forward { 0 } else { len - 1 });
+    };
+    let wrapped = if forward { if idx + 1 >= len { 0 } else { idx + 1 } } else if idx == 0 { len - 1 } else { idx - 1 };
+    match state.play_mode {
+        PlayMode::RepeatOne if auto => Some(idx),
+        PlayMode::Shuffle if forward => state.shuffle.upcoming(&state.playlist, state.playlist_generation, 1).first().copied(),
+        PlayMode::Shuffle => Some(idx),
+        PlayMode::RepeatAll | PlayMode::RepeatOne => Some(skip_filter::sequential_pick(&state.playlist, wrapped, forward)),
+        PlayMode::NoRepeat if forward => {
+            // 跳过被过滤的曲目后回绕到当前曲目之前（或就是当前曲目），说明后面已经没有要播的了
+            let next = skip_filter::sequential_pick(&state.playlist, wrapped, true);
+            (idx + 1 < len && next > idx).then_some(next)
+        }
+        PlayMode::NoRepeat => {
+            let previous = skip_filter::sequential_pick(&state.playlist, wrapped, false);
+            Some(if idx == 0 || previous > idx { idx } else { previous })
+        }
+    }
+}
+
+/// 随机模式下沿随机顺序切到下一首/上一首，本轮播完时重新打乱
+fn shuffle_step(state: &mut SafePlayerState, forward: bool) -> Option<usize> {
+    if forward {
+        state.shuffle.next(&state.playlist, state.playlist_generation, state.current_index)
+    } else {
+        state.shuffle.previous(&state.playlist, state.playlist_generation, state.current_index)
+    }
+}
+
+/// 接下来要播放的曲目索引：随机模式为本轮剩余的随机顺序，不循环模式到列表末尾为止，其他模式回绕到当前曲目之前
+fn up_next(state: &SafePlayerState, limit: usize) -> Vec<usize> {
+    let len = state.playlist.len();
+    if state.play_mode == PlayMode::Shuffle {
+        return state.shuffle.upcoming(&state.playlist, state.playlist_generation, limit);
+    }
+    let (first, count) = match state.current_index {
+        Some(idx) if state.play_mode == PlayMode::NoRepeat => (idx + 1, len.saturating_sub(idx + 1)),
+        Some(idx) => (idx + 1, len.saturating_sub(1)),
+        None => (0, len),
+    };
+    let filter = skip_filter::settings();
+    (0..count)
+        .map(|step| (first + step) % len)
+        .filter(|&i| !filter.in_sequential || filter.allows_song(&state.playlist[i]))
+        .take(limit)
+        .collect()
+}
+
+/// 不循环模式下当前曲目是否是列表中最后一首要播放的曲目
+fn at_playlist_end(state: &SafePlayerState) -> bool {
+    state.play_mode == PlayMode::NoRepeat && state.current_index.is_some() && step_index(state, true, true).is_none()
+}
+
+/// 歌曲自然播完时递减"播完N首后停止"计数，计数归零时返回 true
+fn consume_stop_after(state: &mut SafePlayerState) -> bool {
+    match state.stop_after_tracks {
+        Some(n) if n <= 1 => {
+            state.stop_after_tracks = None;
+            true
+        }
+        Some(n) => {
+            state.stop_after_tracks = Some(n - 1);
+            false
+        }
+        None => false,
+    }
+}
+
+/// 当前曲目已播放到的位置：优先使用音源实际送往输出设备的采样数（缓冲卡顿、更换设备后仍然准确），
+/// 音源尚未上报时按开始播放的时间推算
+fn playback_position(state: &SafePlayerState, play_start_time: Option<std::time::Instant>) -> std::time::Duration {
+    let path = state.current_index.and_then(|idx| state.playlist.get(idx)).map(|song| song.path.as_str());
+    match path.and_then(playback_monitor::playing_position_ms) {
+        Some(ms) => std::time::Duration::from_millis(ms),
+        None => play_start_time.map(|start_time| start_time.elapsed()).unwrap_or_default(),
+    }
+}
+
+/// 切换音频/MV模式前的播放位置（秒）：音频模式取播放器的位置，视频由前端播放，
+/// 取命令带来的前端位置，没有时使用最后上报的视频进度
+fn mode_switch_position(
+    state: &SafePlayerState,
+    current_mode: MediaType,
+    frontend_position: Option<u64>,
+    play_start_time: Option<std::time::Instant>,
+    paused_position: u64,
+) -> u64 {
+    match current_mode {
+        MediaType::Audio => match play_start_time {
+            Some(_) if state.state == PlayerState::Playing => playback_position(state, play_start_time).as_secs(),
+            _ => paused_position,
+        },
+        MediaType::Video => frontend_position.unwrap_or(state.position),
+    }
+}
+
+/// 播放中每隔这么久保存一次续播位置（秒）
+const RESUME_SAVE_INTERVAL_SECS: u64 = 10;
+/// 离结尾不到这么久时视为已听完，下次从头播放（秒）
+const RESUME_END_MARGIN_SECS: u64 = 10;
+
+/// 续播位置：本次运行中保存过的位置优先于条目载入时从音乐库读取的位置
+/// 音乐库由后台线程按保存顺序写入，播放器线程不等待数据库
+struct ResumePositions {
+    saved: std::collections::HashMap<String, Option<u64>>,
+    writer: std::sync::mpsc::Sender<(String, Option<u64>)>,
+}
+
+impl ResumePositions {
+    fn new() -> Self {
+        let (writer, rx) = std::sync::mpsc::channel::<(String, Option<u64>)>();
+        std::thread::spawn(move || {
+            for (path, position_ms) in rx {
+                if let Err(e) = library::with_library(|lib| lib.set_resume_position(&path, position_ms)) {
+                    eprintln!("保存续播位置失败: {}", e);
+                }
+            }
+        });
+        Self {
+            saved: std::collections::HashMap::new(),
+            writer,
+        }
+    }
+
+    /// 开启续播的曲目上次播放到的位置（毫秒）
+    fn position(&self, song: &SongInfo) -> Option<u64> {
+        if !song.resume_enabled {
+            return None;
+        }
+        self.saved.get(&song.path).copied().unwrap_or(song.resume_position)
+    }
+
+    /// 保存续播位置（秒），None 表示已播完；离结尾很近时同样视为已听完，下次从头播放
+    fn save(&mut self, song: &SongInfo, position: Option<u64>) {
+        if !song.resume_enabled {
+            return;
+        }
+        let position_ms = position
+            .filter(|position| !song.duration.map_or(false, |duration| position + RESUME_END_MARGIN_SECS >= duration))
+            .map(|position| position * 1000);
+        // 位置没有变化时不写入（播完时也只清除已有的位置，不为每首歌都建一条记录）
+        if self.position(song) == position_ms {
+            return;
+        }
+        self.saved.insert(song.path.clone(), position_ms);
+        if self.writer.send((song.path.clone(), position_ms)).is_err() {
+            eprintln!("续播位置写入线程已退出");
+        }
+    }
+
+    /// 关闭续播后忘记本次运行中保存的位置
+    fn forget(&mut self, path: &str) {
+        self.saved.remove(path);
+    }
+}
+
+/// 在本章开头这么久之内"上一章"跳到上一章，之后回到本章开头（毫秒）
+const CHAPTER_RESTART_MS: u64 = 3000;
+
+/// 播放历史最多保留的曲目数
+const PLAY_HISTORY_LEN: usize = 100;
+
+/// 按用户设置的起止点裁剪音源
+/// from_ms 为音源当前所在位置；skip_intro 为 true 时从头播放的音源会跳过起始点之前的部分
+/// 返回裁剪后的音源和实际起始位置（毫秒）
+fn apply_trim_points<S>(source: S, trim: Option<library::TrimPoints>, from_ms: u64, skip_intro: bool) -> (Box<dyn Source<Item = i16> + Send>, u64)
+where
+    S: Source<Item = i16> + Send + 'static,
+{
+    let Some(trim) = trim else {
+        return (Box::new(source), from_ms);
+    };
+    let start_ms = if skip_intro && from_ms < trim.start_ms { trim.start_ms } else { from_ms };
+    let source = source.skip_duration(std::time::Duration::from_millis(start_ms - from_ms));
+    match trim.end_ms {
+        Some(end_ms) if end_ms > start_ms => (
+            Box::new(source.take_duration(std::time::Duration::from_millis(end_ms - start_ms))),
+            start_ms,
+        ),
+        _ => (Box::new(source), start_ms),
+    }
+}
+
+/// 打开并解码歌曲，接上播放时的整条处理链：无缝播放裁剪 → 用户设置的起止点 → ReplayGain → DSP → 卡顿监测
+/// start 为开始播放的位置，None 表示从头播放（跳过用户设置的起始点之前的部分）
+/// album_gain 为 true 时按专辑增益标准化（现场专辑模式）；返回处理后的音源和实际起始位置（毫秒）
+fn build_source(
+    song: &SongInfo,
+    start: Option<std::time::Duration>,
+    album_gain: bool,
+    shared_dsp: &Arc<dsp::SharedDsp>,
+    glitch_tx: &std::sync::mpsc::Sender<PlaybackGlitch>,
+    event_tx: &mpsc::Sender<PlayerEvent>,
+) -> anyhow::Result<(Box<dyn Source<Item = i16> + Send>, u64)> {
+    let file = media_source::open(song, event_tx).map_err(|e| anyhow::anyhow!("无法打开音频文件: {}", e))?;
+    let source = matroska::decode(file, song).map_err(|e| anyhow::anyhow!("解码音频文件失败: {}", e))?;
+    let from = start.unwrap_or_default();
+    let source = gapless::trim(source, gapless::playback_info(&song.path, song.gapless)).skip_duration(from);
+    let (source, start_ms) = apply_trim_points(source, song.trim, from.as_millis() as u64, start.is_none());
+    let source = DspChain::new(replaygain::apply(source, song, album_gain), shared_dsp.clone());
+    Ok((Box::new(GlitchMonitor::new(source, &song.path, start_ms, glitch_tx.clone())), start_ms))
+}
+
+/// 开始播放一首歌之前调用：中止其他曲目的网络下载；网络音源还没有缓冲好时在后台缓冲，不在播放器线程中等待，
+/// 返回 false 表示正在缓冲，缓冲好后通过 StreamBuffered 命令从 start 处重新开始播放
+fn ensure_buffered(
+    index: usize,
+    song: &SongInfo,
+    start: Option<std::time::Duration>,
+    event_tx: &mpsc::Sender<PlayerEvent>,
+    command_tx: &mpsc::Sender<PlayerCommand>,
+) -> bool {
+    media_source::cancel_downloads(Some(&song.path));
+    if media_source::is_ready(&song.path) {
+        return true;
+    }
+    let command_tx = command_tx.clone();
+    let error_tx = event_tx.clone();
+    let path = song.path.clone();
+    let position_ms = start.map(|start| start.as_millis() as u64);
+    media_source::buffer_then(song, event_tx, move |result| match result {
+        Ok(()) => {
+            if command_tx.blocking_send(PlayerCommand::StreamBuffered { index, path, position_ms }).is_err() {
+                eprintln!("播放器线程: 无法发送内部 StreamBuffered 命令 (通道已关闭)");
+            }
+        }
+        // 切歌、停止时中止的下载不报错
+        Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+        Err(e) => {
+            let _ = error_tx.try_send(PlayerEvent::Error(format!("无法打开网络音源: {}", e)));
+        }
+    });
+    false
+}
+
+/// 播放列表各条目加入音乐库的时间（Unix秒），不在音乐库中的用文件修改时间；查询数据库和文件，不在播放器线程调用
+fn playlist_added_times(paths: &[String]) -> Vec<Option<i64>> {
+    let added = library::with_library(|lib| lib.added_times()).unwrap_or_else(|e| {
+        eprintln!("读取加入音乐库时间失败: {}", e);
+        Default::default()
+    });
+    paths
+        .iter()
+        .map(|path| added.get(path).copied().or_else(|| library::file_mtime(std::path::Path::new(path))))
+        .collect()
+}
+
+/// 播放列表按字段排序后的原索引顺序（稳定排序，相同值保持原有顺序；没有该字段的歌曲排在最后）
+/// 按加入时间排序时 added 与播放列表逐项对应（见 playlist_added_times）
+fn playlist_sort_order(playlist: &[SongInfo], field: SortField, direction: SortDirection, added: &[Option<i64>]) -> Vec<usize> {
+    use std::cmp::Ordering;
+
+    let text_key = |value: Option<&String>| value.map(|v| collation::sort_key(v));
+    let title_key = |song: &SongInfo| {
+        let file_name = std::path::Path::new(&song.path).file_name().map(|n| n.to_string_lossy().into_owned());
+        text_key(song.title.as_ref().or(file_name.as_ref()))
+    };
+    // 每首歌的排序键：主键 + 同值时的次序（碟号、音轨号）
+    let keys: Vec<(Option<String>, Option<i64>, (u32, u32))> = playlist
+        .iter()
+        .enumerate()
+        .map(|(idx, song)| {
+            let position = (song.disc_number.unwrap_or(0), song.track_number.unwrap_or(0));
+            match field {
+                SortField::Title => (title_key(song), None, (0, 0)),
+                SortField::Artist => (
+                    text_key(song.artist.as_ref()).map(|a| format!("{}\u{0}{}", a, text_key(song.album.as_ref()).unwrap_or_default())),
+                    None,
+                    position,
+                ),
+                SortField::Album => (text_key(song.album.as_ref()), None, position),
+                SortField::Duration => (None, song.duration.map(|d| d as i64), (0, 0)),
+                SortField::DateAdded => (None, added.get(idx).copied().flatten(), (0, 0)),
+                SortField::Path => (Some(song.path.to_lowercase()), None, (0, 0)),
+            }
+        })
+        .collect();
+    let has_key = |k: &(Option<String>, Option<i64>, (u32, u32))| k.0.is_some() || k.1.is_some();
+
+    let mut order: Vec<usize> = (0..playlist.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (ka, kb) = (&keys[a], &keys[b]);
+        match (has_key(ka), has_key(kb)) {
+            (true, false) => return Ordering::Less,
+            (false, true) => return Ordering::Greater,
+            (false, false) => return Ordering::Equal,
+            (true, true) => {}
+        }
+        let ordering = ka.0.cmp(&kb.0).then(ka.1.cmp(&kb.1)).then(ka.2.cmp(&kb.2));
+        match direction {
+            SortDirection::Ascending => ordering,
+            SortDirection::Descending => ordering.reverse(),
+        }
+    });
+    order
+}
+
+/// 在独立线程中运行播放器
+/// 此函数处理所有与rodio相关的操作，确保线程安全
+fn run_player_thread(
+    mut cmd_rx: mpsc::Receiver<PlayerCommand>,
+    event_tx: mpsc::Sender<PlayerEvent>,
+    state: Arc<Mutex<SafePlayerState>>,
+    published: Arc<RwLock<SafePlayerState>>,
+    command_sender_for_internal_use: mpsc::Sender<PlayerCommand>, // For sending commands like auto-next
+) -> anyhow::Result<()> {
+    // 音频输出设备按需获取：只在需要播放时占用，空闲超时后释放
+    let mut output_stream: Option<AudioOutput> = None;
+    // 延迟校准提示音使用的sink，播放完之前需要保持
+    let mut calibration_sink: Option<rodio::Sink> = None;
+    // A/B 对比的 B 路（A 路为 current_sink）
+    let mut ab_compare: Option<(rodio::Sink, AbCompare)> = None;
+    // 当前曲目的 A–B 循环（曲目路径, 区间），切到其他曲目后取消
+    let mut ab_loop: Option<(String, AbLoop)> = None;
+    // 现场专辑模式下已排入当前 sink、紧接着播放的下一首（索引, 路径）
+    let mut chained_next: Option<(usize, String)> = None;
+    // 电台主持人模式的播报，与下一首混音播放
+    let mut announcement: Option<rodio::Sink> = None;
+    // 当前输出设备的DSP配置，所有音源共享
+    let dsp = dsp::shared().clone();
+    // 下一次重新获取设备继续播放时使用的音量渐入时长
+    let mut pending_fade_in: Option<std::time::Duration> = None;
+    // 当前选择的输出设备，切换后在下次获取设备时生效
+    let mut output_selection = OutputDeviceSelection::default(); // 当前打开输出时使用的设备
+    let mut preferred_output = OutputDeviceSelection::default(); // 用户选择的设备，断开期间 output_selection 临时为默认设备
+    // 进入空闲（非播放）状态的时间点，用于判断何时释放音频设备
+    let mut idle_since: Option<std::time::Instant> = None;
+    // 上次检查输出设备是否断开/默认设备是否变化的时间
+    let mut device_checked_at = std::time::Instant::now();
+    // 音频回调线程上报解码卡顿的通道
+    let (glitch_tx, glitch_rx) = std::sync::mpsc::channel::<PlaybackGlitch>();
+    
     println!("🎵 音频播放器线程启动成功");
     
     let mut current_sink: Option<rodio::Sink> = None;
     
     // 添加播放进度追踪
     let mut play_start_time: Option<std::time::Instant> = None;
+    let mut silence_watchdog = SilenceWatchdog::default();
     let mut current_position: u64 = 0; // 当前播放位置（秒）
     let mut paused_position: u64 = 0;  // 暂停时的播放位置（秒）
+    // 曲间静音间隔结束的时间点，存在时表示正处于自动切歌前的静音中
+    let mut gap_deadline: Option<tokio::time::Instant> = None;
+    // 在曲间静音中暂停：恢复播放时直接切到下一首
+    let mut advance_on_resume = false;
+    // 已为哪一首触发过下一首预取，避免每次进度更新重复预取
+    let mut prefetched_for: Option<usize> = None;
+    // 上次保存续播位置时的播放位置（秒）
+    let mut resume_saved_position: u64 = 0;
+    let mut resume = ResumePositions::new();
+    // 播放历史（之前播放过的曲目路径，最近的在最后）和当前曲目的路径，回放越过曲目开头时据此找上一首
+    let mut played: Vec<String> = Vec::new();
+    let mut playing_path: Option<String> = None;
 
     let runtime = tokio::runtime::Builder::new_current_thread()
         .enable_all()
@@ -174,14 +933,69 @@ fn run_player_thread(
 
     runtime.block_on(async move {
         let mut progress_interval = tokio::time::interval(std::time::Duration::from_secs(1));
+        // 毫秒级进度事件：间隔可配置，未开启时不触发
+        let mut position_period = progress::settings().interval();
+        let mut position_interval = tokio::time::interval(position_period.unwrap_or(std::time::Duration::from_secs(1)));
+        position_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut last_position_sent: Option<(usize, u64)> = None;
 
         loop {
+            // 发布状态副本，播放器线程忙于处理命令时界面读取它而不必等待
+            publish_state(&state, &published);
+
             tokio::select! {
                 Some(cmd) = cmd_rx.recv() => {
                     let mut player_state_guard = state.lock().unwrap();
 
+                    // 当前曲目变化时把之前的曲目记入播放历史
+                    let current_path = player_state_guard.current_index.and_then(|idx| player_state_guard.playlist.get(idx)).map(|song| song.path.as_str());
+                    if current_path != playing_path.as_deref() {
+                        if let Some(previous) = std::mem::replace(&mut playing_path, current_path.map(str::to_owned)) {
+                            played.push(previous);
+                            if played.len() > PLAY_HISTORY_LEN {
+                                played.remove(0);
+                            }
+                        }
+                    }
+
+                    // 切歌、跳转、停止或更换输出设备时结束 A/B 对比，丢弃已排入的下一首
+                    // 暂停、停止时不再继续播报
+                    if matches!(
+                        cmd,
+                        PlayerCommand::Pause
+                            | PlayerCommand::Stop
+                            | PlayerCommand::ClearPlaylist
+                            | PlayerCommand::ForceStopAudio
+                            | PlayerCommand::ForceStopAll
+                    ) {
+                        if let Some(sink) = announcement.take() {
+                            sink.stop();
+                        }
+                    }
+                    // 切歌、跳转、停止、暂停前保存续播位置
+                    if replaces_sink(&cmd) || matches!(cmd, PlayerCommand::Pause) {
+                        if let Some(song) = player_state_guard.current_index.and_then(|idx| player_state_guard.playlist.get(idx)) {
+                            if current_position > 0 {
+                                resume.save(song, Some(current_position));
+                            }
+                        }
+                    }
+                    // 用户选择的输出设备（设备断开/恢复时内部重新打开输出不改变用户的选择）
+                    let user_output_choice = matches!(cmd, PlayerCommand::SetOutputDevice(_));
+                    if replaces_sink(&cmd) {
+                        advance_on_resume = false;
+                        chained_next = None;
+                        end_ab_compare(&mut ab_compare, &current_sink, player_state_guard.output_volume(), &player_thread_event_tx);
+                    }
+
                     match cmd {
                         PlayerCommand::Play => {
+                            if std::mem::take(&mut advance_on_resume) {
+                                if command_sender_for_internal_use.try_send(PlayerCommand::AutoAdvance).is_err() {
+                                    eprintln!("播放器线程: 无法发送内部 AutoAdvance 命令 (通道已满或已关闭)");
+                                }
+                                continue;
+                            }
                             match player_state_guard.state {
                                 PlayerState::Paused => {
                                     // 检查当前歌曲是否为视频
@@ -201,11 +1015,17 @@ fn run_player_thread(
                                         println!("🎵 恢复音频播放，当前音量: {}", player_state_guard.volume);
                                         
                                         // 确保音量不为0
-                                        let volume = if player_state_guard.volume <= 0.0 { 1.0 } else { player_state_guard.volume };
-                                        player_state_guard.volume = volume;
+                                        if player_state_guard.volume <= 0.0 {
+                                            player_state_guard.volume = 1.0;
+                                        }
+                                        let volume = player_state_guard.output_volume();
                                         
                                         sink.set_volume(volume); // 确保音量正确
                                         sink.play();
+                                        if let Some((sink_b, _)) = &ab_compare {
+                                            sink_b.play();
+                                        }
+                                        apply_ab_volume(&current_sink, &ab_compare, volume);
                                         player_state_guard.state = PlayerState::Playing;
                                         
                                         // 恢复播放时，记录新的开始时间，但考虑已经播放的时间
@@ -213,6 +1033,48 @@ fn run_player_thread(
                                         
                                         let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(player_state_guard.state));
                                         println!("✅ 音频播放已恢复，音量设置为: {}", volume);
+                                    } else if let Some(song) = player_state_guard.current_index.and_then(|idx| player_state_guard.playlist.get(idx)).cloned() {
+                                        // 音频设备已因空闲被释放：重新获取设备并从暂停位置继续播放
+                                        println!("🔊 音频设备已释放，重新获取并从{}秒处恢复播放", paused_position);
+                                        if player_state_guard.volume <= 0.0 {
+                                            player_state_guard.volume = 1.0;
+                                        }
+                                        let volume = player_state_guard.output_volume();
+                                        let album_gain = player_state_guard.live_album_mode;
+                                        let start = Some(std::time::Duration::from_secs(paused_position));
+                                        // 网络音源在缓冲期间被暂停：缓冲好后由 StreamBuffered 从暂停位置开始播放
+                                        let index = player_state_guard.current_index.unwrap_or_default();
+                                        if !ensure_buffered(index, &song, start, &player_thread_event_tx, &command_sender_for_internal_use) {
+                                            player_state_guard.state = PlayerState::Playing;
+                                            play_start_time = None;
+                                            let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(PlayerState::Playing));
+                                            continue;
+                                        }
+                                        drop(player_state_guard);
+
+                                        match build_source(&song, start, album_gain, &dsp, &glitch_tx, &player_thread_event_tx) {
+                                            Ok((source, _)) => match create_sink(&mut output_stream, &output_selection, Some(source.sample_rate()), &player_thread_event_tx) {
+                                                Ok(sink) => {
+                                                    sink.set_volume(volume);
+                                                    match pending_fade_in.take() {
+                                                        Some(fade) => sink.append(source.fade_in(fade)),
+                                                        None => sink.append(source),
+                                                    }
+                                                    sink.play();
+                                                    current_sink = Some(sink);
+                                                    play_start_time = Some(std::time::Instant::now() - std::time::Duration::from_secs(paused_position));
+
+                                                    state.lock().unwrap().state = PlayerState::Playing;
+                                                    let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(PlayerState::Playing));
+                                                }
+                                                Err(e) => {
+                                                    let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("无法创建音频sink: {}", e)));
+                                                }
+                                            },
+                                            Err(e) => {
+                                                let _ = player_thread_event_tx.try_send(PlayerEvent::Error(e.to_string()));
+                                            }
+                                        }
                                     }
                                 }
                                 _ => { // Stopped or new play
@@ -258,7 +1120,8 @@ fn run_player_thread(
                                         if let Some(duration) = song.duration {
                                             let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
                                                 position: 0, 
-                                                duration 
+                                                duration,
+                                                ab_loop: loop_bounds(&ab_loop),
                                             });
                                         }
                                     } else {
@@ -272,70 +1135,72 @@ fn run_player_thread(
                                         }
                                         
                                         // 确保音量不为0
-                                        let volume = if player_state_guard.volume <= 0.0 { 1.0 } else { player_state_guard.volume };
-                                        player_state_guard.volume = volume;
+                                        if player_state_guard.volume <= 0.0 {
+                                            player_state_guard.volume = 1.0;
+                                        }
+                                        let volume = player_state_guard.output_volume();
+                                        let album_gain = player_state_guard.live_album_mode;
                                         
+                                        if !ensure_buffered(index, &song, None, &player_thread_event_tx, &command_sender_for_internal_use) {
+                                            player_state_guard.state = PlayerState::Playing;
+                                            play_start_time = None;
+                                            let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(PlayerState::Playing));
+                                            let _ = player_thread_event_tx.try_send(PlayerEvent::SongChanged(index, song.clone()));
+                                            continue;
+                                        }
+
                                         drop(player_state_guard); // Release lock before IO
 
                                         // 播放音频文件
-                                        match std::fs::File::open(&song.path) {
-                                            Ok(file) => {
-                                                match rodio::Decoder::new(std::io::BufReader::new(file)) {
-                                                    Ok(source) => {
-                                                        match rodio::Sink::try_new(&stream_handle) {
-                                                            Ok(sink) => {
-                                                                println!("🔊 创建音频sink成功，设置音量: {}", volume);
-                                                                
-                                                                // 关键修复：先设置音量，再添加音源
-                                                                sink.set_volume(volume);
-                                                                
-                                                                // 关键修复：添加音源前确保sink处于正确状态
-                                                                sink.append(source);
-                                                                
-                                                                // 关键修复：立即设置为播放状态，避免默认暂停
-                                                                sink.play();
-                                                                
-                                                                // 重置播放进度和开始时间
-                                                                current_position = 0;
-                                                                play_start_time = Some(std::time::Instant::now());
-                                                                paused_position = 0;
-                                                                
-                                                                // 关键修复：立即更新状态为Playing，避免状态冲突
-                                                                let mut player_state_guard = state.lock().unwrap(); 
-                                                                player_state_guard.state = PlayerState::Playing;
-                                                                
-                                                                // 关键修复：确保sink已设置为播放状态后再保存引用
-                                                                current_sink = Some(sink);
-                                                                
-                                                                // 关键修复：立即发送Playing状态，避免暂停状态被发送
-                                                                let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(PlayerState::Playing));
-                                                                let _ = player_thread_event_tx.try_send(PlayerEvent::SongChanged(index, song.clone()));
-                                                                
-                                                                // 立即发送初始进度更新事件，确保前端进度条重置
-                                                                if let Some(duration) = song.duration {
-                                                                    let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
-                                                                        position: 0, 
-                                                                        duration 
-                                                                    });
-                                                                }
-                                                                
-                                                                println!("✅ 音频播放开始，音量: {}", volume);
-                                                            }
-                                                            Err(e) => {
-                                                                eprintln!("❌ 创建音频sink失败: {}", e);
-                                                                let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("无法创建音频sink: {}", e)));
-                                                            }
+                                        match build_source(&song, None, album_gain, &dsp, &glitch_tx, &player_thread_event_tx) {
+                                            Ok((source, start_ms)) => {
+                                                match create_sink(&mut output_stream, &output_selection, Some(source.sample_rate()), &player_thread_event_tx) {
+                                                    Ok(sink) => {
+                                                        println!("🔊 创建音频sink成功，设置音量: {}", volume);
+                                                        
+                                                        // 关键修复：先设置音量，再添加音源
+                                                        sink.set_volume(volume);
+                                                        sink.append(source);
+                                                        
+                                                        // 关键修复：立即设置为播放状态，避免默认暂停
+                                                        sink.play();
+                                                        
+                                                        // 重置播放进度和开始时间
+                                                        current_position = 0;
+                                                        play_start_time = Some(std::time::Instant::now() - std::time::Duration::from_millis(start_ms));
+                                                        paused_position = 0;
+                                                        
+                                                        // 关键修复：立即更新状态为Playing，避免状态冲突
+                                                        let mut player_state_guard = state.lock().unwrap(); 
+                                                        player_state_guard.state = PlayerState::Playing;
+                                                        
+                                                        // 关键修复：确保sink已设置为播放状态后再保存引用
+                                                        current_sink = Some(sink);
+                                                        
+                                                        // 关键修复：立即发送Playing状态，避免暂停状态被发送
+                                                        let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(PlayerState::Playing));
+                                                        let _ = player_thread_event_tx.try_send(PlayerEvent::SongChanged(index, song.clone()));
+                                                        
+                                                        // 立即发送初始进度更新事件，确保前端进度条重置
+                                                        if let Some(duration) = song.duration {
+                                                            let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
+                                                                position: 0, 
+                                                                duration,
+                                                                ab_loop: loop_bounds(&ab_loop),
+                                                            });
                                                         }
+                                                        
+                                                        println!("✅ 音频播放开始，音量: {}", volume);
                                                     }
                                                     Err(e) => {
-                                                        eprintln!("❌ 音频解码失败: {}", e);
-                                                        let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("解码音频文件失败: {}", e)));
+                                                        eprintln!("❌ 创建音频sink失败: {}", e);
+                                                        let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("无法创建音频sink: {}", e)));
                                                     }
                                                 }
                                             }
                                             Err(e) => {
-                                                eprintln!("❌ 无法打开音频文件: {}", e);
-                                                let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("无法打开音频文件: {}", e)));
+                                                eprintln!("❌ {}", e);
+                                                let _ = player_thread_event_tx.try_send(PlayerEvent::Error(e.to_string()));
                                             }
                                         }
                                     }
@@ -349,6 +1214,15 @@ fn run_player_thread(
                                 continue;
                             }
                             
+                            // 暂停在曲间静音中时不再自动切到下一首，恢复播放时再切歌
+                            if gap_deadline.take().is_some() {
+                                advance_on_resume = true;
+                                player_state_guard.state = PlayerState::Paused;
+                                let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(player_state_guard.state));
+                                println!("⏸️ 在曲间静音中暂停，恢复播放时切到下一首");
+                                continue;
+                            }
+
                             // 检查当前歌曲是否为视频
                             let is_video = if let Some(idx) = player_state_guard.current_index {
                                 if let Some(song) = player_state_guard.playlist.get(idx) {
@@ -363,28 +1237,41 @@ fn run_player_thread(
                             } else if let Some(sink) = &current_sink {
                                 // 音频文件：正常处理
                                 sink.pause();
+                                if let Some((sink_b, _)) = &ab_compare {
+                                    sink_b.pause();
+                                }
                                 player_state_guard.state = PlayerState::Paused;
                                 
 
                                 // 保存当前播放位置用于恢复播放
-                                if let Some(start_time) = play_start_time {
-                                    paused_position = start_time.elapsed().as_secs();
+                                if play_start_time.is_some() {
+                                    paused_position = playback_position(&player_state_guard, play_start_time).as_secs();
                                     // 记录下来，但是不重置 play_start_time，我们会在恢复播放时调整它
                                 }
+                                player_state_guard.position = paused_position;
                                 
                                 let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(player_state_guard.state));
                                 println!("⏸️ 音频播放已暂停，位置: {}秒", paused_position);
+                            } else if player_state_guard.state == PlayerState::Playing {
+                                // 网络音源还在缓冲（或音频设备已释放）：只记下暂停状态和位置，恢复播放时再开始
+                                player_state_guard.state = PlayerState::Paused;
+                                player_state_guard.position = paused_position;
+                                let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(player_state_guard.state));
+                                println!("⏸️ 在缓冲期间暂停，位置: {}秒", paused_position);
                             }
                         }
                         PlayerCommand::Stop => {
+                            gap_deadline = None;
                             if let Some(sink) = current_sink.take() { 
                                 sink.stop();
                             }
+                            media_source::cancel_downloads(None);
                             player_state_guard.state = PlayerState::Stopped;
                             // player_state_guard.current_index = None; // Optionally reset index on stop
                             let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(player_state_guard.state));
                         }
-                        PlayerCommand::Next | PlayerCommand::Previous => {
+                        PlayerCommand::Next | PlayerCommand::Previous | PlayerCommand::AutoAdvance => {
+                            gap_deadline = None;
                             if player_state_guard.playlist.is_empty() {
                                 let _ = player_thread_event_tx.try_send(PlayerEvent::Error("播放列表为空".to_string()));
                                 continue;
@@ -396,46 +1283,12 @@ fn run_player_thread(
                                 println!("切歌操作：停止所有音频播放");
                             }
 
-                            let current_idx_opt = player_state_guard.current_index;
                             let playlist_len = player_state_guard.playlist.len();
-                            let play_mode = player_state_guard.play_mode;
-
-                            let new_index = match cmd {
-                                PlayerCommand::Next => match (current_idx_opt, play_mode) {
-                                    (Some(idx), PlayMode::Sequential) => if idx + 1 >= playlist_len { 0 } else { idx + 1 },
-                                    (Some(idx), PlayMode::Repeat) => idx,
-                                    (Some(_), PlayMode::Shuffle) => {
-                                        // 随机模式：确保不重复选择当前歌曲（除非只有一首歌）
-//                                        if playlist_len == 1 {
-//                                            0
-//                                        } else {
-                                            let mut new_idx = rand::thread_rng().gen_range(0..playlist_len);
-                                            while Some(new_idx) == current_idx_opt {
-                                                new_idx = rand::thread_rng().gen_range(0..playlist_len);
-                                            }
-                                            new_idx
-//                                        }
-                                    },
-                                    (None, _) => 0,
-                                },
-                                PlayerCommand::Previous => match (current_idx_opt, play_mode) {
-                                    (Some(idx), PlayMode::Sequential) => if idx == 0 { playlist_len.saturating_sub(1) } else { idx - 1 },
-                                    (Some(idx), PlayMode::Repeat) => idx,
-                                    (Some(_), PlayMode::Shuffle) => {
-                                        // 随机模式：确保不重复选择当前歌曲（除非只有一首歌）
-//                                        if playlist_len == 1 {
-//                                            0
-//                                        } else {
-                                            let mut new_idx = rand::thread_rng().gen_range(0..playlist_len);
-                                            while Some(new_idx) == current_idx_opt {
-                                                new_idx = rand::thread_rng().gen_range(0..playlist_len);
-                                            }
-                                            new_idx
-//                                        }
-                                    },
-                                    (None, _) => playlist_len.saturating_sub(1),
-                                },
-                                _ => unreachable!(),
+                            let forward = !matches!(cmd, PlayerCommand::Previous);
+                            let auto = matches!(cmd, PlayerCommand::AutoAdvance);
+                            let new_index = match player_state_guard.play_mode {
+                                PlayMode::Shuffle => shuffle_step(&mut player_state_guard, forward),
+                                _ => step_index(&player_state_guard, forward, auto),
                             };
 
                             if playlist_len == 0 {
@@ -444,12 +1297,22 @@ fn run_player_thread(
                                 let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(player_state_guard.state));
                                 continue;
                             }
+                            // 不循环模式下最后一首之后没有下一首：停止播放
+                            let Some(new_index) = new_index else {
+                                player_state_guard.state = PlayerState::Stopped;
+                                play_start_time = None;
+                                let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(player_state_guard.state));
+                                let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistEnded);
+                                continue;
+                            };
 
                             // 获取新歌曲信息
                             player_state_guard.current_index = Some(new_index);
-                            let song = player_state_guard.playlist[new_index].clone();
+                            let mut song = player_state_guard.playlist[new_index].clone();
+                            song.resume_position = resume.position(&song);
                             let is_video = song.media_type == Some(crate::player_fixed::MediaType::Video);
                             let current_playback_mode = player_state_guard.current_playback_mode;
+                            let album_gain = player_state_guard.live_album_mode;
                             
                             // 重置播放进度
                             current_position = 0;
@@ -471,7 +1334,8 @@ fn run_player_thread(
                             if let Some(duration) = song.duration {
                                 let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
                                     position: 0, 
-                                    duration 
+                                    duration,
+                                    ab_loop: loop_bounds(&ab_loop),
                                 });
                             }
                             
@@ -487,30 +1351,34 @@ fn run_player_thread(
 
                             if should_play_audio {
                                 // 播放音频文件
-                                match std::fs::File::open(&song.path) {
-                                    Ok(file) => match rodio::Decoder::new(std::io::BufReader::new(file)) {
-                                        Ok(source) => match rodio::Sink::try_new(&stream_handle) {
-                                            Ok(sink) => {
-                                                // 关键修复：确保音频立即处于播放状态
-                                                sink.append(source);
-                                                sink.play();
-                                                current_sink = Some(sink);
-                                                
-                                                // 设置播放开始时间
-                                                play_start_time = Some(std::time::Instant::now());
+                                // 开启续播的曲目从上次的位置继续
+                                let start = song.resume_position.map(std::time::Duration::from_millis);
+                                if !ensure_buffered(new_index, &song, start, &player_thread_event_tx, &command_sender_for_internal_use) {
+                                    play_start_time = None;
+                                    paused_position = start.map_or(0, |start| start.as_secs());
+                                    continue;
+                                }
+                                match build_source(&song, start, album_gain, &dsp, &glitch_tx, &player_thread_event_tx) {
+                                    Ok((source, start_ms)) => match create_sink(&mut output_stream, &output_selection, Some(source.sample_rate()), &player_thread_event_tx) {
+                                        Ok(sink) => {
+                                            sink.set_volume(state.lock().unwrap().output_volume());
+                                            // 关键修复：确保音频立即处于播放状态
+                                            sink.append(source);
+                                            sink.play();
+                                            current_sink = Some(sink);
+                                            
+                                            // 设置播放开始时间
+                                            play_start_time = Some(std::time::Instant::now() - std::time::Duration::from_millis(start_ms));
+                                            current_position = start_ms / 1000;
 
-                                                println!("音频文件切换完成并开始播放: {}", song.title.as_deref().unwrap_or("未知"));
-                                            }
-                                            Err(e) => { 
-                                                let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("无法创建音频sink: {}", e))); 
-                                            }
-                                        },
+                                            println!("音频文件切换完成并开始播放: {}", song.title.as_deref().unwrap_or("未知"));
+                                        }
                                         Err(e) => { 
-                                            let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("解码音频文件失败: {}", e))); 
+                                            let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("无法创建音频sink: {}", e))); 
                                         }
                                     },
                                     Err(e) => { 
-                                        let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("无法打开音频文件: {}", e))); 
+                                        let _ = player_thread_event_tx.try_send(PlayerEvent::Error(e.to_string())); 
                                     }
                                 }
                             } else {
@@ -522,15 +1390,38 @@ fn run_player_thread(
                                 println!("用户选择视频文件，等待前端VideoPlayer开始播放: {}", song.title.as_deref().unwrap_or("未知"));
                             }
                         }
-                        PlayerCommand::SetSong(index) => {
+                        PlayerCommand::StreamBuffered { index, path, position_ms } => {
+                            // 缓冲期间切歌、暂停或停止则不再开始播放；暂停的曲目留到 Play 时从暂停位置开始
+                            let waiting = current_sink.is_none()
+                                && player_state_guard.state == PlayerState::Playing
+                                && player_state_guard.current_index == Some(index)
+                                && player_state_guard.playlist.get(index).is_some_and(|song| song.path == path);
+                            if waiting {
+                                let cmd = match position_ms {
+                                    Some(position_ms) => PlayerCommand::SetSongAt { index, position_ms },
+                                    None => PlayerCommand::SetSong(index),
+                                };
+                                if command_sender_for_internal_use.try_send(cmd).is_err() {
+                                    eprintln!("播放器线程: 无法发送内部 SetSong 命令 (通道已满或已关闭)");
+                                }
+                            }
+                        }
+                        PlayerCommand::SetSong(index) | PlayerCommand::SetSongAt { index, .. } => {
+                            let start_at = match cmd {
+                                PlayerCommand::SetSongAt { position_ms, .. } => Some(position_ms),
+                                _ => None,
+                            };
                             if index >= player_state_guard.playlist.len() {
                                 let _ = player_thread_event_tx.try_send(PlayerEvent::Error("无效的歌曲索引".to_string()));
                                 continue;
                             }
+                            gap_deadline = None;
                             
                             player_state_guard.current_index = Some(index);
-                            let song = player_state_guard.playlist[index].clone();
+                            let mut song = player_state_guard.playlist[index].clone();
+                            song.resume_position = resume.position(&song);
                             let is_video = song.media_type == Some(crate::player_fixed::MediaType::Video);
+                            let album_gain = player_state_guard.live_album_mode;
                             
                             // 重置播放进度
                             current_position = 0;
@@ -549,7 +1440,8 @@ fn run_player_thread(
                             if let Some(duration) = song.duration {
                                 let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
                                     position: 0, 
-                                    duration 
+                                    duration,
+                                    ab_loop: loop_bounds(&ab_loop),
                                 });
                             }
                             
@@ -557,63 +1449,283 @@ fn run_player_thread(
 
                             if !is_video {
                                 // 音频文件：正常播放
-                                match std::fs::File::open(&song.path) {
-                                    Ok(file) => match rodio::Decoder::new(std::io::BufReader::new(file)) {
-                                        Ok(source) => match rodio::Sink::try_new(&stream_handle) {
-                                            Ok(sink) => {
-                                                // 关键修复：确保音频立即处于播放状态
-                                                sink.append(source);
-                                                sink.play();
-                                                current_sink = Some(sink);
-                                                
-                                                // 设置播放开始时间
-                                                play_start_time = Some(std::time::Instant::now());
+                                // 开启续播的曲目从上次的位置继续，SetSongAt 从指定位置开始
+                                let start = start_at.or(song.resume_position).map(std::time::Duration::from_millis);
+                                if !ensure_buffered(index, &song, start, &player_thread_event_tx, &command_sender_for_internal_use) {
+                                    play_start_time = None;
+                                    // 缓冲期间暂停时从这里恢复
+                                    paused_position = start.map_or(0, |start| start.as_secs());
+                                    continue;
+                                }
+                                match build_source(&song, start, album_gain, &dsp, &glitch_tx, &player_thread_event_tx) {
+                                    Ok((source, start_ms)) => match create_sink(&mut output_stream, &output_selection, Some(source.sample_rate()), &player_thread_event_tx) {
+                                        Ok(sink) => {
+                                            sink.set_volume(state.lock().unwrap().output_volume());
+                                            // 关键修复：确保音频立即处于播放状态
+                                            sink.append(source);
+                                            sink.play();
+                                            current_sink = Some(sink);
+                                            
+                                            // 设置播放开始时间
+                                            play_start_time = Some(std::time::Instant::now() - std::time::Duration::from_millis(start_ms));
+                                            current_position = start_ms / 1000;
 
-                                                println!("音频文件切换完成并开始播放: {}", song.title.as_deref().unwrap_or("未知"));
-                                            }
-                                            Err(e) => { 
-                                                let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("无法创建音频sink: {}", e))); 
-                                            }
-                                        },
+                                            println!("音频文件切换完成并开始播放: {}", song.title.as_deref().unwrap_or("未知"));
+                                        }
                                         Err(e) => { 
-                                            let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("解码音频文件失败: {}", e))); 
+                                            let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("无法创建音频sink: {}", e))); 
                                         }
                                     },
                                     Err(e) => { 
-                                        let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("无法打开音频文件: {}", e))); 
+                                        let _ = player_thread_event_tx.try_send(PlayerEvent::Error(e.to_string())); 
+                                    }
+                                }
+                            } else {
+                                // 视频文件：清理可能存在的音频sink
+                                if let Some(sink) = current_sink.take() {
+                                    sink.stop();
+                                }
+                                
+                                println!("用户选择视频文件，等待前端VideoPlayer开始播放: {}", song.title.as_deref().unwrap_or("未知"));
+                            }
+                        }
+                        PlayerCommand::AddSongs(songs) => {
+                            for song in songs {
+                                player_state_guard.playlist_mut().push(song);
+                            }
+                            if player_state_guard.current_index.is_none() && !player_state_guard.playlist.is_empty() {
+                                player_state_guard.current_index = Some(0);
+                            }
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.to_vec()));
+                        }
+                        PlayerCommand::Enqueue(songs) => {
+                            if songs.is_empty() {
+                                continue;
+                            }
+                            match player_state_guard.enqueue_policy {
+                                EnqueuePolicy::Append => {
+                                    player_state_guard.playlist_mut().extend(songs);
+                                    if player_state_guard.current_index.is_none() {
+                                        player_state_guard.current_index = Some(0);
+                                    }
+                                    let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.to_vec()));
+                                }
+                                EnqueuePolicy::PlayNow => {
+                                    let first_new = player_state_guard.playlist.len();
+                                    player_state_guard.playlist_mut().extend(songs);
+                                    let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.to_vec()));
+                                    if command_sender_for_internal_use.try_send(PlayerCommand::SetSong(first_new)).is_err() {
+                                        eprintln!("播放器线程: 无法发送内部 SetSong 命令 (通道已满或已关闭)");
+                                    }
+                                }
+                                EnqueuePolicy::Replace => {
+                                    gap_deadline = None;
+                                    if let Some(sink) = current_sink.take() {
+                                        sink.stop();
+                                    }
+                                    player_state_guard.replace_playlist(songs);
+                                    player_state_guard.current_index = Some(0);
+                                    player_state_guard.active_playlist = None;
+                                    let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.to_vec()));
+                                    if command_sender_for_internal_use.try_send(PlayerCommand::SetSong(0)).is_err() {
+                                        eprintln!("播放器线程: 无法发送内部 SetSong 命令 (通道已满或已关闭)");
+                                    }
+                                }
+                            }
+                        }
+                        PlayerCommand::SetEnqueuePolicy(policy) => {
+                            player_state_guard.enqueue_policy = policy;
+                            println!("➕ 添加歌曲策略已设置为: {:?}", policy);
+                        }
+                        PlayerCommand::InsertSongs { index, songs } => {
+                            let index = index.min(player_state_guard.playlist.len());
+                            let count = songs.len();
+                            player_state_guard.playlist_mut().splice(index..index, songs);
+                            // 插入位置在当前歌曲之前（或就是当前位置）时，当前索引随之后移
+                            if let Some(current_idx) = player_state_guard.current_index {
+                                if index <= current_idx {
+                                    player_state_guard.current_index = Some(current_idx + count);
+                                }
+                            } else if !player_state_guard.playlist.is_empty() {
+                                player_state_guard.current_index = Some(0);
+                            }
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.to_vec()));
+                        }
+                        PlayerCommand::SetEntryStyle { indices, color, group } => {
+                            for index in indices {
+                                if let Some(song) = Arc::make_mut(&mut player_state_guard.playlist).get_mut(index) {
+                                    song.color = color.clone();
+                                    song.group = group.clone();
+                                }
+                            }
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.to_vec()));
+                        }
+                        PlayerCommand::CompactPlaylist => {
+                            let playlist = Arc::make_mut(&mut player_state_guard.playlist);
+                            *playlist = std::mem::take(playlist).into_iter().map(low_memory::slim).collect();
+                            println!("🗜️ 已精简播放列表条目: {}首", playlist.len());
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.to_vec()));
+                        }
+                        PlayerCommand::ApplyCover { path, cover } => {
+                            let playlist = Arc::make_mut(&mut player_state_guard.playlist);
+                            for (index, entry) in playlist.iter_mut().enumerate().filter(|(_, s)| s.path == path) {
+                                entry.album_cover = cover.clone();
+                                let _ = player_thread_event_tx.try_send(PlayerEvent::SongMetadataUpdated(index, entry.clone()));
+                            }
+                        }
+                        PlayerCommand::RefreshSong(song) => {
+                            // 容器中的音轨/章节有各自的标题，不随文件标签更新；保留用户设置的颜色/分组
+                            let playlist = Arc::make_mut(&mut player_state_guard.playlist);
+                            for (index, entry) in playlist.iter_mut().enumerate().filter(|(_, s)| s.path == song.path && s.segment.is_none()) {
+                                *entry = SongInfo {
+                                    color: entry.color.take(),
+                                    group: entry.group.take(),
+                                    ..song.clone()
+                                };
+                                let _ = player_thread_event_tx.try_send(PlayerEvent::SongMetadataUpdated(index, entry.clone()));
+                            }
+                        }
+                        PlayerCommand::ResolvePlaceholder { path, songs } => {
+                            // 占位条目可能已被移除或移动，按路径查找；同一文件添加了多次时全部替换
+                            let positions: Vec<usize> = player_state_guard
+                                .playlist
+                                .iter()
+                                .enumerate()
+                                .filter(|(_, s)| s.path == path && s.is_placeholder())
+                                .map(|(i, _)| i)
+                                .collect();
+                            if positions.is_empty() || songs.is_empty() {
+                                continue;
+                            }
+                            if songs.len() == 1 {
+                                for index in positions {
+                                    let playlist = Arc::make_mut(&mut player_state_guard.playlist);
+                                    // 保留用户在占位期间设置的颜色/分组
+                                    let song = SongInfo {
+                                        color: playlist[index].color.clone(),
+                                        group: playlist[index].group.clone(),
+                                        ..songs[0].clone()
+                                    };
+                                    playlist[index] = song.clone();
+                                    let _ = player_thread_event_tx.try_send(PlayerEvent::SongMetadataUpdated(index, song));
+                                }
+                            } else {
+                                // 展开为多个条目（多音轨/多章节），从后往前替换，前面的索引不受影响
+                                let extra = songs.len() - 1;
+                                for index in positions.into_iter().rev() {
+                                    player_state_guard.playlist_mut().splice(index..=index, songs.iter().cloned());
+                                    if let Some(current_idx) = player_state_guard.current_index.filter(|current| *current > index) {
+                                        player_state_guard.current_index = Some(current_idx + extra);
                                     }
                                 }
-                            } else {
-                                // 视频文件：清理可能存在的音频sink
-                                if let Some(sink) = current_sink.take() {
-                                    sink.stop();
+                                let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.to_vec()));
+                            }
+                        }
+                        PlayerCommand::ApplyRating { path, rating, favorite } => {
+                            for song in Arc::make_mut(&mut player_state_guard.playlist).iter_mut().filter(|s| s.path == path) {
+                                song.rating = rating;
+                                song.favorite = favorite;
+                            }
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.to_vec()));
+                        }
+                        PlayerCommand::ApplyResumeEnabled { path, enabled } => {
+                            // 关闭时音乐库已清除保存的位置
+                            for song in Arc::make_mut(&mut player_state_guard.playlist).iter_mut().filter(|s| s.path == path) {
+                                song.resume_enabled = enabled;
+                                if !enabled {
+                                    song.resume_position = None;
                                 }
-                                
-                                println!("用户选择视频文件，等待前端VideoPlayer开始播放: {}", song.title.as_deref().unwrap_or("未知"));
+                            }
+                            if !enabled {
+                                resume.forget(&path);
                             }
                         }
-                        PlayerCommand::AddSongs(songs) => {
-                            for song in songs {
-                                player_state_guard.playlist.push(song);
+                        PlayerCommand::ApplyTrimPoints { path, trim } => {
+                            // 起止点针对整个文件，不作用于容器中的音轨/章节
+                            for song in Arc::make_mut(&mut player_state_guard.playlist).iter_mut().filter(|s| s.path == path && s.segment.is_none()) {
+                                song.trim = trim;
                             }
-                            if player_state_guard.current_index.is_none() && !player_state_guard.playlist.is_empty() {
-                                player_state_guard.current_index = Some(0);
+                        }
+                        PlayerCommand::ApplyMetadata { path, metadata } => {
+                            // 容器中的音轨/章节有各自的标题，不随文件元数据更新
+                            for song in Arc::make_mut(&mut player_state_guard.playlist).iter_mut().filter(|s| s.path == path && s.segment.is_none()) {
+                                song.apply_metadata(&metadata);
                             }
-                            let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.clone()));
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.to_vec()));
                         }
                         PlayerCommand::AddSong(song_info) => {
-                            player_state_guard.playlist.push(song_info.clone());
+                            player_state_guard.playlist_mut().push(song_info.clone());
                             if player_state_guard.playlist.len() == 1 {
                                 player_state_guard.current_index = Some(0);
                             }
-                            let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.clone()));
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.to_vec()));
+                        }
+                        PlayerCommand::MoveSong { from, to } => {
+                            let len = player_state_guard.playlist.len();
+                            if from >= len || to >= len {
+                                let _ = player_thread_event_tx.try_send(PlayerEvent::Error("无效的歌曲索引".to_string()));
+                                continue;
+                            }
+                            if from == to {
+                                continue;
+                            }
+                            let playlist = player_state_guard.playlist_mut();
+                            let song = playlist.remove(from);
+                            playlist.insert(to, song);
+                            // 随机播放顺序跟随移动后的索引，不重新打乱
+                            let mut order: Vec<usize> = (0..len).collect();
+                            let moved = order.remove(from);
+                            order.insert(to, moved);
+                            let state = &mut *player_state_guard;
+                            state.shuffle.remap(&order, state.playlist_generation);
+                            // 当前歌曲被移动时跟随到新位置；其他歌曲移过当前歌曲时当前索引前后移一位
+                            if let Some(current_idx) = player_state_guard.current_index {
+                                let new_idx = if current_idx == from {
+                                    to
+                                } else if from < current_idx && to >= current_idx {
+                                    current_idx - 1
+                                } else if from > current_idx && to <= current_idx {
+                                    current_idx + 1
+                                } else {
+                                    current_idx
+                                };
+                                player_state_guard.current_index = Some(new_idx);
+                            }
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.to_vec()));
+                        }
+                        PlayerCommand::SortPlaylist { field: SortField::DateAdded, direction } => {
+                            // 加入时间需要查询音乐库和文件，在后台线程读取后再排序
+                            let generation = player_state_guard.playlist_generation;
+                            let paths: Vec<String> = player_state_guard.playlist.iter().map(|song| song.path.clone()).collect();
+                            let command_tx = command_sender_for_internal_use.clone();
+                            std::thread::spawn(move || {
+                                let added = playlist_added_times(&paths);
+                                if command_tx.blocking_send(PlayerCommand::SortPlaylistByDateAdded { generation, direction, added }).is_err() {
+                                    eprintln!("播放器线程已退出，放弃按加入时间排序");
+                                }
+                            });
+                        }
+                        PlayerCommand::SortPlaylistByDateAdded { generation, direction, added } => {
+                            // 读取期间播放列表已变化时放弃这次排序
+                            if generation != player_state_guard.playlist_generation {
+                                eprintln!("播放列表已变化，放弃按加入时间排序");
+                                continue;
+                            }
+                            let order = playlist_sort_order(&player_state_guard.playlist, SortField::DateAdded, direction, &added);
+                            player_state_guard.reorder_playlist(&order);
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.to_vec()));
+                        }
+                        PlayerCommand::SortPlaylist { field, direction } => {
+                            let order = playlist_sort_order(&player_state_guard.playlist, field, direction, &[]);
+                            player_state_guard.reorder_playlist(&order);
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.to_vec()));
                         }
                         PlayerCommand::RemoveSong(index) => {
                             if index >= player_state_guard.playlist.len() {
                                 let _ = player_thread_event_tx.try_send(PlayerEvent::Error("无效的歌曲索引".to_string()));
                                 continue;
                             }
-                            player_state_guard.playlist.remove(index);
+                            player_state_guard.playlist_mut().remove(index);
 
                             let mut stopped_playing = false;
                             if let Some(current_idx) = player_state_guard.current_index {
@@ -642,7 +1754,7 @@ fn run_player_thread(
                                     player_state_guard.current_index = Some(current_idx - 1);
                                 }
                             }
-                            let playlist_clone = player_state_guard.playlist.clone();
+                            let playlist_clone = player_state_guard.playlist.to_vec();
                             let current_state = player_state_guard.state;
                             drop(player_state_guard);
 
@@ -652,27 +1764,426 @@ fn run_player_thread(
                             let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(playlist_clone));
                         }
                         PlayerCommand::ClearPlaylist => {
+                            gap_deadline = None;
                             if let Some(sink) = current_sink.take() {
                                 sink.stop();
                             }
-                            player_state_guard.playlist.clear();
+                            media_source::cancel_downloads(None);
+                            player_state_guard.replace_playlist(Vec::new());
                             player_state_guard.current_index = None;
+                            player_state_guard.active_playlist = None;
                             player_state_guard.state = PlayerState::Stopped;
                             let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(player_state_guard.state));
-                            let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.clone()));
-                        }                        PlayerCommand::SetPlayMode(mode) => {
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.to_vec()));
+                        }
+                        PlayerCommand::CleanupPlaylist { options, reply } => {
+                            // 清理要检查文件是否存在，在锁外进行；状态只由本线程修改，期间不会变化
+                            let playlist = player_state_guard.playlist.to_vec();
+                            let old_index = player_state_guard.current_index;
+                            drop(player_state_guard);
+                            let (kept, new_index, summary) = crate::playlist_tools::cleanup(playlist, old_index, &options);
+                            let mut player_state_guard = state.lock().unwrap();
+                            player_state_guard.replace_playlist(kept);
+
+                            if old_index.is_some() && new_index.is_none() {
+                                // 正在播放的歌曲被清理掉了：停止播放
+                                gap_deadline = None;
+                                if let Some(sink) = current_sink.take() {
+                                    sink.stop();
+                                }
+                                player_state_guard.current_index = if player_state_guard.playlist.is_empty() { None } else { Some(0) };
+                                player_state_guard.state = PlayerState::Stopped;
+                                let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(player_state_guard.state));
+                            } else {
+                                player_state_guard.current_index = new_index;
+                            }
+
+                            println!("🧹 播放列表清理完成：移除{}项，剩余{}项", summary.total_removed(), summary.remaining);
+                            // 所有清理只发送一次播放列表更新
+                            if summary.total_removed() > 0 {
+                                let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.to_vec()));
+                            }
+                            let _ = reply.send(summary);
+                        }
+                        PlayerCommand::LoadPlaylist { name, songs, index, position } => {
+                            // 切换到已保存的播放列表：替换当前列表，并停在该列表上次的歌曲和位置
+                            gap_deadline = None;
+                            if let Some(sink) = current_sink.take() {
+                                sink.stop();
+                            }
+                            let index = index.filter(|idx| *idx < songs.len()).or(if songs.is_empty() { None } else { Some(0) });
+                            let position = if index.is_some() { position } else { 0 };
+
+                            player_state_guard.replace_playlist(songs);
+                            player_state_guard.current_index = index;
+                            player_state_guard.active_playlist = name.clone();
+                            player_state_guard.position = position;
+                            // 暂停状态且没有sink，再次播放时会从 paused_position 处恢复
+                            player_state_guard.state = if index.is_some() { PlayerState::Paused } else { PlayerState::Stopped };
+                            current_position = position;
+                            paused_position = position;
+                            play_start_time = None;
+
+                            println!("📂 已载入播放列表 {:?}，恢复到第{:?}首 {}秒", name, index, position);
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistUpdated(player_state_guard.playlist.to_vec()));
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(player_state_guard.state));
+                            if let Some(idx) = index {
+                                let song = player_state_guard.playlist[idx].clone();
+                                let _ = player_thread_event_tx.try_send(PlayerEvent::SongChanged(idx, song.clone()));
+                                if let Some(duration) = song.duration {
+                                    let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { position, duration, ab_loop: loop_bounds(&ab_loop) });
+                                }
+                            }
+                        }
+                        PlayerCommand::ResumeWithFadeIn(secs) => {
+                            // 只在需要重新获取音频设备时渐入（如启动后恢复会话），已有sink时直接继续
+                            if secs > 0.0 && current_sink.is_none() {
+                                pending_fade_in = Some(std::time::Duration::from_secs_f32(secs.min(10.0)));
+                            }
+                            if command_sender_for_internal_use.try_send(PlayerCommand::Play).is_err() {
+                                eprintln!("播放器线程: 无法发送内部 Play 命令 (通道已满或已关闭)");
+                            }
+                        }
+                        PlayerCommand::SetActivePlaylist(name) => {
+                            player_state_guard.active_playlist = name;
+                        }
+                        PlayerCommand::SetPlayMode(mode) => {
+                            // 开启随机播放时打乱整个列表，当前曲目视为本轮已播放
+                            if mode == PlayMode::Shuffle && player_state_guard.play_mode != PlayMode::Shuffle {
+                                let state = &mut *player_state_guard;
+                                state.shuffle.reset(state.playlist.len(), state.playlist_generation, state.current_index);
+                            }
                             player_state_guard.play_mode = mode;
                         },
+                        PlayerCommand::SetIdleReleaseTimeout(secs) => {
+                            player_state_guard.idle_release_secs = secs;
+                            println!("🔊 空闲释放音频设备超时已设置为: {}秒", secs);
+                        },
+                        PlayerCommand::ReplayLast(seconds) => {
+                            // 回退指定秒数并继续播放，不足时跳到播放列表上一首的结尾部分
+                            let current_idx = match player_state_guard.current_index {
+                                Some(idx) => idx,
+                                None => continue,
+                            };
+                            let position = match play_start_time {
+                                Some(_) if player_state_guard.state == PlayerState::Playing => playback_position(&player_state_guard, play_start_time).as_secs(),
+                                _ => paused_position,
+                            };
+                            // 上一首是播放历史中最近播放过、仍在播放列表中的曲目（随机模式、跳播后不一定是列表中的前一首）
+                            let previous = (position < seconds)
+                                .then(|| {
+                                    while let Some(path) = played.pop() {
+                                        if let Some(index) = player_state_guard.playlist.iter().position(|song| song.path == path) {
+                                            return Some(index);
+                                        }
+                                    }
+                                    None
+                                })
+                                .flatten();
+                            let targets = match previous {
+                                Some(previous) => {
+                                    // 切到上一首，从离结尾还差不足部分的位置开始播放；回到上一首不算作新的播放历史
+                                    let remaining = seconds - position;
+                                    playing_path = Some(player_state_guard.playlist[previous].path.clone());
+                                    vec![match player_state_guard.playlist[previous].duration {
+                                        Some(duration) => PlayerCommand::SetSongAt {
+                                            index: previous,
+                                            position_ms: duration.saturating_sub(remaining) * 1000,
+                                        },
+                                        None => PlayerCommand::SetSong(previous),
+                                    }]
+                                }
+                                // 暂停/停止时回放也要继续播放：SeekTo 保持暂停状态，之后再恢复播放
+                                None => match player_state_guard.state {
+                                    PlayerState::Playing => vec![PlayerCommand::SeekTo(position.saturating_sub(seconds))],
+                                    PlayerState::Paused => vec![PlayerCommand::SeekTo(position.saturating_sub(seconds)), PlayerCommand::Play],
+                                    _ => vec![PlayerCommand::SetSongAt {
+                                        index: current_idx,
+                                        position_ms: position.saturating_sub(seconds) * 1000,
+                                    }],
+                                },
+                            };
+                            for target in targets {
+                                if command_sender_for_internal_use.try_send(target).is_err() {
+                                    eprintln!("⚠️ 回放时无法发送跳转命令");
+                                }
+                            }
+                            println!("⏪ 回放最近{}秒", seconds);
+                        },
+                        PlayerCommand::SkipBy(offset) => {
+                            // 在当前曲目内快进/快退，保持播放或暂停状态；快进越过结尾时切到下一首
+                            let Some(idx) = player_state_guard.current_index else { continue };
+                            let position = match play_start_time {
+                                Some(_) if player_state_guard.state == PlayerState::Playing => playback_position(&player_state_guard, play_start_time).as_secs(),
+                                _ => paused_position,
+                            };
+                            let target = position.saturating_add_signed(offset);
+                            let seek = match player_state_guard.playlist[idx].duration {
+                                Some(duration) if target >= duration => PlayerCommand::Next,
+                                _ => PlayerCommand::SeekTo(target),
+                            };
+                            if command_sender_for_internal_use.try_send(seek).is_err() {
+                                eprintln!("⚠️ 快进/快退时无法发送跳转命令");
+                            }
+                        },
+                        PlayerCommand::NextChapter | PlayerCommand::PreviousChapter | PlayerCommand::SeekToChapter(_) => {
+                            let Some(idx) = player_state_guard.current_index else { continue };
+                            let chapters = &player_state_guard.playlist[idx].chapters;
+                            if chapters.is_empty() {
+                                let _ = player_thread_event_tx.try_send(PlayerEvent::Error("当前曲目没有章节".to_string()));
+                                continue;
+                            }
+                            let position_ms = match play_start_time {
+                                Some(_) if player_state_guard.state == PlayerState::Playing => playback_position(&player_state_guard, play_start_time).as_millis() as u64,
+                                _ => paused_position * 1000,
+                            };
+                            let current = chapters.iter().rposition(|c| c.start_ms <= position_ms).unwrap_or(0);
+                            let target = match &cmd {
+                                PlayerCommand::NextChapter => chapters.get(current + 1).map(|_| current + 1),
+                                PlayerCommand::PreviousChapter if current > 0 && position_ms.saturating_sub(chapters[current].start_ms) < CHAPTER_RESTART_MS => Some(current - 1),
+                                PlayerCommand::SeekToChapter(chapter) if *chapter >= chapters.len() => {
+                                    let _ = player_thread_event_tx.try_send(PlayerEvent::Error("无效的章节索引".to_string()));
+                                    continue;
+                                }
+                                PlayerCommand::SeekToChapter(chapter) => Some(*chapter),
+                                _ => Some(current),
+                            };
+                            let seek = match target {
+                                Some(chapter) => PlayerCommand::SeekToMs(chapters[chapter].start_ms),
+                                None => PlayerCommand::Next,
+                            };
+                            if command_sender_for_internal_use.try_send(seek).is_err() {
+                                eprintln!("⚠️ 章节跳转时无法发送跳转命令");
+                            }
+                        },
+                        PlayerCommand::QueryPositionMs { reply } => {
+                            // 实际听到的位置：播放中扣除输出设备延迟，暂停时就是暂停位置
+                            let position_ms = match play_start_time {
+                                Some(_) if player_state_guard.state == PlayerState::Playing && current_sink.is_some() => playback_position(&player_state_guard, play_start_time)
+                                    .saturating_sub(std::time::Duration::from_millis(output_device::latency_ms()))
+                                    .as_millis() as u64,
+                                _ => player_state_guard.position * 1000,
+                            };
+                            let _ = reply.send(position_ms);
+                        },
+                        PlayerCommand::PlayCalibrationClicks { count, interval_ms, reply } => {
+                            // 在当前输出设备上播放等间隔的提示音，返回第一声的播放时间（Unix毫秒）
+                            drop(player_state_guard);
+                            let result = create_sink(&mut output_stream, &output_selection, None, &player_thread_event_tx).map(|sink| {
+                                let interval = std::time::Duration::from_millis(interval_ms.max(200));
+                                let click = std::time::Duration::from_millis(30);
+                                for _ in 0..count.max(1) {
+                                    sink.append(rodio::source::SineWave::new(1000.0).take_duration(click).amplify(0.5));
+                                    sink.append(rodio::source::Zero::<f32>::new(1, 48000).take_duration(interval - click));
+                                }
+                                sink.play();
+                                let started = std::time::SystemTime::now()
+                                    .duration_since(std::time::UNIX_EPOCH)
+                                    .map(|d| d.as_millis() as u64)
+                                    .unwrap_or(0);
+                                calibration_sink = Some(sink);
+                                started
+                            });
+                            let _ = reply.send(result.map_err(|e| e.to_string()));
+                        },
+                        PlayerCommand::StartAbCompare { path, reply } => {
+                            // A、B 两路从同一位置重新解码并同时开始，保证时间对齐
+                            let song_a = match player_state_guard.current_index.and_then(|idx| player_state_guard.playlist.get(idx)).cloned() {
+                                Some(song) if song.media_type != Some(MediaType::Video) && current_sink.is_some() => song,
+                                _ => {
+                                    let _ = reply.send(Err("当前没有正在播放的音频".to_string()));
+                                    continue;
+                                }
+                            };
+                            let was_playing = player_state_guard.state == PlayerState::Playing;
+                            let position = match play_start_time {
+                                Some(_) if was_playing => playback_position(&player_state_guard, play_start_time),
+                                _ => std::time::Duration::from_secs(paused_position),
+                            };
+                            let volume = player_state_guard.output_volume();
+                            drop(player_state_guard);
+                            end_ab_compare(&mut ab_compare, &current_sink, volume, &player_thread_event_tx);
+
+                            let result = SongInfo::from_path(std::path::Path::new(&path))
+                                .and_then(|song_b| {
+                                    let source_a = open_compare_source(&song_a, position, &dsp, &player_thread_event_tx)?;
+                                    let source_b = open_compare_source(&song_b, position, &dsp, &player_thread_event_tx)?;
+                                    // 两路共用当前输出设备，不按音源采样率重新打开
+                                    let sink_a = create_sink(&mut output_stream, &output_selection, None, &player_thread_event_tx)?;
+                                    let sink_b = create_sink(&mut output_stream, &output_selection, None, &player_thread_event_tx)?;
+                                    sink_a.pause();
+                                    sink_b.pause();
+                                    sink_a.append(source_a);
+                                    sink_b.append(source_b);
+                                    Ok((sink_a, sink_b))
+                                });
+                            match result {
+                                Ok((sink_a, sink_b)) => {
+                                    if let Some(sink) = current_sink.take() {
+                                        sink.stop();
+                                    }
+                                    if was_playing {
+                                        sink_a.play();
+                                        sink_b.play();
+                                        play_start_time = Some(std::time::Instant::now() - position);
+                                    } else {
+                                        paused_position = position.as_secs();
+                                    }
+                                    let compare = AbCompare {
+                                        path_a: song_a.path.clone(),
+                                        path_b: path,
+                                        active: AbSide::A,
+                                    };
+                                    current_sink = Some(sink_a);
+                                    ab_compare = Some((sink_b, compare.clone()));
+                                    apply_ab_volume(&current_sink, &ab_compare, volume);
+                                    println!("🅰️🅱️ 开始 A/B 对比: {} <-> {}", compare.path_a, compare.path_b);
+                                    let _ = player_thread_event_tx.try_send(PlayerEvent::AbCompareChanged(Some(compare.clone())));
+                                    let _ = reply.send(Ok(compare));
+                                }
+                                Err(e) => {
+                                    let _ = reply.send(Err(format!("无法开始A/B对比: {}", e)));
+                                }
+                            }
+                        },
+                        PlayerCommand::SwitchAb { reply } => {
+                            let volume = player_state_guard.output_volume();
+                            let result = match ab_compare.as_mut() {
+                                Some((_, compare)) => {
+                                    compare.active = match compare.active {
+                                        AbSide::A => AbSide::B,
+                                        AbSide::B => AbSide::A,
+                                    };
+                                    Ok(compare.clone())
+                                }
+                                None => Err("未在进行A/B对比".to_string()),
+                            };
+                            if let Ok(compare) = &result {
+                                apply_ab_volume(&current_sink, &ab_compare, volume);
+                                let _ = player_thread_event_tx.try_send(PlayerEvent::AbCompareChanged(Some(compare.clone())));
+                            }
+                            let _ = reply.send(result);
+                        },
+                        PlayerCommand::StopAbCompare => {
+                            end_ab_compare(&mut ab_compare, &current_sink, player_state_guard.output_volume(), &player_thread_event_tx);
+                        },
+                        PlayerCommand::SetAbLoop { start, end } => {
+                            let song = player_state_guard.current_index.and_then(|idx| player_state_guard.playlist.get(idx));
+                            let Some(song) = song else {
+                                let _ = player_thread_event_tx.try_send(PlayerEvent::Error("没有正在播放的歌曲".to_string()));
+                                continue;
+                            };
+                            let end = song.duration.map(|duration| end.min(duration)).unwrap_or(end);
+                            if start >= end {
+                                let _ = player_thread_event_tx.try_send(PlayerEvent::Error("循环终点必须在起点之后".to_string()));
+                                continue;
+                            }
+                            let bounds = AbLoop { start, end };
+                            ab_loop = Some((song.path.clone(), bounds));
+                            println!("🔁 A–B 循环: {}秒 - {}秒", start, end);
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::AbLoopChanged(Some(bounds)));
+                            // 当前位置不在区间内时从 A 点开始
+                            if (player_state_guard.position < start || player_state_guard.position >= end)
+                                && command_sender_for_internal_use.try_send(PlayerCommand::SeekTo(start)).is_err()
+                            {
+                                eprintln!("播放器线程: 无法发送内部 SeekTo 命令 (通道已满或已关闭)");
+                            }
+                        },
+                        PlayerCommand::ClearAbLoop => {
+                            if ab_loop.take().is_some() {
+                                println!("🔁 A–B 循环已清除");
+                                let _ = player_thread_event_tx.try_send(PlayerEvent::AbLoopChanged(None));
+                            }
+                        },
+                        PlayerCommand::StopAfterTracks(count) => {
+                            player_state_guard.stop_after_tracks = if count == 0 { None } else { Some(count) };
+                            println!("⏹️ 播完{}首后停止", count);
+                        },
+                        PlayerCommand::ClearPlaybackDiagnostics => {
+                            player_state_guard.diagnostics = PlaybackDiagnostics::default();
+                        },
+                        PlayerCommand::SetOutputDevice(selection) | PlayerCommand::ReopenOutput(selection) => {
+                            println!("🔊 切换音频输出设备: {:?}", selection);
+                            if let Some(sink) = calibration_sink.take() {
+                                sink.stop();
+                            }
+                            if user_output_choice {
+                                preferred_output = selection.clone();
+                                player_state_guard.output_device = selection.clone();
+                            }
+                            output_selection = selection;
+
+                            // 释放当前设备，下次创建sink时按新选择重新获取
+                            let was_playing = player_state_guard.state == PlayerState::Playing && current_sink.is_some();
+                            if let Some(sink) = current_sink.take() {
+                                if was_playing {
+                                    if play_start_time.is_some() {
+                                        paused_position = playback_position(&player_state_guard, play_start_time).as_secs();
+                                    }
+                                    player_state_guard.position = paused_position;
+                                    player_state_guard.state = PlayerState::Paused;
+                                }
+                                sink.stop();
+                            }
+                            // 关闭设备可能较慢，先释放状态锁
+                            drop(player_state_guard);
+                            output_stream = None;
+                            idle_since = None;
+
+                            // 正在播放时在新设备上从当前位置继续
+                            if was_playing && command_sender_for_internal_use.try_send(PlayerCommand::Play).is_err() {
+                                eprintln!("播放器线程: 无法发送内部 Play 命令 (通道已满或已关闭)");
+                            }
+                        },
+                        PlayerCommand::SetStopAtPlaylistEnd(enabled) => {
+                            // 与播放模式对应：开启即不循环，关闭时从不循环回到列表循环，其他模式不变
+                            match (enabled, player_state_guard.play_mode) {
+                                (true, PlayMode::RepeatAll) => player_state_guard.play_mode = PlayMode::NoRepeat,
+                                (false, PlayMode::NoRepeat) => player_state_guard.play_mode = PlayMode::RepeatAll,
+                                _ => {}
+                            }
+                            println!("⏹️ 播完列表后停止: {}", if enabled { "开启" } else { "关闭" });
+                        },
+                        PlayerCommand::SetLiveAlbumMode(enabled) => {
+                            player_state_guard.live_album_mode = enabled;
+                            println!("🎤 现场专辑连续播放: {}", if enabled { "开启" } else { "关闭" });
+                        },
+                        PlayerCommand::SetSilenceGap(secs) => {
+                            // 限制在0-5秒之间
+                            let secs = secs.max(0.0).min(5.0);
+                            player_state_guard.silence_gap_secs = secs;
+                            println!("⏱️ 曲间静音间隔已设置为: {}秒", secs);
+                        },
                         PlayerCommand::SetVolume(vol) => {
                             // 确保音量在合理范围内
                             let volume = vol.max(0.0).min(2.0); // 限制在0-2之间
                             player_state_guard.volume = volume;
+                            // 调节音量即取消静音
+                            player_state_guard.muted = false;
                             if let Some(sink) = &current_sink {
                                 sink.set_volume(volume);
                                 println!("🔊 音量已设置为: {}", volume);
                             }
+                            apply_ab_volume(&current_sink, &ab_compare, volume);
+                            if let Some(sink) = &announcement {
+                                sink.set_volume(volume);
+                            }
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::VolumeChanged { volume, muted: false });
+                        },
+                        PlayerCommand::SetMuted(muted) => {
+                            player_state_guard.muted = muted;
+                            let volume = player_state_guard.output_volume();
+                            if let Some(sink) = &current_sink {
+                                sink.set_volume(volume);
+                            }
+                            apply_ab_volume(&current_sink, &ab_compare, volume);
+                            if let Some(sink) = &announcement {
+                                sink.set_volume(volume);
+                            }
+                            println!("🔇 静音: {}", if muted { "开启" } else { "关闭" });
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::VolumeChanged { volume: player_state_guard.volume, muted });
                         },
-                        PlayerCommand::SeekTo(position_secs) => {
+                        PlayerCommand::SeekTo(position) | PlayerCommand::SeekToMs(position) => {
                             if let Some(current_idx) = player_state_guard.current_index {
                                 if let Some(song) = player_state_guard.playlist.get(current_idx) {
                                     //检查当前播放模式和歌曲类型
@@ -690,94 +2201,122 @@ fn run_player_thread(
                                     
                                     // 只有音频模式才处理SeekTo
                                     if let Some(duration) = song.duration {
-                                        let seek_position = position_secs.min(duration);
+                                        let target_ms = match cmd {
+                                            PlayerCommand::SeekToMs(_) => position,
+                                            _ => position.saturating_mul(1000),
+                                        };
+                                        let seek_offset = std::time::Duration::from_millis(target_ms.min(duration * 1000));
+                                        let seek_position = seek_offset.as_secs();
                                         
                                         println!("🎵 音频模式SeekTo: {}秒", seek_position);
                                         
                                         // 关键修复：在drop之前保存需要的状态值
                                         let was_playing = player_state_guard.state == PlayerState::Playing;
                                         let song_clone = song.clone();
+                                        let album_gain = player_state_guard.live_album_mode;
                                         let song_duration = duration; // 保存duration值
+                                        let previous_position = match play_start_time {
+                                            Some(_) if was_playing => playback_position(&player_state_guard, play_start_time).as_secs(),
+                                            _ => paused_position,
+                                        };
                                         
                                         // 立即发送进度更新事件，给用户即时反馈
                                         let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
                                             position: seek_position, 
-                                            duration: song_duration 
+                                            duration: song_duration,
+                                            ab_loop: loop_bounds(&ab_loop),
                                         });
                                         
                                         drop(player_state_guard);
+
+                                        // 记录跳过前奏的行为，多次跳过同一段时建议设置自动起始点
+                                        if let Some(suggestion) = intro_skip::observe_seek(&song_clone.path, previous_position, seek_position, song_duration) {
+                                            let _ = player_thread_event_tx.try_send(PlayerEvent::IntroSkipSuggested(suggestion));
+                                        }
                                         
+                                        // 优先让解码器直接定位（不重新打开文件、不从头解码），音源不支持时再重新解码并跳过
+                                        // 分段曲目、网络音源、去掉了编码器延迟的曲目和设置了裁剪点的曲目的时间轴与文件不一致，
+                                        // sink 中已排入下一首时也无法只定位当前曲目，都走重新解码
+                                        let direct_seek = song_clone.segment.is_none()
+                                            && !media_source::is_url(&song_clone.path)
+                                            && gapless::playback_info(&song_clone.path, song_clone.gapless).is_none()
+                                            && song_clone.trim.is_none();
+                                        if let Some(sink) = current_sink.as_ref().filter(|sink| direct_seek && sink.len() == 1) {
+                                            match sink.try_seek(seek_offset) {
+                                                Ok(()) => {
+                                                    if was_playing {
+                                                        play_start_time = Some(std::time::Instant::now() - seek_offset);
+                                                    } else {
+                                                        paused_position = seek_position;
+                                                        play_start_time = None;
+                                                    }
+                                                    current_position = seek_position;
+                                                    state.lock().unwrap().position = seek_position;
+                                                    println!("✅ 解码器定位成功: {}秒", seek_position);
+                                                    continue;
+                                                }
+                                                Err(e) => eprintln!("解码器不支持直接定位，改为重新解码: {}", e),
+                                            }
+                                        }
+
                                         // 停止当前播放
                                         if let Some(sink) = current_sink.take() {
                                             sink.stop();
                                         }
                                         
                                         // 重新加载文件并从指定位置开始播放
-                                        match std::fs::File::open(&song_clone.path) {
-                                            Ok(file) => {
-                                                match rodio::Decoder::new(std::io::BufReader::new(file)) {
-                                                    Ok(source) => {
-                                                        // 创建新的sink
-                                                        match rodio::Sink::try_new(&stream_handle) {
-                                                            Ok(sink) => {
-                                                                // 如果跳转位置大于0，尝试跳过指定时长
-                                                                if seek_position > 0 {
-                                                                    let skip_duration = std::time::Duration::from_secs(seek_position);
-                                                                    
-                                                                    // 尝试跳过指定的采样数
-                                                                    let skipped_source = source.skip_duration(skip_duration);
-                                                                    sink.append(skipped_source);
-                                                                } else {
-                                                                    // 如果跳转位置为0，直接播放
-                                                                    sink.append(source);
-                                                                }
-                                                                
-                                                                // 根据之前的状态决定是否播放
-                                                                if was_playing {
-                                                                    sink.play();
-                                                                    // 调整播放开始时间，考虑跳转位置
-                                                                    play_start_time = Some(std::time::Instant::now() - std::time::Duration::from_secs(seek_position));
-                                                                } else {
-                                                                    sink.pause();
-                                                                    paused_position = seek_position;
-                                                                    play_start_time = None;
-                                                                }
-                                                                
-                                                                current_sink = Some(sink);
-                                                                current_position = seek_position;
-                                                                
-                                                                println!("✅ 音频跳转成功: {}秒", seek_position);
-                                                                
-                                                                // 更新播放器状态
-                                                                let mut player_state_guard = state.lock().unwrap();
-                                                                if was_playing {
-                                                                    player_state_guard.state = PlayerState::Playing;
-                                                                } else {
-                                                                    player_state_guard.state = PlayerState::Paused;
-                                                                }
-                                                                let final_state = player_state_guard.state;
-                                                                drop(player_state_guard);
-                                                                
-                                                                // 发送确认的进度更新和状态更新
-                                                                let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
-                                                                    position: seek_position, 
-                                                                    duration: song_duration 
-                                                                });
-                                                                
-                                                                let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(final_state));
-                                                            }
-                                                            Err(e) => {
-                                                                let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("跳转时无法创建音频sink: {}", e)));
-                                                            }
+                                        let start = Some(seek_offset);
+                                        match build_source(&song_clone, start, album_gain, &dsp, &glitch_tx, &player_thread_event_tx) {
+                                            Ok((source, _)) => {
+                                                // 创建新的sink
+                                                match create_sink(&mut output_stream, &output_selection, Some(source.sample_rate()), &player_thread_event_tx) {
+                                                    Ok(sink) => {
+                                                        sink.set_volume(state.lock().unwrap().output_volume());
+                                                        sink.append(source);
+                                                        
+                                                        // 根据之前的状态决定是否播放
+                                                        if was_playing {
+                                                            sink.play();
+                                                            // 调整播放开始时间，考虑跳转位置
+                                                            play_start_time = Some(std::time::Instant::now() - seek_offset);
+                                                        } else {
+                                                            sink.pause();
+                                                            paused_position = seek_position;
+                                                            play_start_time = None;
                                                         }
+                                                        
+                                                        current_sink = Some(sink);
+                                                        current_position = seek_position;
+                                                        
+                                                        println!("✅ 音频跳转成功: {}秒", seek_position);
+                                                        
+                                                        // 更新播放器状态
+                                                        let mut player_state_guard = state.lock().unwrap();
+                                                        player_state_guard.position = seek_position;
+                                                        if was_playing {
+                                                            player_state_guard.state = PlayerState::Playing;
+                                                        } else {
+                                                            player_state_guard.state = PlayerState::Paused;
+                                                        }
+                                                        let final_state = player_state_guard.state;
+                                                        drop(player_state_guard);
+                                                        
+                                                        // 发送确认的进度更新和状态更新
+                                                        let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
+                                                            position: seek_position, 
+                                                            duration: song_duration,
+                                                            ab_loop: loop_bounds(&ab_loop),
+                                                        });
+                                                        
+                                                        let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(final_state));
                                                     }
                                                     Err(e) => {
-                                                        let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("跳转时解码音频文件失败: {}", e)));
+                                                        let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("跳转时无法创建音频sink: {}", e)));
                                                     }
                                                 }
                                             }
                                             Err(e) => {
-                                                let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("跳转时无法打开音频文件: {}", e)));
+                                                let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("跳转失败，{}", e)));
                                             }
                                         }
                                     } else {
@@ -794,18 +2333,21 @@ fn run_player_thread(
                             // 处理视频进度更新命令
                             if let Some(current_idx) = player_state_guard.current_index {
                                 if let Some(song) = player_state_guard.playlist.get(current_idx) {
-                                    // 只有当前播放的是视频文件时才处理
-                                    if song.media_type == Some(crate::player_fixed::MediaType::Video) {
+                                    // 只有当前播放的是视频文件或MV时才处理
+                                    let is_mv_mode = player_state_guard.current_playback_mode == MediaType::Video && song.mv_path.is_some();
+                                    if song.media_type == Some(crate::player_fixed::MediaType::Video) || is_mv_mode {
+                                        player_state_guard.position = position;
                                         // 直接发送进度更新事件
                                         let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
                                             position, 
-                                            duration 
+                                            duration,
+                                            ab_loop: loop_bounds(&ab_loop),
                                         });
                                     }
                                 }
                             }
                         }
-                        PlayerCommand::TogglePlaybackMode => {
+                        PlayerCommand::TogglePlaybackMode { position } => {
                             // 切换播放模式（音频<->MV）
                             let current_mode = player_state_guard.current_playback_mode;
                             let new_mode = match current_mode {
@@ -814,6 +2356,7 @@ fn run_player_thread(
                             };
                             
                             println!("播放模式切换：{:?} -> {:?}", current_mode, new_mode);
+                            let switch_position = mode_switch_position(&player_state_guard, current_mode, position, play_start_time, paused_position);
                             
 
                             // 无论什么模式切换，都要先停止当前的音频播放
@@ -827,8 +2370,10 @@ fn run_player_thread(
                             let current_idx = player_state_guard.current_index;
                             
 
-                            // 更新播放模式
+                            // 更新播放模式，切换后从原来的位置继续
                             player_state_guard.current_playback_mode = new_mode;
+                            player_state_guard.position = switch_position;
+                            paused_position = switch_position;
                             
 
                             // 如果之前在播放，需要根据新模式重新开始播放
@@ -836,6 +2381,7 @@ fn run_player_thread(
                                 if let Some(current_idx) = current_idx {
                                     // 先克隆需要的歌曲信息，然后释放锁
                                     let song = player_state_guard.playlist.get(current_idx).cloned();
+                                    let album_gain = player_state_guard.live_album_mode;
                                     drop(player_state_guard);
                                     
                                     if let Some(song) = song {
@@ -843,45 +2389,42 @@ fn run_player_thread(
                                             MediaType::Audio => {
                                                 // 切换到音频模式：重新加载音频文件
                                                 println!("重新加载音频文件: {}", song.path);
-                                                match std::fs::File::open(&song.path) {
-                                                    Ok(file) => match rodio::Decoder::new(std::io::BufReader::new(file)) {
-                                                        Ok(source) => match rodio::Sink::try_new(&stream_handle) {
-                                                            Ok(sink) => {
-                                                                // 关键修复：确保立即播放状态
-                                                                sink.append(source);
-                                                                sink.play();
-                                                                current_sink = Some(sink);
-                                                                
-                                                                // 重置播放追踪
-                                                                current_position = 0;
-                                                                paused_position = 0;
-                                                                play_start_time = Some(std::time::Instant::now());
-                                                                
-                                                                println!("已切换到音频模式并开始播放");
-                                                                
-                                                                // 发送状态更新
-                                                                let mut state_guard = state.lock().unwrap();
-                                                                state_guard.state = PlayerState::Playing;
-                                                                let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(PlayerState::Playing));
-                                                                
-                                                                // 重置进度
-                                                                if let Some(duration) = song.duration {
-                                                                    let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
-                                                                        position: 0, 
-                                                                        duration 
-                                                                    });
-                                                                }
-                                                            }
-                                                            Err(e) => {
-                                                                let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("切换到音频模式失败: {}", e)));
+                                                // 直接从切换前的位置开始解码
+                                                match build_source(&song, Some(std::time::Duration::from_secs(switch_position)), album_gain, &dsp, &glitch_tx, &player_thread_event_tx) {
+                                                    Ok((source, _)) => match create_sink(&mut output_stream, &output_selection, Some(source.sample_rate()), &player_thread_event_tx) {
+                                                        Ok(sink) => {
+                                                            sink.set_volume(state.lock().unwrap().output_volume());
+                                                            // 关键修复：确保立即播放状态
+                                                            sink.append(source);
+                                                            sink.play();
+                                                            current_sink = Some(sink);
+                                                            
+                                                            // 播放追踪从切换前的位置继续
+                                                            current_position = switch_position;
+                                                            paused_position = switch_position;
+                                                            play_start_time = Some(std::time::Instant::now() - std::time::Duration::from_secs(switch_position));
+                                                            
+                                                            println!("已切换到音频模式并开始播放");
+                                                            
+                                                            // 发送状态更新
+                                                            let mut state_guard = state.lock().unwrap();
+                                                            state_guard.state = PlayerState::Playing;
+                                                            let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(PlayerState::Playing));
+                                                            
+                                                            if let Some(duration) = song.duration {
+                                                                let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
+                                                                    position: switch_position, 
+                                                                    duration,
+                                                                    ab_loop: loop_bounds(&ab_loop),
+                                                                });
                                                             }
-                                                        },
+                                                        }
                                                         Err(e) => {
-                                                            let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("音频解码失败: {}", e)));
+                                                            let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("切换到音频模式失败: {}", e)));
                                                         }
                                                     },
                                                     Err(e) => {
-                                                        let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("无法打开音频文件: {}", e)));
+                                                        let _ = player_thread_event_tx.try_send(PlayerEvent::Error(e.to_string()));
                                                     }
                                                 }
                                             }
@@ -894,11 +2437,12 @@ fn run_player_thread(
                                                 state_guard.state = PlayerState::Playing;
                                                 let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(PlayerState::Playing));
                                                 
-                                                // 重置进度（让前端VideoPlayer来提供真实进度）
-//                                                let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
-//                                                    position: 0, 
-//                                                    duration: song.duration.unwrap_or(0)
-//                                                });
+                                                // 告诉前端VideoPlayer从切换前的位置开始播放
+                                                let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
+                                                    position: switch_position, 
+                                                    duration: song.duration.unwrap_or(0),
+                                                    ab_loop: loop_bounds(&ab_loop),
+                                                });
                                             }
                                         }
                                     }
@@ -908,7 +2452,7 @@ fn run_player_thread(
                             // 发送播放模式变更通知
                             println!("播放模式切换完成：{:?}", new_mode);
                         }
-                        PlayerCommand::SetPlaybackMode(mode) => {
+                        PlayerCommand::SetPlaybackMode { mode, position } => {
                             // 简化的播放模式切换逻辑
                             let current_mode = player_state_guard.current_playback_mode;
                             if current_mode == mode {
@@ -917,6 +2461,7 @@ fn run_player_thread(
                             }
                             
                             println!("设置播放模式：{:?} -> {:?}", current_mode, mode);
+                            let switch_position = mode_switch_position(&player_state_guard, current_mode, position, play_start_time, paused_position);
                             
 
                             // 先停止所有音频播放
@@ -930,8 +2475,10 @@ fn run_player_thread(
                             let current_idx = player_state_guard.current_index;
                             
 
-                            // 更新播放模式
+                            // 更新播放模式，切换后从原来的位置继续
                             player_state_guard.current_playback_mode = mode;
+                            player_state_guard.position = switch_position;
+                            paused_position = switch_position;
                             
 
                             // 关键修复：视频切音频时确保立即播放
@@ -952,6 +2499,7 @@ fn run_player_thread(
                                 let _ = player_thread_event_tx.try_send(PlayerEvent::StateChanged(PlayerState::Playing));
                                 
                                 if let Some(song) = player_state_guard.playlist.get(current_idx).cloned() {
+                                    let album_gain = player_state_guard.live_album_mode;
                                     drop(player_state_guard);
                                     
                                     match mode {
@@ -959,51 +2507,49 @@ fn run_player_thread(
                                             // 音频模式：立即加载并播放音频
                                             println!("🎵 切换到音频模式，立即播放: {}", song.path);
                                             
-                                            match std::fs::File::open(&song.path) {
-                                                Ok(file) => match rodio::Decoder::new(std::io::BufReader::new(file)) {
-                                                    Ok(source) => match rodio::Sink::try_new(&stream_handle) {
-                                                        Ok(sink) => {
-                                                            sink.append(source);
-                                                            sink.play();
-                                                            current_sink = Some(sink);
-                                                            
+                                            // 直接从切换前的位置开始解码
+                                            match build_source(&song, Some(std::time::Duration::from_secs(switch_position)), album_gain, &dsp, &glitch_tx, &player_thread_event_tx) {
+                                                Ok((source, _)) => match create_sink(&mut output_stream, &output_selection, Some(source.sample_rate()), &player_thread_event_tx) {
+                                                    Ok(sink) => {
+                                                        sink.set_volume(state.lock().unwrap().output_volume());
+                                                        sink.append(source);
+                                                        sink.play();
+                                                        current_sink = Some(sink);
+                                                        
 
-                                                            // 重置播放追踪
-                                                            current_position = 0;
-                                                            paused_position = 0;
-                                                            play_start_time = Some(std::time::Instant::now());
-                                                            
-                                                            // 发送进度重置
-                                                            if let Some(duration) = song.duration {
-                                                                let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
-                                                                    position: 0, 
-                                                                    duration 
-                                                                });
-                                                            }
-                                                            
-                                                            println!("✅ 视频切音频完成，音频立即播放");
-                                                        }
-                                                        Err(e) => {
-                                                            let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("音频播放失败: {}", e)));
+                                                        // 播放追踪从切换前的位置继续
+                                                        current_position = switch_position;
+                                                        paused_position = switch_position;
+                                                        play_start_time = Some(std::time::Instant::now() - std::time::Duration::from_secs(switch_position));
+                                                        
+                                                        if let Some(duration) = song.duration {
+                                                            let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
+                                                                position: switch_position, 
+                                                                duration,
+                                                                ab_loop: loop_bounds(&ab_loop),
+                                                            });
                                                         }
-                                                    },
+                                                        
+                                                        println!("✅ 视频切音频完成，音频立即播放");
+                                                    }
                                                     Err(e) => {
-                                                        let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("音频解码失败: {}", e)));
+                                                        let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("音频播放失败: {}", e)));
                                                     }
                                                 },
                                                 Err(e) => {
-                                                    let _ = player_thread_event_tx.try_send(PlayerEvent::Error(format!("无法打开音频文件: {}", e)));
+                                                    let _ = player_thread_event_tx.try_send(PlayerEvent::Error(e.to_string()));
                                                 }
                                             }
                                         }
                                         MediaType::Video => {
-                                            // 视频模式：等待前端VideoPlayer
+                                            // 视频模式：等待前端VideoPlayer，从切换前的位置开始播放
                                             println!("🎬 切换到视频模式");
                                             
                                             if let Some(duration) = song.duration {
                                                 let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
-                                                    position: 0, 
-                                                    duration 
+                                                    position: switch_position, 
+                                                    duration,
+                                                    ab_loop: loop_bounds(&ab_loop),
                                                 });
                                             }
                                         }
@@ -1036,6 +2582,7 @@ fn run_player_thread(
                         }
                         PlayerCommand::ForceStopAll => {
                             println!("🔇 强制停止所有播放");
+                            gap_deadline = None;
                             // 停止音频
                             if let Some(sink) = current_sink.take() {
                                 sink.stop();
@@ -1076,15 +2623,225 @@ fn run_player_thread(
                         }
                     }
                 }
+                _ = tokio::time::sleep_until(gap_deadline.unwrap_or_else(tokio::time::Instant::now)), if gap_deadline.is_some() => {
+                    // 曲间静音结束，切换到下一首
+                    gap_deadline = None;
+                    if command_sender_for_internal_use.try_send(PlayerCommand::AutoAdvance).is_err() {
+                        eprintln!("播放器线程: 无法发送内部 AutoAdvance 命令 (通道已满或已关闭)");
+                    }
+                }
+                _ = position_interval.tick(), if position_period.is_some() => {
+                    let player_state_guard = state.lock().unwrap();
+                    if player_state_guard.state != PlayerState::Playing || current_sink.is_none() || play_start_time.is_none() {
+                        continue;
+                    }
+                    let Some(idx) = player_state_guard.current_index else { continue };
+                    let Some(duration) = player_state_guard.playlist.get(idx).and_then(|song| song.duration) else { continue };
+                    let position_ms = playback_position(&player_state_guard, play_start_time)
+                        .saturating_sub(std::time::Duration::from_millis(output_device::latency_ms()))
+                        .as_millis() as u64;
+                    drop(player_state_guard);
+                    // 合并：位置没有变化，或前端事件处理跟不上（通道剩余不足一半）时跳过这一次
+                    if last_position_sent == Some((idx, position_ms)) || player_thread_event_tx.capacity() < player_thread_event_tx.max_capacity() / 2 {
+                        continue;
+                    }
+                    last_position_sent = Some((idx, position_ms));
+                    let _ = player_thread_event_tx.try_send(PlayerEvent::PositionUpdate {
+                        position_ms,
+                        duration_ms: duration * 1000,
+                    });
+                }
                 _ = progress_interval.tick() => {
-                    let player_state_guard = state.lock().unwrap(); 
+                    // 进度事件间隔设置变化后重建定时器
+                    let period = progress::settings().interval();
+                    if period != position_period {
+                        position_period = period;
+                        if let Some(period) = period {
+                            position_interval = tokio::time::interval(period);
+                            position_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+                        }
+                    }
+                    // 现场专辑模式：接近结尾时把同一专辑的下一首排入当前 sink，不做淡入、不插入静音
+                    // 在锁外解码；还没有缓冲好的网络音源不等待，这次不排入
+                    let chain_candidate = match (&current_sink, play_start_time) {
+                        (Some(sink), Some(start_time)) if chained_next.is_none() && ab_compare.is_none() && !sink.empty() => {
+                            let player_state_guard = state.lock().unwrap();
+                            let elapsed = playback_position(&player_state_guard, Some(start_time))
+                                .saturating_sub(std::time::Duration::from_millis(output_device::latency_ms()))
+                                .as_secs();
+                            let near_end = player_state_guard
+                                .current_index
+                                .and_then(|idx| player_state_guard.playlist.get(idx))
+                                .and_then(|song| song.duration)
+                                .map(|duration| elapsed + CHAIN_AHEAD_SECS >= duration)
+                                .unwrap_or(false);
+                            if player_state_guard.state == PlayerState::Playing && near_end {
+                                live_album_next(&player_state_guard).map(|idx| (idx, player_state_guard.playlist[idx].clone()))
+                            } else {
+                                None
+                            }
+                        }
+                        _ => None,
+                    };
+                    if let (Some((next_idx, next)), Some(sink)) = (chain_candidate, &current_sink) {
+                        // 连续播放不跳过起始点；整个专辑按专辑增益标准化，曲目之间的响度关系保持不变
+                        match build_source(&next, Some(std::time::Duration::ZERO), true, &dsp, &glitch_tx, &player_thread_event_tx) {
+                            Ok((source, _)) => {
+                                sink.append(source);
+                                chained_next = Some((next_idx, next.path.clone()));
+                            }
+                            Err(e) => eprintln!("现场专辑模式无法预先解码下一首 {}: {}", next.path, e),
+                        }
+                    }
+
+                    // 空闲释放的输出设备在状态锁释放之后才关闭（声明在锁之前，析构在锁之后）
+                    let mut _released_output: Option<AudioOutput> = None;
+                    let mut player_state_guard = state.lock().unwrap(); 
+
+                    // 汇总音频回调线程上报的卡顿
+                    while let Ok(glitch) = glitch_rx.try_recv() {
+                        eprintln!("⚠️ 播放卡顿: {}ms @ {}ms ({})", glitch.stall_ms, glitch.position, glitch.path);
+                        player_state_guard.diagnostics.record(glitch.clone());
+                        let _ = player_thread_event_tx.try_send(PlayerEvent::PlaybackGlitch(glitch));
+                    }
+                    let silence_gap_secs = player_state_guard.silence_gap_secs;
+
+                    // 空闲时释放音频设备，让蓝牙耳机/独占设备可以被其他应用使用
+                    if player_state_guard.state == PlayerState::Playing && current_sink.is_some() {
+                        idle_since = None;
+                    } else if output_stream.is_some() && gap_deadline.is_none() {
+                        let since = *idle_since.get_or_insert_with(std::time::Instant::now);
+                        if since.elapsed().as_secs() >= player_state_guard.idle_release_secs {
+                            end_ab_compare(&mut ab_compare, &current_sink, player_state_guard.output_volume(), &player_thread_event_tx);
+                            if let Some(sink) = current_sink.take() {
+                                sink.stop();
+                            }
+                            _released_output = output_stream.take();
+                            idle_since = None;
+                            println!("💤 播放器空闲，已释放音频输出设备");
+                        }
+                    }
+
+                    // 输出设备断开或系统默认设备变化（如拔出耳机）：重新打开输出，正在播放时从原位置继续
+                    // 选择的设备断开时临时改用默认设备，设备重新连接后切回
+                    if output_stream.is_some() && device_checked_at.elapsed() >= DEVICE_CHECK_INTERVAL {
+                        device_checked_at = std::time::Instant::now();
+                        let lost = output_device::take_device_lost();
+                        let default_changed = output_selection.is_default() && output_device::default_device_changed();
+                        let restored = if output_selection != preferred_output { output_device::selection_key(&preferred_output) } else { None };
+                        if let Some(device) = restored {
+                            let previous = output_device::active_device();
+                            println!("🔌 输出设备已重新连接: {:?} → {}", previous, device);
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::DeviceChanged { previous, device, fallback: false });
+                            if command_sender_for_internal_use.try_send(PlayerCommand::ReopenOutput(preferred_output.clone())).is_err() {
+                                eprintln!("播放器线程: 无法发送内部 ReopenOutput 命令 (通道已满或已关闭)");
+                            }
+                        } else if lost || default_changed {
+                            let fallback = !preferred_output.is_default() && !output_device::is_available(&preferred_output);
+                            let selection = if fallback { OutputDeviceSelection::default() } else { preferred_output.clone() };
+                            let previous = output_device::active_device();
+                            let device = if selection.is_default() {
+                                output_device::default_device_key()
+                            } else {
+                                previous.clone().unwrap_or_default()
+                            };
+                            println!("🔌 输出设备变化: {:?} → {}", previous, device);
+                            let _ = player_thread_event_tx.try_send(PlayerEvent::DeviceChanged { previous, device, fallback });
+                            if command_sender_for_internal_use.try_send(PlayerCommand::ReopenOutput(selection)).is_err() {
+                                eprintln!("播放器线程: 无法发送内部 ReopenOutput 命令 (通道已满或已关闭)");
+                            }
+                        }
+                    }
+
+                    // 播放中输出静音检测：输出设备不再取样或信号持续静音时上报（网络音源缓冲另有事件，不检测）
+                    let watchdog = playback_monitor::watchdog_settings();
+                    let watched_path = player_state_guard
+                        .current_index
+                        .and_then(|idx| player_state_guard.playlist.get(idx))
+                        .map(|song| song.path.clone())
+                        .filter(|path| !media_source::is_url(path));
+                    let outputting = player_state_guard.state == PlayerState::Playing
+                        && current_sink.as_ref().map(|sink| !sink.is_paused() && !sink.empty()).unwrap_or(false);
+                    match (play_start_time, watched_path) {
+                        (Some(session), Some(path)) if watchdog.enabled && outputting => {
+                            if let Some((kind, seconds)) = silence_watchdog.check(session, watchdog.silence_secs) {
+                                // 信号静音可能是曲目本身的静音段，只上报不重新打开设备
+                                let reinitialized = watchdog.auto_reinit && kind == SilenceKind::Stalled;
+                                eprintln!("⚠️ 播放中输出静音 {:?}: {}秒 ({})", kind, seconds, path);
+                                let _ = player_thread_event_tx.try_send(PlayerEvent::SilentPlaybackDetected { kind, seconds, path, reinitialized });
+                                if reinitialized && command_sender_for_internal_use.try_send(PlayerCommand::ReopenOutput(output_selection.clone())).is_err() {
+                                    eprintln!("播放器线程: 无法发送内部 ReopenOutput 命令 (通道已满或已关闭)");
+                                }
+                            }
+                        }
+                        _ => silence_watchdog.reset(),
+                    }
+
                     if player_state_guard.state == PlayerState::Playing {
                         if let Some(sink) = &current_sink {
+                            // 现场专辑模式：上一首已播完，排入的下一首正在同一 sink 中接着播放，只更新当前曲目
+                            if let Some((next_idx, next_path)) = chained_next.clone().filter(|_| sink.len() <= 1) {
+                                chained_next = None;
+                                let index = if player_state_guard.playlist.get(next_idx).map(|s| s.path == next_path).unwrap_or(false) {
+                                    Some(next_idx)
+                                } else {
+                                    player_state_guard.playlist.iter().position(|s| s.path == next_path)
+                                };
+                                if let Some(index) = index {
+                                    if let Some(finished) = player_state_guard.current_index {
+                                        let _ = player_thread_event_tx.try_send(PlayerEvent::SongFinished(finished));
+                                        if let Some(song) = player_state_guard.playlist.get(finished) {
+                                            resume.save(song, None);
+                                        }
+                                    }
+                                    consume_stop_after(&mut player_state_guard);
+                                    player_state_guard.current_index = Some(index);
+                                    player_state_guard.position = 0;
+                                    current_position = 0;
+                                    paused_position = 0;
+                                    play_start_time = Some(std::time::Instant::now());
+                                    let song = player_state_guard.playlist[index].clone();
+                                    println!("🎤 连续播放下一首: {}", song.title.as_deref().unwrap_or("未知"));
+                                    let _ = player_thread_event_tx.try_send(PlayerEvent::SongChanged(index, song.clone()));
+                                    if let Some(duration) = song.duration {
+                                        let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { position: 0, duration, ab_loop: loop_bounds(&ab_loop) });
+                                    }
+                                }
+                            }
                             if sink.empty() { // Song finished
-                                if player_state_guard.current_index.is_some() && !player_state_guard.playlist.is_empty() {
+                                end_ab_compare(&mut ab_compare, &current_sink, player_state_guard.output_volume(), &player_thread_event_tx);
+                                if let Some(finished) = player_state_guard.current_index.filter(|_| !player_state_guard.playlist.is_empty()) {
+                                    let _ = player_thread_event_tx.try_send(PlayerEvent::SongFinished(finished));
+                                    if let Some(song) = player_state_guard.playlist.get(finished) {
+                                        resume.save(song, None);
+                                    }
+                                    let stop_now = consume_stop_after(&mut player_state_guard);
+                                    let playlist_end = !stop_now && at_playlist_end(&player_state_guard);
+                                    let finished_path = player_state_guard.playlist.get(finished).map(|s| s.path.clone());
+                                    let volume = player_state_guard.output_volume();
                                     drop(player_state_guard); // Release lock before sending command
-                                    if command_sender_for_internal_use.try_send(PlayerCommand::Next).is_err() {
-                                        eprintln!("播放器线程: 无法发送内部 Next 命令 (通道已满或已关闭)");
+                                    if !stop_now && !playlist_end {
+                                        announcement = finished_path.and_then(|path| start_announcement(&output_stream, &path, volume, &player_thread_event_tx));
+                                    }
+                                    if playlist_end {
+                                        println!("⏹️ 已播完播放列表，停止播放");
+                                        let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistEnded);
+                                        if command_sender_for_internal_use.try_send(PlayerCommand::Stop).is_err() {
+                                            eprintln!("播放器线程: 无法发送内部 Stop 命令 (通道已满或已关闭)");
+                                        }
+                                    } else if stop_now {
+                                        println!("⏹️ 已播完设定的曲目数，停止播放");
+                                        let _ = player_thread_event_tx.try_send(PlayerEvent::StopAfterTriggered);
+                                        if command_sender_for_internal_use.try_send(PlayerCommand::Stop).is_err() {
+                                            eprintln!("播放器线程: 无法发送内部 Stop 命令 (通道已满或已关闭)");
+                                        }
+                                    } else if silence_gap_secs > 0.0 {
+                                        // 曲间静音：释放当前sink，等待间隔结束后再切歌
+                                        current_sink = None;
+                                        gap_deadline = Some(tokio::time::Instant::now() + std::time::Duration::from_secs_f32(silence_gap_secs));
+                                        println!("⏱️ 插入曲间静音: {}秒", silence_gap_secs);
+                                    } else if command_sender_for_internal_use.try_send(PlayerCommand::AutoAdvance).is_err() {
+                                        eprintln!("播放器线程: 无法发送内部 AutoAdvance 命令 (通道已满或已关闭)");
                                     }
                                 } else {
                                     // 需要获取当前歌曲的时长
@@ -1094,7 +2851,8 @@ fn run_player_thread(
                                                 // 发送进度更新事件
                                                 let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
                                                     position: current_position, 
-                                                    duration 
+                                                    duration,
+                                                    ab_loop: loop_bounds(&ab_loop),
                                                 });
                                             }
                                         }
@@ -1105,25 +2863,100 @@ fn run_player_thread(
                                 if let Some(idx) = player_state_guard.current_index {
                                     if let Some(song) = player_state_guard.playlist.get(idx) {
                                         if let Some(duration) = song.duration {
+                                            let segment_start = song.segment.as_ref().map(|segment| segment.start_ms / 1000);
                                             // 计算当前播放位置
                                             if let Some(start_time) = play_start_time {
-                                                // 计算当前播放时间（秒）
-                                                let elapsed = start_time.elapsed().as_secs();
+                                                // 计算当前播放时间（秒），扣除输出设备延迟，让歌词高亮与实际听到的声音同步
+                                                let latency = std::time::Duration::from_millis(output_device::latency_ms());
+                                                let elapsed = playback_position(&player_state_guard, Some(start_time)).saturating_sub(latency).as_secs();
                                                 current_position = elapsed;
+                                                player_state_guard.position = current_position;
+
+                                                // 定期保存续播位置，程序异常退出后也能从附近继续
+                                                if current_position.abs_diff(resume_saved_position) >= RESUME_SAVE_INTERVAL_SECS {
+                                                    resume_saved_position = current_position;
+                                                    resume.save(&player_state_guard.playlist[idx], Some(current_position));
+                                                }
+
+                                                // 接近结尾时预取下一首，网络音源提前缓冲，保证切歌无缝
+                                                if elapsed + media_source::PREFETCH_AHEAD_SECS >= duration {
+                                                    if prefetched_for != Some(idx) {
+                                                        prefetched_for = Some(idx);
+                                                        let next_idx = upcoming_index(&player_state_guard);
+                                                        if let Some(next_idx) = next_idx {
+                                                            media_source::prefetch(next_idx, &player_state_guard.playlist[next_idx], player_thread_event_tx.clone());
+                                                        }
+                                                        radio_host::prepare(&player_state_guard.playlist[idx], next_idx.map(|next_idx| &player_state_guard.playlist[next_idx]));
+                                                    }
+                                                } else if prefetched_for == Some(idx) {
+                                                    prefetched_for = None;
+                                                }
                                                 
 
+                                                // A–B 循环：播放越过 B 点时跳回 A 点，切到其他曲目后循环取消
+                                                let mut looped = false;
+                                                if let Some((loop_path, bounds)) = &ab_loop {
+                                                    if *loop_path != player_state_guard.playlist[idx].path {
+                                                        ab_loop = None;
+                                                        let _ = player_thread_event_tx.try_send(PlayerEvent::AbLoopChanged(None));
+                                                    } else if current_position >= bounds.end {
+                                                        looped = true;
+                                                        if command_sender_for_internal_use.try_send(PlayerCommand::SeekTo(bounds.start)).is_err() {
+                                                            eprintln!("播放器线程: 无法发送内部 SeekTo 命令 (通道已满或已关闭)");
+                                                        }
+                                                    }
+                                                }
+
                                                 // 如果到达歌曲结尾或超出时长，自动切换到下一首
-                                                if current_position >= duration && !sink.empty() {
+                                                if !looped && current_position >= duration && !sink.empty() && chained_next.is_none() {
+                                                    let _ = player_thread_event_tx.try_send(PlayerEvent::SongFinished(idx));
+                                                    resume.save(&player_state_guard.playlist[idx], None);
+                                                    let stop_now = consume_stop_after(&mut player_state_guard);
+                                                    let playlist_end = !stop_now && at_playlist_end(&player_state_guard);
+                                                    let finished_path = player_state_guard.playlist[idx].path.clone();
+                                                    let volume = player_state_guard.output_volume();
                                                     drop(player_state_guard);
-                                                    if command_sender_for_internal_use.try_send(PlayerCommand::Next).is_err() {
-                                                        eprintln!("播放器线程: 无法发送内部 Next 命令 (通道已满或已关闭)");
+                                                    if !stop_now && !playlist_end {
+                                                        announcement = start_announcement(&output_stream, &finished_path, volume, &player_thread_event_tx);
+                                                    }
+                                                    if playlist_end {
+                                                        println!("⏹️ 已播完播放列表，停止播放");
+                                                        let _ = player_thread_event_tx.try_send(PlayerEvent::PlaylistEnded);
+                                                        if command_sender_for_internal_use.try_send(PlayerCommand::Stop).is_err() {
+                                                            eprintln!("播放器线程: 无法发送内部 Stop 命令 (通道已满或已关闭)");
+                                                        }
+                                                    } else if stop_now {
+                                                        println!("⏹️ 已播完设定的曲目数，停止播放");
+                                                        let _ = player_thread_event_tx.try_send(PlayerEvent::StopAfterTriggered);
+                                                        if command_sender_for_internal_use.try_send(PlayerCommand::Stop).is_err() {
+                                                            eprintln!("播放器线程: 无法发送内部 Stop 命令 (通道已满或已关闭)");
+                                                        }
+                                                    } else if silence_gap_secs > 0.0 {
+                                                        // 曲间静音：停止当前sink，等待间隔结束后再切歌
+                                                        if let Some(sink) = current_sink.take() {
+                                                            sink.stop();
+                                                        }
+                                                        gap_deadline = Some(tokio::time::Instant::now() + std::time::Duration::from_secs_f32(silence_gap_secs));
+                                                        println!("⏱️ 插入曲间静音: {}秒", silence_gap_secs);
+                                                    } else if command_sender_for_internal_use.try_send(PlayerCommand::AutoAdvance).is_err() {
+                                                        eprintln!("播放器线程: 无法发送内部 AutoAdvance 命令 (通道已满或已关闭)");
                                                     }
                                                 } else {
                                                     // 发送进度更新事件
                                                     let _ = player_thread_event_tx.try_send(PlayerEvent::ProgressUpdate { 
                                                         position: current_position, 
-                                                        duration 
+                                                        duration,
+                                                        ab_loop: loop_bounds(&ab_loop),
                                                     });
+                                                    // 虚拟曲目/章节：进度相对本曲，另附整个文件中的位置
+                                                    if let Some(segment_start) = segment_start {
+                                                        let _ = player_thread_event_tx.try_send(PlayerEvent::SegmentProgress {
+                                                            position: current_position,
+                                                            duration,
+                                                            file_position: segment_start + current_position,
+                                                            segment_start,
+                                                        });
+                                                    }
                                                 }
                                             }
                                         }
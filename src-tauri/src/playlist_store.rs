@@ -0,0 +1,99 @@
+use crate::player_fixed::SongInfo;
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// 已保存的播放列表
+/// 除歌曲外还记录该列表上次播放到的歌曲和位置，切换列表时各自恢复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedPlaylist {
+    pub name: String,
+    pub songs: Vec<SongInfo>,
+    #[serde(rename = "lastIndex", default)]
+    pub last_index: Option<usize>,
+    #[serde(rename = "lastPosition", default)]
+    pub last_position: u64, // 单位：秒
+}
+
+/// 播放列表摘要（用于列表展示，不包含歌曲详情）
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedPlaylistSummary {
+    pub name: String,
+    #[serde(rename = "songCount")]
+    pub song_count: usize,
+    #[serde(rename = "lastIndex")]
+    pub last_index: Option<usize>,
+    #[serde(rename = "lastPosition")]
+    pub last_position: u64,
+}
+
+/// 播放列表保存目录
+fn playlists_dir() -> PathBuf {
+    storage::data_dir().join("playlists")
+}
+
+fn playlist_path(name: &str) -> PathBuf {
+    playlists_dir().join(format!("{}.json", storage::sanitize_file_name(name)))
+}
+
+/// 保存播放列表（同名覆盖）
+pub fn save(playlist: &SavedPlaylist) -> anyhow::Result<()> {
+    if storage::sanitize_file_name(&playlist.name).is_empty() {
+        return Err(anyhow::anyhow!("播放列表名称不能为空"));
+    }
+    storage::save_json(&playlist_path(&playlist.name), playlist)
+}
+
+/// 读取播放列表
+pub fn load(name: &str) -> anyhow::Result<SavedPlaylist> {
+    storage::load_json(&playlist_path(name))?
+        .ok_or_else(|| anyhow::anyhow!("播放列表不存在: {}", name))
+}
+
+/// 删除播放列表
+pub fn delete(name: &str) -> anyhow::Result<()> {
+    let path = playlist_path(name);
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// 更新播放列表的恢复位置（列表不存在时忽略）
+pub fn update_position(name: &str, index: Option<usize>, position: u64) -> anyhow::Result<()> {
+    let mut playlist = match storage::load_json::<SavedPlaylist>(&playlist_path(name))? {
+        Some(playlist) => playlist,
+        None => return Ok(()),
+    };
+    playlist.last_index = index.filter(|idx| *idx < playlist.songs.len());
+    playlist.last_position = position;
+    save(&playlist)
+}
+
+/// 列出所有已保存的播放列表
+pub fn list() -> anyhow::Result<Vec<SavedPlaylistSummary>> {
+    let dir = playlists_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut summaries = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        match storage::load_json::<SavedPlaylist>(&path) {
+            Ok(Some(playlist)) => summaries.push(SavedPlaylistSummary {
+                name: playlist.name,
+                song_count: playlist.songs.len(),
+                last_index: playlist.last_index,
+                last_position: playlist.last_position,
+            }),
+            Ok(None) => {}
+            Err(e) => eprintln!("读取播放列表失败 {}: {}", path.display(), e),
+        }
+    }
+    summaries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(summaries)
+}
@@ -0,0 +1,318 @@
+use crate::m3u;
+use crate::player_fixed::SongInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// 播放列表清理选项
+#[derive(Debug, Clone, Deserialize)]
+pub struct CleanupOptions {
+    #[serde(rename = "removeDuplicates", default = "default_true")]
+    pub remove_duplicates: bool, // 移除重复条目（保留第一次出现的；同一文件的不同音轨/章节/CUE曲目不算重复）
+    #[serde(rename = "removeDeadPaths", default = "default_true")]
+    pub remove_dead_paths: bool, // 移除文件已不存在的条目
+    #[serde(rename = "removeZeroLength", default = "default_true")]
+    pub remove_zero_length: bool, // 移除空文件或时长为0的条目
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for CleanupOptions {
+    fn default() -> Self {
+        Self {
+            remove_duplicates: true,
+            remove_dead_paths: true,
+            remove_zero_length: true,
+        }
+    }
+}
+
+/// 播放列表清理结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CleanupSummary {
+    #[serde(rename = "removedDuplicates")]
+    pub removed_duplicates: Vec<String>,
+    #[serde(rename = "removedDeadPaths")]
+    pub removed_dead_paths: Vec<String>,
+    #[serde(rename = "removedZeroLength")]
+    pub removed_zero_length: Vec<String>,
+    pub remaining: usize,
+}
+
+impl CleanupSummary {
+    /// 被移除的条目总数
+    pub fn total_removed(&self) -> usize {
+        self.removed_duplicates.len() + self.removed_dead_paths.len() + self.removed_zero_length.len()
+    }
+}
+
+/// 用于比较的路径键（Windows 下路径不区分大小写）
+fn path_key(path: &str) -> String {
+    let normalized = path.replace('\\', "/");
+    if cfg!(windows) {
+        normalized.to_lowercase()
+    } else {
+        normalized
+    }
+}
+
+/// 比较两个播放列表时的条目键：路径相同且为同一音轨/章节才视为同一条目
+fn entry_key(song: &SongInfo) -> String {
+    match &song.segment {
+        Some(segment) => format!("{}#{}@{}", path_key(&song.path), segment.track_id.unwrap_or(0), segment.start_ms),
+        None => path_key(&song.path),
+    }
+}
+
+/// 检查条目是否为空文件或时长为0
+fn is_zero_length(song: &SongInfo) -> bool {
+    if song.duration == Some(0) {
+        return true;
+    }
+    std::fs::metadata(&song.path)
+        .map(|m| m.is_file() && m.len() == 0)
+        .unwrap_or(false)
+}
+
+/// 一次遍历清理播放列表
+/// 返回清理后的列表、当前歌曲在新列表中的索引（被移除时为 None）和清理结果
+pub fn cleanup(
+    playlist: Vec<SongInfo>,
+    current_index: Option<usize>,
+    options: &CleanupOptions,
+) -> (Vec<SongInfo>, Option<usize>, CleanupSummary) {
+    let mut summary = CleanupSummary::default();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut kept = Vec::with_capacity(playlist.len());
+    let mut new_current = None;
+
+    for (idx, song) in playlist.into_iter().enumerate() {
+        if options.remove_dead_paths && !Path::new(&song.path).exists() {
+            summary.removed_dead_paths.push(song.path);
+            continue;
+        }
+        if options.remove_zero_length && is_zero_length(&song) {
+            summary.removed_zero_length.push(song.path);
+            continue;
+        }
+        if options.remove_duplicates {
+            let key = entry_key(&song);
+            if let Some(&first_idx) = seen.get(&key) {
+                // 正在播放的是重复条目时，改为指向保留下来的同一文件
+                if Some(idx) == current_index {
+                    new_current = Some(first_idx);
+                }
+                summary.removed_duplicates.push(song.path);
+                continue;
+            }
+            seen.insert(key, kept.len());
+        }
+        if Some(idx) == current_index {
+            new_current = Some(kept.len());
+        }
+        kept.push(song);
+    }
+
+    summary.remaining = kept.len();
+    (kept, new_current, summary)
+}
+
+/// 播放列表中的一个分组
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaylistGroup {
+    pub name: String,
+    pub color: Option<String>, // 组内第一个带颜色条目的颜色
+    pub indices: Vec<usize>,
+}
+
+/// 按分组汇总播放列表条目，分组按首次出现的顺序排列，未分组的条目不计入
+pub fn groups(playlist: &[SongInfo]) -> Vec<PlaylistGroup> {
+    let mut groups: Vec<PlaylistGroup> = Vec::new();
+    for (idx, song) in playlist.iter().enumerate() {
+        let name = match song.group.as_deref() {
+            Some(name) if !name.is_empty() => name,
+            _ => continue,
+        };
+        match groups.iter_mut().find(|g| g.name == name) {
+            Some(group) => {
+                group.indices.push(idx);
+                if group.color.is_none() {
+                    group.color = song.color.clone();
+                }
+            }
+            None => groups.push(PlaylistGroup {
+                name: name.to_string(),
+                color: song.color.clone(),
+                indices: vec![idx],
+            }),
+        }
+    }
+    groups
+}
+
+/// 校验并规范化条目颜色，只接受 #RGB / #RRGGBB，统一为大写 #RRGGBB
+pub fn normalize_color(color: &str) -> Option<String> {
+    let hex = color.trim().strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    match hex.len() {
+        6 => Some(format!("#{}", hex.to_uppercase())),
+        3 => Some(format!("#{}", hex.chars().flat_map(|c| [c, c]).collect::<String>().to_uppercase())),
+        _ => None,
+    }
+}
+
+/// 比较/合并时播放列表的来源
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PlaylistSource {
+    Current,                // 当前播放列表
+    Saved { name: String }, // 已保存的播放列表
+    M3u { path: String },   // M3U/M3U8 文件
+}
+
+/// 两个播放列表的差异（以 a 为基准）
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PlaylistDiff {
+    pub added: Vec<SongInfo>,   // 只在 b 中出现的条目
+    pub removed: Vec<SongInfo>, // 只在 a 中出现的条目
+    pub common: usize,          // 两边都有的条目数
+    #[serde(rename = "orderChanged")]
+    pub order_changed: bool, // 共同条目的先后顺序不同
+}
+
+/// 比较两个播放列表，各自内部的重复条目只计一次
+pub fn diff(a: &[SongInfo], b: &[SongInfo]) -> PlaylistDiff {
+    let keys_a: Vec<String> = dedup(a).iter().map(|s| entry_key(s)).collect();
+    let keys_b: Vec<String> = dedup(b).iter().map(|s| entry_key(s)).collect();
+    let in_a: HashSet<&String> = keys_a.iter().collect();
+    let in_b: HashSet<&String> = keys_b.iter().collect();
+
+    let common_a: Vec<&String> = keys_a.iter().filter(|k| in_b.contains(k)).collect();
+    let common_b: Vec<&String> = keys_b.iter().filter(|k| in_a.contains(k)).collect();
+    PlaylistDiff {
+        added: dedup(b).into_iter().filter(|s| !in_a.contains(&entry_key(s))).cloned().collect(),
+        removed: dedup(a).into_iter().filter(|s| !in_b.contains(&entry_key(s))).cloned().collect(),
+        common: common_a.len(),
+        order_changed: common_a != common_b,
+    }
+}
+
+/// 去掉列表内的重复条目，保留第一次出现的
+fn dedup(songs: &[SongInfo]) -> Vec<&SongInfo> {
+    let mut seen = HashSet::new();
+    songs.iter().filter(|s| seen.insert(entry_key(s))).collect()
+}
+
+/// 合并策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeStrategy {
+    #[default]
+    Union, // 保持 a 的顺序，b 中新增的条目按 b 的顺序追加在后
+    PreferB, // 保持 b 的顺序，只在 a 中出现的条目追加在后
+    Intersection, // 只保留两边都有的条目，按 a 的顺序
+}
+
+/// 合并两个播放列表，结果中不含重复条目；两边都有的条目使用 a 中的信息（保留颜色、分组）
+pub fn merge(a: &[SongInfo], b: &[SongInfo], strategy: MergeStrategy) -> Vec<SongInfo> {
+    let in_b: HashSet<String> = b.iter().map(entry_key).collect();
+    let from_a: HashMap<String, &SongInfo> = dedup(a).into_iter().map(|s| (entry_key(s), s)).collect();
+    let (first, second) = match strategy {
+        MergeStrategy::Union => (a, b),
+        MergeStrategy::PreferB => (b, a),
+        MergeStrategy::Intersection => {
+            return dedup(a).into_iter().filter(|s| in_b.contains(&entry_key(s))).cloned().collect();
+        }
+    };
+    let mut seen = HashSet::new();
+    first
+        .iter()
+        .chain(second)
+        .filter(|s| seen.insert(entry_key(s)))
+        .map(|s| from_a.get(&entry_key(s)).copied().unwrap_or(s).clone())
+        .collect()
+}
+
+/// 播放列表导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PlaylistFormat {
+    M3u,  // 扩展 M3U（UTF-8，通常保存为 .m3u8）
+    Json, // 包含标题/艺术家/时长等信息的 JSON 文档
+}
+
+impl PlaylistFormat {
+    /// 保存对话框中的默认扩展名
+    pub fn extension(self) -> &'static str {
+        match self {
+            PlaylistFormat::M3u => "m3u8",
+            PlaylistFormat::Json => "json",
+        }
+    }
+}
+
+/// JSON 导出中的一个条目
+#[derive(Debug, Clone, Serialize)]
+struct ExportedEntry<'a> {
+    path: &'a str,
+    title: Option<&'a str>,
+    artist: Option<&'a str>,
+    album: Option<&'a str>,
+    duration: Option<u64>, // 单位：秒
+    #[serde(skip_serializing_if = "Option::is_none")]
+    segment: Option<&'a crate::matroska::MediaSegment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color: Option<&'a str>,
+}
+
+/// JSON 导出文档
+#[derive(Debug, Clone, Serialize)]
+struct ExportedPlaylist<'a> {
+    #[serde(rename = "exportedAt")]
+    exported_at: u64, // Unix秒
+    tracks: Vec<ExportedEntry<'a>>,
+}
+
+/// 把播放列表写入文件，返回导出的条目数
+pub fn export(songs: &[SongInfo], path: &Path, format: PlaylistFormat) -> anyhow::Result<usize> {
+    if songs.is_empty() {
+        return Err(anyhow::anyhow!("播放列表为空"));
+    }
+    let content = match format {
+        // 同一文件的连续虚拟曲目在 M3U 中合并为一个条目
+        PlaylistFormat::M3u => {
+            let content = m3u::to_extm3u(songs, |_, song| song.path.clone());
+            let count = content.lines().filter(|line| line.starts_with("#EXTINF:")).count();
+            std::fs::write(path, content)?;
+            return Ok(count);
+        }
+        PlaylistFormat::Json => {
+            let exported_at = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            let tracks = songs
+                .iter()
+                .map(|song| ExportedEntry {
+                    path: &song.path,
+                    title: song.title.as_deref(),
+                    artist: song.artist.as_deref(),
+                    album: song.album.as_deref(),
+                    duration: song.duration,
+                    segment: song.segment.as_ref(),
+                    group: song.group.as_deref(),
+                    color: song.color.as_deref(),
+                })
+                .collect();
+            serde_json::to_string_pretty(&ExportedPlaylist { exported_at, tracks })?
+        }
+    };
+    std::fs::write(path, content)?;
+    Ok(songs.len())
+}
@@ -0,0 +1,502 @@
+use crate::library;
+use crate::net;
+use crate::player_fixed::SongInfo;
+use crate::storage;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+/// 下载订阅源的超时时间（下载节目不限制）
+const FEED_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// 订阅的播客
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Podcast {
+    pub id: String,
+    pub url: String, // RSS 地址
+    pub title: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(rename = "lastRefreshed", default)]
+    pub last_refreshed: Option<u64>, // Unix秒
+    #[serde(default)]
+    pub episodes: Vec<Episode>, // 按订阅源中的顺序（通常最新在前）
+}
+
+/// 播客的一期节目
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Episode {
+    pub id: String, // guid，没有时为音频地址
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>, // 已去掉 HTML 标记
+    #[serde(default)]
+    pub published: Option<String>, // 订阅源中的 pubDate 原文
+    #[serde(default)]
+    pub duration: Option<u64>, // 秒
+    pub url: String,
+    #[serde(rename = "mimeType", default)]
+    pub mime_type: Option<String>,
+    #[serde(default)]
+    pub size: Option<u64>, // 字节
+    #[serde(rename = "downloadPath", default)]
+    pub download_path: Option<String>, // 已下载时的本地文件
+}
+
+impl Episode {
+    /// 播放时使用的路径：已下载的文件优先，否则在线播放
+    pub fn play_path(&self) -> &str {
+        self.download_path.as_deref().unwrap_or(&self.url)
+    }
+}
+
+/// 播客列表中的一项（不含节目）
+#[derive(Debug, Clone, Serialize)]
+pub struct PodcastSummary {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    #[serde(rename = "lastRefreshed")]
+    pub last_refreshed: Option<u64>,
+    #[serde(rename = "episodeCount")]
+    pub episode_count: usize,
+}
+
+impl From<&Podcast> for PodcastSummary {
+    fn from(podcast: &Podcast) -> Self {
+        Self {
+            id: podcast.id.clone(),
+            url: podcast.url.clone(),
+            title: podcast.title.clone(),
+            author: podcast.author.clone(),
+            description: podcast.description.clone(),
+            image: podcast.image.clone(),
+            last_refreshed: podcast.last_refreshed,
+            episode_count: podcast.episodes.len(),
+        }
+    }
+}
+
+/// 节目和上次收听到的位置
+#[derive(Debug, Clone, Serialize)]
+pub struct EpisodeEntry {
+    #[serde(flatten)]
+    pub episode: Episode,
+    pub position: Option<u64>, // 秒，没有记录或已听完时为空
+}
+
+fn podcasts_path() -> PathBuf {
+    storage::data_dir().join("podcasts.json")
+}
+
+fn downloads_dir() -> PathBuf {
+    storage::data_dir().join("podcasts")
+}
+
+fn podcasts_lock() -> &'static RwLock<Vec<Podcast>> {
+    static PODCASTS: OnceLock<RwLock<Vec<Podcast>>> = OnceLock::new();
+    PODCASTS.get_or_init(|| {
+        let loaded = storage::load_json(&podcasts_path()).unwrap_or_else(|e| {
+            eprintln!("读取播客订阅失败: {}", e);
+            None
+        });
+        RwLock::new(loaded.unwrap_or_default())
+    })
+}
+
+fn save(podcasts: &[Podcast]) -> anyhow::Result<()> {
+    storage::save_json(&podcasts_path(), podcasts)
+}
+
+/// 修改订阅列表并保存
+fn update<T>(f: impl FnOnce(&mut Vec<Podcast>) -> anyhow::Result<T>) -> anyhow::Result<T> {
+    let mut podcasts = podcasts_lock().write().map_err(|_| anyhow::anyhow!("无法锁定播客订阅"))?;
+    let result = f(&mut podcasts)?;
+    save(&podcasts)?;
+    Ok(result)
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 所有订阅（不含节目）
+pub fn list() -> Vec<PodcastSummary> {
+    podcasts_lock()
+        .read()
+        .map(|p| p.iter().map(PodcastSummary::from).collect())
+        .unwrap_or_default()
+}
+
+/// 查找一期节目
+pub fn episode(podcast_id: &str, episode_id: &str) -> Option<(Podcast, Episode)> {
+    let podcasts = podcasts_lock().read().ok()?;
+    let podcast = podcasts.iter().find(|p| p.id == podcast_id)?;
+    let episode = podcast.episodes.iter().find(|e| e.id == episode_id)?.clone();
+    Some((podcast.clone(), episode))
+}
+
+/// 路径是否为播客节目（在线地址或已下载的文件），播客节目总是记住收听位置
+pub fn is_episode(path: &str) -> bool {
+    if Path::new(path).starts_with(downloads_dir()) {
+        return true;
+    }
+    podcasts_lock()
+        .read()
+        .map(|p| p.iter().flat_map(|p| &p.episodes).any(|e| e.url == path))
+        .unwrap_or(false)
+}
+
+/// 节目列表，附带各期上次收听到的位置
+pub fn episodes(podcast_id: &str) -> anyhow::Result<Vec<EpisodeEntry>> {
+    let episodes = podcasts_lock()
+        .read()
+        .map_err(|_| anyhow::anyhow!("无法锁定播客订阅"))?
+        .iter()
+        .find(|p| p.id == podcast_id)
+        .ok_or_else(|| anyhow::anyhow!("未订阅该播客: {}", podcast_id))?
+        .episodes
+        .clone();
+    let entries = library::with_library(|lib| {
+        episodes
+            .into_iter()
+            .map(|episode| -> anyhow::Result<EpisodeEntry> {
+                let point = lib.resume_point(episode.play_path())?;
+                let position = point.and_then(|p| p.position_ms).map(|ms| ms / 1000);
+                Ok(EpisodeEntry { episode, position })
+            })
+            .collect()
+    })
+    .map_err(|e| anyhow::anyhow!(e))?;
+    Ok(entries)
+}
+
+/// 订阅播客：下载并解析 RSS，已订阅时刷新节目
+pub async fn add(url: &str) -> anyhow::Result<PodcastSummary> {
+    let url = url.trim().to_string();
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(anyhow::anyhow!("无效的订阅地址: {}", url));
+    }
+    let feed = fetch(&url).await?;
+    let id = blake3::hash(url.as_bytes()).to_hex()[..16].to_string();
+    update(|podcasts| {
+        let index = match podcasts.iter().position(|p| p.id == id) {
+            Some(index) => index,
+            None => {
+                podcasts.push(Podcast {
+                    id: id.clone(),
+                    url: url.clone(),
+                    title: String::new(),
+                    author: None,
+                    description: None,
+                    image: None,
+                    last_refreshed: None,
+                    episodes: Vec::new(),
+                });
+                podcasts.len() - 1
+            }
+        };
+        let podcast = &mut podcasts[index];
+        apply_feed(podcast, feed);
+        println!("🎧 已订阅播客: {}（{} 期）", podcast.title, podcast.episodes.len());
+        Ok(PodcastSummary::from(&*podcast))
+    })
+}
+
+/// 取消订阅，同时删除已下载的节目
+pub fn remove(podcast_id: &str) -> anyhow::Result<()> {
+    let removed = update(|podcasts| {
+        let index = podcasts
+            .iter()
+            .position(|p| p.id == podcast_id)
+            .ok_or_else(|| anyhow::anyhow!("未订阅该播客: {}", podcast_id))?;
+        Ok(podcasts.remove(index))
+    })?;
+    let dir = downloads_dir().join(&removed.id);
+    if dir.exists() {
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            eprintln!("删除已下载的播客节目失败 {}: {}", dir.display(), e);
+        }
+    }
+    Ok(())
+}
+
+/// 刷新所有订阅，单个订阅源失败时保留原来的节目
+pub async fn refresh_all() -> Vec<PodcastSummary> {
+    let urls: Vec<(String, String)> = podcasts_lock()
+        .read()
+        .map(|p| p.iter().map(|p| (p.id.clone(), p.url.clone())).collect())
+        .unwrap_or_default();
+    for (id, url) in urls {
+        let feed = match fetch(&url).await {
+            Ok(feed) => feed,
+            Err(e) => {
+                eprintln!("刷新播客失败 {}: {}", url, e);
+                continue;
+            }
+        };
+        let result = update(|podcasts| {
+            if let Some(podcast) = podcasts.iter_mut().find(|p| p.id == id) {
+                apply_feed(podcast, feed);
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            eprintln!("保存播客订阅失败: {}", e);
+        }
+    }
+    list()
+}
+
+/// 下载一期节目，返回本地文件路径；之后播放这期节目时使用本地文件
+pub async fn download(podcast_id: &str, episode_id: &str) -> anyhow::Result<String> {
+    let (podcast, episode) = episode(podcast_id, episode_id).ok_or_else(|| anyhow::anyhow!("找不到这期节目: {}", episode_id))?;
+    if let Some(path) = episode.download_path.as_ref().filter(|p| Path::new(p).exists()) {
+        return Ok(path.clone());
+    }
+
+    let ext = episode
+        .url
+        .split(['?', '#'])
+        .next()
+        .and_then(|u| Path::new(u).extension())
+        .and_then(|e| e.to_str())
+        .filter(|e| e.len() <= 4 && e.chars().all(|c| c.is_ascii_alphanumeric()))
+        .unwrap_or("mp3")
+        .to_string();
+    let dir = downloads_dir().join(&podcast.id);
+    tokio::fs::create_dir_all(&dir).await?;
+    // 文件名取节目 ID 的哈希：标题可能重复、过长或含有不能用于文件名的字符，显示的标题取自订阅源
+    let name = blake3::hash(episode.id.as_bytes()).to_hex()[..16].to_string();
+    let path = dir.join(format!("{}.{}", name, ext));
+    let part_path = path.with_extension(format!("{}.part", ext));
+
+    let result: anyhow::Result<()> = async {
+        let (mut response, _) = net::send(net::RetryPolicy::default(), |client| client.get(&episode.url)).await?;
+        let mut file = tokio::fs::File::create(&part_path).await?;
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+        drop(file);
+        tokio::fs::rename(&part_path, &path).await?;
+        Ok(())
+    }
+    .await;
+    if let Err(e) = result {
+        // 下载失败时删除未完成的文件
+        let _ = tokio::fs::remove_file(&part_path).await;
+        return Err(e);
+    }
+
+    let path = path.to_string_lossy().into_owned();
+    // 在线收听时记住的位置转到本地文件上
+    let url = episode.url.clone();
+    let local = path.clone();
+    if let Err(e) = library::with_library(|lib| {
+        if let Some(position_ms) = lib.resume_point(&url)?.and_then(|p| p.position_ms) {
+            lib.set_resume_position(&local, Some(position_ms))?;
+        }
+        Ok(())
+    }) {
+        eprintln!("迁移播客收听位置失败: {}", e);
+    }
+    update(|podcasts| {
+        if let Some(episode) = podcasts
+            .iter_mut()
+            .find(|p| p.id == podcast_id)
+            .and_then(|p| p.episodes.iter_mut().find(|e| e.id == episode_id))
+        {
+            episode.download_path = Some(path.clone());
+        }
+        Ok(())
+    })?;
+    println!("🎧 已下载播客节目: {}", episode.title);
+    Ok(path)
+}
+
+/// 节目的播放条目：标题、主播和时长取自订阅源
+pub fn song_for(podcast: &Podcast, episode: &Episode) -> SongInfo {
+    let mut song = match &episode.download_path {
+        Some(path) if Path::new(path).exists() => SongInfo::from_path(Path::new(path)).unwrap_or_else(|_| SongInfo::from_url(path)),
+        _ => SongInfo::from_url(&episode.url),
+    };
+    song.title = Some(episode.title.clone());
+    song.artist = podcast.author.clone().or_else(|| Some(podcast.title.clone()));
+    song.album = Some(podcast.title.clone());
+    if song.duration.is_none() {
+        song.duration = episode.duration;
+    }
+    // 在线收听的节目由 from_url 创建，不读取音乐库
+    if crate::media_source::is_url(&song.path) {
+        song.apply_resume_point();
+    }
+    song
+}
+
+/// 用新解析的订阅源更新播客，保留已下载节目的本地路径
+fn apply_feed(podcast: &mut Podcast, feed: Feed) {
+    let old = std::mem::take(&mut podcast.episodes);
+    podcast.title = feed.title.unwrap_or_else(|| podcast.url.clone());
+    podcast.author = feed.author;
+    podcast.description = feed.description;
+    podcast.image = feed.image;
+    podcast.last_refreshed = Some(now_secs());
+    podcast.episodes = feed
+        .episodes
+        .into_iter()
+        .map(|mut episode| {
+            episode.download_path = old
+                .iter()
+                .find(|e| e.id == episode.id)
+                .and_then(|e| e.download_path.clone())
+                .filter(|p| Path::new(p).exists());
+            episode
+        })
+        .collect();
+}
+
+async fn fetch(url: &str) -> anyhow::Result<Feed> {
+    let (response, _) = net::send(net::RetryPolicy::default(), |client| client.get(url).timeout(FEED_TIMEOUT)).await?;
+    let xml = response.text().await?;
+    parse_feed(&xml)
+}
+
+#[derive(Default)]
+struct Feed {
+    title: Option<String>,
+    author: Option<String>,
+    description: Option<String>,
+    image: Option<String>,
+    episodes: Vec<Episode>,
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Option<String> {
+    let value = element.try_get_attribute(name).ok()??.unescape_value().ok()?;
+    Some(value.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+/// 去掉 HTML 标记并合并空白
+fn plain_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text.replace("&nbsp;", " ").replace("&amp;", "&").replace("&lt;", "<").replace("&gt;", ">");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 节目时长：秒数、MM:SS 或 HH:MM:SS
+fn parse_duration(text: &str) -> Option<u64> {
+    text.trim()
+        .split(':')
+        .try_fold(0u64, |total, part| Some(total * 60 + part.trim().parse::<f64>().ok()? as u64))
+        .filter(|secs| *secs > 0)
+}
+
+/// 解析 RSS 2.0（含 iTunes 扩展）：元素按本地名匹配，itunes:duration 即 duration
+fn parse_feed(xml: &str) -> anyhow::Result<Feed> {
+    let mut reader = Reader::from_str(xml);
+    let mut feed = Feed::default();
+    let mut path: Vec<String> = Vec::new();
+    let mut item: Option<Episode> = None;
+    let mut text = String::new();
+
+    loop {
+        let event = reader.read_event().map_err(|e| anyhow::anyhow!("解析订阅源失败: {}", e))?;
+        match event {
+            Event::Start(ref element) | Event::Empty(ref element) => {
+                let name = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+                match name.as_str() {
+                    "item" => item = Some(Episode::default()),
+                    "enclosure" => {
+                        if let (Some(episode), Some(url)) = (item.as_mut(), attribute(element, "url")) {
+                            episode.url = url;
+                            episode.mime_type = attribute(element, "type");
+                            episode.size = attribute(element, "length").and_then(|l| l.parse().ok()).filter(|l| *l > 0);
+                        }
+                    }
+                    // itunes:image 的地址在 href 属性中
+                    "image" if item.is_none() && feed.image.is_none() => feed.image = attribute(element, "href"),
+                    _ => {}
+                }
+                if matches!(event, Event::Start(_)) {
+                    path.push(name);
+                    text.clear();
+                }
+            }
+            Event::Text(t) => text.push_str(&t.unescape().unwrap_or_default()),
+            Event::CData(c) => text.push_str(&String::from_utf8_lossy(&c.into_inner())),
+            Event::End(_) => {
+                let Some(name) = path.pop() else { continue };
+                let parent = path.last().map(String::as_str);
+                let value = std::mem::take(&mut text).trim().to_string();
+                // 没有音频附件的条目（如纯文字公告）跳过
+                if name == "item" {
+                    if let Some(mut episode) = item.take().filter(|e| !e.url.is_empty()) {
+                        if episode.id.is_empty() {
+                            episode.id = episode.url.clone();
+                        }
+                        if episode.title.is_empty() {
+                            episode.title = episode.published.clone().unwrap_or_else(|| episode.url.clone());
+                        }
+                        feed.episodes.push(episode);
+                    }
+                    continue;
+                }
+                match item.as_mut() {
+                    Some(_) if value.is_empty() => {}
+                    Some(episode) => match name.as_str() {
+                        "title" => episode.title = value,
+                        "guid" => episode.id = value,
+                        "pubDate" => episode.published = Some(value),
+                        "duration" => episode.duration = parse_duration(&value),
+                        // 优先用 description，没有时用 itunes:summary 或 content:encoded
+                        "description" => episode.description = Some(plain_text(&value)),
+                        "summary" | "encoded" if episode.description.is_none() => episode.description = Some(plain_text(&value)),
+                        _ => {}
+                    },
+                    None if value.is_empty() => {}
+                    None => match (name.as_str(), parent) {
+                        ("title", Some("channel")) => feed.title = Some(value),
+                        ("author", Some("channel")) => feed.author = Some(value),
+                        ("description", Some("channel")) => feed.description = Some(plain_text(&value)),
+                        ("summary", Some("channel")) if feed.description.is_none() => feed.description = Some(plain_text(&value)),
+                        ("url", Some("image")) if feed.image.is_none() => feed.image = Some(value),
+                        _ => {}
+                    },
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if feed.title.is_none() && feed.episodes.is_empty() {
+        return Err(anyhow::anyhow!("不是有效的播客订阅源"));
+    }
+    Ok(feed)
+}
@@ -0,0 +1,66 @@
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+/// 允许的毫秒级进度事件间隔范围
+const MIN_INTERVAL_MS: u64 = 50;
+const MAX_INTERVAL_MS: u64 = 1000;
+
+/// 毫秒级进度事件设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressSettings {
+    /// 播放中发送 PositionUpdate 事件的间隔（毫秒）；0 表示不发送，只保留每秒一次的 ProgressUpdate
+    #[serde(rename = "intervalMs", default = "default_interval_ms")]
+    pub interval_ms: u64,
+}
+
+fn default_interval_ms() -> u64 {
+    250
+}
+
+impl Default for ProgressSettings {
+    fn default() -> Self {
+        Self {
+            interval_ms: default_interval_ms(),
+        }
+    }
+}
+
+impl ProgressSettings {
+    /// 发送间隔，关闭时返回 None
+    pub fn interval(&self) -> Option<Duration> {
+        (self.interval_ms > 0).then(|| Duration::from_millis(self.interval_ms))
+    }
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("progress.json")
+}
+
+fn settings_lock() -> &'static RwLock<ProgressSettings> {
+    static SETTINGS: OnceLock<RwLock<ProgressSettings>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let loaded = storage::load_json(&settings_path()).unwrap_or_else(|e| {
+            eprintln!("读取进度事件设置失败: {}", e);
+            None
+        });
+        RwLock::new(loaded.unwrap_or_default())
+    })
+}
+
+/// 获取进度事件设置
+pub fn settings() -> ProgressSettings {
+    settings_lock().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// 保存进度事件设置（播放器线程在下一次进度更新时采用新的间隔）
+pub fn set_settings(mut settings: ProgressSettings) -> anyhow::Result<()> {
+    if settings.interval_ms > 0 {
+        settings.interval_ms = settings.interval_ms.clamp(MIN_INTERVAL_MS, MAX_INTERVAL_MS);
+    }
+    storage::save_json(&settings_path(), &settings)?;
+    *settings_lock().write().map_err(|_| anyhow::anyhow!("无法锁定进度事件设置"))? = settings;
+    Ok(())
+}
@@ -0,0 +1,326 @@
+use crate::player_fixed::SongInfo;
+use crate::storage;
+use rodio::Source;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::Duration;
+
+/// 每帧音乐音量向目标靠近的幅度（48kHz 下约 80ms 完成一次压低/恢复）
+const DUCK_STEP: f32 = 0.0002;
+
+/// 音乐音量目标值（f32 位模式），播报期间为压低后的音量，其余时间为 1.0
+static DUCK_TARGET: AtomicU32 = AtomicU32::new(0x3F80_0000);
+
+/// 自上次播报以来自然切歌的次数
+static TRANSITIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// 电台主持人模式设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RadioHostSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 每隔几次自然切歌播报一次（1 为每次都播报）
+    #[serde(default = "default_every")]
+    pub every: u32,
+    /// 播报期间音乐音量的比例（0-1）
+    #[serde(rename = "duckLevel", default = "default_duck_level")]
+    pub duck_level: f32,
+    /// 播报文本，{prev}/{prevArtist} 为刚播完的曲目，{next}/{nextArtist} 为下一首
+    #[serde(default = "default_template")]
+    pub template: String,
+    /// 无法预知下一首（随机播放）时的播报文本
+    #[serde(rename = "templateNoNext", default = "default_template_no_next")]
+    pub template_no_next: String,
+    /// 系统语音合成使用的声音名称，为空时使用系统默认声音
+    #[serde(default)]
+    pub voice: Option<String>,
+}
+
+fn default_every() -> u32 {
+    1
+}
+
+fn default_duck_level() -> f32 {
+    0.25
+}
+
+fn default_template() -> String {
+    "刚才为您播放的是{prevArtist}的{prev}，接下来是{nextArtist}的{next}".to_string()
+}
+
+fn default_template_no_next() -> String {
+    "刚才为您播放的是{prevArtist}的{prev}".to_string()
+}
+
+impl Default for RadioHostSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            every: default_every(),
+            duck_level: default_duck_level(),
+            template: default_template(),
+            template_no_next: default_template_no_next(),
+            voice: None,
+        }
+    }
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("radio_host.json")
+}
+
+fn settings_lock() -> &'static RwLock<RadioHostSettings> {
+    static SETTINGS: OnceLock<RwLock<RadioHostSettings>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let loaded = storage::load_json(&settings_path()).unwrap_or_else(|e| {
+            eprintln!("读取电台主持人设置失败: {}", e);
+            None
+        });
+        RwLock::new(loaded.unwrap_or_default())
+    })
+}
+
+/// 获取电台主持人模式设置
+pub fn settings() -> RadioHostSettings {
+    settings_lock().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// 保存电台主持人模式设置
+pub fn set_settings(mut settings: RadioHostSettings) -> anyhow::Result<()> {
+    settings.every = settings.every.max(1);
+    settings.duck_level = settings.duck_level.clamp(0.0, 1.0);
+    storage::save_json(&settings_path(), &settings)?;
+    *settings_lock().write().map_err(|_| anyhow::anyhow!("无法锁定电台主持人设置"))? = settings;
+    TRANSITIONS.store(0, Ordering::Relaxed);
+    Ok(())
+}
+
+/// 预先合成好的播报
+#[derive(Debug, Clone)]
+struct PreparedClip {
+    prev_path: String,
+    text: String,
+    file: PathBuf,
+}
+
+/// 当前曲目结束时要播放的播报（合成完成后才有值）
+fn prepared() -> &'static Mutex<Option<PreparedClip>> {
+    static PREPARED: OnceLock<Mutex<Option<PreparedClip>>> = OnceLock::new();
+    PREPARED.get_or_init(|| Mutex::new(None))
+}
+
+fn clip_dir() -> PathBuf {
+    std::env::temp_dir().join("music-player-radio-host")
+}
+
+fn display_title(song: &SongInfo) -> String {
+    song.title.clone().unwrap_or_else(|| {
+        Path::new(&song.path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    })
+}
+
+fn render_text(settings: &RadioHostSettings, prev: &SongInfo, next: Option<&SongInfo>) -> String {
+    let template = if next.is_some() { &settings.template } else { &settings.template_no_next };
+    let unknown = "未知艺术家".to_string();
+    let mut text = template
+        .replace("{prevArtist}", prev.artist.as_ref().unwrap_or(&unknown))
+        .replace("{prev}", &display_title(prev));
+    if let Some(next) = next {
+        text = text
+            .replace("{nextArtist}", next.artist.as_ref().unwrap_or(&unknown))
+            .replace("{next}", &display_title(next));
+    }
+    text
+}
+
+/// 调用系统语音合成把文本渲染为 WAV 文件
+fn synthesize(text: &str, voice: Option<&str>, out: &Path) -> anyhow::Result<()> {
+    let status = if cfg!(target_os = "windows") {
+        let script = "Add-Type -AssemblyName System.Speech; \
+            $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+            if ($env:TTS_VOICE) { $s.SelectVoice($env:TTS_VOICE) }; \
+            $s.SetOutputToWaveFile($env:TTS_OUT); $s.Speak($env:TTS_TEXT); $s.Dispose()";
+        Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", script])
+            .env("TTS_OUT", out)
+            .env("TTS_TEXT", text)
+            .env("TTS_VOICE", voice.unwrap_or(""))
+            .status()?
+    } else if cfg!(target_os = "macos") {
+        let mut cmd = Command::new("say");
+        cmd.arg("-o").arg(out).args(["--file-format=WAVE", "--data-format=LEI16@22050"]);
+        if let Some(voice) = voice {
+            cmd.args(["-v", voice]);
+        }
+        cmd.arg(text).status()?
+    } else {
+        let run = |program: &str| {
+            let mut cmd = Command::new(program);
+            cmd.arg("-w").arg(out);
+            if let Some(voice) = voice {
+                cmd.args(["-v", voice]);
+            }
+            cmd.arg(text).status()
+        };
+        run("espeak-ng").or_else(|_| run("espeak"))?
+    };
+    if !status.success() || !out.is_file() {
+        return Err(anyhow::anyhow!("语音合成失败: {}", status));
+    }
+    Ok(())
+}
+
+/// 在后台为当前曲目结束时的播报合成语音，播放器在曲目接近结尾时调用
+/// next 为空表示无法预知下一首
+pub fn prepare(prev: &SongInfo, next: Option<&SongInfo>) {
+    let settings = settings();
+    if !settings.enabled {
+        return;
+    }
+    let text = render_text(&settings, prev, next);
+    let prev_path = prev.path.clone();
+    std::thread::spawn(move || {
+        let dir = clip_dir();
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            eprintln!("无法创建播报缓存目录 {}: {}", dir.display(), e);
+            return;
+        }
+        let key = format!("{}\n{}", text, settings.voice.as_deref().unwrap_or(""));
+        let file = dir.join(format!("{}.wav", blake3::hash(key.as_bytes()).to_hex()));
+        if !file.is_file() {
+            if let Err(e) = synthesize(&text, settings.voice.as_deref(), &file) {
+                eprintln!("🎙️ 生成电台播报失败: {}", e);
+                return;
+            }
+        }
+        if let Ok(mut prepared) = prepared().lock() {
+            // 删除上一条未播放的播报
+            if let Some(old) = prepared.take().filter(|old| old.file != file) {
+                let _ = std::fs::remove_file(old.file);
+            }
+            *prepared = Some(PreparedClip { prev_path, text, file });
+        }
+    });
+}
+
+/// 曲目自然播完时取出要播放的播报：按频率设置计数，只返回为该曲目合成好的播报
+/// 返回 (播报文本, 解码后的音源)
+pub fn take_clip(finished_path: &str) -> Option<(String, Announcement<rodio::Decoder<std::io::BufReader<std::fs::File>>>)> {
+    let settings = settings();
+    let clip = prepared().lock().ok()?.take()?;
+    if !settings.enabled || clip.prev_path != finished_path {
+        return None;
+    }
+    let count = TRANSITIONS.fetch_add(1, Ordering::Relaxed) + 1;
+    if count % settings.every.max(1) as usize != 0 {
+        return None;
+    }
+    let decoder = std::fs::File::open(&clip.file)
+        .map_err(anyhow::Error::from)
+        .and_then(|file| Ok(rodio::Decoder::new(std::io::BufReader::new(file))?));
+    // 已解码打开，删除文件不影响播放（Windows 下删除会失败，留待下次清理目录）
+    let _ = std::fs::remove_file(&clip.file);
+    match decoder {
+        Ok(decoder) => Some((clip.text, Announcement::new(decoder, settings.duck_level))),
+        Err(e) => {
+            eprintln!("🎙️ 无法打开电台播报 {}: {}", clip.file.display(), e);
+            None
+        }
+    }
+}
+
+/// 按播报状态让音乐音量向目标靠近一帧，由音乐的 DSP 链每帧调用
+#[inline]
+pub fn duck_step(current: f32) -> f32 {
+    let target = f32::from_bits(DUCK_TARGET.load(Ordering::Relaxed));
+    if current > target {
+        (current - DUCK_STEP).max(target)
+    } else {
+        (current + DUCK_STEP).min(target)
+    }
+}
+
+fn set_duck_target(level: f32) {
+    DUCK_TARGET.store(level.to_bits(), Ordering::Relaxed);
+}
+
+/// 播报音源：开始发声时压低音乐音量，播完或被停止时恢复
+pub struct Announcement<S> {
+    inner: S,
+    duck_level: f32,
+    started: bool,
+}
+
+impl<S> Announcement<S> {
+    fn new(inner: S, duck_level: f32) -> Self {
+        Self {
+            inner,
+            duck_level,
+            started: false,
+        }
+    }
+}
+
+impl<S> Iterator for Announcement<S>
+where
+    S: Source<Item = i16>,
+{
+    type Item = i16;
+
+    #[inline]
+    fn next(&mut self) -> Option<i16> {
+        if !self.started {
+            self.started = true;
+            set_duck_target(self.duck_level);
+        }
+        let sample = self.inner.next();
+        if sample.is_none() {
+            set_duck_target(1.0);
+        }
+        sample
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<S> Source for Announcement<S>
+where
+    S: Source<Item = i16>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+impl<S> Drop for Announcement<S> {
+    fn drop(&mut self) {
+        if self.started {
+            set_duck_target(1.0);
+        }
+    }
+}
@@ -0,0 +1,538 @@
+use crate::global_player::GlobalPlayer;
+use crate::http_server;
+use crate::player_fixed::{PlayMode, PlayerCommand, PlayerState, SongInfo};
+use crate::player_safe::SafePlayerManager;
+use crate::storage;
+use axum::extract::{Path as UrlPath, Request};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use rand::distributions::{Alphanumeric, DistString};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// 内嵌HTTP服务还没有运行时使用的端口（已运行时远程控制接口使用同一个端口）
+const DEFAULT_PORT: u16 = 17891;
+
+/// 访问令牌长度
+const TOKEN_LENGTH: usize = 32;
+
+/// 远程控制接口设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteApiSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub token: String, // 请求需带 Authorization: Bearer <token>，第一次启用时生成
+    #[serde(rename = "guestToken", default)]
+    pub guest_token: String, // 二维码中给手机使用的令牌，可以单独重置收回，不影响 token
+}
+
+fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+impl Default for RemoteApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+            token: String::new(),
+            guest_token: String::new(),
+        }
+    }
+}
+
+/// 设置和运行状态
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteApiStatus {
+    #[serde(flatten)]
+    pub settings: RemoteApiSettings,
+    #[serde(rename = "runningPort")]
+    pub running_port: Option<u16>,
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("remote_api.json")
+}
+
+fn settings_lock() -> &'static RwLock<RemoteApiSettings> {
+    static SETTINGS: OnceLock<RwLock<RemoteApiSettings>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let loaded = storage::load_json(&settings_path()).unwrap_or_else(|e| {
+            eprintln!("读取远程控制设置失败: {}", e);
+            None
+        });
+        RwLock::new(loaded.unwrap_or_default())
+    })
+}
+
+/// 获取远程控制设置
+pub fn settings() -> RemoteApiSettings {
+    settings_lock().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+fn save_settings(settings: RemoteApiSettings) -> anyhow::Result<()> {
+    storage::save_json(&settings_path(), &settings)?;
+    *settings_lock().write().map_err(|_| anyhow::anyhow!("无法锁定远程控制设置"))? = settings;
+    Ok(())
+}
+
+fn new_token() -> String {
+    Alphanumeric.sample_string(&mut rand::thread_rng(), TOKEN_LENGTH)
+}
+
+/// 当前服务端口（未启用或内嵌HTTP服务没有在局域网上运行时为 None）
+pub fn running_port() -> Option<u16> {
+    if !settings().enabled {
+        return None;
+    }
+    http_server::lan_port()
+}
+
+/// 获取设置和运行中的端口
+pub fn status() -> RemoteApiStatus {
+    RemoteApiStatus {
+        settings: settings(),
+        running_port: running_port(),
+    }
+}
+
+/// 启用或停用远程控制接口并保存设置，启用时返回实际端口
+pub async fn set_enabled(enabled: bool, port: Option<u16>) -> anyhow::Result<Option<u16>> {
+    let mut settings = settings();
+    settings.enabled = enabled;
+    if let Some(port) = port {
+        settings.port = port;
+    }
+    if settings.token.is_empty() {
+        settings.token = new_token();
+    }
+    save_settings(settings.clone())?;
+    if !enabled {
+        return Ok(None);
+    }
+    http_server::ensure_lan(settings.port).await.map(Some).map_err(anyhow::Error::msg)
+}
+
+/// 重新生成访问令牌，之前的令牌立即失效
+pub fn reset_token() -> anyhow::Result<String> {
+    let mut settings = settings();
+    settings.token = new_token();
+    save_settings(settings.clone())?;
+    Ok(settings.token)
+}
+
+/// 重新生成二维码中的手机令牌，已扫码的手机立即失效，需要重新扫描新的二维码
+pub fn reset_guest_token() -> anyhow::Result<()> {
+    let mut settings = settings();
+    settings.guest_token = new_token();
+    save_settings(settings)
+}
+
+/// 按保存的设置启动（应用启动时调用）
+pub async fn start_saved() {
+    let settings = settings();
+    if settings.enabled && !settings.token.is_empty() {
+        if let Err(e) = http_server::ensure_lan(settings.port).await {
+            eprintln!("启动远程控制接口失败: {}", e);
+        }
+    }
+}
+
+/// 远程控制接口的路由，挂在内嵌HTTP服务上（由 http_server 负责监听），未启用时全部返回 404
+/// 页面本身不需要令牌，页面从地址的 #token= 中取得令牌后再调用接口；派对模式的来宾接口用来宾 ID 识别
+pub fn router() -> Router {
+    Router::new()
+        .route("/api/state", get(get_state))
+        .route("/api/playlist", get(get_playlist))
+        .route("/api/play", post(play))
+        .route("/api/play/:index", post(play_index))
+        .route("/api/pause", post(pause))
+        .route("/api/toggle", post(toggle))
+        .route("/api/stop", post(stop_playback))
+        .route("/api/next", post(next))
+        .route("/api/previous", post(previous))
+        .route("/api/seek", post(seek))
+        .route("/api/volume", post(volume))
+        .route_layer(middleware::from_fn(authorize))
+        .route("/", get(remote_page))
+        .merge(crate::party::router())
+        .route_layer(middleware::from_fn(require_enabled))
+}
+
+/// 是否为远程控制接口（含派对模式）的路径，局域网设备可以访问
+pub fn serves(path: &str) -> bool {
+    path == "/" || path.starts_with("/api/") || path == "/party" || path.starts_with("/party/")
+}
+
+async fn require_enabled(request: Request, next: Next) -> Response {
+    if settings().enabled {
+        next.run(request).await
+    } else {
+        (StatusCode::NOT_FOUND, "远程控制接口未启用").into_response()
+    }
+}
+
+/// 本机在局域网中的地址：向外“连接”一个 UDP 套接字（不会发送数据），取系统选择的本地地址
+fn lan_ip() -> Option<std::net::IpAddr> {
+    let socket = std::net::UdpSocket::bind(("0.0.0.0", 0)).ok()?;
+    socket.connect(("192.168.0.1", 80)).ok()?;
+    Some(socket.local_addr().ok()?.ip()).filter(|ip| !ip.is_unspecified())
+}
+
+/// 手机遥控页面的地址和二维码
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteQr {
+    pub url: String,
+    pub svg: String, // 二维码 SVG
+}
+
+/// 生成手机遥控页面的局域网地址（令牌放在 # 之后，不会出现在请求中）和二维码，服务未运行时返回错误
+/// 二维码使用单独的手机令牌（第一次生成时创建），而不是主令牌
+pub fn qr() -> anyhow::Result<RemoteQr> {
+    let port = running_port().ok_or_else(|| anyhow::anyhow!("远程控制接口未启用"))?;
+    let ip = lan_ip().ok_or_else(|| anyhow::anyhow!("无法获取局域网地址"))?;
+    let mut settings = settings();
+    if settings.guest_token.is_empty() {
+        settings.guest_token = new_token();
+        save_settings(settings.clone())?;
+    }
+    let url = format!("http://{}:{}/#token={}", ip, port, settings.guest_token);
+    let svg = qrcode::QrCode::new(url.as_bytes())?
+        .render::<qrcode::render::svg::Color>()
+        .min_dimensions(256, 256)
+        .quiet_zone(true)
+        .build();
+    Ok(RemoteQr { url, svg })
+}
+
+/// GET / — 手机遥控页面
+async fn remote_page() -> Response {
+    ([(header::CONTENT_TYPE, "text/html; charset=utf-8")], REMOTE_HTML).into_response()
+}
+
+/// 检查 Authorization: Bearer <token>，主令牌和手机令牌都可以
+async fn authorize(request: Request, next: Next) -> Response {
+    let settings = settings();
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(str::trim);
+    match provided {
+        Some(provided)
+            if [&settings.token, &settings.guest_token]
+                .into_iter()
+                .any(|token| !token.is_empty() && token_matches(provided, token)) =>
+        {
+            next.run(request).await
+        }
+        _ => (StatusCode::UNAUTHORIZED, [(header::WWW_AUTHENTICATE, "Bearer")], "缺少或错误的访问令牌").into_response(),
+    }
+}
+
+/// 比较令牌，耗时只取决于长度而与内容无关，不能通过响应时间逐字猜出令牌
+pub fn token_matches(provided: &str, expected: &str) -> bool {
+    provided.len() == expected.len()
+        && provided.bytes().zip(expected.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+pub type ApiResult<T> = Result<T, (StatusCode, String)>;
+
+pub async fn player() -> ApiResult<Arc<SafePlayerManager>> {
+    let instance = GlobalPlayer::instance()
+        .lock()
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "无法锁定 GlobalPlayer".to_string()))?
+        .get_player()
+        .ok_or_else(|| (StatusCode::SERVICE_UNAVAILABLE, "播放器未初始化".to_string()))?;
+    let player = instance.lock().await.player.clone();
+    Ok(player)
+}
+
+pub async fn send(command: PlayerCommand) -> ApiResult<StatusCode> {
+    player()
+        .await?
+        .send_command(command)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 播放列表中的一首歌（不含封面和歌词）
+#[derive(Serialize)]
+pub struct RemoteSong {
+    index: usize,
+    title: String,
+    artist: Option<String>,
+    album: Option<String>,
+    duration: Option<u64>,
+}
+
+impl RemoteSong {
+    pub fn new(index: usize, song: &SongInfo) -> Self {
+        let title = song.title.clone().unwrap_or_else(|| {
+            std::path::Path::new(&song.path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+        Self {
+            index,
+            title,
+            artist: song.artist.clone(),
+            album: song.album.clone(),
+            duration: song.duration,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RemoteState {
+    state: PlayerState,
+    song: Option<RemoteSong>,
+    position: u64, // 秒
+    volume: f32, // 0.0 - 2.0
+    muted: bool,
+    #[serde(rename = "playMode")]
+    play_mode: PlayMode,
+    #[serde(rename = "playlistVersion")]
+    playlist_version: u64, // 播放列表变化后改变，页面据此重新载入队列
+}
+
+/// GET /api/state — 播放状态和当前曲目
+async fn get_state() -> ApiResult<Json<RemoteState>> {
+    let player = player().await?;
+    let playlist = player.get_playlist();
+    let song = player
+        .get_current_index()
+        .and_then(|index| playlist.get(index).map(|song| RemoteSong::new(index, song)));
+    Ok(Json(RemoteState {
+        state: player.get_state(),
+        song,
+        position: player.get_position(),
+        volume: player.get_volume(),
+        muted: player.is_muted(),
+        play_mode: player.get_play_mode(),
+        playlist_version: player.get_playlist_generation(),
+    }))
+}
+
+/// GET /api/playlist — 播放列表
+async fn get_playlist() -> ApiResult<Json<Vec<RemoteSong>>> {
+    let playlist = player().await?.get_playlist();
+    Ok(Json(playlist.iter().enumerate().map(|(index, song)| RemoteSong::new(index, song)).collect()))
+}
+
+/// POST /api/play
+async fn play() -> ApiResult<StatusCode> {
+    send(PlayerCommand::Play).await
+}
+
+/// POST /api/play/:index — 播放列表中的指定歌曲
+async fn play_index(UrlPath(index): UrlPath<usize>) -> ApiResult<StatusCode> {
+    if index >= player().await?.get_playlist().len() {
+        return Err((StatusCode::NOT_FOUND, "歌曲不存在".to_string()));
+    }
+    send(PlayerCommand::SetSong(index)).await
+}
+
+/// POST /api/pause
+async fn pause() -> ApiResult<StatusCode> {
+    send(PlayerCommand::Pause).await
+}
+
+/// POST /api/toggle — 播放/暂停
+async fn toggle() -> ApiResult<StatusCode> {
+    let command = match player().await?.get_state() {
+        PlayerState::Playing => PlayerCommand::Pause,
+        _ => PlayerCommand::Play,
+    };
+    send(command).await
+}
+
+/// POST /api/stop
+async fn stop_playback() -> ApiResult<StatusCode> {
+    send(PlayerCommand::Stop).await
+}
+
+/// POST /api/next
+async fn next() -> ApiResult<StatusCode> {
+    send(PlayerCommand::Next).await
+}
+
+/// POST /api/previous
+async fn previous() -> ApiResult<StatusCode> {
+    send(PlayerCommand::Previous).await
+}
+
+#[derive(Deserialize)]
+struct SeekBody {
+    position: u64, // 秒
+}
+
+/// POST /api/seek {"position": 秒}
+async fn seek(Json(body): Json<SeekBody>) -> ApiResult<StatusCode> {
+    send(PlayerCommand::SeekTo(body.position)).await
+}
+
+#[derive(Deserialize)]
+struct VolumeBody {
+    #[serde(default)]
+    volume: Option<f32>, // 0.0 - 2.0，与播放器相同（1.0 为原音量，超过为增益）
+    #[serde(default)]
+    muted: Option<bool>,
+}
+
+/// POST /api/volume {"volume": 0.0-2.0, "muted": bool}，两项都可省略
+async fn volume(Json(body): Json<VolumeBody>) -> ApiResult<StatusCode> {
+    if let Some(volume) = body.volume {
+        if !(0.0..=2.0).contains(&volume) {
+            return Err((StatusCode::BAD_REQUEST, "音量应在 0 到 2 之间".to_string()));
+        }
+        send(PlayerCommand::SetVolume(volume)).await?;
+    }
+    if let Some(muted) = body.muted {
+        send(PlayerCommand::SetMuted(muted)).await?;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 手机遥控页面：大按钮的上一首/播放暂停/下一首、音量和播放队列，每两秒刷新一次状态
+const REMOTE_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1, maximum-scale=1">
+<title>遥控</title>
+<style>
+  * { box-sizing: border-box; }
+  body { margin: 0; font-family: -apple-system, "PingFang SC", "Microsoft YaHei", sans-serif;
+         background: #111; color: #eee; -webkit-tap-highlight-color: transparent; }
+  header { padding: 24px 20px 12px; text-align: center; }
+  #title { font-size: 22px; font-weight: bold; white-space: nowrap; overflow: hidden; text-overflow: ellipsis; }
+  #artist { margin-top: 6px; color: #999; white-space: nowrap; overflow: hidden; text-overflow: ellipsis; }
+  #progress { height: 4px; margin: 16px 20px 0; background: #333; border-radius: 2px; overflow: hidden; }
+  #progress div { height: 100%; width: 0; background: #5ecbff; }
+  .controls { display: flex; justify-content: center; gap: 20px; padding: 20px; }
+  .controls button { width: 76px; height: 76px; border: 0; border-radius: 50%; background: #262626;
+                     color: #fff; font-size: 30px; }
+  .controls button#toggle { width: 96px; height: 96px; background: #5ecbff; color: #111; font-size: 38px; }
+  .volume { display: flex; align-items: center; gap: 12px; padding: 0 24px 16px; }
+  .volume input { flex: 1; }
+  ul { list-style: none; margin: 0; padding: 0; border-top: 1px solid #222; }
+  li { padding: 14px 20px; border-bottom: 1px solid #222; }
+  li.current { color: #5ecbff; }
+  li small { display: block; color: #777; margin-top: 2px; }
+  #error { display: none; padding: 12px 20px; background: #5a1f1f; text-align: center; }
+</style>
+</head>
+<body>
+<div id="error"></div>
+<header>
+  <div id="title">未在播放</div>
+  <div id="artist"></div>
+  <div id="progress"><div></div></div>
+</header>
+<div class="controls">
+  <button id="previous">&#9198;</button>
+  <button id="toggle">&#9654;</button>
+  <button id="next">&#9197;</button>
+</div>
+<div class="volume">&#128264;<input id="volume" type="range" min="0" max="200">&#128266;</div>
+<ul id="queue"></ul>
+<script>
+// 令牌在地址的 #token= 中，保存后从地址栏去掉
+const hash = new URLSearchParams(location.hash.slice(1));
+if (hash.get("token")) {
+  localStorage.setItem("remoteToken", hash.get("token"));
+  history.replaceState(null, "", location.pathname);
+}
+const token = localStorage.getItem("remoteToken") || "";
+let current = null;
+let playlistVersion = null;
+
+function showError(message) {
+  const el = document.getElementById("error");
+  el.textContent = message || "";
+  el.style.display = message ? "block" : "none";
+}
+
+async function api(path, body) {
+  const options = { method: body === undefined ? "GET" : "POST", headers: { Authorization: "Bearer " + token } };
+  if (body !== undefined && body !== null) {
+    options.headers["Content-Type"] = "application/json";
+    options.body = JSON.stringify(body);
+  }
+  const response = await fetch("/api/" + path, options);
+  if (response.status === 401) throw new Error("访问令牌无效，请重新扫描二维码");
+  if (!response.ok) throw new Error(await response.text());
+  return response.status === 204 ? null : response.json();
+}
+
+async function refresh() {
+  try {
+    const state = await api("state");
+    showError("");
+    const song = state.song;
+    document.getElementById("title").textContent = song ? song.title : "未在播放";
+    document.getElementById("artist").textContent = song && song.artist ? song.artist : "";
+    document.getElementById("toggle").innerHTML = state.state === "Playing" ? "&#10074;&#10074;" : "&#9654;";
+    const percent = song && song.duration ? Math.min(100, state.position / song.duration * 100) : 0;
+    document.querySelector("#progress div").style.width = percent + "%";
+    const volume = document.getElementById("volume");
+    if (document.activeElement !== volume) volume.value = Math.round(state.volume * 100);
+    const index = song ? song.index : null;
+    if (index !== current || state.playlistVersion !== playlistVersion) {
+      current = index;
+      playlistVersion = state.playlistVersion;
+      await loadQueue();
+    }
+  } catch (e) {
+    showError(e.message);
+  }
+}
+
+async function loadQueue() {
+  const songs = await api("playlist");
+  const list = document.getElementById("queue");
+  list.textContent = "";
+  songs.forEach((song) => {
+    const item = document.createElement("li");
+    item.textContent = song.title;
+    if (song.artist) {
+      const artist = document.createElement("small");
+      artist.textContent = song.artist;
+      item.appendChild(artist);
+    }
+    if (song.index === current) item.className = "current";
+    item.onclick = () => act("play/" + song.index);
+    list.appendChild(item);
+  });
+}
+
+async function act(path, body) {
+  try {
+    await api(path, body === undefined ? null : body);
+    setTimeout(refresh, 200);
+  } catch (e) {
+    showError(e.message);
+  }
+}
+
+document.getElementById("previous").onclick = () => act("previous");
+document.getElementById("toggle").onclick = () => act("toggle");
+document.getElementById("next").onclick = () => act("next");
+document.getElementById("volume").onchange = (e) => act("volume", { volume: e.target.value / 100 });
+refresh();
+setInterval(refresh, 2000);
+</script>
+</body>
+</html>
+"#;
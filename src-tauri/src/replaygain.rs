@@ -0,0 +1,153 @@
+use crate::export::REPLAYGAIN_REFERENCE_LUFS;
+use crate::player_fixed::SongInfo;
+use crate::storage;
+use lofty::{ItemKey, TaggedFileExt};
+use rodio::Source;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+/// 允许的前级增益范围（dB）
+const MAX_PREAMP_DB: f32 = 15.0;
+
+/// 音量标准化方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayGainMode {
+    #[default]
+    Off,
+    Track, // 按曲目增益，没有时使用专辑增益
+    Album, // 按专辑增益，保留专辑内曲目间的响度差异；没有时使用曲目增益
+}
+
+/// ReplayGain 设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayGainSettings {
+    #[serde(default)]
+    pub mode: ReplayGainMode,
+    #[serde(default)]
+    pub preamp: f32, // 在标签增益之上额外增加的增益（dB）
+    #[serde(rename = "preventClipping", default = "default_true")]
+    pub prevent_clipping: bool, // 按峰值限制增益，避免削波
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for ReplayGainSettings {
+    fn default() -> Self {
+        Self {
+            mode: ReplayGainMode::Off,
+            preamp: 0.0,
+            prevent_clipping: true,
+        }
+    }
+}
+
+/// 文件标签中的 ReplayGain 信息（增益为 dB，峰值为线性幅度，1.0 为满幅）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplayGainTags {
+    #[serde(rename = "trackGain")]
+    pub track_gain: Option<f32>,
+    #[serde(rename = "trackPeak")]
+    pub track_peak: Option<f32>,
+    #[serde(rename = "albumGain")]
+    pub album_gain: Option<f32>,
+    #[serde(rename = "albumPeak")]
+    pub album_peak: Option<f32>,
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("replaygain.json")
+}
+
+fn settings_lock() -> &'static RwLock<ReplayGainSettings> {
+    static SETTINGS: OnceLock<RwLock<ReplayGainSettings>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let loaded = storage::load_json(&settings_path()).unwrap_or_else(|e| {
+            eprintln!("读取ReplayGain设置失败: {}", e);
+            None
+        });
+        RwLock::new(loaded.unwrap_or_default())
+    })
+}
+
+/// 获取 ReplayGain 设置
+pub fn settings() -> ReplayGainSettings {
+    settings_lock().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// 保存 ReplayGain 设置（从下一次创建音源开始生效）
+pub fn set_settings(mut settings: ReplayGainSettings) -> anyhow::Result<()> {
+    settings.preamp = settings.preamp.clamp(-MAX_PREAMP_DB, MAX_PREAMP_DB);
+    storage::save_json(&settings_path(), &settings)?;
+    *settings_lock().write().map_err(|_| anyhow::anyhow!("无法锁定ReplayGain设置"))? = settings;
+    Ok(())
+}
+
+/// 解析 "-6.52 dB" / "0.988" 形式的标签值
+fn parse_value(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let value = value.strip_suffix("dB").or_else(|| value.strip_suffix("db")).unwrap_or(value);
+    value.trim().parse().ok().filter(|v: &f32| v.is_finite())
+}
+
+/// 读取文件标签中的 ReplayGain 信息，没有任何 ReplayGain 标签时返回 None
+pub fn read_tags(tagged_file: &lofty::TaggedFile) -> Option<ReplayGainTags> {
+    let tag = tagged_file.primary_tag().or_else(|| tagged_file.first_tag())?;
+    let get = |key: ItemKey| tag.get_string(&key).and_then(parse_value);
+    let tags = ReplayGainTags {
+        track_gain: get(ItemKey::ReplayGainTrackGain),
+        track_peak: get(ItemKey::ReplayGainTrackPeak),
+        album_gain: get(ItemKey::ReplayGainAlbumGain),
+        album_peak: get(ItemKey::ReplayGainAlbumPeak),
+    };
+    (tags != ReplayGainTags::default()).then_some(tags)
+}
+
+/// 没有 ReplayGain 标签时，用音乐库中的响度分析结果（积分响度 LUFS、采样峰值）换算出曲目增益
+pub fn analyzed_tags((lufs, peak): (f64, Option<f32>)) -> ReplayGainTags {
+    ReplayGainTags {
+        track_gain: Some((REPLAYGAIN_REFERENCE_LUFS - lufs) as f32),
+        track_peak: peak,
+        ..Default::default()
+    }
+}
+
+/// 按设置计算线性增益系数，不需要调整时返回 None
+pub fn gain_factor(tags: &ReplayGainTags, settings: &ReplayGainSettings) -> Option<f32> {
+    let track = tags.track_gain.map(|gain| (gain, tags.track_peak));
+    let album = tags.album_gain.map(|gain| (gain, tags.album_peak));
+    let (gain_db, peak) = match settings.mode {
+        ReplayGainMode::Off => return None,
+        ReplayGainMode::Track => track.or(album)?,
+        ReplayGainMode::Album => album.or(track)?,
+    };
+    let mut factor = 10f32.powf((gain_db + settings.preamp) / 20.0);
+    if settings.prevent_clipping {
+        if let Some(peak) = peak.filter(|p| *p > 0.0) {
+            factor = factor.min(1.0 / peak);
+        }
+    }
+    Some(factor)
+}
+
+/// 按当前设置为歌曲的音源应用 ReplayGain 增益（没有标签的文件在载入时已换算为响度分析结果）
+/// album 为 true 时（现场专辑模式）开启标准化就按专辑增益，保持同一专辑曲目间的响度关系
+pub fn apply<S>(source: S, song: &SongInfo, album: bool) -> Box<dyn Source<Item = i16> + Send>
+where
+    S: Source<Item = i16> + Send + 'static,
+{
+    let mut settings = settings();
+    if settings.mode == ReplayGainMode::Off {
+        return Box::new(source);
+    }
+    if album {
+        settings.mode = ReplayGainMode::Album;
+    }
+    match song.replay_gain.and_then(|tags| gain_factor(&tags, &settings)) {
+        Some(factor) if (factor - 1.0).abs() > f32::EPSILON => Box::new(source.amplify(factor)),
+        _ => Box::new(source),
+    }
+}
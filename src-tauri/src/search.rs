@@ -0,0 +1,296 @@
+use crate::collation;
+use crate::library;
+use crate::player_fixed::SongInfo;
+use pinyin::ToPinyin;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// 各字段命中时的基础分，完全相同和开头匹配在此基础上加分
+const TITLE_WEIGHT: u32 = 40;
+const ARTIST_WEIGHT: u32 = 30;
+const ALBUM_WEIGHT: u32 = 20;
+const FILENAME_WEIGHT: u32 = 10;
+
+/// 搜索词至少这么多个字符才允许拼写错误
+const FUZZY_MIN_CHARS: usize = 4;
+/// 搜索词达到这么多个字符时允许两处拼写错误
+const FUZZY_TWO_TYPOS_CHARS: usize = 8;
+
+/// 搜索结果来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchSource {
+    Playlist, // 当前播放列表，index 为条目索引
+    Library,  // 音乐库（不在播放列表中的曲目）
+}
+
+/// 一条搜索结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub source: SearchSource,
+    pub index: Option<usize>,
+    pub path: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub score: u32,
+}
+
+/// 待匹配的一条记录，各字段的匹配形式（规范化和拼音）在建立时计算一次
+struct Candidate {
+    path: String,
+    title: Option<String>,
+    artist: Option<String>,
+    album: Option<String>,
+    forms: [Option<FieldForms>; 4], // 标题、艺术家、专辑、文件名
+}
+
+impl Candidate {
+    fn new(path: &str, title: Option<&str>, artist: Option<&str>, album: Option<&str>) -> Self {
+        let file_name = Path::new(path).file_name().map(|n| n.to_string_lossy().into_owned());
+        Self {
+            path: path.to_string(),
+            title: title.map(str::to_string),
+            artist: artist.map(str::to_string),
+            album: album.map(str::to_string),
+            forms: [
+                title.map(FieldForms::new),
+                artist.map(FieldForms::new),
+                album.map(FieldForms::new),
+                file_name.as_deref().map(FieldForms::new),
+            ],
+        }
+    }
+
+    /// 与播放列表条目的元数据相同（条目没有被单独修改过），可以直接复用音乐库中的匹配形式
+    fn same_as(&self, song: &SongInfo) -> bool {
+        self.title == song.title && self.artist == song.artist && self.album == song.album
+    }
+}
+
+/// 音乐库的搜索索引，曲目索引有变动（修订号变化）时才重新建立，避免每次搜索都读取整个音乐库并重新计算拼音
+struct LibraryIndex {
+    revision: u64,
+    tracks: Vec<Candidate>,
+    by_path: HashMap<String, usize>,
+}
+
+fn library_index() -> Arc<LibraryIndex> {
+    static INDEX: OnceLock<Mutex<Option<Arc<LibraryIndex>>>> = OnceLock::new();
+    let cache = INDEX.get_or_init(|| Mutex::new(None));
+    let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+    // 先取修订号再读取曲目：读取期间有写入时下一次搜索会再重建
+    let revision = library::revision();
+    if let Some(index) = cache.as_ref().filter(|index| index.revision == revision) {
+        return index.clone();
+    }
+    let tracks = match library::with_library(|lib| lib.query_tracks(&library::TrackQuery::default())) {
+        Ok(tracks) => tracks,
+        Err(e) => {
+            eprintln!("搜索音乐库失败: {}", e);
+            return Arc::new(LibraryIndex {
+                revision,
+                tracks: Vec::new(),
+                by_path: HashMap::new(),
+            });
+        }
+    };
+    let tracks: Vec<Candidate> = tracks
+        .iter()
+        .map(|t| Candidate::new(&t.path, t.title.as_deref(), t.artist.as_deref(), t.album.as_deref()))
+        .collect();
+    let by_path = tracks.iter().enumerate().map(|(i, t)| (t.path.clone(), i)).collect();
+    let index = Arc::new(LibraryIndex {
+        revision,
+        tracks,
+        by_path,
+    });
+    *cache = Some(index.clone());
+    index
+}
+
+/// 字段的可匹配形式：规范化后的原文，含汉字时还有全拼和拼音首字母（"周杰伦" → "zhoujielun"、"zjl"）
+struct FieldForms {
+    text: String,
+    pinyin: Option<(String, String)>,
+}
+
+impl FieldForms {
+    fn new(value: &str) -> Self {
+        let text = collation::normalize(value);
+        let pinyin = pinyin_forms(&text);
+        Self { text, pinyin }
+    }
+
+    /// 可按完全相同/开头/包含匹配的所有形式
+    fn variants(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.text.as_str())
+            .chain(self.pinyin.iter().flat_map(|(full, initials)| [full.as_str(), initials.as_str()]))
+    }
+}
+
+/// 生成全拼和拼音首字母，不含汉字时返回 None；其他字母数字原样保留，空格和标点去掉
+fn pinyin_forms(text: &str) -> Option<(String, String)> {
+    if !text.chars().any(|c| c.to_pinyin().is_some()) {
+        return None;
+    }
+    let mut full = String::new();
+    let mut initials = String::new();
+    for c in text.chars() {
+        match c.to_pinyin() {
+            Some(p) => {
+                full.push_str(p.plain());
+                initials.push_str(p.first_letter());
+            }
+            None if c.is_alphanumeric() => {
+                full.push(c);
+                initials.push(c);
+            }
+            None => {}
+        }
+    }
+    Some((full, initials))
+}
+
+/// 两个字符串的编辑距离，超过 max 时提前返回 max + 1
+fn edit_distance(a: &[char], b: &[char], max: usize) -> usize {
+    if a.len().abs_diff(b.len()) > max {
+        return max + 1;
+    }
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            row[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        if row.iter().min().is_some_and(|&m| m > max) {
+            return max + 1;
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+/// 拼写容错：搜索词与字段中某个词（或全拼）的编辑距离在允许范围内
+fn fuzzy_match(forms: &FieldForms, term: &str) -> bool {
+    let term: Vec<char> = term.chars().collect();
+    if term.len() < FUZZY_MIN_CHARS {
+        return false;
+    }
+    let max = if term.len() >= FUZZY_TWO_TYPOS_CHARS { 2 } else { 1 };
+    forms
+        .text
+        .split(|c: char| !c.is_alphanumeric())
+        .chain(forms.pinyin.iter().map(|(full, _)| full.as_str()))
+        .filter(|word| !word.is_empty())
+        .any(|word| edit_distance(&term, &word.chars().collect::<Vec<_>>(), max) <= max)
+}
+
+/// 单个字段的得分：完全相同 > 开头匹配 > 包含（原文或拼音）> 拼写相近
+fn field_score(forms: Option<&FieldForms>, term: &str, weight: u32) -> u32 {
+    let Some(forms) = forms else {
+        return 0;
+    };
+    let best = forms
+        .variants()
+        .map(|value| {
+            if value == term {
+                weight * 3
+            } else if value.starts_with(term) {
+                weight * 2
+            } else if value.contains(term) {
+                weight
+            } else {
+                0
+            }
+        })
+        .max()
+        .unwrap_or(0);
+    if best == 0 && fuzzy_match(forms, term) {
+        weight / 2
+    } else {
+        best
+    }
+}
+
+/// 记录的得分：每个搜索词都要命中至少一个字段，否则为 0
+fn score(candidate: &Candidate, terms: &[String]) -> u32 {
+    let [title, artist, album, file_name] = &candidate.forms;
+    let mut total = 0;
+    for term in terms {
+        let best = [
+            field_score(title.as_ref(), term, TITLE_WEIGHT),
+            field_score(artist.as_ref(), term, ARTIST_WEIGHT),
+            field_score(album.as_ref(), term, ALBUM_WEIGHT),
+            field_score(file_name.as_ref(), term, FILENAME_WEIGHT),
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(0);
+        if best == 0 {
+            return 0;
+        }
+        total += best;
+    }
+    total
+}
+
+/// 在播放列表和音乐库中搜索标题、艺术家、专辑和文件名（不区分大小写和重音，中文可用全拼或拼音首字母，
+/// 较长的词允许拼写错误），多个词时每个词都要命中；按得分从高到低返回，同一文件在播放列表中时不再重复返回音乐库结果
+/// 音乐库使用缓存的索引，播放列表条目与音乐库中元数据相同时复用其匹配形式
+pub fn search(query: &str, playlist: &[SongInfo], limit: usize) -> Vec<SearchResult> {
+    let terms: Vec<String> = query.split_whitespace().map(collation::normalize).collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+    let index = library_index();
+
+    let mut results = Vec::new();
+    for (i, song) in playlist.iter().enumerate() {
+        let indexed = index.by_path.get(&song.path).map(|&t| &index.tracks[t]).filter(|t| t.same_as(song));
+        let built;
+        let candidate = match indexed {
+            Some(candidate) => candidate,
+            None => {
+                built = Candidate::new(&song.path, song.title.as_deref(), song.artist.as_deref(), song.album.as_deref());
+                &built
+            }
+        };
+        let score = score(candidate, &terms);
+        if score > 0 {
+            results.push(SearchResult {
+                source: SearchSource::Playlist,
+                index: Some(i),
+                path: song.path.clone(),
+                title: song.title.clone(),
+                artist: song.artist.clone(),
+                album: song.album.clone(),
+                score,
+            });
+        }
+    }
+
+    let in_playlist: HashSet<&str> = playlist.iter().map(|song| song.path.as_str()).collect();
+    for track in index.tracks.iter().filter(|track| !in_playlist.contains(track.path.as_str())) {
+        let score = score(track, &terms);
+        if score > 0 {
+            results.push(SearchResult {
+                source: SearchSource::Library,
+                index: None,
+                path: track.path.clone(),
+                title: track.title.clone(),
+                artist: track.artist.clone(),
+                album: track.album.clone(),
+                score,
+            });
+        }
+    }
+
+    // 同分时播放列表在前，再按原有顺序
+    results.sort_by_key(|r| (std::cmp::Reverse(r.score), r.source == SearchSource::Library));
+    results.truncate(limit);
+    results
+}
@@ -0,0 +1,62 @@
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+/// 单次快进/快退允许的最大步长（秒）
+const MAX_STEP_SECS: u64 = 600;
+
+/// 快进/快退的默认步长，快捷键和耳机按键未指定秒数时使用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeekStepSettings {
+    #[serde(rename = "forwardSecs", default = "default_forward_secs")]
+    pub forward_secs: u64,
+    #[serde(rename = "backwardSecs", default = "default_backward_secs")]
+    pub backward_secs: u64,
+}
+
+fn default_forward_secs() -> u64 {
+    30
+}
+
+fn default_backward_secs() -> u64 {
+    10
+}
+
+impl Default for SeekStepSettings {
+    fn default() -> Self {
+        Self {
+            forward_secs: default_forward_secs(),
+            backward_secs: default_backward_secs(),
+        }
+    }
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("seek_step.json")
+}
+
+fn settings_lock() -> &'static RwLock<SeekStepSettings> {
+    static SETTINGS: OnceLock<RwLock<SeekStepSettings>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let loaded = storage::load_json(&settings_path()).unwrap_or_else(|e| {
+            eprintln!("读取快进快退步长设置失败: {}", e);
+            None
+        });
+        RwLock::new(loaded.unwrap_or_default())
+    })
+}
+
+/// 获取快进/快退步长设置
+pub fn settings() -> SeekStepSettings {
+    settings_lock().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// 保存快进/快退步长设置
+pub fn set_settings(mut settings: SeekStepSettings) -> anyhow::Result<()> {
+    settings.forward_secs = settings.forward_secs.clamp(1, MAX_STEP_SECS);
+    settings.backward_secs = settings.backward_secs.clamp(1, MAX_STEP_SECS);
+    storage::save_json(&settings_path(), &settings)?;
+    *settings_lock().write().map_err(|_| anyhow::anyhow!("无法锁定快进快退步长设置"))? = settings;
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use crate::player_fixed::{PlayerState, SongInfo};
+use crate::player_safe::SafePlayerManager;
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// 会话变化后等待这么久再写盘，切歌时接连发生的列表、状态、音量变化合并为一次写入
+const SAVE_DELAY: Duration = Duration::from_secs(2);
+
+/// 播放中位置变化超过这个秒数才重新写盘，异常退出后最多从这么早的位置恢复
+const POSITION_SAVE_INTERVAL_SECS: u64 = 15;
+
+/// 上次退出时的播放会话，下次启动时恢复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastSession {
+    pub songs: Vec<SongInfo>,
+    #[serde(rename = "currentIndex", default)]
+    pub current_index: Option<usize>,
+    #[serde(default)]
+    pub position: u64, // 单位：秒
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+    #[serde(default)]
+    pub muted: bool,
+    #[serde(rename = "wasPlaying", default)]
+    pub was_playing: bool,
+}
+
+/// 命名会话：播放队列及播放位置的快照，与播放列表分开保存
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamedSession {
+    pub name: String,
+    #[serde(rename = "savedAt", default)]
+    pub saved_at: u64, // Unix秒
+    pub songs: Vec<SongInfo>,
+    #[serde(rename = "currentIndex", default)]
+    pub current_index: Option<usize>,
+    #[serde(default)]
+    pub position: u64, // 单位：秒
+}
+
+/// 命名会话摘要
+#[derive(Debug, Clone, Serialize)]
+pub struct NamedSessionSummary {
+    pub name: String,
+    #[serde(rename = "savedAt")]
+    pub saved_at: u64,
+    #[serde(rename = "songCount")]
+    pub song_count: usize,
+    #[serde(rename = "currentTitle")]
+    pub current_title: Option<String>,
+    pub position: u64,
+}
+
+/// 启动行为设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartupOptions {
+    #[serde(rename = "autoResume", default)]
+    pub auto_resume: bool, // 上次退出时正在播放则启动后自动继续播放
+    #[serde(rename = "volumeRampSecs", default = "default_ramp_secs")]
+    pub volume_ramp_secs: f32, // 自动继续播放时音量从0渐升到原音量的时长，0为不渐升
+}
+
+impl Default for StartupOptions {
+    fn default() -> Self {
+        Self {
+            auto_resume: false,
+            volume_ramp_secs: default_ramp_secs(),
+        }
+    }
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+fn default_ramp_secs() -> f32 {
+    3.0
+}
+
+fn session_path() -> PathBuf {
+    storage::data_dir().join("session.json")
+}
+
+fn options_path() -> PathBuf {
+    storage::data_dir().join("startup.json")
+}
+
+fn sessions_dir() -> PathBuf {
+    storage::data_dir().join("sessions")
+}
+
+fn named_session_path(name: &str) -> PathBuf {
+    sessions_dir().join(format!("{}.json", storage::sanitize_file_name(name)))
+}
+
+/// 内存中的当前会话，延迟写盘
+#[derive(Default)]
+struct Recorder {
+    session: Option<LastSession>,
+    player: Option<Arc<SafePlayerManager>>, // 会话有变化、写盘时要从播放器重新读取
+    saved_position: u64, // 上次写盘时的位置
+    dirty: bool,         // 有尚未写盘的变化
+    scheduled: bool,     // 已安排延迟写盘
+}
+
+fn recorder() -> &'static Mutex<Recorder> {
+    static RECORDER: OnceLock<Mutex<Recorder>> = OnceLock::new();
+    RECORDER.get_or_init(|| Mutex::new(Recorder::default()))
+}
+
+/// 播放列表、当前曲目、播放状态或音量变化后调用，SAVE_DELAY 后写盘
+/// 写盘时才从播放器的发布副本读取一次状态（不等待播放器线程），期间的多次变化只复制一次播放列表
+pub fn record(player: &Arc<SafePlayerManager>) {
+    let Ok(mut recorder) = recorder().lock() else { return };
+    recorder.player = Some(player.clone());
+    recorder.dirty = true;
+    schedule(&mut recorder);
+}
+
+/// 记录播放中的位置；与上次写盘的位置相差 POSITION_SAVE_INTERVAL_SECS 以上时安排写盘，其余留到退出时写出
+pub fn record_position(position: u64) {
+    let Ok(mut recorder) = recorder().lock() else { return };
+    let Some(session) = recorder.session.as_mut() else { return };
+    if session.position == position {
+        return;
+    }
+    session.position = position;
+    recorder.dirty = true;
+    if position.abs_diff(recorder.saved_position) >= POSITION_SAVE_INTERVAL_SECS {
+        schedule(&mut recorder);
+    }
+}
+
+fn schedule(recorder: &mut Recorder) {
+    if recorder.scheduled {
+        return;
+    }
+    recorder.scheduled = true;
+    tauri::async_runtime::spawn(async {
+        tokio::time::sleep(SAVE_DELAY).await;
+        flush();
+    });
+}
+
+/// 写出尚未保存的会话（延迟到期和退出时调用）
+pub fn flush() {
+    let player = recorder().lock().ok().and_then(|mut recorder| recorder.player.take());
+    if let Some(player) = player {
+        // 不含封面，恢复后按需重新读取
+        let snapshot = player.get_state_nonblocking();
+        let session = LastSession {
+            songs: snapshot.playlist.into_iter().map(SongInfo::without_cover).collect(),
+            current_index: snapshot.current_index,
+            position: snapshot.position,
+            volume: snapshot.volume,
+            muted: snapshot.muted,
+            was_playing: snapshot.state == PlayerState::Playing,
+        };
+        if let Ok(mut recorder) = recorder().lock() {
+            recorder.session = Some(session);
+        }
+    }
+    let session = {
+        let Ok(mut recorder) = recorder().lock() else { return };
+        recorder.scheduled = false;
+        if !recorder.dirty {
+            return;
+        }
+        recorder.dirty = false;
+        let Some(session) = recorder.session.clone() else { return };
+        recorder.saved_position = session.position;
+        session
+    };
+    if let Err(e) = storage::save_json(&session_path(), &session) {
+        eprintln!("保存播放会话失败: {}", e);
+    }
+}
+
+/// 读取上次的会话
+pub fn load_last() -> anyhow::Result<Option<LastSession>> {
+    storage::load_json(&session_path())
+}
+
+/// 读取启动设置
+pub fn load_options() -> StartupOptions {
+    storage::load_json(&options_path())
+        .unwrap_or_else(|e| {
+            eprintln!("读取启动设置失败: {}", e);
+            None
+        })
+        .unwrap_or_default()
+}
+
+/// 保存启动设置（音量渐入时长限制在 0-10 秒）
+pub fn save_options(options: &StartupOptions) -> anyhow::Result<()> {
+    let options = StartupOptions {
+        volume_ramp_secs: options.volume_ramp_secs.clamp(0.0, 10.0),
+        ..options.clone()
+    };
+    storage::save_json(&options_path(), &options)
+}
+
+/// 保存命名会话（同名覆盖）
+pub fn save_named(session: &NamedSession) -> anyhow::Result<()> {
+    if storage::sanitize_file_name(&session.name).is_empty() {
+        return Err(anyhow::anyhow!("会话名称不能为空"));
+    }
+    storage::save_json(&named_session_path(&session.name), session)
+}
+
+/// 读取命名会话
+pub fn load_named(name: &str) -> anyhow::Result<NamedSession> {
+    storage::load_json(&named_session_path(name))?.ok_or_else(|| anyhow::anyhow!("会话不存在: {}", name))
+}
+
+/// 删除命名会话
+pub fn delete_named(name: &str) -> anyhow::Result<()> {
+    let path = named_session_path(name);
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// 列出所有命名会话，最近保存的在前
+pub fn list_named() -> anyhow::Result<Vec<NamedSessionSummary>> {
+    let dir = sessions_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut summaries = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        match storage::load_json::<NamedSession>(&path) {
+            Ok(Some(session)) => summaries.push(NamedSessionSummary {
+                current_title: session
+                    .current_index
+                    .and_then(|idx| session.songs.get(idx))
+                    .and_then(|song| song.title.clone()),
+                name: session.name,
+                saved_at: session.saved_at,
+                song_count: session.songs.len(),
+                position: session.position,
+            }),
+            Ok(None) => {}
+            Err(e) => eprintln!("读取会话失败 {}: {}", path.display(), e),
+        }
+    }
+    summaries.sort_by(|a, b| b.saved_at.cmp(&a.saved_at));
+    Ok(summaries)
+}
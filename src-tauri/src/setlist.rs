@@ -0,0 +1,208 @@
+use crate::player_fixed::{PlayerEvent, PlayerState, SongInfo};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+/// 曲目列表中的一条播放记录
+#[derive(Debug, Clone, Serialize)]
+pub struct SetlistEntry {
+    pub path: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    #[serde(rename = "startedAt")]
+    pub started_at: u64, // Unix秒
+    #[serde(rename = "endedAt")]
+    pub ended_at: Option<u64>, // 正在播放时为空
+}
+
+impl SetlistEntry {
+    fn display_name(&self) -> String {
+        let title = self.title.clone().unwrap_or_else(|| {
+            Path::new(&self.path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+        match &self.artist {
+            Some(artist) if !artist.is_empty() => format!("{} - {}", artist, title),
+            _ => title,
+        }
+    }
+}
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SetlistFormat {
+    Txt,
+    Csv,
+    Cue,
+}
+
+#[derive(Default)]
+struct Setlist {
+    entries: Vec<SetlistEntry>,
+    pending: Option<SongInfo>, // 已切换但尚未开始播放的歌曲
+    open: bool,                // 最后一条记录是否仍在播放
+}
+
+impl Setlist {
+    fn close(&mut self, now: u64) {
+        if self.open {
+            if let Some(entry) = self.entries.last_mut() {
+                entry.ended_at = Some(now);
+            }
+            self.open = false;
+        }
+    }
+}
+
+fn setlist() -> &'static Mutex<Setlist> {
+    static SETLIST: OnceLock<Mutex<Setlist>> = OnceLock::new();
+    SETLIST.get_or_init(|| Mutex::new(Setlist::default()))
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 根据播放器事件记录本次运行期间播放过的曲目
+/// 切歌时结束上一条记录，播放进度开始走动时才开始新记录，只切换不播放的歌曲不会计入
+pub fn on_player_event(event: &PlayerEvent) {
+    let mut setlist = match setlist().lock() {
+        Ok(setlist) => setlist,
+        Err(_) => return,
+    };
+    let now = now_secs();
+    match event {
+        PlayerEvent::SongChanged(_, song) => {
+            setlist.close(now);
+            setlist.pending = Some(song.clone());
+        }
+        PlayerEvent::ProgressUpdate { position, .. } if *position > 0 && !setlist.open => {
+            if let Some(song) = setlist.pending.clone() {
+                setlist.entries.push(SetlistEntry {
+                    path: song.path,
+                    title: song.title,
+                    artist: song.artist,
+                    album: song.album,
+                    started_at: now.saturating_sub(*position),
+                    ended_at: None,
+                });
+                setlist.open = true;
+            }
+        }
+        PlayerEvent::StateChanged(PlayerState::Stopped) => setlist.close(now),
+        _ => {}
+    }
+}
+
+/// 获取本次运行的曲目列表
+pub fn entries() -> Vec<SetlistEntry> {
+    setlist().lock().map(|s| s.entries.clone()).unwrap_or_default()
+}
+
+/// 清空曲目列表，正在播放的歌曲在下次进度更新时重新计入
+pub fn clear() {
+    if let Ok(mut setlist) = setlist().lock() {
+        setlist.entries.clear();
+        setlist.open = false;
+    }
+}
+
+/// 导出曲目列表，返回导出的条目数
+pub fn export(path: &Path, format: SetlistFormat) -> anyhow::Result<usize> {
+    let entries = entries();
+    if entries.is_empty() {
+        return Err(anyhow::anyhow!("曲目列表为空"));
+    }
+    let content = match format {
+        SetlistFormat::Txt => to_txt(&entries),
+        SetlistFormat::Csv => to_csv(&entries),
+        SetlistFormat::Cue => to_cue(&entries, path),
+    };
+    std::fs::write(path, content)?;
+    Ok(entries.len())
+}
+
+/// 相对第一首开始时间的偏移，格式 HH:MM:SS
+fn format_offset(secs: u64) -> String {
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+fn to_txt(entries: &[SetlistEntry]) -> String {
+    let base = entries[0].started_at;
+    entries
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| {
+            format!(
+                "{:>3}. [{}] {}\n",
+                idx + 1,
+                format_offset(entry.started_at.saturating_sub(base)),
+                entry.display_name()
+            )
+        })
+        .collect()
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_csv(entries: &[SetlistEntry]) -> String {
+    let base = entries[0].started_at;
+    let mut out = String::from("index,offset,started_at,ended_at,duration,artist,title,album,path\n");
+    for (idx, entry) in entries.iter().enumerate() {
+        let duration = entry.ended_at.map(|end| end.saturating_sub(entry.started_at));
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{}\n",
+            idx + 1,
+            format_offset(entry.started_at.saturating_sub(base)),
+            entry.started_at,
+            entry.ended_at.map(|t| t.to_string()).unwrap_or_default(),
+            duration.map(|d| d.to_string()).unwrap_or_default(),
+            csv_field(entry.artist.as_deref().unwrap_or("")),
+            csv_field(entry.title.as_deref().unwrap_or("")),
+            csv_field(entry.album.as_deref().unwrap_or("")),
+            csv_field(&entry.path),
+        ));
+    }
+    out
+}
+
+fn cue_text(value: &str) -> String {
+    value.replace('"', "'").replace('\n', " ")
+}
+
+/// CUE 中的曲目位置对应同名的整场录音文件（与导出文件同名的 .wav）
+fn to_cue(entries: &[SetlistEntry], path: &Path) -> String {
+    let recording = path
+        .with_extension("wav")
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "setlist.wav".to_string());
+    let base = entries[0].started_at;
+    let mut out = String::from("TITLE \"Setlist\"\n");
+    out.push_str(&format!("FILE \"{}\" WAVE\n", cue_text(&recording)));
+    for (idx, entry) in entries.iter().enumerate() {
+        let offset = entry.started_at.saturating_sub(base);
+        let title = entry.title.clone().unwrap_or_else(|| entry.display_name());
+        out.push_str(&format!("  TRACK {:02} AUDIO\n", idx + 1));
+        out.push_str(&format!("    TITLE \"{}\"\n", cue_text(&title)));
+        if let Some(artist) = entry.artist.as_deref().filter(|a| !a.is_empty()) {
+            out.push_str(&format!("    PERFORMER \"{}\"\n", cue_text(artist)));
+        }
+        // CUE 时间格式为 分:秒:帧（每秒75帧），分钟可以超过60
+        out.push_str(&format!("    INDEX 01 {:02}:{:02}:00\n", offset / 60, offset % 60));
+    }
+    out
+}
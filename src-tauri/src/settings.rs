@@ -0,0 +1,377 @@
+use crate::hotkeys::{self, HotkeyAction};
+use crate::output_device::OutputDeviceSelection;
+use crate::player_fixed::{MediaType, PlayMode, PlayerCommand, PlayerEvent};
+use crate::{
+    cache, clipboard_watch, collation, cover_fetch, library, library_scan, library_watch, low_memory, lyrics_provider,
+    maintenance, metadata_priority, midi, net, party, playback_monitor, progress, radio_host, remote_api, replaygain,
+    seek_step, session, skip_filter, storage, tag_write, webhooks,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// 保存在其他模块各自文件中的设置项，键名与 get_setting/set_setting 使用的相同
+/// 按设备区分的设置（DSP 配置、设备延迟）不在其中，仍由各自的命令读写
+const MODULE_KEYS: &[&str] = &[
+    "libraryFolders",
+    "hotkeys",
+    "startup",
+    "replayGain",
+    "seekStep",
+    "progress",
+    "skipFilter",
+    "radioHost",
+    "lowMemory",
+    "collation",
+    "metadataPriority",
+    "coverFetch",
+    "lyricsFetch",
+    "tagWrite",
+    "midi",
+    "clipboardWatch",
+    "watchdog",
+    "maintenance",
+    "cache",
+    "proxy",
+    "webhooks",
+    "remoteApi",
+    "party",
+];
+
+/// 音量变化后延迟写盘的时间，拖动音量条时不必每一步都写文件
+const VOLUME_SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// 有尚未写盘的音量变化
+static SAVE_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// 播放偏好，启动时应用到播放器；对应的设置命令调用后和音量变化时自动保存
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preferences {
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+    #[serde(default)]
+    pub muted: bool,
+    #[serde(rename = "playMode", default = "default_play_mode")]
+    pub play_mode: PlayMode,
+    #[serde(rename = "playbackMode", default = "default_playback_mode")]
+    pub playback_mode: MediaType, // 有MV的歌曲默认播放音频还是视频
+    #[serde(rename = "silenceGap", default)]
+    pub silence_gap: f32, // 自动切歌时的曲间静音（秒），播放器没有交叉淡入淡出，切歌过渡只有这一项
+    #[serde(rename = "outputDevice", default)]
+    pub output_device: OutputDeviceSelection,
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+fn default_play_mode() -> PlayMode {
+    PlayMode::RepeatAll
+}
+
+fn default_playback_mode() -> MediaType {
+    MediaType::Audio
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            volume: default_volume(),
+            muted: false,
+            play_mode: default_play_mode(),
+            playback_mode: default_playback_mode(),
+            silence_gap: 0.0,
+            output_device: OutputDeviceSelection::default(),
+        }
+    }
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("settings.json")
+}
+
+fn settings_lock() -> &'static RwLock<Preferences> {
+    static SETTINGS: OnceLock<RwLock<Preferences>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let loaded = storage::load_json(&settings_path()).unwrap_or_else(|e| {
+            eprintln!("读取设置失败: {}", e);
+            None
+        });
+        // 旧版本只在会话中记录了音量
+        let preferences = loaded.unwrap_or_else(|| match session::load_last() {
+            Ok(Some(last)) => Preferences {
+                volume: last.volume,
+                muted: last.muted,
+                ..Preferences::default()
+            },
+            _ => Preferences::default(),
+        });
+        RwLock::new(preferences)
+    })
+}
+
+/// 获取播放偏好
+pub fn preferences() -> Preferences {
+    settings_lock().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// 启动时把保存的偏好应用到播放器的命令
+pub fn startup_commands() -> Vec<PlayerCommand> {
+    let preferences = preferences();
+    let mut commands = vec![
+        PlayerCommand::SetVolume(preferences.volume),
+        PlayerCommand::SetPlayMode(preferences.play_mode),
+    ];
+    if preferences.muted {
+        commands.push(PlayerCommand::SetMuted(true));
+    }
+    if preferences.playback_mode != MediaType::Audio {
+        commands.push(PlayerCommand::SetPlaybackMode {
+            mode: preferences.playback_mode,
+            position: None,
+        });
+    }
+    if preferences.silence_gap > 0.0 {
+        commands.push(PlayerCommand::SetSilenceGap(preferences.silence_gap));
+    }
+    // 默认设备不必重新打开输出
+    if preferences.output_device != OutputDeviceSelection::default() {
+        commands.push(PlayerCommand::SetOutputDevice(preferences.output_device));
+    }
+    commands
+}
+
+/// 读取保存在其他模块中的一项设置，不是这类设置项时返回 None
+fn module_setting(key: &str) -> Option<Value> {
+    let value = match key {
+        "libraryFolders" => serde_json::to_value(library_scan::load_folders().folders),
+        "hotkeys" => serde_json::to_value(hotkeys::settings().bindings),
+        "startup" => serde_json::to_value(session::load_options()),
+        "replayGain" => serde_json::to_value(replaygain::settings()),
+        "seekStep" => serde_json::to_value(seek_step::settings()),
+        "progress" => serde_json::to_value(progress::settings()),
+        "skipFilter" => serde_json::to_value(skip_filter::settings()),
+        "radioHost" => serde_json::to_value(radio_host::settings()),
+        "lowMemory" => serde_json::to_value(low_memory::settings()),
+        "collation" => serde_json::to_value(collation::settings()),
+        "metadataPriority" => serde_json::to_value(metadata_priority::get()),
+        "coverFetch" => serde_json::to_value(cover_fetch::settings()),
+        "lyricsFetch" => serde_json::to_value(lyrics_provider::settings()),
+        "tagWrite" => serde_json::to_value(tag_write::settings()),
+        "midi" => serde_json::to_value(midi::load_options()),
+        "clipboardWatch" => serde_json::to_value(clipboard_watch::ClipboardWatchOptions {
+            enabled: clipboard_watch::is_enabled(),
+        }),
+        "watchdog" => serde_json::to_value(playback_monitor::watchdog_settings()),
+        "maintenance" => serde_json::to_value(maintenance::load_settings()),
+        "cache" => serde_json::to_value(cache::load_settings()),
+        "proxy" => serde_json::to_value(net::proxy_settings()),
+        "webhooks" => serde_json::to_value(webhooks::list()),
+        "remoteApi" => serde_json::to_value(remote_api::settings()),
+        "party" => serde_json::to_value(party::settings()),
+        _ => return None,
+    };
+    Some(value.unwrap_or(Value::Null))
+}
+
+fn parse<T: DeserializeOwned>(key: &str, value: Value) -> anyhow::Result<T> {
+    serde_json::from_value(value).map_err(|e| anyhow::anyhow!("无效的 {}: {}", key, e))
+}
+
+/// 修改保存在其他模块中的一项设置，与对应的单独设置命令效果相同
+/// 会阻塞（重建排序键、淘汰缓存、启动远程控制接口），需在阻塞线程中调用
+fn set_module_setting<R: Runtime>(app: &AppHandle<R>, key: &str, value: Value) -> anyhow::Result<Vec<PlayerCommand>> {
+    let mut commands = Vec::new();
+    match key {
+        "libraryFolders" => {
+            let mut saved = library_scan::load_folders();
+            saved.folders = parse(key, value)?;
+            library_scan::save_folders(&saved)?;
+            library_watch::refresh();
+        }
+        "hotkeys" => {
+            let bindings: BTreeMap<HotkeyAction, Option<String>> = parse(key, value)?;
+            hotkeys::set_bindings(app, bindings)?;
+        }
+        "startup" => session::save_options(&parse(key, value)?)?,
+        "replayGain" => replaygain::set_settings(parse(key, value)?)?,
+        "seekStep" => seek_step::set_settings(parse(key, value)?)?,
+        "progress" => progress::set_settings(parse(key, value)?)?,
+        "skipFilter" => skip_filter::set_settings(parse(key, value)?)?,
+        "radioHost" => radio_host::set_settings(parse(key, value)?)?,
+        "lowMemory" => {
+            let settings: low_memory::LowMemorySettings = parse(key, value)?;
+            if settings.enabled {
+                commands.push(PlayerCommand::CompactPlaylist);
+            }
+            low_memory::set_settings(settings)?;
+        }
+        "collation" => {
+            collation::set_settings(parse(key, value)?)?;
+            library::with_library(|lib| lib.rebuild_sort_keys()).map_err(anyhow::Error::msg)?;
+        }
+        "metadataPriority" => metadata_priority::set(parse(key, value)?)?,
+        "coverFetch" => cover_fetch::set_settings(parse(key, value)?)?,
+        "lyricsFetch" => lyrics_provider::set_settings(parse(key, value)?)?,
+        "tagWrite" => tag_write::set_settings(parse(key, value)?)?,
+        "midi" => midi::save_options(&parse(key, value)?)?,
+        "clipboardWatch" => {
+            let options: clipboard_watch::ClipboardWatchOptions = parse(key, value)?;
+            clipboard_watch::set_enabled(options.enabled)?;
+        }
+        "watchdog" => playback_monitor::set_watchdog_settings(parse(key, value)?)?,
+        "maintenance" => maintenance::save_settings(&parse(key, value)?)?,
+        "cache" => cache::save_settings(&parse(key, value)?)?,
+        "proxy" => net::set_proxy_settings(parse(key, value)?)?,
+        "webhooks" => webhooks::set(parse(key, value)?)?,
+        "remoteApi" => {
+            // 令牌只能通过重置命令更换，这里只应用开关和端口
+            let settings: remote_api::RemoteApiSettings = parse(key, value)?;
+            tauri::async_runtime::block_on(remote_api::set_enabled(settings.enabled, Some(settings.port)))?;
+        }
+        "party" => party::set_settings(parse(key, value)?)?,
+        _ => return Err(anyhow::anyhow!("未知的设置项: {}", key)),
+    }
+    notify(app, key);
+    Ok(commands)
+}
+
+/// 全部设置：播放偏好和保存在其他模块中的各项设置（音乐库文件夹、快捷键、ReplayGain、代理等），键名与 get_setting/set_setting 使用的相同
+pub fn all() -> serde_json::Map<String, Value> {
+    let mut all = match serde_json::to_value(preferences()) {
+        Ok(Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    for key in MODULE_KEYS {
+        if let Some(value) = module_setting(key) {
+            all.insert(key.to_string(), value);
+        }
+    }
+    all
+}
+
+/// 读取一项设置
+pub fn get(key: &str) -> anyhow::Result<Value> {
+    if let Some(value) = module_setting(key) {
+        return Ok(value);
+    }
+    match serde_json::to_value(preferences())? {
+        Value::Object(mut map) => map.remove(key),
+        _ => None,
+    }
+    .ok_or_else(|| anyhow::anyhow!("未知的设置项: {}", key))
+}
+
+/// 修改一项设置并保存，发送 SettingsChanged；返回需要发给播放器的命令
+/// 会阻塞，需在阻塞线程中调用
+pub fn set<R: Runtime>(app: &AppHandle<R>, key: &str, value: Value) -> anyhow::Result<Vec<PlayerCommand>> {
+    if MODULE_KEYS.contains(&key) {
+        return set_module_setting(app, key, value);
+    }
+
+    // 在整份偏好上替换这一项再反序列化，顺便校验类型
+    let mut map = match serde_json::to_value(preferences())? {
+        Value::Object(map) => map,
+        _ => serde_json::Map::new(),
+    };
+    if !map.contains_key(key) {
+        return Err(anyhow::anyhow!("未知的设置项: {}", key));
+    }
+    map.insert(key.to_string(), value);
+    let mut updated: Preferences =
+        serde_json::from_value(Value::Object(map)).map_err(|e| anyhow::anyhow!("无效的 {}: {}", key, e))?;
+    updated.volume = updated.volume.clamp(0.0, 2.0);
+    updated.silence_gap = updated.silence_gap.clamp(0.0, 5.0);
+
+    let command = match key {
+        "volume" => PlayerCommand::SetVolume(updated.volume),
+        "muted" => PlayerCommand::SetMuted(updated.muted),
+        "playMode" => PlayerCommand::SetPlayMode(updated.play_mode),
+        "playbackMode" => PlayerCommand::SetPlaybackMode {
+            mode: updated.playback_mode,
+            position: None,
+        },
+        "silenceGap" => PlayerCommand::SetSilenceGap(updated.silence_gap),
+        _ => PlayerCommand::SetOutputDevice(updated.output_device.clone()),
+    };
+    remember(app, |preferences| *preferences = updated)?;
+    Ok(vec![command])
+}
+
+/// 更新播放偏好并保存，有变化的项发送 SettingsChanged
+pub fn remember<R: Runtime>(app: &AppHandle<R>, f: impl FnOnce(&mut Preferences)) -> anyhow::Result<()> {
+    update(app, f, true).map(|_| ())
+}
+
+/// 记录音量变化：立即发送 SettingsChanged，写盘延迟 VOLUME_SAVE_DELAY 合并为一次
+pub fn remember_volume<R: Runtime>(app: &AppHandle<R>, volume: f32, muted: bool) -> anyhow::Result<()> {
+    let changed = update(
+        app,
+        |preferences| {
+            preferences.volume = volume;
+            preferences.muted = muted;
+        },
+        false,
+    )?;
+    if changed && !SAVE_PENDING.swap(true, Ordering::SeqCst) {
+        tauri::async_runtime::spawn(async {
+            tokio::time::sleep(VOLUME_SAVE_DELAY).await;
+            flush();
+        });
+    }
+    Ok(())
+}
+
+/// 写出尚未保存的音量变化（退出时调用）
+pub fn flush() {
+    if !SAVE_PENDING.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    if let Err(e) = storage::save_json(&settings_path(), &preferences()) {
+        eprintln!("保存设置失败: {}", e);
+    }
+}
+
+/// 修改播放偏好，save 为 true 时立即写盘；有变化的项发送 SettingsChanged，返回是否有变化
+fn update<R: Runtime>(app: &AppHandle<R>, f: impl FnOnce(&mut Preferences), save: bool) -> anyhow::Result<bool> {
+    let (before, after) = {
+        let mut preferences = settings_lock().write().map_err(|_| anyhow::anyhow!("无法锁定设置"))?;
+        let before = preferences.clone();
+        f(&mut preferences);
+        if *preferences == before {
+            return Ok(false);
+        }
+        if save {
+            storage::save_json(&settings_path(), &*preferences)?;
+        }
+        (serde_json::to_value(before)?, serde_json::to_value(preferences.clone())?)
+    };
+    if let (Value::Object(before), Value::Object(after)) = (before, after) {
+        for (key, value) in after {
+            if before.get(&key) != Some(&value) {
+                emit(app, key, value);
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// 其他模块中的设置项（如快捷键）修改后通知前端
+pub fn notify<R: Runtime>(app: &AppHandle<R>, key: &str) {
+    match get(key) {
+        Ok(value) => emit(app, key.to_string(), value),
+        Err(e) => eprintln!("读取设置失败: {}", e),
+    }
+}
+
+fn emit<R: Runtime>(app: &AppHandle<R>, key: String, value: Value) {
+    if let Err(e) = app.emit("player-event", PlayerEvent::SettingsChanged { key, value }) {
+        eprintln!("发送设置变化事件失败: {:?}", e);
+    }
+}
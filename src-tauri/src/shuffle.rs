@@ -0,0 +1,117 @@
+use crate::player_fixed::SongInfo;
+use crate::skip_filter;
+use rand::seq::SliceRandom;
+use std::sync::Arc;
+
+/// 随机播放顺序：开启随机播放时打乱整个播放列表，按这个顺序切歌，全部播过一遍后重新打乱，
+/// 保证每首歌在一轮中只出现一次；上一首沿着顺序往回走
+/// 顺序用 Arc 共享，发布状态副本时不复制
+#[derive(Debug, Clone, Default)]
+pub struct ShuffleQueue {
+    order: Arc<Vec<usize>>, // 播放列表索引的一个排列
+    pos: Option<usize>,     // 当前曲目在 order 中的位置，None 为这一轮还没有开始
+    generation: u64,        // 打乱或换算时的播放列表版本
+}
+
+impl ShuffleQueue {
+    /// 重新打乱，当前曲目排在最前面并视为已播放
+    pub fn reset(&mut self, len: usize, generation: u64, current: Option<usize>) {
+        self.generation = generation;
+        let mut order: Vec<usize> = (0..len).collect();
+        order.shuffle(&mut rand::thread_rng());
+        self.pos = None;
+        if let Some(at) = current.and_then(|current| order.iter().position(|&i| i == current)) {
+            order.swap(0, at);
+            self.pos = Some(0);
+        }
+        self.order = Arc::new(order);
+    }
+
+    /// 播放列表版本变化（增删、替换了条目）后重新打乱；用户直接选了还没播到的曲目时把它移到当前位置之后，其余顺序不变
+    pub fn sync(&mut self, len: usize, generation: u64, current: Option<usize>) {
+        if self.generation != generation || self.order.len() != len {
+            self.reset(len, generation, current);
+            return;
+        }
+        let Some(current) = current else { return };
+        if self.pos.map(|pos| self.order[pos]) == Some(current) {
+            return;
+        }
+        let start = self.pos.map_or(0, |pos| pos + 1);
+        match self.order.iter().position(|&i| i == current) {
+            Some(at) if at >= start => {
+                Arc::make_mut(&mut self.order).swap(start, at);
+                self.pos = Some(start);
+            }
+            // 这一轮已经播过的曲目：从原来的位置继续往后走
+            _ => {}
+        }
+    }
+
+    /// 播放列表排序或移动条目后把顺序换算到新的索引，order[新索引] 为原索引；generation 为重排后的播放列表版本
+    pub fn remap(&mut self, order: &[usize], generation: u64) {
+        if order.len() != self.order.len() {
+            return;
+        }
+        self.generation = generation;
+        let mut new_index = vec![0; order.len()];
+        for (new, &old) in order.iter().enumerate() {
+            new_index[old] = new;
+        }
+        self.order = Arc::new(self.order.iter().map(|&old| new_index[old]).collect());
+    }
+
+    /// 切到下一首：跳过被时长过滤的曲目，这一轮播完后重新打乱（新一轮的第一首不是刚播完的曲目）
+    pub fn next(&mut self, playlist: &[SongInfo], generation: u64, current: Option<usize>) -> Option<usize> {
+        self.sync(playlist.len(), generation, current);
+        if playlist.is_empty() {
+            return None;
+        }
+        let filter = skip_filter::settings();
+        let start = self.pos.map_or(0, |pos| pos + 1);
+        let found = (start..self.order.len()).find(|&at| filter.allows_song(&playlist[self.order[at]]));
+        let at = match found {
+            Some(at) => at,
+            None => {
+                self.reset(playlist.len(), generation, None);
+                if playlist.len() > 1 && Some(self.order[0]) == current {
+                    let last = self.order.len() - 1;
+                    Arc::make_mut(&mut self.order).swap(0, last);
+                }
+                // 全部被过滤时退回到不过滤
+                (0..self.order.len()).find(|&at| filter.allows_song(&playlist[self.order[at]])).unwrap_or(0)
+            }
+        };
+        self.pos = Some(at);
+        Some(self.order[at])
+    }
+
+    /// 切到上一首：沿顺序往回走，已经在这一轮第一首时重新播放当前曲目
+    pub fn previous(&mut self, playlist: &[SongInfo], generation: u64, current: Option<usize>) -> Option<usize> {
+        self.sync(playlist.len(), generation, current);
+        let filter = skip_filter::settings();
+        let Some(pos) = self.pos else { return current.or_else(|| self.order.first().copied()) };
+        match (0..pos).rev().find(|&at| filter.allows_song(&playlist[self.order[at]])) {
+            Some(at) => {
+                self.pos = Some(at);
+                Some(self.order[at])
+            }
+            None => Some(self.order[pos]),
+        }
+    }
+
+    /// 这一轮接下来要播放的曲目（最多 limit 首），不包括被时长过滤的曲目
+    pub fn upcoming(&self, playlist: &[SongInfo], generation: u64, limit: usize) -> Vec<usize> {
+        if self.generation != generation || self.order.len() != playlist.len() {
+            return Vec::new();
+        }
+        let filter = skip_filter::settings();
+        let start = self.pos.map_or(0, |pos| pos + 1);
+        self.order[start..]
+            .iter()
+            .copied()
+            .filter(|&i| filter.allows_song(&playlist[i]))
+            .take(limit)
+            .collect()
+    }
+}
@@ -0,0 +1,77 @@
+use crate::player_fixed::SongInfo;
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+/// 按时长自动跳过曲目的设置：过短的（铃声片段）或过长的（整张专辑的单文件）
+/// 时长未知的曲目不受影响
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SkipFilterSettings {
+    /// 短于该秒数的曲目被跳过，0 表示不限制
+    #[serde(rename = "minSecs", default)]
+    pub min_secs: u64,
+    /// 长于该分钟数的曲目被跳过，0 表示不限制
+    #[serde(rename = "maxMinutes", default)]
+    pub max_minutes: u64,
+    /// 顺序播放时也跳过；关闭时只作用于随机播放和随机挑选曲目
+    #[serde(rename = "inSequential", default)]
+    pub in_sequential: bool,
+}
+
+impl SkipFilterSettings {
+    /// 该时长的曲目是否可以被自动选中
+    pub fn allows(&self, duration: Option<u64>) -> bool {
+        let duration = match duration {
+            Some(d) => d,
+            None => return true,
+        };
+        (self.min_secs == 0 || duration >= self.min_secs)
+            && (self.max_minutes == 0 || duration <= self.max_minutes * 60)
+    }
+
+    /// 曲目是否可以被自动选中
+    pub fn allows_song(&self, song: &SongInfo) -> bool {
+        self.allows(song.duration)
+    }
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("skip_filter.json")
+}
+
+fn settings_lock() -> &'static RwLock<SkipFilterSettings> {
+    static SETTINGS: OnceLock<RwLock<SkipFilterSettings>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let loaded = storage::load_json(&settings_path()).unwrap_or_else(|e| {
+            eprintln!("读取时长过滤设置失败: {}", e);
+            None
+        });
+        RwLock::new(loaded.unwrap_or_default())
+    })
+}
+
+/// 获取时长过滤设置
+pub fn settings() -> SkipFilterSettings {
+    settings_lock().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// 保存时长过滤设置
+pub fn set_settings(settings: SkipFilterSettings) -> anyhow::Result<()> {
+    storage::save_json(&settings_path(), &settings)?;
+    *settings_lock().write().map_err(|_| anyhow::anyhow!("无法锁定时长过滤设置"))? = settings;
+    Ok(())
+}
+
+/// 顺序播放时从 start 开始按方向跳过被过滤的曲目（未开启 inSequential 或全部被过滤时返回 start）
+pub fn sequential_pick(playlist: &[SongInfo], start: usize, forward: bool) -> usize {
+    let filter = settings();
+    let len = playlist.len();
+    if !filter.in_sequential || len == 0 {
+        return start;
+    }
+    (0..len)
+        .map(|step| if forward { (start + step) % len } else { (start + len - step) % len })
+        .find(|&i| filter.allows_song(&playlist[i]))
+        .unwrap_or(start)
+}
@@ -0,0 +1,238 @@
+use crate::player_fixed::SongInfo;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// 支持的外挂字幕格式
+const SUBTITLE_EXTENSIONS: [&str; 4] = ["srt", "vtt", "ass", "ssa"];
+
+/// 一条字幕
+#[derive(Debug, Clone, Serialize)]
+pub struct SubtitleCue {
+    #[serde(rename = "startMs")]
+    pub start_ms: u64,
+    #[serde(rename = "endMs")]
+    pub end_ms: u64,
+    pub text: String, // 已去掉样式标记，多行用 \n 分隔
+}
+
+/// 视频旁的一个字幕文件
+#[derive(Debug, Clone, Serialize)]
+pub struct SubtitleTrack {
+    pub id: usize,
+    pub path: String,
+    pub label: String,  // 文件名中视频名之后的部分（如 zh、en.forced），没有时为格式名
+    pub format: String, // srt / vtt / ass / ssa
+}
+
+/// 视频的字幕轨和当前选中字幕的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct Subtitles {
+    pub tracks: Vec<SubtitleTrack>,
+    pub selected: Option<usize>,
+    pub cues: Vec<SubtitleCue>,
+}
+
+/// 用户为各视频选择的字幕文件（None 为关闭字幕），未选择时使用第一个
+fn selections() -> &'static Mutex<HashMap<String, Option<String>>> {
+    static SELECTIONS: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+    SELECTIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 查找与视频同名的字幕文件：movie.srt、movie.zh.srt、movie.en.forced.ass 等
+pub fn find_tracks(video_path: &Path) -> Vec<SubtitleTrack> {
+    let (Some(dir), Some(stem)) = (video_path.parent(), video_path.file_stem().and_then(|s| s.to_str())) else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+    let stem_lower = stem.to_lowercase();
+    let mut found: Vec<(PathBuf, String, String)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter_map(|path| {
+            let format = path.extension()?.to_str()?.to_lowercase();
+            if !SUBTITLE_EXTENSIONS.contains(&format.as_str()) {
+                return None;
+            }
+            let file_stem = path.file_stem()?.to_str()?.to_lowercase();
+            let label = if file_stem == stem_lower {
+                format.to_uppercase()
+            } else {
+                file_stem.strip_prefix(&stem_lower)?.strip_prefix('.')?.to_string()
+            };
+            Some((path, label, format))
+        })
+        .collect();
+    found.sort_by(|a, b| a.0.cmp(&b.0));
+    found
+        .into_iter()
+        .enumerate()
+        .map(|(id, (path, label, format))| SubtitleTrack {
+            id,
+            path: path.to_string_lossy().into_owned(),
+            label,
+            format,
+        })
+        .collect()
+}
+
+/// 读取并解析字幕文件，编码识别与歌词文件相同
+pub fn load(path: &Path) -> anyhow::Result<Vec<SubtitleCue>> {
+    let content = SongInfo::read_file_with_encoding(path).ok_or_else(|| anyhow::anyhow!("无法读取字幕文件: {}", path.display()))?;
+    let content = content.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let format = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    let mut cues = match format.as_str() {
+        "ass" | "ssa" => parse_ass(&content),
+        _ => parse_srt(&content),
+    };
+    cues.retain(|cue| !cue.text.is_empty() && cue.end_ms > cue.start_ms);
+    cues.sort_by_key(|cue| cue.start_ms);
+    Ok(cues)
+}
+
+/// 视频的字幕轨和选中的字幕（同名文件的第一个，或用户用 select 选择的）
+pub fn subtitles(video_path: &Path) -> Subtitles {
+    let tracks = find_tracks(video_path);
+    let key = video_path.to_string_lossy().into_owned();
+    let selected = match selections().lock().ok().and_then(|s| s.get(&key).cloned()) {
+        Some(Some(path)) => tracks.iter().find(|t| t.path == path).or(tracks.first()).map(|t| t.id),
+        Some(None) => None,
+        None => tracks.first().map(|t| t.id),
+    };
+    let cues = selected
+        .and_then(|id| tracks.get(id))
+        .map(|track| {
+            load(Path::new(&track.path)).unwrap_or_else(|e| {
+                eprintln!("读取字幕失败 {}: {}", track.path, e);
+                Vec::new()
+            })
+        })
+        .unwrap_or_default();
+    Subtitles { tracks, selected, cues }
+}
+
+/// 选择视频的字幕轨，None 为关闭字幕
+pub fn select(video_path: &Path, track_id: Option<usize>) -> anyhow::Result<Subtitles> {
+    let path = match track_id {
+        Some(id) => Some(
+            find_tracks(video_path)
+                .into_iter()
+                .find(|t| t.id == id)
+                .ok_or_else(|| anyhow::anyhow!("无效的字幕轨: {}", id))?
+                .path,
+        ),
+        None => None,
+    };
+    selections()
+        .lock()
+        .map_err(|_| anyhow::anyhow!("无法锁定字幕选择"))?
+        .insert(video_path.to_string_lossy().into_owned(), path);
+    Ok(subtitles(video_path))
+}
+
+/// 解析 SRT/WebVTT 时间：[hh:]mm:ss[,.]mmm
+fn parse_timestamp(text: &str) -> Option<u64> {
+    let (clock, millis) = text.trim().split_once([',', '.'])?;
+    let parts: Vec<u64> = clock.split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    let seconds = match parts.as_slice() {
+        [h, m, s] => h * 3600 + m * 60 + s,
+        [m, s] => m * 60 + s,
+        _ => return None,
+    };
+    let millis: u64 = format!("{:0<3}", millis.get(..3).unwrap_or(millis)).parse().ok()?;
+    Some(seconds * 1000 + millis)
+}
+
+/// 去掉 <i>、<font ...> 等 HTML 样式标记
+fn strip_tags(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => result.push(c),
+            _ => {}
+        }
+    }
+    result
+}
+
+/// SRT 与 WebVTT：空行分隔的块，时间行为 "开始 --> 结束"（WebVTT 之后可能有位置设置）
+fn parse_srt(content: &str) -> Vec<SubtitleCue> {
+    let mut cues = Vec::new();
+    for block in content.split("\n\n") {
+        let mut lines = block.lines().skip_while(|l| !l.contains("-->"));
+        let Some(timing) = lines.next() else { continue };
+        let Some((start, rest)) = timing.split_once("-->") else { continue };
+        let end = rest.split_whitespace().next().unwrap_or("");
+        let (Some(start_ms), Some(end_ms)) = (parse_timestamp(start), parse_timestamp(end)) else { continue };
+        let text: Vec<String> = lines.map(|l| strip_tags(l).trim().to_string()).filter(|l| !l.is_empty()).collect();
+        cues.push(SubtitleCue {
+            start_ms,
+            end_ms,
+            text: text.join("\n"),
+        });
+    }
+    cues
+}
+
+/// 解析 ASS 时间：h:mm:ss.cc
+fn parse_ass_time(text: &str) -> Option<u64> {
+    let (clock, centis) = text.trim().split_once('.')?;
+    let parts: Vec<u64> = clock.split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    let [h, m, s] = parts.as_slice() else { return None };
+    let centis: u64 = centis.parse().ok()?;
+    Some((h * 3600 + m * 60 + s) * 1000 + centis * 10)
+}
+
+/// 去掉 ASS 的 {\...} 覆盖标记，\N 换行，\h 为空格
+fn clean_ass_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut in_override = false;
+    for c in text.chars() {
+        match c {
+            '{' => in_override = true,
+            '}' if in_override => in_override = false,
+            c if !in_override => result.push(c),
+            _ => {}
+        }
+    }
+    result.replace("\\N", "\n").replace("\\n", "\n").replace("\\h", " ").trim().to_string()
+}
+
+/// ASS/SSA：[Events] 段中按 Format 行的字段顺序解析 Dialogue 行，Text 是最后一个字段（可能含逗号）
+fn parse_ass(content: &str) -> Vec<SubtitleCue> {
+    let mut cues = Vec::new();
+    let mut in_events = false;
+    let mut fields: Vec<String> = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_events = line.eq_ignore_ascii_case("[events]");
+            continue;
+        }
+        if !in_events {
+            continue;
+        }
+        if let Some(format) = line.strip_prefix("Format:") {
+            fields = format.split(',').map(|f| f.trim().to_lowercase()).collect();
+            continue;
+        }
+        let Some(dialogue) = line.strip_prefix("Dialogue:") else { continue };
+        if fields.is_empty() {
+            continue;
+        }
+        let values: Vec<&str> = dialogue.splitn(fields.len(), ',').collect();
+        let field = |name: &str| fields.iter().position(|f| f == name).and_then(|i| values.get(i).copied());
+        let (Some(start_ms), Some(end_ms)) = (field("start").and_then(parse_ass_time), field("end").and_then(parse_ass_time)) else {
+            continue;
+        };
+        cues.push(SubtitleCue {
+            start_ms,
+            end_ms,
+            text: clean_ass_text(field("text").unwrap_or("")),
+        });
+    }
+    cues
+}
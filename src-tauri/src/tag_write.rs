@@ -0,0 +1,316 @@
+use crate::library::{self, MetadataOverride};
+use crate::storage;
+use lofty::{Accessor, ItemKey, MimeType, Picture, PictureType, Probe, Tag, TagExt, TaggedFileExt};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+/// 嵌入封面图片的最大大小
+const MAX_COVER_SIZE: usize = 16 * 1024 * 1024;
+
+/// 写入标签的保护设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TagWriteSettings {
+    /// 只读音乐库：开启后所有写入文件标签的操作都会被拒绝（试运行不受影响）
+    #[serde(rename = "readOnly", default)]
+    pub read_only: bool,
+}
+
+/// 要写入的标签，未填写的字段保持不变，文本字段填空字符串表示删除该字段
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TagEdit {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub artist: Option<String>,
+    #[serde(default)]
+    pub album: Option<String>,
+    #[serde(rename = "trackNumber", default)]
+    pub track_number: Option<u32>,
+    #[serde(default)]
+    pub year: Option<u32>,
+    #[serde(default)]
+    pub genre: Option<String>,
+}
+
+impl TagEdit {
+    /// 写入后用于刷新播放列表条目的元数据（删除的字段不覆盖）
+    pub fn as_metadata(&self) -> MetadataOverride {
+        let text = |value: &Option<String>| value.clone().filter(|v| !v.is_empty());
+        MetadataOverride {
+            title: text(&self.title),
+            artist: text(&self.artist),
+            album: text(&self.album),
+            track_number: self.track_number,
+            disc_number: None,
+        }
+    }
+}
+
+/// 要嵌入的封面：图片文件路径或图片数据
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum CoverImage {
+    Path(String),
+    Bytes(Vec<u8>),
+}
+
+impl CoverImage {
+    /// 读取图片数据
+    pub fn load(&self) -> anyhow::Result<Vec<u8>> {
+        let data = match self {
+            CoverImage::Path(path) => {
+                let size = std::fs::metadata(path)?.len();
+                if size > MAX_COVER_SIZE as u64 {
+                    return Err(anyhow::anyhow!("封面图片过大: {} 字节", size));
+                }
+                std::fs::read(path)?
+            }
+            CoverImage::Bytes(data) => data.clone(),
+        };
+        if data.len() > MAX_COVER_SIZE {
+            return Err(anyhow::anyhow!("封面图片过大: {} 字节", data.len()));
+        }
+        Ok(data)
+    }
+}
+
+/// 批量操作中的一个文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathTagEdit {
+    pub path: String,
+    #[serde(flatten)]
+    pub edit: TagEdit,
+}
+
+/// 单个字段的变化
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// 单个文件的变化预览或写入结果
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChanges {
+    pub path: String,
+    pub changes: Vec<FieldChange>,
+    pub written: bool,
+    pub error: Option<String>,
+}
+
+/// 批量写入标签的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchResult {
+    #[serde(rename = "dryRun")]
+    pub dry_run: bool,
+    pub files: Vec<FileChanges>,
+}
+
+fn settings_path() -> PathBuf {
+    storage::data_dir().join("tag_write.json")
+}
+
+fn settings_lock() -> &'static RwLock<TagWriteSettings> {
+    static SETTINGS: OnceLock<RwLock<TagWriteSettings>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let loaded = storage::load_json(&settings_path()).unwrap_or_else(|e| {
+            eprintln!("读取标签写入设置失败: {}", e);
+            None
+        });
+        RwLock::new(loaded.unwrap_or_default())
+    })
+}
+
+/// 获取标签写入保护设置
+pub fn settings() -> TagWriteSettings {
+    settings_lock().read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// 保存标签写入保护设置
+pub fn set_settings(settings: TagWriteSettings) -> anyhow::Result<()> {
+    storage::save_json(&settings_path(), &settings)?;
+    *settings_lock().write().map_err(|_| anyhow::anyhow!("无法锁定标签写入设置"))? = settings;
+    Ok(())
+}
+
+/// 所有修改文件标签的功能在写入前都应调用，只读音乐库时返回错误
+pub fn ensure_writable() -> anyhow::Result<()> {
+    if settings().read_only {
+        return Err(anyhow::anyhow!("音乐库已设为只读，不允许修改文件标签"));
+    }
+    Ok(())
+}
+
+/// 比较当前标签和要写入的值，列出会变化的字段
+fn diff(tag: Option<&Tag>, edit: &TagEdit) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    let mut text = |field: &'static str, old: Option<String>, new: &Option<String>| {
+        if let Some(new) = new {
+            let new = Some(new.clone()).filter(|v| !v.is_empty());
+            if new != old {
+                changes.push(FieldChange { field, old, new });
+            }
+        }
+    };
+    text("title", tag.and_then(|t| t.title()).map(|v| v.into_owned()), &edit.title);
+    text("artist", tag.and_then(|t| t.artist()).map(|v| v.into_owned()), &edit.artist);
+    text("album", tag.and_then(|t| t.album()).map(|v| v.into_owned()), &edit.album);
+    text("genre", tag.and_then(|t| t.genre()).map(|v| v.into_owned()), &edit.genre);
+
+    let mut number = |field: &'static str, old: Option<u32>, new: Option<u32>| {
+        if let Some(new) = new {
+            if Some(new) != old {
+                changes.push(FieldChange {
+                    field,
+                    old: old.map(|v| v.to_string()),
+                    new: Some(new.to_string()),
+                });
+            }
+        }
+    };
+    number("trackNumber", tag.and_then(|t| t.track()), edit.track_number);
+    number("year", tag.and_then(|t| t.year()), edit.year);
+    changes
+}
+
+/// 预览写入标签会产生的变化，不修改文件
+pub fn preview(path: &Path, edit: &TagEdit) -> anyhow::Result<Vec<FieldChange>> {
+    let tagged_file = Probe::open(path)?.read()?;
+    Ok(diff(tagged_file.primary_tag(), edit))
+}
+
+/// 把标签写回文件（没有标签时按文件格式新建），返回实际变化的字段
+pub fn write(path: &Path, edit: &TagEdit) -> anyhow::Result<Vec<FieldChange>> {
+    ensure_writable()?;
+    let mut tagged_file = Probe::open(path)?.read()?;
+    let changes = diff(tagged_file.primary_tag(), edit);
+    if changes.is_empty() {
+        return Ok(changes);
+    }
+
+    if tagged_file.primary_tag().is_none() {
+        let tag_type = tagged_file.primary_tag_type();
+        tagged_file.insert_tag(Tag::new(tag_type));
+    }
+    let tag = tagged_file
+        .primary_tag_mut()
+        .ok_or_else(|| anyhow::anyhow!("该格式不支持写入标签"))?;
+    set_text(tag, &edit.title, Tag::set_title, Tag::remove_title);
+    set_text(tag, &edit.artist, Tag::set_artist, Tag::remove_artist);
+    set_text(tag, &edit.album, Tag::set_album, Tag::remove_album);
+    set_text(tag, &edit.genre, Tag::set_genre, Tag::remove_genre);
+    if let Some(track) = edit.track_number {
+        tag.set_track(track);
+    }
+    if let Some(year) = edit.year {
+        tag.set_year(year);
+    }
+    tag.save_to_path(path)?;
+    println!("✏️ 已写入标签: {} ({}项)", path.display(), changes.len());
+    // 标签已改写，之前在冲突值中选定的元数据不再适用
+    if let Err(e) = library::with_library(|lib| lib.clear_metadata_override(&path.to_string_lossy())) {
+        eprintln!("清除选定的元数据失败: {}", e);
+    }
+    Ok(changes)
+}
+
+/// 写入曲目 ReplayGain 增益（dB）和峰值标签（没有标签时按文件格式新建）
+pub fn write_replaygain(path: &Path, gain_db: f64, peak: f32) -> anyhow::Result<()> {
+    ensure_writable()?;
+    let mut tagged_file = Probe::open(path)?.read()?;
+    if tagged_file.primary_tag().is_none() {
+        let tag_type = tagged_file.primary_tag_type();
+        tagged_file.insert_tag(Tag::new(tag_type));
+    }
+    let tag = tagged_file
+        .primary_tag_mut()
+        .ok_or_else(|| anyhow::anyhow!("该格式不支持写入标签"))?;
+    tag.insert_text(ItemKey::ReplayGainTrackGain, format!("{:.2} dB", gain_db));
+    tag.insert_text(ItemKey::ReplayGainTrackPeak, format!("{:.6}", peak));
+    tag.save_to_path(path)?;
+    Ok(())
+}
+
+/// 把歌词（LRC 或纯文本）写入文件的歌词标签（没有标签时按文件格式新建）
+pub fn write_lyrics(path: &Path, lyrics: &str) -> anyhow::Result<()> {
+    ensure_writable()?;
+    let mut tagged_file = Probe::open(path)?.read()?;
+    if tagged_file.primary_tag().is_none() {
+        let tag_type = tagged_file.primary_tag_type();
+        tagged_file.insert_tag(Tag::new(tag_type));
+    }
+    let tag = tagged_file
+        .primary_tag_mut()
+        .ok_or_else(|| anyhow::anyhow!("该格式不支持写入标签"))?;
+    tag.insert_text(ItemKey::Lyrics, lyrics.to_string());
+    tag.save_to_path(path)?;
+    println!("📝 已写入歌词标签: {}", path.display());
+    Ok(())
+}
+
+/// 把图片作为封面（正面）嵌入文件标签，替换原有的封面；MP3 写入 ID3 APIC，FLAC 写入 PICTURE 块
+pub fn write_cover(path: &Path, image: &[u8]) -> anyhow::Result<()> {
+    ensure_writable()?;
+    let mime_type = match image::guess_format(image)? {
+        image::ImageFormat::Jpeg => MimeType::Jpeg,
+        image::ImageFormat::Png => MimeType::Png,
+        image::ImageFormat::Gif => MimeType::Gif,
+        image::ImageFormat::Bmp => MimeType::Bmp,
+        image::ImageFormat::Tiff => MimeType::Tiff,
+        format => return Err(anyhow::anyhow!("不支持的封面图片格式: {:?}", format)),
+    };
+    let mut tagged_file = Probe::open(path)?.read()?;
+    if tagged_file.primary_tag().is_none() {
+        let tag_type = tagged_file.primary_tag_type();
+        tagged_file.insert_tag(Tag::new(tag_type));
+    }
+    let tag = tagged_file
+        .primary_tag_mut()
+        .ok_or_else(|| anyhow::anyhow!("该格式不支持写入标签"))?;
+    tag.remove_picture_type(PictureType::CoverFront);
+    tag.push_picture(Picture::new_unchecked(PictureType::CoverFront, Some(mime_type), None, image.to_vec()));
+    tag.save_to_path(path)?;
+    println!("🖼️ 已嵌入封面: {} ({} 字节)", path.display(), image.len());
+    Ok(())
+}
+
+/// 写入文本字段：空字符串删除，None 保持不变
+fn set_text(tag: &mut Tag, value: &Option<String>, set: fn(&mut Tag, String), remove: fn(&mut Tag)) {
+    match value.as_deref() {
+        Some("") => remove(tag),
+        Some(value) => set(tag, value.to_string()),
+        None => {}
+    }
+}
+
+/// 批量写入标签；dry_run 为 true 时只返回变化预览，不修改任何文件
+/// 只读音乐库时非试运行的批量操作整体被拒绝
+pub fn batch(edits: &[PathTagEdit], dry_run: bool) -> anyhow::Result<BatchResult> {
+    if !dry_run {
+        ensure_writable()?;
+    }
+    let files = edits
+        .iter()
+        .map(|item| {
+            let path = Path::new(&item.path);
+            let result = if dry_run { preview(path, &item.edit) } else { write(path, &item.edit) };
+            match result {
+                Ok(changes) => FileChanges {
+                    path: item.path.clone(),
+                    written: !dry_run && !changes.is_empty(),
+                    changes,
+                    error: None,
+                },
+                Err(e) => FileChanges {
+                    path: item.path.clone(),
+                    changes: Vec::new(),
+                    written: false,
+                    error: Some(e.to_string()),
+                },
+            }
+        })
+        .collect();
+    Ok(BatchResult { dry_run, files })
+}
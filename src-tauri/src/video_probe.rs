@@ -0,0 +1,209 @@
+use crate::matroska;
+use crate::mp4;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::Path;
+use std::process::Command;
+
+/// 视频文件的时长、分辨率、编码和码率
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VideoInfo {
+    #[serde(rename = "durationMs", default)]
+    pub duration_ms: Option<u64>,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    #[serde(rename = "videoCodec", default)]
+    pub video_codec: Option<String>,
+    #[serde(rename = "audioCodec", default)]
+    pub audio_codec: Option<String>,
+    #[serde(default)]
+    pub bitrate: Option<u32>, // 总码率（kbps）
+}
+
+impl VideoInfo {
+    fn is_complete(&self) -> bool {
+        self.duration_ms.is_some() && self.width.is_some() && self.video_codec.is_some()
+    }
+}
+
+/// 读取视频文件信息：MP4/MOV 和 MKV/WebM 直接解析容器头部，其他格式或解析不全时调用 ffprobe（找不到时忽略）
+pub fn probe(path: &Path) -> Option<VideoInfo> {
+    let ext = path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).unwrap_or_default();
+    let parsed = match ext.as_str() {
+        "mp4" | "m4v" | "mov" => probe_mp4(path),
+        "mkv" | "webm" => probe_matroska(path),
+        _ => Err(anyhow::anyhow!("不支持直接解析的格式")),
+    };
+    let mut info = match parsed {
+        Ok(info) if info.is_complete() => info,
+        parsed => {
+            let fallback = ffprobe(path);
+            match (parsed, fallback) {
+                // 两边都有结果时用 ffprobe 补全缺少的字段
+                (Ok(info), Ok(probed)) => VideoInfo {
+                    duration_ms: info.duration_ms.or(probed.duration_ms),
+                    width: info.width.or(probed.width),
+                    height: info.height.or(probed.height),
+                    video_codec: info.video_codec.or(probed.video_codec),
+                    audio_codec: info.audio_codec.or(probed.audio_codec),
+                    bitrate: info.bitrate.or(probed.bitrate),
+                },
+                (Ok(info), Err(_)) => info,
+                (Err(_), Ok(probed)) => probed,
+                (Err(e), Err(fallback)) => {
+                    eprintln!("读取视频信息失败 {}: {}; {}", path.display(), e, fallback);
+                    return None;
+                }
+            }
+        }
+    };
+    // 容器中没有码率时按文件大小和时长估算
+    if info.bitrate.is_none() {
+        if let (Some(duration_ms), Ok(metadata)) = (info.duration_ms.filter(|d| *d > 0), std::fs::metadata(path)) {
+            info.bitrate = Some((metadata.len() * 8 / duration_ms) as u32);
+        }
+    }
+    Some(info)
+}
+
+/// 把 MP4 样本描述的 FourCC 换成常见的编码名
+fn mp4_codec_name(fourcc: &[u8; 4]) -> String {
+    match fourcc {
+        b"avc1" | b"avc3" => "H.264".to_string(),
+        b"hvc1" | b"hev1" => "HEVC".to_string(),
+        b"av01" => "AV1".to_string(),
+        b"vp08" => "VP8".to_string(),
+        b"vp09" => "VP9".to_string(),
+        b"mp4v" => "MPEG-4".to_string(),
+        b"mp4a" => "AAC".to_string(),
+        b"ac-3" => "AC-3".to_string(),
+        b"ec-3" => "E-AC-3".to_string(),
+        b"alac" => "ALAC".to_string(),
+        b"Opus" => "Opus".to_string(),
+        b"fLaC" => "FLAC".to_string(),
+        b".mp3" => "MP3".to_string(),
+        other => String::from_utf8_lossy(other).trim().to_string(),
+    }
+}
+
+/// 把 Matroska 的 CodecID（V_MPEG4/ISO/AVC 等）换成常见的编码名
+fn matroska_codec_name(codec_id: &str) -> String {
+    let name = match codec_id {
+        "V_MPEG4/ISO/AVC" => "H.264",
+        "V_MPEGH/ISO/HEVC" => "HEVC",
+        "V_AV1" => "AV1",
+        "V_VP8" => "VP8",
+        "V_VP9" => "VP9",
+        "A_OPUS" => "Opus",
+        "A_VORBIS" => "Vorbis",
+        "A_FLAC" => "FLAC",
+        "A_AC3" => "AC-3",
+        "A_EAC3" => "E-AC-3",
+        "A_DTS" => "DTS",
+        "A_MPEG/L3" => "MP3",
+        id if id.starts_with("A_AAC") => "AAC",
+        id => id.trim_start_matches("V_").trim_start_matches("A_"),
+    };
+    name.to_string()
+}
+
+/// MP4/MOV：时长取自 mvhd，编码和分辨率取自各轨的样本描述（stsd）
+fn probe_mp4(path: &Path) -> anyhow::Result<VideoInfo> {
+    let moov = mp4::read_moov(&mut File::open(path)?)?;
+    let mut info = VideoInfo::default();
+
+    if let Some(mvhd) = mp4::child(&moov, b"mvhd") {
+        let (timescale, duration) = if mvhd.first() == Some(&1) {
+            (mp4::be_u32(mvhd, 20), mp4::be_u64(mvhd, 24))
+        } else {
+            (mp4::be_u32(mvhd, 12), mp4::be_u32(mvhd, 16).map(u64::from))
+        };
+        if let (Some(timescale), Some(duration)) = (timescale.filter(|t| *t > 0), duration) {
+            info.duration_ms = Some(duration * 1000 / timescale as u64);
+        }
+    }
+
+    for (_, trak) in mp4::boxes(&moov).into_iter().filter(|(k, _)| k == b"trak") {
+        let Some(mdia) = mp4::child(trak, b"mdia") else { continue };
+        let Some(handler) = mp4::child(mdia, b"hdlr").and_then(|h| h.get(8..12)) else { continue };
+        let Some(stsd) = mp4::child(mdia, b"minf")
+            .and_then(|minf| mp4::child(minf, b"stbl"))
+            .and_then(|stbl| mp4::child(stbl, b"stsd"))
+        else {
+            continue;
+        };
+        // stsd：版本/标志和条目数之后是样本描述条目
+        let Some((fourcc, entry)) = stsd.get(8..).and_then(|entries| mp4::boxes(entries).into_iter().next()) else {
+            continue;
+        };
+        match handler {
+            b"vide" if info.video_codec.is_none() => {
+                info.video_codec = Some(mp4_codec_name(&fourcc));
+                // 视频样本描述：保留字段和预定义字段之后是 16 位宽高
+                let dimension = |at: usize| entry.get(at..at + 2).map(|b| u16::from_be_bytes([b[0], b[1]]) as u32);
+                info.width = dimension(24).filter(|w| *w > 0);
+                info.height = dimension(26).filter(|h| *h > 0);
+            }
+            b"soun" if info.audio_codec.is_none() => info.audio_codec = Some(mp4_codec_name(&fourcc)),
+            _ => {}
+        }
+    }
+    Ok(info)
+}
+
+/// MKV/WebM：复用 Matroska 头部解析
+fn probe_matroska(path: &Path) -> anyhow::Result<VideoInfo> {
+    let probed = matroska::probe(path)?;
+    let video = probed.video.as_ref();
+    Ok(VideoInfo {
+        duration_ms: probed.duration_ms,
+        width: video.and_then(|v| v.width),
+        height: video.and_then(|v| v.height),
+        video_codec: video.map(|v| matroska_codec_name(&v.codec)).filter(|c| !c.is_empty()),
+        audio_codec: probed.tracks.first().map(|t| matroska_codec_name(&t.codec)).filter(|c| !c.is_empty()),
+        bitrate: None,
+    })
+}
+
+/// 调用系统中的 ffprobe 读取 AVI/WMV/FLV 等格式
+fn ffprobe(path: &Path) -> anyhow::Result<VideoInfo> {
+    let mut cmd = Command::new("ffprobe");
+    cmd.args([
+        "-v",
+        "error",
+        "-show_entries",
+        "format=duration,bit_rate:stream=codec_type,codec_name,width,height",
+        "-of",
+        "json",
+    ])
+    .arg(path);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = cmd.output().map_err(|e| anyhow::anyhow!("无法运行 ffprobe: {}", e))?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("ffprobe 失败: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+
+    // ffprobe 的数值字段是字符串
+    let number = |value: &serde_json::Value| -> Option<f64> {
+        value.as_str().and_then(|s| s.parse().ok()).or_else(|| value.as_f64())
+    };
+    let streams = json["streams"].as_array().cloned().unwrap_or_default();
+    let stream = |kind: &str| streams.iter().find(|s| s["codec_type"] == kind);
+    let video = stream("video");
+    Ok(VideoInfo {
+        duration_ms: number(&json["format"]["duration"]).map(|secs| (secs * 1000.0) as u64),
+        width: video.and_then(|v| v["width"].as_u64()).map(|w| w as u32),
+        height: video.and_then(|v| v["height"].as_u64()).map(|h| h as u32),
+        video_codec: video.and_then(|v| v["codec_name"].as_str()).map(str::to_string),
+        audio_codec: stream("audio").and_then(|a| a["codec_name"].as_str()).map(str::to_string),
+        bitrate: number(&json["format"]["bit_rate"]).map(|bps| (bps / 1000.0) as u32),
+    })
+}
@@ -0,0 +1,153 @@
+use crate::player_fixed::SongInfo;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tauri::http::{header, Request, Response, StatusCode};
+
+/// 自定义协议名，前端用 convertFileSrc(path, "stream") 得到地址
+pub const SCHEME: &str = "stream";
+
+/// 一次响应最多返回的字节数；<video> 会按需继续请求后面的范围
+const MAX_CHUNK: u64 = 4 * 1024 * 1024;
+
+/// 从请求地址中取出文件路径（stream://localhost/<编码后的路径>，Windows 上为 http://stream.localhost/...）
+fn request_path(request: &Request<Vec<u8>>) -> Option<PathBuf> {
+    let encoded = request.uri().path().trim_start_matches('/');
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Some(PathBuf::from(String::from_utf8(decoded).ok()?))
+}
+
+/// 文件的 stream:// 地址，与前端 convertFileSrc(path, "stream") 的结果相同
+pub fn url_for(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'!' | b'~' | b'*' | b'\'' | b'(' | b')' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://{}.localhost/{}", SCHEME, encoded)
+    } else {
+        format!("{}://localhost/{}", SCHEME, encoded)
+    }
+}
+
+/// 只提供音视频文件，避免网页通过协议读取任意文件
+fn content_type(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    let mime = match ext.as_str() {
+        "mp4" | "m4v" => "video/mp4",
+        "mkv" => "video/x-matroska",
+        "webm" => "video/webm",
+        "mov" => "video/quicktime",
+        "avi" => "video/x-msvideo",
+        "wmv" => "video/x-ms-wmv",
+        "flv" => "video/x-flv",
+        "mp3" => "audio/mpeg",
+        "flac" => "audio/flac",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "m4a" | "aac" => "audio/mp4",
+        ext if SongInfo::is_audio_format(ext) => "application/octet-stream",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// 解析 Range 头（只支持单个范围）：bytes=start-end、bytes=start-、bytes=-suffix，返回闭区间
+pub fn parse_range(value: &str, len: u64) -> Option<(u64, u64)> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') || len == 0 {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().ok()?;
+            (len.saturating_sub(suffix), len - 1)
+        }
+        (start, "") => (start.parse().ok()?, len - 1),
+        (start, end) => (start.parse().ok()?, end.parse::<u64>().ok()?.min(len - 1)),
+    };
+    (start <= end && start < len).then_some((start, end))
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(message.as_bytes().to_vec())
+        .unwrap_or_default()
+}
+
+/// 处理 stream:// 请求：按 Range 读取文件的一段，<video> 可以边下边播并直接跳转，不必把整个文件读进内存
+pub fn handle(request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let Some(path) = request_path(request) else {
+        return error_response(StatusCode::BAD_REQUEST, "无效的文件路径");
+    };
+    let Some(mime) = content_type(&path) else {
+        return error_response(StatusCode::FORBIDDEN, "只能读取音视频文件");
+    };
+    match read_range(&path, request.headers().get(header::RANGE).and_then(|v| v.to_str().ok())) {
+        Ok((status, data, content_range)) => {
+            let mut builder = Response::builder()
+                .status(status)
+                .header(header::CONTENT_TYPE, mime)
+                .header(header::ACCEPT_RANGES, "bytes")
+                .header(header::CONTENT_LENGTH, data.len())
+                .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*");
+            if let Some(content_range) = content_range {
+                builder = builder.header(header::CONTENT_RANGE, content_range);
+            }
+            builder.body(data).unwrap_or_default()
+        }
+        Err(e) => {
+            eprintln!("读取视频流失败 {}: {}", path.display(), e);
+            match e.downcast_ref::<std::io::Error>().map(|e| e.kind()) {
+                Some(std::io::ErrorKind::NotFound) => error_response(StatusCode::NOT_FOUND, "文件不存在"),
+                _ => error_response(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+            }
+        }
+    }
+}
+
+/// 读取请求的范围，返回 (状态码, 数据, Content-Range)
+fn read_range(path: &Path, range: Option<&str>) -> anyhow::Result<(StatusCode, Vec<u8>, Option<String>)> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let (start, end) = match range {
+        Some(range) => match parse_range(range, len) {
+            Some(range) => range,
+            None => {
+                return Ok((StatusCode::RANGE_NOT_SATISFIABLE, Vec::new(), Some(format!("bytes */{}", len))));
+            }
+        },
+        // 没有 Range 的小文件整个返回
+        None if len <= MAX_CHUNK => {
+            let mut data = Vec::with_capacity(len as usize);
+            file.read_to_end(&mut data)?;
+            return Ok((StatusCode::OK, data, None));
+        }
+        None => (0, len - 1),
+    };
+    let end = end.min(start + MAX_CHUNK - 1);
+    let mut data = vec![0u8; (end - start + 1) as usize];
+    file.seek(SeekFrom::Start(start))?;
+    file.read_exact(&mut data)?;
+    Ok((StatusCode::PARTIAL_CONTENT, data, Some(format!("bytes {}-{}/{}", start, end, len))))
+}
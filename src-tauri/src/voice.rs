@@ -0,0 +1,169 @@
+use crate::player_fixed::{PlayMode, SongInfo};
+use serde::Serialize;
+
+/// 语音指令调节音量的步长
+pub const VOLUME_STEP: f32 = 0.1;
+
+/// 按标题/艺术家/专辑查找曲目的条件，any 匹配任一字段；均为小写
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TrackQuery {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub any: Option<String>,
+}
+
+impl TrackQuery {
+    /// 曲目是否满足所有给出的条件（包含匹配，不区分大小写）
+    pub fn matches(&self, song: &SongInfo) -> bool {
+        let field = |value: &Option<String>| value.as_deref().unwrap_or("").to_lowercase();
+        let (title, artist, album) = (field(&song.title), field(&song.artist), field(&song.album));
+        let check = |wanted: &Option<String>, value: &str| wanted.as_deref().map(|w| value.contains(w)).unwrap_or(true);
+        check(&self.title, &title)
+            && check(&self.artist, &artist)
+            && check(&self.album, &album)
+            && self
+                .any
+                .as_deref()
+                .map(|w| title.contains(w) || artist.contains(w) || album.contains(w))
+                .unwrap_or(true)
+    }
+}
+
+/// 语音指令解析结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum VoiceIntent {
+    Play,
+    Pause,
+    Stop,
+    Next,
+    Previous,
+    VolumeUp,
+    VolumeDown,
+    Mute,
+    SetVolume { volume: f32 },
+    SetPlayMode { mode: PlayMode },
+    PlayQuery { query: TrackQuery, random: bool }, // random：从匹配的曲目中随机挑选（"放点XX的歌"）
+}
+
+/// 去掉客套前缀和句末标点
+fn normalize(text: &str) -> String {
+    let mut text = text
+        .trim()
+        .trim_end_matches(['.', '!', '?', '。', '！', '？', ',', '，'])
+        .trim()
+        .to_lowercase();
+    for prefix in ["hey ", "please ", "can you ", "could you ", "请", "帮我", "给我"] {
+        if let Some(rest) = text.strip_prefix(prefix) {
+            text = rest.trim().to_string();
+        }
+    }
+    text.trim_end_matches(" please").trim().to_string()
+}
+
+/// 从"音量50"、"volume to 30%"等说法中取出百分比（最高 200%，与播放器音量范围一致）
+fn parse_volume(text: &str) -> Option<f32> {
+    let digits: String = text
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    let percent: u32 = digits.parse().ok()?;
+    Some((percent.min(200) as f32) / 100.0)
+}
+
+/// 解析要播放的内容："something by queen"、"bohemian rhapsody by queen"、"the album a night at the opera"、
+/// "周杰伦的歌"、"专辑范特西"
+fn parse_query(what: &str) -> Option<VoiceIntent> {
+    let what = what.trim();
+    if what.is_empty() {
+        return None;
+    }
+    let some = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+
+    for prefix in ["the album ", "album ", "专辑"] {
+        if let Some(album) = what.strip_prefix(prefix) {
+            let query = TrackQuery { album: some(album), ..Default::default() };
+            return query.album.is_some().then_some(VoiceIntent::PlayQuery { query, random: false });
+        }
+    }
+
+    if let Some((title, artist)) = what.rsplit_once(" by ") {
+        let random = ["something", "anything", "some music", "some songs", "songs", "music", "a song"]
+            .contains(&title.trim());
+        let query = TrackQuery {
+            title: if random { None } else { some(title) },
+            artist: some(artist),
+            ..Default::default()
+        };
+        return query.artist.is_some().then_some(VoiceIntent::PlayQuery { query, random });
+    }
+
+    for suffix in ["的歌曲", "的歌", "的音乐"] {
+        if let Some(artist) = what.strip_suffix(suffix) {
+            let artist = artist.trim_start_matches(['点', '首', '一']);
+            let query = TrackQuery { artist: some(artist), ..Default::default() };
+            return query.artist.is_some().then_some(VoiceIntent::PlayQuery { query, random: true });
+        }
+    }
+
+    let query = TrackQuery { any: some(what), ..Default::default() };
+    Some(VoiceIntent::PlayQuery { query, random: false })
+}
+
+/// 把简单的自然语言指令（中英文）解析为播放器操作，无法识别时返回 None
+pub fn parse(text: &str) -> Option<VoiceIntent> {
+    let text = normalize(text);
+    let is = |phrases: &[&str]| phrases.contains(&text.as_str());
+
+    if is(&["pause", "pause music", "pause it", "暂停", "暂停播放"]) {
+        return Some(VoiceIntent::Pause);
+    }
+    if is(&["stop", "stop music", "stop playing", "停止", "停止播放", "别放了"]) {
+        return Some(VoiceIntent::Stop);
+    }
+    if is(&["play", "resume", "continue", "play music", "unpause", "播放", "继续", "继续播放"]) {
+        return Some(VoiceIntent::Play);
+    }
+    if is(&["next", "skip", "skip this", "skip it", "next song", "next track", "下一首", "切歌", "跳过", "换一首"]) {
+        return Some(VoiceIntent::Next);
+    }
+    if is(&["previous", "back", "go back", "previous song", "last song", "上一首"]) {
+        return Some(VoiceIntent::Previous);
+    }
+    if is(&["volume up", "louder", "turn it up", "turn up the volume", "大声点", "大声一点", "调大音量", "音量调高", "音量大一点"]) {
+        return Some(VoiceIntent::VolumeUp);
+    }
+    if is(&["volume down", "quieter", "softer", "turn it down", "turn down the volume", "小声点", "小声一点", "调小音量", "音量调低", "音量小一点"]) {
+        return Some(VoiceIntent::VolumeDown);
+    }
+    if is(&["mute", "silence", "静音"]) {
+        return Some(VoiceIntent::Mute);
+    }
+    if is(&["shuffle", "shuffle on", "shuffle music", "随机播放"]) {
+        return Some(VoiceIntent::SetPlayMode { mode: PlayMode::Shuffle });
+    }
+    if is(&["repeat", "repeat this", "repeat one", "repeat this song", "单曲循环"]) {
+        return Some(VoiceIntent::SetPlayMode { mode: PlayMode::RepeatOne });
+    }
+    if is(&["repeat all", "loop the playlist", "列表循环"]) {
+        return Some(VoiceIntent::SetPlayMode { mode: PlayMode::RepeatAll });
+    }
+    if is(&["repeat off", "no repeat", "stop repeating", "不循环"]) {
+        return Some(VoiceIntent::SetPlayMode { mode: PlayMode::NoRepeat });
+    }
+    if is(&["shuffle off", "play in order", "stop shuffling", "顺序播放"]) {
+        return Some(VoiceIntent::SetPlayMode { mode: PlayMode::RepeatAll });
+    }
+    if text.starts_with("volume") || text.starts_with("set volume") || text.starts_with("音量") {
+        return parse_volume(&text).map(|volume| VoiceIntent::SetVolume { volume });
+    }
+
+    for prefix in ["play ", "put on ", "listen to ", "播放", "来点", "来一首", "放一首", "放点", "我想听", "想听", "放"] {
+        if let Some(what) = text.strip_prefix(prefix) {
+            return parse_query(what);
+        }
+    }
+    None
+}
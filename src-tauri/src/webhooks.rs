@@ -0,0 +1,204 @@
+use crate::net;
+use crate::player_fixed::{PlayerEvent, PlayerState, SongInfo};
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// 单个webhook最多尝试次数
+const MAX_ATTEMPTS: u32 = 3;
+
+/// 单次请求超时时间
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 可触发webhook的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WebhookEvent {
+    SongChanged,
+    Play,
+    Pause,
+}
+
+impl WebhookEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            WebhookEvent::SongChanged => "song_changed",
+            WebhookEvent::Play => "play",
+            WebhookEvent::Pause => "pause",
+        }
+    }
+}
+
+/// webhook配置
+/// template 为空时发送默认JSON；否则替换其中的 {{event}} {{title}} {{artist}} {{album}}
+/// {{path}} {{duration}} {{timestamp}} 占位符（值已按JSON字符串转义）后原样发送
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub url: String,
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// webhook测试结果
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookTestResult {
+    pub status: Option<u16>,
+    pub attempts: u32,
+    pub error: Option<String>,
+}
+
+fn webhooks_path() -> PathBuf {
+    storage::data_dir().join("webhooks.json")
+}
+
+fn webhooks() -> &'static Mutex<Vec<Webhook>> {
+    static WEBHOOKS: OnceLock<Mutex<Vec<Webhook>>> = OnceLock::new();
+    WEBHOOKS.get_or_init(|| {
+        let loaded = storage::load_json(&webhooks_path()).unwrap_or_else(|e| {
+            eprintln!("读取webhook配置失败: {}", e);
+            None
+        });
+        Mutex::new(loaded.unwrap_or_default())
+    })
+}
+
+/// 最近一次 SongChanged 的歌曲，播放/暂停事件使用
+fn current_song() -> &'static Mutex<Option<SongInfo>> {
+    static CURRENT: OnceLock<Mutex<Option<SongInfo>>> = OnceLock::new();
+    CURRENT.get_or_init(|| Mutex::new(None))
+}
+
+/// 获取webhook列表
+pub fn list() -> Vec<Webhook> {
+    webhooks().lock().map(|w| w.clone()).unwrap_or_default()
+}
+
+/// 替换并保存webhook列表
+pub fn set(new_webhooks: Vec<Webhook>) -> anyhow::Result<()> {
+    for webhook in &new_webhooks {
+        if !webhook.url.starts_with("http://") && !webhook.url.starts_with("https://") {
+            return Err(anyhow::anyhow!("无效的webhook地址: {}", webhook.url));
+        }
+    }
+    storage::save_json(&webhooks_path(), &new_webhooks)?;
+    *webhooks().lock().map_err(|_| anyhow::anyhow!("无法锁定webhook配置"))? = new_webhooks;
+    Ok(())
+}
+
+/// 根据播放器事件触发webhook
+pub fn on_player_event(event: &PlayerEvent) {
+    let webhook_event = match event {
+        PlayerEvent::SongChanged(_, song) => {
+            if let Ok(mut current) = current_song().lock() {
+                *current = Some(song.clone());
+            }
+            WebhookEvent::SongChanged
+        }
+        PlayerEvent::StateChanged(PlayerState::Playing) => WebhookEvent::Play,
+        PlayerEvent::StateChanged(PlayerState::Paused) => WebhookEvent::Pause,
+        _ => return,
+    };
+
+    let targets: Vec<Webhook> = list()
+        .into_iter()
+        .filter(|w| w.enabled && w.events.contains(&webhook_event))
+        .collect();
+    if targets.is_empty() {
+        return;
+    }
+
+    let song = current_song().lock().ok().and_then(|s| s.clone());
+    for webhook in targets {
+        let body = render_payload(&webhook, webhook_event, song.as_ref());
+        tokio::spawn(async move {
+            let result = send_with_retry(&webhook.url, body).await;
+            if let Some(e) = result.error {
+                eprintln!("webhook发送失败 {} ({}次尝试): {}", webhook.url, result.attempts, e);
+            }
+        });
+    }
+}
+
+/// 用示例数据立即触发一次webhook，用于测试配置
+pub async fn test_fire(webhook: &Webhook) -> WebhookTestResult {
+    let song = current_song().lock().ok().and_then(|s| s.clone());
+    let body = render_payload(webhook, WebhookEvent::SongChanged, song.as_ref());
+    send_with_retry(&webhook.url, body).await
+}
+
+/// 转义为JSON字符串内容（不含两侧引号）
+fn json_escape(value: &str) -> String {
+    let quoted = serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string());
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// 生成请求体
+fn render_payload(webhook: &Webhook, event: WebhookEvent, song: Option<&SongInfo>) -> String {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let title = song.and_then(|s| s.title.clone()).unwrap_or_default();
+    let artist = song.and_then(|s| s.artist.clone()).unwrap_or_default();
+    let album = song.and_then(|s| s.album.clone()).unwrap_or_default();
+    let path = song.map(|s| s.path.clone()).unwrap_or_default();
+    let duration = song.and_then(|s| s.duration).unwrap_or(0);
+
+    match &webhook.template {
+        Some(template) if !template.trim().is_empty() => template
+            .replace("{{event}}", event.name())
+            .replace("{{title}}", &json_escape(&title))
+            .replace("{{artist}}", &json_escape(&artist))
+            .replace("{{album}}", &json_escape(&album))
+            .replace("{{path}}", &json_escape(&path))
+            .replace("{{duration}}", &duration.to_string())
+            .replace("{{timestamp}}", &timestamp.to_string()),
+        _ => serde_json::json!({
+            "event": event.name(),
+            "title": title,
+            "artist": artist,
+            "album": album,
+            "path": path,
+            "duration": duration,
+            "timestamp": timestamp,
+        })
+        .to_string(),
+    }
+}
+
+/// 发送POST请求，失败时按 1s、2s 指数退避重试
+async fn send_with_retry(url: &str, body: String) -> WebhookTestResult {
+    let policy = net::RetryPolicy {
+        max_attempts: MAX_ATTEMPTS,
+        ..Default::default()
+    };
+    let result = net::send(policy, |client| {
+        client
+            .post(url)
+            .timeout(REQUEST_TIMEOUT)
+            .header("Content-Type", "application/json")
+            .body(body.clone())
+    })
+    .await;
+    match result {
+        Ok((response, attempts)) => WebhookTestResult {
+            status: Some(response.status().as_u16()),
+            attempts,
+            error: None,
+        },
+        Err(e) => WebhookTestResult {
+            status: e.status,
+            attempts: e.attempts,
+            error: Some(e.message),
+        },
+    }
+}
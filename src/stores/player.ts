@@ -39,8 +39,9 @@ export enum PlayerState {
 }
 
 export enum PlayMode {
-  Sequential = 'Sequential',
-  Repeat = 'Repeat',
+  RepeatAll = 'RepeatAll', // 列表循环
+  RepeatOne = 'RepeatOne', // 单曲循环
+  NoRepeat = 'NoRepeat',   // 不循环，播完最后一首后停止
   Shuffle = 'Shuffle'
 }
 
@@ -49,7 +50,7 @@ export const usePlayerStore = defineStore('player', () => {
   const state = ref<PlayerState>(PlayerState.Stopped);
   const playlist = ref<SongInfo[]>([]);
   const currentIndex = ref<number | null>(null);
-  const playMode = ref<PlayMode>(PlayMode.Sequential);
+  const playMode = ref<PlayMode>(PlayMode.RepeatAll);
   const position = ref<number>(0);
   const duration = ref<number>(0);
   const currentPlaybackMode = ref<MediaType>(MediaType.Audio); // 当前播放模式
@@ -112,12 +113,31 @@ export const usePlayerStore = defineStore('player', () => {
     return (position.value / duration.value) * 100;
   });
   
+  // 当前歌曲的封面：播放列表条目不携带封面，取自 SongChanged 事件或按需通过 get_album_cover 获取
+  const currentCover = ref<string | null>(null);
+
   const currentSong = computed(() => {
     if (currentIndex.value !== null && playlist.value.length > 0) {
-      return playlist.value[currentIndex.value];
+      const song = playlist.value[currentIndex.value];
+      if (song && currentCover.value) {
+        return { ...song, albumCover: currentCover.value };
+      }
+      return song;
     }
     return null;
   });
+
+  // 获取当前歌曲的封面，返回前已切到其他歌曲时丢弃结果
+  const loadCurrentCover = async (index: number, path: string) => {
+    try {
+      const cover = await invoke('get_album_cover', { songId: index }) as string | null;
+      if (currentSong.value?.path === path) {
+        currentCover.value = cover;
+      }
+    } catch (error) {
+      console.error('获取封面失败:', error);
+    }
+  };
   
   // 增强：音视频互斥控制方法
   const activateVideoPlayer = () => {
@@ -481,9 +501,19 @@ export const usePlayerStore = defineStore('player', () => {
     isNewSong.value = true; // 新歌曲标记
   };
 
-  const updateCurrentSong = (index: number) => {
+  const updateCurrentSong = (index: number, song?: SongInfo) => {
     const oldIndex = currentIndex.value;
     currentIndex.value = index;
+
+    // SongChanged 事件中的当前歌曲带有封面，没有时再单独获取
+    if (song) {
+      currentCover.value = song.albumCover || song.videoThumbnail || null;
+      if (!currentCover.value) {
+        loadCurrentCover(index, song.path);
+      }
+    } else if (oldIndex !== index) {
+      currentCover.value = null;
+    }
     
     // 如果歌曲索引发生变化，重置进度条
     if (oldIndex !== index) {
@@ -496,6 +526,23 @@ export const usePlayerStore = defineStore('player', () => {
     // 清空现有播放列表并重新赋值以确保响应性
     playlist.value.splice(0, playlist.value.length, ...newPlaylist);
   };
+
+  // 单个条目的元数据更新（占位条目读取完成、标签/歌词/封面修改后重新读取），事件中的条目不带封面
+  const updateSong = (index: number, song: SongInfo) => {
+    if (index < 0 || index >= playlist.value.length) return;
+    // 视频时长由前端播放器上报，后端条目中可能没有
+    const videoDuration = videoDurations.value.get(song.path);
+    if (!song.duration && videoDuration) {
+      song = { ...song, duration: videoDuration };
+    }
+    playlist.value.splice(index, 1, song);
+    if (index === currentIndex.value) {
+      if (song.duration) {
+        duration.value = song.duration;
+      }
+      loadCurrentCover(index, song.path);
+    }
+  };
   
   const updateState = (newState: PlayerState) => {
     state.value = newState;
@@ -530,7 +577,8 @@ export const usePlayerStore = defineStore('player', () => {
   
   // 新增：切换播放模式的方法
   const togglePlaybackMode = async () => {
-    await invoke('toggle_playback_mode');
+    // 带上当前位置，从MV切回音频时后端从这里继续播放
+    await invoke('toggle_playback_mode', { position: Math.floor(position.value) });
     // 切换后更新本地状态
     const newMode = currentPlaybackMode.value === MediaType.Audio ? MediaType.Video : MediaType.Audio;
     currentPlaybackMode.value = newMode;
@@ -547,7 +595,7 @@ export const usePlayerStore = defineStore('player', () => {
     
     try {
       // 调用后端设置播放模式
-      await invoke('set_playback_mode', { mode });
+      await invoke('set_playback_mode', { mode, position: Math.floor(position.value) });
       
       // 立即更新本地状态
       currentPlaybackMode.value = mode;
@@ -640,7 +688,9 @@ export const usePlayerStore = defineStore('player', () => {
     seekTo,
     updateProgress,
     updatePlaylist,
+    updateSong,
     updateCurrentSong,
+    loadCurrentCover,
     updateState,
     updatePlayMode,
     setTransitioning, 